@@ -0,0 +1,46 @@
+//! Chart layout: the data range and screen position of every point, as a chart
+//! computes them each time it paints.
+//!
+//! ```sh
+//! cargo bench --bench charts
+//! ```
+
+use adabraka_ui::charts::{ChartArea, ChartPadding, DataPoint, DataRange};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use gpui::{point, px, size, Bounds};
+
+fn points(count: usize) -> Vec<DataPoint> {
+    (0..count)
+        .map(|ix| {
+            let x = ix as f64;
+            DataPoint::new(x, (x / 50.0).sin() * 100.0 + (x / 7.0).cos() * 10.0)
+        })
+        .collect()
+}
+
+fn layout(points: &[DataPoint]) -> f32 {
+    let area = ChartArea {
+        bounds: Bounds::new(point(px(0.0), px(0.0)), size(px(1200.0), px(400.0))),
+        range: DataRange::from_points(points).with_padding(0.05),
+        padding: ChartPadding::new(50.0, 20.0, 20.0, 40.0),
+    };
+    points
+        .iter()
+        .map(|point| f32::from(area.data_to_screen(point).y))
+        .sum()
+}
+
+fn chart_layout(c: &mut Criterion) {
+    let mut group = c.benchmark_group("charts/layout");
+    for count in [1_000, 100_000] {
+        let points = points(count);
+        group.throughput(Throughput::Elements(count as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(count), &points, |b, points| {
+            b.iter(|| layout(black_box(points)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, chart_layout);
+criterion_main!(benches);
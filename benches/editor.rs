@@ -0,0 +1,72 @@
+//! Editor hot paths on a 100k-line buffer: search and highlight span collection.
+//!
+//! ```sh
+//! cargo bench --bench editor --features editor-languages -- --save-baseline main
+//! # after a change:
+//! cargo bench --bench editor --features editor-languages -- --baseline main
+//! ```
+//!
+//! Keystroke latency and scroll paint cost need a window; the `bench` example measures
+//! those, and checks every path against its regression threshold.
+
+use adabraka_ui::components::editor::{bench, Language};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ropey::Rope;
+
+const LINES: usize = 100_000;
+
+/// Rust source of about `lines` lines, varied enough that searches don't hit every line.
+fn large_source(lines: usize) -> String {
+    let mut source = String::new();
+    for ix in 0..lines / 10 {
+        source.push_str(&format!(
+            "/// Doubles the value of item {ix}.\n\
+             pub fn value_{ix}(input: &[u32]) -> Option<u64> {{\n    \
+                 let total: u64 = input.iter().map(|&x| x as u64).sum();\n    \
+                 if total > {ix} {{\n        \
+                     return Some(total * 2);\n    \
+                 }}\n    \
+                 let label = \"item {ix}\";\n    \
+                 println!(\"{{}}: {{}}\", label, total);\n    \
+                 None\n\
+             }}\n"
+        ));
+    }
+    source
+}
+
+fn search(c: &mut Criterion) {
+    let rope = Rope::from_str(&large_source(LINES));
+    let mut group = c.benchmark_group("editor/search");
+    group.throughput(Throughput::Bytes(rope.len_bytes() as u64));
+    for query in ["total", "value_9999(", "no such text"] {
+        group.bench_with_input(BenchmarkId::from_parameter(query), query, |b, query| {
+            b.iter(|| bench::search(&rope, black_box(query)))
+        });
+    }
+    group.finish();
+}
+
+fn highlight(c: &mut Criterion) {
+    let rope = Rope::from_str(&large_source(LINES));
+    let Some((tree, query)) = bench::parse(Language::Rust, &rope) else {
+        eprintln!("skipping editor/highlight: build with --features editor-languages");
+        return;
+    };
+
+    let mut group = c.benchmark_group("editor/highlight");
+    // One 60-line viewport at the top of the file and one deep into it.
+    for start in [0, LINES / 2] {
+        group.bench_with_input(BenchmarkId::new("viewport", start), &start, |b, &start| {
+            b.iter(|| bench::highlight_spans(&rope, &tree, &query, start..start + 60))
+        });
+    }
+    group.sample_size(10);
+    group.bench_function("full_parse", |b| {
+        b.iter(|| bench::parse(Language::Rust, black_box(&rope)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, search, highlight);
+criterion_main!(benches);
@@ -0,0 +1,244 @@
+//! Performance harness for the editor and charts, with regression thresholds.
+//!
+//! Opens a window with a 100k-line Rust file in the editor, measures keystroke latency
+//! and scroll paint cost as the time from the input to the end of the frame it causes,
+//! then times search, highlight span collection and painting a 100k-point line chart.
+//! Each measurement's 95th percentile is checked against its threshold, and the
+//! process exits with status 1 if any is over.
+//!
+//! ```sh
+//! cargo run --release --example bench --features editor-languages
+//! ```
+//!
+//! The criterion benches in `benches/` cover the same non-window paths in more detail.
+
+use adabraka_ui::charts::{LineChart, LineChartPoint, LineChartSeries};
+use adabraka_ui::components::editor::{bench, Editor, EditorState, Language};
+use adabraka_ui::prelude::{install_theme, Theme};
+use futures::channel::oneshot;
+use gpui::{prelude::FluentBuilder as _, *};
+use ropey::Rope;
+use std::time::{Duration, Instant};
+
+const LINES: usize = 100_000;
+const CHART_POINTS: usize = 100_000;
+const SAMPLES: usize = 100;
+
+/// 95th-percentile budgets. A keystroke or scroll has to land within one 60Hz frame.
+const THRESHOLDS: &[(&str, Duration)] = &[
+    ("keystroke", Duration::from_millis(16)),
+    ("scroll", Duration::from_millis(16)),
+    ("search", Duration::from_millis(50)),
+    ("highlight", Duration::from_millis(2)),
+    ("chart_paint", Duration::from_millis(33)),
+];
+
+fn large_source(lines: usize) -> String {
+    let mut source = String::new();
+    for ix in 0..lines / 5 {
+        source.push_str(&format!(
+            "/// Item {ix}.\nfn value_{ix}(x: u64) -> u64 {{\n    let label = \"item {ix}\";\n    x * {ix} + label.len() as u64\n}}\n"
+        ));
+    }
+    source
+}
+
+struct BenchView {
+    editor: Entity<EditorState>,
+    chart: Option<LineChartSeries>,
+}
+
+impl Render for BenchView {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        div()
+            .size_full()
+            .flex()
+            .child(Editor::new(&self.editor).flex_1())
+            .when_some(self.chart.clone(), |this, series| {
+                this.child(div().w(px(500.0)).child(LineChart::single(series)))
+            })
+    }
+}
+
+struct Measurement {
+    name: &'static str,
+    samples: Vec<Duration>,
+}
+
+impl Measurement {
+    fn percentile(&self, fraction: f64) -> Duration {
+        let mut sorted = self.samples.clone();
+        sorted.sort();
+        let ix = ((sorted.len() - 1) as f64 * fraction).round() as usize;
+        sorted[ix]
+    }
+}
+
+/// Time from running `input` to the end of the next frame, `SAMPLES` times.
+async fn frame_latency(
+    window: AnyWindowHandle,
+    cx: &mut AsyncApp,
+    input: impl Fn(&mut Window, &mut App),
+) -> Vec<Duration> {
+    let mut samples = Vec::new();
+    for _ in 0..SAMPLES {
+        let (tx, rx) = oneshot::channel();
+        let start = Instant::now();
+        let updated = window.update(cx, |_, window, cx| {
+            input(window, cx);
+            window.refresh();
+            window.on_next_frame(move |_, _| {
+                let _ = tx.send(start.elapsed());
+            });
+        });
+        if updated.is_err() {
+            break;
+        }
+        if let Ok(elapsed) = rx.await {
+            samples.push(elapsed);
+        }
+    }
+    samples
+}
+
+fn timed(mut run: impl FnMut()) -> Vec<Duration> {
+    (0..SAMPLES)
+        .map(|_| {
+            let start = Instant::now();
+            run();
+            start.elapsed()
+        })
+        .collect()
+}
+
+async fn run_benches(view: WindowHandle<BenchView>, cx: &mut AsyncApp) -> Vec<Measurement> {
+    let window: AnyWindowHandle = view.into();
+    let mut results = Vec::new();
+
+    let typed = frame_latency(window, cx, |window, cx| {
+        window.dispatch_keystroke(Keystroke::parse("a").unwrap(), cx);
+    })
+    .await;
+    results.push(Measurement {
+        name: "keystroke",
+        samples: typed,
+    });
+
+    let scrolled = frame_latency(window, cx, |window, cx| {
+        window.dispatch_event(
+            PlatformInput::ScrollWheel(ScrollWheelEvent {
+                position: point(px(300.0), px(300.0)),
+                delta: ScrollDelta::Pixels(point(px(0.0), px(-120.0))),
+                ..Default::default()
+            }),
+            cx,
+        );
+    })
+    .await;
+    results.push(Measurement {
+        name: "scroll",
+        samples: scrolled,
+    });
+
+    let rope = Rope::from_str(&large_source(LINES));
+    results.push(Measurement {
+        name: "search",
+        samples: timed(|| {
+            bench::search(&rope, "label");
+        }),
+    });
+    match bench::parse(Language::Rust, &rope) {
+        Some((tree, query)) => results.push(Measurement {
+            name: "highlight",
+            samples: timed(|| {
+                bench::highlight_spans(&rope, &tree, &query, LINES / 2..LINES / 2 + 60);
+            }),
+        }),
+        None => eprintln!("skipping highlight: build with --features editor-languages"),
+    }
+
+    let points = (0..CHART_POINTS)
+        .map(|ix| LineChartPoint::new(ix as f64, (ix as f64 / 50.0).sin() * 100.0))
+        .collect();
+    let _ = view.update(cx, |view, _, cx| {
+        view.chart = Some(LineChartSeries::new("signal", points));
+        cx.notify();
+    });
+    let painted = frame_latency(window, cx, |_, _| {}).await;
+    results.push(Measurement {
+        name: "chart_paint",
+        samples: painted,
+    });
+
+    results
+}
+
+/// Print a table of the results and whether each is within its threshold.
+fn report(results: &[Measurement]) -> bool {
+    let mut passed = true;
+    println!(
+        "{:<12} {:>10} {:>10} {:>10}  result",
+        "bench", "median", "p95", "threshold"
+    );
+    for result in results.iter().filter(|result| !result.samples.is_empty()) {
+        let p95 = result.percentile(0.95);
+        let threshold = THRESHOLDS
+            .iter()
+            .find(|(name, _)| *name == result.name)
+            .map(|(_, threshold)| *threshold);
+        let ok = threshold.map_or(true, |threshold| p95 <= threshold);
+        passed &= ok;
+        println!(
+            "{:<12} {:>10.2?} {:>10.2?} {:>10.2?}  {}",
+            result.name,
+            result.percentile(0.5),
+            p95,
+            threshold.unwrap_or_default(),
+            if ok { "ok" } else { "REGRESSION" }
+        );
+    }
+    passed
+}
+
+fn main() {
+    Application::new().run(|cx| {
+        adabraka_ui::init(cx);
+        install_theme(cx, Theme::dark());
+
+        let view = cx
+            .open_window(
+                WindowOptions {
+                    titlebar: Some(TitlebarOptions {
+                        title: Some("adabraka-ui bench".into()),
+                        ..Default::default()
+                    }),
+                    window_bounds: Some(WindowBounds::Windowed(Bounds {
+                        origin: Point::default(),
+                        size: size(px(1300.0), px(800.0)),
+                    })),
+                    ..Default::default()
+                },
+                |window, cx| {
+                    let editor = cx.new(|cx| {
+                        let mut state = EditorState::new(cx);
+                        state.set_language(Language::Rust);
+                        state.set_content(&large_source(LINES), cx);
+                        state
+                    });
+                    window.focus(&editor.read(cx).focus_handle(cx));
+                    cx.new(|_| BenchView {
+                        editor,
+                        chart: None,
+                    })
+                },
+            )
+            .unwrap();
+
+        cx.spawn(async move |cx| {
+            let results = run_benches(view, cx).await;
+            let passed = report(&results);
+            std::process::exit(if passed { 0 } else { 1 });
+        })
+        .detach();
+    });
+}
@@ -1,4 +1,5 @@
 use adabraka_ui::{
+    callbacks,
     components::button::{Button, ButtonVariant},
     components::icon_source::IconSource,
     layout::VStack,
@@ -92,146 +93,91 @@ impl CommandPaletteDemo {
                 .description("Create a new file")
                 .category("File")
                 .shortcut("Cmd+N")
-                .on_select({
-                    let entity = entity.clone();
-                    move |_, cx| {
-                        cx.update_entity(&entity, |this, cx| {
-                            this.execute_command("New File", cx);
-                        });
-                    }
-                }),
+                .on_select(callbacks::weak_handler(&entity, |this, _, cx| {
+                    this.execute_command("New File", cx);
+                })),
             Command::new("file.open", "Open File")
                 .icon(IconSource::Named("folder-open".into()))
                 .description("Open an existing file")
                 .category("File")
                 .shortcut("Cmd+O")
-                .on_select({
-                    let entity = entity.clone();
-                    move |_, cx| {
-                        cx.update_entity(&entity, |this, cx| {
-                            this.execute_command("Open File", cx);
-                        });
-                    }
-                }),
+                .on_select(callbacks::weak_handler(&entity, |this, _, cx| {
+                    this.execute_command("Open File", cx);
+                })),
             Command::new("file.save", "Save")
                 .icon(IconSource::Named("save".into()))
                 .description("Save the current file")
                 .category("File")
                 .shortcut("Cmd+S")
-                .on_select({
-                    let entity = entity.clone();
-                    move |_, cx| {
-                        cx.update_entity(&entity, |this, cx| {
-                            this.execute_command("Save", cx);
-                        });
-                    }
-                }),
+                .on_select(callbacks::weak_handler(&entity, |this, _, cx| {
+                    this.execute_command("Save", cx);
+                })),
             // Edit commands
             Command::new("edit.undo", "Undo")
                 .icon(IconSource::Named("undo".into()))
                 .description("Undo the last action")
                 .category("Edit")
                 .shortcut("Cmd+Z")
-                .on_select({
-                    let entity = entity.clone();
-                    move |_, cx| {
-                        cx.update_entity(&entity, |this, cx| {
-                            this.execute_command("Undo", cx);
-                        });
-                    }
-                }),
+                .on_select(callbacks::weak_handler(&entity, |this, _, cx| {
+                    this.execute_command("Undo", cx);
+                })),
             Command::new("edit.redo", "Redo")
                 .icon(IconSource::Named("redo".into()))
                 .description("Redo the last undone action")
                 .category("Edit")
                 .shortcut("Cmd+Shift+Z")
-                .on_select({
-                    let entity = entity.clone();
-                    move |_, cx| {
-                        cx.update_entity(&entity, |this, cx| {
-                            this.execute_command("Redo", cx);
-                        });
-                    }
-                }),
+                .on_select(callbacks::weak_handler(&entity, |this, _, cx| {
+                    this.execute_command("Redo", cx);
+                })),
             Command::new("edit.find", "Find")
                 .icon(IconSource::Named("search".into()))
                 .description("Find text in the current file")
                 .category("Edit")
                 .shortcut("Cmd+F")
-                .on_select({
-                    let entity = entity.clone();
-                    move |_, cx| {
-                        cx.update_entity(&entity, |this, cx| {
-                            this.execute_command("Find", cx);
-                        });
-                    }
-                }),
+                .on_select(callbacks::weak_handler(&entity, |this, _, cx| {
+                    this.execute_command("Find", cx);
+                })),
             // View commands
             Command::new("view.sidebar", "Toggle Sidebar")
                 .icon(IconSource::Named("sidebar".into()))
                 .description("Show or hide the sidebar")
                 .category("View")
                 .shortcut("Cmd+B")
-                .on_select({
-                    let entity = entity.clone();
-                    move |_, cx| {
-                        cx.update_entity(&entity, |this, cx| {
-                            this.execute_command("Toggle Sidebar", cx);
-                        });
-                    }
-                }),
+                .on_select(callbacks::weak_handler(&entity, |this, _, cx| {
+                    this.execute_command("Toggle Sidebar", cx);
+                })),
             Command::new("view.terminal", "Toggle Terminal")
                 .icon(IconSource::Named("terminal".into()))
                 .description("Show or hide the terminal")
                 .category("View")
                 .shortcut("Cmd+`")
-                .on_select({
-                    let entity = entity.clone();
-                    move |_, cx| {
-                        cx.update_entity(&entity, |this, cx| {
-                            this.execute_command("Toggle Terminal", cx);
-                        });
-                    }
-                }),
+                .on_select(callbacks::weak_handler(&entity, |this, _, cx| {
+                    this.execute_command("Toggle Terminal", cx);
+                })),
             // Theme commands
             Command::new("theme.light", "Switch to Light Theme")
                 .icon(IconSource::Named("sun".into()))
                 .description("Use light color theme")
                 .category("Theme")
-                .on_select({
-                    let entity = entity.clone();
-                    move |_, cx| {
-                        cx.update_entity(&entity, |this, cx| {
-                            this.execute_command("Switch to Light Theme", cx);
-                        });
-                    }
-                }),
+                .on_select(callbacks::weak_handler(&entity, |this, _, cx| {
+                    this.execute_command("Switch to Light Theme", cx);
+                })),
             Command::new("theme.dark", "Switch to Dark Theme")
                 .icon(IconSource::Named("moon".into()))
                 .description("Use dark color theme")
                 .category("Theme")
-                .on_select({
-                    let entity = entity.clone();
-                    move |_, cx| {
-                        cx.update_entity(&entity, |this, cx| {
-                            this.execute_command("Switch to Dark Theme", cx);
-                        });
-                    }
-                }),
+                .on_select(callbacks::weak_handler(&entity, |this, _, cx| {
+                    this.execute_command("Switch to Dark Theme", cx);
+                })),
             // Settings commands
             Command::new("settings.open", "Open Settings")
                 .icon(IconSource::Named("settings".into()))
                 .description("Open application settings")
                 .category("Settings")
                 .shortcut("Cmd+,")
-                .on_select({
-                    let entity = entity.clone();
-                    move |_, cx| {
-                        cx.update_entity(&entity, |this, cx| {
-                            this.execute_command("Open Settings", cx);
-                        });
-                    }
-                }),
+                .on_select(callbacks::weak_handler(&entity, |this, _, cx| {
+                    this.execute_command("Open Settings", cx);
+                })),
         ]
     }
 }
@@ -431,12 +377,10 @@ impl Render for CommandPaletteDemo {
 
                 vec![cx.new(|palette_cx| {
                     CommandPalette::new(_window, palette_cx, commands)
-                        .on_close(move |_, close_cx| {
-                            close_cx.update_entity(&entity, |this, inner_cx| {
-                                this.show_palette = false;
-                                inner_cx.notify();
-                            });
-                        })
+                        .on_close(callbacks::weak_handler(&entity, |this, _, cx| {
+                            this.show_palette = false;
+                            cx.notify();
+                        }))
                 }).into_any_element()]
             } else {
                 vec![]
@@ -0,0 +1,573 @@
+//! Kitchen sink: every component family in one navigable app.
+//!
+//! A sidebar switches between pages for inputs, overlays, charts, navigation and the
+//! code editor. Each page wires real component state (sliders, number inputs, toggles,
+//! dialogs, toasts) so the example doubles as a manual integration test: if a component
+//! regresses, it shows up here.
+
+use adabraka_ui::{
+    charts::{BarChart, BarChartData, LineChart, LineChartPoint, LineChartSeries},
+    components::{
+        button::{Button, ButtonSize, ButtonVariant},
+        checkbox::Checkbox,
+        editor::{Editor, EditorState, Language},
+        input::Input,
+        input_state::InputState,
+        number_input::{NumberInput, NumberInputState},
+        progress::ProgressBar,
+        scrollable::scrollable_vertical,
+        slider::{Slider, SliderState},
+        text::{caption, h2, h3, muted},
+        toggle::Toggle,
+        tooltip::Tooltip,
+    },
+    display::badge::{Badge, BadgeVariant},
+    navigation::{
+        breadcrumbs::{BreadcrumbItem, Breadcrumbs},
+        tabs::{TabItem, TabPanel, Tabs},
+    },
+    overlays::{
+        dialog::Dialog,
+        toast::{ToastItem, ToastManager, ToastVariant},
+    },
+    theme::{install_theme, use_theme, Theme},
+};
+use gpui::{prelude::FluentBuilder as _, *};
+use std::path::PathBuf;
+
+struct Assets {
+    base: PathBuf,
+}
+
+impl gpui::AssetSource for Assets {
+    fn load(&self, path: &str) -> Result<Option<std::borrow::Cow<'static, [u8]>>> {
+        std::fs::read(self.base.join(path))
+            .map(|data| Some(std::borrow::Cow::Owned(data)))
+            .map_err(|err| err.into())
+    }
+
+    fn list(&self, path: &str) -> Result<Vec<SharedString>> {
+        std::fs::read_dir(self.base.join(path))
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| {
+                        entry
+                            .ok()
+                            .and_then(|entry| entry.file_name().into_string().ok())
+                            .map(SharedString::from)
+                    })
+                    .collect()
+            })
+            .map_err(|err| err.into())
+    }
+}
+
+fn main() {
+    Application::new()
+        .with_assets(Assets {
+            base: PathBuf::from(env!("CARGO_MANIFEST_DIR")),
+        })
+        .run(|cx| {
+            adabraka_ui::init(cx);
+            adabraka_ui::set_icon_base_path("assets/icons");
+            install_theme(cx, Theme::dark());
+
+            cx.open_window(
+                WindowOptions {
+                    titlebar: Some(TitlebarOptions {
+                        title: Some("adabraka-ui Kitchen Sink".into()),
+                        ..Default::default()
+                    }),
+                    window_bounds: Some(WindowBounds::Windowed(Bounds {
+                        origin: Point::default(),
+                        size: size(px(1280.0), px(820.0)),
+                    })),
+                    ..Default::default()
+                },
+                |_, cx| cx.new(KitchenSink::new),
+            )
+            .unwrap();
+        });
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Page {
+    Inputs,
+    Overlays,
+    Charts,
+    Navigation,
+    Editor,
+}
+
+impl Page {
+    const ALL: [Page; 5] = [
+        Page::Inputs,
+        Page::Overlays,
+        Page::Charts,
+        Page::Navigation,
+        Page::Editor,
+    ];
+
+    fn title(&self) -> &'static str {
+        match self {
+            Page::Inputs => "Inputs",
+            Page::Overlays => "Overlays",
+            Page::Charts => "Charts",
+            Page::Navigation => "Navigation",
+            Page::Editor => "Editor",
+        }
+    }
+
+    fn icon(&self) -> &'static str {
+        match self {
+            Page::Inputs => "text-cursor-input",
+            Page::Overlays => "layers",
+            Page::Charts => "chart-bar",
+            Page::Navigation => "compass",
+            Page::Editor => "code",
+        }
+    }
+}
+
+struct KitchenSink {
+    page: Page,
+    dark: bool,
+    name_input: Entity<InputState>,
+    volume: Entity<SliderState>,
+    quantity: Entity<NumberInputState>,
+    notifications: bool,
+    accept_terms: bool,
+    dialog: Entity<Dialog>,
+    dialog_open: bool,
+    toasts: Entity<ToastManager>,
+    next_toast_id: u64,
+    selected_tab: usize,
+    editor: Entity<EditorState>,
+}
+
+impl KitchenSink {
+    fn new(cx: &mut Context<Self>) -> Self {
+        let name_input = cx.new(|cx| InputState::new(cx).placeholder("Your name"));
+        let volume = cx.new(|cx| {
+            let mut state = SliderState::new(cx);
+            state.set_value(40.0, cx);
+            state
+        });
+        let quantity = cx.new(|cx| NumberInputState::with_value(cx, 3.0));
+
+        let this = cx.entity().downgrade();
+        let dialog = cx.new(|cx| {
+            Dialog::new(cx)
+                .title("Delete project?")
+                .description("This removes the project and all of its history.")
+                .on_close(move |_, cx| {
+                    let _ = this.update(cx, |sink, cx| {
+                        sink.dialog_open = false;
+                        cx.notify();
+                    });
+                })
+        });
+
+        let editor = cx.new(|cx| {
+            let mut state = EditorState::new(cx);
+            state.set_language(Language::Rust);
+            state.set_content(SAMPLE_SOURCE, cx);
+            state
+        });
+
+        Self {
+            page: Page::Inputs,
+            dark: true,
+            name_input,
+            volume,
+            quantity,
+            notifications: true,
+            accept_terms: false,
+            dialog,
+            dialog_open: false,
+            toasts: cx.new(ToastManager::new),
+            next_toast_id: 0,
+            selected_tab: 0,
+            editor,
+        }
+    }
+
+    fn toggle_theme(&mut self, cx: &mut Context<Self>) {
+        self.dark = !self.dark;
+        install_theme(
+            cx,
+            if self.dark {
+                Theme::dark()
+            } else {
+                Theme::light()
+            },
+        );
+        cx.refresh_windows();
+    }
+
+    fn show_toast(&mut self, variant: ToastVariant, window: &mut Window, cx: &mut Context<Self>) {
+        self.next_toast_id += 1;
+        let title = match variant {
+            ToastVariant::Default => "Saved",
+            ToastVariant::Success => "Deployed",
+            ToastVariant::Warning => "Disk almost full",
+            ToastVariant::Error => "Build failed",
+        };
+        let toast = ToastItem::new(self.next_toast_id, title)
+            .description("Triggered from the kitchen sink")
+            .variant(variant);
+        self.toasts
+            .update(cx, |manager, cx| manager.add_toast(toast, window, cx));
+    }
+
+    fn render_sidebar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = use_theme();
+
+        div()
+            .flex()
+            .flex_col()
+            .w(px(220.0))
+            .h_full()
+            .flex_shrink_0()
+            .gap(px(4.0))
+            .p(px(12.0))
+            .bg(theme.tokens.card)
+            .border_r_1()
+            .border_color(theme.tokens.border)
+            .child(div().px(px(8.0)).pb(px(12.0)).child(h3("Kitchen Sink")))
+            .children(Page::ALL.iter().map(|page| {
+                let page = *page;
+                let active = self.page == page;
+                Button::new(SharedString::from(page.title()), page.title())
+                    .icon(page.icon())
+                    .variant(if active {
+                        ButtonVariant::Secondary
+                    } else {
+                        ButtonVariant::Ghost
+                    })
+                    .w_full()
+                    .on_click(cx.listener(move |this, _: &ClickEvent, _, cx| {
+                        this.page = page;
+                        cx.notify();
+                    }))
+            }))
+            .child(div().flex_1())
+            .child(
+                Button::new(
+                    "toggle-theme",
+                    if self.dark {
+                        "Light theme"
+                    } else {
+                        "Dark theme"
+                    },
+                )
+                .variant(ButtonVariant::Outline)
+                .size(ButtonSize::Sm)
+                .on_click(cx.listener(|this, _: &ClickEvent, _, cx| this.toggle_theme(cx))),
+            )
+    }
+
+    fn render_inputs(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let name = self.name_input.read(cx).content().to_string();
+        let volume = self.volume.read(cx).value();
+        let quantity = self.quantity.read(cx).value();
+
+        page_column()
+            .child(
+                section("Text input")
+                    .child(Input::new(&self.name_input).placeholder("Your name"))
+                    .child(muted(if name.is_empty() {
+                        "Type to see the state update live".to_string()
+                    } else {
+                        format!("Hello, {}!", name)
+                    })),
+            )
+            .child(
+                section("Buttons").child(
+                    row()
+                        .child(Button::new("btn-default", "Default"))
+                        .child(
+                            Button::new("btn-secondary", "Secondary")
+                                .variant(ButtonVariant::Secondary),
+                        )
+                        .child(
+                            Button::new("btn-outline", "Outline").variant(ButtonVariant::Outline),
+                        )
+                        .child(Button::new("btn-ghost", "Ghost").variant(ButtonVariant::Ghost))
+                        .child(
+                            Button::new("btn-destructive", "Destructive")
+                                .variant(ButtonVariant::Destructive),
+                        )
+                        .child(Button::new("btn-disabled", "Disabled").disabled(true)),
+                ),
+            )
+            .child(
+                section("Slider and number input")
+                    .child(Slider::new(self.volume.clone()).show_value(true))
+                    .child(ProgressBar::new(volume / 100.0).label("Volume"))
+                    .child(
+                        row()
+                            .child(NumberInput::new(self.quantity.clone()))
+                            .child(caption(format!("Quantity: {}", quantity))),
+                    ),
+            )
+            .child(
+                section("Toggles").child(
+                    row()
+                        .child(
+                            Toggle::new("notifications")
+                                .label("Notifications")
+                                .checked(self.notifications)
+                                .on_click(cx.listener(|this, checked: &bool, _, cx| {
+                                    this.notifications = *checked;
+                                    cx.notify();
+                                })),
+                        )
+                        .child(
+                            Checkbox::new("terms")
+                                .label("Accept terms")
+                                .checked(self.accept_terms)
+                                .on_click(cx.listener(|this, checked: &bool, _, cx| {
+                                    this.accept_terms = *checked;
+                                    cx.notify();
+                                })),
+                        ),
+                ),
+            )
+    }
+
+    fn render_overlays(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        page_column()
+            .child(
+                section("Dialog").child(
+                    row().child(
+                        Button::new("open-dialog", "Open dialog")
+                            .variant(ButtonVariant::Destructive)
+                            .on_click(cx.listener(|this, _: &ClickEvent, _, cx| {
+                                this.dialog_open = true;
+                                cx.notify();
+                            })),
+                    ),
+                ),
+            )
+            .child(
+                section("Toasts").child(
+                    row()
+                        .child(
+                            Button::new("toast-default", "Default").on_click(cx.listener(
+                                |this, _: &ClickEvent, window, cx| {
+                                    this.show_toast(ToastVariant::Default, window, cx)
+                                },
+                            )),
+                        )
+                        .child(
+                            Button::new("toast-success", "Success")
+                                .variant(ButtonVariant::Secondary)
+                                .on_click(cx.listener(|this, _: &ClickEvent, window, cx| {
+                                    this.show_toast(ToastVariant::Success, window, cx)
+                                })),
+                        )
+                        .child(
+                            Button::new("toast-error", "Error")
+                                .variant(ButtonVariant::Outline)
+                                .on_click(cx.listener(|this, _: &ClickEvent, window, cx| {
+                                    this.show_toast(ToastVariant::Error, window, cx)
+                                })),
+                        ),
+                ),
+            )
+            .child(section("Tooltip").child(row().child(
+                Tooltip::new("Tooltips follow the theme").child(
+                    Button::new("tooltip-target", "Hover me").variant(ButtonVariant::Outline),
+                ),
+            )))
+    }
+
+    fn render_charts(&self) -> impl IntoElement {
+        let revenue = vec![
+            BarChartData::new("Jan", 42.0),
+            BarChartData::new("Feb", 58.0),
+            BarChartData::new("Mar", 35.0),
+            BarChartData::new("Apr", 71.0),
+            BarChartData::new("May", 64.0),
+        ];
+        let users = LineChartSeries::new(
+            "Active users",
+            (0..12)
+                .map(|i| {
+                    LineChartPoint::new(
+                        i as f64,
+                        20.0 + (i as f64 * 0.8).sin() * 10.0 + i as f64 * 3.0,
+                    )
+                })
+                .collect(),
+        )
+        .fill_area(true);
+
+        page_column()
+            .child(
+                section("Bar chart").child(
+                    BarChart::new(revenue)
+                        .show_values(true)
+                        .chart_height(px(220.0)),
+                ),
+            )
+            .child(
+                section("Line chart").child(
+                    div()
+                        .h(px(240.0))
+                        .child(LineChart::single(users).smooth(true).show_grid(true)),
+                ),
+            )
+    }
+
+    fn render_navigation(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let entity = cx.entity().downgrade();
+
+        page_column()
+            .child(
+                section("Breadcrumbs").child(Breadcrumbs::new(cx).items(vec![
+                    BreadcrumbItem {
+                        id: Page::Inputs,
+                        label: "Home".into(),
+                        icon: None,
+                    },
+                    BreadcrumbItem {
+                        id: Page::Navigation,
+                        label: "Components".into(),
+                        icon: None,
+                    },
+                    BreadcrumbItem {
+                        id: Page::Navigation,
+                        label: "Navigation".into(),
+                        icon: None,
+                    },
+                ])),
+            )
+            .child(
+                section("Tabs").child(
+                    Tabs::new()
+                        .tabs(vec![
+                            TabItem::new(0usize, "Overview"),
+                            TabItem::new(1usize, "Activity").badge("3"),
+                            TabItem::new(2usize, "Settings"),
+                        ])
+                        .panels(vec![
+                            TabPanel::new(|| muted("Project overview").into_any_element()),
+                            TabPanel::new(|| muted("Recent activity").into_any_element()),
+                            TabPanel::new(|| muted("Project settings").into_any_element()),
+                        ])
+                        .selected_index(self.selected_tab)
+                        .on_change(move |index, _, cx| {
+                            let index = *index;
+                            let _ = entity.update(cx, |this, cx| {
+                                this.selected_tab = index;
+                                cx.notify();
+                            });
+                        }),
+                ),
+            )
+            .child(
+                section("Badges").child(
+                    row()
+                        .child(Badge::new("Default"))
+                        .child(Badge::new("Secondary").variant(BadgeVariant::Secondary))
+                        .child(Badge::new("Outline").variant(BadgeVariant::Outline))
+                        .child(Badge::new("Warning").variant(BadgeVariant::Warning))
+                        .child(Badge::new("Destructive").variant(BadgeVariant::Destructive)),
+                ),
+            )
+    }
+
+    fn render_editor(&self) -> impl IntoElement {
+        div()
+            .flex()
+            .flex_col()
+            .size_full()
+            .gap(px(12.0))
+            .p(px(24.0))
+            .child(h2("Editor"))
+            .child(muted("Syntax highlighting, folding, search and a minimap."))
+            .child(
+                div()
+                    .flex_1()
+                    .min_h_0()
+                    .child(Editor::new(&self.editor).minimap(true)),
+            )
+    }
+}
+
+impl Render for KitchenSink {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = use_theme();
+
+        let content = match self.page {
+            Page::Inputs => scrollable_vertical(self.render_inputs(cx)).into_any_element(),
+            Page::Overlays => scrollable_vertical(self.render_overlays(cx)).into_any_element(),
+            Page::Charts => scrollable_vertical(self.render_charts()).into_any_element(),
+            Page::Navigation => scrollable_vertical(self.render_navigation(cx)).into_any_element(),
+            Page::Editor => self.render_editor().into_any_element(),
+        };
+
+        div()
+            .size_full()
+            .flex()
+            .bg(theme.tokens.background)
+            .text_color(theme.tokens.foreground)
+            .font_family(theme.tokens.font_family.clone())
+            .child(self.render_sidebar(cx))
+            .child(
+                div()
+                    .flex_1()
+                    .h_full()
+                    .min_w_0()
+                    .overflow_hidden()
+                    .child(content),
+            )
+            .when(self.dialog_open, |this| this.child(self.dialog.clone()))
+            .child(self.toasts.clone())
+    }
+}
+
+fn page_column() -> Div {
+    div().flex().flex_col().gap(px(24.0)).p(px(24.0))
+}
+
+fn section(title: &'static str) -> Div {
+    let theme = use_theme();
+    div()
+        .flex()
+        .flex_col()
+        .gap(px(12.0))
+        .p(px(20.0))
+        .bg(theme.tokens.card)
+        .border_1()
+        .border_color(theme.tokens.border)
+        .rounded(theme.tokens.radius_lg)
+        .child(h3(title))
+}
+
+fn row() -> Div {
+    div().flex().flex_wrap().items_center().gap(px(12.0))
+}
+
+const SAMPLE_SOURCE: &str = r#"use std::collections::HashMap;
+
+/// Count word frequencies in a block of text.
+fn word_counts(text: &str) -> HashMap<&str, usize> {
+    let mut counts = HashMap::new();
+    for word in text.split_whitespace() {
+        *counts.entry(word).or_insert(0) += 1;
+    }
+    counts
+}
+
+fn main() {
+    let counts = word_counts("the quick brown fox jumps over the lazy dog");
+    let mut sorted: Vec<_> = counts.into_iter().collect();
+    sorted.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    for (word, count) in sorted {
+        println!("{word}: {count}");
+    }
+}
+"#;
@@ -22,6 +22,7 @@ impl RangeSliderDemo {
             state.set_max(1000.0, cx);
             state.set_range(200.0, 800.0, cx);
             state.set_step(10.0, cx);
+            state.set_min_gap(50.0, cx);
             state
         });
 
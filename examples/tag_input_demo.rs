@@ -14,7 +14,18 @@ struct TagInputDemo {
 
 impl TagInputDemo {
     fn new(cx: &mut Context<Self>) -> Self {
-        let basic_tags = cx.new(|cx| TagInputState::new(cx));
+        let basic_tags = cx.new(|cx| {
+            let mut state = TagInputState::new(cx);
+            state.set_ignore_case(true, cx);
+            state.set_validator(|text| {
+                if text.contains(char::is_whitespace) {
+                    Err("Tags can't contain spaces".into())
+                } else {
+                    Ok(text.to_lowercase().into())
+                }
+            });
+            state
+        });
         let limited_tags = cx.new(|cx| {
             let mut state = TagInputState::new(cx);
             state.set_max_tags(Some(5), cx);
@@ -59,7 +70,7 @@ impl Render for TagInputDemo {
                                 div()
                                     .text_size(px(14.0))
                                     .text_color(theme.tokens.muted_foreground)
-                                    .child("Add and remove tags with keyboard support (Enter to add, Backspace to remove)"),
+                                    .child("Add and remove tags with keyboard support (Enter or comma to add, Backspace to remove)"),
                             ),
                     )
                     .child(
@@ -77,11 +88,15 @@ impl Render for TagInputDemo {
                                         div()
                                             .text_size(px(14.0))
                                             .font_weight(FontWeight::MEDIUM)
-                                            .child("Basic Tag Input"),
+                                            .child("Lowercase Tags with Suggestions"),
                                     )
                                     .child(
                                         TagInput::new(self.basic_tags.clone())
                                             .placeholder("Add tags...")
+                                            .suggestions(vec![
+                                                "design", "develop", "devops", "docs", "release",
+                                                "research",
+                                            ])
                                             .on_change({
                                                 move |tags, _, cx| {
                                                     entity.update(cx, |this, cx| {
@@ -0,0 +1,187 @@
+//! User idle and window activation, for throttling background work.
+//!
+//! Work nobody can see is wasted CPU: a cursor blinking in a window in the background,
+//! or a resource polled while the user is away from the machine. Components ask
+//! [`should_animate`] before running a timer for the sake of what's on screen, and
+//! [`should_poll`] before work whose results can wait for the user to come back.
+//! Animation loops driven from an entity, which has no window to ask, wait on
+//! [`wait_until_pollable`] between frames.
+//!
+//! The user counts as idle after [`set_idle_timeout`] (five minutes by default) with
+//! no keystrokes, mouse input in a window that called [`track_window`], or
+//! [`note_activity`] calls. Observers registered with [`observe`] run
+//! when the user goes idle or comes back, and when a window that called
+//! [`track_window`] is activated or deactivated, so paused work can resume.
+//!
+//! ```rust,ignore
+//! // in an entity's constructor:
+//! self._activity = activity::observe(cx, |this, cx| this.resume_if_needed(cx));
+//!
+//! // in a poll loop:
+//! smol::Timer::after(interval).await;
+//! activity::wait_until_pollable(cx).await;
+//! ```
+
+use gpui::*;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// How often the user's idle time is checked.
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// How often paused polling checks whether it may resume.
+const RESUME_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Input is noted on every keystroke and mouse move, so everything but `idle` sits in
+/// cells: mutable access to a global notifies its observers, and those re-render.
+struct ActivityMonitor {
+    last_input: Cell<Instant>,
+    idle_timeout: Cell<Duration>,
+    idle: bool,
+    windows: RefCell<HashMap<AnyWindowHandle, Subscription>>,
+    _keystrokes: Option<Subscription>,
+    _idle_check: Option<Task<()>>,
+}
+
+impl Default for ActivityMonitor {
+    fn default() -> Self {
+        Self {
+            last_input: Cell::new(Instant::now()),
+            idle_timeout: Cell::new(DEFAULT_IDLE_TIMEOUT),
+            idle: false,
+            windows: RefCell::new(HashMap::new()),
+            _keystrokes: None,
+            _idle_check: None,
+        }
+    }
+}
+
+impl Global for ActivityMonitor {}
+
+/// The monitor, without notifying its observers unless this creates it.
+fn monitor(cx: &mut App) -> &ActivityMonitor {
+    if cx.try_global::<ActivityMonitor>().is_none() {
+        cx.set_global(ActivityMonitor::default());
+    }
+    cx.global::<ActivityMonitor>()
+}
+
+/// Observers hear about the user going idle or coming back, not each input.
+fn set_idle(idle: bool, cx: &mut App) {
+    if monitor(cx).idle != idle {
+        cx.update_global::<ActivityMonitor, _>(|monitor, _| monitor.idle = idle);
+    }
+}
+
+pub fn init(cx: &mut App) {
+    let keystrokes = cx.observe_keystrokes(|_, _, cx| note_activity(cx));
+    let idle_check = cx.spawn(async move |cx| loop {
+        smol::Timer::after(IDLE_CHECK_INTERVAL).await;
+        let checked = cx.update(|cx| {
+            let monitor = monitor(cx);
+            if monitor.last_input.get().elapsed() >= monitor.idle_timeout.get() {
+                set_idle(true, cx);
+            }
+        });
+        if checked.is_err() {
+            break;
+        }
+    });
+    let monitor = cx.default_global::<ActivityMonitor>();
+    monitor._keystrokes = Some(keystrokes);
+    monitor._idle_check = Some(idle_check);
+}
+
+/// Record user input that isn't a keystroke, e.g. a click or scroll in a window that
+/// doesn't [`track_window`], so the user isn't taken for idle. Cheap enough to call on
+/// every mouse move.
+pub fn note_activity(cx: &mut App) {
+    monitor(cx).last_input.set(Instant::now());
+    set_idle(false, cx);
+}
+
+/// How long without input before the user counts as idle.
+pub fn set_idle_timeout(timeout: Duration, cx: &mut App) {
+    monitor(cx).idle_timeout.set(timeout);
+}
+
+pub fn is_idle(cx: &App) -> bool {
+    cx.try_global::<ActivityMonitor>()
+        .is_some_and(|monitor| monitor.idle)
+}
+
+/// Whether any of the app's windows is the active one, i.e. the app is in front.
+pub fn is_app_active(cx: &App) -> bool {
+    cx.active_window().is_some()
+}
+
+/// Whether `window` is in front and the user is at the machine, i.e. animation in it
+/// would be seen.
+pub fn should_animate(window: &Window, cx: &App) -> bool {
+    window.is_window_active() && !is_idle(cx)
+}
+
+/// Whether background refreshes are worth doing: the app is in front and the user is
+/// at the machine.
+pub fn should_poll(cx: &App) -> bool {
+    is_app_active(cx) && !is_idle(cx)
+}
+
+/// Wait until [`should_poll`] holds, returning right away if it does.
+pub async fn wait_until_pollable(cx: &mut AsyncApp) {
+    while matches!(cx.update(|cx| should_poll(cx)), Ok(false)) {
+        smol::Timer::after(RESUME_CHECK_INTERVAL).await;
+    }
+}
+
+/// Count mouse movement, clicks and scrolling in `window` as activity, and notify
+/// [`observe`]rs when it's activated or deactivated. Call it while painting, on every
+/// frame: the mouse listeners only last the frame. Components that pause for a window
+/// call it when they paint.
+pub fn track_window(window: &mut Window, cx: &mut App) {
+    window.on_mouse_event(|_: &MouseMoveEvent, phase, _, cx| {
+        if phase.bubble() {
+            note_activity(cx);
+        }
+    });
+    window.on_mouse_event(|_: &MouseDownEvent, phase, _, cx| {
+        if phase.bubble() {
+            note_activity(cx);
+        }
+    });
+    window.on_mouse_event(|_: &ScrollWheelEvent, phase, _, cx| {
+        if phase.bubble() {
+            note_activity(cx);
+        }
+    });
+
+    let handle = window.window_handle();
+    if monitor(cx).windows.borrow().contains_key(&handle) {
+        return;
+    }
+    let subscription = window.observe_window_activation(|_, cx| {
+        monitor(cx);
+        cx.update_global::<ActivityMonitor, _>(|monitor, cx| {
+            let open = cx.windows();
+            monitor
+                .windows
+                .get_mut()
+                .retain(|handle, _| open.contains(handle));
+        });
+    });
+    monitor(cx)
+        .windows
+        .borrow_mut()
+        .insert(handle, subscription);
+}
+
+/// Run `f` whenever the user goes idle or returns, or a tracked window's activation
+/// changes.
+pub fn observe<T: 'static>(
+    cx: &mut Context<T>,
+    f: impl FnMut(&mut T, &mut Context<T>) + 'static,
+) -> Subscription {
+    monitor(cx);
+    cx.observe_global::<ActivityMonitor>(f)
+}
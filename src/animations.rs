@@ -662,6 +662,22 @@ pub fn spring_bounce(delta: f32, amplitude: f32) -> f32 {
     amplitude * decay * oscillation
 }
 
+#[derive(Default)]
+struct ReducedMotion(bool);
+
+impl Global for ReducedMotion {}
+
+/// Ask components with optional motion, such as smooth scrolling, to jump straight to
+/// their end state. Hosts typically mirror the platform's "reduce motion" setting here.
+pub fn set_reduced_motion(reduced: bool, cx: &mut App) {
+    cx.set_global(ReducedMotion(reduced));
+}
+
+pub fn reduced_motion(cx: &App) -> bool {
+    cx.try_global::<ReducedMotion>()
+        .is_some_and(|reduced| reduced.0)
+}
+
 pub fn lerp_f32(from: f32, to: f32, t: f32) -> f32 {
     from + (to - from) * t.clamp(0.0, 1.0)
 }
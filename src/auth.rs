@@ -0,0 +1,989 @@
+//! OAuth 2.0 sign-in for desktop apps.
+//!
+//! Two flows are covered. [`sign_in_with_browser`] runs the authorization code flow
+//! with PKCE: it opens the provider's consent page in the default browser and captures
+//! the redirect on a loopback port. [`DeviceCodeFlow`] runs the device authorization
+//! grant for providers or setups where a redirect isn't possible: the user enters a
+//! short code on the provider's site while the app polls for the result, and
+//! [`device_code_dialog`] shows the code with a countdown.
+//!
+//! Both hand the resulting [`OAuthToken`] to the app, which usually keeps the refresh
+//! token in the keychain with the `secrets` module.
+//!
+//! ```rust,ignore
+//! let config = OAuthConfig::new(
+//!     "my-client-id",
+//!     "https://github.com/login/oauth/authorize",
+//!     "https://github.com/login/oauth/access_token",
+//! )
+//! .device_authorization_url("https://github.com/login/device/code")
+//! .scopes(["repo", "read:user"]);
+//!
+//! let sign_in = auth::sign_in_with_browser(&config, cx);
+//! cx.spawn(async move |cx| match sign_in.await {
+//!     Ok(token) => { /* store token.refresh_token */ }
+//!     Err(err) => tracing::warn!(%err, "sign-in failed"),
+//! })
+//! .detach();
+//! ```
+
+use crate::components::button::{Button, ButtonSize, ButtonVariant};
+use crate::components::icon::Icon;
+use crate::components::spinner::{Spinner, SpinnerSize};
+use crate::components::text::caption;
+use crate::overlays::dialog::{Dialog, DialogSize};
+use crate::theme::use_theme;
+use crate::util::parse_query;
+use futures::{AsyncReadExt as _, AsyncWriteExt as _};
+use gpui::http_client::{AsyncBody, HttpClient, Request};
+use gpui::{prelude::FluentBuilder as _, *};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+const CALLBACK_PATH: &str = "/callback";
+const DEFAULT_REDIRECT_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// Polling interval when the device authorization response doesn't specify one.
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const SLOW_DOWN_STEP: Duration = Duration::from_secs(5);
+const MAX_REQUEST_HEADER: usize = 16 * 1024;
+
+/// Endpoints and client registration for one OAuth provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OAuthConfig {
+    pub client_id: SharedString,
+    /// Only for providers that require one from native apps; it can't be kept secret
+    /// in a shipped binary.
+    pub client_secret: Option<SharedString>,
+    pub authorization_url: SharedString,
+    pub token_url: SharedString,
+    pub device_authorization_url: Option<SharedString>,
+    pub scopes: Vec<SharedString>,
+    /// Loopback port for the browser redirect, `0` for any free port.
+    pub redirect_port: u16,
+    /// How long [`sign_in_with_browser`] waits for the user to finish in the browser.
+    pub redirect_timeout: Duration,
+}
+
+impl OAuthConfig {
+    pub fn new(
+        client_id: impl Into<SharedString>,
+        authorization_url: impl Into<SharedString>,
+        token_url: impl Into<SharedString>,
+    ) -> Self {
+        Self {
+            client_id: client_id.into(),
+            client_secret: None,
+            authorization_url: authorization_url.into(),
+            token_url: token_url.into(),
+            device_authorization_url: None,
+            scopes: Vec::new(),
+            redirect_port: 0,
+            redirect_timeout: DEFAULT_REDIRECT_TIMEOUT,
+        }
+    }
+
+    pub fn client_secret(mut self, secret: impl Into<SharedString>) -> Self {
+        self.client_secret = Some(secret.into());
+        self
+    }
+
+    /// Required for [`DeviceCodeFlow`].
+    pub fn device_authorization_url(mut self, url: impl Into<SharedString>) -> Self {
+        self.device_authorization_url = Some(url.into());
+        self
+    }
+
+    pub fn scopes<S: Into<SharedString>>(mut self, scopes: impl IntoIterator<Item = S>) -> Self {
+        self.scopes = scopes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Use a fixed loopback port, for providers that match the redirect URI exactly.
+    pub fn redirect_port(mut self, port: u16) -> Self {
+        self.redirect_port = port;
+        self
+    }
+
+    pub fn redirect_timeout(mut self, timeout: Duration) -> Self {
+        self.redirect_timeout = timeout;
+        self
+    }
+
+    fn scope_param(&self) -> String {
+        self.scopes
+            .iter()
+            .map(|scope| scope.as_ref())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn client_params(&self) -> Vec<(&'static str, String)> {
+        let mut params = vec![("client_id", self.client_id.to_string())];
+        if let Some(secret) = &self.client_secret {
+            params.push(("client_secret", secret.to_string()));
+        }
+        params
+    }
+}
+
+/// Tokens issued by the provider. `Debug` output leaves the token values out.
+#[derive(Clone, PartialEq, Eq)]
+pub struct OAuthToken {
+    pub access_token: SharedString,
+    pub token_type: SharedString,
+    pub refresh_token: Option<SharedString>,
+    /// Lifetime of the access token from when it was issued.
+    pub expires_in: Option<Duration>,
+    /// Granted scopes, if the provider reports them.
+    pub scope: Option<SharedString>,
+    pub id_token: Option<SharedString>,
+}
+
+impl fmt::Debug for OAuthToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OAuthToken")
+            .field("token_type", &self.token_type)
+            .field("has_refresh_token", &self.refresh_token.is_some())
+            .field("expires_in", &self.expires_in)
+            .field("scope", &self.scope)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthError {
+    Network(SharedString),
+    InvalidResponse(SharedString),
+    /// The user declined access.
+    Denied(SharedString),
+    /// The provider refused the request, e.g. an unknown client or a revoked grant.
+    Rejected(SharedString),
+    /// The device code expired before the user entered it.
+    Expired,
+    /// The browser never redirected back within [`OAuthConfig::redirect_timeout`].
+    TimedOut,
+    Cancelled,
+    Io(SharedString),
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::Network(message) => write!(f, "network error: {}", message),
+            AuthError::InvalidResponse(message) => write!(f, "invalid response: {}", message),
+            AuthError::Denied(message) => write!(f, "access denied: {}", message),
+            AuthError::Rejected(message) => write!(f, "sign-in rejected: {}", message),
+            AuthError::Expired => write!(f, "the sign-in code expired"),
+            AuthError::TimedOut => write!(f, "timed out waiting for the browser"),
+            AuthError::Cancelled => write!(f, "sign-in cancelled"),
+            AuthError::Io(message) => write!(f, "i/o error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+fn network_error(err: impl fmt::Display) -> AuthError {
+    AuthError::Network(err.to_string().into())
+}
+
+fn io_error(err: impl fmt::Display) -> AuthError {
+    AuthError::Io(err.to_string().into())
+}
+
+/// Sign in through the browser with the authorization code flow and PKCE.
+///
+/// Opens the consent page, waits for the redirect to `http://127.0.0.1:<port>/callback`
+/// and exchanges the code for tokens. Dropping the task stops waiting.
+pub fn sign_in_with_browser(
+    config: &OAuthConfig,
+    cx: &mut App,
+) -> Task<Result<OAuthToken, AuthError>> {
+    let (listener, port) = match bind_loopback(config.redirect_port) {
+        Ok(bound) => bound,
+        Err(err) => return Task::ready(Err(io_error(err))),
+    };
+    let (verifier, state) = match (random_token(), random_token()) {
+        (Ok(verifier), Ok(state)) => (verifier, state),
+        (Err(err), _) | (_, Err(err)) => return Task::ready(Err(err)),
+    };
+
+    let redirect_uri = format!("http://127.0.0.1:{}{}", port, CALLBACK_PATH);
+    let url = authorization_url(config, &redirect_uri, &state, &pkce_challenge(&verifier));
+    cx.open_url(&url);
+
+    let client = cx.http_client();
+    let config = config.clone();
+    cx.background_executor().spawn(async move {
+        let timeout = config.redirect_timeout;
+        let code = smol::future::or(wait_for_redirect(&listener, &state), async {
+            smol::Timer::after(timeout).await;
+            Err(AuthError::TimedOut)
+        })
+        .await?;
+
+        let mut params = config.client_params();
+        params.extend([
+            ("grant_type", "authorization_code".to_string()),
+            ("code", code),
+            ("redirect_uri", redirect_uri),
+            ("code_verifier", verifier),
+        ]);
+        let response = post_form(client.as_ref(), &config.token_url, &params).await?;
+        let token = parse_token_response(&response)?;
+        tracing::debug!(?token, "browser sign-in completed");
+        Ok(token)
+    })
+}
+
+/// Exchange a refresh token for a new access token.
+pub fn refresh_token(
+    config: &OAuthConfig,
+    refresh_token: impl Into<String>,
+    cx: &App,
+) -> Task<Result<OAuthToken, AuthError>> {
+    let client = cx.http_client();
+    let config = config.clone();
+    let refresh_token = refresh_token.into();
+    cx.background_executor().spawn(async move {
+        let mut params = config.client_params();
+        params.extend([
+            ("grant_type", "refresh_token".to_string()),
+            ("refresh_token", refresh_token.clone()),
+        ]);
+        let response = post_form(client.as_ref(), &config.token_url, &params).await?;
+        let mut token = parse_token_response(&response)?;
+        // Providers that don't rotate refresh tokens leave it out of the response.
+        if token.refresh_token.is_none() {
+            token.refresh_token = Some(refresh_token.into());
+        }
+        Ok(token)
+    })
+}
+
+fn bind_loopback(port: u16) -> std::io::Result<(smol::Async<TcpListener>, u16)> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+    let port = listener.local_addr()?.port();
+    Ok((smol::Async::new(listener)?, port))
+}
+
+/// Accept loopback connections until one carries the authorization response.
+async fn wait_for_redirect(
+    listener: &smol::Async<TcpListener>,
+    state: &str,
+) -> Result<String, AuthError> {
+    loop {
+        let (mut stream, _) = listener.accept().await.map_err(io_error)?;
+        let target = match read_request_target(&mut stream).await {
+            Ok(target) => target,
+            Err(err) => {
+                tracing::debug!(%err, "ignoring unreadable loopback request");
+                continue;
+            }
+        };
+        let Some(result) = parse_callback(&target, state) else {
+            let _ = respond(&mut stream, "404 Not Found", "Not found.").await;
+            continue;
+        };
+        let page = match &result {
+            Ok(_) => "You're signed in. You can close this tab and return to the app.",
+            Err(_) => "Sign-in didn't complete. You can close this tab and try again in the app.",
+        };
+        let _ = respond(&mut stream, "200 OK", page).await;
+        return result;
+    }
+}
+
+async fn read_request_target(stream: &mut smol::Async<TcpStream>) -> std::io::Result<String> {
+    let mut request = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n")
+        && request.len() < MAX_REQUEST_HEADER
+    {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&chunk[..read]);
+    }
+    let request = String::from_utf8_lossy(&request);
+    let request_line = request.lines().next().unwrap_or_default();
+    Ok(request_line
+        .split_whitespace()
+        .nth(1)
+        .unwrap_or_default()
+        .to_string())
+}
+
+async fn respond(
+    stream: &mut smol::Async<TcpStream>,
+    status: &str,
+    message: &str,
+) -> std::io::Result<()> {
+    let body = format!(
+        "<!doctype html><meta charset=\"utf-8\"><title>Sign-in</title>\
+         <body style=\"font-family: system-ui, sans-serif; margin: 4em; text-align: center\">\
+         <p>{}</p></body>",
+        message
+    );
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.flush().await
+}
+
+/// The authorization code from a redirect to `target`, or `None` for requests to other
+/// paths, such as the browser asking for a favicon.
+fn parse_callback(target: &str, expected_state: &str) -> Option<Result<String, AuthError>> {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    if path != CALLBACK_PATH {
+        return None;
+    }
+    let params = parse_query(query);
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+
+    if param("state") != Some(expected_state) {
+        return Some(Err(AuthError::InvalidResponse(
+            "redirect state doesn't match the request".into(),
+        )));
+    }
+    if let Some(error) = param("error") {
+        return Some(Err(token_error(error, param("error_description"))));
+    }
+    Some(
+        param("code")
+            .map(str::to_string)
+            .ok_or_else(|| AuthError::InvalidResponse("redirect without a code".into())),
+    )
+}
+
+/// A code the user enters at the verification page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceCode {
+    pub user_code: SharedString,
+    pub verification_uri: SharedString,
+    /// The verification page with the code already filled in, if the provider has one.
+    pub verification_uri_complete: Option<SharedString>,
+    pub expires_at: Instant,
+    device_code: String,
+    interval: Duration,
+}
+
+impl DeviceCode {
+    /// Time left to enter the code.
+    pub fn remaining(&self) -> Duration {
+        self.expires_at.saturating_duration_since(Instant::now())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceFlowStatus {
+    Requesting,
+    /// Waiting for the user to enter the code.
+    Pending(DeviceCode),
+    Authorized,
+    Failed(AuthError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceFlowEvent {
+    Authorized(OAuthToken),
+    Failed(AuthError),
+}
+
+/// Runs the device authorization grant and renders its progress: the code to enter,
+/// buttons to copy it and open the verification page, and the time left.
+///
+/// The flow starts when the entity is created. Subscribe to [`DeviceFlowEvent`] for the
+/// token; [`device_code_dialog`] presents the view in a dialog.
+pub struct DeviceCodeFlow {
+    config: OAuthConfig,
+    status: DeviceFlowStatus,
+    _task: Option<Task<()>>,
+    _countdown: Option<Task<()>>,
+}
+
+impl EventEmitter<DeviceFlowEvent> for DeviceCodeFlow {}
+
+impl DeviceCodeFlow {
+    pub fn new(config: OAuthConfig, cx: &mut Context<Self>) -> Self {
+        let mut this = Self {
+            config,
+            status: DeviceFlowStatus::Requesting,
+            _task: None,
+            _countdown: None,
+        };
+        this.restart(cx);
+        this
+    }
+
+    pub fn status(&self) -> &DeviceFlowStatus {
+        &self.status
+    }
+
+    /// Request a new code, abandoning the current one.
+    pub fn restart(&mut self, cx: &mut Context<Self>) {
+        self._countdown = None;
+        let Some(device_url) = self.config.device_authorization_url.clone() else {
+            self.finish(
+                Err(AuthError::Rejected(
+                    "no device authorization endpoint configured".into(),
+                )),
+                cx,
+            );
+            return;
+        };
+        self.status = DeviceFlowStatus::Requesting;
+        cx.notify();
+
+        let client = cx.http_client();
+        let config = self.config.clone();
+        let request = cx.background_executor().spawn(async move {
+            let mut params = config.client_params();
+            params.push(("scope", config.scope_param()));
+            let response = post_form(client.as_ref(), &device_url, &params).await?;
+            parse_device_code(&response, Instant::now())
+        });
+
+        self._task = Some(cx.spawn(async move |this, cx| {
+            let code = match request.await {
+                Ok(code) => code,
+                Err(err) => {
+                    let _ = this.update(cx, |this, cx| this.finish(Err(err), cx));
+                    return;
+                }
+            };
+            let polling = this.update(cx, |this, cx| {
+                this.status = DeviceFlowStatus::Pending(code.clone());
+                this.start_countdown(cx);
+                cx.notify();
+                let client = cx.http_client();
+                let config = this.config.clone();
+                cx.background_executor()
+                    .spawn(async move { poll_for_token(client.as_ref(), &config, &code).await })
+            });
+            let Ok(polling) = polling else {
+                return;
+            };
+            let result = polling.await;
+            let _ = this.update(cx, |this, cx| this.finish(result, cx));
+        }));
+    }
+
+    /// Stop polling. No event is emitted.
+    pub fn cancel(&mut self, cx: &mut Context<Self>) {
+        self._task = None;
+        self._countdown = None;
+        self.status = DeviceFlowStatus::Failed(AuthError::Cancelled);
+        cx.notify();
+    }
+
+    /// Open the verification page, with the code filled in when the provider allows it.
+    pub fn open_verification_page(&self, cx: &mut App) {
+        if let DeviceFlowStatus::Pending(code) = &self.status {
+            let url = code
+                .verification_uri_complete
+                .as_ref()
+                .unwrap_or(&code.verification_uri);
+            cx.open_url(url);
+        }
+    }
+
+    pub fn copy_code(&self, cx: &mut App) {
+        if let DeviceFlowStatus::Pending(code) = &self.status {
+            cx.write_to_clipboard(ClipboardItem::new_string(code.user_code.to_string()));
+        }
+    }
+
+    fn start_countdown(&mut self, cx: &mut Context<Self>) {
+        self._countdown = Some(cx.spawn(async move |this, cx| loop {
+            smol::Timer::after(Duration::from_secs(1)).await;
+            let pending = this.update(cx, |this, cx| {
+                cx.notify();
+                matches!(this.status, DeviceFlowStatus::Pending(_))
+            });
+            if !matches!(pending, Ok(true)) {
+                break;
+            }
+        }));
+    }
+
+    fn finish(&mut self, result: Result<OAuthToken, AuthError>, cx: &mut Context<Self>) {
+        self._countdown = None;
+        match result {
+            Ok(token) => {
+                tracing::debug!(?token, "device sign-in completed");
+                self.status = DeviceFlowStatus::Authorized;
+                cx.emit(DeviceFlowEvent::Authorized(token));
+            }
+            Err(err) => {
+                tracing::warn!(%err, "device sign-in failed");
+                self.status = DeviceFlowStatus::Failed(err.clone());
+                cx.emit(DeviceFlowEvent::Failed(err));
+            }
+        }
+        cx.notify();
+    }
+}
+
+impl Render for DeviceCodeFlow {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = use_theme();
+        let container = div()
+            .flex()
+            .flex_col()
+            .items_center()
+            .gap(px(12.0))
+            .py(px(8.0));
+
+        match &self.status {
+            DeviceFlowStatus::Requesting => container.child(
+                Spinner::new()
+                    .size(SpinnerSize::Sm)
+                    .label("Requesting a sign-in code…"),
+            ),
+            DeviceFlowStatus::Pending(code) => {
+                let remaining = code.remaining();
+                container
+                    .child(
+                        div()
+                            .text_size(px(13.0))
+                            .text_color(theme.tokens.muted_foreground)
+                            .child(format!(
+                                "Go to {} and enter this code:",
+                                code.verification_uri
+                            )),
+                    )
+                    .child(
+                        div()
+                            .px(px(20.0))
+                            .py(px(10.0))
+                            .rounded(theme.tokens.radius_md)
+                            .border_1()
+                            .border_color(theme.tokens.border)
+                            .bg(theme.tokens.muted)
+                            .font_family(theme.tokens.font_mono.clone())
+                            .text_size(px(28.0))
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .text_color(theme.tokens.foreground)
+                            .child(code.user_code.clone()),
+                    )
+                    .child(
+                        div()
+                            .flex()
+                            .gap(px(8.0))
+                            .child(
+                                Button::new("device-code-copy", "Copy Code")
+                                    .variant(ButtonVariant::Outline)
+                                    .size(ButtonSize::Sm)
+                                    .icon("copy")
+                                    .on_click(cx.listener(|this, _, _, cx| this.copy_code(cx))),
+                            )
+                            .child(
+                                Button::new("device-code-open", "Open Browser")
+                                    .variant(ButtonVariant::Default)
+                                    .size(ButtonSize::Sm)
+                                    .icon("external-link")
+                                    .on_click(cx.listener(|this, _, _, cx| {
+                                        this.open_verification_page(cx)
+                                    })),
+                            ),
+                    )
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap(px(6.0))
+                            .child(Spinner::new().size(SpinnerSize::Xs))
+                            .child(
+                                caption(format!(
+                                    "Waiting for authorization · code expires in {}",
+                                    format_countdown(remaining)
+                                ))
+                                .color(theme.tokens.muted_foreground),
+                            ),
+                    )
+            }
+            DeviceFlowStatus::Authorized => container
+                .child(Icon::new("circle-check").size(px(28.0)).color(hsla(
+                    142.0 / 360.0,
+                    0.71,
+                    0.45,
+                    1.0,
+                )))
+                .child(
+                    div()
+                        .text_size(px(14.0))
+                        .text_color(theme.tokens.foreground)
+                        .child("You're signed in."),
+                ),
+            DeviceFlowStatus::Failed(err) => container
+                .child(caption(err.to_string()).color(theme.tokens.destructive))
+                .when(*err != AuthError::Cancelled, |this| {
+                    this.child(
+                        Button::new("device-code-retry", "Try Again")
+                            .variant(ButtonVariant::Outline)
+                            .size(ButtonSize::Sm)
+                            .on_click(cx.listener(|this, _, _, cx| this.restart(cx))),
+                    )
+                }),
+        }
+    }
+}
+
+/// A dialog around `flow`. `on_done` runs when the dialog is dismissed; close it from a
+/// [`DeviceFlowEvent`] subscription once the token arrives.
+pub fn device_code_dialog(
+    flow: &Entity<DeviceCodeFlow>,
+    on_done: impl Fn(&mut Window, &mut App) + 'static,
+    cx: &mut App,
+) -> Entity<Dialog> {
+    let on_done = Rc::new(on_done);
+
+    let cancel = on_done.clone();
+    let entity = flow.clone();
+    let footer = div().flex().justify_end().child(
+        Button::new("device-code-cancel", "Cancel")
+            .variant(ButtonVariant::Outline)
+            .on_click(move |_, window, cx| {
+                entity.update(cx, |flow, cx| flow.cancel(cx));
+                cancel(window, cx);
+            }),
+    );
+
+    let on_close = on_done.clone();
+    let entity = flow.clone();
+    let flow = flow.clone();
+    cx.new(|cx| {
+        Dialog::new(cx)
+            .title("Sign in")
+            .description("Authorize this app in your browser to finish signing in.")
+            .size(DialogSize::Md)
+            .child(flow)
+            .footer(footer)
+            .on_close(move |window, cx| {
+                entity.update(cx, |flow, cx| {
+                    if matches!(
+                        flow.status(),
+                        DeviceFlowStatus::Requesting | DeviceFlowStatus::Pending(_)
+                    ) {
+                        flow.cancel(cx);
+                    }
+                });
+                on_close(window, cx);
+            })
+    })
+}
+
+async fn poll_for_token(
+    client: &dyn HttpClient,
+    config: &OAuthConfig,
+    code: &DeviceCode,
+) -> Result<OAuthToken, AuthError> {
+    let mut interval = code.interval;
+    let mut params = config.client_params();
+    params.extend([
+        ("grant_type", DEVICE_CODE_GRANT.to_string()),
+        ("device_code", code.device_code.clone()),
+    ]);
+    loop {
+        smol::Timer::after(interval).await;
+        if Instant::now() >= code.expires_at {
+            return Err(AuthError::Expired);
+        }
+        let response = post_form(client, &config.token_url, &params).await?;
+        match response.get("error").and_then(|error| error.as_str()) {
+            Some("authorization_pending") => {}
+            Some("slow_down") => interval += SLOW_DOWN_STEP,
+            _ => return parse_token_response(&response),
+        }
+    }
+}
+
+/// POST `params` as a form and parse the JSON reply. OAuth errors come back as JSON
+/// with a 4xx status, so the body is parsed whatever the status.
+async fn post_form(
+    client: &dyn HttpClient,
+    url: &str,
+    params: &[(&str, String)],
+) -> Result<serde_json::Value, AuthError> {
+    let request = Request::builder()
+        .method("POST")
+        .uri(url)
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("Accept", "application/json")
+        .body(AsyncBody::from(form_encode(params)))
+        .map_err(network_error)?;
+    let mut response = client.send(request).await.map_err(network_error)?;
+    let status = response.status();
+    let mut text = String::new();
+    response
+        .body_mut()
+        .read_to_string(&mut text)
+        .await
+        .map_err(network_error)?;
+    serde_json::from_str(&text).map_err(|_| {
+        if status.is_success() {
+            AuthError::InvalidResponse(format!("{} didn't return JSON", url).into())
+        } else {
+            network_error(format!("{} returned {}", url, status))
+        }
+    })
+}
+
+fn parse_token_response(response: &serde_json::Value) -> Result<OAuthToken, AuthError> {
+    let text = |key: &str| {
+        response
+            .get(key)
+            .and_then(|value| value.as_str())
+            .map(|value| SharedString::from(value.to_string()))
+    };
+    if let Some(error) = response.get("error").and_then(|error| error.as_str()) {
+        return Err(token_error(
+            error,
+            response
+                .get("error_description")
+                .and_then(|description| description.as_str()),
+        ));
+    }
+    let access_token = text("access_token")
+        .ok_or_else(|| AuthError::InvalidResponse("no access_token in response".into()))?;
+    Ok(OAuthToken {
+        access_token,
+        token_type: text("token_type").unwrap_or_else(|| "Bearer".into()),
+        refresh_token: text("refresh_token"),
+        expires_in: response
+            .get("expires_in")
+            .and_then(|value| value.as_u64())
+            .map(Duration::from_secs),
+        scope: text("scope"),
+        id_token: text("id_token"),
+    })
+}
+
+fn parse_device_code(response: &serde_json::Value, now: Instant) -> Result<DeviceCode, AuthError> {
+    if let Some(error) = response.get("error").and_then(|error| error.as_str()) {
+        return Err(token_error(
+            error,
+            response
+                .get("error_description")
+                .and_then(|description| description.as_str()),
+        ));
+    }
+    let text = |key: &str| response.get(key).and_then(|value| value.as_str());
+    let missing = |key: &str| AuthError::InvalidResponse(format!("no {} in response", key).into());
+    let seconds = |key: &str| response.get(key).and_then(|value| value.as_u64());
+
+    Ok(DeviceCode {
+        device_code: text("device_code")
+            .ok_or_else(|| missing("device_code"))?
+            .to_string(),
+        user_code: text("user_code")
+            .ok_or_else(|| missing("user_code"))?
+            .to_string()
+            .into(),
+        // Some providers predate the RFC and say "url".
+        verification_uri: text("verification_uri")
+            .or_else(|| text("verification_url"))
+            .ok_or_else(|| missing("verification_uri"))?
+            .to_string()
+            .into(),
+        verification_uri_complete: text("verification_uri_complete")
+            .map(|uri| uri.to_string().into()),
+        expires_at: now
+            + Duration::from_secs(seconds("expires_in").ok_or_else(|| missing("expires_in"))?),
+        interval: seconds("interval")
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_POLL_INTERVAL),
+    })
+}
+
+fn token_error(error: &str, description: Option<&str>) -> AuthError {
+    let message = SharedString::from(description.unwrap_or(error).to_string());
+    match error {
+        "access_denied" => AuthError::Denied(message),
+        "expired_token" => AuthError::Expired,
+        _ => AuthError::Rejected(message),
+    }
+}
+
+fn authorization_url(
+    config: &OAuthConfig,
+    redirect_uri: &str,
+    state: &str,
+    challenge: &str,
+) -> String {
+    let mut params = vec![
+        ("response_type", "code".to_string()),
+        ("client_id", config.client_id.to_string()),
+        ("redirect_uri", redirect_uri.to_string()),
+        ("state", state.to_string()),
+        ("code_challenge", challenge.to_string()),
+        ("code_challenge_method", "S256".to_string()),
+    ];
+    if !config.scopes.is_empty() {
+        params.push(("scope", config.scope_param()));
+    }
+    let separator = if config.authorization_url.contains('?') {
+        '&'
+    } else {
+        '?'
+    };
+    format!(
+        "{}{}{}",
+        config.authorization_url,
+        separator,
+        form_encode(&params)
+    )
+}
+
+/// 32 random bytes, base64url-encoded: a PKCE verifier or a `state` value.
+fn random_token() -> Result<String, AuthError> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).map_err(io_error)?;
+    Ok(base64_url(&bytes))
+}
+
+fn pkce_challenge(verifier: &str) -> String {
+    base64_url(&Sha256::digest(verifier.as_bytes()))
+}
+
+/// Unpadded base64url, as PKCE requires.
+fn base64_url(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    let mut out = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for group in bytes.chunks(3) {
+        let value = group.iter().enumerate().fold(0u32, |value, (i, &byte)| {
+            value | ((byte as u32) << (16 - 8 * i))
+        });
+        for i in 0..=group.len() {
+            out.push(ALPHABET[((value >> (18 - 6 * i)) & 0x3F) as usize] as char);
+        }
+    }
+    out
+}
+
+fn form_encode(params: &[(&str, String)]) -> String {
+    params
+        .iter()
+        .map(|(key, value)| format!("{}={}", percent_encode(key), percent_encode(value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn percent_encode(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+fn format_countdown(remaining: Duration) -> String {
+    let seconds = remaining.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_pkce_challenge() {
+        // RFC 7636, appendix B.
+        assert_eq!(
+            pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+        assert_eq!(base64_url(b"f"), "Zg");
+        assert_eq!(base64_url(b"fo"), "Zm8");
+        assert_eq!(base64_url(b"foo"), "Zm9v");
+        assert_eq!(base64_url(&[0xFB, 0xFF]), "-_8");
+    }
+
+    #[test]
+    fn parses_redirects() {
+        assert_eq!(
+            parse_callback("/callback?code=a%2Fb&state=xyz", "xyz"),
+            Some(Ok("a/b".to_string()))
+        );
+        assert_eq!(parse_callback("/favicon.ico", "xyz"), None);
+        assert!(matches!(
+            parse_callback("/callback?code=abc&state=other", "xyz"),
+            Some(Err(AuthError::InvalidResponse(_)))
+        ));
+        assert_eq!(
+            parse_callback(
+                "/callback?error=access_denied&error_description=User+said+no&state=xyz",
+                "xyz"
+            ),
+            Some(Err(AuthError::Denied("User said no".into())))
+        );
+    }
+
+    #[test]
+    fn builds_authorization_url() {
+        let config = OAuthConfig::new("app", "https://example.com/authorize", "unused")
+            .scopes(["read", "write"]);
+        assert_eq!(
+            authorization_url(&config, "http://127.0.0.1:8000/callback", "s", "c"),
+            "https://example.com/authorize?response_type=code&client_id=app\
+             &redirect_uri=http%3A%2F%2F127.0.0.1%3A8000%2Fcallback&state=s\
+             &code_challenge=c&code_challenge_method=S256&scope=read%20write"
+        );
+    }
+
+    #[test]
+    fn parses_token_responses() {
+        let token = parse_token_response(&serde_json::json!({
+            "access_token": "at",
+            "token_type": "bearer",
+            "expires_in": 3600,
+            "refresh_token": "rt"
+        }))
+        .unwrap();
+        assert_eq!(token.access_token, "at");
+        assert_eq!(token.refresh_token.as_deref(), Some("rt"));
+        assert_eq!(token.expires_in, Some(Duration::from_secs(3600)));
+        assert!(!format!("{:?}", token).contains("at\""));
+
+        assert_eq!(
+            parse_token_response(&serde_json::json!({ "error": "expired_token" })),
+            Err(AuthError::Expired)
+        );
+
+        let now = Instant::now();
+        let code = parse_device_code(
+            &serde_json::json!({
+                "device_code": "dc",
+                "user_code": "WDJB-MJHT",
+                "verification_url": "https://example.com/device",
+                "expires_in": 900
+            }),
+            now,
+        )
+        .unwrap();
+        assert_eq!(code.user_code, "WDJB-MJHT");
+        assert_eq!(code.verification_uri, "https://example.com/device");
+        assert_eq!(code.interval, DEFAULT_POLL_INTERVAL);
+        assert_eq!(code.expires_at, now + Duration::from_secs(900));
+    }
+}
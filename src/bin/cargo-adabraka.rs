@@ -0,0 +1,470 @@
+//! `cargo adabraka` — project scaffolding for adabraka-ui applications.
+//!
+//! Generates a ready-to-run GPUI app with `adabraka_ui::init`, theme installation,
+//! an asset source and a main window. Optional modules add a code editor, an output
+//! panel, or a resizable sidebar layout.
+//!
+//! ```text
+//! cargo install adabraka-ui
+//! cargo adabraka new my-app --with editor,layout --theme light
+//! ```
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Scaffold a new adabraka-ui application
+
+USAGE:
+    cargo adabraka new <NAME> [OPTIONS]
+
+OPTIONS:
+    --with <MODULES>   Comma-separated optional modules: editor, output, layout
+    --theme <THEME>    Initial theme: dark (default) or light
+    --path <DIR>       Directory to create the project in (defaults to ./<NAME>)
+    -h, --help         Print this help
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Module {
+    Editor,
+    Output,
+    Layout,
+}
+
+impl Module {
+    fn parse(name: &str) -> Result<Self, String> {
+        match name.trim() {
+            "editor" => Ok(Module::Editor),
+            "output" => Ok(Module::Output),
+            "layout" | "dock" => Ok(Module::Layout),
+            "terminal" => Err(
+                "the `terminal` module isn't available yet; use `output` for a read-only log panel"
+                    .to_string(),
+            ),
+            other => Err(format!(
+                "unknown module `{other}` (expected editor, output, layout)"
+            )),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Options {
+    name: String,
+    path: PathBuf,
+    modules: Vec<Module>,
+    light_theme: bool,
+}
+
+impl Options {
+    fn has(&self, module: Module) -> bool {
+        self.modules.contains(&module)
+    }
+}
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    // Invoked as `cargo adabraka ...`, cargo passes the subcommand name first.
+    if args.first().map(String::as_str) == Some("adabraka") {
+        args.remove(0);
+    }
+
+    let wants_help = args.iter().any(|arg| arg == "-h" || arg == "--help");
+    let result = match args.first().map(String::as_str) {
+        Some("new") if !wants_help => {
+            parse_new(&args[1..]).and_then(|opts| generate(&opts).map(|_| opts))
+        }
+        Some("new") | Some("-h") | Some("--help") | None => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Some(other) => Err(format!("unknown command `{other}`\n\n{USAGE}")),
+    };
+
+    match result {
+        Ok(opts) => {
+            println!(
+                "Created adabraka-ui app `{}` at {}",
+                opts.name,
+                opts.path.display()
+            );
+            println!("\n    cd {}\n    cargo run\n", opts.path.display());
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_new(args: &[String]) -> Result<Options, String> {
+    let mut name = None;
+    let mut path = None;
+    let mut modules = Vec::new();
+    let mut light_theme = false;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--with" => {
+                let value = iter.next().ok_or("`--with` expects a module list")?;
+                for module in value.split(',').filter(|m| !m.trim().is_empty()) {
+                    let module = Module::parse(module)?;
+                    if !modules.contains(&module) {
+                        modules.push(module);
+                    }
+                }
+            }
+            "--theme" => match iter.next().map(String::as_str) {
+                Some("dark") => light_theme = false,
+                Some("light") => light_theme = true,
+                _ => return Err("`--theme` expects `dark` or `light`".to_string()),
+            },
+            "--path" => {
+                path = Some(PathBuf::from(
+                    iter.next().ok_or("`--path` expects a directory")?,
+                ));
+            }
+            other if other.starts_with('-') => return Err(format!("unknown option `{other}`")),
+            other => {
+                if name.replace(other.to_string()).is_some() {
+                    return Err("only one project name may be given".to_string());
+                }
+            }
+        }
+    }
+
+    let name = name.ok_or("missing project name")?;
+    validate_name(&name)?;
+    let path = path.unwrap_or_else(|| PathBuf::from(&name));
+
+    Ok(Options {
+        name,
+        path,
+        modules,
+        light_theme,
+    })
+}
+
+fn validate_name(name: &str) -> Result<(), String> {
+    let valid_chars = name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    let starts_ok = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic());
+    if valid_chars && starts_ok {
+        Ok(())
+    } else {
+        Err(format!(
+            "invalid project name `{name}`: use letters, digits, `-` or `_`, starting with a letter"
+        ))
+    }
+}
+
+fn generate(opts: &Options) -> Result<(), String> {
+    if opts.path.exists()
+        && fs::read_dir(&opts.path)
+            .map(|mut d| d.next().is_some())
+            .unwrap_or(true)
+    {
+        return Err(format!(
+            "destination `{}` already exists and is not empty",
+            opts.path.display()
+        ));
+    }
+
+    write_file(&opts.path.join("Cargo.toml"), &cargo_toml(opts))?;
+    write_file(&opts.path.join("src/main.rs"), &main_rs(opts))?;
+    write_file(&opts.path.join("assets/icons/.gitkeep"), "")?;
+    write_file(&opts.path.join(".gitignore"), "/target\n")?;
+    Ok(())
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("failed to create {}: {e}", parent.display()))?;
+    }
+    fs::write(path, contents).map_err(|e| format!("failed to write {}: {e}", path.display()))
+}
+
+fn cargo_toml(opts: &Options) -> String {
+    let features = if opts.has(Module::Editor) {
+        ", features = [\"editor-languages\"]"
+    } else {
+        ""
+    };
+    format!(
+        r#"[package]
+name = "{name}"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+adabraka-ui = {{ version = "{version}"{features} }}
+gpui = {{ package = "adabraka-gpui", version = "0.5" }}
+"#,
+        name = opts.name,
+        version = env!("CARGO_PKG_VERSION"),
+    )
+}
+
+fn title_case(name: &str) -> String {
+    name.split(['-', '_'])
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn main_rs(opts: &Options) -> String {
+    let title = title_case(&opts.name);
+    let theme = if opts.light_theme { "light" } else { "dark" };
+    let mut out = String::new();
+
+    if opts.has(Module::Layout) {
+        out.push_str("use adabraka_ui::components::resizable::{h_resizable, resizable_panel};\n");
+    }
+    out.push_str("use adabraka_ui::prelude::*;\nuse gpui::*;\nuse std::path::PathBuf;\n\n");
+    out.push_str(ASSETS_SNIPPET);
+
+    let _ = write!(
+        out,
+        r#"
+fn main() {{
+    Application::new()
+        .with_assets(Assets {{
+            base: PathBuf::from(env!("CARGO_MANIFEST_DIR")),
+        }})
+        .run(|cx| {{
+            adabraka_ui::init(cx);
+            adabraka_ui::set_icon_base_path("assets/icons");
+            install_theme(cx, Theme::{theme}());
+
+            cx.open_window(
+                WindowOptions {{
+                    titlebar: Some(TitlebarOptions {{
+                        title: Some("{title}".into()),
+                        ..Default::default()
+                    }}),
+                    window_bounds: Some(WindowBounds::Windowed(Bounds {{
+                        origin: Point::default(),
+                        size: size(px(1100.0), px(720.0)),
+                    }})),
+                    ..Default::default()
+                }},
+                |window, cx| cx.new(|cx| AppView::new(window, cx)),
+            )
+            .unwrap();
+        }});
+}}
+"#
+    );
+
+    // View struct
+    out.push_str("\nstruct AppView {\n");
+    if opts.has(Module::Editor) {
+        out.push_str("    editor: Entity<EditorState>,\n");
+    }
+    if opts.has(Module::Output) {
+        out.push_str("    output: Entity<OutputPanel>,\n");
+    }
+    if opts.has(Module::Layout) {
+        out.push_str("    layout: Entity<ResizableState>,\n");
+    }
+    out.push_str("}\n\nimpl AppView {\n");
+    out.push_str("    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {\n");
+    if !opts.has(Module::Output) {
+        out.push_str("        let _ = window;\n");
+    }
+    if opts.has(Module::Editor) {
+        out.push_str(
+            r#"        let editor = cx.new(|cx| {
+            let mut state = EditorState::new(cx);
+            state.set_language(EditorLanguage::Rust);
+            state.set_content("fn main() {\n    println!(\"Hello from adabraka-ui\");\n}\n", cx);
+            state
+        });
+"#,
+        );
+    }
+    if opts.has(Module::Output) {
+        out.push_str(
+            r#"        let channels = cx.new(OutputChannels::new);
+        let log = channels.update(cx, |channels, cx| channels.channel("App", cx));
+        log.append_line("Application started");
+        let output = cx.new(|cx| OutputPanel::new(channels, window, cx));
+"#,
+        );
+    }
+    if opts.has(Module::Layout) {
+        out.push_str("        let layout = ResizableState::new(cx);\n");
+    }
+    out.push_str("        Self {");
+    let fields: Vec<&str> = [
+        (Module::Editor, "editor"),
+        (Module::Output, "output"),
+        (Module::Layout, "layout"),
+    ]
+    .iter()
+    .filter(|(m, _)| opts.has(*m))
+    .map(|(_, f)| *f)
+    .collect();
+    if fields.is_empty() {
+        out.push_str("}\n");
+    } else {
+        let _ = writeln!(out, " {} }}", fields.join(", "));
+    }
+    out.push_str("    }\n}\n");
+
+    // Render
+    out.push_str(
+        r#"
+impl Render for AppView {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = use_theme();
+
+"#,
+    );
+    out.push_str("        let content = div()\n            .flex()\n            .flex_col()\n            .size_full()\n");
+    if opts.has(Module::Editor) {
+        out.push_str("            .child(div().flex_1().child(Editor::new(&self.editor)))");
+    } else {
+        let _ = write!(
+            out,
+            r#"            .items_center()
+            .justify_center()
+            .gap_4()
+            .child(h1("{title}"))
+            .child(muted("Edit src/main.rs to get started."))"#
+        );
+    }
+    if opts.has(Module::Output) {
+        out.push_str(
+            "\n            .child(\n                div()\n                    .h(px(180.0))\n                    .border_t_1()\n                    .border_color(theme.tokens.border)\n                    .child(self.output.clone()),\n            )",
+        );
+    }
+    out.push_str(";\n\n");
+
+    if opts.has(Module::Layout) {
+        out.push_str(
+            r#"        let sidebar = div()
+            .size_full()
+            .p_4()
+            .bg(theme.tokens.card)
+            .child(label_small("Sidebar"));
+
+        div()
+            .size_full()
+            .bg(theme.tokens.background)
+            .text_color(theme.tokens.foreground)
+            .child(
+                h_resizable("main-layout", self.layout.clone())
+                    .child(
+                        resizable_panel()
+                            .size(px(240.0))
+                            .size_range(px(160.0)..px(480.0))
+                            .child(sidebar),
+                    )
+                    .child(resizable_panel().child(content)),
+            )
+    }
+}
+"#,
+        );
+    } else {
+        out.push_str(
+            r#"        div()
+            .size_full()
+            .bg(theme.tokens.background)
+            .text_color(theme.tokens.foreground)
+            .child(content)
+    }
+}
+"#,
+        );
+    }
+
+    out
+}
+
+const ASSETS_SNIPPET: &str = r#"struct Assets {
+    base: PathBuf,
+}
+
+impl gpui::AssetSource for Assets {
+    fn load(&self, path: &str) -> Result<Option<std::borrow::Cow<'static, [u8]>>> {
+        std::fs::read(self.base.join(path))
+            .map(|data| Some(std::borrow::Cow::Owned(data)))
+            .map_err(|err| err.into())
+    }
+
+    fn list(&self, path: &str) -> Result<Vec<SharedString>> {
+        std::fs::read_dir(self.base.join(path))
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| {
+                        entry
+                            .ok()
+                            .and_then(|entry| entry.file_name().into_string().ok())
+                            .map(SharedString::from)
+                    })
+                    .collect()
+            })
+            .map_err(|err| err.into())
+    }
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_modules_and_theme() {
+        let opts = parse_new(&args(&[
+            "demo",
+            "--with",
+            "editor,layout,editor",
+            "--theme",
+            "light",
+        ]))
+        .unwrap();
+        assert_eq!(opts.name, "demo");
+        assert_eq!(opts.modules, vec![Module::Editor, Module::Layout]);
+        assert!(opts.light_theme);
+        assert_eq!(opts.path, PathBuf::from("demo"));
+    }
+
+    #[test]
+    fn test_rejects_invalid_names_and_modules() {
+        assert!(parse_new(&args(&["1app"])).is_err());
+        assert!(parse_new(&args(&["my app"])).is_err());
+        assert!(parse_new(&args(&["app", "--with", "terminal"])).is_err());
+        assert!(parse_new(&args(&[])).is_err());
+    }
+
+    #[test]
+    fn test_main_rs_includes_selected_modules() {
+        let opts = parse_new(&args(&["my-app", "--with", "editor,output"])).unwrap();
+        let src = main_rs(&opts);
+        assert!(src.contains("adabraka_ui::init(cx);"));
+        assert!(src.contains("Editor::new(&self.editor)"));
+        assert!(src.contains("OutputPanel::new"));
+        assert!(!src.contains("h_resizable"));
+        assert!(src.contains("\"My App\""));
+    }
+}
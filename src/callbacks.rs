@@ -0,0 +1,162 @@
+//! Callbacks that hold their entity weakly, and a leak check for entities that
+//! outlive their owner.
+//!
+//! A handler stored somewhere long-lived, such as another entity's field, a global or
+//! a detached subscription, keeps every entity it captures alive. When the entity in
+//! turn holds the handler, neither is ever released. The helpers here capture a
+//! [`WeakEntity`] instead and do nothing once the entity is gone:
+//!
+//! ```rust,ignore
+//! editor.update(cx, |editor, _| {
+//!     editor.set_overlay_active_check(callbacks::weak_reader(&palette, false, |palette, _| {
+//!         palette.is_open()
+//!     }));
+//! });
+//!
+//! Command::new("files.refresh", "Refresh Files")
+//!     .on_select(callbacks::weak_handler(&file_tree, |tree, _window, cx| tree.refresh(cx)));
+//! ```
+//!
+//! To find the leaks that remain, tell the detector when an entity should be gone,
+//! e.g. when its tab closes, with [`expect_released`]. [`leaked_entities`] lists the
+//! ones still alive a moment later: nothing the app owns refers to them any more, so
+//! what keeps them alive is almost always a callback that captured them.
+//!
+//! ```rust,ignore
+//! callbacks::expect_released(&closed_tab, "closed tab", cx);
+//! // later, e.g. from a debug menu:
+//! callbacks::report_leaks(cx);
+//! ```
+
+use gpui::*;
+use std::time::{Duration, Instant};
+
+/// How long an entity gets to be released before it counts as leaked. Entities are
+/// released at the end of the effect cycle they're dropped in, so any later check
+/// would do; this leaves room for a closing animation.
+const RELEASE_GRACE: Duration = Duration::from_secs(2);
+
+/// A handler for `on_click` and similar listeners that updates `entity`, if it's still
+/// alive.
+pub fn weak_listener<T: 'static, E: ?Sized>(
+    entity: &Entity<T>,
+    f: impl Fn(&mut T, &E, &mut Window, &mut Context<T>) + 'static,
+) -> impl Fn(&E, &mut Window, &mut App) + 'static {
+    let entity = entity.downgrade();
+    move |event: &E, window: &mut Window, cx: &mut App| {
+        let _ = entity.update(cx, |this, cx| f(this, event, window, cx));
+    }
+}
+
+/// A handler without an event, such as `on_select` or `on_close`, that updates
+/// `entity` if it's still alive.
+pub fn weak_handler<T: 'static>(
+    entity: &Entity<T>,
+    f: impl Fn(&mut T, &mut Window, &mut Context<T>) + 'static,
+) -> impl Fn(&mut Window, &mut App) + 'static {
+    let entity = entity.downgrade();
+    move |window: &mut Window, cx: &mut App| {
+        let _ = entity.update(cx, |this, cx| f(this, window, cx));
+    }
+}
+
+/// A handler taking a value, such as a picked date or color, that updates `entity` if
+/// it's still alive.
+pub fn weak_value_handler<T: 'static, V>(
+    entity: &Entity<T>,
+    f: impl Fn(&mut T, V, &mut Window, &mut Context<T>) + 'static,
+) -> impl Fn(V, &mut Window, &mut App) + 'static {
+    let entity = entity.downgrade();
+    move |value: V, window: &mut Window, cx: &mut App| {
+        let _ = entity.update(cx, |this, cx| f(this, value, window, cx));
+    }
+}
+
+/// A check that reads `entity`, answering `default` once it's released.
+pub fn weak_reader<T: 'static, R: Clone + 'static>(
+    entity: &Entity<T>,
+    default: R,
+    f: impl Fn(&T, &App) -> R + 'static,
+) -> impl Fn(&App) -> R + 'static {
+    let entity = entity.downgrade();
+    move |cx: &App| match entity.upgrade() {
+        Some(entity) => f(entity.read(cx), cx),
+        None => default.clone(),
+    }
+}
+
+struct Expected {
+    id: EntityId,
+    type_name: &'static str,
+    label: SharedString,
+    since: Instant,
+    is_alive: Box<dyn Fn() -> bool>,
+}
+
+#[derive(Default)]
+struct LeakDetector {
+    expected: Vec<Expected>,
+}
+
+impl Global for LeakDetector {}
+
+/// An entity still alive after [`expect_released`] was called for it.
+#[derive(Clone, Debug)]
+pub struct LeakedEntity {
+    pub id: EntityId,
+    /// The entity's Rust type.
+    pub type_name: &'static str,
+    pub label: SharedString,
+    /// How long ago it should have been released.
+    pub overdue: Duration,
+}
+
+/// Note that `entity` should be released soon, because its owner has dropped it or
+/// is about to. Keeps only a weak reference.
+pub fn expect_released<T: 'static>(
+    entity: &Entity<T>,
+    label: impl Into<SharedString>,
+    cx: &mut App,
+) {
+    let weak = entity.downgrade();
+    cx.default_global::<LeakDetector>().expected.push(Expected {
+        id: entity.entity_id(),
+        type_name: std::any::type_name::<T>(),
+        label: label.into(),
+        since: Instant::now(),
+        is_alive: Box::new(move || weak.upgrade().is_some()),
+    });
+}
+
+/// Entities passed to [`expect_released`] that are still alive. Forgets the ones
+/// released since the last call.
+pub fn leaked_entities(cx: &mut App) -> Vec<LeakedEntity> {
+    let detector = cx.default_global::<LeakDetector>();
+    detector.expected.retain(|expected| (expected.is_alive)());
+    detector
+        .expected
+        .iter()
+        .filter(|expected| expected.since.elapsed() >= RELEASE_GRACE)
+        .map(|expected| LeakedEntity {
+            id: expected.id,
+            type_name: expected.type_name,
+            label: expected.label.clone(),
+            overdue: expected.since.elapsed(),
+        })
+        .collect()
+}
+
+/// Log a warning for each of [`leaked_entities`] and return how many there were.
+pub fn report_leaks(cx: &mut App) -> usize {
+    let leaked = leaked_entities(cx);
+    for entity in &leaked {
+        tracing::warn!(
+            id = ?entity.id,
+            type_name = entity.type_name,
+            label = %entity.label,
+            overdue_ms = entity.overdue.as_millis() as u64,
+            "entity still alive after its owner released it; a callback probably captures it"
+        );
+    }
+    leaked.len()
+}
@@ -1,19 +1,15 @@
+use super::description::{format_value, ChartDataTable, ChartDescription, Describe, KeyedSeries};
+use super::palette::{paint_area_pattern, pattern_overlay, ChartPalette, PatternFill};
 use crate::theme::use_theme;
+use crate::util::snap_stroke_center;
 use gpui::{prelude::FluentBuilder as _, *};
 
-const CHART_COLORS: [u32; 8] = [
-    0x3b82f6, 0x22c55e, 0xf59e0b, 0xef4444, 0x8b5cf6, 0x06b6d4, 0xf97316, 0xec4899,
-];
-
-fn default_color(index: usize) -> Hsla {
-    rgb(CHART_COLORS[index % CHART_COLORS.len()]).into()
-}
-
 #[derive(Clone, Debug)]
 pub struct AreaChartSeries {
     pub label: SharedString,
     pub points: Vec<(f64, f64)>,
     pub color: Option<Hsla>,
+    pub pattern: Option<PatternFill>,
 }
 
 impl AreaChartSeries {
@@ -22,6 +18,7 @@ impl AreaChartSeries {
             label: label.into(),
             points,
             color: None,
+            pattern: None,
         }
     }
 
@@ -29,6 +26,11 @@ impl AreaChartSeries {
         self.color = Some(color);
         self
     }
+
+    pub fn pattern(mut self, pattern: PatternFill) -> Self {
+        self.pattern = Some(pattern);
+        self
+    }
 }
 
 #[derive(Copy, Clone, Default, PartialEq, Eq)]
@@ -144,6 +146,20 @@ struct PaintData {
     grid_color: Hsla,
     _text_color: Hsla,
     fill_opacity: f32,
+    palette: ChartPalette,
+    pattern_fills: bool,
+}
+
+impl PaintData {
+    fn series_color(&self, index: usize) -> Hsla {
+        self.series[index]
+            .color
+            .unwrap_or_else(|| self.palette.color(index))
+    }
+
+    fn series_pattern(&self, index: usize) -> PatternFill {
+        PatternFill::resolve(self.series[index].pattern, index, self.pattern_fills)
+    }
 }
 
 #[derive(IntoElement)]
@@ -158,6 +174,8 @@ pub struct AreaChart {
     x_labels: Vec<SharedString>,
     y_label_count: usize,
     fill_opacity: f32,
+    palette: ChartPalette,
+    pattern_fills: bool,
     style: StyleRefinement,
 }
 
@@ -174,6 +192,8 @@ impl AreaChart {
             x_labels: Vec::new(),
             y_label_count: 5,
             fill_opacity: 0.25,
+            palette: ChartPalette::default(),
+            pattern_fills: false,
             style: StyleRefinement::default(),
         }
     }
@@ -237,6 +257,18 @@ impl AreaChart {
         self.fill_opacity = opacity.clamp(0.0, 1.0);
         self
     }
+
+    pub fn palette(mut self, palette: ChartPalette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Texture each series' area with a different [`PatternFill`] so series can be told
+    /// apart without color. Series with their own pattern keep it.
+    pub fn pattern_fills(mut self, enabled: bool) -> Self {
+        self.pattern_fills = enabled;
+        self
+    }
 }
 
 impl Styled for AreaChart {
@@ -257,6 +289,7 @@ fn format_value(value: f64) -> String {
 
 impl RenderOnce for AreaChart {
     fn render(self, _window: &mut Window, _cx: &mut App) -> impl IntoElement {
+        let _span = tracing::trace_span!("chart.area", series = self.series.len()).entered();
         let theme = use_theme();
         let user_style = self.style;
         let (chart_w, chart_h) = self.size.dimensions();
@@ -267,6 +300,9 @@ impl RenderOnce for AreaChart {
         let padding_bottom: f32 = if self.show_x_axis { 40.0 } else { 10.0 };
 
         let series_for_legend = self.series.clone();
+        let legend_palette = self.palette.clone();
+        let pattern_fills = self.pattern_fills;
+        let pattern_color = theme.tokens.background.opacity(0.7);
         let show_legend = self.show_legend && self.series.len() > 1;
 
         let range = AreaChartRange::from_series(&self.series, self.mode);
@@ -295,6 +331,8 @@ impl RenderOnce for AreaChart {
             grid_color: theme.tokens.border,
             _text_color: text_color,
             fill_opacity: self.fill_opacity,
+            palette: self.palette,
+            pattern_fills: self.pattern_fills,
         };
 
         div()
@@ -337,6 +375,8 @@ impl RenderOnce for AreaChart {
                                     for i in 0..=grid_lines {
                                         let y = chart_top
                                             + chart_height * (i as f32 / grid_lines as f32);
+                                        let y =
+                                            snap_stroke_center(y, px(1.0), window.scale_factor());
                                         let mut builder = PathBuilder::stroke(px(1.0));
                                         builder.move_to(point(chart_left, y));
                                         builder.line_to(point(chart_right, y));
@@ -348,6 +388,8 @@ impl RenderOnce for AreaChart {
                                     for i in 0..=grid_lines {
                                         let x = chart_left
                                             + chart_width * (i as f32 / grid_lines as f32);
+                                        let x =
+                                            snap_stroke_center(x, px(1.0), window.scale_factor());
                                         let mut builder = PathBuilder::stroke(px(1.0));
                                         builder.move_to(point(x, chart_top));
                                         builder.line_to(point(x, chart_bottom));
@@ -363,8 +405,7 @@ impl RenderOnce for AreaChart {
                                             if s.points.len() < 2 {
                                                 continue;
                                             }
-                                            let color =
-                                                s.color.unwrap_or_else(|| default_color(idx));
+                                            let color = data.series_color(idx);
 
                                             let screen_pts: Vec<Point<Pixels>> = s
                                                 .points
@@ -396,6 +437,20 @@ impl RenderOnce for AreaChart {
                                                     color.opacity(data.fill_opacity),
                                                 );
                                             }
+                                            let baseline = [
+                                                point(screen_pts[0].x, chart_bottom),
+                                                point(
+                                                    screen_pts[screen_pts.len() - 1].x,
+                                                    chart_bottom,
+                                                ),
+                                            ];
+                                            paint_area_pattern(
+                                                data.series_pattern(idx),
+                                                &screen_pts,
+                                                &baseline,
+                                                color.opacity(0.7),
+                                                window,
+                                            );
 
                                             let mut stroke_builder = PathBuilder::stroke(px(2.0));
                                             stroke_builder.move_to(screen_pts[0]);
@@ -441,8 +496,7 @@ impl RenderOnce for AreaChart {
                                         baselines.push(base_pts);
 
                                         for (idx, s) in data.series.iter().enumerate() {
-                                            let color =
-                                                s.color.unwrap_or_else(|| default_color(idx));
+                                            let color = data.series_color(idx);
 
                                             for (i, pt) in s.points.iter().enumerate() {
                                                 if i < max_len {
@@ -479,6 +533,13 @@ impl RenderOnce for AreaChart {
                                                     color.opacity(data.fill_opacity),
                                                 );
                                             }
+                                            paint_area_pattern(
+                                                data.series_pattern(idx),
+                                                &top_pts,
+                                                bottom_pts,
+                                                color.opacity(0.7),
+                                                window,
+                                            );
 
                                             let mut stroke_builder = PathBuilder::stroke(px(2.0));
                                             stroke_builder.move_to(top_pts[0]);
@@ -543,12 +604,21 @@ impl RenderOnce for AreaChart {
                         .px(px(padding_left))
                         .py(px(8.0))
                         .children(series_for_legend.iter().enumerate().map(|(i, s)| {
-                            let color = s.color.unwrap_or_else(|| default_color(i));
+                            let color = s.color.unwrap_or_else(|| legend_palette.color(i));
+                            let pattern = PatternFill::resolve(s.pattern, i, pattern_fills);
                             div()
                                 .flex()
                                 .items_center()
                                 .gap(px(6.0))
-                                .child(div().size(px(12.0)).rounded(px(2.0)).bg(color))
+                                .child(
+                                    div()
+                                        .relative()
+                                        .overflow_hidden()
+                                        .size(px(12.0))
+                                        .rounded(px(2.0))
+                                        .bg(color)
+                                        .child(pattern_overlay(pattern, pattern_color)),
+                                )
                                 .child(
                                     div()
                                         .text_xs()
@@ -560,3 +630,30 @@ impl RenderOnce for AreaChart {
             })
     }
 }
+
+impl Describe for AreaChart {
+    fn describe(&self) -> ChartDescription {
+        let series: Vec<KeyedSeries> = self
+            .series
+            .iter()
+            .map(|series| {
+                KeyedSeries::new(
+                    series.label.clone(),
+                    series
+                        .points
+                        .iter()
+                        .map(|&(x, y)| (x, format_value(x).into(), y))
+                        .collect(),
+                )
+            })
+            .collect();
+        let kind = match self.mode {
+            AreaChartMode::Stacked => "Stacked area chart",
+            _ => "Area chart",
+        };
+        series.iter().fold(
+            ChartDescription::new(kind, ChartDataTable::from_series("X", &series)),
+            |description, series| description.series(series.summary()),
+        )
+    }
+}
@@ -1,14 +1,8 @@
+use super::description::{ChartDataTable, ChartDescription, Describe, KeyedSeries};
+use super::palette::{pattern_overlay, ChartPalette, PatternFill};
 use crate::theme::use_theme;
 use gpui::{prelude::FluentBuilder as _, *};
 
-const CHART_COLORS: [u32; 8] = [
-    0x3b82f6, 0x22c55e, 0xf59e0b, 0xef4444, 0x8b5cf6, 0x06b6d4, 0xf97316, 0xec4899,
-];
-
-fn get_chart_color(index: usize) -> Hsla {
-    rgb(CHART_COLORS[index % CHART_COLORS.len()]).into()
-}
-
 fn pixels_to_f32(p: Pixels) -> f32 {
     p / px(1.0)
 }
@@ -40,6 +34,7 @@ pub struct BarChartSeries {
     pub name: SharedString,
     pub data: Vec<f64>,
     pub color: Option<Hsla>,
+    pub pattern: Option<PatternFill>,
 }
 
 impl BarChartSeries {
@@ -48,6 +43,7 @@ impl BarChartSeries {
             name: name.into(),
             data,
             color: None,
+            pattern: None,
         }
     }
 
@@ -55,6 +51,21 @@ impl BarChartSeries {
         self.color = Some(color);
         self
     }
+
+    pub fn pattern(mut self, pattern: PatternFill) -> Self {
+        self.pattern = Some(pattern);
+        self
+    }
+}
+
+/// Fill a bar or legend swatch with `color`, textured with `pattern`.
+fn fill_bar(bar: Div, color: Hsla, pattern: PatternFill, theme: &crate::theme::Theme) -> Div {
+    bar.bg(color).when(pattern != PatternFill::Solid, |this| {
+        this.relative().overflow_hidden().child(pattern_overlay(
+            pattern,
+            theme.tokens.background.opacity(0.6),
+        ))
+    })
 }
 
 #[derive(Copy, Clone, Default, PartialEq, Eq)]
@@ -86,6 +97,8 @@ pub struct BarChart {
     bar_width: Option<Pixels>,
     gap: Pixels,
     height: Pixels,
+    palette: ChartPalette,
+    pattern_fills: bool,
     style: StyleRefinement,
 }
 
@@ -104,6 +117,8 @@ impl BarChart {
             bar_width: None,
             gap: px(8.0),
             height: px(300.0),
+            palette: ChartPalette::default(),
+            pattern_fills: false,
             style: StyleRefinement::default(),
         }
     }
@@ -122,6 +137,8 @@ impl BarChart {
             bar_width: None,
             gap: px(8.0),
             height: px(300.0),
+            palette: ChartPalette::default(),
+            pattern_fills: false,
             style: StyleRefinement::default(),
         }
     }
@@ -191,6 +208,34 @@ impl BarChart {
         self
     }
 
+    pub fn palette(mut self, palette: ChartPalette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Texture each series (or each bar of a single-series chart) with a different
+    /// [`PatternFill`] so they can be told apart without color. Series with their own
+    /// pattern keep it.
+    pub fn pattern_fills(mut self, enabled: bool) -> Self {
+        self.pattern_fills = enabled;
+        self
+    }
+
+    fn auto_pattern(&self, index: usize) -> PatternFill {
+        if self.pattern_fills {
+            PatternFill::for_series(index)
+        } else {
+            PatternFill::Solid
+        }
+    }
+
+    fn series_pattern(&self, index: usize) -> PatternFill {
+        self.series
+            .get(index)
+            .and_then(|series| series.pattern)
+            .unwrap_or_else(|| self.auto_pattern(index))
+    }
+
     fn render_single_vertical(self, theme: &crate::theme::Theme) -> Div {
         let max_value = self
             .data
@@ -249,7 +294,8 @@ impl BarChart {
                                 } else {
                                     0.0
                                 };
-                                let bar_color = item.color.unwrap_or_else(|| get_chart_color(i));
+                                let bar_color = item.color.unwrap_or_else(|| self.palette.color(i));
+                                let pattern = self.auto_pattern(i);
                                 let value = item.value;
                                 let bar_height = chart_height * height_percent;
 
@@ -272,7 +318,7 @@ impl BarChart {
                                         div()
                                             .w(bar_width)
                                             .h(bar_height)
-                                            .bg(bar_color)
+                                            .map(|bar| fill_bar(bar, bar_color, pattern, theme))
                                             .rounded_t(theme.tokens.radius_sm),
                                     )
                             })),
@@ -315,7 +361,8 @@ impl BarChart {
                 } else {
                     0.0
                 };
-                let bar_color = item.color.unwrap_or_else(|| get_chart_color(i));
+                let bar_color = item.color.unwrap_or_else(|| self.palette.color(i));
+                let pattern = self.auto_pattern(i);
                 let value = item.value;
                 let label = item.label.clone();
 
@@ -356,7 +403,7 @@ impl BarChart {
                                 div()
                                     .h_full()
                                     .w(relative(width_percent))
-                                    .bg(bar_color)
+                                    .map(|bar| fill_bar(bar, bar_color, pattern, theme))
                                     .rounded_r(theme.tokens.radius_sm),
                             ),
                     )
@@ -445,7 +492,8 @@ impl BarChart {
                                         };
                                         let bar_color = series
                                             .color
-                                            .unwrap_or_else(|| get_chart_color(series_idx));
+                                            .unwrap_or_else(|| self.palette.color(series_idx));
+                                        let pattern = self.series_pattern(series_idx);
                                         let bar_height = chart_height * height_percent;
 
                                         div()
@@ -467,7 +515,9 @@ impl BarChart {
                                                 div()
                                                     .w(single_bar_width)
                                                     .h(bar_height)
-                                                    .bg(bar_color)
+                                                    .map(|bar| {
+                                                        fill_bar(bar, bar_color, pattern, theme)
+                                                    })
                                                     .rounded_t(theme.tokens.radius_sm),
                                             )
                                     }),
@@ -498,12 +548,17 @@ impl BarChart {
                         .gap(px(16.0))
                         .justify_center()
                         .children(series_for_legend.iter().enumerate().map(|(i, s)| {
-                            let color = s.color.unwrap_or_else(|| get_chart_color(i));
+                            let color = s.color.unwrap_or_else(|| self.palette.color(i));
                             div()
                                 .flex()
                                 .items_center()
                                 .gap(px(6.0))
-                                .child(div().size(px(12.0)).rounded(px(2.0)).bg(color))
+                                .child(fill_bar(
+                                    div().size(px(12.0)).rounded(px(2.0)),
+                                    color,
+                                    self.series_pattern(i),
+                                    theme,
+                                ))
                                 .child(
                                     div()
                                         .text_xs()
@@ -625,13 +680,15 @@ impl BarChart {
                                                         };
                                                     let bar_color =
                                                         series.color.unwrap_or_else(|| {
-                                                            get_chart_color(series_idx)
+                                                            self.palette.color(series_idx)
                                                         });
+                                                    let pattern = self.series_pattern(series_idx);
 
-                                                    div()
-                                                        .w_full()
-                                                        .h(relative(segment_percent))
-                                                        .bg(bar_color)
+                                                    div().w_full().h(relative(segment_percent)).map(
+                                                        |bar| {
+                                                            fill_bar(bar, bar_color, pattern, theme)
+                                                        },
+                                                    )
                                                 },
                                             )),
                                     )
@@ -661,12 +718,17 @@ impl BarChart {
                         .gap(px(16.0))
                         .justify_center()
                         .children(series_for_legend.iter().enumerate().map(|(i, s)| {
-                            let color = s.color.unwrap_or_else(|| get_chart_color(i));
+                            let color = s.color.unwrap_or_else(|| self.palette.color(i));
                             div()
                                 .flex()
                                 .items_center()
                                 .gap(px(6.0))
-                                .child(div().size(px(12.0)).rounded(px(2.0)).bg(color))
+                                .child(fill_bar(
+                                    div().size(px(12.0)).rounded(px(2.0)),
+                                    color,
+                                    self.series_pattern(i),
+                                    theme,
+                                ))
                                 .child(
                                     div()
                                         .text_xs()
@@ -753,7 +815,8 @@ impl BarChart {
                                             };
                                             let bar_color = series
                                                 .color
-                                                .unwrap_or_else(|| get_chart_color(series_idx));
+                                                .unwrap_or_else(|| self.palette.color(series_idx));
+                                            let pattern = self.series_pattern(series_idx);
 
                                             div()
                                                 .flex()
@@ -763,7 +826,9 @@ impl BarChart {
                                                     div()
                                                         .h(single_bar_height)
                                                         .w(relative(width_percent))
-                                                        .bg(bar_color)
+                                                        .map(|bar| {
+                                                            fill_bar(bar, bar_color, pattern, theme)
+                                                        })
                                                         .rounded_r(theme.tokens.radius_sm),
                                                 )
                                                 .when(show_values, |this| {
@@ -789,12 +854,17 @@ impl BarChart {
                         .gap(px(16.0))
                         .justify_center()
                         .children(series_for_legend.iter().enumerate().map(|(i, s)| {
-                            let color = s.color.unwrap_or_else(|| get_chart_color(i));
+                            let color = s.color.unwrap_or_else(|| self.palette.color(i));
                             div()
                                 .flex()
                                 .items_center()
                                 .gap(px(6.0))
-                                .child(div().size(px(12.0)).rounded(px(2.0)).bg(color))
+                                .child(fill_bar(
+                                    div().size(px(12.0)).rounded(px(2.0)),
+                                    color,
+                                    self.series_pattern(i),
+                                    theme,
+                                ))
                                 .child(
                                     div()
                                         .text_xs()
@@ -902,13 +972,15 @@ impl BarChart {
                                                         };
                                                     let bar_color =
                                                         series.color.unwrap_or_else(|| {
-                                                            get_chart_color(series_idx)
+                                                            self.palette.color(series_idx)
                                                         });
+                                                    let pattern = self.series_pattern(series_idx);
 
-                                                    div()
-                                                        .h_full()
-                                                        .w(relative(segment_percent))
-                                                        .bg(bar_color)
+                                                    div().h_full().w(relative(segment_percent)).map(
+                                                        |bar| {
+                                                            fill_bar(bar, bar_color, pattern, theme)
+                                                        },
+                                                    )
                                                 },
                                             )),
                                     ),
@@ -932,12 +1004,17 @@ impl BarChart {
                         .gap(px(16.0))
                         .justify_center()
                         .children(series_for_legend.iter().enumerate().map(|(i, s)| {
-                            let color = s.color.unwrap_or_else(|| get_chart_color(i));
+                            let color = s.color.unwrap_or_else(|| self.palette.color(i));
                             div()
                                 .flex()
                                 .items_center()
                                 .gap(px(6.0))
-                                .child(div().size(px(12.0)).rounded(px(2.0)).bg(color))
+                                .child(fill_bar(
+                                    div().size(px(12.0)).rounded(px(2.0)),
+                                    color,
+                                    self.series_pattern(i),
+                                    theme,
+                                ))
                                 .child(
                                     div()
                                         .text_xs()
@@ -958,6 +1035,12 @@ impl Styled for BarChart {
 
 impl RenderOnce for BarChart {
     fn render(self, _window: &mut Window, _cx: &mut App) -> impl IntoElement {
+        let _span = tracing::trace_span!(
+            "chart.bar",
+            bars = self.data.len(),
+            series = self.series.len()
+        )
+        .entered();
         let theme = use_theme();
         let user_style = self.style.clone();
 
@@ -981,3 +1064,47 @@ impl RenderOnce for BarChart {
         })
     }
 }
+
+impl Describe for BarChart {
+    fn describe(&self) -> ChartDescription {
+        let series: Vec<KeyedSeries> = if self.series.is_empty() {
+            vec![KeyedSeries::new(
+                "Value",
+                self.data
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, bar)| (ix as f64, bar.label.clone(), bar.value))
+                    .collect(),
+            )]
+        } else {
+            self.series
+                .iter()
+                .map(|series| {
+                    KeyedSeries::new(
+                        series.name.clone(),
+                        series
+                            .data
+                            .iter()
+                            .enumerate()
+                            .map(|(ix, value)| {
+                                let label =
+                                    self.labels.get(ix).cloned().unwrap_or_else(|| {
+                                        SharedString::from(format!("{}", ix + 1))
+                                    });
+                                (ix as f64, label, *value)
+                            })
+                            .collect(),
+                    )
+                })
+                .collect()
+        };
+        let kind = match self.mode {
+            BarChartMode::Stacked => "Stacked bar chart",
+            _ => "Bar chart",
+        };
+        series.iter().fold(
+            ChartDescription::new(kind, ChartDataTable::from_series("Category", &series)),
+            |description, series| description.series(series.summary()),
+        )
+    }
+}
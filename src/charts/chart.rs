@@ -1,15 +1,10 @@
+use super::description::{format_value, ChartDataTable, ChartDescription, Describe, KeyedSeries};
+use super::palette::{paint_area_pattern, pattern_overlay, ChartPalette, PatternFill};
 use crate::theme::use_theme;
+use crate::util::{snap_stroke_center, SnapToDevicePixels};
 use gpui::{prelude::FluentBuilder as _, *};
 use std::rc::Rc;
 
-const CHART_COLORS: [u32; 8] = [
-    0x3b82f6, 0x22c55e, 0xf59e0b, 0xef4444, 0x8b5cf6, 0x06b6d4, 0xf97316, 0xec4899,
-];
-
-fn default_color(index: usize) -> Hsla {
-    rgb(CHART_COLORS[index % CHART_COLORS.len()]).into()
-}
-
 #[derive(Clone, Debug)]
 pub struct DataPoint {
     pub x: f64,
@@ -424,6 +419,8 @@ pub struct Series {
     pub fill_opacity: f32,
     pub smooth: bool,
     pub bar_width: Option<f32>,
+    /// Texture for bar and area fills.
+    pub pattern: Option<PatternFill>,
 }
 
 impl Series {
@@ -439,6 +436,7 @@ impl Series {
             fill_opacity: 0.2,
             smooth: false,
             bar_width: None,
+            pattern: None,
         }
     }
 
@@ -502,6 +500,11 @@ impl Series {
         self
     }
 
+    pub fn pattern(mut self, pattern: PatternFill) -> Self {
+        self.pattern = Some(pattern);
+        self
+    }
+
     pub fn data_range(&self) -> DataRange {
         DataRange::from_points(&self.data)
     }
@@ -523,9 +526,10 @@ struct ChartPaintState {
     grid_color: Hsla,
     #[allow(dead_code)]
     text_color: Hsla,
-    #[allow(dead_code)]
     background: Hsla,
     padding: ChartPadding,
+    palette: ChartPalette,
+    pattern_fills: bool,
 }
 
 #[derive(IntoElement)]
@@ -535,6 +539,8 @@ pub struct Chart {
     y_axis: Axis,
     legend: Legend,
     tooltip: TooltipConfig,
+    palette: ChartPalette,
+    pattern_fills: bool,
     style: StyleRefinement,
 }
 
@@ -552,6 +558,8 @@ impl Chart {
             y_axis: Axis::new().left(),
             legend: Legend::new(),
             tooltip: TooltipConfig::new(),
+            palette: ChartPalette::default(),
+            pattern_fills: false,
             style: StyleRefinement::default(),
         }
     }
@@ -596,6 +604,18 @@ impl Chart {
         self
     }
 
+    pub fn palette(mut self, palette: ChartPalette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Texture each bar and area series with a different [`PatternFill`] so series can
+    /// be told apart without color. Series with their own pattern keep it.
+    pub fn pattern_fills(mut self, enabled: bool) -> Self {
+        self.pattern_fills = enabled;
+        self
+    }
+
     fn compute_data_range(&self) -> DataRange {
         let mut range = DataRange::new(f64::MAX, f64::MIN, f64::MAX, f64::MIN);
 
@@ -633,6 +653,7 @@ impl Styled for Chart {
 
 impl RenderOnce for Chart {
     fn render(self, _window: &mut Window, _cx: &mut App) -> impl IntoElement {
+        let _span = tracing::trace_span!("chart", series = self.series.len()).entered();
         let theme = use_theme();
 
         let show_y_axis = self.y_axis.show_labels;
@@ -649,6 +670,9 @@ impl RenderOnce for Chart {
         let user_style = self.style;
         let series_for_legend = self.series.clone();
         let legend = self.legend.clone();
+        let legend_palette = self.palette.clone();
+        let pattern_fills = self.pattern_fills;
+        let pattern_color = theme.tokens.background.opacity(0.6);
 
         let y_axis_clone = self.y_axis.clone();
         let y_labels: Vec<String> = if show_y_axis {
@@ -673,6 +697,8 @@ impl RenderOnce for Chart {
             text_color: theme.tokens.muted_foreground,
             background: theme.tokens.background,
             padding: padding.clone(),
+            palette: self.palette,
+            pattern_fills: self.pattern_fills,
         };
 
         let text_color = theme.tokens.muted_foreground;
@@ -729,6 +755,8 @@ impl RenderOnce for Chart {
                                         let y = area.chart_top()
                                             + area.chart_height()
                                                 * (i as f32 / state.y_axis.tick_count as f32);
+                                        let y =
+                                            snap_stroke_center(y, px(1.0), window.scale_factor());
                                         let mut builder = PathBuilder::stroke(px(1.0));
                                         builder.move_to(point(area.chart_left(), y));
                                         builder.line_to(point(area.chart_right(), y));
@@ -743,6 +771,8 @@ impl RenderOnce for Chart {
                                         let x = area.chart_left()
                                             + area.chart_width()
                                                 * (i as f32 / state.x_axis.tick_count as f32);
+                                        let x =
+                                            snap_stroke_center(x, px(1.0), window.scale_factor());
                                         let mut builder = PathBuilder::stroke(px(1.0));
                                         builder.move_to(point(x, area.chart_top()));
                                         builder.line_to(point(x, area.chart_bottom()));
@@ -756,8 +786,14 @@ impl RenderOnce for Chart {
                                 let hover_radius = px(15.0);
 
                                 for (series_index, series) in state.series.iter().enumerate() {
-                                    let color =
-                                        series.color.unwrap_or_else(|| default_color(series_index));
+                                    let color = series
+                                        .color
+                                        .unwrap_or_else(|| state.palette.color(series_index));
+                                    let pattern = PatternFill::resolve(
+                                        series.pattern,
+                                        series_index,
+                                        state.pattern_fills,
+                                    );
 
                                     if series.data.is_empty() {
                                         continue;
@@ -797,6 +833,20 @@ impl RenderOnce for Chart {
                                                         color.opacity(series.fill_opacity),
                                                     );
                                                 }
+                                                let baseline = [
+                                                    point(screen_points[0].x, area.chart_bottom()),
+                                                    point(
+                                                        screen_points[screen_points.len() - 1].x,
+                                                        area.chart_bottom(),
+                                                    ),
+                                                ];
+                                                paint_area_pattern(
+                                                    pattern,
+                                                    &screen_points,
+                                                    &baseline,
+                                                    color.opacity(0.7),
+                                                    window,
+                                                );
                                             }
 
                                             if screen_points.len() >= 2 {
@@ -837,9 +887,15 @@ impl RenderOnce for Chart {
                                                         screen_pt.y,
                                                     ),
                                                     size(px(bar_width), bar_height),
-                                                );
+                                                )
+                                                .snap_to_device(window.scale_factor());
 
                                                 window.paint_quad(fill(bar_bounds, color));
+                                                pattern.paint(
+                                                    bar_bounds,
+                                                    state.background.opacity(0.6),
+                                                    window,
+                                                );
 
                                                 if bar_bounds.contains(&mouse_pos) {
                                                     hovered_point = Some(HoveredPoint {
@@ -976,15 +1032,58 @@ impl RenderOnce for Chart {
                         .py(px(12.0))
                         .justify_center()
                         .children(series_for_legend.iter().enumerate().map(|(i, s)| {
-                            let color = s.color.unwrap_or_else(|| default_color(i));
+                            let color = s.color.unwrap_or_else(|| legend_palette.color(i));
+                            let pattern = match s.series_type {
+                                SeriesType::Bar | SeriesType::Area => {
+                                    PatternFill::resolve(s.pattern, i, pattern_fills)
+                                }
+                                SeriesType::Line | SeriesType::Scatter => PatternFill::Solid,
+                            };
                             div()
                                 .flex()
                                 .items_center()
                                 .gap(px(6.0))
-                                .child(div().size(px(12.0)).rounded(px(2.0)).bg(color))
+                                .child(
+                                    div()
+                                        .relative()
+                                        .overflow_hidden()
+                                        .size(px(12.0))
+                                        .rounded(px(2.0))
+                                        .bg(color)
+                                        .child(pattern_overlay(pattern, pattern_color)),
+                                )
                                 .child(div().text_sm().text_color(text_color).child(s.name.clone()))
                         })),
                 )
             })
     }
 }
+
+impl Describe for Chart {
+    fn describe(&self) -> ChartDescription {
+        let x_label = |point: &DataPoint| match (&point.label, &self.x_axis.tick_format) {
+            (Some(label), _) => label.clone(),
+            (None, Some(format)) => format(point.x).into(),
+            (None, None) => format_value(point.x).into(),
+        };
+        let series: Vec<KeyedSeries> = self
+            .series
+            .iter()
+            .map(|series| {
+                KeyedSeries::new(
+                    series.name.clone(),
+                    series
+                        .data
+                        .iter()
+                        .map(|point| (point.x, x_label(point), point.y))
+                        .collect(),
+                )
+            })
+            .collect();
+        let key = self.x_axis.label.clone().unwrap_or_else(|| "X".into());
+        series.iter().fold(
+            ChartDescription::new("Chart", ChartDataTable::from_series(key, &series)),
+            |description, series| description.series(series.summary()),
+        )
+    }
+}
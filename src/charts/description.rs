@@ -0,0 +1,553 @@
+//! Text descriptions and data tables for charts, for readers who can't see them.
+//!
+//! Every chart type implements [`Describe`]. Its [`ChartDescription`] reads the chart
+//! out as sentences — series names, how many values, the range they cover, whether
+//! they rise or fall, and where the lowest and highest ones are — and holds the plotted
+//! values as a [`ChartDataTable`].
+//!
+//! [`DescribedChart`] puts a chart under a small header with an info button, whose
+//! tooltip is the summary, and a button switching between the chart and its table.
+//!
+//! ```rust,ignore
+//! DescribedChart::new("revenue", LineChart::new(series)).title("Monthly revenue")
+//! ```
+
+use crate::components::button::ButtonVariant;
+use crate::components::icon_button::IconButton;
+use crate::components::tooltip::Tooltip;
+use crate::theme::use_theme;
+use gpui::{prelude::FluentBuilder as _, *};
+
+/// A change smaller than this share of a series' span doesn't count as a trend.
+const TREND_THRESHOLD: f64 = 0.1;
+
+/// Which way a series goes from its first value to its last.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Trend {
+    Rising,
+    Falling,
+    /// Every value is the same.
+    Flat,
+    /// Moves up and down but ends about where it started.
+    Mixed,
+}
+
+impl Trend {
+    pub fn of(values: &[f64]) -> Self {
+        let (Some(&first), Some(&last)) = (values.first(), values.last()) else {
+            return Trend::Flat;
+        };
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let span = max - min;
+        if span <= 0.0 {
+            return Trend::Flat;
+        }
+        let change = last - first;
+        if change > span * TREND_THRESHOLD {
+            Trend::Rising
+        } else if change < -span * TREND_THRESHOLD {
+            Trend::Falling
+        } else {
+            Trend::Mixed
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Trend::Rising => "rising",
+            Trend::Falling => "falling",
+            Trend::Flat => "flat",
+            Trend::Mixed => "with no clear trend",
+        }
+    }
+}
+
+/// How a chart's values relate, which decides what's said about them.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Arrangement {
+    /// Values in order, e.g. over time, read out with their trend.
+    #[default]
+    Sequence,
+    /// Values for unordered categories, e.g. the axes of a radar chart.
+    Categories,
+    /// Parts of a whole, as in a pie chart, read out with their shares.
+    PartsOfWhole,
+}
+
+/// The figures describing one series, or one set of parts for charts of a whole.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SeriesSummary {
+    pub name: SharedString,
+    /// The values in plotting order, each with the label of where it's plotted.
+    pub values: Vec<(SharedString, f64)>,
+    pub trend: Trend,
+}
+
+impl SeriesSummary {
+    /// Values that aren't finite are left out.
+    pub fn new(
+        name: impl Into<SharedString>,
+        values: impl IntoIterator<Item = (SharedString, f64)>,
+    ) -> Self {
+        let values: Vec<_> = values
+            .into_iter()
+            .filter(|(_, value)| value.is_finite())
+            .collect();
+        let plain: Vec<f64> = values.iter().map(|(_, value)| *value).collect();
+        Self {
+            name: name.into(),
+            trend: Trend::of(&plain),
+            values,
+        }
+    }
+
+    pub fn min(&self) -> Option<&(SharedString, f64)> {
+        self.values.iter().min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    pub fn max(&self) -> Option<&(SharedString, f64)> {
+        self.values.iter().max_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    pub fn total(&self) -> f64 {
+        self.values.iter().map(|(_, value)| value).sum()
+    }
+
+    fn sentence(&self, arrangement: Arrangement) -> String {
+        match arrangement {
+            Arrangement::Sequence => self.sequence_sentence(),
+            Arrangement::Categories => self.categories_sentence(),
+            Arrangement::PartsOfWhole => self.parts_sentence(),
+        }
+    }
+
+    /// "Sales: 12 values from 3 to 9.5, rising. Lowest 2 at Feb, highest 11 at Nov."
+    fn sequence_sentence(&self) -> String {
+        let (Some(first), Some(last), Some(min), Some(max)) = (
+            self.values.first(),
+            self.values.last(),
+            self.min(),
+            self.max(),
+        ) else {
+            return format!("{}: no data.", self.name);
+        };
+        if self.values.len() == 1 {
+            return format!("{}: {}.", self.name, format_value(first.1));
+        }
+        format!(
+            "{}: {} values from {} to {}, {}. Lowest {} at {}, highest {} at {}.",
+            self.name,
+            self.values.len(),
+            format_value(first.1),
+            format_value(last.1),
+            self.trend.describe(),
+            format_value(min.1),
+            min.0,
+            format_value(max.1),
+            max.0,
+        )
+    }
+
+    /// "Skills: 5 values. Lowest 2 at Design, highest 9 at Rust."
+    fn categories_sentence(&self) -> String {
+        let (Some(min), Some(max)) = (self.min(), self.max()) else {
+            return format!("{}: no data.", self.name);
+        };
+        if self.values.len() == 1 {
+            return format!("{}: {} at {}.", self.name, format_value(max.1), max.0);
+        }
+        format!(
+            "{}: {} values. Lowest {} at {}, highest {} at {}.",
+            self.name,
+            self.values.len(),
+            format_value(min.1),
+            min.0,
+            format_value(max.1),
+            max.0,
+        )
+    }
+
+    /// "Browsers: 4 parts totalling 100. Largest Chrome at 65 (65%), smallest Edge at
+    /// 5 (5%)."
+    fn parts_sentence(&self) -> String {
+        let (Some(min), Some(max)) = (self.min(), self.max()) else {
+            return format!("{}: no data.", self.name);
+        };
+        let total = self.total();
+        let share = |value: f64| {
+            if total == 0.0 {
+                0.0
+            } else {
+                value / total * 100.0
+            }
+        };
+        format!(
+            "{}: {} parts totalling {}. Largest {} at {} ({:.0}%), smallest {} at {} ({:.0}%).",
+            self.name,
+            self.values.len(),
+            format_value(total),
+            max.0,
+            format_value(max.1),
+            share(max.1),
+            min.0,
+            format_value(min.1),
+            share(min.1),
+        )
+    }
+}
+
+/// A chart's values as rows and columns, for showing in place of the chart.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChartDataTable {
+    pub columns: Vec<SharedString>,
+    pub rows: Vec<Vec<SharedString>>,
+}
+
+impl ChartDataTable {
+    pub fn new(columns: impl IntoIterator<Item = impl Into<SharedString>>) -> Self {
+        Self {
+            columns: columns.into_iter().map(Into::into).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn push_row(&mut self, cells: impl IntoIterator<Item = impl Into<SharedString>>) {
+        self.rows.push(cells.into_iter().map(Into::into).collect());
+    }
+
+    /// A table with a `key` column and one column per series. Series may be plotted at
+    /// different keys: there's a row for every key any series has, sorted by the number
+    /// it sits at, with blank cells where a series has no value.
+    pub fn from_series(key: impl Into<SharedString>, series: &[KeyedSeries]) -> Self {
+        let mut keys: Vec<(f64, SharedString)> = series
+            .iter()
+            .flat_map(|series| {
+                series
+                    .values
+                    .iter()
+                    .map(|(position, label, _)| (*position, label.clone()))
+            })
+            .collect();
+        keys.sort_by(|a, b| a.0.total_cmp(&b.0));
+        keys.dedup_by(|a, b| a.0 == b.0);
+
+        let mut table = Self::new(
+            std::iter::once(key.into()).chain(series.iter().map(|series| series.name.clone())),
+        );
+        for (position, label) in keys {
+            let cells = series.iter().map(|series| {
+                series
+                    .values
+                    .iter()
+                    .find(|(at, _, _)| *at == position)
+                    .map(|(_, _, value)| SharedString::from(format_value(*value)))
+                    .unwrap_or_default()
+            });
+            table.push_row(std::iter::once(label).chain(cells));
+        }
+        table
+    }
+}
+
+/// A series for [`ChartDataTable::from_series`]: values, each with the number it's plotted
+/// at and the label shown for it.
+#[derive(Clone, Debug)]
+pub struct KeyedSeries {
+    pub name: SharedString,
+    pub values: Vec<(f64, SharedString, f64)>,
+}
+
+impl KeyedSeries {
+    pub fn new(name: impl Into<SharedString>, values: Vec<(f64, SharedString, f64)>) -> Self {
+        Self {
+            name: name.into(),
+            values,
+        }
+    }
+
+    pub fn summary(&self) -> SeriesSummary {
+        SeriesSummary::new(
+            self.name.clone(),
+            self.values
+                .iter()
+                .map(|(_, label, value)| (label.clone(), *value)),
+        )
+    }
+}
+
+/// What a chart shows, in words and as a table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChartDescription {
+    /// The kind of chart, e.g. "Line chart".
+    pub kind: SharedString,
+    pub series: Vec<SeriesSummary>,
+    pub arrangement: Arrangement,
+    /// Sentences read after the series, for what they don't cover.
+    pub notes: Vec<SharedString>,
+    pub table: ChartDataTable,
+}
+
+impl ChartDescription {
+    pub fn new(kind: impl Into<SharedString>, table: ChartDataTable) -> Self {
+        Self {
+            kind: kind.into(),
+            series: Vec::new(),
+            arrangement: Arrangement::default(),
+            notes: Vec::new(),
+            table,
+        }
+    }
+
+    pub fn series(mut self, series: SeriesSummary) -> Self {
+        self.series.push(series);
+        self
+    }
+
+    pub fn arrangement(mut self, arrangement: Arrangement) -> Self {
+        self.arrangement = arrangement;
+        self
+    }
+
+    pub fn note(mut self, note: impl Into<SharedString>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    /// The chart read out as sentences, e.g. for a tooltip or screen reader.
+    pub fn summary(&self) -> String {
+        let mut text = match self.series.len() {
+            0 if self.notes.is_empty() => format!("{} with no data.", self.kind),
+            0 | 1 => format!("{}.", self.kind),
+            count => {
+                let names: Vec<&str> = self.series.iter().map(|s| s.name.as_ref()).collect();
+                format!("{} with {} series: {}.", self.kind, count, names.join(", "))
+            }
+        };
+        for series in &self.series {
+            text.push(' ');
+            text.push_str(&series.sentence(self.arrangement));
+        }
+        for note in &self.notes {
+            text.push(' ');
+            text.push_str(note);
+        }
+        text
+    }
+}
+
+/// Charts that can describe what they show.
+pub trait Describe {
+    fn describe(&self) -> ChartDescription;
+}
+
+/// Up to two decimals, without trailing zeros.
+pub(crate) fn format_value(value: f64) -> String {
+    let text = format!("{:.2}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+/// A chart with an info button carrying its description and a toggle to show its data
+/// as a table instead.
+#[derive(IntoElement)]
+pub struct DescribedChart {
+    id: ElementId,
+    title: Option<SharedString>,
+    description: ChartDescription,
+    chart: AnyElement,
+    style: StyleRefinement,
+}
+
+impl DescribedChart {
+    pub fn new(id: impl Into<ElementId>, chart: impl Describe + IntoElement) -> Self {
+        Self {
+            id: id.into(),
+            title: None,
+            description: chart.describe(),
+            chart: chart.into_any_element(),
+            style: StyleRefinement::default(),
+        }
+    }
+
+    pub fn title(mut self, title: impl Into<SharedString>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn description(&self) -> &ChartDescription {
+        &self.description
+    }
+}
+
+impl Styled for DescribedChart {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+fn render_table(table: ChartDataTable, theme: &crate::theme::Theme) -> Div {
+    let header = div()
+        .flex()
+        .bg(theme.tokens.muted)
+        .font_weight(FontWeight::SEMIBOLD)
+        .children(
+            table
+                .columns
+                .into_iter()
+                .map(|column| div().flex_1().px(px(8.0)).py(px(4.0)).child(column)),
+        );
+    let rows = table.rows.into_iter().map(|row| {
+        div()
+            .flex()
+            .border_t_1()
+            .border_color(theme.tokens.border)
+            .children(
+                row.into_iter()
+                    .map(|cell| div().flex_1().px(px(8.0)).py(px(4.0)).child(cell)),
+            )
+    });
+    div()
+        .flex()
+        .flex_col()
+        .text_sm()
+        .text_color(theme.tokens.foreground)
+        .border_1()
+        .border_color(theme.tokens.border)
+        .rounded(theme.tokens.radius_md)
+        .overflow_hidden()
+        .child(header)
+        .children(rows)
+}
+
+impl RenderOnce for DescribedChart {
+    fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let theme = use_theme();
+        let user_style = self.style;
+        let show_table = window.use_keyed_state(self.id.clone(), cx, |_, _| false);
+        let showing_table = *show_table.read(cx);
+        let summary = self.description.summary();
+
+        div()
+            .id(self.id)
+            .flex()
+            .flex_col()
+            .gap_2()
+            .map(|this| {
+                let mut div = this;
+                div.style().refine(&user_style);
+                div
+            })
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_1()
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_sm()
+                            .font_weight(FontWeight::MEDIUM)
+                            .text_color(theme.tokens.foreground)
+                            .when_some(self.title, |this, title| this.child(title)),
+                    )
+                    .child(
+                        Tooltip::new(summary).child(
+                            IconButton::new("info")
+                                .variant(ButtonVariant::Ghost)
+                                .size(px(24.0)),
+                        ),
+                    )
+                    .child(
+                        IconButton::new(if showing_table {
+                            "chart-column"
+                        } else {
+                            "table"
+                        })
+                        .variant(ButtonVariant::Ghost)
+                        .size(px(24.0))
+                        .on_click(move |_, _, cx| {
+                            show_table.update(cx, |showing, cx| {
+                                *showing = !*showing;
+                                cx.notify();
+                            });
+                        }),
+                    ),
+            )
+            .map(|this| {
+                if showing_table {
+                    this.child(
+                        div()
+                            .id("data-table")
+                            .overflow_y_scroll()
+                            .child(render_table(self.description.table, &theme)),
+                    )
+                } else {
+                    this.child(self.chart)
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labeled(values: &[f64]) -> Vec<(SharedString, f64)> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(ix, value)| (SharedString::from(format!("P{}", ix + 1)), *value))
+            .collect()
+    }
+
+    #[test]
+    fn trends_follow_first_and_last_values() {
+        assert_eq!(Trend::of(&[1.0, 3.0, 2.0, 8.0]), Trend::Rising);
+        assert_eq!(Trend::of(&[8.0, 9.0, 4.0, 1.0]), Trend::Falling);
+        assert_eq!(Trend::of(&[5.0, 5.0, 5.0]), Trend::Flat);
+        assert_eq!(Trend::of(&[5.0, 9.0, 1.0, 5.2]), Trend::Mixed);
+        assert_eq!(Trend::of(&[]), Trend::Flat);
+    }
+
+    #[test]
+    fn summary_reads_out_ranges_and_extremes() {
+        let description = ChartDescription::new("Line chart", ChartDataTable::default())
+            .series(SeriesSummary::new("Sales", labeled(&[3.0, 2.0, 11.0, 9.5])))
+            .series(SeriesSummary::new("Costs", labeled(&[f64::NAN, 4.0])));
+        assert_eq!(
+            description.summary(),
+            "Line chart with 2 series: Sales, Costs. \
+             Sales: 4 values from 3 to 9.5, rising. Lowest 2 at P2, highest 11 at P3. \
+             Costs: 4."
+        );
+
+        let pie = ChartDescription::new("Pie chart", ChartDataTable::default())
+            .series(SeriesSummary::new("Share", labeled(&[75.0, 25.0])))
+            .arrangement(Arrangement::PartsOfWhole);
+        assert_eq!(
+            pie.summary(),
+            "Pie chart. Share: 2 parts totalling 100. \
+             Largest P1 at 75 (75%), smallest P2 at 25 (25%)."
+        );
+    }
+
+    #[test]
+    fn tables_merge_series_by_key() {
+        let a = KeyedSeries::new(
+            "A",
+            vec![(1.0, "x1".into(), 10.0), (2.0, "x2".into(), 20.5)],
+        );
+        let b = KeyedSeries::new("B", vec![(3.0, "x3".into(), 7.0), (1.0, "x1".into(), 1.0)]);
+        let table = ChartDataTable::from_series("X", &[a, b]);
+        let cells = |row: &Vec<SharedString>| row.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+        assert_eq!(cells(&table.columns), ["X", "A", "B"]);
+        assert_eq!(cells(&table.rows[0]), ["x1", "10", "1"]);
+        assert_eq!(cells(&table.rows[1]), ["x2", "20.5", ""]);
+        assert_eq!(cells(&table.rows[2]), ["x3", "", "7"]);
+    }
+}
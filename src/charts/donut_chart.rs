@@ -1,15 +1,10 @@
+use super::description::{ChartDescription, Describe};
+use super::palette::ChartPalette;
+use crate::charts::pie_chart::describe_segments;
 use crate::charts::pie_chart::PieChartSegment;
 use crate::theme::use_theme;
 use gpui::{prelude::FluentBuilder as _, *};
 
-const CHART_COLORS: [u32; 8] = [
-    0x3b82f6, 0x22c55e, 0xf59e0b, 0xef4444, 0x8b5cf6, 0x06b6d4, 0xf97316, 0xec4899,
-];
-
-fn default_color(index: usize) -> Hsla {
-    rgb(CHART_COLORS[index % CHART_COLORS.len()]).into()
-}
-
 #[derive(Copy, Clone, Default, PartialEq, Eq)]
 pub enum DonutChartSize {
     Sm,
@@ -39,6 +34,7 @@ pub struct DonutChart {
     size: DonutChartSize,
     show_legend: bool,
     show_percentages: bool,
+    palette: ChartPalette,
     style: StyleRefinement,
 }
 
@@ -52,6 +48,7 @@ impl DonutChart {
             size: DonutChartSize::default(),
             show_legend: false,
             show_percentages: false,
+            palette: ChartPalette::default(),
             style: StyleRefinement::default(),
         }
     }
@@ -100,6 +97,11 @@ impl DonutChart {
         self.show_percentages = show;
         self
     }
+
+    pub fn palette(mut self, palette: ChartPalette) -> Self {
+        self.palette = palette;
+        self
+    }
 }
 
 impl Styled for DonutChart {
@@ -129,6 +131,7 @@ fn get_color_at_angle(angle: f32, segment_data: &[(f32, f32, Hsla)]) -> Hsla {
 
 impl RenderOnce for DonutChart {
     fn render(self, _window: &mut Window, _cx: &mut App) -> impl IntoElement {
+        let _span = tracing::trace_span!("chart.donut", segments = self.segments.len()).entered();
         let theme = use_theme();
         let user_style = self.style;
         let chart_size = self.size.to_pixels();
@@ -147,6 +150,7 @@ impl RenderOnce for DonutChart {
                 self.inner_radius,
                 self.center_label.clone(),
                 self.center_value.clone(),
+                &self.palette,
                 &theme,
             )
         };
@@ -156,6 +160,7 @@ impl RenderOnce for DonutChart {
                 &self.segments,
                 total,
                 show_percentages,
+                &self.palette,
                 &theme,
             ))
         } else {
@@ -199,6 +204,7 @@ fn render_donut(
     inner_ratio: f32,
     center_label: Option<SharedString>,
     center_value: Option<SharedString>,
+    palette: &ChartPalette,
     theme: &crate::theme::Theme,
 ) -> Div {
     let size_f32 = chart_size / px(1.0);
@@ -215,7 +221,7 @@ fn render_donut(
         }
         let fraction = (segment.value / total) as f32;
         let sweep = fraction * std::f32::consts::TAU;
-        let color = segment.color.unwrap_or_else(|| default_color(idx));
+        let color = segment.color.unwrap_or_else(|| palette.color(idx));
         segment_data.push((current_angle, sweep, color));
         current_angle += sweep;
     }
@@ -357,6 +363,7 @@ fn render_legend(
     segments: &[PieChartSegment],
     total: f64,
     show_percentages: bool,
+    palette: &ChartPalette,
     theme: &crate::theme::Theme,
 ) -> Div {
     div()
@@ -368,7 +375,7 @@ fn render_legend(
                 return None;
             }
 
-            let color = segment.color.unwrap_or_else(|| default_color(idx));
+            let color = segment.color.unwrap_or_else(|| palette.color(idx));
             let percentage = if total > 0.0 {
                 (segment.value / total * 100.0) as u32
             } else {
@@ -406,3 +413,9 @@ fn render_legend(
             )
         }))
 }
+
+impl Describe for DonutChart {
+    fn describe(&self) -> ChartDescription {
+        describe_segments("Donut chart", &self.segments)
+    }
+}
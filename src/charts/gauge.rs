@@ -1,3 +1,4 @@
+use super::description::{ChartDataTable, ChartDescription, Describe};
 use crate::theme::use_theme;
 use gpui::{prelude::FluentBuilder as _, *};
 use std::rc::Rc;
@@ -233,3 +234,16 @@ impl RenderOnce for Gauge {
             })
     }
 }
+
+impl Describe for Gauge {
+    fn describe(&self) -> ChartDescription {
+        let reading = match self.format_fn {
+            Some(ref format) => format(self.value),
+            None => format!("{}%", (self.value * 100.0) as i32),
+        };
+        let name = self.label.clone().unwrap_or_else(|| "Value".into());
+        let mut table = ChartDataTable::new(["Gauge", "Reading"]);
+        table.push_row([name.clone(), reading.clone().into()]);
+        ChartDescription::new("Gauge", table).note(format!("{}: {}.", name, reading))
+    }
+}
@@ -1,3 +1,6 @@
+use super::description::{
+    format_value, Arrangement, ChartDataTable, ChartDescription, Describe, KeyedSeries,
+};
 use crate::theme::use_theme;
 use gpui::{prelude::FluentBuilder as _, *};
 
@@ -91,6 +94,7 @@ impl Styled for Heatmap {
 
 impl RenderOnce for Heatmap {
     fn render(self, _window: &mut Window, _cx: &mut App) -> impl IntoElement {
+        let _span = tracing::trace_span!("chart.heatmap", rows = self.data.len()).entered();
         let theme = use_theme();
         let user_style = self.style;
 
@@ -207,3 +211,50 @@ impl RenderOnce for Heatmap {
             })
     }
 }
+
+impl Describe for Heatmap {
+    fn describe(&self) -> ChartDescription {
+        let column_label = |ix: usize| {
+            self.x_labels
+                .get(ix)
+                .cloned()
+                .unwrap_or_else(|| format!("Column {}", ix + 1).into())
+        };
+        let series: Vec<KeyedSeries> = self
+            .data
+            .iter()
+            .enumerate()
+            .map(|(row, values)| {
+                let name = self
+                    .y_labels
+                    .get(row)
+                    .cloned()
+                    .unwrap_or_else(|| format!("Row {}", row + 1).into());
+                let values = values
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, value)| (ix as f64, column_label(ix), *value))
+                    .collect();
+                KeyedSeries::new(name, values)
+            })
+            .collect();
+
+        let columns = series.iter().map(|row| row.values.len()).max().unwrap_or(0);
+        let mut table = ChartDataTable::new(
+            std::iter::once(SharedString::default()).chain((0..columns).map(column_label)),
+        );
+        for row in &series {
+            table.push_row(
+                std::iter::once(row.name.clone()).chain(
+                    row.values
+                        .iter()
+                        .map(|(_, _, value)| format_value(*value).into()),
+                ),
+            );
+        }
+        series.iter().fold(
+            ChartDescription::new("Heatmap", table).arrangement(Arrangement::Categories),
+            |description, row| description.series(row.summary()),
+        )
+    }
+}
@@ -1,14 +1,10 @@
-use crate::theme::use_theme;
+use super::description::{format_value, ChartDataTable, ChartDescription, Describe, KeyedSeries};
+use super::palette::ChartPalette;
+use crate::svg_export::{ExportSvg, PathSink, SvgDocument, SvgPath, SvgTextAnchor, SvgTextStyle};
+use crate::theme::{use_theme, Theme};
+use crate::util::snap_stroke_center;
 use gpui::{prelude::FluentBuilder as _, *};
 
-const CHART_COLORS: [u32; 8] = [
-    0x3b82f6, 0x22c55e, 0xf59e0b, 0xef4444, 0x8b5cf6, 0x06b6d4, 0xf97316, 0xec4899,
-];
-
-fn get_chart_color(index: usize) -> Hsla {
-    rgb(CHART_COLORS[index % CHART_COLORS.len()]).into()
-}
-
 #[derive(Clone, Debug)]
 pub struct LineChartPoint {
     pub x: f64,
@@ -138,6 +134,7 @@ pub struct LineChart {
     y_max: Option<f64>,
     smooth: bool,
     show_legend: bool,
+    palette: ChartPalette,
     style: StyleRefinement,
 }
 
@@ -153,6 +150,7 @@ impl LineChart {
             y_max: None,
             smooth: false,
             show_legend: true,
+            palette: ChartPalette::default(),
             style: StyleRefinement::default(),
         }
     }
@@ -196,6 +194,11 @@ impl LineChart {
         self.show_legend = show;
         self
     }
+
+    pub fn palette(mut self, palette: ChartPalette) -> Self {
+        self.palette = palette;
+        self
+    }
 }
 
 impl Styled for LineChart {
@@ -214,6 +217,96 @@ fn format_y_value(value: f64) -> String {
     }
 }
 
+/// Grid lines and y-axis labels split the plot into this many bands each way.
+const GRID_DIVISIONS: usize = 5;
+const LABEL_SIZE: f32 = 11.0;
+/// Gap between the x-axis labels and the bottom of the chart area.
+const X_LABEL_INSET: f32 = 8.0;
+const POINT_RADIUS: f32 = 4.0;
+const LEGEND_PADDING_Y: f32 = 8.0;
+const LEGEND_GAP: f32 = 16.0;
+const LEGEND_SWATCH: f32 = 12.0;
+const LEGEND_SWATCH_GAP: f32 = 6.0;
+const LEGEND_TEXT_SIZE: f32 = 14.0;
+const LEGEND_LINE_HEIGHT: f32 = 20.0;
+
+/// Room around the plot for the axis labels. Painting, the label overlay and SVG
+/// export all measure from these, so they place things alike.
+#[derive(Clone, Copy)]
+struct Insets {
+    left: f32,
+    right: f32,
+    top: f32,
+    bottom: f32,
+}
+
+impl Insets {
+    fn new(show_x_axis: bool, show_y_axis: bool) -> Self {
+        Self {
+            left: if show_y_axis { 50.0 } else { 10.0 },
+            right: 20.0,
+            top: 20.0,
+            bottom: if show_x_axis { 40.0 } else { 10.0 },
+        }
+    }
+
+    /// The plot within the chart area `bounds`, if there's room for one.
+    fn plot(&self, bounds: Bounds<Pixels>) -> Option<Bounds<Pixels>> {
+        let plot = Bounds::from_corners(
+            point(bounds.left() + px(self.left), bounds.top() + px(self.top)),
+            point(
+                bounds.right() - px(self.right),
+                bounds.bottom() - px(self.bottom),
+            ),
+        );
+        (plot.size.width > px(0.0) && plot.size.height > px(0.0)).then_some(plot)
+    }
+
+    /// For an overlay placed at `relative(fraction)` of the chart area: the offset that
+    /// puts it `fraction` of the way across the plot, between insets `start` and `end`.
+    fn offset(fraction: f32, start: f32, end: f32) -> Pixels {
+        px(start - (start + end) * fraction)
+    }
+}
+
+fn screen_points(
+    series: &LineChartSeries,
+    data_range: &DataRange,
+    plot: Bounds<Pixels>,
+) -> Vec<Point<Pixels>> {
+    series
+        .points
+        .iter()
+        .map(|p| {
+            point(
+                plot.left() + plot.size.width * data_range.normalize_x(p.x),
+                plot.bottom() - plot.size.height * data_range.normalize_y(p.y),
+            )
+        })
+        .collect()
+}
+
+/// The region under a series, down to `baseline`.
+fn trace_area(path: &mut impl PathSink, points: &[Point<Pixels>], baseline: Pixels) {
+    path.move_to(point(points[0].x, baseline));
+    for pt in points {
+        path.line_to(*pt);
+    }
+    path.line_to(point(points[points.len() - 1].x, baseline));
+    path.close();
+}
+
+fn trace_line(path: &mut impl PathSink, points: &[Point<Pixels>], smooth: bool) {
+    path.move_to(points[0]);
+    for pair in points.windows(2) {
+        if smooth && points.len() >= 3 {
+            path.curve_to(pair[1], point((pair[0].x + pair[1].x) * 0.5, pair[0].y));
+        } else {
+            path.line_to(pair[1]);
+        }
+    }
+}
+
 struct PaintData {
     series: Vec<LineChartSeries>,
     show_grid: bool,
@@ -221,14 +314,13 @@ struct PaintData {
     y_min: Option<f64>,
     y_max: Option<f64>,
     grid_color: Hsla,
-    padding_left: f32,
-    padding_right: f32,
-    padding_top: f32,
-    padding_bottom: f32,
+    palette: ChartPalette,
+    insets: Insets,
 }
 
 impl RenderOnce for LineChart {
     fn render(self, _window: &mut Window, _cx: &mut App) -> impl IntoElement {
+        let _span = tracing::trace_span!("chart.line", series = self.series.len()).entered();
         let theme = use_theme();
         let user_style = self.style;
 
@@ -244,19 +336,17 @@ impl RenderOnce for LineChart {
         let grid_color = theme.tokens.border;
         let text_color = theme.tokens.muted_foreground;
 
-        let padding_left: f32 = if show_y_axis { 50.0 } else { 10.0 };
-        let padding_right: f32 = 20.0;
-        let padding_top: f32 = 20.0;
-        let padding_bottom: f32 = if show_x_axis { 40.0 } else { 10.0 };
+        let insets = Insets::new(show_x_axis, show_y_axis);
 
         let series_for_legend = series.clone();
+        let legend_palette = self.palette.clone();
 
         let data_range = DataRange::from_series(&series, y_min, y_max);
 
         let y_labels: Vec<String> = if show_y_axis {
-            (0..=5)
+            (0..=GRID_DIVISIONS)
                 .map(|i| {
-                    let normalized = i as f64 / 5.0;
+                    let normalized = i as f64 / GRID_DIVISIONS as f64;
                     let value = data_range.y_value_at(normalized);
                     format_y_value(value)
                 })
@@ -267,15 +357,13 @@ impl RenderOnce for LineChart {
 
         let paint_data = PaintData {
             series,
+            palette: self.palette,
             show_grid,
             smooth,
             y_min,
             y_max,
             grid_color,
-            padding_left,
-            padding_right,
-            padding_top,
-            padding_bottom,
+            insets,
         };
 
         div()
@@ -306,44 +394,32 @@ impl RenderOnce for LineChart {
                                     paint_data.y_max,
                                 );
 
-                                let chart_left = bounds.left() + px(paint_data.padding_left);
-                                let chart_right = bounds.right() - px(paint_data.padding_right);
-                                let chart_top = bounds.top() + px(paint_data.padding_top);
-                                let chart_bottom = bounds.bottom() - px(paint_data.padding_bottom);
-                                let chart_width = chart_right - chart_left;
-                                let chart_height = chart_bottom - chart_top;
-
-                                if chart_width <= px(0.0) || chart_height <= px(0.0) {
+                                let Some(plot) = paint_data.insets.plot(bounds) else {
                                     return;
-                                }
+                                };
 
                                 if paint_data.show_grid {
-                                    let grid_lines = 5;
-                                    for i in 0..=grid_lines {
-                                        let y = chart_top
-                                            + chart_height * (i as f32 / grid_lines as f32);
+                                    let grid_color = paint_data.grid_color.opacity(0.3);
+                                    for i in 0..=GRID_DIVISIONS {
+                                        let fraction = i as f32 / GRID_DIVISIONS as f32;
+                                        let y = plot.top() + plot.size.height * fraction;
+                                        let y =
+                                            snap_stroke_center(y, px(1.0), window.scale_factor());
                                         let mut builder = PathBuilder::stroke(px(1.0));
-                                        builder.move_to(point(chart_left, y));
-                                        builder.line_to(point(chart_right, y));
+                                        builder.move_to(point(plot.left(), y));
+                                        builder.line_to(point(plot.right(), y));
                                         if let Ok(path) = builder.build() {
-                                            window.paint_path(
-                                                path,
-                                                paint_data.grid_color.opacity(0.3),
-                                            );
+                                            window.paint_path(path, grid_color);
                                         }
-                                    }
 
-                                    for i in 0..=grid_lines {
-                                        let x = chart_left
-                                            + chart_width * (i as f32 / grid_lines as f32);
+                                        let x = plot.left() + plot.size.width * fraction;
+                                        let x =
+                                            snap_stroke_center(x, px(1.0), window.scale_factor());
                                         let mut builder = PathBuilder::stroke(px(1.0));
-                                        builder.move_to(point(x, chart_top));
-                                        builder.line_to(point(x, chart_bottom));
+                                        builder.move_to(point(x, plot.top()));
+                                        builder.line_to(point(x, plot.bottom()));
                                         if let Ok(path) = builder.build() {
-                                            window.paint_path(
-                                                path,
-                                                paint_data.grid_color.opacity(0.3),
-                                            );
+                                            window.paint_path(path, grid_color);
                                         }
                                     }
                                 }
@@ -353,36 +429,14 @@ impl RenderOnce for LineChart {
                                         continue;
                                     }
 
-                                    let color =
-                                        s.color.unwrap_or_else(|| get_chart_color(series_index));
-
-                                    let screen_points: Vec<Point<Pixels>> = s
-                                        .points
-                                        .iter()
-                                        .map(|p| {
-                                            let norm_x = data_range.normalize_x(p.x);
-                                            let norm_y = data_range.normalize_y(p.y);
-                                            let screen_x = chart_left + chart_width * norm_x;
-                                            let screen_y = chart_bottom - chart_height * norm_y;
-                                            point(screen_x, screen_y)
-                                        })
-                                        .collect();
+                                    let color = s
+                                        .color
+                                        .unwrap_or_else(|| paint_data.palette.color(series_index));
+                                    let screen_points = screen_points(s, &data_range, plot);
 
                                     if s.fill_area && screen_points.len() >= 2 {
                                         let mut builder = PathBuilder::fill();
-                                        builder.move_to(point(screen_points[0].x, chart_bottom));
-                                        builder.line_to(screen_points[0]);
-
-                                        for pt in screen_points.iter().skip(1) {
-                                            builder.line_to(*pt);
-                                        }
-
-                                        builder.line_to(point(
-                                            screen_points.last().unwrap().x,
-                                            chart_bottom,
-                                        ));
-                                        builder.close();
-
+                                        trace_area(&mut builder, &screen_points, plot.bottom());
                                         if let Ok(path) = builder.build() {
                                             window.paint_path(path, color.opacity(0.15));
                                         }
@@ -390,34 +444,17 @@ impl RenderOnce for LineChart {
 
                                     if screen_points.len() >= 2 {
                                         let mut builder = PathBuilder::stroke(px(2.0));
-                                        builder.move_to(screen_points[0]);
-
-                                        if paint_data.smooth && screen_points.len() >= 3 {
-                                            for i in 0..screen_points.len() - 1 {
-                                                let p0 = screen_points[i];
-                                                let p1 = screen_points[i + 1];
-                                                let ctrl_x = (p0.x + p1.x) * 0.5;
-                                                builder.curve_to(p1, point(ctrl_x, p0.y));
-                                            }
-                                        } else {
-                                            for pt in screen_points.iter().skip(1) {
-                                                builder.line_to(*pt);
-                                            }
-                                        }
-
+                                        trace_line(&mut builder, &screen_points, paint_data.smooth);
                                         if let Ok(path) = builder.build() {
                                             window.paint_path(path, color);
                                         }
                                     }
 
                                     if s.show_points {
-                                        let point_radius = px(4.0);
+                                        let diameter = px(POINT_RADIUS * 2.0);
                                         for pt in &screen_points {
                                             window.paint_quad(fill(
-                                                Bounds::centered_at(
-                                                    *pt,
-                                                    size(point_radius * 2.0, point_radius * 2.0),
-                                                ),
+                                                Bounds::centered_at(*pt, size(diameter, diameter)),
                                                 color,
                                             ));
                                         }
@@ -429,13 +466,14 @@ impl RenderOnce for LineChart {
                     )
                     .when(show_y_axis, |this| {
                         this.children(y_labels.iter().enumerate().map(|(i, label)| {
-                            let top_percent = (i as f32 / 5.0) * 100.0;
+                            let fraction = i as f32 / GRID_DIVISIONS as f32;
                             div()
                                 .absolute()
                                 .left(px(4.0))
-                                .top(relative(top_percent / 100.0))
-                                .mt(px(padding_top - 6.0))
-                                .text_size(px(11.0))
+                                .top(relative(fraction))
+                                .mt(Insets::offset(fraction, insets.top, insets.bottom)
+                                    - px(LABEL_SIZE * 0.5))
+                                .text_size(px(LABEL_SIZE))
                                 .text_color(text_color)
                                 .child(label.clone())
                         }))
@@ -443,21 +481,13 @@ impl RenderOnce for LineChart {
                     .when(show_x_axis && !x_axis_labels.is_empty(), |this| {
                         let num_labels = x_axis_labels.len();
                         this.children(x_axis_labels.iter().enumerate().map(|(i, label)| {
-                            let left_percent = if num_labels == 1 {
-                                50.0
-                            } else {
-                                (i as f32 / (num_labels - 1) as f32) * 100.0
-                            };
-                            let chart_width_percent =
-                                (100.0 - padding_left / 4.0 - padding_right / 4.0) / 100.0;
-                            let adjusted_left =
-                                padding_left / 4.0 + left_percent * chart_width_percent;
+                            let fraction = x_label_fraction(i, num_labels);
                             div()
                                 .absolute()
-                                .bottom(px(8.0))
-                                .left(relative(adjusted_left / 100.0))
-                                .ml(px(-15.0))
-                                .text_size(px(11.0))
+                                .bottom(px(X_LABEL_INSET))
+                                .left(relative(fraction))
+                                .ml(Insets::offset(fraction, insets.left, insets.right) - px(15.0))
+                                .text_size(px(LABEL_SIZE))
                                 .text_color(text_color)
                                 .child(label.clone())
                         }))
@@ -468,19 +498,197 @@ impl RenderOnce for LineChart {
                     div()
                         .flex()
                         .flex_wrap()
-                        .gap(px(16.0))
-                        .px(px(padding_left))
-                        .py(px(8.0))
+                        .gap(px(LEGEND_GAP))
+                        .px(px(insets.left))
+                        .py(px(LEGEND_PADDING_Y))
                         .children(series_for_legend.iter().enumerate().map(|(i, s)| {
-                            let color = s.color.unwrap_or_else(|| get_chart_color(i));
+                            let color = s.color.unwrap_or_else(|| legend_palette.color(i));
                             div()
                                 .flex()
                                 .items_center()
-                                .gap(px(6.0))
-                                .child(div().size(px(12.0)).rounded(px(2.0)).bg(color))
-                                .child(div().text_sm().text_color(text_color).child(s.name.clone()))
+                                .gap(px(LEGEND_SWATCH_GAP))
+                                .child(div().size(px(LEGEND_SWATCH)).rounded(px(2.0)).bg(color))
+                                .child(
+                                    div()
+                                        .text_size(px(LEGEND_TEXT_SIZE))
+                                        .line_height(px(LEGEND_LINE_HEIGHT))
+                                        .text_color(text_color)
+                                        .child(s.name.clone()),
+                                )
                         })),
                 )
             })
     }
 }
+
+/// Where the `index`th of `count` x-axis labels sits across the plot.
+fn x_label_fraction(index: usize, count: usize) -> f32 {
+    if count == 1 {
+        0.5
+    } else {
+        index as f32 / (count - 1) as f32
+    }
+}
+
+impl ExportSvg for LineChart {
+    fn export_svg(&self, bounds: Bounds<Pixels>, theme: &Theme, svg: &mut SvgDocument) {
+        let grid_color = theme.tokens.border.opacity(0.3);
+        let label_style = SvgTextStyle::new(px(LABEL_SIZE), theme.tokens.muted_foreground);
+        let data_range = DataRange::from_series(&self.series, self.y_min, self.y_max);
+        let insets = Insets::new(self.show_x_axis, self.show_y_axis);
+
+        // The legend wraps like the flex row it is on screen, so lay it out first to
+        // know how much of the bottom it takes.
+        let legend_style = SvgTextStyle::new(px(LEGEND_TEXT_SIZE), theme.tokens.muted_foreground);
+        let mut legend_rows: Vec<Vec<(Pixels, usize)>> = Vec::new();
+        if self.show_legend && self.series.len() > 1 {
+            let row_width = bounds.size.width - px(insets.left * 2.0);
+            let mut x = px(0.0);
+            for (i, s) in self.series.iter().enumerate() {
+                let width =
+                    px(LEGEND_SWATCH + LEGEND_SWATCH_GAP) + svg.text_width(&s.name, &legend_style);
+                match legend_rows.last_mut() {
+                    Some(row) if x + width <= row_width => {
+                        row.push((x, i));
+                    }
+                    _ => {
+                        x = px(0.0);
+                        legend_rows.push(vec![(x, i)]);
+                    }
+                }
+                x += width + px(LEGEND_GAP);
+            }
+        }
+        let legend_height = if legend_rows.is_empty() {
+            px(0.0)
+        } else {
+            let rows = legend_rows.len() as f32;
+            px(LEGEND_PADDING_Y * 2.0 + LEGEND_LINE_HEIGHT * rows + LEGEND_GAP * (rows - 1.0))
+        };
+
+        let area = Bounds::from_corners(
+            bounds.origin,
+            point(bounds.right(), bounds.bottom() - legend_height),
+        );
+        let Some(plot) = insets.plot(area) else {
+            return;
+        };
+
+        svg.begin_group(Some("grid"));
+        for i in 0..=GRID_DIVISIONS {
+            let fraction = i as f32 / GRID_DIVISIONS as f32;
+            let y = plot.top() + plot.size.height * fraction;
+            if self.show_grid {
+                let x = plot.left() + plot.size.width * fraction;
+                svg.line_segment(
+                    point(plot.left(), y),
+                    point(plot.right(), y),
+                    (grid_color, px(1.0)),
+                );
+                svg.line_segment(
+                    point(x, plot.top()),
+                    point(x, plot.bottom()),
+                    (grid_color, px(1.0)),
+                );
+            }
+            if self.show_y_axis {
+                let label = format_y_value(data_range.y_value_at(fraction as f64));
+                let baseline = y + px(LABEL_SIZE * 0.35);
+                svg.text(point(area.left() + px(4.0), baseline), &label, &label_style);
+            }
+        }
+        if self.show_x_axis {
+            let count = self.x_axis_labels.len();
+            let style = label_style.clone().anchor(SvgTextAnchor::Middle);
+            let baseline = area.bottom() - px(X_LABEL_INSET + LABEL_SIZE * 0.25);
+            for (i, label) in self.x_axis_labels.iter().enumerate() {
+                let x = plot.left() + plot.size.width * x_label_fraction(i, count);
+                svg.text(point(x, baseline), label, &style);
+            }
+        }
+        svg.end_group();
+
+        for (series_index, s) in self.series.iter().enumerate() {
+            if s.points.is_empty() {
+                continue;
+            }
+            let color = s.color.unwrap_or_else(|| self.palette.color(series_index));
+            let screen_points = screen_points(s, &data_range, plot);
+
+            svg.begin_group(Some(s.name.as_ref()));
+            if s.fill_area && screen_points.len() >= 2 {
+                let mut path = SvgPath::new();
+                trace_area(&mut path, &screen_points, plot.bottom());
+                svg.path(&path, Some(color.opacity(0.15)), None);
+            }
+            if screen_points.len() >= 2 {
+                let mut path = SvgPath::new();
+                trace_line(&mut path, &screen_points, self.smooth);
+                svg.path(&path, None, Some((color, px(2.0))));
+            }
+            if s.show_points {
+                let diameter = px(POINT_RADIUS * 2.0);
+                for pt in &screen_points {
+                    let square = Bounds::centered_at(*pt, size(diameter, diameter));
+                    svg.rect(square, px(0.0), Some(color), None);
+                }
+            }
+            svg.end_group();
+        }
+
+        if !legend_rows.is_empty() {
+            svg.begin_group(Some("legend"));
+            let mut row_top = area.bottom() + px(LEGEND_PADDING_Y);
+            for row in &legend_rows {
+                let middle = row_top + px(LEGEND_LINE_HEIGHT * 0.5);
+                for &(x, i) in row {
+                    let s = &self.series[i];
+                    let color = s.color.unwrap_or_else(|| self.palette.color(i));
+                    let left = bounds.left() + px(insets.left) + x;
+                    let swatch = Bounds::new(
+                        point(left, middle - px(LEGEND_SWATCH * 0.5)),
+                        size(px(LEGEND_SWATCH), px(LEGEND_SWATCH)),
+                    );
+                    svg.rect(swatch, px(2.0), Some(color), None);
+                    let baseline = middle + px(LEGEND_TEXT_SIZE * 0.35);
+                    svg.text(
+                        point(left + px(LEGEND_SWATCH + LEGEND_SWATCH_GAP), baseline),
+                        &s.name,
+                        &legend_style,
+                    );
+                }
+                row_top += px(LEGEND_LINE_HEIGHT + LEGEND_GAP);
+            }
+            svg.end_group();
+        }
+    }
+}
+
+impl Describe for LineChart {
+    fn describe(&self) -> ChartDescription {
+        let series: Vec<KeyedSeries> = self
+            .series
+            .iter()
+            .map(|series| {
+                KeyedSeries::new(
+                    series.name.clone(),
+                    series
+                        .points
+                        .iter()
+                        .map(|point| {
+                            let label = point
+                                .label
+                                .clone()
+                                .unwrap_or_else(|| format_value(point.x).into());
+                            (point.x, label, point.y)
+                        })
+                        .collect(),
+                )
+            })
+            .collect();
+        series.iter().fold(
+            ChartDescription::new("Line chart", ChartDataTable::from_series("X", &series)),
+            |description, series| description.series(series.summary()),
+        )
+    }
+}
@@ -1,10 +1,12 @@
 pub mod area_chart;
 pub mod bar_chart;
 pub mod chart;
+pub mod description;
 pub mod donut_chart;
 pub mod gauge;
 pub mod heatmap;
 pub mod line_chart;
+pub mod palette;
 pub mod pie_chart;
 pub mod radar_chart;
 pub mod treemap;
@@ -14,7 +16,12 @@ pub use chart::{
     Axis, AxisPosition, Chart, ChartArea, ChartPadding, DataPoint, DataRange, Legend,
     LegendPosition, Series, SeriesType, TooltipConfig,
 };
+pub use description::{
+    Arrangement, ChartDataTable, ChartDescription, Describe, DescribedChart, KeyedSeries,
+    SeriesSummary, Trend,
+};
 pub use line_chart::{LineChart, LineChartPoint, LineChartSeries};
+pub use palette::{pattern_overlay, ChartPalette, PatternFill};
 pub use pie_chart::{
     PieChart, PieChartLabelPosition, PieChartSegment, PieChartSize, PieChartVariant,
 };
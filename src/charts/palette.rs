@@ -0,0 +1,344 @@
+//! Series colors and pattern fills shared by the charts.
+//!
+//! The color-blind-safe palettes keep series distinguishable under the common forms of
+//! color vision deficiency. Pattern fills go further and tell bars and areas apart
+//! without relying on color at all, which also survives grayscale printing.
+
+use gpui::*;
+
+const DEFAULT_COLORS: [u32; 8] = [
+    0x3b82f6, 0x22c55e, 0xf59e0b, 0xef4444, 0x8b5cf6, 0x06b6d4, 0xf97316, 0xec4899,
+];
+
+// Okabe & Ito, "Color Universal Design". Black is left out so the palette works on dark
+// backgrounds.
+const OKABE_ITO_COLORS: [u32; 7] = [
+    0x0072b2, 0xe69f00, 0x009e73, 0xcc79a7, 0x56b4e9, 0xd55e00, 0xf0e442,
+];
+
+// Paul Tol's qualitative schemes.
+const TOL_BRIGHT_COLORS: [u32; 7] = [
+    0x4477aa, 0xee6677, 0x228833, 0xccbb44, 0x66ccee, 0xaa3377, 0xbbbbbb,
+];
+const TOL_MUTED_COLORS: [u32; 9] = [
+    0x332288, 0x88ccee, 0x44aa99, 0x117733, 0x999933, 0xddcc77, 0xcc6677, 0x882255, 0xaa4499,
+];
+
+// IBM Design Library's color-blind-safe set.
+const IBM_COLORS: [u32; 5] = [0x648fff, 0x785ef0, 0xdc267f, 0xfe6100, 0xffb000];
+
+/// The colors given to series that don't set their own.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum ChartPalette {
+    #[default]
+    Default,
+    /// Okabe–Ito: safe for protanopia, deuteranopia and tritanopia.
+    OkabeIto,
+    /// Paul Tol's bright scheme.
+    TolBright,
+    /// Paul Tol's muted scheme, with more colors for charts with many series.
+    TolMuted,
+    /// IBM's five-color accessible palette.
+    Ibm,
+    Custom(Vec<Hsla>),
+}
+
+impl ChartPalette {
+    /// The color for series `index`, wrapping around when there are more series than
+    /// colors.
+    pub fn color(&self, index: usize) -> Hsla {
+        let hex = |colors: &[u32]| -> Hsla { rgb(colors[index % colors.len()]).into() };
+        match self {
+            ChartPalette::Default => hex(&DEFAULT_COLORS),
+            ChartPalette::OkabeIto => hex(&OKABE_ITO_COLORS),
+            ChartPalette::TolBright => hex(&TOL_BRIGHT_COLORS),
+            ChartPalette::TolMuted => hex(&TOL_MUTED_COLORS),
+            ChartPalette::Ibm => hex(&IBM_COLORS),
+            ChartPalette::Custom(colors) if !colors.is_empty() => colors[index % colors.len()],
+            ChartPalette::Custom(_) => hex(&DEFAULT_COLORS),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            ChartPalette::Default => DEFAULT_COLORS.len(),
+            ChartPalette::OkabeIto => OKABE_ITO_COLORS.len(),
+            ChartPalette::TolBright => TOL_BRIGHT_COLORS.len(),
+            ChartPalette::TolMuted => TOL_MUTED_COLORS.len(),
+            ChartPalette::Ibm => IBM_COLORS.len(),
+            ChartPalette::Custom(colors) => colors.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_color_blind_safe(&self) -> bool {
+        matches!(
+            self,
+            ChartPalette::OkabeIto
+                | ChartPalette::TolBright
+                | ChartPalette::TolMuted
+                | ChartPalette::Ibm
+        )
+    }
+}
+
+/// A texture painted over a bar or area fill.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PatternFill {
+    #[default]
+    Solid,
+    /// Lines rising to the right.
+    DiagonalHatch,
+    /// Lines falling to the right.
+    ReverseDiagonalHatch,
+    CrossHatch,
+    Horizontal,
+    Vertical,
+    Dots,
+}
+
+const PATTERN_SPACING: f32 = 6.0;
+
+impl PatternFill {
+    /// The pattern for series `index` when a chart assigns them automatically. The first
+    /// series stays solid.
+    pub fn for_series(index: usize) -> Self {
+        const CYCLE: [PatternFill; 7] = [
+            PatternFill::Solid,
+            PatternFill::DiagonalHatch,
+            PatternFill::Dots,
+            PatternFill::CrossHatch,
+            PatternFill::ReverseDiagonalHatch,
+            PatternFill::Horizontal,
+            PatternFill::Vertical,
+        ];
+        CYCLE[index % CYCLE.len()]
+    }
+
+    /// `explicit` if set, otherwise the automatic pattern for series `index` when `auto`
+    /// is on.
+    pub(crate) fn resolve(explicit: Option<PatternFill>, index: usize, auto: bool) -> Self {
+        explicit.unwrap_or(if auto {
+            PatternFill::for_series(index)
+        } else {
+            PatternFill::Solid
+        })
+    }
+
+    /// Paint the pattern over all of `bounds`.
+    pub fn paint(self, bounds: Bounds<Pixels>, color: Hsla, window: &mut Window) {
+        self.paint_within(bounds, color, |_| true, window);
+    }
+
+    /// Paint the pattern over the part of `bounds` where `inside` holds, for fills that
+    /// aren't rectangles such as chart areas.
+    pub fn paint_within(
+        self,
+        bounds: Bounds<Pixels>,
+        color: Hsla,
+        inside: impl Fn(Point<Pixels>) -> bool,
+        window: &mut Window,
+    ) {
+        if self == PatternFill::Solid || bounds.size.width <= px(0.0) {
+            return;
+        }
+        window.with_content_mask(Some(ContentMask { bounds }), |window| {
+            if self == PatternFill::Dots {
+                paint_dots(bounds, color, &inside, window);
+                return;
+            }
+            let mut builder = PathBuilder::stroke(px(1.0));
+            // Each direction is (dx, dy) along the line; lines are spaced across it.
+            let directions: &[(f32, f32)] = match self {
+                PatternFill::DiagonalHatch => &[(1.0, -1.0)],
+                PatternFill::ReverseDiagonalHatch => &[(1.0, 1.0)],
+                PatternFill::CrossHatch => &[(1.0, -1.0), (1.0, 1.0)],
+                PatternFill::Horizontal => &[(1.0, 0.0)],
+                _ => &[(0.0, 1.0)],
+            };
+            for &direction in directions {
+                add_hatch_lines(&mut builder, bounds, direction, &inside);
+            }
+            if let Ok(path) = builder.build() {
+                window.paint_path(path, color);
+            }
+        });
+    }
+}
+
+/// Add parallel lines running along `direction` across `bounds`, split into the runs
+/// where `inside` holds.
+fn add_hatch_lines(
+    builder: &mut PathBuilder,
+    bounds: Bounds<Pixels>,
+    (dx, dy): (f32, f32),
+    inside: &impl Fn(Point<Pixels>) -> bool,
+) {
+    let (left, top) = (f32::from(bounds.left()), f32::from(bounds.top()));
+    let (right, bottom) = (f32::from(bounds.right()), f32::from(bounds.bottom()));
+    let length = (dx * dx + dy * dy).sqrt();
+    let (ux, uy) = (dx / length, dy / length);
+    // The normal to the lines; offsets along it pick out each line.
+    let (nx, ny) = (-uy, ux);
+    let corners = [(left, top), (right, top), (left, bottom), (right, bottom)];
+    let project = |(x, y): (f32, f32), (ax, ay): (f32, f32)| x * ax + y * ay;
+    let (min_offset, max_offset) = corners.iter().fold((f32::MAX, f32::MIN), |acc, &c| {
+        let offset = project(c, (nx, ny));
+        (acc.0.min(offset), acc.1.max(offset))
+    });
+    let (min_along, max_along) = corners.iter().fold((f32::MAX, f32::MIN), |acc, &c| {
+        let along = project(c, (ux, uy));
+        (acc.0.min(along), acc.1.max(along))
+    });
+
+    // Lines sit on a grid anchored at the window origin so the texture of adjacent bars
+    // lines up.
+    let first = (min_offset / PATTERN_SPACING).floor() as i32;
+    let last = (max_offset / PATTERN_SPACING).ceil() as i32;
+    const STEP: f32 = 2.0;
+    for line in first..=last {
+        let offset = line as f32 * PATTERN_SPACING;
+        let at = |along: f32| point(px(nx * offset + ux * along), px(ny * offset + uy * along));
+        let mut run_start: Option<f32> = None;
+        let mut along = min_along;
+        while along <= max_along + STEP {
+            let is_inside = along <= max_along && inside(at(along));
+            match (run_start, is_inside) {
+                (None, true) => run_start = Some(along),
+                (Some(start), false) => {
+                    builder.move_to(at(start));
+                    builder.line_to(at(along - STEP));
+                    run_start = None;
+                }
+                _ => {}
+            }
+            along += STEP;
+        }
+    }
+}
+
+fn paint_dots(
+    bounds: Bounds<Pixels>,
+    color: Hsla,
+    inside: &impl Fn(Point<Pixels>) -> bool,
+    window: &mut Window,
+) {
+    let radius = px(1.25);
+    let spacing = px(PATTERN_SPACING);
+    let mut row = 0;
+    let mut y = bounds.top() + spacing / 2.0;
+    while y < bounds.bottom() {
+        // Alternate rows are offset by half a step for an even, staggered texture.
+        let mut x = bounds.left() + if row % 2 == 0 { spacing / 2.0 } else { spacing };
+        while x < bounds.right() {
+            let center = point(x, y);
+            if inside(center) {
+                window.paint_quad(
+                    fill(
+                        Bounds::centered_at(center, size(radius * 2.0, radius * 2.0)),
+                        color,
+                    )
+                    .corner_radii(radius),
+                );
+            }
+            x += spacing;
+        }
+        y += spacing;
+        row += 1;
+    }
+}
+
+/// An element covering its (relatively positioned) parent with `pattern` in `color`,
+/// for bars and legend swatches built from divs.
+pub fn pattern_overlay(pattern: PatternFill, color: Hsla) -> impl IntoElement {
+    canvas(
+        |_, _, _| (),
+        move |bounds, _, window, _| pattern.paint(bounds, color, window),
+    )
+    .absolute()
+    .top_0()
+    .left_0()
+    .size_full()
+}
+
+/// Paint `pattern` over the area between the `top` and `bottom` polylines.
+pub(crate) fn paint_area_pattern(
+    pattern: PatternFill,
+    top: &[Point<Pixels>],
+    bottom: &[Point<Pixels>],
+    color: Hsla,
+    window: &mut Window,
+) {
+    if pattern == PatternFill::Solid {
+        return;
+    }
+    let outline: Vec<Point<Pixels>> = top.iter().chain(bottom).copied().collect();
+    pattern.paint_within(
+        points_bounds(&outline),
+        color,
+        |p| match (polyline_y_at(top, p.x), polyline_y_at(bottom, p.x)) {
+            (Some(top_y), Some(bottom_y)) => p.y >= top_y && p.y <= bottom_y,
+            _ => false,
+        },
+        window,
+    );
+}
+
+/// The y of the polyline `points` (sorted by x) at `x`, if `x` is within its span.
+pub(crate) fn polyline_y_at(points: &[Point<Pixels>], x: Pixels) -> Option<Pixels> {
+    let (first, last) = (points.first()?, points.last()?);
+    if x < first.x || x > last.x {
+        return None;
+    }
+    points.windows(2).find_map(|pair| {
+        let (a, b) = (pair[0], pair[1]);
+        if x < a.x || x > b.x {
+            return None;
+        }
+        if b.x - a.x <= px(0.0) {
+            return Some(a.y);
+        }
+        Some(a.y + (b.y - a.y) * ((x - a.x) / (b.x - a.x)))
+    })
+}
+
+/// Bounds around `points`.
+pub(crate) fn points_bounds(points: &[Point<Pixels>]) -> Bounds<Pixels> {
+    let Some(first) = points.first() else {
+        return Bounds::default();
+    };
+    let (mut min, mut max) = (*first, *first);
+    for point in points {
+        min = min.min(point);
+        max = max.max(point);
+    }
+    Bounds::from_corners(min, max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn palettes_wrap_and_interpolate_areas() {
+        let palette = ChartPalette::OkabeIto;
+        assert_eq!(palette.color(0), palette.color(palette.len()));
+        assert!(palette.is_color_blind_safe());
+        assert_eq!(PatternFill::for_series(0), PatternFill::Solid);
+        assert_ne!(PatternFill::for_series(1), PatternFill::Solid);
+
+        let line = [
+            point(px(0.0), px(10.0)),
+            point(px(10.0), px(20.0)),
+            point(px(20.0), px(0.0)),
+        ];
+        assert_eq!(polyline_y_at(&line, px(5.0)), Some(px(15.0)));
+        assert_eq!(polyline_y_at(&line, px(15.0)), Some(px(10.0)));
+        assert_eq!(polyline_y_at(&line, px(25.0)), None);
+        let bounds = points_bounds(&line);
+        assert_eq!(bounds.size, size(px(20.0), px(20.0)));
+    }
+}
@@ -1,16 +1,77 @@
-use crate::theme::use_theme;
+use super::description::{
+    format_value, Arrangement, ChartDataTable, ChartDescription, Describe, SeriesSummary,
+};
+use super::palette::ChartPalette;
+use crate::svg_export::{ExportSvg, PathSink, SvgDocument, SvgPath, SvgTextAnchor, SvgTextStyle};
+use crate::theme::{use_theme, Theme};
 use gpui::{prelude::FluentBuilder as _, *};
 
-const CHART_COLORS: [u32; 8] = [
-    0x3b82f6, 0x22c55e, 0xf59e0b, 0xef4444, 0x8b5cf6, 0x06b6d4, 0xf97316, 0xec4899,
-];
+fn pixels_to_f32(p: Pixels) -> f32 {
+    p / px(1.0)
+}
 
-fn default_color(index: usize) -> Hsla {
-    rgb(CHART_COLORS[index % CHART_COLORS.len()]).into()
+/// Gap between the chart and its legend.
+const LEGEND_SPACING: f32 = 24.0;
+const LEGEND_ROW_GAP: f32 = 8.0;
+const LEGEND_SWATCH: f32 = 12.0;
+/// Gap after the swatch, and between a label and its percentage.
+const LEGEND_SWATCH_GAP: f32 = 8.0;
+const LEGEND_VALUE_GAP: f32 = 12.0;
+const LEGEND_TEXT_SIZE: f32 = 14.0;
+const LEGEND_LINE_HEIGHT: f32 = 20.0;
+
+/// Start angle, sweep and color of each visible segment, clockwise from twelve
+/// o'clock. Painting and SVG export both slice the pie from these.
+fn slices(
+    segments: &[PieChartSegment],
+    total: f64,
+    palette: &ChartPalette,
+) -> Vec<(f32, f32, Hsla)> {
+    let mut slices = Vec::new();
+    let mut current_angle: f32 = -std::f32::consts::FRAC_PI_2;
+    for (idx, segment) in segments.iter().enumerate() {
+        if segment.value <= 0.0 {
+            continue;
+        }
+        let sweep_angle = (segment.value / total) as f32 * std::f32::consts::TAU;
+        let color = segment.color.unwrap_or_else(|| palette.color(idx));
+        slices.push((current_angle, sweep_angle, color));
+        current_angle += sweep_angle;
+    }
+    slices
 }
 
-fn pixels_to_f32(p: Pixels) -> f32 {
-    p / px(1.0)
+/// The radius of the hole in a donut of `outer_radius`, or zero for a pie.
+fn inner_radius(outer_radius: f32, variant: PieChartVariant, donut_thickness: f32) -> f32 {
+    if variant == PieChartVariant::Donut {
+        outer_radius * (1.0 - donut_thickness)
+    } else {
+        0.0
+    }
+}
+
+/// One slice, or a ring section when `inner_radius` isn't zero.
+fn trace_slice(
+    path: &mut impl PathSink,
+    center: Point<Pixels>,
+    radius: Pixels,
+    inner_radius: Pixels,
+    (start, sweep): (f32, f32),
+) {
+    let at = |angle: f32, r: Pixels| point(center.x + r * angle.cos(), center.y + r * angle.sin());
+    let end = start + sweep;
+    let large_arc = sweep > std::f32::consts::PI;
+    if inner_radius > px(0.0) {
+        path.move_to(at(start, radius));
+        path.arc_to(radius, large_arc, true, at(end, radius));
+        path.line_to(at(end, inner_radius));
+        path.arc_to(inner_radius, large_arc, false, at(start, inner_radius));
+    } else {
+        path.move_to(center);
+        path.line_to(at(start, radius));
+        path.arc_to(radius, large_arc, true, at(end, radius));
+    }
+    path.close();
 }
 
 #[derive(Clone)]
@@ -78,6 +139,7 @@ pub struct PieChart {
     center_label: Option<SharedString>,
     size: PieChartSize,
     donut_thickness: f32,
+    palette: ChartPalette,
     style: StyleRefinement,
 }
 
@@ -91,6 +153,7 @@ impl PieChart {
             center_label: None,
             size: PieChartSize::Md,
             donut_thickness: 0.35,
+            palette: ChartPalette::default(),
             style: StyleRefinement::default(),
         }
     }
@@ -137,6 +200,11 @@ impl PieChart {
         self.label_position = position;
         self
     }
+
+    pub fn palette(mut self, palette: ChartPalette) -> Self {
+        self.palette = palette;
+        self
+    }
 }
 
 impl Styled for PieChart {
@@ -147,6 +215,7 @@ impl Styled for PieChart {
 
 impl RenderOnce for PieChart {
     fn render(self, _window: &mut Window, _cx: &mut App) -> impl IntoElement {
+        let _span = tracing::trace_span!("chart.pie", segments = self.segments.len()).entered();
         let chart_size = self.size.to_pixels();
         let show_legend = self.label_position == PieChartLabelPosition::Legend;
         let show_percentages = self.show_percentages;
@@ -164,18 +233,24 @@ impl RenderOnce for PieChart {
                 self.variant,
                 self.donut_thickness,
                 self.center_label.clone(),
+                &self.palette,
             )
         };
 
         let legend = if show_legend {
-            Some(render_legend(&self.segments, total, show_percentages))
+            Some(render_legend(
+                &self.segments,
+                total,
+                show_percentages,
+                &self.palette,
+            ))
         } else {
             None
         };
 
         div()
             .flex()
-            .gap(px(24.0))
+            .gap(px(LEGEND_SPACING))
             .items_center()
             .child(chart)
             .when_some(legend, |this, legend| this.child(legend))
@@ -194,30 +269,12 @@ fn render_pie_chart(
     variant: PieChartVariant,
     donut_thickness: f32,
     center_label: Option<SharedString>,
+    palette: &ChartPalette,
 ) -> Div {
     let theme = use_theme();
-    let size_f32 = pixels_to_f32(chart_size);
-    let center = size_f32 * 0.5;
-    let outer_radius = size_f32 * 0.5;
-    let inner_radius = if variant == PieChartVariant::Donut {
-        outer_radius * (1.0 - donut_thickness)
-    } else {
-        0.0
-    };
-
-    let mut segment_data: Vec<(f32, f32, Hsla)> = Vec::new();
-    let mut current_angle: f32 = -std::f32::consts::FRAC_PI_2;
-
-    for (idx, segment) in segments.iter().enumerate() {
-        if segment.value <= 0.0 {
-            continue;
-        }
-        let fraction = (segment.value / total) as f32;
-        let sweep_angle = fraction * std::f32::consts::TAU;
-        let color = segment.color.unwrap_or_else(|| default_color(idx));
-        segment_data.push((current_angle, sweep_angle, color));
-        current_angle += sweep_angle;
-    }
+    let outer_radius = pixels_to_f32(chart_size) * 0.5;
+    let inner_radius = inner_radius(outer_radius, variant, donut_thickness);
+    let segment_data = slices(segments, total, palette);
 
     if segment_data.len() == 1 {
         return render_single_segment(
@@ -229,89 +286,52 @@ fn render_pie_chart(
         );
     }
 
-    let ring_width = outer_radius - inner_radius;
-    let ring_count = ((ring_width / 3.0).max(1.0) as usize).min(20);
-
-    let mut container = div()
-        .size(chart_size)
-        .rounded(px(9999.0))
-        .relative()
-        .overflow_hidden();
-
-    for ring_idx in 0..ring_count {
-        let ring_radius = inner_radius + (ring_idx as f32 + 0.5) * (ring_width / ring_count as f32);
-        let circumference = std::f32::consts::TAU * ring_radius;
-        let dots_in_ring = (circumference / 4.0).max(16.0) as usize;
-
-        for i in 0..dots_in_ring {
-            let angle = -std::f32::consts::FRAC_PI_2
-                + (i as f32 / dots_in_ring as f32) * std::f32::consts::TAU;
-            let color = get_color_at_angle(angle, &segment_data);
-            let x = center + ring_radius * angle.cos() - 2.0;
-            let y = center + ring_radius * angle.sin() - 2.0;
+    let mut container = div().size(chart_size).relative().child(
+        canvas(
+            |_, _, _| {},
+            move |bounds, _, window, _| {
+                let center = bounds.center();
+                for &(start, sweep, color) in &segment_data {
+                    let mut builder = PathBuilder::fill();
+                    trace_slice(
+                        &mut builder,
+                        center,
+                        px(outer_radius),
+                        px(inner_radius),
+                        (start, sweep),
+                    );
+                    if let Ok(path) = builder.build() {
+                        window.paint_path(path, color);
+                    }
+                }
+            },
+        )
+        .size_full(),
+    );
 
-            container = container.child(
+    if variant == PieChartVariant::Donut {
+        container = container.when_some(center_label, |this, label| {
+            this.child(
                 div()
                     .absolute()
-                    .size(px(5.0))
-                    .rounded(px(9999.0))
-                    .bg(color)
-                    .left(px(x))
-                    .top(px(y)),
-            );
-        }
-    }
-
-    if variant == PieChartVariant::Donut {
-        let inner_size = inner_radius * 2.0 - 4.0;
-        let inner_offset = center - inner_radius + 2.0;
-
-        container = container.child(
-            div()
-                .absolute()
-                .size(px(inner_size))
-                .rounded(px(9999.0))
-                .bg(theme.tokens.background)
-                .left(px(inner_offset))
-                .top(px(inner_offset))
-                .flex()
-                .items_center()
-                .justify_center()
-                .when_some(center_label, |this, label| {
-                    this.child(
+                    .inset_0()
+                    .flex()
+                    .items_center()
+                    .justify_center()
+                    .child(
                         div()
                             .text_sm()
                             .font_weight(FontWeight::SEMIBOLD)
                             .text_color(theme.tokens.foreground)
                             .child(label),
-                    )
-                }),
-        );
+                    ),
+            )
+        });
     }
 
     container
 }
 
-fn get_color_at_angle(angle: f32, segment_data: &[(f32, f32, Hsla)]) -> Hsla {
-    let normalized_angle = if angle < -std::f32::consts::FRAC_PI_2 {
-        angle + std::f32::consts::TAU
-    } else {
-        angle
-    };
-
-    for &(start_angle, sweep_angle, color) in segment_data {
-        let end_angle = start_angle + sweep_angle;
-        if normalized_angle >= start_angle && normalized_angle < end_angle {
-            return color;
-        }
-    }
-
-    segment_data
-        .last()
-        .map(|&(_, _, c)| c)
-        .unwrap_or(hsla(0.0, 0.0, 0.5, 1.0))
-}
-
 fn render_single_segment(
     chart_size: Pixels,
     color: Hsla,
@@ -377,19 +397,21 @@ fn render_empty_chart(chart_size: Pixels) -> Div {
         )
 }
 
-fn render_legend(segments: &[PieChartSegment], total: f64, show_percentages: bool) -> Div {
+fn render_legend(
+    segments: &[PieChartSegment],
+    total: f64,
+    show_percentages: bool,
+    palette: &ChartPalette,
+) -> Div {
     let theme = use_theme();
 
-    div()
-        .flex()
-        .flex_col()
-        .gap(px(8.0))
-        .children(segments.iter().enumerate().filter_map(|(idx, segment)| {
+    div().flex().flex_col().gap(px(LEGEND_ROW_GAP)).children(
+        segments.iter().enumerate().filter_map(|(idx, segment)| {
             if segment.value <= 0.0 {
                 return None;
             }
 
-            let color = segment.color.unwrap_or_else(|| default_color(idx));
+            let color = segment.color.unwrap_or_else(|| palette.color(idx));
             let percentage = if total > 0.0 {
                 (segment.value / total * 100.0) as u32
             } else {
@@ -400,30 +422,169 @@ fn render_legend(segments: &[PieChartSegment], total: f64, show_percentages: boo
                 div()
                     .flex()
                     .items_center()
-                    .gap(px(8.0))
-                    .child(div().size(px(12.0)).rounded(px(2.0)).bg(color))
+                    .gap(px(LEGEND_SWATCH_GAP))
+                    .child(div().size(px(LEGEND_SWATCH)).rounded(px(2.0)).bg(color))
                     .child(
                         div()
                             .flex()
                             .flex_1()
                             .items_center()
                             .justify_between()
-                            .gap(px(12.0))
+                            .gap(px(LEGEND_VALUE_GAP))
+                            .text_size(px(LEGEND_TEXT_SIZE))
+                            .line_height(px(LEGEND_LINE_HEIGHT))
                             .child(
                                 div()
-                                    .text_sm()
                                     .text_color(theme.tokens.foreground)
                                     .child(segment.label.clone()),
                             )
                             .when(show_percentages, |this| {
                                 this.child(
                                     div()
-                                        .text_sm()
                                         .text_color(theme.tokens.muted_foreground)
                                         .child(format!("{}%", percentage)),
                                 )
                             }),
                     ),
             )
-        }))
+        }),
+    )
+}
+
+impl ExportSvg for PieChart {
+    fn export_svg(&self, bounds: Bounds<Pixels>, theme: &Theme, svg: &mut SvgDocument) {
+        let chart_size = self.size.to_pixels().min(bounds.size.height);
+        let radius = chart_size * 0.5;
+        let center = point(
+            bounds.left() + radius,
+            bounds.top() + bounds.size.height * 0.5,
+        );
+        let inner_radius = px(inner_radius(
+            pixels_to_f32(radius),
+            self.variant,
+            self.donut_thickness,
+        ));
+        let total: f64 = self.segments.iter().map(|s| s.value).sum();
+        let text_style = |color| SvgTextStyle::new(px(LEGEND_TEXT_SIZE), color);
+
+        if total == 0.0 || self.segments.is_empty() {
+            svg.circle(center, radius, theme.tokens.muted);
+            let style = text_style(theme.tokens.muted_foreground).anchor(SvgTextAnchor::Middle);
+            svg.text(point(center.x, center.y + px(5.0)), "No data", &style);
+            return;
+        }
+
+        let slices = slices(&self.segments, total, &self.palette);
+        svg.begin_group(Some("segments"));
+        if let [(_, _, color)] = slices.as_slice() {
+            svg.circle(center, radius, *color);
+            if inner_radius > px(0.0) {
+                svg.circle(center, inner_radius, theme.tokens.background);
+            }
+        } else {
+            for &(start, sweep, color) in &slices {
+                let mut path = SvgPath::new();
+                trace_slice(&mut path, center, radius, inner_radius, (start, sweep));
+                svg.path(&path, Some(color), None);
+            }
+        }
+        if inner_radius > px(0.0) {
+            if let Some(label) = &self.center_label {
+                let style = text_style(theme.tokens.foreground)
+                    .weight(FontWeight::SEMIBOLD)
+                    .anchor(SvgTextAnchor::Middle);
+                svg.text(point(center.x, center.y + px(5.0)), label, &style);
+            }
+        }
+        svg.end_group();
+
+        if self.label_position == PieChartLabelPosition::Legend {
+            let rows: Vec<(&PieChartSegment, Hsla, String)> = self
+                .segments
+                .iter()
+                .filter(|segment| segment.value > 0.0)
+                .zip(&slices)
+                .map(|(segment, &(_, _, color))| {
+                    let percentage = (segment.value / total * 100.0) as u32;
+                    (segment, color, format!("{}%", percentage))
+                })
+                .collect();
+
+            // The legend is a column as wide as its widest row, with percentages
+            // pushed to its right edge.
+            let label_style = text_style(theme.tokens.foreground);
+            let value_style = text_style(theme.tokens.muted_foreground).anchor(SvgTextAnchor::End);
+            let text_left = px(LEGEND_SWATCH + LEGEND_SWATCH_GAP);
+            let width = rows
+                .iter()
+                .map(|(segment, _, percentage)| {
+                    let mut width = text_left + svg.text_width(&segment.label, &label_style);
+                    if self.show_percentages {
+                        width += px(LEGEND_VALUE_GAP) + svg.text_width(percentage, &value_style);
+                    }
+                    width
+                })
+                .fold(px(0.0), |widest, width| widest.max(width));
+            let height =
+                LEGEND_LINE_HEIGHT * rows.len() as f32 + LEGEND_ROW_GAP * (rows.len() as f32 - 1.0);
+
+            svg.begin_group(Some("legend"));
+            let left = center.x + radius + px(LEGEND_SPACING);
+            let mut middle = center.y - px(height * 0.5 - LEGEND_LINE_HEIGHT * 0.5);
+            for (segment, color, percentage) in &rows {
+                let swatch = Bounds::new(
+                    point(left, middle - px(LEGEND_SWATCH * 0.5)),
+                    size(px(LEGEND_SWATCH), px(LEGEND_SWATCH)),
+                );
+                svg.rect(swatch, px(2.0), Some(*color), None);
+                let baseline = middle + px(LEGEND_TEXT_SIZE * 0.35);
+                svg.text(
+                    point(left + text_left, baseline),
+                    &segment.label,
+                    &label_style,
+                );
+                if self.show_percentages {
+                    svg.text(point(left + width, baseline), percentage, &value_style);
+                }
+                middle += px(LEGEND_LINE_HEIGHT + LEGEND_ROW_GAP);
+            }
+            svg.end_group();
+        }
+    }
+}
+
+/// A table and part-to-whole description of pie or donut segments.
+pub(crate) fn describe_segments(kind: &str, segments: &[PieChartSegment]) -> ChartDescription {
+    let total: f64 = segments.iter().map(|segment| segment.value).sum();
+    let mut table = ChartDataTable::new(["Segment", "Value", "Share"]);
+    for segment in segments {
+        let share = if total == 0.0 {
+            0.0
+        } else {
+            segment.value / total * 100.0
+        };
+        table.push_row([
+            segment.label.clone(),
+            format_value(segment.value).into(),
+            format!("{:.1}%", share).into(),
+        ]);
+    }
+    ChartDescription::new(kind.to_string(), table)
+        .series(SeriesSummary::new(
+            "Segments",
+            segments
+                .iter()
+                .map(|segment| (segment.label.clone(), segment.value)),
+        ))
+        .arrangement(Arrangement::PartsOfWhole)
+}
+
+impl Describe for PieChart {
+    fn describe(&self) -> ChartDescription {
+        let kind = match self.variant {
+            PieChartVariant::Donut => "Donut chart",
+            _ => "Pie chart",
+        };
+        describe_segments(kind, &self.segments)
+    }
 }
@@ -1,14 +1,8 @@
+use super::description::{Arrangement, ChartDataTable, ChartDescription, Describe, KeyedSeries};
+use super::palette::ChartPalette;
 use crate::theme::use_theme;
 use gpui::{prelude::FluentBuilder as _, *};
 
-const CHART_COLORS: [u32; 8] = [
-    0x3b82f6, 0x22c55e, 0xf59e0b, 0xef4444, 0x8b5cf6, 0x06b6d4, 0xf97316, 0xec4899,
-];
-
-fn default_color(index: usize) -> Hsla {
-    rgb(CHART_COLORS[index % CHART_COLORS.len()]).into()
-}
-
 #[derive(Clone)]
 pub struct RadarDataset {
     pub label: SharedString,
@@ -58,6 +52,7 @@ struct PaintData {
     grid_levels: usize,
     fill_opacity: f32,
     grid_color: Hsla,
+    palette: ChartPalette,
     _text_color: Hsla,
     label_padding: f32,
 }
@@ -71,6 +66,7 @@ pub struct RadarChart {
     show_legend: bool,
     grid_levels: usize,
     fill_opacity: f32,
+    palette: ChartPalette,
     style: StyleRefinement,
 }
 
@@ -84,6 +80,7 @@ impl RadarChart {
             show_legend: true,
             grid_levels: 5,
             fill_opacity: 0.2,
+            palette: ChartPalette::default(),
             style: StyleRefinement::default(),
         }
     }
@@ -127,6 +124,11 @@ impl RadarChart {
         self.fill_opacity = opacity.clamp(0.0, 1.0);
         self
     }
+
+    pub fn palette(mut self, palette: ChartPalette) -> Self {
+        self.palette = palette;
+        self
+    }
 }
 
 impl Styled for RadarChart {
@@ -146,6 +148,7 @@ impl RenderOnce for RadarChart {
         let chart_size = self.size.to_pixels();
         let show_legend = self.show_legend && self.datasets.len() > 1;
         let datasets_for_legend = self.datasets.clone();
+        let legend_palette = self.palette.clone();
         let text_color = theme.tokens.muted_foreground;
         let label_padding: f32 = 30.0;
 
@@ -178,6 +181,7 @@ impl RenderOnce for RadarChart {
             grid_levels: self.grid_levels,
             fill_opacity: self.fill_opacity,
             grid_color: theme.tokens.border,
+            palette: self.palette,
             _text_color: text_color,
             label_padding,
         };
@@ -259,7 +263,8 @@ impl RenderOnce for RadarChart {
                                     if ds.values.is_empty() {
                                         continue;
                                     }
-                                    let color = ds.color.unwrap_or_else(|| default_color(ds_idx));
+                                    let color =
+                                        ds.color.unwrap_or_else(|| data.palette.color(ds_idx));
 
                                     let pts: Vec<Point<Pixels>> = (0..n)
                                         .map(|i| {
@@ -338,7 +343,7 @@ impl RenderOnce for RadarChart {
                         .py(px(8.0))
                         .justify_center()
                         .children(datasets_for_legend.iter().enumerate().map(|(i, ds)| {
-                            let color = ds.color.unwrap_or_else(|| default_color(i));
+                            let color = ds.color.unwrap_or_else(|| legend_palette.color(i));
                             div()
                                 .flex()
                                 .items_center()
@@ -356,3 +361,28 @@ impl RenderOnce for RadarChart {
             .into_any_element()
     }
 }
+
+impl Describe for RadarChart {
+    fn describe(&self) -> ChartDescription {
+        let series: Vec<KeyedSeries> = self
+            .datasets
+            .iter()
+            .map(|dataset| {
+                KeyedSeries::new(
+                    dataset.label.clone(),
+                    self.axes
+                        .iter()
+                        .zip(&dataset.values)
+                        .enumerate()
+                        .map(|(ix, (axis, value))| (ix as f64, axis.clone(), *value))
+                        .collect(),
+                )
+            })
+            .collect();
+        series.iter().fold(
+            ChartDescription::new("Radar chart", ChartDataTable::from_series("Axis", &series))
+                .arrangement(Arrangement::Categories),
+            |description, series| description.series(series.summary()),
+        )
+    }
+}
@@ -1,5 +1,8 @@
 //! Squarified treemap chart for hierarchical data visualization.
 
+use super::description::{
+    format_value, Arrangement, ChartDataTable, ChartDescription, Describe, SeriesSummary,
+};
 use crate::theme::use_theme;
 use gpui::{prelude::FluentBuilder as _, *};
 
@@ -319,6 +322,7 @@ impl Styled for TreeMap {
 
 impl RenderOnce for TreeMap {
     fn render(self, _window: &mut Window, _cx: &mut App) -> impl IntoElement {
+        let _span = tracing::trace_span!("chart.treemap", nodes = self.data.len()).entered();
         let theme = use_theme();
         let user_style = self.style;
         let data = self.data;
@@ -436,3 +440,32 @@ impl RenderOnce for TreeMap {
             )
     }
 }
+
+fn push_node_rows(table: &mut ChartDataTable, node: &TreeMapNode, path: &str) {
+    let path = if path.is_empty() {
+        node.label.to_string()
+    } else {
+        format!("{} / {}", path, node.label)
+    };
+    table.push_row([path.clone(), format_value(node.value)]);
+    for child in &node.children {
+        push_node_rows(table, child, &path);
+    }
+}
+
+impl Describe for TreeMap {
+    fn describe(&self) -> ChartDescription {
+        let mut table = ChartDataTable::new(["Item", "Value"]);
+        for node in &self.data {
+            push_node_rows(&mut table, node, "");
+        }
+        ChartDescription::new("Treemap", table)
+            .series(SeriesSummary::new(
+                "Top level",
+                self.data
+                    .iter()
+                    .map(|node| (node.label.clone(), node.value)),
+            ))
+            .arrangement(Arrangement::PartsOfWhole)
+    }
+}
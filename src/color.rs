@@ -0,0 +1,465 @@
+//! Color math for palettes and accessibility.
+//!
+//! Mixing and scale generation work in OKLCH, a perceptual space where equal steps in
+//! lightness look equal and hue stays put as colors lighten or darken, unlike HSL.
+//! Contrast follows WCAG 2.x relative luminance.
+//!
+//! ```rust,ignore
+//! let theme = use_theme();
+//! let series = series_palette(theme.tokens.primary, 6, theme.tokens.background);
+//! let label = ensure_contrast(series[2], theme.tokens.background, ContrastLevel::Aa.min_ratio());
+//! let fill = Gradient::linear(180.0)
+//!     .stop(0.0, series[0].opacity(0.4))
+//!     .stop(1.0, series[0].opacity(0.0));
+//! ```
+
+use gpui::{linear_color_stop, Background, Hsla, LinearColorStop, Rgba};
+
+/// A color in OKLCH: perceptual lightness `l` (0–1), chroma `c` (0 to about 0.37 for
+/// sRGB colors) and hue `h` in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Oklch {
+    pub l: f32,
+    pub c: f32,
+    pub h: f32,
+    pub a: f32,
+}
+
+impl Oklch {
+    pub fn new(l: f32, c: f32, h: f32, a: f32) -> Self {
+        Self { l, c, h, a }
+    }
+
+    fn to_linear_srgb(self) -> [f32; 3] {
+        let hue = self.h.to_radians();
+        let (a, b) = (self.c * hue.cos(), self.c * hue.sin());
+        let l = (self.l + 0.396_337_78 * a + 0.215_803_76 * b).powi(3);
+        let m = (self.l - 0.105_561_346 * a - 0.063_854_17 * b).powi(3);
+        let s = (self.l - 0.089_484_18 * a - 1.291_485_5 * b).powi(3);
+        [
+            4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s,
+            -1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s,
+            -0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s,
+        ]
+    }
+
+    fn in_gamut(self) -> bool {
+        self.to_linear_srgb()
+            .iter()
+            .all(|channel| (-1e-4..=1.0 + 1e-4).contains(channel))
+    }
+}
+
+impl From<Hsla> for Oklch {
+    fn from(color: Hsla) -> Self {
+        let rgba = Rgba::from(color);
+        let [r, g, b] = [rgba.r, rgba.g, rgba.b].map(srgb_to_linear);
+        let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+        let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+        let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+        let lightness = 0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s;
+        let a = 1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s;
+        let b = 0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s;
+        Self {
+            l: lightness,
+            c: (a * a + b * b).sqrt(),
+            h: b.atan2(a).to_degrees().rem_euclid(360.0),
+            a: color.a,
+        }
+    }
+}
+
+impl From<Oklch> for Hsla {
+    /// Colors outside sRGB are brought in by reducing chroma, which keeps lightness and
+    /// hue intact.
+    fn from(color: Oklch) -> Self {
+        let mut color = Oklch {
+            l: color.l.clamp(0.0, 1.0),
+            c: color.c.max(0.0),
+            ..color
+        };
+        if !color.in_gamut() {
+            let (mut low, mut high) = (0.0, color.c);
+            for _ in 0..20 {
+                let mid = (low + high) / 2.0;
+                if (Oklch { c: mid, ..color }).in_gamut() {
+                    low = mid;
+                } else {
+                    high = mid;
+                }
+            }
+            color.c = low;
+        }
+        let [r, g, b] = color
+            .to_linear_srgb()
+            .map(|channel| linear_to_srgb(channel.clamp(0.0, 1.0)));
+        Rgba {
+            r,
+            g,
+            b,
+            a: color.a,
+        }
+        .into()
+    }
+}
+
+fn srgb_to_linear(channel: f32) -> f32 {
+    if channel <= 0.040_45 {
+        channel / 12.92
+    } else {
+        ((channel + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(channel: f32) -> f32 {
+    if channel <= 0.003_130_8 {
+        channel * 12.92
+    } else {
+        1.055 * channel.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Interpolate from `from` to `to` in OKLCH, taking the shorter way around the hue
+/// circle. `t` is clamped to 0–1.
+pub fn mix(from: Hsla, to: Hsla, t: f32) -> Hsla {
+    let t = t.clamp(0.0, 1.0);
+    let (mut a, mut b) = (Oklch::from(from), Oklch::from(to));
+    // A gray has no meaningful hue; borrow the other color's so the mix doesn't swing
+    // through unrelated hues.
+    if a.c < 1e-4 {
+        a.h = b.h;
+    }
+    if b.c < 1e-4 {
+        b.h = a.h;
+    }
+    let mut hue_delta = b.h - a.h;
+    if hue_delta > 180.0 {
+        hue_delta -= 360.0;
+    } else if hue_delta < -180.0 {
+        hue_delta += 360.0;
+    }
+    Oklch {
+        l: a.l + (b.l - a.l) * t,
+        c: a.c + (b.c - a.c) * t,
+        h: (a.h + hue_delta * t).rem_euclid(360.0),
+        a: a.a + (b.a - a.a) * t,
+    }
+    .into()
+}
+
+/// WCAG relative luminance, ignoring alpha.
+pub fn relative_luminance(color: Hsla) -> f32 {
+    let rgba = Rgba::from(color);
+    let [r, g, b] = [rgba.r, rgba.g, rgba.b].map(srgb_to_linear);
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+/// WCAG contrast ratio between two opaque colors, from 1 to 21.
+pub fn contrast_ratio(a: Hsla, b: Hsla) -> f32 {
+    let (la, lb) = (relative_luminance(a), relative_luminance(b));
+    let (lighter, darker) = if la > lb { (la, lb) } else { (lb, la) };
+    (lighter + 0.05) / (darker + 0.05)
+}
+
+/// WCAG 2.x contrast requirements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContrastLevel {
+    /// Large text and graphical objects such as chart marks and focus rings (3:1).
+    AaLarge,
+    /// Body text (4.5:1).
+    Aa,
+    /// Enhanced contrast for body text (7:1).
+    Aaa,
+}
+
+impl ContrastLevel {
+    pub fn min_ratio(self) -> f32 {
+        match self {
+            ContrastLevel::AaLarge => 3.0,
+            ContrastLevel::Aa => 4.5,
+            ContrastLevel::Aaa => 7.0,
+        }
+    }
+}
+
+pub fn meets_contrast(foreground: Hsla, background: Hsla, level: ContrastLevel) -> bool {
+    contrast_ratio(foreground, background) >= level.min_ratio()
+}
+
+/// `foreground` with its lightness moved just far enough from `background` to reach
+/// `min_ratio`, keeping hue and as much chroma as fits. Returned unchanged if it already
+/// passes; if the ratio can't be reached, the closest of black or white wins.
+pub fn ensure_contrast(foreground: Hsla, background: Hsla, min_ratio: f32) -> Hsla {
+    if contrast_ratio(foreground, background) >= min_ratio {
+        return foreground;
+    }
+    let base = Oklch::from(foreground);
+    let with_lightness = |l: f32| -> Hsla { Oklch { l, ..base }.into() };
+
+    // Go darker on light backgrounds and lighter on dark ones, falling back to the
+    // other direction when the preferred one can't get there.
+    let darker_first = relative_luminance(background) > 0.18;
+    let directions = if darker_first { [0.0, 1.0] } else { [1.0, 0.0] };
+    for target in directions {
+        if contrast_ratio(with_lightness(target), background) < min_ratio {
+            continue;
+        }
+        let (mut passing, mut failing) = (target, base.l);
+        for _ in 0..24 {
+            let mid = (passing + failing) / 2.0;
+            if contrast_ratio(with_lightness(mid), background) >= min_ratio {
+                passing = mid;
+            } else {
+                failing = mid;
+            }
+        }
+        return with_lightness(passing);
+    }
+
+    let (black, white) = (gpui::black(), gpui::white());
+    if contrast_ratio(black, background) >= contrast_ratio(white, background) {
+        black.opacity(foreground.a)
+    } else {
+        white.opacity(foreground.a)
+    }
+}
+
+/// `steps` tints and shades of `seed` from near-white to near-black with even
+/// perceptual spacing, like a design system's 50–950 scale. Chroma tapers toward both
+/// ends so the lightest and darkest steps don't look neon.
+pub fn color_scale(seed: Hsla, steps: usize) -> Vec<Hsla> {
+    let seed = Oklch::from(seed);
+    let (lightest, darkest) = (0.97, 0.25);
+    (0..steps)
+        .map(|step| {
+            let t = if steps > 1 {
+                step as f32 / (steps - 1) as f32
+            } else {
+                0.5
+            };
+            let taper = 1.0 - (2.0 * t - 1.0).powi(2) * 0.7;
+            Oklch {
+                l: lightest + (darkest - lightest) * t,
+                c: seed.c * taper,
+                ..seed
+            }
+            .into()
+        })
+        .collect()
+}
+
+/// `count` distinct colors for chart series, starting at `seed`. Hues are spread by the
+/// golden angle at the seed's lightness and chroma, so neighbouring series differ
+/// clearly however many there are, and each is adjusted to 3:1 against `background`.
+pub fn series_palette(seed: Hsla, count: usize, background: Hsla) -> Vec<Hsla> {
+    const GOLDEN_ANGLE: f32 = 137.507_77;
+    let base = Oklch::from(seed);
+    // Grays have no hue to rotate; give them enough chroma to tell series apart.
+    let chroma = base.c.max(0.1);
+    (0..count)
+        .map(|index| {
+            let color = if index == 0 {
+                seed
+            } else {
+                Oklch {
+                    c: chroma,
+                    h: (base.h + GOLDEN_ANGLE * index as f32).rem_euclid(360.0),
+                    ..base
+                }
+                .into()
+            };
+            ensure_contrast(color, background, ContrastLevel::AaLarge.min_ratio())
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GradientKind {
+    /// Angle in degrees, 0 pointing up and increasing clockwise.
+    Linear { angle: f32 },
+    /// Center as a fraction of the filled bounds, radius as a fraction of their size.
+    Radial {
+        center_x: f32,
+        center_y: f32,
+        radius: f32,
+    },
+}
+
+/// A multi-stop gradient usable as an element background or a path fill.
+///
+/// The GPU interpolates between stops in sRGB. [`Gradient::color_at`] interpolates in
+/// OKLCH, and [`Gradient::smoothed`] adds stops so the painted result follows it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    pub kind: GradientKind,
+    stops: Vec<(f32, Hsla)>,
+}
+
+impl Gradient {
+    pub fn linear(angle: f32) -> Self {
+        Self {
+            kind: GradientKind::Linear { angle },
+            stops: Vec::new(),
+        }
+    }
+
+    pub fn radial(center_x: f32, center_y: f32, radius: f32) -> Self {
+        Self {
+            kind: GradientKind::Radial {
+                center_x,
+                center_y,
+                radius,
+            },
+            stops: Vec::new(),
+        }
+    }
+
+    /// Add a stop at `position` (0–1). Stops may be added in any order.
+    pub fn stop(mut self, position: f32, color: Hsla) -> Self {
+        let position = position.clamp(0.0, 1.0);
+        let index = self.stops.partition_point(|(p, _)| *p <= position);
+        self.stops.insert(index, (position, color));
+        self
+    }
+
+    /// Evenly spaced stops through `colors`.
+    pub fn colors(mut self, colors: &[Hsla]) -> Self {
+        let last = colors.len().saturating_sub(1).max(1) as f32;
+        for (index, color) in colors.iter().enumerate() {
+            self = self.stop(index as f32 / last, *color);
+        }
+        self
+    }
+
+    pub fn stops(&self) -> &[(f32, Hsla)] {
+        &self.stops
+    }
+
+    /// The color at `t` (0–1), interpolated between the surrounding stops in OKLCH.
+    pub fn color_at(&self, t: f32) -> Hsla {
+        let Some(&(first_position, first_color)) = self.stops.first() else {
+            return gpui::transparent_black();
+        };
+        if t <= first_position {
+            return first_color;
+        }
+        for pair in self.stops.windows(2) {
+            let ((p0, c0), (p1, c1)) = (pair[0], pair[1]);
+            if t <= p1 {
+                let span = p1 - p0;
+                let local = if span > 0.0 { (t - p0) / span } else { 1.0 };
+                return mix(c0, c1, local);
+            }
+        }
+        self.stops[self.stops.len() - 1].1
+    }
+
+    /// The same gradient with `per_segment - 1` extra stops between each pair, sampled
+    /// in OKLCH, so the painted gradient avoids the muddy midpoints of sRGB blending.
+    pub fn smoothed(&self, per_segment: usize) -> Self {
+        let per_segment = per_segment.max(1);
+        let mut stops = Vec::new();
+        for pair in self.stops.windows(2) {
+            let (p0, p1) = (pair[0].0, pair[1].0);
+            for step in 0..per_segment {
+                let position = p0 + (p1 - p0) * step as f32 / per_segment as f32;
+                stops.push((position, self.color_at(position)));
+            }
+        }
+        if let Some(&last) = self.stops.last() {
+            stops.push(last);
+        }
+        Self {
+            kind: self.kind,
+            stops,
+        }
+    }
+
+    fn color_stops(&self) -> Vec<LinearColorStop> {
+        match self.stops.as_slice() {
+            [] => vec![
+                linear_color_stop(gpui::transparent_black(), 0.0),
+                linear_color_stop(gpui::transparent_black(), 1.0),
+            ],
+            [(_, color)] => vec![
+                linear_color_stop(*color, 0.0),
+                linear_color_stop(*color, 1.0),
+            ],
+            stops => stops
+                .iter()
+                .map(|(position, color)| linear_color_stop(*color, *position))
+                .collect(),
+        }
+    }
+}
+
+impl From<Gradient> for Background {
+    fn from(gradient: Gradient) -> Self {
+        let stops = gradient.color_stops();
+        match gradient.kind {
+            GradientKind::Linear { angle } => gpui::multi_stop_linear_gradient(angle, &stops),
+            GradientKind::Radial {
+                center_x,
+                center_y,
+                radius,
+            } => gpui::radial_gradient(center_x, center_y, radius, &stops),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::hsla;
+
+    fn approx(a: f32, b: f32, tolerance: f32) -> bool {
+        (a - b).abs() <= tolerance
+    }
+
+    #[test]
+    fn oklch_round_trips_through_hsla() {
+        let orange = hsla(30.0 / 360.0, 0.9, 0.55, 1.0);
+        let back = Rgba::from(Hsla::from(Oklch::from(orange)));
+        let original = Rgba::from(orange);
+        assert!(approx(back.r, original.r, 0.002));
+        assert!(approx(back.g, original.g, 0.002));
+        assert!(approx(back.b, original.b, 0.002));
+
+        let white = Oklch::from(gpui::white());
+        assert!(approx(white.l, 1.0, 0.001) && white.c < 0.001);
+    }
+
+    #[test]
+    fn contrast_matches_wcag() {
+        let ratio = contrast_ratio(gpui::black(), gpui::white());
+        assert!(approx(ratio, 21.0, 0.01));
+
+        let background = gpui::white();
+        let pale = hsla(210.0 / 360.0, 0.8, 0.75, 1.0);
+        assert!(!meets_contrast(pale, background, ContrastLevel::Aa));
+        let fixed = ensure_contrast(pale, background, 4.5);
+        assert!(contrast_ratio(fixed, background) >= 4.5);
+        assert!(contrast_ratio(fixed, background) < 4.7);
+        assert!(approx(Oklch::from(fixed).h, Oklch::from(pale).h, 3.0));
+    }
+
+    #[test]
+    fn scales_and_gradients_interpolate_perceptually() {
+        let scale = color_scale(hsla(0.6, 0.7, 0.5, 1.0), 5);
+        assert_eq!(scale.len(), 5);
+        let lightness: Vec<f32> = scale.iter().map(|c| Oklch::from(*c).l).collect();
+        assert!(lightness.windows(2).all(|pair| pair[0] > pair[1]));
+
+        let gradient = Gradient::linear(90.0)
+            .stop(1.0, gpui::white())
+            .stop(0.0, gpui::black());
+        assert_eq!(gradient.stops()[0].0, 0.0);
+        assert!(approx(Oklch::from(gradient.color_at(0.5)).l, 0.5, 0.01));
+        assert_eq!(gradient.smoothed(4).stops().len(), 5);
+
+        let series = series_palette(hsla(0.6, 0.7, 0.5, 1.0), 4, gpui::white());
+        assert!(series
+            .iter()
+            .all(|c| contrast_ratio(*c, gpui::white()) >= 2.99));
+    }
+}
@@ -0,0 +1,307 @@
+//! API key field that keeps the key in the OS keychain instead of app settings.
+
+use crate::components::button::{Button, ButtonSize, ButtonVariant};
+use crate::components::icon::Icon;
+use crate::components::icon_button::IconButton;
+use crate::components::input::Input;
+use crate::components::input_state::{InputEvent, InputState};
+use crate::components::text::caption;
+use crate::secrets::{self, SecretError};
+use crate::theme::use_theme;
+use gpui::{prelude::FluentBuilder as _, *};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiKeyInputEvent {
+    Saved,
+    Removed,
+    Error(SecretError),
+}
+
+/// Shows whether a key is stored (with only its last characters visible) and lets the
+/// user enter, replace or remove it. Read the key with [`secrets::get`] using the same
+/// service and account.
+pub struct ApiKeyInput {
+    service: SharedString,
+    account: SharedString,
+    input: Entity<InputState>,
+    stored_hint: Option<SharedString>,
+    loaded: bool,
+    editing: bool,
+    revealed: bool,
+    busy: bool,
+    error: Option<SecretError>,
+    _task: Option<Task<()>>,
+    style: StyleRefinement,
+}
+
+impl EventEmitter<ApiKeyInputEvent> for ApiKeyInput {}
+
+impl ApiKeyInput {
+    pub fn new(
+        service: impl Into<SharedString>,
+        account: impl Into<SharedString>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let input = cx.new(|cx| InputState::new(cx).placeholder("Paste API key"));
+        cx.subscribe(&input, |this, _input, event, cx| match event {
+            InputEvent::Enter => this.save(cx),
+            InputEvent::Change => cx.notify(),
+            _ => {}
+        })
+        .detach();
+
+        let mut this = Self {
+            service: service.into(),
+            account: account.into(),
+            input,
+            stored_hint: None,
+            loaded: false,
+            editing: false,
+            revealed: false,
+            busy: false,
+            error: None,
+            _task: None,
+            style: StyleRefinement::default(),
+        };
+        this.reload(cx);
+        this
+    }
+
+    pub fn placeholder(
+        self,
+        placeholder: impl Into<SharedString>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        self.input.update(cx, |input, cx| {
+            input.set_placeholder(placeholder, window, cx);
+        });
+        self
+    }
+
+    /// Whether a key is stored in the keychain.
+    pub fn is_stored(&self) -> bool {
+        self.stored_hint.is_some()
+    }
+
+    /// Check the keychain again, e.g. after the key was changed elsewhere.
+    pub fn reload(&mut self, cx: &mut Context<Self>) {
+        let lookup = secrets::get(self.service.to_string(), self.account.to_string(), cx);
+        self._task = Some(cx.spawn(async move |this, cx| {
+            let result = lookup.await;
+            let _ = this.update(cx, |this, cx| {
+                this.loaded = true;
+                match result {
+                    Ok(secret) => this.stored_hint = secret.as_deref().map(key_hint),
+                    Err(err) => this.fail(err, cx),
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    /// Store the entered key.
+    pub fn save(&mut self, cx: &mut Context<Self>) {
+        let key = self.input.read(cx).content().trim().to_string();
+        if key.is_empty() || self.busy {
+            return;
+        }
+        self.busy = true;
+        self.error = None;
+        let hint = key_hint(&key);
+        let store = secrets::store(self.service.to_string(), self.account.to_string(), key, cx);
+        self._task = Some(cx.spawn(async move |this, cx| {
+            let result = store.await;
+            let _ = this.update(cx, |this, cx| {
+                this.busy = false;
+                match result {
+                    Ok(()) => {
+                        this.stored_hint = Some(hint);
+                        this.editing = false;
+                        this.revealed = false;
+                        cx.emit(ApiKeyInputEvent::Saved);
+                    }
+                    Err(err) => this.fail(err, cx),
+                }
+                cx.notify();
+            });
+        }));
+        cx.notify();
+    }
+
+    /// Delete the stored key.
+    pub fn remove(&mut self, cx: &mut Context<Self>) {
+        if self.busy {
+            return;
+        }
+        self.busy = true;
+        self.error = None;
+        let delete = secrets::delete(self.service.to_string(), self.account.to_string(), cx);
+        self._task = Some(cx.spawn(async move |this, cx| {
+            let result = delete.await;
+            let _ = this.update(cx, |this, cx| {
+                this.busy = false;
+                match result {
+                    Ok(_) => {
+                        this.stored_hint = None;
+                        this.editing = false;
+                        cx.emit(ApiKeyInputEvent::Removed);
+                    }
+                    Err(err) => this.fail(err, cx),
+                }
+                cx.notify();
+            });
+        }));
+        cx.notify();
+    }
+
+    fn start_editing(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.input
+            .update(cx, |input, cx| input.set_value("", window, cx));
+        self.editing = true;
+        self.revealed = false;
+        self.error = None;
+        cx.notify();
+    }
+
+    fn cancel_editing(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.input
+            .update(cx, |input, cx| input.set_value("", window, cx));
+        self.editing = false;
+        self.error = None;
+        cx.notify();
+    }
+
+    fn fail(&mut self, err: SecretError, cx: &mut Context<Self>) {
+        tracing::warn!(%err, service = %self.service, account = %self.account, "keychain access failed");
+        self.error = Some(err.clone());
+        cx.emit(ApiKeyInputEvent::Error(err));
+    }
+}
+
+/// Masked form of `key` showing at most its last four characters.
+fn key_hint(key: &str) -> SharedString {
+    let chars: Vec<char> = key.chars().collect();
+    if chars.len() < 12 {
+        return "••••••••".into();
+    }
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("••••••••{}", tail).into()
+}
+
+impl Styled for ApiKeyInput {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+impl Render for ApiKeyInput {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = use_theme();
+        let user_style = self.style.clone();
+
+        let body = if !self.loaded {
+            div()
+                .text_size(px(13.0))
+                .text_color(theme.tokens.muted_foreground)
+                .child("Checking keychain…")
+                .into_any_element()
+        } else if let (Some(hint), false) = (self.stored_hint.clone(), self.editing) {
+            div()
+                .flex()
+                .items_center()
+                .gap(px(8.0))
+                .child(
+                    div()
+                        .flex_1()
+                        .flex()
+                        .items_center()
+                        .gap(px(8.0))
+                        .h(px(36.0))
+                        .px(px(12.0))
+                        .rounded(theme.tokens.radius_md)
+                        .bg(theme.tokens.muted)
+                        .child(
+                            Icon::new("key-round")
+                                .size(px(14.0))
+                                .color(theme.tokens.muted_foreground),
+                        )
+                        .child(
+                            div()
+                                .font_family(theme.tokens.font_mono.clone())
+                                .text_size(px(13.0))
+                                .text_color(theme.tokens.foreground)
+                                .child(hint),
+                        ),
+                )
+                .child(
+                    Button::new("api-key-replace", "Replace")
+                        .variant(ButtonVariant::Outline)
+                        .size(ButtonSize::Sm)
+                        .on_click(
+                            cx.listener(|this, _, window, cx| this.start_editing(window, cx)),
+                        ),
+                )
+                .child(
+                    Button::new("api-key-remove", "Remove")
+                        .variant(ButtonVariant::Ghost)
+                        .size(ButtonSize::Sm)
+                        .disabled(self.busy)
+                        .on_click(cx.listener(|this, _, _, cx| this.remove(cx))),
+                )
+                .into_any_element()
+        } else {
+            let has_value = !self.input.read(cx).content().trim().is_empty();
+            div()
+                .flex()
+                .items_center()
+                .gap(px(8.0))
+                .child(
+                    div().flex_1().child(
+                        Input::new(&self.input).password(!self.revealed).suffix(
+                            IconButton::new(if self.revealed { "eye-off" } else { "eye" })
+                                .variant(ButtonVariant::Ghost)
+                                .size(px(24.0))
+                                .on_click(cx.listener(|this, _, _, cx| {
+                                    this.revealed = !this.revealed;
+                                    cx.notify();
+                                })),
+                        ),
+                    ),
+                )
+                .when(self.stored_hint.is_some(), |this| {
+                    this.child(
+                        Button::new("api-key-cancel", "Cancel")
+                            .variant(ButtonVariant::Ghost)
+                            .size(ButtonSize::Sm)
+                            .on_click(
+                                cx.listener(|this, _, window, cx| this.cancel_editing(window, cx)),
+                            ),
+                    )
+                })
+                .child(
+                    Button::new("api-key-save", "Save")
+                        .variant(ButtonVariant::Default)
+                        .size(ButtonSize::Sm)
+                        .disabled(!has_value || self.busy)
+                        .loading(self.busy)
+                        .on_click(cx.listener(|this, _, _, cx| this.save(cx))),
+                )
+                .into_any_element()
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .gap(px(6.0))
+            .map(|this| {
+                let mut div = this;
+                div.style().refine(&user_style);
+                div
+            })
+            .child(body)
+            .when_some(self.error.clone(), |this, err| {
+                this.child(caption(err.to_string()).color(theme.tokens.destructive))
+            })
+    }
+}
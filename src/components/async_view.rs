@@ -0,0 +1,170 @@
+//! Loading, error and loaded states for data that arrives asynchronously.
+
+use crate::components::button::{Button, ButtonSize, ButtonVariant};
+use crate::components::icon::Icon;
+use crate::components::spinner::{Spinner, SpinnerSize};
+use crate::theme::use_theme;
+use gpui::{prelude::FluentBuilder as _, *};
+use std::rc::Rc;
+
+/// Shows a spinner until content is available, and an error with a retry button if
+/// loading failed. Once content exists it stays visible while reloading, with the
+/// spinner and any error shown in a strip above it.
+#[derive(IntoElement)]
+pub struct AsyncView {
+    id: ElementId,
+    loading: bool,
+    error: Option<SharedString>,
+    content: Option<AnyElement>,
+    loading_label: Option<SharedString>,
+    on_retry: Option<Rc<dyn Fn(&mut Window, &mut App)>>,
+    style: StyleRefinement,
+}
+
+impl AsyncView {
+    pub fn new(id: impl Into<ElementId>) -> Self {
+        Self {
+            id: id.into(),
+            loading: false,
+            error: None,
+            content: None,
+            loading_label: None,
+            on_retry: None,
+            style: StyleRefinement::default(),
+        }
+    }
+
+    pub fn loading(mut self, loading: bool) -> Self {
+        self.loading = loading;
+        self
+    }
+
+    pub fn error(mut self, error: impl Into<SharedString>) -> Self {
+        self.error = Some(error.into());
+        self
+    }
+
+    pub fn content(mut self, content: impl IntoElement) -> Self {
+        self.content = Some(content.into_any_element());
+        self
+    }
+
+    pub fn loading_label(mut self, label: impl Into<SharedString>) -> Self {
+        self.loading_label = Some(label.into());
+        self
+    }
+
+    pub fn on_retry(mut self, handler: impl Fn(&mut Window, &mut App) + 'static) -> Self {
+        self.on_retry = Some(Rc::new(handler));
+        self
+    }
+
+    /// The state of a [`Resource`](crate::net::Resource), rendering its value with
+    /// `render` and refreshing it on retry.
+    #[cfg(feature = "net")]
+    pub fn resource<T>(
+        id: impl Into<ElementId>,
+        resource: &Entity<crate::net::Resource<T>>,
+        cx: &App,
+        render: impl FnOnce(&T) -> AnyElement,
+    ) -> Self
+    where
+        T: serde::de::DeserializeOwned + Send + Sync + 'static,
+    {
+        let state = resource.read(cx);
+        let mut view = Self::new(id).loading(state.is_loading());
+        if let Some(err) = state.error() {
+            view = view.error(err.to_string());
+        }
+        if let Some(data) = state.data() {
+            view = view.content(render(data));
+        }
+        let resource = resource.clone();
+        view.on_retry(move |_, cx| resource.update(cx, |resource, cx| resource.refresh(cx)))
+    }
+}
+
+impl Styled for AsyncView {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+impl RenderOnce for AsyncView {
+    fn render(self, _window: &mut Window, _cx: &mut App) -> impl IntoElement {
+        let theme = use_theme();
+        let user_style = self.style;
+        let retry_id = ElementId::Name(format!("{}-retry", self.id).into());
+        let retry_button = |on_retry: Option<Rc<dyn Fn(&mut Window, &mut App)>>| {
+            on_retry.map(|handler| {
+                Button::new(retry_id.clone(), "Retry")
+                    .variant(ButtonVariant::Outline)
+                    .size(ButtonSize::Sm)
+                    .on_click(move |_, window, cx| handler(window, cx))
+            })
+        };
+
+        let base = div().id(self.id.clone()).flex().flex_col().map(|mut this| {
+            this.style().refine(&user_style);
+            this
+        });
+
+        let Some(content) = self.content else {
+            return base
+                .items_center()
+                .justify_center()
+                .gap(px(12.0))
+                .p(px(24.0))
+                .map(|this| match (self.loading, self.error) {
+                    (true, _) => this.child(
+                        Spinner::new()
+                            .size(SpinnerSize::Sm)
+                            .when_some(self.loading_label, |spinner, label| spinner.label(label)),
+                    ),
+                    (false, Some(error)) => this
+                        .child(
+                            Icon::new("circle-alert")
+                                .size(px(24.0))
+                                .color(theme.tokens.destructive),
+                        )
+                        .child(
+                            div()
+                                .max_w(px(360.0))
+                                .text_size(px(13.0))
+                                .text_align(TextAlign::Center)
+                                .text_color(theme.tokens.muted_foreground)
+                                .child(error),
+                        )
+                        .children(retry_button(self.on_retry)),
+                    (false, None) => this,
+                });
+        };
+
+        base.when(self.loading || self.error.is_some(), |this| {
+            this.child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap(px(8.0))
+                    .px(px(8.0))
+                    .py(px(4.0))
+                    .text_size(px(12.0))
+                    .text_color(theme.tokens.muted_foreground)
+                    .when(self.loading, |this| {
+                        this.child(Spinner::new().size(SpinnerSize::Xs))
+                            .child(self.loading_label.unwrap_or_else(|| "Refreshing…".into()))
+                    })
+                    .when_some(self.error.filter(|_| !self.loading), |this, error| {
+                        this.child(
+                            Icon::new("circle-alert")
+                                .size(px(14.0))
+                                .color(theme.tokens.destructive),
+                        )
+                        .child(div().flex_1().truncate().child(error))
+                        .children(retry_button(self.on_retry))
+                    }),
+            )
+        })
+        .child(content)
+    }
+}
@@ -0,0 +1,252 @@
+//! Syntax-aware indentation for new lines and pasted blocks.
+//!
+//! Indent scopes are recognised from node shape rather than per-language queries: a
+//! node opened and closed by brackets, closed by an `end`-style keyword, or listed
+//! below as a block or continuation kind for its language. A new line inside a scope
+//! is indented one level deeper than the line the scope starts on.
+
+use crate::components::editor::Language;
+use tree_sitter::{Node, Tree};
+
+/// How a line inserted at a byte offset should be indented.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum NewlineIndent {
+    /// One level deeper than `row`. `close` is the offset of the scope's closing token,
+    /// if it has one, so a closer right after the cursor can be kept at `row`'s indent.
+    Inside { row: usize, close: Option<usize> },
+    /// One level shallower than the current line, e.g. after Python's `return`.
+    Dedent,
+}
+
+/// Indentation for a line break inserted at `byte`, which should be the end of the
+/// text before the cursor with trailing whitespace excluded.
+pub(crate) fn newline_indent(
+    tree: &Tree,
+    language: Language,
+    byte: usize,
+) -> Option<NewlineIndent> {
+    let root = tree.root_node();
+    if ends_block(root, language, byte) {
+        return Some(NewlineIndent::Dedent);
+    }
+
+    let mut node = root.descendant_for_byte_range(byte, byte)?;
+    loop {
+        if node.start_byte() < byte && byte < node.end_byte() {
+            if let Some(close) = scope_close(node, language, byte) {
+                return Some(NewlineIndent::Inside {
+                    row: node.start_position().row,
+                    close,
+                });
+            }
+        }
+        node = node.parent()?;
+    }
+}
+
+/// `Some(close)` if `node` indents its contents at `byte`, with `close` the start of
+/// its closing token.
+fn scope_close(node: Node, language: Language, byte: usize) -> Option<Option<usize>> {
+    let first = node.child(0)?;
+    let last = node.child(node.child_count() - 1)?;
+    if first.end_byte() > byte {
+        return None;
+    }
+
+    let delimited = matches!(
+        (first.kind(), last.kind()),
+        ("{", "}") | ("(", ")") | ("[", "]")
+    ) || matches!(last.kind(), "end" | "fi" | "done" | "esac" | "end_tag");
+    if delimited && node.child_count() > 1 {
+        if byte > last.start_byte() {
+            return None;
+        }
+        return Some((!last.is_missing()).then_some(last.start_byte()));
+    }
+
+    if block_kinds(language).contains(&node.kind()) {
+        // Only the body is indented, not a header split across lines.
+        let mut cursor = node.walk();
+        let header_end = node
+            .children(&mut cursor)
+            .find(|child| child.kind() == ":")
+            .map_or(0, |colon| colon.end_byte());
+        return (header_end <= byte).then_some(None);
+    }
+    continuation_kinds(language)
+        .contains(&node.kind())
+        .then_some(None)
+}
+
+/// Statements with no bracket or keyword closer whose body is indented.
+fn block_kinds(language: Language) -> &'static [&'static str] {
+    match language {
+        Language::Python => &[
+            "function_definition",
+            "class_definition",
+            "if_statement",
+            "elif_clause",
+            "else_clause",
+            "for_statement",
+            "while_statement",
+            "with_statement",
+            "try_statement",
+            "except_clause",
+            "finally_clause",
+            "match_statement",
+            "case_clause",
+        ],
+        _ => &[],
+    }
+}
+
+/// Expressions continued on the next line one level deeper, like method chains.
+fn continuation_kinds(language: Language) -> &'static [&'static str] {
+    match language {
+        Language::Rust | Language::C | Language::Cpp => &["field_expression"],
+        Language::JavaScript | Language::TypeScript => &["member_expression"],
+        Language::Go => &["selector_expression"],
+        Language::Java => &["field_access", "method_invocation"],
+        Language::Python => &["attribute"],
+        _ => &[],
+    }
+}
+
+/// Whether the statement ending at `byte` leaves its block, like `return` in Python.
+fn ends_block(root: Node, language: Language, byte: usize) -> bool {
+    let kinds: &[&str] = match language {
+        Language::Python => &[
+            "return_statement",
+            "pass_statement",
+            "break_statement",
+            "continue_statement",
+            "raise_statement",
+        ],
+        _ => return false,
+    };
+    let Some(mut node) = root.descendant_for_byte_range(byte.saturating_sub(1), byte) else {
+        return false;
+    };
+    while node.end_byte() == byte {
+        if kinds.contains(&node.kind()) {
+            return true;
+        }
+        match node.parent() {
+            Some(parent) => node = parent,
+            None => break,
+        }
+    }
+    false
+}
+
+/// Shift the lines of a multi-line paste so their indentation is relative to
+/// `target` columns, keeping their indentation relative to each other.
+///
+/// When `at_line_start` the cursor sits in leading whitespace, so the first line's own
+/// indentation is dropped in favour of the cursor's.
+pub(crate) fn reindent_paste(
+    text: &str,
+    target: usize,
+    at_line_start: bool,
+    tab_size: usize,
+    use_tabs: bool,
+) -> String {
+    let mut lines = text.split('\n');
+    let first = lines.next().unwrap_or_default();
+    let rest: Vec<&str> = lines.collect();
+    if rest.is_empty() {
+        return text.to_string();
+    }
+
+    // A copy that started after the first line's indentation has none on that line,
+    // so measure from the shallowest of the others instead.
+    let reference = if first.starts_with([' ', '\t']) {
+        indent_width(first, tab_size)
+    } else {
+        rest.iter()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| indent_width(line, tab_size))
+            .min()
+            .unwrap_or(0)
+    };
+
+    let mut out = String::with_capacity(text.len());
+    out.push_str(if at_line_start {
+        first.trim_start()
+    } else {
+        first
+    });
+    for line in rest {
+        out.push('\n');
+        if line.trim().is_empty() {
+            continue;
+        }
+        let width = (target + indent_width(line, tab_size)).saturating_sub(reference);
+        out.push_str(&indent_string(width, tab_size, use_tabs));
+        out.push_str(line.trim_start());
+    }
+    out
+}
+
+/// Column width of `line`'s leading whitespace.
+pub(crate) fn indent_width(line: &str, tab_size: usize) -> usize {
+    line.chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .fold(0, |width, c| match c {
+            '\t' => (width / tab_size + 1) * tab_size,
+            _ => width + 1,
+        })
+}
+
+fn indent_string(width: usize, tab_size: usize, use_tabs: bool) -> String {
+    if use_tabs {
+        let mut indent = "\t".repeat(width / tab_size);
+        indent.push_str(&" ".repeat(width % tab_size));
+        indent
+    } else {
+        " ".repeat(width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paste_keeps_relative_indent() {
+        let text = "if ready {\n        start();\n    }\n";
+        assert_eq!(
+            reindent_paste(text, 8, true, 4, false),
+            "if ready {\n            start();\n        }\n"
+        );
+        assert_eq!(
+            reindent_paste("    a\n\t\tb\n", 0, true, 4, true),
+            "a\n\tb\n"
+        );
+        assert_eq!(reindent_paste("single", 4, true, 4, false), "single");
+    }
+
+    #[cfg(feature = "tree-sitter-rust")]
+    #[test]
+    fn indents_inside_enclosing_scope() {
+        let source = "fn main() {\n    let v = items\n        .iter()\n        .map(f);\n}\n";
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&tree_sitter_rust::LANGUAGE.into())
+            .unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let at = |needle: &str| source.find(needle).unwrap() + needle.len();
+
+        assert_eq!(
+            newline_indent(&tree, Language::Rust, at("fn main() {")),
+            Some(NewlineIndent::Inside {
+                row: 0,
+                close: Some(source.rfind('}').unwrap()),
+            })
+        );
+        assert!(matches!(
+            newline_indent(&tree, Language::Rust, at(".iter()")),
+            Some(NewlineIndent::Inside { row: 1, .. })
+        ));
+    }
+}
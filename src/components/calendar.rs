@@ -165,7 +165,8 @@ impl Default for CalendarLocale {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// A calendar date. Ordered by year, then month, then day.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct DateValue {
     pub year: i32,
     pub month: u32,
@@ -177,7 +178,66 @@ impl DateValue {
         Self { year, month, day }
     }
 
-    fn days_in_month(&self) -> u32 {
+    /// Today's date in UTC.
+    pub fn today() -> Self {
+        let seconds = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        Self::from_days((seconds / 86_400) as i64)
+    }
+
+    /// Whether the month is 1-12 and the day exists in it.
+    pub fn is_valid(&self) -> bool {
+        (1..=12).contains(&self.month) && self.day >= 1 && self.day <= self.days_in_month()
+    }
+
+    /// The date `days` later, or earlier when negative.
+    pub fn add_days(&self, days: i64) -> Self {
+        Self::from_days(self.to_days() + days)
+    }
+
+    /// The same day `months` later, or earlier when negative, moved back to the last
+    /// day of the month when the month is shorter.
+    pub fn add_months(&self, months: i32) -> Self {
+        let index = self.year * 12 + self.month as i32 - 1 + months;
+        let mut date = Self::new(index.div_euclid(12), index.rem_euclid(12) as u32 + 1, 1);
+        date.day = self.day.min(date.days_in_month());
+        date
+    }
+
+    /// Day of the week, 0 for Sunday through 6 for Saturday.
+    pub fn weekday(&self) -> u32 {
+        // 1970-01-01 was a Thursday.
+        (self.to_days() + 4).rem_euclid(7) as u32
+    }
+
+    /// Days since 1970-01-01.
+    fn to_days(self) -> i64 {
+        let year = self.year as i64 - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let month = self.month as i64;
+        let day_of_year =
+            (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    fn from_days(days: i64) -> Self {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        Self::new(year as i32, month, day)
+    }
+
+    pub fn days_in_month(&self) -> u32 {
         match self.month {
             1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
             4 | 6 | 9 | 11 => 30,
@@ -222,7 +282,13 @@ pub struct Calendar {
     selected_date: Option<DateValue>,
     selected_range: Option<DateRange>,
     range_start_temp: Option<DateValue>,
+    focused_date: Option<DateValue>,
+    range_hover: Option<DateValue>,
+    show_year_navigation: bool,
+    show_prev_month: bool,
+    show_next_month: bool,
     on_date_select: Option<Rc<dyn Fn(&DateValue, &mut Window, &mut App)>>,
+    on_date_hover: Option<Rc<dyn Fn(&DateValue, &mut Window, &mut App)>>,
     on_month_change: Option<Rc<dyn Fn(&DateValue, &mut Window, &mut App)>>,
     disabled_dates: Vec<DateValue>,
     is_date_disabled: Option<Rc<dyn Fn(&DateValue) -> bool>>,
@@ -239,7 +305,13 @@ impl Calendar {
             selected_date: None,
             selected_range: None,
             range_start_temp: None,
+            focused_date: None,
+            range_hover: None,
+            show_year_navigation: false,
+            show_prev_month: true,
+            show_next_month: true,
             on_date_select: None,
+            on_date_hover: None,
             on_month_change: None,
             disabled_dates: Vec::new(),
             is_date_disabled: None,
@@ -271,6 +343,15 @@ impl Calendar {
         self
     }
 
+    /// Called with the day under the pointer as it moves over the days.
+    pub fn on_date_hover<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&DateValue, &mut Window, &mut App) + 'static,
+    {
+        self.on_date_hover = Some(Rc::new(handler));
+        self
+    }
+
     pub fn on_month_change<F>(mut self, handler: F) -> Self
     where
         F: Fn(&DateValue, &mut Window, &mut App) + 'static,
@@ -302,6 +383,34 @@ impl Calendar {
         self
     }
 
+    /// The day keyboard navigation is on, drawn with a focus ring.
+    pub fn focused_date(mut self, date: Option<DateValue>) -> Self {
+        self.focused_date = date;
+        self
+    }
+
+    /// Show buttons either side of the month buttons that move a year at a time.
+    pub fn show_year_navigation(mut self, show: bool) -> Self {
+        self.show_year_navigation = show;
+        self
+    }
+
+    /// The day under the pointer while a range has only its first day picked. The days
+    /// between it and [`Calendar::range_start_temp`] are drawn as the range it would
+    /// make.
+    pub fn range_hover(mut self, date: Option<DateValue>) -> Self {
+        self.range_hover = date;
+        self
+    }
+
+    /// Which of the previous and next month buttons to show, e.g. only the outer ones
+    /// when two calendars sit side by side. A hidden button keeps its space.
+    pub fn show_month_buttons(mut self, prev: bool, next: bool) -> Self {
+        self.show_prev_month = prev;
+        self.show_next_month = next;
+        self
+    }
+
     fn is_date_in_range(date: &DateValue, range: &DateRange) -> bool {
         let date_num = date.year * 10000 + date.month as i32 * 100 + date.day as i32;
         let start_num =
@@ -348,13 +457,26 @@ impl RenderOnce for Calendar {
 
         let prev_month_date = self.prev_month();
         let next_month_date = self.next_month();
+        let prev_year_date = current_month.add_months(-12);
+        let next_year_date = current_month.add_months(12);
+        let show_year_navigation = self.show_year_navigation;
+        let focused_date = self.focused_date;
 
         let on_month_change_handler = self.on_month_change.clone();
         let on_date_select_handler = self.on_date_select;
         let is_date_disabled_fn = self.is_date_disabled;
         let disabled_dates = self.disabled_dates;
-        let selected_range = self.selected_range;
         let range_start_temp = self.range_start_temp;
+        let preview_range = range_start_temp
+            .zip(self.range_hover)
+            .map(|(start, hover)| DateRange {
+                start: start.min(hover),
+                end: start.max(hover),
+            });
+        let selected_range = preview_range.or(self.selected_range);
+        let on_date_hover_handler = self.on_date_hover;
+        let show_prev_month = self.show_prev_month;
+        let show_next_month = self.show_next_month;
 
         let days_in_month = current_month.days_in_month();
         let first_day_of_week = current_month.first_day_of_week();
@@ -379,17 +501,35 @@ impl RenderOnce for Calendar {
                     .items_center()
                     .justify_between()
                     .mb(px(16.0))
-                    .child({
+                    .when(show_year_navigation, |this| {
                         let handler = on_month_change_handler.clone();
-                        Button::new("prev-month-btn", "‹")
-                            .variant(ButtonVariant::Ghost)
-                            .size(ButtonSize::Sm)
-                            .when(handler.is_some(), |btn| {
-                                let handler = handler.unwrap();
-                                btn.on_click(move |_, window, cx| {
-                                    handler(&prev_month_date, window, cx);
-                                })
-                            })
+                        this.child(
+                            Button::new("prev-year-btn", "«")
+                                .variant(ButtonVariant::Ghost)
+                                .size(ButtonSize::Sm)
+                                .when_some(handler, |btn, handler| {
+                                    btn.on_click(move |_, window, cx| {
+                                        handler(&prev_year_date, window, cx);
+                                    })
+                                }),
+                        )
+                    })
+                    .map(|this| {
+                        if !show_prev_month {
+                            return this.child(div().size(px(36.0)));
+                        }
+                        let handler = on_month_change_handler.clone();
+                        this.child(
+                            Button::new("prev-month-btn", "‹")
+                                .variant(ButtonVariant::Ghost)
+                                .size(ButtonSize::Sm)
+                                .when(handler.is_some(), |btn| {
+                                    let handler = handler.unwrap();
+                                    btn.on_click(move |_, window, cx| {
+                                        handler(&prev_month_date, window, cx);
+                                    })
+                                }),
+                        )
                     })
                     .child(
                         div()
@@ -400,17 +540,35 @@ impl RenderOnce for Calendar {
                             .text_color(theme.tokens.foreground)
                             .child(format!("{} {}", month_name, current_month.year)),
                     )
-                    .child({
+                    .map(|this| {
+                        if !show_next_month {
+                            return this.child(div().size(px(36.0)));
+                        }
+                        let handler = on_month_change_handler.clone();
+                        this.child(
+                            Button::new("next-month-btn", "›")
+                                .variant(ButtonVariant::Ghost)
+                                .size(ButtonSize::Sm)
+                                .when(handler.is_some(), |btn| {
+                                    let handler = handler.unwrap();
+                                    btn.on_click(move |_, window, cx| {
+                                        handler(&next_month_date, window, cx);
+                                    })
+                                }),
+                        )
+                    })
+                    .when(show_year_navigation, |this| {
                         let handler = on_month_change_handler;
-                        Button::new("next-month-btn", "›")
-                            .variant(ButtonVariant::Ghost)
-                            .size(ButtonSize::Sm)
-                            .when(handler.is_some(), |btn| {
-                                let handler = handler.unwrap();
-                                btn.on_click(move |_, window, cx| {
-                                    handler(&next_month_date, window, cx);
-                                })
-                            })
+                        this.child(
+                            Button::new("next-year-btn", "»")
+                                .variant(ButtonVariant::Ghost)
+                                .size(ButtonSize::Sm)
+                                .when_some(handler, |btn, handler| {
+                                    btn.on_click(move |_, window, cx| {
+                                        handler(&next_year_date, window, cx);
+                                    })
+                                }),
+                        )
                     }),
             )
             .child(
@@ -452,10 +610,12 @@ impl RenderOnce for Calendar {
                 }
 
                 let on_date_select_for_weeks = on_date_select_handler.clone();
+                let on_date_hover_for_weeks = on_date_hover_handler.clone();
                 let is_date_disabled_for_weeks = is_date_disabled_fn.clone();
                 let disabled_dates_for_weeks = disabled_dates.clone();
                 weeks.into_iter().map(move |week| {
                     let on_date_select_for_days = on_date_select_for_weeks.clone();
+                    let on_date_hover_for_days = on_date_hover_for_weeks.clone();
                     let is_date_disabled_for_days = is_date_disabled_for_weeks.clone();
                     let disabled_dates_for_days = disabled_dates_for_weeks.clone();
                     let range_for_week = selected_range;
@@ -510,6 +670,9 @@ impl RenderOnce for Calendar {
                                         .h(px(36.0))
                                         .text_size(px(14.0))
                                         .rounded(theme.tokens.radius_sm)
+                                        .when(focused_date == Some(date), |this: Div| {
+                                            this.border_2().border_color(theme.tokens.ring)
+                                        })
                                         // Disabled state styling
                                         .when(is_disabled, |this: Div| {
                                             this.text_color(
@@ -566,6 +729,14 @@ impl RenderOnce for Calendar {
                                                 },
                                             )
                                         })
+                                        .when_some(
+                                            on_date_hover_for_days.clone(),
+                                            |this: Div, handler| {
+                                                this.on_mouse_move(move |_, window, cx| {
+                                                    handler(&date, window, cx);
+                                                })
+                                            },
+                                        )
                                         .child(day.to_string())
                                         .into_any_element()
                                 }
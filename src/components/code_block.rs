@@ -1,5 +1,12 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::{Range, RangeInclusive};
+use std::rc::Rc;
+
 use gpui::*;
+use tree_sitter::{Parser, Query, QueryCursor, StreamingIterator};
 
+use crate::components::editor::{highlight_color_for_capture, Language};
 use crate::theme::use_theme;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -26,6 +33,9 @@ pub struct CodeBlock {
     show_line_numbers: bool,
     show_copy_button: bool,
     highlight_lines: Vec<usize>,
+    line_backgrounds: Vec<(RangeInclusive<usize>, Hsla)>,
+    start_line: usize,
+    syntax_color_fn: Option<Rc<dyn Fn(&str) -> Hsla>>,
     max_height: Option<Pixels>,
 }
 
@@ -38,10 +48,15 @@ impl CodeBlock {
             show_line_numbers: true,
             show_copy_button: true,
             highlight_lines: Vec::new(),
+            line_backgrounds: Vec::new(),
+            start_line: 1,
+            syntax_color_fn: None,
             max_height: None,
         }
     }
 
+    /// A language name ("rust", "TypeScript") or file extension ("rs", "tsx"). Code is
+    /// highlighted with tree-sitter when the language's grammar feature is enabled.
     pub fn language(mut self, lang: impl Into<SharedString>) -> Self {
         self.language = Some(lang.into());
         self
@@ -57,6 +72,33 @@ impl CodeBlock {
         self
     }
 
+    /// Highlight a span of line numbers, inclusive, in addition to `highlight_lines`.
+    pub fn highlight_range(mut self, lines: RangeInclusive<usize>) -> Self {
+        self.highlight_lines.extend(lines);
+        self
+    }
+
+    /// Give a span of line numbers its own background, e.g. green and red for the
+    /// added and removed lines of a diff. Later ranges win where they overlap.
+    pub fn line_background(mut self, lines: RangeInclusive<usize>, color: Hsla) -> Self {
+        self.line_backgrounds.push((lines, color));
+        self
+    }
+
+    /// Number of the first line, for excerpts of a larger file. Line numbers passed
+    /// to the highlighting methods are in the same numbering.
+    pub fn start_line(mut self, line: usize) -> Self {
+        self.start_line = line;
+        self
+    }
+
+    /// Map tree-sitter capture names like `keyword` or `string` to colors, in place of
+    /// the editor's default palette.
+    pub fn syntax_colors(mut self, color_fn: impl Fn(&str) -> Hsla + 'static) -> Self {
+        self.syntax_color_fn = Some(Rc::new(color_fn));
+        self
+    }
+
     pub fn max_height(mut self, height: Pixels) -> Self {
         self.max_height = Some(height);
         self
@@ -72,11 +114,12 @@ impl RenderOnce for CodeBlock {
     fn render(self, _window: &mut Window, _cx: &mut App) -> impl IntoElement {
         let theme = use_theme();
         let lines: Vec<&str> = self.code.split('\n').collect();
-        let is_rust = self
+        let language = self
             .language
             .as_ref()
-            .map(|l| l.as_ref() == "rust" || l.as_ref() == "rs")
-            .unwrap_or(false);
+            .map_or(Language::Plain, |name| language_for_name(name));
+        let is_rust = language == Language::Rust;
+        let syntax_spans = syntax_spans(&self.code, language, self.syntax_color_fn.as_deref());
 
         let keyword_color = theme.tokens.primary;
         let string_color = hsla(0.4, 0.7, 0.5, 1.0);
@@ -127,13 +170,20 @@ impl RenderOnce for CodeBlock {
         let mut content = div().flex().flex_col().py(px(12.0));
 
         for (idx, line_text) in lines.iter().enumerate() {
-            let line_num = idx + 1;
+            let line_num = idx + self.start_line;
             let is_highlighted = self.highlight_lines.contains(&line_num);
+            let background = self
+                .line_backgrounds
+                .iter()
+                .rev()
+                .find(|(lines, _)| lines.contains(&line_num))
+                .map(|(_, color)| *color)
+                .or(is_highlighted.then_some(highlight_bg));
 
             let mut row = div().flex().flex_row().px(px(12.0));
 
-            if is_highlighted {
-                row = row.bg(highlight_bg);
+            if let Some(background) = background {
+                row = row.bg(background);
             }
 
             if self.show_line_numbers {
@@ -150,17 +200,41 @@ impl RenderOnce for CodeBlock {
             }
 
             let mut code_row = div().flex().flex_row().flex_1().min_w_0();
-            let tokens = tokenize(line_text, is_rust);
-
-            for (kind, text) in tokens {
-                let color = match kind {
-                    TokenKind::Keyword => keyword_color,
-                    TokenKind::StringLiteral => string_color,
-                    TokenKind::Comment => comment_color,
-                    TokenKind::Number => number_color,
-                    TokenKind::Plain => plain_color,
-                };
-                code_row = code_row.child(div().text_color(color).child(text.to_string()));
+            if let Some(spans) = syntax_spans.as_ref() {
+                let mut pos = 0;
+                for (range, color) in &spans[idx] {
+                    if pos < range.start {
+                        code_row = code_row.child(
+                            div()
+                                .text_color(plain_color)
+                                .child(line_text[pos..range.start].to_string()),
+                        );
+                    }
+                    code_row = code_row.child(
+                        div()
+                            .text_color(*color)
+                            .child(line_text[range.clone()].to_string()),
+                    );
+                    pos = range.end;
+                }
+                if pos < line_text.len() {
+                    code_row = code_row.child(
+                        div()
+                            .text_color(plain_color)
+                            .child(line_text[pos..].to_string()),
+                    );
+                }
+            } else {
+                for (kind, text) in tokenize(line_text, is_rust) {
+                    let color = match kind {
+                        TokenKind::Keyword => keyword_color,
+                        TokenKind::StringLiteral => string_color,
+                        TokenKind::Comment => comment_color,
+                        TokenKind::Number => number_color,
+                        TokenKind::Plain => plain_color,
+                    };
+                    code_row = code_row.child(div().text_color(color).child(text.to_string()));
+                }
             }
 
             row = row.child(code_row);
@@ -181,6 +255,136 @@ impl RenderOnce for CodeBlock {
     }
 }
 
+/// Resolve a fence-style language name or a file extension.
+fn language_for_name(name: &str) -> Language {
+    const LANGUAGES: [Language; 22] = [
+        Language::Rust,
+        Language::JavaScript,
+        Language::TypeScript,
+        Language::Python,
+        Language::Json,
+        Language::Toml,
+        Language::Markdown,
+        Language::Go,
+        Language::C,
+        Language::Cpp,
+        Language::Java,
+        Language::Ruby,
+        Language::Bash,
+        Language::Css,
+        Language::Html,
+        Language::Yaml,
+        Language::Lua,
+        Language::Zig,
+        Language::Scala,
+        Language::Php,
+        Language::OCaml,
+        Language::Sql,
+    ];
+
+    match Language::from_extension(name) {
+        Language::Plain => LANGUAGES
+            .into_iter()
+            .find(|language| language.display_name().eq_ignore_ascii_case(name))
+            .unwrap_or(Language::Plain),
+        language => language,
+    }
+}
+
+thread_local! {
+    /// Compiled highlight queries, which are far more expensive to build than a short
+    /// snippet is to parse.
+    static HIGHLIGHT_QUERIES: RefCell<HashMap<Language, Option<Rc<Query>>>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Colored byte ranges for each line of `code`, or `None` without a grammar.
+fn syntax_spans(
+    code: &str,
+    language: Language,
+    color_fn: Option<&dyn Fn(&str) -> Hsla>,
+) -> Option<Vec<Vec<(Range<usize>, Hsla)>>> {
+    let ts_language = language.tree_sitter_language()?;
+    let query = HIGHLIGHT_QUERIES.with(|queries| {
+        queries
+            .borrow_mut()
+            .entry(language)
+            .or_insert_with(|| {
+                let source = language.highlight_query_source()?;
+                Query::new(&ts_language, &source).ok().map(Rc::new)
+            })
+            .clone()
+    })?;
+
+    let mut parser = Parser::new();
+    parser.set_language(&ts_language).ok()?;
+    let tree = parser.parse(code, None)?;
+
+    let mut captures = Vec::new();
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(&query, tree.root_node(), code.as_bytes());
+    while let Some(m) = matches.next() {
+        for capture in m.captures {
+            let name = &query.capture_names()[capture.index as usize];
+            let color = match color_fn {
+                Some(color_fn) => color_fn(name),
+                None => highlight_color_for_capture(name),
+            };
+            captures.push((capture.node.byte_range(), m.pattern_index, color));
+        }
+    }
+    Some(line_spans(code, captures))
+}
+
+/// Split `captures` into per-line ranges relative to each line's start. Nested
+/// captures take precedence over the ones enclosing them; for the same node, the
+/// earliest pattern wins.
+fn line_spans<C: Copy + PartialEq>(
+    code: &str,
+    mut captures: Vec<(Range<usize>, usize, C)>,
+) -> Vec<Vec<(Range<usize>, C)>> {
+    captures.sort_by(|(a, a_pattern, _), (b, b_pattern, _)| {
+        a.start
+            .cmp(&b.start)
+            .then(b.end.cmp(&a.end))
+            .then(a_pattern.cmp(b_pattern))
+    });
+
+    let mut colors: Vec<Option<C>> = vec![None; code.len()];
+    let mut previous: Option<Range<usize>> = None;
+    for (range, _, color) in captures {
+        if previous.as_ref() == Some(&range) {
+            continue;
+        }
+        let end = range.end.min(code.len());
+        for slot in &mut colors[range.start.min(end)..end] {
+            *slot = Some(color);
+        }
+        previous = Some(range);
+    }
+
+    let mut lines = Vec::new();
+    let mut line_start = 0;
+    for line in code.split('\n') {
+        let mut spans: Vec<(Range<usize>, C)> = Vec::new();
+        for (offset, color) in colors[line_start..line_start + line.len()]
+            .iter()
+            .enumerate()
+        {
+            let Some(color) = *color else {
+                continue;
+            };
+            match spans.last_mut() {
+                Some((range, last)) if range.end == offset && *last == color => range.end += 1,
+                _ => spans.push((offset..offset + 1, color)),
+            }
+        }
+        lines.push(spans);
+        line_start += line.len() + 1;
+    }
+    lines
+}
+
 fn tokenize<'a>(line: &'a str, is_rust: bool) -> Vec<(TokenKind, &'a str)> {
     let mut tokens = Vec::new();
     let bytes = line.as_bytes();
@@ -345,3 +549,27 @@ impl ParentElement for CodeBlock {
         self.base.extend(elements)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_captures_split_per_line() {
+        let code = "let s = \"a\\n\";\n// done";
+        let captures = vec![
+            (0..3, 0, 'k'),
+            (8..13, 2, 's'),
+            (10..12, 1, 'e'),
+            (15..22, 3, 'c'),
+            (15..22, 0, 'x'),
+        ];
+        assert_eq!(
+            line_spans(code, captures),
+            vec![
+                vec![(0..3, 'k'), (8..10, 's'), (10..12, 'e'), (12..13, 's')],
+                vec![(0..7, 'x')],
+            ]
+        );
+    }
+}
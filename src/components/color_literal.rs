@@ -0,0 +1,264 @@
+//! Color literals in source text: `#rgb` / `#rrggbb` hex codes and the CSS `rgb()`,
+//! `rgba()`, `hsl()` and `hsla()` functions. Used by the editor's inline color swatches.
+
+use gpui::{Hsla, Rgba};
+use std::ops::Range;
+
+/// How a literal was written, so a replacement color can be written the same way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorFormat {
+    Hex,
+    Rgb,
+    Hsl,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorLiteral {
+    /// Byte range of the literal in the scanned text.
+    pub range: Range<usize>,
+    pub color: Hsla,
+    pub format: ColorFormat,
+}
+
+/// Every color literal in `text`, in order.
+pub fn find_color_literals(text: &str) -> Vec<ColorLiteral> {
+    let bytes = text.as_bytes();
+    let mut literals = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let boundary = i == 0 || !is_ident_byte(bytes[i - 1]);
+        let found = match bytes[i] {
+            b'#' if boundary && (i == 0 || bytes[i - 1] != b'&') => parse_hex(text, i),
+            b'r' | b'R' | b'h' | b'H' if boundary => parse_function(text, i),
+            _ => None,
+        };
+        match found {
+            Some(literal) => {
+                i = literal.range.end;
+                literals.push(literal);
+            }
+            None => i += 1,
+        }
+    }
+    literals
+}
+
+/// Write `color` as a literal in `format`. Alpha is only written when below 1.
+pub fn format_color_literal(color: Hsla, format: ColorFormat) -> String {
+    let opaque = color.a >= 1.0;
+    match format {
+        ColorFormat::Hex => {
+            let rgba = Rgba::from(color);
+            let mut hex = format!(
+                "#{:02x}{:02x}{:02x}",
+                to_byte(rgba.r),
+                to_byte(rgba.g),
+                to_byte(rgba.b)
+            );
+            if !opaque {
+                hex.push_str(&format!("{:02x}", to_byte(rgba.a)));
+            }
+            hex
+        }
+        ColorFormat::Rgb => {
+            let rgba = Rgba::from(color);
+            let (r, g, b) = (to_byte(rgba.r), to_byte(rgba.g), to_byte(rgba.b));
+            if opaque {
+                format!("rgb({}, {}, {})", r, g, b)
+            } else {
+                format!("rgba({}, {}, {}, {})", r, g, b, format_alpha(color.a))
+            }
+        }
+        ColorFormat::Hsl => {
+            let h = (color.h * 360.0).round() % 360.0;
+            let s = (color.s * 100.0).round();
+            let l = (color.l * 100.0).round();
+            if opaque {
+                format!("hsl({}, {}%, {}%)", h, s, l)
+            } else {
+                format!("hsla({}, {}%, {}%, {})", h, s, l, format_alpha(color.a))
+            }
+        }
+    }
+}
+
+fn is_ident_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'-'
+}
+
+fn to_byte(channel: f32) -> u8 {
+    (channel.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn format_alpha(alpha: f32) -> String {
+    let text = format!("{:.2}", alpha.clamp(0.0, 1.0));
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn parse_hex(text: &str, start: usize) -> Option<ColorLiteral> {
+    let digits = text[start + 1..]
+        .bytes()
+        .take_while(u8::is_ascii_hexdigit)
+        .count();
+    let end = start + 1 + digits;
+    if !matches!(digits, 3 | 4 | 6 | 8)
+        || text.as_bytes().get(end).is_some_and(|&b| is_ident_byte(b))
+    {
+        return None;
+    }
+    let hex = &text[start + 1..end];
+    let channel = |index: usize| -> f32 {
+        let value = if digits <= 4 {
+            let digit = u8::from_str_radix(&hex[index..index + 1], 16).unwrap_or(0);
+            digit * 17
+        } else {
+            u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).unwrap_or(0)
+        };
+        value as f32 / 255.0
+    };
+    let has_alpha = digits == 4 || digits == 8;
+    let rgba = Rgba {
+        r: channel(0),
+        g: channel(1),
+        b: channel(2),
+        a: if has_alpha { channel(3) } else { 1.0 },
+    };
+    Some(ColorLiteral {
+        range: start..end,
+        color: rgba.into(),
+        format: ColorFormat::Hex,
+    })
+}
+
+fn parse_function(text: &str, start: usize) -> Option<ColorLiteral> {
+    let rest = &text[start..];
+    let name_len = rest.bytes().take_while(u8::is_ascii_alphabetic).count();
+    let name = rest[..name_len].to_ascii_lowercase();
+    let format = match name.as_str() {
+        "rgb" | "rgba" => ColorFormat::Rgb,
+        "hsl" | "hsla" => ColorFormat::Hsl,
+        _ => return None,
+    };
+    let args_start = name_len + 1;
+    if rest.as_bytes().get(name_len) != Some(&b'(') {
+        return None;
+    }
+    let close = rest[args_start..].find(')')? + args_start;
+    let args = &rest[args_start..close];
+    if args.contains('(') {
+        return None;
+    }
+
+    // Both `rgb(1, 2, 3, 0.5)` and `rgb(1 2 3 / 50%)` are accepted.
+    let (channels, slash_alpha) = match args.split_once('/') {
+        Some((channels, alpha)) => (channels, Some(alpha.trim())),
+        None => (args, None),
+    };
+    let mut values: Vec<&str> = channels
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|value| !value.is_empty())
+        .collect();
+    let alpha = match slash_alpha {
+        Some(alpha) => parse_alpha(alpha)?,
+        None if values.len() == 4 => parse_alpha(values.pop()?)?,
+        None => 1.0,
+    };
+    if values.len() != 3 {
+        return None;
+    }
+
+    let color = match format {
+        ColorFormat::Rgb => {
+            let channel = |value: &str| -> Option<f32> {
+                let channel = match value.strip_suffix('%') {
+                    Some(percent) => percent.parse::<f32>().ok()? / 100.0,
+                    None => value.parse::<f32>().ok()? / 255.0,
+                };
+                Some(channel.clamp(0.0, 1.0))
+            };
+            Rgba {
+                r: channel(values[0])?,
+                g: channel(values[1])?,
+                b: channel(values[2])?,
+                a: alpha,
+            }
+            .into()
+        }
+        _ => {
+            let hue = values[0].strip_suffix("deg").unwrap_or(values[0]);
+            let hue = hue.parse::<f32>().ok()?.rem_euclid(360.0) / 360.0;
+            let percent = |value: &str| -> Option<f32> {
+                let percent = value.strip_suffix('%').unwrap_or(value);
+                Some((percent.parse::<f32>().ok()? / 100.0).clamp(0.0, 1.0))
+            };
+            gpui::hsla(hue, percent(values[1])?, percent(values[2])?, alpha)
+        }
+    };
+    Some(ColorLiteral {
+        range: start..start + close + 1,
+        color,
+        format,
+    })
+}
+
+fn parse_alpha(value: &str) -> Option<f32> {
+    let alpha = match value.strip_suffix('%') {
+        Some(percent) => percent.parse::<f32>().ok()? / 100.0,
+        None => value.parse::<f32>().ok()?,
+    };
+    Some(alpha.clamp(0.0, 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgba(literal: &ColorLiteral) -> (u8, u8, u8, u8) {
+        let rgba = Rgba::from(literal.color);
+        (
+            to_byte(rgba.r),
+            to_byte(rgba.g),
+            to_byte(rgba.b),
+            to_byte(rgba.a),
+        )
+    }
+
+    #[test]
+    fn finds_hex_literals() {
+        let text = "color: #ff8000; border: #0F08 solid; a#fff #12345 #abcdefgh &#123;";
+        let literals = find_color_literals(text);
+        assert_eq!(literals.len(), 2);
+        assert_eq!(&text[literals[0].range.clone()], "#ff8000");
+        assert_eq!(rgba(&literals[0]), (255, 128, 0, 255));
+        assert_eq!(&text[literals[1].range.clone()], "#0F08");
+        assert_eq!(rgba(&literals[1]), (0, 255, 0, 136));
+        assert_eq!(literals[1].format, ColorFormat::Hex);
+    }
+
+    #[test]
+    fn finds_functional_literals() {
+        let text = "rgb(255, 0, 0) RGBA(0 0 255 / 50%) hsl(120deg, 100%, 50%) mrgb(1,2,3) rgb(1,2)";
+        let literals = find_color_literals(text);
+        assert_eq!(literals.len(), 3);
+        assert_eq!(&text[literals[0].range.clone()], "rgb(255, 0, 0)");
+        assert_eq!(rgba(&literals[0]), (255, 0, 0, 255));
+        assert_eq!(rgba(&literals[1]), (0, 0, 255, 128));
+        assert_eq!(literals[2].format, ColorFormat::Hsl);
+        assert_eq!(rgba(&literals[2]), (0, 255, 0, 255));
+    }
+
+    #[test]
+    fn formats_in_original_style() {
+        let color = find_color_literals("#ff800080")[0].color;
+        assert_eq!(format_color_literal(color, ColorFormat::Hex), "#ff800080");
+        assert_eq!(
+            format_color_literal(color, ColorFormat::Rgb),
+            "rgba(255, 128, 0, 0.5)"
+        );
+        let opaque = Hsla { a: 1.0, ..color };
+        assert_eq!(
+            format_color_literal(opaque, ColorFormat::Hsl),
+            "hsl(30, 100%, 50%)"
+        );
+    }
+}
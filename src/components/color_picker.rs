@@ -250,53 +250,14 @@ impl RenderOnce for ColorPicker {
         Popover::new(picker_id)
             .trigger(preview_button)
             .content(move |window, cx| {
-                let swatches_for_content = swatches.clone();
-                let on_change_for_content = on_change.clone();
-                let state_for_content = state.clone();
-
+                let panel = ColorPickerPanel {
+                    state: state.clone(),
+                    swatches: swatches.clone(),
+                    on_change: on_change.clone(),
+                };
                 cx.new(|cx| {
-                    PopoverContent::new(window, cx, move |_window, cx| {
-                        let _theme = use_theme();
-
-                        // Read state fresh on every render so mode changes work
-                        let current_color = state_for_content.read(cx).selected_color();
-                        let current_mode = state_for_content.read(cx).mode();
-                        let recent_vec = state_for_content.read(cx).recent_colors().to_vec();
-
-                        let swatches_clone = swatches_for_content.clone();
-                        let on_change_clone = on_change_for_content.clone();
-
-                        div()
-                            .flex()
-                            .flex_col()
-                            .gap_3()
-                            .w(px(280.0))
-                            .child(render_color_preview(current_color))
-                            .child(render_mode_selector(
-                                current_mode,
-                                state_for_content.clone(),
-                            ))
-                            .child(render_color_value(current_color, current_mode))
-                            .when(!swatches_clone.is_empty(), |this| {
-                                this.child(render_swatches(
-                                    swatches_clone,
-                                    state_for_content.clone(),
-                                    on_change_clone.clone(),
-                                ))
-                            })
-                            .when(!recent_vec.is_empty(), |this| {
-                                this.child(render_recent_colors(
-                                    recent_vec,
-                                    state_for_content.clone(),
-                                    on_change_clone.clone(),
-                                ))
-                            })
-                            .child(render_actions(
-                                current_color,
-                                state_for_content.clone(),
-                                on_change_clone.clone(),
-                            ))
-                            .into_any_element()
+                    PopoverContent::new(window, cx, move |_window, _cx| {
+                        panel.clone().into_any_element()
                     })
                 })
             })
@@ -304,6 +265,68 @@ impl RenderOnce for ColorPicker {
     }
 }
 
+/// The picker's panel without its trigger button, for hosts that decide where it
+/// opens, such as the editor's color swatches. [`ColorPicker`] shows it in a popover.
+#[derive(IntoElement, Clone)]
+pub struct ColorPickerPanel {
+    state: Entity<ColorPickerState>,
+    swatches: Vec<Hsla>,
+    on_change: Option<Rc<dyn Fn(Hsla, &mut Window, &mut App)>>,
+}
+
+impl ColorPickerPanel {
+    pub fn new(state: Entity<ColorPickerState>) -> Self {
+        Self {
+            state,
+            swatches: default_swatches(),
+            on_change: None,
+        }
+    }
+
+    pub fn swatches(mut self, swatches: Vec<Hsla>) -> Self {
+        self.swatches = swatches;
+        self
+    }
+
+    /// Called with the color when a swatch is clicked or Apply is pressed.
+    pub fn on_change(mut self, handler: impl Fn(Hsla, &mut Window, &mut App) + 'static) -> Self {
+        self.on_change = Some(Rc::new(handler));
+        self
+    }
+}
+
+impl RenderOnce for ColorPickerPanel {
+    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let state = self.state;
+        // Read state fresh on every render so mode changes work
+        let current_color = state.read(cx).selected_color();
+        let current_mode = state.read(cx).mode();
+        let recent_vec = state.read(cx).recent_colors().to_vec();
+        let swatches = self.swatches;
+        let on_change = self.on_change;
+
+        div()
+            .flex()
+            .flex_col()
+            .gap_3()
+            .w(px(280.0))
+            .child(render_color_preview(current_color))
+            .child(render_mode_selector(current_mode, state.clone()))
+            .child(render_color_value(current_color, current_mode))
+            .when(!swatches.is_empty(), |this| {
+                this.child(render_swatches(swatches, state.clone(), on_change.clone()))
+            })
+            .when(!recent_vec.is_empty(), |this| {
+                this.child(render_recent_colors(
+                    recent_vec,
+                    state.clone(),
+                    on_change.clone(),
+                ))
+            })
+            .child(render_actions(current_color, state, on_change))
+    }
+}
+
 fn render_color_preview(color: Hsla) -> impl IntoElement {
     let theme = use_theme();
 
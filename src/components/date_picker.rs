@@ -1,4 +1,13 @@
 //! DatePicker component - Date selection with calendar popup and keyboard navigation.
+//!
+//! In single mode the date can be typed into the field, in the picker's [`DateFormat`]
+//! or as an ISO date, and is read when the user presses enter or leaves the field. Text
+//! that isn't a date, or a date outside the allowed ones, puts the field back to the
+//! selected date.
+//!
+//! The calendar takes the keyboard when it opens: arrows move by day and week,
+//! home/end to the start and end of the week, page up/down by month (with shift, by
+//! year), and enter or space selects.
 
 use gpui::{prelude::FluentBuilder as _, *};
 use std::rc::Rc;
@@ -6,6 +15,8 @@ use std::rc::Rc;
 use crate::components::button::{Button, ButtonSize, ButtonVariant};
 use crate::components::calendar::{Calendar, CalendarLocale, DateRange, DateValue};
 use crate::components::icon::Icon;
+use crate::components::input::Input;
+use crate::components::input_state::{InputEvent, InputState};
 use crate::overlays::popover::{Popover, PopoverContent};
 use crate::theme::use_theme;
 
@@ -46,6 +57,90 @@ impl DateFormat {
             }
         }
     }
+
+    /// Read a date typed in this format. ISO dates are accepted whatever the format,
+    /// numeric formats take `/`, `-` or `.` between the parts, and month names in
+    /// [`DateFormat::LongDate`] can be shortened to their first three letters.
+    pub fn parse(&self, text: &str, locale: &CalendarLocale) -> Option<DateValue> {
+        let text = text.trim();
+        let parts: Vec<&str> = text.split(['/', '-', '.']).map(str::trim).collect();
+        // Indices of the year, month and day in `parts`. The year takes four digits.
+        let numeric = |order: [usize; 3]| -> Option<DateValue> {
+            if parts.len() != 3 || parts[order[0]].len() != 4 {
+                return None;
+            }
+            Some(DateValue::new(
+                parts[order[0]].parse().ok()?,
+                parts[order[1]].parse().ok()?,
+                parts[order[2]].parse().ok()?,
+            ))
+        };
+        let date = match self {
+            DateFormat::IsoDate => None,
+            DateFormat::UsDate => numeric([2, 0, 1]),
+            DateFormat::EuDate => numeric([2, 1, 0]),
+            DateFormat::LongDate => {
+                let words: Vec<&str> = text
+                    .split(|ch: char| ch.is_whitespace() || ch == ',')
+                    .filter(|word| !word.is_empty())
+                    .collect();
+                match words.as_slice() {
+                    [month, day, year] if month.chars().count() >= 3 => {
+                        let month = month.to_lowercase();
+                        let index = locale
+                            .months
+                            .iter()
+                            .position(|name| name.to_lowercase().starts_with(&month))?;
+                        Some(DateValue::new(
+                            year.parse().ok()?,
+                            index as u32 + 1,
+                            day.parse().ok()?,
+                        ))
+                    }
+                    _ => None,
+                }
+            }
+        }
+        .or_else(|| numeric([0, 1, 2]))?;
+        date.is_valid().then_some(date)
+    }
+}
+
+/// Which dates can be picked.
+#[derive(Clone, Default)]
+pub(crate) struct DateConstraints {
+    pub(crate) min: Option<DateValue>,
+    pub(crate) max: Option<DateValue>,
+    pub(crate) disabled: Vec<DateValue>,
+    pub(crate) weekends: bool,
+}
+
+impl DateConstraints {
+    pub(crate) fn allows(&self, date: &DateValue) -> bool {
+        self.min.map_or(true, |min| *date >= min)
+            && self.max.map_or(true, |max| *date <= max)
+            && !self.disabled.contains(date)
+            && !(self.weekends && matches!(date.weekday(), 0 | 6))
+    }
+
+    pub(crate) fn clamp(&self, date: DateValue) -> DateValue {
+        let date = self.min.map_or(date, |min| date.max(min));
+        self.max.map_or(date, |max| date.min(max))
+    }
+}
+
+enum TextCommit {
+    Selected(DateValue),
+    Cleared,
+}
+
+/// What the text field needs from the last render to read typed dates.
+struct TextConfig {
+    format: DateFormat,
+    locale: CalendarLocale,
+    constraints: DateConstraints,
+    on_select: Option<Rc<dyn Fn(&DateValue, &mut Window, &mut App)>>,
+    on_clear: Option<Rc<dyn Fn(&mut Window, &mut App)>>,
 }
 
 /// Date selection mode
@@ -65,45 +160,42 @@ pub struct DatePickerState {
     pub selection_mode: DateSelectionMode,
     pub is_open: bool,
     pub viewing_month: DateValue,
+    /// The day keyboard navigation is on while the calendar is open.
+    pub focused_date: Option<DateValue>,
     focus_handle: FocusHandle,
+    input: Entity<InputState>,
+    /// The selected date the text field was last filled from.
+    input_date: Option<DateValue>,
+    text_config: Option<Rc<TextConfig>>,
+    _input_subscription: Option<Subscription>,
 }
 
 impl DatePickerState {
     pub fn new(cx: &mut App) -> Self {
-        let today = DateValue::new(2025, 1, 23);
-        Self {
-            selected_date: None,
-            selected_range: None,
-            range_start_temp: None,
-            selection_mode: DateSelectionMode::Single,
-            is_open: false,
-            viewing_month: today,
-            focus_handle: cx.focus_handle(),
-        }
+        Self::new_with_mode(DateSelectionMode::Single, cx)
     }
 
     pub fn new_with_date(date: DateValue, cx: &mut App) -> Self {
-        Self {
-            selected_date: Some(date),
-            selected_range: None,
-            range_start_temp: None,
-            selection_mode: DateSelectionMode::Single,
-            is_open: false,
-            viewing_month: date,
-            focus_handle: cx.focus_handle(),
-        }
+        let mut state = Self::new(cx);
+        state.selected_date = Some(date);
+        state.viewing_month = date;
+        state
     }
 
     pub fn new_with_mode(mode: DateSelectionMode, cx: &mut App) -> Self {
-        let today = DateValue::new(2025, 1, 23);
         Self {
             selected_date: None,
             selected_range: None,
             range_start_temp: None,
             selection_mode: mode,
             is_open: false,
-            viewing_month: today,
+            viewing_month: DateValue::today(),
+            focused_date: None,
             focus_handle: cx.focus_handle(),
+            input: cx.new(InputState::new),
+            input_date: None,
+            text_config: None,
+            _input_subscription: None,
         }
     }
 
@@ -172,8 +264,38 @@ impl DatePickerState {
     }
 
     pub fn jump_to_today(&mut self, _cx: &mut App) {
-        let today = DateValue::new(2025, 1, 23);
-        self.viewing_month = today;
+        self.viewing_month = DateValue::today();
+    }
+
+    /// Put the keyboard focus on `date`, showing its month.
+    pub fn set_focused_date(&mut self, date: DateValue, _cx: &mut App) {
+        self.focused_date = Some(date);
+        self.viewing_month = date;
+    }
+
+    /// Read the text field after enter or blur.
+    fn commit_text(&mut self, cx: &mut App) -> Option<TextCommit> {
+        let config = self.text_config.clone()?;
+        let text = self.input.read(cx).content().trim().to_string();
+        if text.is_empty() {
+            self.input_date = None;
+            return self.selected_date.take().map(|_| TextCommit::Cleared);
+        }
+        match config.format.parse(&text, &config.locale) {
+            Some(date) if config.constraints.allows(&date) => {
+                if self.selected_date == Some(date) {
+                    // Refill the field to tidy up how the date was typed.
+                    self.input_date = None;
+                    return None;
+                }
+                self.select_date(date, cx);
+                Some(TextCommit::Selected(date))
+            }
+            _ => {
+                self.input_date = None;
+                None
+            }
+        }
     }
 }
 
@@ -187,7 +309,21 @@ impl EventEmitter<DismissEvent> for DatePickerState {}
 
 actions!(
     date_picker,
-    [ClosePicker, SelectToday, NextMonth, PrevMonth]
+    [
+        ClosePicker,
+        SelectToday,
+        NextMonth,
+        PrevMonth,
+        NextYear,
+        PrevYear,
+        NextDay,
+        PrevDay,
+        NextWeek,
+        PrevWeek,
+        WeekStart,
+        WeekEnd,
+        SelectFocused,
+    ]
 );
 
 /// Initialize DatePicker keybindings
@@ -197,9 +333,38 @@ pub fn init(cx: &mut App) {
         KeyBinding::new("cmd-t", SelectToday, Some("DatePicker")),
         KeyBinding::new("cmd-]", NextMonth, Some("DatePicker")),
         KeyBinding::new("cmd-[", PrevMonth, Some("DatePicker")),
+        KeyBinding::new("pagedown", NextMonth, Some("DatePicker")),
+        KeyBinding::new("pageup", PrevMonth, Some("DatePicker")),
+        KeyBinding::new("shift-pagedown", NextYear, Some("DatePicker")),
+        KeyBinding::new("shift-pageup", PrevYear, Some("DatePicker")),
+        KeyBinding::new("right", NextDay, Some("DatePicker")),
+        KeyBinding::new("left", PrevDay, Some("DatePicker")),
+        KeyBinding::new("down", NextWeek, Some("DatePicker")),
+        KeyBinding::new("up", PrevWeek, Some("DatePicker")),
+        KeyBinding::new("home", WeekStart, Some("DatePicker")),
+        KeyBinding::new("end", WeekEnd, Some("DatePicker")),
+        KeyBinding::new("enter", SelectFocused, Some("DatePicker")),
+        KeyBinding::new("space", SelectFocused, Some("DatePicker")),
     ]);
 }
 
+/// Move the calendar's keyboard focus by `step`, staying within the allowed dates.
+fn move_focus<A: Action>(
+    state: &Entity<DatePickerState>,
+    constraints: &DateConstraints,
+    step: fn(DateValue) -> DateValue,
+) -> impl Fn(&A, &mut Window, &mut App) + 'static {
+    let state = state.clone();
+    let constraints = constraints.clone();
+    move |_: &A, _: &mut Window, cx: &mut App| {
+        state.update(cx, |state, cx| {
+            let from = state.focused_date.unwrap_or(state.viewing_month);
+            state.set_focused_date(constraints.clamp(step(from)), cx);
+            cx.notify();
+        });
+    }
+}
+
 /// DatePicker component with calendar popup
 #[derive(IntoElement)]
 pub struct DatePicker {
@@ -209,6 +374,7 @@ pub struct DatePicker {
     min_date: Option<DateValue>,
     max_date: Option<DateValue>,
     disabled_dates: Vec<DateValue>,
+    disable_weekends: bool,
     disabled: bool,
     clearable: bool,
     show_today_button: bool,
@@ -227,6 +393,7 @@ impl DatePicker {
             min_date: None,
             max_date: None,
             disabled_dates: Vec::new(),
+            disable_weekends: false,
             disabled: false,
             clearable: true,
             show_today_button: true,
@@ -274,7 +441,8 @@ impl DatePicker {
     }
 
     /// Disable weekends
-    pub fn disable_weekends(self) -> Self {
+    pub fn disable_weekends(mut self) -> Self {
+        self.disable_weekends = true;
         self
     }
 
@@ -321,124 +489,108 @@ impl DatePicker {
     }
 }
 
-impl Styled for DatePicker {
-    fn style(&mut self) -> &mut StyleRefinement {
-        &mut self.style
+impl DatePicker {
+    fn constraints(&self) -> DateConstraints {
+        DateConstraints {
+            min: self.min_date,
+            max: self.max_date,
+            disabled: self.disabled_dates.clone(),
+            weekends: self.disable_weekends,
+        }
     }
-}
 
-impl RenderOnce for DatePicker {
-    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
-        let theme = use_theme();
-        let state_entity = self.state.clone();
-        let state = self.state.read(cx);
+    /// Hand the text field this render's settings and refill it if the selected date
+    /// changed since it was last filled.
+    fn sync_text_field(&self, window: &mut Window, cx: &mut App) {
+        let config = Rc::new(TextConfig {
+            format: self.format,
+            locale: self.locale.clone(),
+            constraints: self.constraints(),
+            on_select: self.on_select.clone(),
+            on_clear: self.on_clear.clone(),
+        });
+        let picker = self.state.downgrade();
+        let (input, refill) = self.state.update(cx, |state, cx| {
+            state.text_config = Some(config.clone());
+            if state._input_subscription.is_none() {
+                let picker = picker.clone();
+                state._input_subscription = Some(window.subscribe(
+                    &state.input,
+                    cx,
+                    move |_, event: &InputEvent, window, cx| {
+                        if !matches!(event, InputEvent::Enter | InputEvent::Blur) {
+                            return;
+                        }
+                        let Some(picker) = picker.upgrade() else {
+                            return;
+                        };
+                        let committed = picker.update(cx, |state, cx| {
+                            let committed = state.commit_text(cx);
+                            cx.notify();
+                            committed.zip(state.text_config.clone())
+                        });
+                        match committed {
+                            Some((TextCommit::Selected(date), config)) => {
+                                if let Some(on_select) = &config.on_select {
+                                    on_select(&date, window, cx);
+                                }
+                            }
+                            Some((TextCommit::Cleared, config)) => {
+                                if let Some(on_clear) = &config.on_clear {
+                                    on_clear(window, cx);
+                                }
+                            }
+                            None => {}
+                        }
+                    },
+                ));
+            }
+            let refill = (state.input_date != state.selected_date).then(|| {
+                state.input_date = state.selected_date;
+                state.selected_date
+            });
+            (state.input.clone(), refill)
+        });
+        if let Some(date) = refill {
+            let text = date
+                .map(|date| config.format.format(&date, &config.locale))
+                .unwrap_or_default();
+            input.update(cx, |input, cx| input.set_value(text, window, cx));
+        }
+    }
 
-        let selected_date = state.selected_date;
-        let _viewing_month = state.viewing_month;
+    /// The calendar popover, opened from `trigger`.
+    fn calendar_popover(&self, trigger: impl IntoElement + 'static) -> Popover {
+        let state_entity = self.state.clone();
+        let on_select_handler = self.on_select.clone();
         let locale = self.locale.clone();
-        let format = self.format;
-        let disabled = self.disabled;
-        let clearable = self.clearable;
         let show_today_button = self.show_today_button;
-
-        let display_text = if let Some(date) = selected_date {
-            format.format(&date, &locale)
-        } else {
-            self.placeholder.to_string()
-        };
-
-        let has_value = selected_date.is_some();
-        let text_color = if has_value {
-            theme.tokens.foreground
-        } else {
-            theme.tokens.muted_foreground
-        };
-
-        let state_for_clear = state_entity.clone();
-        let state_for_calendar = state_entity.clone();
-        let state_for_today = state_entity.clone();
-        let on_select_handler = self.on_select.clone();
-        let on_clear_handler = self.on_clear.clone();
-        let min_date = self.min_date;
-        let max_date = self.max_date;
-        let disabled_dates = self.disabled_dates.clone();
-
-        let user_style = self.style;
+        let constraints = self.constraints();
 
         let popover_id = ElementId::Name(
             format!("date-picker-popover-{}", state_entity.entity_id().as_u64()).into(),
         );
 
-        Popover::new(popover_id.clone())
-            .trigger(
-                div()
-                    .flex()
-                    .items_center()
-                    .justify_between()
-                    .w_full()
-                    .h(px(40.0))
-                    .px(px(12.0))
-                    .gap(px(8.0))
-                    .bg(theme.tokens.background)
-                    .border_1()
-                    .border_color(theme.tokens.input)
-                    .rounded(theme.tokens.radius_md)
-                    .when(!disabled, |div| {
-                        div.cursor(CursorStyle::PointingHand)
-                            .hover(|style| style.border_color(theme.tokens.ring))
-                    })
-                    .when(disabled, |div| {
-                        div.cursor(CursorStyle::OperationNotAllowed).opacity(0.5)
-                    })
-                    .child(
-                        div()
-                            .flex_1()
-                            .text_size(px(14.0))
-                            .text_color(text_color)
-                            .child(display_text),
-                    )
-                    .child(
-                        div()
-                            .flex()
-                            .gap(px(4.0))
-                            .when(clearable && has_value && !disabled, |parent_div| {
-                                let on_clear = on_clear_handler.clone();
-                                let muted_bg = theme.tokens.muted;
-                                let muted_fg = theme.tokens.muted_foreground;
-                                parent_div.child(
-                                    div()
-                                        .p(px(2.0))
-                                        .rounded(px(4.0))
-                                        .cursor_pointer()
-                                        .hover(move |style| style.bg(muted_bg))
-                                        .on_mouse_down(
-                                            MouseButton::Left,
-                                            move |_event: &MouseDownEvent, window, cx| {
-                                                cx.stop_propagation();
-                                                state_for_clear.update(cx, |state, cx| {
-                                                    state.clear_date(cx);
-                                                });
-                                                if let Some(handler) = on_clear.as_ref() {
-                                                    handler(window, cx);
-                                                }
-                                            },
-                                        )
-                                        .child(Icon::new("x").size(px(16.0)).color(muted_fg)),
-                                )
-                            })
-                            .child(
-                                Icon::new("calendar")
-                                    .size(px(16.0))
-                                    .color(theme.tokens.muted_foreground),
-                            ),
-                    ),
-            )
-            .content(move |window: &mut Window, app_cx: &mut App| {
-                let state_ref = state_for_calendar.clone();
+        Popover::new(popover_id).trigger(trigger).content(
+            move |window: &mut Window, app_cx: &mut App| {
+                let state_ref = state_entity.clone();
                 let on_select_ref = on_select_handler.clone();
                 let locale_ref = locale.clone();
-                let disabled_dates_ref = disabled_dates.clone();
-                let state_today_ref = state_for_today.clone();
+                let constraints_ref = constraints.clone();
+
+                // Start keyboard navigation on the selected date, once the popover has
+                // taken focus.
+                let focus_handle = state_ref.update(app_cx, |state, cx| {
+                    let start = state
+                        .selected_date
+                        .or(state.range_start_temp)
+                        .or(state.selected_range.map(|range| range.start))
+                        .unwrap_or_else(DateValue::today);
+                    state.set_focused_date(constraints_ref.clamp(start), cx);
+                    state.open(cx);
+                    state.focus_handle.clone()
+                });
+                window.defer(app_cx, move |window, _| window.focus(&focus_handle));
 
                 app_cx.new(move |cx| {
                     PopoverContent::new(
@@ -451,25 +603,109 @@ impl RenderOnce for DatePicker {
                             let selected_date = state.selected_date;
                             let selected_range = state.selected_range;
                             let range_start_temp = state.range_start_temp;
+                            let focused_date = state.focused_date;
+                            let focus_handle = state.focus_handle.clone();
 
-                            let state_for_select = state_ref.clone();
                             let state_for_month = state_ref.clone();
-                            let on_select = on_select_ref.clone();
-                            let locale_clone = locale_ref.clone();
-                            let min_date_clone = min_date;
-                            let max_date_clone = max_date;
-                            let disabled_dates_clone = disabled_dates_ref.clone();
-                            let state_today = state_today_ref.clone();
                             let border_color = theme.tokens.border;
 
                             // Get entity reference for closing popover
                             let popover_entity = popover_cx.entity().clone();
 
+                            let select: Rc<dyn Fn(&DateValue, &mut Window, &mut App)> = {
+                                let state = state_ref.clone();
+                                let on_select = on_select_ref.clone();
+                                let constraints = constraints_ref.clone();
+                                let popover = popover_entity.clone();
+                                Rc::new(move |date, window, app_cx| {
+                                    if !constraints.allows(date) {
+                                        return;
+                                    }
+                                    // Single dates close right away, ranges on their
+                                    // second date.
+                                    let picker = state.read(app_cx);
+                                    let should_close = picker.selection_mode
+                                        == DateSelectionMode::Single
+                                        || picker.range_start_temp.is_some();
+
+                                    state.update(app_cx, |state, cx| {
+                                        state.select_date(*date, cx);
+                                        if should_close {
+                                            state.close(cx);
+                                        }
+                                        cx.notify();
+                                    });
+
+                                    if should_close {
+                                        if let Some(handler) = on_select.as_ref() {
+                                            handler(date, window, app_cx);
+                                        }
+                                        popover.update(app_cx, |_, cx| {
+                                            cx.emit(DismissEvent);
+                                        });
+                                    }
+                                })
+                            };
+
+                            let select_focused = select.clone();
+                            let select_today = select.clone();
+                            let select_today_key = select.clone();
+                            let state_for_enter = state_ref.clone();
+                            let popover_for_escape = popover_entity.clone();
+                            let constraints = constraints_ref.clone();
+
                             div()
                                 .flex()
                                 .flex_col()
                                 .gap(px(8.0))
+                                .track_focus(&focus_handle)
+                                .key_context("DatePicker")
+                                .on_action(move_focus::<PrevDay>(&state_ref, &constraints, |d| {
+                                    d.add_days(-1)
+                                }))
+                                .on_action(move_focus::<NextDay>(&state_ref, &constraints, |d| {
+                                    d.add_days(1)
+                                }))
+                                .on_action(move_focus::<PrevWeek>(&state_ref, &constraints, |d| {
+                                    d.add_days(-7)
+                                }))
+                                .on_action(move_focus::<NextWeek>(&state_ref, &constraints, |d| {
+                                    d.add_days(7)
+                                }))
+                                .on_action(move_focus::<WeekStart>(&state_ref, &constraints, |d| {
+                                    d.add_days(-(d.weekday() as i64))
+                                }))
+                                .on_action(move_focus::<WeekEnd>(&state_ref, &constraints, |d| {
+                                    d.add_days(6 - d.weekday() as i64)
+                                }))
+                                .on_action(move_focus::<PrevMonth>(&state_ref, &constraints, |d| {
+                                    d.add_months(-1)
+                                }))
+                                .on_action(move_focus::<NextMonth>(&state_ref, &constraints, |d| {
+                                    d.add_months(1)
+                                }))
+                                .on_action(move_focus::<PrevYear>(&state_ref, &constraints, |d| {
+                                    d.add_months(-12)
+                                }))
+                                .on_action(move_focus::<NextYear>(&state_ref, &constraints, |d| {
+                                    d.add_months(12)
+                                }))
+                                .on_action(move |_: &SelectFocused, window, cx| {
+                                    if let Some(date) = state_for_enter.read(cx).focused_date {
+                                        select_focused(&date, window, cx);
+                                    }
+                                })
+                                .on_action(move |_: &SelectToday, window, cx| {
+                                    select_today_key(&DateValue::today(), window, cx);
+                                })
+                                .on_action(move |_: &ClosePicker, window, cx| {
+                                    popover_for_escape.update(cx, |popover, cx| {
+                                        popover.dismiss(window, cx);
+                                    });
+                                })
                                 .child({
+                                    let constraints = constraints.clone();
+                                    let select = select.clone();
                                     Calendar::new()
                                         .current_month(viewing_month)
                                         .when_some(selected_date, |cal, date| {
@@ -477,111 +713,14 @@ impl RenderOnce for DatePicker {
                                         })
                                         .selected_range(selected_range)
                                         .range_start_temp(range_start_temp)
-                                        .locale(locale_clone.clone())
-                                        .is_date_disabled({
-                                            let min = min_date_clone;
-                                            let max = max_date_clone;
-                                            let disabled = disabled_dates_clone.clone();
-                                            move |date: &DateValue| {
-                                                let is_before_min = if let Some(min) = min {
-                                                    date.year < min.year
-                                                        || (date.year == min.year
-                                                            && date.month < min.month)
-                                                        || (date.year == min.year
-                                                            && date.month == min.month
-                                                            && date.day < min.day)
-                                                } else {
-                                                    false
-                                                };
-
-                                                let is_after_max = if let Some(max) = max {
-                                                    date.year > max.year
-                                                        || (date.year == max.year
-                                                            && date.month > max.month)
-                                                        || (date.year == max.year
-                                                            && date.month == max.month
-                                                            && date.day > max.day)
-                                                } else {
-                                                    false
-                                                };
-
-                                                let is_in_disabled_list =
-                                                    disabled.iter().any(|d| d == date);
-                                                is_before_min || is_after_max || is_in_disabled_list
-                                            }
+                                        .focused_date(focused_date)
+                                        .show_year_navigation(true)
+                                        .locale(locale_ref.clone())
+                                        .is_date_disabled(move |date: &DateValue| {
+                                            !constraints.allows(date)
                                         })
-                                        .on_date_select({
-                                            let popover_for_dismiss = popover_entity.clone();
-                                            let on_select_for_date = on_select.clone();
-                                            move |date, window, app_cx| {
-                                                let is_before_min =
-                                                    if let Some(min) = min_date_clone {
-                                                        date.year < min.year
-                                                            || (date.year == min.year
-                                                                && date.month < min.month)
-                                                            || (date.year == min.year
-                                                                && date.month == min.month
-                                                                && date.day < min.day)
-                                                    } else {
-                                                        false
-                                                    };
-
-                                                let is_after_max = if let Some(max) = max_date_clone
-                                                {
-                                                    date.year > max.year
-                                                        || (date.year == max.year
-                                                            && date.month > max.month)
-                                                        || (date.year == max.year
-                                                            && date.month == max.month
-                                                            && date.day > max.day)
-                                                } else {
-                                                    false
-                                                };
-
-                                                let is_in_disabled_list =
-                                                    disabled_dates_clone.iter().any(|d| d == date);
-                                                let is_disabled = is_before_min
-                                                    || is_after_max
-                                                    || is_in_disabled_list;
-
-                                                if !is_disabled {
-                                                    // Check if we should close (different logic for single vs range mode)
-                                                    let should_close = state_for_select
-                                                        .read(app_cx)
-                                                        .selection_mode
-                                                        == DateSelectionMode::Single
-                                                        || state_for_select
-                                                            .read(app_cx)
-                                                            .range_start_temp
-                                                            .is_some(); // In range mode, close on second click
-
-                                                    // Update the date picker state
-                                                    state_for_select.update(app_cx, |state, cx| {
-                                                        state.select_date(*date, cx);
-                                                        if should_close {
-                                                            state.close(cx);
-                                                        }
-                                                        cx.notify(); // Notify to trigger re-render
-                                                    });
-
-                                                    // Call the on_select callback (only when selection is complete)
-                                                    if should_close {
-                                                        if let Some(handler) =
-                                                            on_select_for_date.as_ref()
-                                                        {
-                                                            handler(date, window, app_cx);
-                                                        }
-
-                                                        // Close the popover by emitting DismissEvent
-                                                        popover_for_dismiss.update(
-                                                            app_cx,
-                                                            |_, cx| {
-                                                                cx.emit(DismissEvent);
-                                                            },
-                                                        );
-                                                    }
-                                                }
-                                            }
+                                        .on_date_select(move |date, window, app_cx| {
+                                            select(date, window, app_cx);
                                         })
                                         .on_month_change(move |date, _window, app_cx| {
                                             state_for_month.update(app_cx, |state, cx| {
@@ -591,8 +730,6 @@ impl RenderOnce for DatePicker {
                                         })
                                 })
                                 .when(show_today_button, |parent_div| {
-                                    let popover_for_today = popover_entity.clone();
-                                    let on_select_for_today = on_select.clone();
                                     parent_div.child(
                                         div()
                                             .flex()
@@ -605,26 +742,10 @@ impl RenderOnce for DatePicker {
                                                     .variant(ButtonVariant::Outline)
                                                     .size(ButtonSize::Sm)
                                                     .on_click(move |_, window, app_cx| {
-                                                        let today = DateValue::new(2025, 1, 23);
-                                                        state_today.update(app_cx, |state, cx| {
-                                                            state.select_date(today, cx);
-                                                            state.close(cx);
-                                                            cx.notify(); // Notify to trigger re-render
-                                                        });
-
-                                                        // Call the on_select callback
-                                                        if let Some(handler) =
-                                                            on_select_for_today.as_ref()
-                                                        {
-                                                            handler(&today, window, app_cx);
-                                                        }
-
-                                                        // Close the popover
-                                                        popover_for_today.update(
+                                                        select_today(
+                                                            &DateValue::today(),
+                                                            window,
                                                             app_cx,
-                                                            |_, cx| {
-                                                                cx.emit(DismissEvent);
-                                                            },
                                                         );
                                                     }),
                                             ),
@@ -634,11 +755,180 @@ impl RenderOnce for DatePicker {
                         },
                     )
                 })
-            })
-            .map(|this| {
-                let mut popover = this;
-                popover.style().refine(&user_style);
-                popover
-            })
+            },
+        )
+    }
+}
+
+impl Styled for DatePicker {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+impl RenderOnce for DatePicker {
+    fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let theme = use_theme();
+        let state_entity = self.state.clone();
+        let state = self.state.read(cx);
+
+        let selection_mode = state.selection_mode;
+        let selected_date = state.selected_date;
+        let selected_range = state.selected_range;
+        let range_start_temp = state.range_start_temp;
+        let input = state.input.clone();
+        let disabled = self.disabled;
+
+        let has_value = match selection_mode {
+            DateSelectionMode::Single => selected_date.is_some(),
+            DateSelectionMode::Range => selected_range.is_some(),
+        };
+        let clear_button = (self.clearable && has_value && !disabled).then(|| {
+            let on_clear = self.on_clear.clone();
+            let state_for_clear = state_entity.clone();
+            let muted_bg = theme.tokens.muted;
+            div()
+                .p(px(2.0))
+                .rounded(px(4.0))
+                .cursor_pointer()
+                .hover(move |style| style.bg(muted_bg))
+                .on_mouse_down(
+                    MouseButton::Left,
+                    move |_event: &MouseDownEvent, window, cx| {
+                        cx.stop_propagation();
+                        state_for_clear.update(cx, |state, cx| {
+                            state.clear_date(cx);
+                            cx.notify();
+                        });
+                        if let Some(handler) = on_clear.as_ref() {
+                            handler(window, cx);
+                        }
+                    },
+                )
+                .child(
+                    Icon::new("x")
+                        .size(px(16.0))
+                        .color(theme.tokens.muted_foreground),
+                )
+        });
+        let calendar_icon = Icon::new("calendar")
+            .size(px(16.0))
+            .color(theme.tokens.muted_foreground);
+
+        let user_style = self.style.clone();
+
+        match selection_mode {
+            DateSelectionMode::Single => {
+                self.sync_text_field(window, cx);
+                let calendar_button = div()
+                    .p(px(2.0))
+                    .rounded(px(4.0))
+                    .when(!disabled, |this| {
+                        this.cursor_pointer()
+                            .hover(|style| style.bg(theme.tokens.muted))
+                    })
+                    .child(calendar_icon);
+                let suffix = div()
+                    .flex()
+                    .items_center()
+                    .gap(px(4.0))
+                    .children(clear_button)
+                    .map(|this| {
+                        if disabled {
+                            this.child(calendar_button)
+                        } else {
+                            this.child(
+                                self.calendar_popover(calendar_button)
+                                    .anchor(Corner::TopRight),
+                            )
+                        }
+                    });
+
+                div()
+                    .w_full()
+                    .child(
+                        Input::new(&input)
+                            .placeholder(self.placeholder.clone())
+                            .disabled(disabled)
+                            .suffix(suffix),
+                    )
+                    .map(|this| {
+                        let mut div = this;
+                        div.style().refine(&user_style);
+                        div
+                    })
+                    .into_any_element()
+            }
+            DateSelectionMode::Range => {
+                let display_text = match (selected_range, range_start_temp) {
+                    (Some(range), _) => format!(
+                        "{} – {}",
+                        self.format.format(&range.start, &self.locale),
+                        self.format.format(&range.end, &self.locale)
+                    ),
+                    (None, Some(start)) => {
+                        format!("{} – …", self.format.format(&start, &self.locale))
+                    }
+                    (None, None) => self.placeholder.to_string(),
+                };
+                let text_color = if has_value {
+                    theme.tokens.foreground
+                } else {
+                    theme.tokens.muted_foreground
+                };
+
+                let trigger = div()
+                    .flex()
+                    .items_center()
+                    .justify_between()
+                    .w_full()
+                    .h(px(40.0))
+                    .px(px(12.0))
+                    .gap(px(8.0))
+                    .bg(theme.tokens.background)
+                    .border_1()
+                    .border_color(theme.tokens.input)
+                    .rounded(theme.tokens.radius_md)
+                    .when(!disabled, |div| {
+                        div.cursor(CursorStyle::PointingHand)
+                            .hover(|style| style.border_color(theme.tokens.ring))
+                    })
+                    .when(disabled, |div| {
+                        div.cursor(CursorStyle::OperationNotAllowed).opacity(0.5)
+                    })
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_size(px(14.0))
+                            .text_color(text_color)
+                            .child(display_text),
+                    )
+                    .child(
+                        div()
+                            .flex()
+                            .gap(px(4.0))
+                            .children(clear_button)
+                            .child(calendar_icon),
+                    );
+
+                if disabled {
+                    return div()
+                        .child(trigger)
+                        .map(|this| {
+                            let mut div = this;
+                            div.style().refine(&user_style);
+                            div
+                        })
+                        .into_any_element();
+                }
+                self.calendar_popover(trigger)
+                    .map(|this| {
+                        let mut popover = this;
+                        popover.style().refine(&user_style);
+                        popover
+                    })
+                    .into_any_element()
+            }
+        }
     }
 }
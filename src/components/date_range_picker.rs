@@ -0,0 +1,522 @@
+//! DateRangePicker component - Range selection over two months side by side.
+//!
+//! The first click on a day starts the range and the second ends it, in either order;
+//! in between, the days up to the one under the pointer are drawn as the range they
+//! would make. Presets such as "Last 7 days" pick a whole range in one click. Either
+//! way, `on_change` is called once, with the range's first and last day.
+//!
+//! ```rust,ignore
+//! let state = cx.new(DateRangePickerState::new);
+//!
+//! DateRangePicker::new(state.clone())
+//!     .max_date(DateValue::today())
+//!     .on_change(|start, end, _window, cx| {
+//!         load_revenue(start, end, cx);
+//!     })
+//! ```
+
+use gpui::{prelude::FluentBuilder as _, *};
+use std::rc::Rc;
+
+use crate::components::button::{Button, ButtonSize, ButtonVariant};
+use crate::components::calendar::{Calendar, CalendarLocale, DateRange, DateValue};
+use crate::components::date_picker::{DateConstraints, DateFormat};
+use crate::components::icon::Icon;
+use crate::overlays::popover::{Popover, PopoverContent};
+use crate::theme::use_theme;
+
+/// A named range relative to today, offered as a shortcut next to the calendars.
+#[derive(Clone)]
+pub struct DateRangePreset {
+    pub label: SharedString,
+    range: Rc<dyn Fn(DateValue) -> DateRange>,
+}
+
+impl DateRangePreset {
+    /// A preset computing its range from today's date.
+    pub fn new(
+        label: impl Into<SharedString>,
+        range: impl Fn(DateValue) -> DateRange + 'static,
+    ) -> Self {
+        Self {
+            label: label.into(),
+            range: Rc::new(range),
+        }
+    }
+
+    /// The last `days` days, today included.
+    pub fn last_days(days: u32) -> Self {
+        Self::new(format!("Last {} days", days), move |today| DateRange {
+            start: today.add_days(1 - days.max(1) as i64),
+            end: today,
+        })
+    }
+
+    /// From the first of this month to today.
+    pub fn this_month() -> Self {
+        Self::new("This month", |today| DateRange {
+            start: DateValue::new(today.year, today.month, 1),
+            end: today,
+        })
+    }
+
+    pub fn last_month() -> Self {
+        Self::new("Last month", |today| {
+            let start = DateValue::new(today.year, today.month, 1).add_months(-1);
+            DateRange {
+                start,
+                end: DateValue::new(start.year, start.month, start.days_in_month()),
+            }
+        })
+    }
+
+    /// From the first of January to today.
+    pub fn year_to_date() -> Self {
+        Self::new("Year to date", |today| DateRange {
+            start: DateValue::new(today.year, 1, 1),
+            end: today,
+        })
+    }
+
+    /// Last 7 and 30 days, this month, last month and year to date.
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::last_days(7),
+            Self::last_days(30),
+            Self::this_month(),
+            Self::last_month(),
+            Self::year_to_date(),
+        ]
+    }
+
+    pub fn range(&self, today: DateValue) -> DateRange {
+        (self.range)(today)
+    }
+}
+
+pub struct DateRangePickerState {
+    range: Option<DateRange>,
+    /// The first day clicked, while waiting for the second.
+    anchor: Option<DateValue>,
+    hover: Option<DateValue>,
+    /// The month shown on the left; the one after it is on the right.
+    viewing_month: DateValue,
+}
+
+impl DateRangePickerState {
+    pub fn new(_cx: &mut App) -> Self {
+        let today = DateValue::today();
+        Self {
+            range: None,
+            anchor: None,
+            hover: None,
+            // Today on the right, as ranges are mostly picked looking back.
+            viewing_month: DateValue::new(today.year, today.month, 1).add_months(-1),
+        }
+    }
+
+    pub fn new_with_range(range: DateRange, cx: &mut App) -> Self {
+        let mut state = Self::new(cx);
+        state.set_range(Some(range), cx);
+        state
+    }
+
+    pub fn range(&self) -> Option<DateRange> {
+        self.range
+    }
+
+    /// Replace the range, putting its last day in view. The days are swapped if `end`
+    /// comes before `start`.
+    pub fn set_range(&mut self, range: Option<DateRange>, _cx: &mut App) {
+        self.range = range.map(|range| DateRange {
+            start: range.start.min(range.end),
+            end: range.start.max(range.end),
+        });
+        self.anchor = None;
+        self.hover = None;
+        if let Some(range) = self.range {
+            self.viewing_month = DateValue::new(range.end.year, range.end.month, 1).add_months(-1);
+        }
+    }
+
+    pub fn clear(&mut self, _cx: &mut App) {
+        self.range = None;
+        self.anchor = None;
+        self.hover = None;
+    }
+
+    pub fn set_viewing_month(&mut self, date: DateValue, _cx: &mut App) {
+        self.viewing_month = DateValue::new(date.year, date.month, 1);
+    }
+
+    /// Click `date`: the first click starts a range, the second returns it.
+    fn pick(&mut self, date: DateValue) -> Option<DateRange> {
+        match self.anchor.take() {
+            Some(anchor) => {
+                let range = DateRange {
+                    start: anchor.min(date),
+                    end: anchor.max(date),
+                };
+                self.range = Some(range);
+                self.hover = None;
+                Some(range)
+            }
+            None => {
+                self.anchor = Some(date);
+                None
+            }
+        }
+    }
+}
+
+/// DateRangePicker component with a two-month calendar popup and presets
+#[derive(IntoElement)]
+pub struct DateRangePicker {
+    state: Entity<DateRangePickerState>,
+    placeholder: SharedString,
+    format: DateFormat,
+    locale: CalendarLocale,
+    presets: Vec<DateRangePreset>,
+    constraints: DateConstraints,
+    disabled: bool,
+    clearable: bool,
+    on_change: Option<Rc<dyn Fn(DateValue, DateValue, &mut Window, &mut App)>>,
+    on_clear: Option<Rc<dyn Fn(&mut Window, &mut App)>>,
+    style: StyleRefinement,
+}
+
+impl DateRangePicker {
+    pub fn new(state: Entity<DateRangePickerState>) -> Self {
+        Self {
+            state,
+            placeholder: "Pick a date range".into(),
+            format: DateFormat::default(),
+            locale: CalendarLocale::default(),
+            presets: DateRangePreset::defaults(),
+            constraints: DateConstraints::default(),
+            disabled: false,
+            clearable: true,
+            on_change: None,
+            on_clear: None,
+            style: StyleRefinement::default(),
+        }
+    }
+
+    pub fn placeholder(mut self, placeholder: impl Into<SharedString>) -> Self {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    pub fn format(mut self, format: DateFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn locale(mut self, locale: CalendarLocale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// The shortcuts shown beside the calendars, replacing the defaults. An empty list
+    /// hides the column.
+    pub fn presets(mut self, presets: Vec<DateRangePreset>) -> Self {
+        self.presets = presets;
+        self
+    }
+
+    pub fn min_date(mut self, date: DateValue) -> Self {
+        self.constraints.min = Some(date);
+        self
+    }
+
+    pub fn max_date(mut self, date: DateValue) -> Self {
+        self.constraints.max = Some(date);
+        self
+    }
+
+    pub fn disabled_dates(mut self, dates: Vec<DateValue>) -> Self {
+        self.constraints.disabled = dates;
+        self
+    }
+
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+
+    pub fn clearable(mut self, clearable: bool) -> Self {
+        self.clearable = clearable;
+        self
+    }
+
+    /// Called with the first and last day once a range is picked, by clicking its two
+    /// ends or a preset.
+    pub fn on_change<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(DateValue, DateValue, &mut Window, &mut App) + 'static,
+    {
+        self.on_change = Some(Rc::new(handler));
+        self
+    }
+
+    pub fn on_clear<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&mut Window, &mut App) + 'static,
+    {
+        self.on_clear = Some(Rc::new(handler));
+        self
+    }
+
+    fn calendar_popover(&self, trigger: impl IntoElement + 'static) -> Popover {
+        let state_entity = self.state.clone();
+        let on_change = self.on_change.clone();
+        let locale = self.locale.clone();
+        let presets = self.presets.clone();
+        let constraints = self.constraints.clone();
+
+        let popover_id = ElementId::Name(
+            format!("date-range-picker-{}", state_entity.entity_id().as_u64()).into(),
+        );
+
+        Popover::new(popover_id).trigger(trigger).content(
+            move |window: &mut Window, app_cx: &mut App| {
+                let state_ref = state_entity.clone();
+                let on_change = on_change.clone();
+                let locale = locale.clone();
+                let presets = presets.clone();
+                let constraints = constraints.clone();
+
+                app_cx.new(move |cx| {
+                    PopoverContent::new(
+                        window,
+                        cx,
+                        move |_window, popover_cx: &mut Context<PopoverContent>| {
+                            let theme = use_theme();
+                            let state = state_ref.read(popover_cx);
+                            let left_month = state.viewing_month;
+                            let range = state.range;
+                            let anchor = state.anchor;
+                            let hover = state.hover;
+                            let popover = popover_cx.entity();
+
+                            // Store the range, tell the app and close.
+                            let commit: Rc<dyn Fn(DateRange, &mut Window, &mut App)> = {
+                                let state = state_ref.clone();
+                                let on_change = on_change.clone();
+                                Rc::new(move |range, window, cx| {
+                                    state.update(cx, |state, cx| {
+                                        state.set_range(Some(range), cx);
+                                        cx.notify();
+                                    });
+                                    if let Some(handler) = on_change.as_ref() {
+                                        handler(range.start, range.end, window, cx);
+                                    }
+                                    popover.update(cx, |_, cx| cx.emit(DismissEvent));
+                                })
+                            };
+
+                            let calendar = |month: DateValue, prev: bool, next: bool| {
+                                let state_for_pick = state_ref.clone();
+                                let state_for_hover = state_ref.clone();
+                                let state_for_month = state_ref.clone();
+                                let commit = commit.clone();
+                                let constraints = constraints.clone();
+                                Calendar::new()
+                                    .current_month(month)
+                                    .selected_range(range)
+                                    .range_start_temp(anchor)
+                                    .range_hover(hover)
+                                    .show_month_buttons(prev, next)
+                                    .locale(locale.clone())
+                                    .is_date_disabled(move |date: &DateValue| {
+                                        !constraints.allows(date)
+                                    })
+                                    .on_date_select(move |date, window, cx| {
+                                        let picked = state_for_pick.update(cx, |state, cx| {
+                                            cx.notify();
+                                            state.pick(*date)
+                                        });
+                                        if let Some(range) = picked {
+                                            commit(range, window, cx);
+                                        }
+                                    })
+                                    .on_date_hover(move |date, _window, cx| {
+                                        state_for_hover.update(cx, |state, cx| {
+                                            if state.anchor.is_some() && state.hover != Some(*date)
+                                            {
+                                                state.hover = Some(*date);
+                                                cx.notify();
+                                            }
+                                        });
+                                    })
+                                    .on_month_change(move |date, _window, cx| {
+                                        // Either calendar's buttons move both by a month.
+                                        let left = if next { date.add_months(-1) } else { *date };
+                                        state_for_month.update(cx, |state, cx| {
+                                            state.set_viewing_month(left, cx);
+                                            cx.notify();
+                                        });
+                                    })
+                            };
+
+                            let today = DateValue::today();
+                            div()
+                                .flex()
+                                .when(!presets.is_empty(), |this| {
+                                    this.child(
+                                        div()
+                                            .flex()
+                                            .flex_col()
+                                            .gap(px(2.0))
+                                            .pr(px(12.0))
+                                            .mr(px(4.0))
+                                            .border_r_1()
+                                            .border_color(theme.tokens.border)
+                                            .children(presets.iter().enumerate().map(
+                                                |(ix, preset)| {
+                                                    let preset_range = preset.range(today);
+                                                    let preset_range = DateRange {
+                                                        start: constraints
+                                                            .clamp(preset_range.start),
+                                                        end: constraints.clamp(preset_range.end),
+                                                    };
+                                                    let commit = commit.clone();
+                                                    Button::new(
+                                                        ("date-range-preset", ix),
+                                                        preset.label.clone(),
+                                                    )
+                                                    .variant(ButtonVariant::Ghost)
+                                                    .size(ButtonSize::Sm)
+                                                    .selected(range == Some(preset_range))
+                                                    .w_full()
+                                                    .justify_start()
+                                                    .on_click(move |_, window, cx| {
+                                                        commit(preset_range, window, cx);
+                                                    })
+                                                },
+                                            )),
+                                    )
+                                })
+                                .child(calendar(left_month, true, false))
+                                .child(calendar(left_month.add_months(1), false, true))
+                                .into_any_element()
+                        },
+                    )
+                })
+            },
+        )
+    }
+}
+
+impl Styled for DateRangePicker {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+impl RenderOnce for DateRangePicker {
+    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let theme = use_theme();
+        let state = self.state.read(cx);
+        let range = state.range;
+        let anchor = state.anchor;
+        let disabled = self.disabled;
+
+        let display_text = match (range, anchor) {
+            (_, Some(start)) => format!("{} – …", self.format.format(&start, &self.locale)),
+            (Some(range), None) => format!(
+                "{} – {}",
+                self.format.format(&range.start, &self.locale),
+                self.format.format(&range.end, &self.locale)
+            ),
+            (None, None) => self.placeholder.to_string(),
+        };
+        let text_color = if range.is_some() || anchor.is_some() {
+            theme.tokens.foreground
+        } else {
+            theme.tokens.muted_foreground
+        };
+
+        let clear_button = (self.clearable && range.is_some() && !disabled).then(|| {
+            let on_clear = self.on_clear.clone();
+            let state_for_clear = self.state.clone();
+            let muted_bg = theme.tokens.muted;
+            div()
+                .p(px(2.0))
+                .rounded(px(4.0))
+                .cursor_pointer()
+                .hover(move |style| style.bg(muted_bg))
+                .on_mouse_down(
+                    MouseButton::Left,
+                    move |_event: &MouseDownEvent, window, cx| {
+                        cx.stop_propagation();
+                        state_for_clear.update(cx, |state, cx| {
+                            state.clear(cx);
+                            cx.notify();
+                        });
+                        if let Some(handler) = on_clear.as_ref() {
+                            handler(window, cx);
+                        }
+                    },
+                )
+                .child(
+                    Icon::new("x")
+                        .size(px(16.0))
+                        .color(theme.tokens.muted_foreground),
+                )
+        });
+
+        let trigger = div()
+            .flex()
+            .items_center()
+            .justify_between()
+            .w_full()
+            .h(px(40.0))
+            .px(px(12.0))
+            .gap(px(8.0))
+            .bg(theme.tokens.background)
+            .border_1()
+            .border_color(theme.tokens.input)
+            .rounded(theme.tokens.radius_md)
+            .when(!disabled, |div| {
+                div.cursor(CursorStyle::PointingHand)
+                    .hover(|style| style.border_color(theme.tokens.ring))
+            })
+            .when(disabled, |div| {
+                div.cursor(CursorStyle::OperationNotAllowed).opacity(0.5)
+            })
+            .child(
+                div()
+                    .flex_1()
+                    .text_size(px(14.0))
+                    .text_color(text_color)
+                    .child(display_text),
+            )
+            .child(
+                div().flex().gap(px(4.0)).children(clear_button).child(
+                    Icon::new("calendar")
+                        .size(px(16.0))
+                        .color(theme.tokens.muted_foreground),
+                ),
+            );
+
+        let user_style = self.style.clone();
+        if disabled {
+            return div()
+                .child(trigger)
+                .map(|this| {
+                    let mut div = this;
+                    div.style().refine(&user_style);
+                    div
+                })
+                .into_any_element();
+        }
+        self.calendar_popover(trigger)
+            .map(|this| {
+                let mut popover = this;
+                popover.style().refine(&user_style);
+                popover
+            })
+            .into_any_element()
+    }
+}
@@ -0,0 +1,324 @@
+//! Side-by-side diff view.
+//!
+//! `DiffEditor` shows two versions of a text in aligned left and right panes. Removed,
+//! added and changed lines get tinted backgrounds, changed lines also highlight the
+//! words that differ, and long unchanged stretches fold down to a few lines of context
+//! that expand on click. Both panes live in one virtualized list, so they always
+//! scroll together.
+
+use crate::{
+    components::text_diff::{
+        diff_texts, fold_rows, parse_unified_diff, DiffItem, DiffRow, DiffRowKind, DiffSide,
+    },
+    navigation::virtual_list::v_virtual_list,
+    theme::use_theme,
+};
+use gpui::{prelude::FluentBuilder as _, *};
+use std::collections::HashSet;
+use std::rc::Rc;
+
+const DEFAULT_CONTEXT_LINES: usize = 3;
+
+pub struct DiffEditor {
+    rows: Rc<Vec<DiffRow>>,
+    items: Rc<Vec<DiffItem>>,
+    context_lines: usize,
+    /// First hidden row of each fold the user opened.
+    expanded: HashSet<usize>,
+    old_title: Option<SharedString>,
+    new_title: Option<SharedString>,
+    scroll_handle: ScrollHandle,
+    style: StyleRefinement,
+}
+
+impl DiffEditor {
+    pub fn new(_cx: &mut Context<Self>) -> Self {
+        Self {
+            rows: Rc::new(Vec::new()),
+            items: Rc::new(Vec::new()),
+            context_lines: DEFAULT_CONTEXT_LINES,
+            expanded: HashSet::new(),
+            old_title: None,
+            new_title: None,
+            scroll_handle: ScrollHandle::new(),
+            style: StyleRefinement::default(),
+        }
+    }
+
+    /// Headings above the panes, e.g. `HEAD` and `Working tree`.
+    pub fn titles(mut self, old: impl Into<SharedString>, new: impl Into<SharedString>) -> Self {
+        self.old_title = Some(old.into());
+        self.new_title = Some(new.into());
+        self
+    }
+
+    /// Unchanged lines kept around each change when folding. Defaults to 3.
+    pub fn context_lines(mut self, lines: usize) -> Self {
+        self.context_lines = lines;
+        self.refold();
+        self
+    }
+
+    pub fn set_context_lines(&mut self, lines: usize, cx: &mut Context<Self>) {
+        self.context_lines = lines;
+        self.refold();
+        cx.notify();
+    }
+
+    /// Compare two full texts.
+    pub fn set_texts(&mut self, old: &str, new: &str, cx: &mut Context<Self>) {
+        self.set_rows(diff_texts(old, new), cx);
+    }
+
+    /// Show a unified diff. Only the lines in its hunks are available, so the gaps
+    /// between hunks can't be expanded.
+    pub fn set_unified_diff(&mut self, diff: &str, cx: &mut Context<Self>) {
+        self.set_rows(parse_unified_diff(diff), cx);
+    }
+
+    fn set_rows(&mut self, rows: Vec<DiffRow>, cx: &mut Context<Self>) {
+        self.rows = Rc::new(rows);
+        self.expanded.clear();
+        self.refold();
+        self.scroll_handle.set_offset(point(px(0.0), px(0.0)));
+        cx.notify();
+    }
+
+    pub fn rows(&self) -> &[DiffRow] {
+        &self.rows
+    }
+
+    /// Lines added and removed, counting a changed line as one of each.
+    pub fn stats(&self) -> (usize, usize) {
+        self.rows
+            .iter()
+            .fold((0, 0), |(added, removed), row| match row.kind {
+                DiffRowKind::Added => (added + 1, removed),
+                DiffRowKind::Removed => (added, removed + 1),
+                DiffRowKind::Changed => (added + 1, removed + 1),
+                _ => (added, removed),
+            })
+    }
+
+    pub fn expand_all(&mut self, cx: &mut Context<Self>) {
+        for item in self.items.iter() {
+            if let DiffItem::Fold(rows) = item {
+                self.expanded.insert(rows.start);
+            }
+        }
+        self.refold();
+        cx.notify();
+    }
+
+    fn expand_fold(&mut self, first_row: usize, cx: &mut Context<Self>) {
+        self.expanded.insert(first_row);
+        self.refold();
+        cx.notify();
+    }
+
+    fn refold(&mut self) {
+        self.items = Rc::new(fold_rows(&self.rows, self.context_lines, &self.expanded));
+    }
+}
+
+impl Styled for DiffEditor {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+struct DiffColors {
+    added: Hsla,
+    removed: Hsla,
+    added_word: Hsla,
+    removed_word: Hsla,
+    filler: Hsla,
+    line_number: Hsla,
+    text: Hsla,
+}
+
+impl Render for DiffEditor {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = use_theme();
+        let user_style = self.style.clone();
+        let line_height = px(20.0);
+        let item_sizes = Rc::new(vec![size(px(0.0), line_height); self.items.len()]);
+        let has_titles = self.old_title.is_some() || self.new_title.is_some();
+
+        div()
+            .flex()
+            .flex_col()
+            .size_full()
+            .bg(theme.tokens.background)
+            .font_family(theme.tokens.font_mono.clone())
+            .text_size(px(13.0))
+            .map(|this| {
+                let mut div = this;
+                div.style().refine(&user_style);
+                div
+            })
+            .when(has_titles, |this| {
+                let title = |title: Option<SharedString>| {
+                    div()
+                        .flex_1()
+                        .px(px(12.0))
+                        .py(px(6.0))
+                        .overflow_hidden()
+                        .whitespace_nowrap()
+                        .text_color(theme.tokens.muted_foreground)
+                        .children(title)
+                };
+                this.child(
+                    div()
+                        .flex()
+                        .border_b_1()
+                        .border_color(theme.tokens.border)
+                        .child(title(self.old_title.clone()))
+                        .child(div().w(px(1.0)).bg(theme.tokens.border))
+                        .child(title(self.new_title.clone())),
+                )
+            })
+            .child(
+                div().flex_1().overflow_hidden().child(
+                    v_virtual_list(
+                        cx.entity(),
+                        "diff-rows",
+                        item_sizes,
+                        move |editor, range, _window, cx| {
+                            let theme = use_theme();
+                            let added = hsla(0.36, 0.55, 0.48, 1.0);
+                            let removed = hsla(0.0, 0.70, 0.58, 1.0);
+                            let colors = DiffColors {
+                                added: added.opacity(0.12),
+                                removed: removed.opacity(0.12),
+                                added_word: added.opacity(0.35),
+                                removed_word: removed.opacity(0.35),
+                                filler: theme.tokens.muted.opacity(0.4),
+                                line_number: theme.tokens.muted_foreground,
+                                text: theme.tokens.foreground,
+                            };
+                            let items = editor.items.clone();
+                            range
+                                .map(|ix| match &items[ix] {
+                                    DiffItem::Row(row) => {
+                                        diff_row(&editor.rows[*row], line_height, &colors)
+                                            .into_any_element()
+                                    }
+                                    DiffItem::Fold(rows) => {
+                                        let first_row = rows.start;
+                                        fold_row(
+                                            format!("⋯ {} unchanged lines", rows.len()),
+                                            line_height,
+                                        )
+                                        .cursor_pointer()
+                                        .hover(|style| style.bg(theme.tokens.muted))
+                                        .on_mouse_down(
+                                            MouseButton::Left,
+                                            cx.listener(move |editor, _, _, cx| {
+                                                editor.expand_fold(first_row, cx)
+                                            }),
+                                        )
+                                        .into_any_element()
+                                    }
+                                })
+                                .collect::<Vec<_>>()
+                        },
+                    )
+                    .track_scroll(&self.scroll_handle),
+                ),
+            )
+    }
+}
+
+fn diff_row(row: &DiffRow, line_height: Pixels, colors: &DiffColors) -> AnyElement {
+    if let DiffRowKind::Skipped(lines) = row.kind {
+        return fold_row(format!("⋯ {} lines not in the diff", lines), line_height)
+            .into_any_element();
+    }
+    let (old_bg, new_bg) = match row.kind {
+        DiffRowKind::Removed => (Some(colors.removed), None),
+        DiffRowKind::Added => (None, Some(colors.added)),
+        DiffRowKind::Changed => (Some(colors.removed), Some(colors.added)),
+        _ => (None, None),
+    };
+    div()
+        .flex()
+        .h(line_height)
+        .child(diff_cell(
+            row.old.as_ref(),
+            old_bg,
+            colors.removed_word,
+            colors,
+        ))
+        .child(div().w(px(1.0)).h_full().bg(colors.filler))
+        .child(diff_cell(
+            row.new.as_ref(),
+            new_bg,
+            colors.added_word,
+            colors,
+        ))
+        .into_any_element()
+}
+
+/// One pane's half of a row; `None` is filler facing a line the other side added
+/// or removed.
+fn diff_cell(
+    side: Option<&DiffSide>,
+    background: Option<Hsla>,
+    word_background: Hsla,
+    colors: &DiffColors,
+) -> Div {
+    let cell = div()
+        .flex()
+        .flex_1()
+        .min_w(px(0.0))
+        .h_full()
+        .items_center()
+        .overflow_hidden()
+        .whitespace_nowrap();
+    let Some(side) = side else {
+        return cell.bg(colors.filler);
+    };
+    let text = SharedString::from(side.text.clone());
+    let highlights = side
+        .changes
+        .iter()
+        .map(|range| {
+            let style = HighlightStyle {
+                background_color: Some(word_background),
+                ..Default::default()
+            };
+            (range.clone(), style)
+        })
+        .collect::<Vec<_>>();
+
+    cell.when_some(background, |this, bg| this.bg(bg))
+        .child(
+            div()
+                .flex_none()
+                .w(px(48.0))
+                .pr(px(8.0))
+                .flex()
+                .justify_end()
+                .text_color(colors.line_number)
+                .child(side.line.to_string()),
+        )
+        .child(
+            div()
+                .text_color(colors.text)
+                .child(StyledText::new(text).with_highlights(highlights)),
+        )
+}
+
+fn fold_row(label: String, line_height: Pixels) -> Div {
+    let theme = use_theme();
+    div()
+        .flex()
+        .items_center()
+        .h(line_height)
+        .pl(px(56.0))
+        .bg(theme.tokens.muted.opacity(0.5))
+        .text_color(theme.tokens.muted_foreground)
+        .text_size(px(12.0))
+        .child(label)
+}
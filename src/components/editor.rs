@@ -1,120 +1,112 @@
-use crate::components::scrollable::scrollable_vertical;
+use crate::activity;
+use crate::components::auto_indent::{indent_width, newline_indent, reindent_paste, NewlineIndent};
+use crate::components::color_literal::{find_color_literals, format_color_literal, ColorLiteral};
+use crate::components::color_picker::{ColorPickerPanel, ColorPickerState};
+use crate::components::editor_keymap::{set_keymap, EditorKeymap};
+use crate::components::editor_settings::{
+    EditorSettings, EditorSettingsOverride, LanguageSettings,
+};
+use crate::components::image_hover::{path_literal_at, resolve_image_path, ImagePreview};
+use crate::components::input::{Input, InputSize};
+use crate::components::input_history::UNDO_GROUP_INTERVAL;
+use crate::components::input_state::{InputEvent, InputState};
+use crate::components::scrollable::{scrollable_vertical, SmoothScroll};
+use crate::components::snippet::{Snippet, SnippetSession};
+use crate::components::spell_check::{misspelled_words, Misspelling, SpellChecker};
+use crate::components::text_encoding::TextEncoding;
 use crate::icon_config::resolve_icon_path;
 use crate::theme::use_theme;
+use crate::util::{snap_length, SnapToDevicePixels};
 use gpui::{prelude::FluentBuilder as _, *};
 use regex::Regex;
-use ropey::Rope;
+use regex_cursor::engines::meta::Regex as RopeRegex;
+use regex_cursor::{Input as RopeInput, RopeyCursor};
+use ropey::{Rope, RopeSlice};
 use smol::Timer;
 use std::cmp::min;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ops::Range;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::sync::Arc;
 use std::time::Duration;
 use tree_sitter::{
     InputEdit, Parser, Point as TSPoint, Query, QueryCursor, StreamingIterator, Tree,
 };
 
-actions!(
-    editor,
-    [
-        MoveUp,
-        MoveDown,
-        MoveLeft,
-        MoveRight,
-        MoveToLineStart,
-        MoveToLineEnd,
-        MoveToDocStart,
-        MoveToDocEnd,
-        MoveWordLeft,
-        MoveWordRight,
-        PageUp,
-        PageDown,
-        SelectUp,
-        SelectDown,
-        SelectLeft,
-        SelectRight,
-        SelectToLineStart,
-        SelectToLineEnd,
-        SelectAll,
-        Backspace,
-        Delete,
-        DeleteWord,
-        Enter,
-        Tab,
-        Copy,
-        Cut,
-        Paste,
-        Undo,
-        Redo,
-    ]
+/// Declares the editor's actions along with [`EDITOR_ACTIONS`] and a lookup that binds
+/// them by name, so [`crate::components::editor_keymap`] can map config entries to them.
+macro_rules! editor_actions {
+    ($($action:ident),* $(,)?) => {
+        actions!(editor, [$($action),*]);
+
+        /// Names of every editor action, as used in an [`EditorKeymap`].
+        pub const EDITOR_ACTIONS: &[&str] = &[$(stringify!($action)),*];
+
+        /// A binding of `keystrokes` to the editor action called `name`, with or without
+        /// the `editor::` namespace.
+        pub(crate) fn action_binding(
+            name: &str,
+            keystrokes: &str,
+            context: Option<&str>,
+        ) -> Option<KeyBinding> {
+            match name.strip_prefix("editor::").unwrap_or(name) {
+                $(stringify!($action) => Some(KeyBinding::new(keystrokes, $action, context)),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+editor_actions!(
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    MoveToLineStart,
+    MoveToLineEnd,
+    MoveToDocStart,
+    MoveToDocEnd,
+    MoveWordLeft,
+    MoveWordRight,
+    PageUp,
+    PageDown,
+    SelectUp,
+    SelectDown,
+    SelectLeft,
+    SelectRight,
+    SelectToLineStart,
+    SelectToLineEnd,
+    SelectAll,
+    SelectNextOccurrence,
+    SelectAllOccurrences,
+    Backspace,
+    Delete,
+    DeleteWord,
+    Enter,
+    Tab,
+    ShiftTab,
+    Indent,
+    Outdent,
+    Copy,
+    Cut,
+    Paste,
+    Undo,
+    Redo,
+    ToggleComment,
+    SelectColumnUp,
+    SelectColumnDown,
+    SelectColumnLeft,
+    SelectColumnRight,
+    ShowCodeActions,
+    Rename,
+    NavigateBack,
+    NavigateForward,
 );
 
+/// Bind the default [`EditorKeymap`]. Call [`set_keymap`] afterwards to remap.
 pub fn init(cx: &mut App) {
-    cx.bind_keys([
-        KeyBinding::new("up", MoveUp, Some("Editor")),
-        KeyBinding::new("down", MoveDown, Some("Editor")),
-        KeyBinding::new("left", MoveLeft, Some("Editor")),
-        KeyBinding::new("right", MoveRight, Some("Editor")),
-        KeyBinding::new("home", MoveToLineStart, Some("Editor")),
-        KeyBinding::new("end", MoveToLineEnd, Some("Editor")),
-        #[cfg(target_os = "macos")]
-        KeyBinding::new("alt-left", MoveWordLeft, Some("Editor")),
-        #[cfg(not(target_os = "macos"))]
-        KeyBinding::new("ctrl-left", MoveWordLeft, Some("Editor")),
-        #[cfg(target_os = "macos")]
-        KeyBinding::new("alt-right", MoveWordRight, Some("Editor")),
-        #[cfg(not(target_os = "macos"))]
-        KeyBinding::new("ctrl-right", MoveWordRight, Some("Editor")),
-        #[cfg(target_os = "macos")]
-        KeyBinding::new("cmd-up", MoveToDocStart, Some("Editor")),
-        #[cfg(not(target_os = "macos"))]
-        KeyBinding::new("ctrl-home", MoveToDocStart, Some("Editor")),
-        #[cfg(target_os = "macos")]
-        KeyBinding::new("cmd-down", MoveToDocEnd, Some("Editor")),
-        #[cfg(not(target_os = "macos"))]
-        KeyBinding::new("ctrl-end", MoveToDocEnd, Some("Editor")),
-        KeyBinding::new("pageup", PageUp, Some("Editor")),
-        KeyBinding::new("pagedown", PageDown, Some("Editor")),
-        KeyBinding::new("shift-up", SelectUp, Some("Editor")),
-        KeyBinding::new("shift-down", SelectDown, Some("Editor")),
-        KeyBinding::new("shift-left", SelectLeft, Some("Editor")),
-        KeyBinding::new("shift-right", SelectRight, Some("Editor")),
-        KeyBinding::new("shift-home", SelectToLineStart, Some("Editor")),
-        KeyBinding::new("shift-end", SelectToLineEnd, Some("Editor")),
-        #[cfg(target_os = "macos")]
-        KeyBinding::new("cmd-a", SelectAll, Some("Editor")),
-        #[cfg(not(target_os = "macos"))]
-        KeyBinding::new("ctrl-a", SelectAll, Some("Editor")),
-        KeyBinding::new("backspace", Backspace, Some("Editor")),
-        KeyBinding::new("delete", Delete, Some("Editor")),
-        #[cfg(target_os = "macos")]
-        KeyBinding::new("alt-backspace", DeleteWord, Some("Editor")),
-        #[cfg(not(target_os = "macos"))]
-        KeyBinding::new("ctrl-backspace", DeleteWord, Some("Editor")),
-        KeyBinding::new("enter", Enter, Some("Editor")),
-        KeyBinding::new("tab", Tab, Some("Editor")),
-        #[cfg(target_os = "macos")]
-        KeyBinding::new("cmd-c", Copy, Some("Editor")),
-        #[cfg(not(target_os = "macos"))]
-        KeyBinding::new("ctrl-c", Copy, Some("Editor")),
-        #[cfg(target_os = "macos")]
-        KeyBinding::new("cmd-x", Cut, Some("Editor")),
-        #[cfg(not(target_os = "macos"))]
-        KeyBinding::new("ctrl-x", Cut, Some("Editor")),
-        #[cfg(target_os = "macos")]
-        KeyBinding::new("cmd-v", Paste, Some("Editor")),
-        #[cfg(not(target_os = "macos"))]
-        KeyBinding::new("ctrl-v", Paste, Some("Editor")),
-        #[cfg(target_os = "macos")]
-        KeyBinding::new("cmd-z", Undo, Some("Editor")),
-        #[cfg(not(target_os = "macos"))]
-        KeyBinding::new("ctrl-z", Undo, Some("Editor")),
-        #[cfg(target_os = "macos")]
-        KeyBinding::new("cmd-shift-z", Redo, Some("Editor")),
-        #[cfg(not(target_os = "macos"))]
-        KeyBinding::new("ctrl-shift-z", Redo, Some("Editor")),
-    ]);
+    set_keymap(EditorKeymap::defaults(), cx);
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -133,6 +125,158 @@ impl Position {
     }
 }
 
+/// Line terminator used when a buffer is saved. The buffer itself always uses `\n`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    /// The ending used by most lines of `text`, `Lf` when there are no line breaks.
+    pub fn detect(text: &str) -> Self {
+        let crlf = text.matches("\r\n").count();
+        let lf = text.matches('\n').count() - crlf;
+        if crlf > lf {
+            LineEnding::CrLf
+        } else {
+            LineEnding::Lf
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+
+    /// Short name for status bars.
+    pub fn label(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "LF",
+            LineEnding::CrLf => "CRLF",
+        }
+    }
+}
+
+/// Replace `\r\n` and lone `\r` with `\n`.
+/// The regex for a search query, made case-insensitive unless `case_sensitive`.
+fn search_regex(query: &str, case_sensitive: bool) -> Result<Regex, regex::Error> {
+    if case_sensitive {
+        Regex::new(query)
+    } else {
+        Regex::new(&format!("(?i){}", query))
+    }
+}
+
+/// Parse `rope` by feeding tree-sitter its chunks, so the text is never copied out.
+fn parse_rope(parser: &mut Parser, rope: &Rope, old_tree: Option<&Tree>) -> Option<Tree> {
+    parser.parse_with_options(
+        &mut |byte_idx, _pos| -> &[u8] {
+            if byte_idx >= rope.len_bytes() {
+                return &[];
+            }
+            let (chunk, start, _, _) = rope.chunk_at_byte(byte_idx);
+            &chunk.as_bytes()[byte_idx - start..]
+        },
+        old_tree,
+        None,
+    )
+}
+
+/// Builds the regex used to search a rope. Plain queries are escaped so both modes
+/// share the chunked matcher.
+fn rope_search_regex(query: &str, use_regex: bool, case_sensitive: bool) -> Option<RopeRegex> {
+    let pattern = if use_regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    let pattern = if case_sensitive {
+        pattern
+    } else {
+        format!("(?i){}", pattern)
+    };
+    match RopeRegex::new(&pattern) {
+        Ok(regex) => Some(regex),
+        Err(err) => {
+            tracing::debug!(error = %err, "invalid search regex");
+            None
+        }
+    }
+}
+
+/// Non-overlapping matches of `regex` in `rope`, read chunk by chunk instead of from a
+/// copy of the whole buffer.
+fn rope_matches(rope: &Rope, regex: &RopeRegex) -> Vec<Range<usize>> {
+    let input = RopeInput::new(RopeyCursor::new(rope.slice(..)));
+    regex.find_iter(input).map(|m| m.start()..m.end()).collect()
+}
+
+/// Non-overlapping matches of `query` in `rope`; with `whole_word`, only those not
+/// adjoining other word characters.
+fn occurrence_ranges(rope: &Rope, query: &str, whole_word: bool) -> Vec<Range<usize>> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let Some(regex) = rope_search_regex(query, false, true) else {
+        return Vec::new();
+    };
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    rope_matches(rope, &regex)
+        .into_iter()
+        .filter(|range| {
+            let before = rope.chars_at(rope.byte_to_char(range.start)).prev();
+            let after = rope.chars_at(rope.byte_to_char(range.end)).next();
+            !whole_word || !(before.is_some_and(is_word_char) || after.is_some_and(is_word_char))
+        })
+        .collect()
+}
+
+/// Where byte `offset` ends up after `edits`, which are sorted and non-overlapping.
+/// Offsets inside a replaced range move to the end of its replacement; an offset at
+/// an insertion point stays before the inserted text.
+fn map_through_edits(offset: usize, edits: &[(Range<usize>, String)]) -> usize {
+    let mut mapped = offset;
+    for (range, text) in edits {
+        if offset <= range.start {
+            break;
+        }
+        if offset >= range.end {
+            mapped = mapped - range.len() + text.len();
+        } else {
+            mapped = mapped - (offset - range.start) + text.len();
+            break;
+        }
+    }
+    mapped
+}
+
+/// [`ColorPickerState`] keeps hue in degrees, where gpui's [`Hsla`] uses 0 to 1.
+fn hue_to_degrees(color: Hsla) -> Hsla {
+    Hsla {
+        h: color.h * 360.0,
+        ..color
+    }
+}
+
+fn hue_from_degrees(color: Hsla) -> Hsla {
+    Hsla {
+        h: (color.h / 360.0).rem_euclid(1.0),
+        ..color
+    }
+}
+
+fn normalize_line_endings(text: &str) -> std::borrow::Cow<'_, str> {
+    if text.contains('\r') {
+        std::borrow::Cow::Owned(text.replace("\r\n", "\n").replace('\r', "\n"))
+    } else {
+        std::borrow::Cow::Borrowed(text)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Selection {
     pub anchor: Position,
@@ -157,18 +301,197 @@ impl Selection {
     }
 }
 
+/// The unit a mouse drag extends the selection by, set by the click that started it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DragGranularity {
+    Char,
+    /// Double-click: whole words, never shrinking below the word first clicked.
+    Word {
+        start: Position,
+        end: Position,
+    },
+    /// Triple-click: whole lines, never shrinking below the line first clicked.
+    Line(usize),
+}
+
 #[derive(Debug, Clone)]
 enum EditOp {
     Insert { byte_offset: usize, text: String },
     Delete { byte_offset: usize, text: String },
 }
 
+impl EditOp {
+    /// A single typed or deleted character, the kind of edit that coalesces.
+    fn is_single_char(&self) -> bool {
+        let (EditOp::Insert { text, .. } | EditOp::Delete { text, .. }) = self;
+        let mut chars = text.chars();
+        matches!((chars.next(), chars.next()), (Some(ch), None) if ch != '\n')
+    }
+}
+
+/// Cursor moves across at least this many lines are recorded for `NavigateBack`.
+const NAV_JUMP_MIN_LINES: usize = 10;
+const NAV_HISTORY_LIMIT: usize = 50;
+
+/// Hover time before an image path's preview is loaded.
+const IMAGE_HOVER_DELAY: Duration = Duration::from_millis(300);
+
+const DEFAULT_CURSOR_BLINK: Duration = Duration::from_millis(500);
+/// How long a smooth caret takes to glide to its new position.
+const CARET_GLIDE: Duration = Duration::from_millis(90);
+
+/// How the caret is drawn. See [`Editor::cursor_shape`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CursorShape {
+    /// A vertical bar before the character, `cursor_width` wide.
+    #[default]
+    Bar,
+    /// A translucent box over the character, as wide as it.
+    Block,
+    /// A line under the character, `cursor_width` thick.
+    Underline,
+}
+
+/// How the line holding the cursor is marked. See [`Editor::line_highlight`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineHighlight {
+    None,
+    /// Fill the whole line, gutter included.
+    #[default]
+    Full,
+    /// Fill only the line's gutter.
+    Gutter,
+    /// Rule the text area above and below the line.
+    Border,
+}
+
+/// The primary caret's glide between positions, in content coordinates so that
+/// scrolling doesn't restart it.
+#[derive(Debug, Clone, Copy)]
+struct CaretGlide {
+    from: Point<Pixels>,
+    to: Point<Pixels>,
+    started: std::time::Instant,
+}
+
+impl CaretGlide {
+    /// Where the caret is drawn now, and whether it's still moving.
+    fn position(&self) -> (Point<Pixels>, bool) {
+        let t = self.started.elapsed().as_secs_f32() / CARET_GLIDE.as_secs_f32();
+        if t >= 1.0 {
+            return (self.to, false);
+        }
+        let eased = 1.0 - (1.0 - t).powi(3);
+        let position = point(
+            crate::animations::lerp_pixels(self.from.x, self.to.x, eased),
+            crate::animations::lerp_pixels(self.from.y, self.to.y, eased),
+        );
+        (position, true)
+    }
+}
+
+/// One undo step: edits applied in order, reverted in reverse, plus the selections
+/// (primary first) to restore on either side of it.
+#[derive(Debug, Clone)]
+struct UndoTransaction {
+    ops: Vec<EditOp>,
+    selections_before: Vec<Selection>,
+    /// Empty until the step is finished; redo then falls back to the last edit's end.
+    selections_after: Vec<Selection>,
+    last_edit: std::time::Instant,
+    /// Whether further single-character edits may still join this step.
+    open: bool,
+}
+
+impl UndoTransaction {
+    fn new(op: EditOp, selections_before: Vec<Selection>, now: std::time::Instant) -> Self {
+        Self {
+            open: op.is_single_char(),
+            ops: vec![op],
+            selections_before,
+            selections_after: Vec::new(),
+            last_edit: now,
+        }
+    }
+
+    /// Typing continues a run when it lands right after the previous character and
+    /// doesn't start a new word; deleting continues when it is adjacent on either side.
+    fn coalesces_with(&self, op: &EditOp, now: std::time::Instant) -> bool {
+        if !self.open
+            || !op.is_single_char()
+            || now.duration_since(self.last_edit) > UNDO_GROUP_INTERVAL
+        {
+            return false;
+        }
+        match (self.ops.last(), op) {
+            (
+                Some(EditOp::Insert {
+                    byte_offset: prev_offset,
+                    text: prev_text,
+                }),
+                EditOp::Insert { byte_offset, text },
+            ) => {
+                *byte_offset == prev_offset + prev_text.len()
+                    && !(text.starts_with(char::is_whitespace)
+                        && !prev_text.ends_with(char::is_whitespace))
+            }
+            (
+                Some(EditOp::Delete {
+                    byte_offset: prev_offset,
+                    ..
+                }),
+                EditOp::Delete { byte_offset, text },
+            ) => byte_offset + text.len() == *prev_offset || byte_offset == prev_offset,
+            _ => false,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct FoldRange {
     pub start_line: usize,
     pub end_line: usize,
 }
 
+/// Where a user was in a buffer: cursors, scroll position and folds. Taken from an
+/// editor with [`EditorState::session`] when its tab closes and put back with
+/// [`EditorState::restore_session`] when the tab is reopened.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditorSession {
+    /// All selections, primary first, as [`EditorState::selections`] returns them.
+    pub selections: Vec<Selection>,
+    /// Top of the viewport as a fractional buffer line.
+    pub scroll_top_line: f32,
+    pub scroll_x: f32,
+    pub folded: Vec<FoldRange>,
+}
+
+/// A syntax-derived guide along the body of a bracketed block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ScopeGuide {
+    /// Buffer lines between the block's opening and closing lines.
+    lines: Range<usize>,
+    /// Byte indentation of the opening line, where the guide is drawn.
+    column: usize,
+    active: bool,
+}
+
+const MINIMAP_WIDTH: f32 = 96.0;
+const STICKY_SCROLL_MAX_LINES: usize = 5;
+type WordOccurrenceKey = (u64, Position, Range<usize>);
+
+/// Rows highlighted beyond each edge of the viewport.
+const HIGHLIGHT_OVERSCAN_ROWS: usize = 50;
+const MINIMAP_ROW_HEIGHT: f32 = 2.0;
+const MINIMAP_CHAR_WIDTH: f32 = 1.0;
+/// Lines longer than this many bytes are shaped a window at a time around the visible
+/// columns instead of whole, so minified files and long log lines stay responsive.
+const LONG_LINE_BYTES: usize = 4096;
+/// Columns a long line's shaped window reaches past the viewport on either side. The
+/// window moves in steps of this size, so it is reshaped only every so often while
+/// scrolling sideways.
+const LONG_LINE_WINDOW_STEP: usize = 512;
+
 const AUTO_CLOSE_PAIRS: &[(char, char)] = &[
     ('(', ')'),
     ('[', ']'),
@@ -178,6 +501,14 @@ const AUTO_CLOSE_PAIRS: &[(char, char)] = &[
     ('`', '`'),
 ];
 
+/// Comment syntax for a language. Languages without a line comment token
+/// fall back to wrapping each line in the block delimiters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommentTokens {
+    pub line: Option<&'static str>,
+    pub block: Option<(&'static str, &'static str)>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Language {
     Rust,
@@ -269,6 +600,33 @@ impl Language {
         }
     }
 
+    pub fn comment_tokens(&self) -> CommentTokens {
+        let (line, block) = match self {
+            Language::Rust
+            | Language::JavaScript
+            | Language::TypeScript
+            | Language::Go
+            | Language::C
+            | Language::Cpp
+            | Language::Java
+            | Language::Scala
+            | Language::Php => (Some("//"), Some(("/*", "*/"))),
+            Language::Json | Language::Zig => (Some("//"), None),
+            Language::Python
+            | Language::Toml
+            | Language::Ruby
+            | Language::Bash
+            | Language::Yaml => (Some("#"), None),
+            Language::Lua => (Some("--"), Some(("--[[", "]]"))),
+            Language::Sql => (Some("--"), Some(("/*", "*/"))),
+            Language::Css => (None, Some(("/*", "*/"))),
+            Language::Html | Language::Markdown => (None, Some(("<!--", "-->"))),
+            Language::OCaml => (None, Some(("(*", "*)"))),
+            Language::Plain => (None, None),
+        };
+        CommentTokens { line, block }
+    }
+
     pub fn tree_sitter_language(&self) -> Option<tree_sitter::Language> {
         match self {
             #[cfg(feature = "tree-sitter-rust")]
@@ -486,46 +844,108 @@ pub struct EditorState {
     cursor: Position,
     selection: Option<Selection>,
 
-    undo_stack: Vec<EditOp>,
-    redo_stack: Vec<EditOp>,
+    undo_stack: Vec<UndoTransaction>,
+    redo_stack: Vec<UndoTransaction>,
+    /// Undo stack length when the outermost open transaction began.
+    undo_transaction_start: Option<usize>,
+    undo_transaction_depth: usize,
+    /// Selections when the outermost open transaction began.
+    undo_selections_before: Option<Vec<Selection>>,
 
     file_path: Option<PathBuf>,
     is_modified: bool,
+    line_ending: LineEnding,
+    encoding: TextEncoding,
     content_version: u64,
 
     parser: Parser,
     syntax_tree: Option<Tree>,
-    highlight_query: Option<Query>,
+    highlight_query: Option<Arc<Query>>,
     language: Language,
 
     scroll_handle: ScrollHandle,
     scroll_offset_x: Pixels,
     max_line_width: Pixels,
-    line_layouts: HashMap<usize, ShapedLine>,
+    line_layouts: HashMap<usize, DisplayLineLayout>,
     line_content_hashes: HashMap<usize, u64>,
     cached_highlight_spans: Vec<HighlightSpan>,
     highlight_cache_version: u64,
     highlight_cache_first_line: usize,
     highlight_cache_last_line: usize,
+    /// Content version and line range being highlighted in the background.
+    highlight_request: Option<(u64, usize, usize)>,
+    highlight_task: Option<Task<()>>,
+    /// Bracket pair and word occurrences painted around the cursor, keyed by content
+    /// version, cursor and (for occurrences) the visible lines.
+    bracket_match_cache: Option<((u64, Position), Option<(Position, Position)>)>,
+    word_occurrence_cache: Option<(WordOccurrenceKey, Rc<Vec<(usize, usize, usize)>>)>,
     last_bounds: Option<Bounds<Pixels>>,
+    /// Vertical scroll offset `last_bounds` was painted at.
+    painted_scroll_y: Pixels,
+    /// Buffer lines painted as sticky headers, top to bottom.
+    sticky_header_lines: Vec<usize>,
 
     is_selecting: bool,
     dragging_h_scrollbar: bool,
     last_mouse_pos: Option<Point<Pixels>>,
     last_mouse_gutter_width: Pixels,
     autoscroll_task: Option<Task<()>>,
-    last_click_time: Option<std::time::Instant>,
+    drag_granularity: DragGranularity,
 
+    /// Bytes of the text an input method is still composing.
     marked_range: Option<Range<usize>>,
 
     pub show_line_numbers: bool,
+    /// Number shown beside the first buffer line, for a buffer holding part of a file.
+    pub first_line_number: usize,
+    /// Let the last line scroll up to the middle of the viewport. Off, the editor is
+    /// exactly as tall as its text.
+    pub scroll_past_end: bool,
+    /// Pin the definition lines of enclosing scopes to the top while scrolling.
+    pub sticky_scroll: bool,
+    /// Draw guides along bracketed blocks from the syntax tree instead of indent guides.
+    pub scope_guides: bool,
+    /// Ease mouse-wheel scrolling and jumps such as `goto_line` and search matches.
+    pub smooth_scroll: bool,
+    smooth_scroller: SmoothScroll,
+    /// Draw a color chip before hex, `rgb()` and `hsl()` literals.
+    pub color_swatches: bool,
+    /// Preview images whose paths are hovered in string literals and markdown links.
+    pub image_hovers: bool,
+    /// Columns at which a vertical guide is drawn through the text area.
+    pub rulers: Vec<usize>,
     tab_size: usize,
+    /// Indent with tabs even on lines not already indented with them.
+    hard_tabs: bool,
+    /// Insert the closing bracket or quote when the opening one is typed.
+    pub auto_closing: bool,
+    /// The settings last applied, for what the editor doesn't act on itself.
+    settings: EditorSettings,
+    /// The shared profiles, while following them.
+    language_settings: Option<LanguageSettings>,
+    /// The `.editorconfig` layer for the loaded file.
+    file_settings: Option<EditorSettingsOverride>,
+    _settings_subscription: Option<Subscription>,
+    _activity_subscription: Subscription,
     read_only: bool,
 
     pub font_size: Pixels,
     pub line_height: Pixels,
     pub font_family_override: Option<SharedString>,
 
+    /// How the line holding the cursor is marked.
+    pub line_highlight: LineHighlight,
+    /// Corner radius of selections; `None` uses the theme's `radius_sm`.
+    pub selection_corner_radius: Option<Pixels>,
+    /// How the caret is drawn.
+    pub cursor_shape: CursorShape,
+    /// Width of the bar, or thickness of the underline, caret.
+    pub cursor_width: Pixels,
+    /// Time between caret blinks; `None` keeps it solid.
+    pub cursor_blink_interval: Option<Duration>,
+    /// Glide the caret to its new position instead of jumping.
+    pub smooth_caret: bool,
+    caret_glide: Option<CaretGlide>,
     cursor_visible: bool,
     blink_task: Option<Task<()>>,
     last_cursor_move: std::time::Instant,
@@ -541,6 +961,8 @@ pub struct EditorState {
     current_match_idx: Option<usize>,
     search_case_sensitive: bool,
     search_use_regex: bool,
+    /// Byte range find/replace is restricted to, kept in step with edits.
+    search_scope: Option<Range<usize>>,
 
     pub cursor_color_override: Option<Hsla>,
     pub selection_color_override: Option<Hsla>,
@@ -554,10 +976,17 @@ pub struct EditorState {
     pub indent_guide_color_override: Option<Hsla>,
     pub indent_guide_active_color_override: Option<Hsla>,
     pub fold_marker_color_override: Option<Hsla>,
+    pub ruler_color_override: Option<Hsla>,
     pub diagnostic_error_color: Option<Hsla>,
     pub diagnostic_warning_color: Option<Hsla>,
     pub diagnostic_info_color: Option<Hsla>,
     pub diagnostic_hint_color: Option<Hsla>,
+    pub spelling_color: Option<Hsla>,
+    /// Underline of the text an input method is still composing.
+    pub composition_color: Option<Hsla>,
+    pub diff_added_color: Option<Hsla>,
+    pub diff_modified_color: Option<Hsla>,
+    pub diff_deleted_color: Option<Hsla>,
     pub syntax_color_fn: Option<Box<dyn Fn(&str) -> Hsla>>,
 
     fold_ranges: Vec<FoldRange>,
@@ -565,8 +994,61 @@ pub struct EditorState {
     cached_display_lines: Option<Rc<Vec<usize>>>,
 
     diagnostics: Vec<EditorDiagnostic>,
+    /// Sorted by start.
+    semantic_tokens: Vec<SemanticSpan>,
+    spell_checker: Option<Arc<SpellChecker>>,
+    /// Found with the highlight spans, so only for the lines those cover.
+    spelling_errors: Vec<Misspelling>,
+
+    minimap_bounds: Option<Bounds<Pixels>>,
+    dragging_minimap: bool,
+    /// Secondary selections edited together with the primary `cursor`/`selection`.
+    extra_selections: Vec<Selection>,
+    /// Anchor and head of a rectangular selection, as (line, char column).
+    column_select: Option<(Position, Position)>,
+    /// Word selected by [`SelectNextOccurrence`] from a bare cursor; further
+    /// occurrences of it only match whole words.
+    occurrence_word: Option<String>,
+    active_snippet: Option<SnippetSession>,
+    rename: Option<RenameSession>,
+    /// Where the cursor was before recent jumps, newest last.
+    nav_back: Vec<Position>,
+    nav_forward: Vec<Position>,
+    inlay_hints: HashMap<usize, Vec<InlayHint>>,
+    code_action_lines: HashSet<usize>,
+    on_code_action_request: Option<Rc<dyn Fn(&CodeActionRequest, &mut Window, &mut App)>>,
+    on_navigate_request: Option<Rc<dyn Fn(&NavigateRequest, &mut Window, &mut App)>>,
+    /// Identifier underlined while the navigation modifier is held: line and byte columns.
+    navigate_hover: Option<(usize, Range<usize>)>,
+    /// Sorted by start line.
+    diff_hunks: Vec<DiffHunk>,
+    on_diff_hunk_click: Option<Rc<dyn Fn(&DiffHunk, Point<Pixels>, &mut Window, &mut App)>>,
+    on_color_swatch_click: Option<Rc<dyn Fn(&ColorSwatchClick, &mut Window, &mut App)>>,
+    /// The built-in picker opened from a swatch when no click handler is set.
+    color_picker: Option<(ColorSwatchClick, Entity<ColorPickerState>)>,
+    image_resolver: Option<Rc<dyn Fn(&str, Option<&Path>) -> Option<PathBuf>>>,
+    /// Path literal under the mouse (line and byte columns) and its preview once loaded.
+    image_hover: Option<(usize, Range<usize>, Option<ImagePreview>)>,
+    image_hover_task: Option<Task<()>>,
+    anchored_overlays: Vec<AnchoredOverlay>,
+    next_overlay_id: usize,
+    /// Host decorations by source, drawn in insertion order.
+    gutter_decorations: Vec<(SharedString, Vec<GutterDecoration>)>,
+    on_gutter_click: Option<Rc<dyn Fn(&GutterClick, &mut Window, &mut App)>>,
+    /// Sorted by order.
+    gutter_lanes: Vec<GutterLane>,
+    scroll_links: Vec<ScrollLink>,
+    /// Scroll offset and cursor line last passed to linked editors.
+    synced_scroll_y: Pixels,
+    synced_cursor_line: Option<usize>,
+    /// Cursor and selections last published as [`BufferEvent`]s.
+    reported_cursor: Position,
+    reported_selections: (Option<Selection>, Vec<Selection>),
+    reported_scroll_y: Pixels,
 }
 
+impl EventEmitter<BufferEvent> for EditorState {}
+
 #[derive(Debug, Clone)]
 pub struct EditorDiagnostic {
     pub start_line: u32,
@@ -577,148 +1059,1939 @@ pub struct EditorDiagnostic {
     pub message: String,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum DiagnosticSeverity {
-    Error,
-    Warning,
-    Information,
-    Hint,
+/// An open rename: the symbol's occurrences and the box the new name is typed in.
+struct RenameSession {
+    /// Byte ranges to replace, in buffer order.
+    ranges: Vec<Range<usize>>,
+    /// Index into `ranges` of the occurrence the box opens over.
+    primary: usize,
+    original: String,
+    input: Entity<InputState>,
+    _subscription: Subscription,
 }
 
-impl EditorState {
-    pub fn new(cx: &mut Context<Self>) -> Self {
-        let parser = Parser::new();
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiffHunkKind {
+    Added,
+    Modified,
+    Deleted,
+}
+
+/// A region that differs from the file's base version, such as the git index.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DiffHunk {
+    pub kind: DiffHunkKind,
+    /// Buffer lines of the hunk. Empty for deletions, where `start` is the line the
+    /// removed text used to precede.
+    pub lines: Range<usize>,
+    /// Lines of the base version this hunk replaces. Empty for additions.
+    pub base_lines: Range<usize>,
+}
+
+impl DiffHunk {
+    /// Whether the hunk covers `line`. A deletion covers the line below the removed
+    /// text, or the last line for a deletion at the end of the file.
+    fn covers_line(&self, line: usize, line_count: usize) -> bool {
+        if self.lines.is_empty() {
+            self.lines.start.min(line_count.saturating_sub(1)) == line
+        } else {
+            self.lines.contains(&line)
+        }
+    }
+}
 
+/// Passed to the `on_code_action_request` handler when the user asks for code actions.
+#[derive(Debug, Clone, Copy)]
+pub struct CodeActionRequest {
+    /// Start of the selection, or the cursor when nothing is selected.
+    pub start: Position,
+    pub end: Position,
+    /// Window coordinates where a quick-fix menu should open.
+    pub position: Point<Pixels>,
+}
+
+/// Passed to the `on_navigate_request` handler when an identifier is cmd-clicked
+/// (ctrl-clicked on Linux and Windows).
+#[derive(Debug, Clone)]
+pub struct NavigateRequest {
+    pub word: String,
+    pub start: Position,
+    pub end: Position,
+    /// Kind of the smallest syntax node covering the word, e.g. `"identifier"` or
+    /// `"field_identifier"`, when the buffer has a syntax tree.
+    pub node_kind: Option<&'static str>,
+    /// Window coordinates of the click.
+    pub position: Point<Pixels>,
+}
+
+/// Passed to the `on_color_swatch_click` handler when a color chip is clicked.
+#[derive(Debug, Clone)]
+pub struct ColorSwatchClick {
+    pub line: usize,
+    /// The literal, with its range in byte columns of `line`.
+    pub literal: ColorLiteral,
+    /// Window coordinates below the chip, where a color picker can open.
+    pub position: Point<Pixels>,
+}
+
+/// An icon a host app draws in the gutter beside a line number, such as a breakpoint,
+/// bookmark or test-run button. Set with [`EditorState::set_gutter_decorations`].
+#[derive(Clone)]
+pub struct GutterDecoration {
+    pub line: usize,
+    /// Icon name, resolved like other editor icons.
+    pub icon: SharedString,
+    pub color: Hsla,
+    on_click: Option<Rc<dyn Fn(&GutterClick, &mut Window, &mut App)>>,
+}
+
+impl GutterDecoration {
+    pub fn new(line: usize, icon: impl Into<SharedString>, color: Hsla) -> Self {
         Self {
-            focus_handle: cx.focus_handle(),
-            rope: Rope::from_str("\n"),
-            cursor: Position::zero(),
-            selection: None,
-            undo_stack: Vec::new(),
-            redo_stack: Vec::new(),
-            file_path: None,
-            is_modified: false,
-            content_version: 0,
-            parser,
-            syntax_tree: None,
-            highlight_query: None,
-            language: Language::Plain,
-            scroll_handle: ScrollHandle::new(),
-            scroll_offset_x: px(0.0),
-            max_line_width: px(0.0),
-            line_layouts: HashMap::new(),
-            line_content_hashes: HashMap::new(),
-            cached_highlight_spans: Vec::new(),
-            highlight_cache_version: u64::MAX,
-            highlight_cache_first_line: 0,
-            highlight_cache_last_line: 0,
-            last_bounds: None,
-            is_selecting: false,
-            dragging_h_scrollbar: false,
-            last_mouse_pos: None,
-            last_mouse_gutter_width: px(80.0),
-            autoscroll_task: None,
-            last_click_time: None,
-            marked_range: None,
-            show_line_numbers: true,
-            tab_size: 4,
-            read_only: false,
-            font_size: px(14.0),
-            line_height: px(20.0),
-            font_family_override: None,
-            cursor_visible: true,
-            blink_task: None,
-            last_cursor_move: std::time::Instant::now(),
-            last_blink_cursor: Position::zero(),
-            overlay_active_check: None,
-            reparse_task: None,
-            search_task: None,
-            search_query: String::new(),
-            search_matches: Vec::new(),
-            current_match_idx: None,
-            search_case_sensitive: false,
-            search_use_regex: false,
-            cursor_color_override: None,
-            selection_color_override: None,
-            line_number_color_override: None,
-            line_number_active_color_override: None,
-            gutter_bg_override: None,
-            search_match_color_overrides: None,
-            current_line_color_override: None,
-            bracket_match_color_override: None,
-            word_highlight_color_override: None,
-            indent_guide_color_override: None,
-            indent_guide_active_color_override: None,
-            fold_marker_color_override: None,
-            diagnostic_error_color: None,
-            diagnostic_warning_color: None,
-            diagnostic_info_color: None,
-            diagnostic_hint_color: None,
-            syntax_color_fn: None,
-            fold_ranges: Vec::new(),
-            folded: Vec::new(),
-            cached_display_lines: None,
-            diagnostics: Vec::new(),
+            line,
+            icon: icon.into(),
+            color,
+            on_click: None,
         }
     }
 
-    pub fn set_cursor_position(&mut self, line: usize, col: usize, cx: &mut Context<Self>) {
-        let max_line = self.total_lines().saturating_sub(1);
-        self.cursor.line = line.min(max_line);
-        let line_len = self.line_len(self.cursor.line);
-        self.cursor.col = col.min(line_len);
-        self.selection = None;
-        self.reset_cursor_blink(cx);
-        self.ensure_cursor_visible(cx);
+    pub fn on_click(
+        mut self,
+        handler: impl Fn(&GutterClick, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.on_click = Some(Rc::new(handler));
+        self
     }
+}
 
-    pub fn set_font_size(&mut self, size: f32, cx: &mut Context<Self>) {
-        self.font_size = px(size);
-        self.line_height = px((size * 1.5).round());
-        self.line_layouts.clear();
-        self.line_content_hashes.clear();
-        cx.notify();
+/// Passed to gutter lane, gutter decoration and `on_gutter_click` handlers.
+#[derive(Debug, Clone, Copy)]
+pub struct GutterClick {
+    pub line: usize,
+    /// Window coordinates below the clicked lane's cell, where a popover can open.
+    pub position: Point<Pixels>,
+}
+
+/// One line's cell in a gutter lane, as passed to the lane's paint callback.
+#[derive(Debug, Clone, Copy)]
+pub struct GutterCell {
+    pub line: usize,
+    /// Window bounds of the cell: the lane's width by the line height.
+    pub bounds: Bounds<Pixels>,
+    /// Whether the cursor is on this line of a focused editor.
+    pub is_current: bool,
+}
+
+impl GutterCell {
+    /// Paint `icon`, resolved like other editor icons, centered in the cell.
+    pub fn paint_icon(&self, icon: &str, color: Hsla, window: &mut Window, cx: &mut App) {
+        let icon_size = px(14.0)
+            .min(self.bounds.size.width)
+            .min(self.bounds.size.height);
+        let origin = self.bounds.center() - point(icon_size / 2.0, icon_size / 2.0);
+        let _ = window.paint_svg(
+            Bounds::new(origin, size(icon_size, icon_size)),
+            SharedString::from(resolve_icon_path(icon)),
+            TransformationMatrix::default(),
+            color,
+            cx,
+        );
     }
 
-    pub fn set_font_family(&mut self, family: impl Into<SharedString>, cx: &mut Context<Self>) {
-        self.font_family_override = Some(family.into());
-        self.line_layouts.clear();
-        self.line_content_hashes.clear();
-        cx.notify();
+    /// Paint `text` on a rounded `background`, centered in the cell, e.g. a count.
+    pub fn paint_badge(
+        &self,
+        text: &str,
+        color: Hsla,
+        background: Hsla,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let run = TextRun {
+            len: text.len(),
+            font: window.text_style().font(),
+            color,
+            background_color: None,
+            underline: None,
+            strikethrough: None,
+        };
+        let shaped =
+            window
+                .text_system()
+                .shape_line(text.to_string().into(), px(10.0), &[run], None);
+        let badge = size(
+            (shaped.width + px(6.0)).min(self.bounds.size.width),
+            px(14.0).min(self.bounds.size.height),
+        );
+        let origin = self.bounds.center() - point(badge.width / 2.0, badge.height / 2.0);
+        window.paint_quad(
+            fill(Bounds::new(origin, badge), background).corner_radii(Corners::all(px(3.0))),
+        );
+        let text_origin = point(origin.x + (badge.width - shaped.width) / 2.0, origin.y);
+        let _ = shaped.paint(text_origin, badge.height, window, cx);
     }
+}
 
-    fn reset_cursor_blink(&mut self, cx: &mut Context<Self>) {
-        self.cursor_visible = true;
-        self.last_cursor_move = std::time::Instant::now();
-        self.blink_task = Some(cx.spawn(async |this, cx| {
-            loop {
-                smol::Timer::after(std::time::Duration::from_millis(500)).await;
-                let ok = this
-                    .update(cx, |state, cx| {
-                        state.cursor_visible = !state.cursor_visible;
-                        cx.notify();
-                    })
-                    .is_ok();
-                if !ok {
-                    break;
-                }
+type GutterPaint = Rc<dyn Fn(&GutterCell, &mut Window, &mut App)>;
+
+#[derive(Clone)]
+enum GutterLaneKind {
+    Diff,
+    LineNumbers,
+    Decorations,
+    Folds,
+    Custom(GutterPaint),
+}
+
+/// A column of the editor gutter. Lanes sit left to right by `order`; the built-in
+/// ones are the diff markers (0), line numbers (100), decorations and code action
+/// lightbulbs (200) and fold chevrons (300), and custom lanes default to 250. Add
+/// lanes with [`EditorState::add_gutter_lane`]; built-in ones can be removed by id.
+#[derive(Clone)]
+pub struct GutterLane {
+    pub id: SharedString,
+    pub width: Pixels,
+    pub order: i32,
+    kind: GutterLaneKind,
+    on_click: Option<Rc<dyn Fn(&GutterClick, &mut Window, &mut App)>>,
+}
+
+impl GutterLane {
+    pub const DIFF: &'static str = "diff";
+    pub const LINE_NUMBERS: &'static str = "line-numbers";
+    pub const DECORATIONS: &'static str = "decorations";
+    pub const FOLDS: &'static str = "folds";
+
+    /// A lane that calls `paint` for each visible line.
+    pub fn new(
+        id: impl Into<SharedString>,
+        width: Pixels,
+        paint: impl Fn(&GutterCell, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            width,
+            order: 250,
+            kind: GutterLaneKind::Custom(Rc::new(paint)),
+            on_click: None,
+        }
+    }
+
+    /// A lane with an icon on each line `icon_for` returns one for, such as
+    /// bookmarks.
+    pub fn icons(
+        id: impl Into<SharedString>,
+        width: Pixels,
+        icon_for: impl Fn(usize, &App) -> Option<(SharedString, Hsla)> + 'static,
+    ) -> Self {
+        Self::new(id, width, move |cell, window, cx| {
+            if let Some((icon, color)) = icon_for(cell.line, cx) {
+                cell.paint_icon(&icon, color, window, cx);
             }
-        }));
+        })
     }
 
-    pub fn set_diagnostics(&mut self, diagnostics: Vec<EditorDiagnostic>, cx: &mut Context<Self>) {
-        self.diagnostics = diagnostics;
-        cx.notify();
+    fn built_in(id: &'static str, width: Pixels, order: i32, kind: GutterLaneKind) -> Self {
+        Self {
+            id: id.into(),
+            width,
+            order,
+            kind,
+            on_click: None,
+        }
+    }
+
+    fn defaults() -> Vec<Self> {
+        vec![
+            Self::built_in(Self::DIFF, px(6.0), 0, GutterLaneKind::Diff),
+            Self::built_in(
+                Self::LINE_NUMBERS,
+                px(38.0),
+                100,
+                GutterLaneKind::LineNumbers,
+            ),
+            Self::built_in(
+                Self::DECORATIONS,
+                px(18.0),
+                200,
+                GutterLaneKind::Decorations,
+            ),
+            Self::built_in(Self::FOLDS, px(18.0), 300, GutterLaneKind::Folds),
+        ]
+    }
+
+    pub fn order(mut self, order: i32) -> Self {
+        self.order = order;
+        self
     }
 
-    pub fn diagnostics(&self) -> &[EditorDiagnostic] {
-        &self.diagnostics
+    /// Called when a cell of this lane is clicked. Without a handler the click goes
+    /// to the text, as on the line numbers.
+    pub fn on_click(
+        mut self,
+        handler: impl Fn(&GutterClick, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.on_click = Some(Rc::new(handler));
+        self
     }
+}
 
-    pub fn diagnostics_at_line(&self, line: usize) -> Vec<&EditorDiagnostic> {
-        self.diagnostics
-            .iter()
-            .filter(|d| d.start_line as usize <= line && line <= d.end_line as usize)
+/// Identifies an overlay added with [`EditorState::anchor_overlay`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OverlayId(usize);
+
+/// Which side of its line an anchored overlay opens on. It flips to the other side
+/// when there is no room.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverlayPlacement {
+    #[default]
+    Below,
+    Above,
+}
+
+struct AnchoredOverlay {
+    id: OverlayId,
+    /// Byte offset of the anchor, moved by edits before it.
+    offset: usize,
+    placement: OverlayPlacement,
+    view: AnyView,
+}
+
+/// Structured changes published by [`EditorState`], for autosave, language servers and
+/// collaboration layers that need more than a notification that something changed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BufferEvent {
+    /// The bytes `range` of the buffer, as it was just before this edit, were replaced.
+    /// Applying the events in order to a copy of the text reproduces the buffer.
+    Edited {
+        range: Range<usize>,
+        old_text: String,
+        new_text: String,
+    },
+    /// The primary cursor moved. Cursor and selection events are reported at most
+    /// once per frame.
+    CursorMoved {
+        position: Position,
+    },
+    /// A selection or extra cursor changed. `selections` starts with the primary one.
+    SelectionChanged {
+        selections: Vec<Selection>,
+    },
+    Saved {
+        path: PathBuf,
+    },
+    /// The viewport scrolled vertically. `top_line` is the buffer line at its top edge,
+    /// with the fraction scrolled past it. Reported at most once per frame.
+    Scrolled {
+        top_line: f32,
+    },
+}
+
+/// How linked editors follow each other's scroll position. See
+/// [`EditorState::link_scroll`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScrollSyncMode {
+    /// Both show the same fraction of their scrollable height.
+    Proportional,
+    /// Corresponding lines stay level. Each pair maps a line of the first editor to a
+    /// line of the second, sorted on both; lines between pairs are interpolated, e.g.
+    /// the hunk boundaries of a side-by-side diff.
+    Aligned(Vec<(usize, usize)>),
+}
+
+/// One side of a scroll link, with alignment pairs oriented from this editor to `peer`.
+struct ScrollLink {
+    peer: WeakEntity<EditorState>,
+    alignment: Option<Vec<(usize, usize)>>,
+    sync_cursor: bool,
+}
+
+/// Map `line` through alignment `pairs`, interpolating between them and keeping
+/// the offset from the nearest pair beyond either end.
+fn map_aligned_line(pairs: &[(usize, usize)], line: f32) -> f32 {
+    let (Some(&(first, first_peer)), Some(&(last, last_peer))) = (pairs.first(), pairs.last())
+    else {
+        return line;
+    };
+    if line <= first as f32 {
+        return (first_peer as f32 - (first as f32 - line)).max(0.0);
+    }
+    for pair in pairs.windows(2) {
+        let ((start, peer_start), (end, peer_end)) = (pair[0], pair[1]);
+        if line < end as f32 {
+            let t = (line - start as f32) / (end - start).max(1) as f32;
+            return peer_start as f32 + t * (peer_end as f32 - peer_start as f32);
+        }
+    }
+    last_peer as f32 + (line - last as f32)
+}
+
+/// Placeholder spliced into the line where a color swatch is painted.
+const COLOR_SWATCH_TEXT: &str = "  ";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InlayHintKind {
+    Type,
+    Parameter,
+}
+
+/// Virtual text drawn inline before the character at `position`, e.g. an inferred
+/// type or a parameter name. Hints are display-only: columns, selections and the
+/// buffer are unaffected.
+#[derive(Debug, Clone)]
+pub struct InlayHint {
+    pub position: Position,
+    pub label: SharedString,
+    pub kind: InlayHintKind,
+}
+
+impl InlayHint {
+    pub fn new(position: Position, label: impl Into<SharedString>, kind: InlayHintKind) -> Self {
+        Self {
+            position,
+            label: label.into(),
+            kind,
+        }
+    }
+
+    fn display_text(&self) -> String {
+        match self.kind {
+            InlayHintKind::Type => self.label.to_string(),
+            InlayHintKind::Parameter => format!("{} ", self.label),
+        }
+    }
+}
+
+/// A span colored by a language server, e.g. from `textDocument/semanticTokens`,
+/// drawn over the tree-sitter highlighting. Columns are bytes on `line`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SemanticToken {
+    pub line: usize,
+    pub start_col: usize,
+    pub end_col: usize,
+    /// LSP token type, e.g. `"function"` or `"enumMember"`.
+    pub token_type: SharedString,
+    /// LSP token modifiers, e.g. `"readonly"` or `"defaultLibrary"`.
+    pub modifiers: Vec<SharedString>,
+}
+
+impl SemanticToken {
+    pub fn new(line: usize, cols: Range<usize>, token_type: impl Into<SharedString>) -> Self {
+        Self {
+            line,
+            start_col: cols.start,
+            end_col: cols.end,
+            token_type: token_type.into(),
+            modifiers: Vec::new(),
+        }
+    }
+
+    pub fn modifier(mut self, modifier: impl Into<SharedString>) -> Self {
+        self.modifiers.push(modifier.into());
+        self
+    }
+
+    /// The highlight capture name the token is colored as, so `syntax_color_fn` themes
+    /// both layers alike. `None` for types with no equivalent, which leave the
+    /// tree-sitter color in place.
+    fn capture_name(&self) -> Option<&'static str> {
+        let has = |modifier: &str| self.modifiers.iter().any(|m| m.as_ref() == modifier);
+        Some(match self.token_type.as_ref() {
+            "namespace" => "namespace",
+            "type" | "class" | "enum" | "interface" | "struct" | "typeParameter" | "typeAlias"
+                if has("defaultLibrary") =>
+            {
+                "type.builtin"
+            }
+            "type" | "class" | "enum" | "interface" | "struct" | "typeParameter" | "typeAlias" => {
+                "type"
+            }
+            "parameter" => "variable.parameter",
+            "variable" if has("readonly") || has("constant") => "constant",
+            "variable" if has("defaultLibrary") => "variable.builtin",
+            "variable" => "variable",
+            "property" | "event" => "property",
+            "enumMember" => "constant",
+            "function" if has("defaultLibrary") => "function.builtin",
+            "function" => "function",
+            "method" => "function.method",
+            "macro" => "function.macro",
+            "keyword" => "keyword",
+            "modifier" => "keyword.modifier",
+            "comment" if has("documentation") => "comment.documentation",
+            "comment" => "comment",
+            "string" => "string",
+            "number" => "number",
+            "regexp" => "string.regex",
+            "operator" => "operator",
+            "decorator" => "attribute",
+            "label" => "label",
+            _ => return None,
+        })
+    }
+}
+
+/// A semantic token resolved to a byte range of the buffer, which edits shift.
+struct SemanticSpan {
+    range: Range<usize>,
+    capture: &'static str,
+    /// Position in the list it was set with; later tokens win where they overlap.
+    order: usize,
+}
+
+/// Paint `top` spans over `base` ones: `base` is flattened with the first span winning
+/// where spans overlap, then each `top` span in turn replaces whatever is under it.
+/// The result is sorted and disjoint.
+fn layer_spans(
+    base: impl IntoIterator<Item = (Range<usize>, Hsla)>,
+    top: &[(Range<usize>, Hsla)],
+) -> Vec<(Range<usize>, Hsla)> {
+    let mut spans: Vec<(Range<usize>, Hsla)> = Vec::new();
+    let mut covered = 0;
+    for (range, color) in base {
+        let start = range.start.max(covered);
+        if start < range.end {
+            spans.push((start..range.end, color));
+            covered = range.end;
+        }
+    }
+    for (range, color) in top {
+        if range.is_empty() {
+            continue;
+        }
+        let mut cut = Vec::with_capacity(spans.len() + 2);
+        for (span, span_color) in spans {
+            if span.end <= range.start || span.start >= range.end {
+                cut.push((span, span_color));
+                continue;
+            }
+            if span.start < range.start {
+                cut.push((span.start..range.start, span_color));
+            }
+            if span.end > range.end {
+                cut.push((range.end..span.end, span_color));
+            }
+        }
+        cut.push((range.clone(), *color));
+        spans = cut;
+    }
+    spans.sort_by_key(|(range, _)| range.start);
+    spans
+}
+
+/// A shaped line with inlay hints spliced into its text. Index-based queries take and
+/// return buffer columns, skipping over inlay text.
+#[derive(Clone)]
+struct DisplayLineLayout {
+    shaped: ShapedLine,
+    /// (buffer column, display length) of each inlay, sorted by column.
+    inlays: Vec<(usize, usize)>,
+    /// Set when only part of a long line was shaped.
+    window: Option<LineWindow>,
+}
+
+/// The shaped part of a long line. Columns outside it are placed at an estimated
+/// `advance` each, which is exact for monospace ASCII and close enough elsewhere for
+/// scrolling and the scrollbar.
+#[derive(Clone, Copy)]
+struct LineWindow {
+    /// Buffer column of the shaped text's first byte.
+    start: usize,
+    /// Length of the whole line.
+    line_len: usize,
+    advance: Pixels,
+}
+
+impl LineWindow {
+    fn x_start(&self) -> Pixels {
+        self.advance * self.start as f32
+    }
+}
+
+impl DisplayLineLayout {
+    fn display_index(&self, col: usize) -> usize {
+        col + self
+            .inlays
+            .iter()
+            .take_while(|(at, _)| *at < col)
+            .map(|(_, len)| len)
+            .sum::<usize>()
+    }
+
+    fn x_for_index(&self, col: usize) -> Pixels {
+        let Some(window) = self.window else {
+            return self.shaped.x_for_index(self.display_index(col));
+        };
+        let end = window.start + self.shaped.len();
+        if col < window.start {
+            window.advance * col as f32
+        } else if col > end {
+            window.x_start() + self.shaped.width + window.advance * (col - end) as f32
+        } else {
+            window.x_start() + self.shaped.x_for_index(col - window.start)
+        }
+    }
+
+    fn closest_index_for_x(&self, x: Pixels) -> usize {
+        if let Some(window) = self.window {
+            let shaped_left = window.x_start();
+            let shaped_right = shaped_left + self.shaped.width;
+            let end = window.start + self.shaped.len();
+            return if x < shaped_left {
+                (x / window.advance).round().max(0.0) as usize
+            } else if x > shaped_right {
+                let past = ((x - shaped_right) / window.advance).round() as usize;
+                (end + past).min(window.line_len)
+            } else {
+                window.start + self.shaped.closest_index_for_x(x - shaped_left)
+            };
+        }
+        let display = self.shaped.closest_index_for_x(x);
+        let mut shift = 0;
+        for (at, len) in &self.inlays {
+            let start = at + shift;
+            if display <= start {
+                break;
+            }
+            if display < start + len {
+                return *at;
+            }
+            shift += len;
+        }
+        display - shift
+    }
+
+    fn len(&self) -> usize {
+        if let Some(window) = self.window {
+            return window.line_len;
+        }
+        self.shaped.len() - self.inlays.iter().map(|(_, len)| len).sum::<usize>()
+    }
+
+    /// Width of the whole line, estimated past the shaped window of a long line.
+    fn width(&self) -> Pixels {
+        match self.window {
+            Some(window) => {
+                let end = window.start + self.shaped.len();
+                window.x_start()
+                    + self.shaped.width
+                    + window.advance * window.line_len.saturating_sub(end) as f32
+            }
+            None => self.shaped.width,
+        }
+    }
+
+    /// Paint with the line starting at `origin`, offsetting a long line's shaped window
+    /// to its place.
+    fn paint(&self, origin: Point<Pixels>, line_height: Pixels, window: &mut Window, cx: &mut App) {
+        let x_start = self.window.map_or(px(0.0), |lw| lw.x_start());
+        let _ = self
+            .shaped
+            .paint(point(origin.x + x_start, origin.y), line_height, window, cx);
+    }
+
+    /// Left and right x of the swatch placeholder before the color literal at `col`.
+    fn color_swatch_bounds(&self, col: usize) -> (Pixels, Pixels) {
+        let display = self.display_index(col);
+        (
+            self.shaped.x_for_index(display),
+            self.shaped.x_for_index(display + COLOR_SWATCH_TEXT.len()),
+        )
+    }
+}
+
+impl std::ops::Deref for DisplayLineLayout {
+    type Target = ShapedLine;
+
+    fn deref(&self) -> &ShapedLine {
+        &self.shaped
+    }
+}
+
+/// The part of a line's `runs` covering the byte `range`, for shaping a window of a long
+/// line.
+fn slice_text_runs(runs: Vec<TextRun>, range: Range<usize>) -> Vec<TextRun> {
+    let mut pos = 0;
+    let mut sliced = Vec::new();
+    for run in runs {
+        let start = pos.max(range.start);
+        let end = (pos + run.len).min(range.end);
+        pos += run.len;
+        if end > start {
+            sliced.push(TextRun {
+                len: end - start,
+                ..run
+            });
+        }
+        if pos >= range.end {
+            break;
+        }
+    }
+    sliced
+}
+
+/// Insert inlay text (hint labels, swatch placeholders) into a line's text and runs,
+/// returning the display text, its runs and the inlay spans for [`DisplayLineLayout`].
+/// `pending` holds (buffer column, text, run) sorted by column.
+fn splice_inlay_hints(
+    line_text: &str,
+    runs: Vec<TextRun>,
+    pending: Vec<(usize, String, TextRun)>,
+) -> (String, Vec<TextRun>, Vec<(usize, usize)>) {
+    let mut text = String::with_capacity(line_text.len());
+    let mut out_runs = Vec::with_capacity(runs.len() + pending.len() * 2);
+    let mut inlays = Vec::with_capacity(pending.len());
+    let mut pending = pending
+        .into_iter()
+        .map(|(col, label, run)| {
+            let mut col = col.min(line_text.len());
+            while !line_text.is_char_boundary(col) {
+                col -= 1;
+            }
+            (col, label, run)
+        })
+        .peekable();
+
+    let mut offset = 0;
+    for run in runs {
+        let mut remaining = run.len;
+        loop {
+            while let Some((col, label, inlay_run)) = pending.next_if(|(col, ..)| *col <= offset) {
+                text.push_str(&label);
+                out_runs.push(TextRun {
+                    len: label.len(),
+                    ..inlay_run
+                });
+                inlays.push((col, label.len()));
+            }
+            if remaining == 0 {
+                break;
+            }
+            let next = pending.peek().map_or(usize::MAX, |(col, ..)| *col);
+            let take = remaining.min(next - offset);
+            text.push_str(&line_text[offset..offset + take]);
+            out_runs.push(TextRun {
+                len: take,
+                ..run.clone()
+            });
+            offset += take;
+            remaining -= take;
+        }
+    }
+    for (col, label, inlay_run) in pending {
+        text.push_str(&label);
+        out_runs.push(TextRun {
+            len: label.len(),
+            ..inlay_run
+        });
+        inlays.push((col, label.len()));
+    }
+    (text, out_runs, inlays)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+impl EditorState {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        let parser = Parser::new();
+
+        Self {
+            focus_handle: cx.focus_handle(),
+            rope: Rope::from_str("\n"),
+            cursor: Position::zero(),
+            selection: None,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            undo_transaction_start: None,
+            undo_transaction_depth: 0,
+            undo_selections_before: None,
+            file_path: None,
+            is_modified: false,
+            line_ending: LineEnding::default(),
+            encoding: TextEncoding::default(),
+            content_version: 0,
+            parser,
+            syntax_tree: None,
+            highlight_query: None,
+            language: Language::Plain,
+            scroll_handle: ScrollHandle::new(),
+            scroll_offset_x: px(0.0),
+            max_line_width: px(0.0),
+            line_layouts: HashMap::new(),
+            line_content_hashes: HashMap::new(),
+            cached_highlight_spans: Vec::new(),
+            highlight_cache_version: u64::MAX,
+            highlight_cache_first_line: 0,
+            highlight_cache_last_line: 0,
+            highlight_request: None,
+            highlight_task: None,
+            bracket_match_cache: None,
+            word_occurrence_cache: None,
+            last_bounds: None,
+            painted_scroll_y: px(0.0),
+            sticky_header_lines: Vec::new(),
+            is_selecting: false,
+            dragging_h_scrollbar: false,
+            last_mouse_pos: None,
+            last_mouse_gutter_width: px(80.0),
+            autoscroll_task: None,
+            drag_granularity: DragGranularity::Char,
+            marked_range: None,
+            show_line_numbers: true,
+            first_line_number: 1,
+            scroll_past_end: true,
+            sticky_scroll: false,
+            scope_guides: false,
+            smooth_scroll: false,
+            smooth_scroller: SmoothScroll::new(),
+            color_swatches: false,
+            image_hovers: false,
+            rulers: Vec::new(),
+            tab_size: 4,
+            hard_tabs: false,
+            auto_closing: true,
+            settings: EditorSettings::default(),
+            language_settings: None,
+            file_settings: None,
+            _settings_subscription: None,
+            _activity_subscription: activity::observe(cx, |_, cx| cx.notify()),
+            read_only: false,
+            font_size: px(14.0),
+            line_height: px(20.0),
+            font_family_override: None,
+            line_highlight: LineHighlight::Full,
+            selection_corner_radius: None,
+            cursor_shape: CursorShape::Bar,
+            cursor_width: px(2.0),
+            cursor_blink_interval: Some(DEFAULT_CURSOR_BLINK),
+            smooth_caret: false,
+            caret_glide: None,
+            cursor_visible: true,
+            blink_task: None,
+            last_cursor_move: std::time::Instant::now(),
+            last_blink_cursor: Position::zero(),
+            overlay_active_check: None,
+            reparse_task: None,
+            search_task: None,
+            search_query: String::new(),
+            search_matches: Vec::new(),
+            current_match_idx: None,
+            search_case_sensitive: false,
+            search_use_regex: false,
+            search_scope: None,
+            cursor_color_override: None,
+            selection_color_override: None,
+            line_number_color_override: None,
+            line_number_active_color_override: None,
+            gutter_bg_override: None,
+            search_match_color_overrides: None,
+            current_line_color_override: None,
+            bracket_match_color_override: None,
+            word_highlight_color_override: None,
+            indent_guide_color_override: None,
+            indent_guide_active_color_override: None,
+            fold_marker_color_override: None,
+            ruler_color_override: None,
+            diagnostic_error_color: None,
+            diagnostic_warning_color: None,
+            diagnostic_info_color: None,
+            diagnostic_hint_color: None,
+            spelling_color: None,
+            composition_color: None,
+            diff_added_color: None,
+            diff_modified_color: None,
+            diff_deleted_color: None,
+            syntax_color_fn: None,
+            fold_ranges: Vec::new(),
+            folded: Vec::new(),
+            cached_display_lines: None,
+            diagnostics: Vec::new(),
+            semantic_tokens: Vec::new(),
+            spell_checker: None,
+            spelling_errors: Vec::new(),
+            minimap_bounds: None,
+            dragging_minimap: false,
+            extra_selections: Vec::new(),
+            column_select: None,
+            occurrence_word: None,
+            active_snippet: None,
+            rename: None,
+            nav_back: Vec::new(),
+            nav_forward: Vec::new(),
+            inlay_hints: HashMap::new(),
+            code_action_lines: HashSet::new(),
+            on_code_action_request: None,
+            on_navigate_request: None,
+            navigate_hover: None,
+            diff_hunks: Vec::new(),
+            on_diff_hunk_click: None,
+            on_color_swatch_click: None,
+            color_picker: None,
+            image_resolver: None,
+            image_hover: None,
+            image_hover_task: None,
+            anchored_overlays: Vec::new(),
+            next_overlay_id: 0,
+            gutter_decorations: Vec::new(),
+            on_gutter_click: None,
+            gutter_lanes: GutterLane::defaults(),
+            scroll_links: Vec::new(),
+            synced_scroll_y: px(0.0),
+            synced_cursor_line: None,
+            reported_cursor: Position::zero(),
+            reported_selections: (None, Vec::new()),
+            reported_scroll_y: px(0.0),
+        }
+    }
+
+    pub fn set_cursor_position(&mut self, line: usize, col: usize, cx: &mut Context<Self>) {
+        let from = self.cursor;
+        let max_line = self.total_lines().saturating_sub(1);
+        self.cursor.line = line.min(max_line);
+        let line_len = self.line_len(self.cursor.line);
+        self.cursor.col = col.min(line_len);
+        self.selection = None;
+        self.record_jump(from);
+        self.reset_cursor_blink(cx);
+        self.ensure_cursor_visible(cx);
+    }
+
+    pub fn set_font_size(&mut self, size: f32, cx: &mut Context<Self>) {
+        self.font_size = px(size);
+        self.line_height = px((size * 1.5).round());
+        self.line_layouts.clear();
+        self.line_content_hashes.clear();
+        cx.notify();
+    }
+
+    pub fn set_font_family(&mut self, family: impl Into<SharedString>, cx: &mut Context<Self>) {
+        self.font_family_override = Some(family.into());
+        self.line_layouts.clear();
+        self.line_content_hashes.clear();
+        cx.notify();
+    }
+
+    fn reset_cursor_blink(&mut self, cx: &mut Context<Self>) {
+        self.cursor_visible = true;
+        self.last_cursor_move = std::time::Instant::now();
+        let Some(interval) = self.cursor_blink_interval else {
+            self.blink_task = None;
+            return;
+        };
+        self.blink_task = Some(cx.spawn(async move |this, cx| loop {
+            smol::Timer::after(interval).await;
+            let ok = this
+                .update(cx, |state, cx| {
+                    state.cursor_visible = !state.cursor_visible;
+                    cx.notify();
+                })
+                .is_ok();
+            if !ok {
+                break;
+            }
+        }));
+    }
+
+    pub fn set_diagnostics(&mut self, diagnostics: Vec<EditorDiagnostic>, cx: &mut Context<Self>) {
+        self.diagnostics = diagnostics;
+        cx.notify();
+    }
+
+    pub fn diagnostics(&self) -> &[EditorDiagnostic] {
+        &self.diagnostics
+    }
+
+    /// Check comments and strings against `checker`, or in Markdown all text outside
+    /// code and links. Misspelled words get a dotted underline; `None` turns checking
+    /// off.
+    pub fn set_spell_checker(&mut self, checker: Option<SpellChecker>, cx: &mut Context<Self>) {
+        self.spell_checker = checker.map(Arc::new);
+        self.spelling_errors.clear();
+        self.refresh_highlights(cx);
+    }
+
+    pub fn spell_checker(&self) -> Option<&SpellChecker> {
+        self.spell_checker.as_deref()
+    }
+
+    /// Misspelled words on the lines around the viewport.
+    pub fn spelling_errors(&self) -> &[Misspelling] {
+        &self.spelling_errors
+    }
+
+    /// The misspelled word touching `pos`, e.g. the cursor, for a context menu to offer
+    /// corrections from [`Self::spelling_suggestions`].
+    pub fn misspelling_at(&self, pos: Position) -> Option<&Misspelling> {
+        self.spelling_errors.iter().find(|misspelling| {
+            misspelling.line == pos.line
+                && misspelling.range.start <= pos.col
+                && pos.col <= misspelling.range.end
+        })
+    }
+
+    pub fn spelling_suggestions(&self, misspelling: &Misspelling, limit: usize) -> Vec<String> {
+        self.spell_checker
+            .as_ref()
+            .map(|checker| checker.suggestions(&misspelling.word, limit))
+            .unwrap_or_default()
+    }
+
+    /// Replace a misspelled word with `replacement` as one undo step. Returns false if
+    /// the word has since been edited.
+    pub fn correct_misspelling(
+        &mut self,
+        misspelling: &Misspelling,
+        replacement: &str,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        if self.read_only || misspelling.line >= self.rope.len_lines() {
+            return false;
+        }
+        let line_text = self.line_text(misspelling.line);
+        if line_text.get(misspelling.range.clone()) != Some(misspelling.word.as_str()) {
+            return false;
+        }
+        let line_start = self.rope.line_to_byte(misspelling.line);
+        let range = line_start + misspelling.range.start..line_start + misspelling.range.end;
+        let cursor = range.start + replacement.len();
+        self.replace_bytes(range, replacement, cursor, cx);
+        true
+    }
+
+    /// Stop flagging `word`. The additions can be saved from
+    /// [`SpellChecker::added_words`] and added back on the next start.
+    pub fn add_to_dictionary(&mut self, word: &str, cx: &mut Context<Self>) {
+        let Some(checker) = self.spell_checker.as_mut() else {
+            return;
+        };
+        let checker = Arc::make_mut(checker);
+        checker.add_word(word);
+        self.spelling_errors
+            .retain(|misspelling| !checker.is_correct(&misspelling.word));
+        self.refresh_highlights(cx);
+    }
+
+    /// Replace the semantic tokens, which are drawn over the tree-sitter highlighting:
+    ///
+    /// - A token's color replaces the tree-sitter color on its range.
+    /// - Where tokens overlap, the one later in `tokens` wins.
+    /// - Token types with no highlight equivalent are skipped, keeping the tree-sitter
+    ///   color.
+    ///
+    /// Edits shift the tokens after them and drop the ones they touch, so the rest
+    /// stay correct until the server sends a fresh set.
+    pub fn set_semantic_tokens(&mut self, tokens: Vec<SemanticToken>, cx: &mut Context<Self>) {
+        let line_count = self.rope.len_lines();
+        let mut spans: Vec<SemanticSpan> = tokens
+            .iter()
+            .enumerate()
+            .filter(|(_, token)| token.line < line_count && token.start_col < token.end_col)
+            .filter_map(|(order, token)| {
+                let capture = token.capture_name()?;
+                let line_start = self.rope.line_to_byte(token.line);
+                let line_len = self.line_len(token.line);
+                let range = line_start + token.start_col.min(line_len)
+                    ..line_start + token.end_col.min(line_len);
+                (!range.is_empty()).then_some(SemanticSpan {
+                    range,
+                    capture,
+                    order,
+                })
+            })
+            .collect();
+        spans.sort_by_key(|span| span.range.start);
+        self.semantic_tokens = spans;
+        // Lines are only reshaped when their text changes otherwise.
+        self.line_content_hashes.clear();
+        cx.notify();
+    }
+
+    pub fn clear_semantic_tokens(&mut self, cx: &mut Context<Self>) {
+        self.set_semantic_tokens(Vec::new(), cx);
+    }
+
+    /// Semantic token colors on `line` as line-relative byte ranges, lowest precedence
+    /// first.
+    fn semantic_spans(&self, line: usize) -> Vec<(Range<usize>, Hsla)> {
+        if self.semantic_tokens.is_empty() || line >= self.rope.len_lines() {
+            return Vec::new();
+        }
+        let line_start = self.rope.line_to_byte(line);
+        let line_end = line_start + self.line_len(line);
+        let first = self
+            .semantic_tokens
+            .partition_point(|span| span.range.start < line_start);
+        let mut spans: Vec<&SemanticSpan> = self.semantic_tokens[first..]
+            .iter()
+            .take_while(|span| span.range.start < line_end)
+            .collect();
+        spans.sort_by_key(|span| span.order);
+        spans
+            .into_iter()
+            .map(|span| {
+                let range =
+                    span.range.start - line_start..span.range.end.min(line_end) - line_start;
+                (range, self.capture_color(span.capture))
+            })
+            .collect()
+    }
+
+    /// Replace all inlay hints. Hints are not moved by later edits; send a fresh set
+    /// after each change, as LSP servers do.
+    pub fn set_inlay_hints(&mut self, hints: Vec<InlayHint>, cx: &mut Context<Self>) {
+        self.inlay_hints.clear();
+        for hint in hints {
+            self.inlay_hints
+                .entry(hint.position.line)
+                .or_default()
+                .push(hint);
+        }
+        for line_hints in self.inlay_hints.values_mut() {
+            line_hints.sort_by_key(|hint| hint.position.col);
+        }
+        self.invalidate_all_caches();
+        cx.notify();
+    }
+
+    pub fn inlay_hints_at_line(&self, line: usize) -> &[InlayHint] {
+        self.inlay_hints.get(&line).map_or(&[], Vec::as_slice)
+    }
+
+    /// Mark the lines that have code actions available. Marked lines show a lightbulb
+    /// in the gutter that opens them.
+    pub fn set_code_action_lines(
+        &mut self,
+        lines: impl IntoIterator<Item = usize>,
+        cx: &mut Context<Self>,
+    ) {
+        self.code_action_lines = lines.into_iter().collect();
+        cx.notify();
+    }
+
+    pub fn has_code_actions(&self, line: usize) -> bool {
+        self.code_action_lines.contains(&line)
+    }
+
+    /// Called when the gutter lightbulb is clicked or `ShowCodeActions` is triggered.
+    pub fn on_code_action_request(
+        &mut self,
+        handler: impl Fn(&CodeActionRequest, &mut Window, &mut App) + 'static,
+    ) {
+        self.on_code_action_request = Some(Rc::new(handler));
+    }
+
+    pub fn show_code_actions(
+        &mut self,
+        _: &ShowCodeActions,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.is_overlay_active(cx) {
+            cx.propagate();
+            return;
+        }
+        self.request_code_actions(None, window, cx);
+    }
+
+    /// Fire `on_code_action_request` for the current selection. The menu opens at
+    /// `anchor`, or below the cursor when `None`.
+    fn request_code_actions(
+        &self,
+        anchor: Option<Point<Pixels>>,
+        window: &Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(handler) = self.on_code_action_request.clone() else {
+            return;
+        };
+        let Some(position) = anchor.or_else(|| self.cursor_screen_position(self.line_height))
+        else {
+            return;
+        };
+        let (start, end) = self
+            .selection
+            .map(|sel| sel.range())
+            .unwrap_or((self.cursor, self.cursor));
+        let request = CodeActionRequest {
+            start,
+            end,
+            position,
+        };
+        // Deferred so the handler can update this editor.
+        window.defer(cx, move |window, cx| handler(&request, window, cx));
+    }
+
+    /// Called when an identifier is cmd-clicked (ctrl-clicked on Linux and Windows), for
+    /// go-to-definition. While a handler is set, holding the modifier underlines the
+    /// identifier under the mouse.
+    pub fn on_navigate_request(
+        &mut self,
+        handler: impl Fn(&NavigateRequest, &mut Window, &mut App) + 'static,
+    ) {
+        self.on_navigate_request = Some(Rc::new(handler));
+    }
+
+    /// The identifier at `pos` as its byte column range on the line.
+    fn identifier_range_at(&self, pos: Position) -> Option<Range<usize>> {
+        let line_text = self.line_text(pos.line);
+        let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+        let col = pos.col.min(line_text.len());
+        let start = line_text[..col]
+            .char_indices()
+            .rev()
+            .take_while(|&(_, c)| is_word_char(c))
+            .last()
+            .map_or(col, |(i, _)| i);
+        let end = line_text[col..]
+            .char_indices()
+            .find(|&(_, c)| !is_word_char(c))
+            .map_or(line_text.len(), |(i, _)| col + i);
+        let word = &line_text[start..end];
+        if word.is_empty() || word.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        Some(start..end)
+    }
+
+    fn navigate_request_at(
+        &self,
+        pos: Position,
+        position: Point<Pixels>,
+    ) -> Option<NavigateRequest> {
+        let range = self.identifier_range_at(pos)?;
+        let line_start = self.rope.line_to_byte(pos.line);
+        let node_kind = self.syntax_tree.as_ref().and_then(|tree| {
+            tree.root_node()
+                .descendant_for_byte_range(line_start + range.start, line_start + range.end)
+                .map(|node| node.kind())
+        });
+        Some(NavigateRequest {
+            word: self.line_text(pos.line)[range.clone()].to_string(),
+            start: Position::new(pos.line, range.start),
+            end: Position::new(pos.line, range.end),
+            node_kind,
+            position,
+        })
+    }
+
+    fn update_navigate_hover(
+        &mut self,
+        hover: Option<(usize, Range<usize>)>,
+        cx: &mut Context<Self>,
+    ) {
+        if self.navigate_hover != hover {
+            self.navigate_hover = hover;
+            cx.notify();
+        }
+    }
+
+    /// Called when an inline color swatch is clicked (see `color_swatches`), e.g. to
+    /// open a color picker and write the result back with `replace_color_literal`.
+    /// Without a handler, clicking a swatch opens a [`ColorPickerPanel`] below it.
+    pub fn on_color_swatch_click(
+        &mut self,
+        handler: impl Fn(&ColorSwatchClick, &mut Window, &mut App) + 'static,
+    ) {
+        self.on_color_swatch_click = Some(Rc::new(handler));
+    }
+
+    /// Replace the literal of a swatch click with `color`, written in the literal's
+    /// format, as one undo step. Returns false if the literal has since been edited.
+    pub fn replace_color_literal(
+        &mut self,
+        click: &ColorSwatchClick,
+        color: Hsla,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        if self.read_only || click.line >= self.rope.len_lines() {
+            return false;
+        }
+        let unchanged = find_color_literals(&self.line_text(click.line))
+            .iter()
+            .any(|literal| literal.range == click.literal.range);
+        if !unchanged {
+            return false;
+        }
+        let line_start = self.rope.line_to_byte(click.line);
+        let text = format_color_literal(color, click.literal.format);
+        let range = line_start + click.literal.range.start..line_start + click.literal.range.end;
+        let cursor = range.start + text.len();
+        self.replace_bytes(range, &text, cursor, cx);
+        true
+    }
+
+    fn open_color_picker(&mut self, click: ColorSwatchClick, cx: &mut Context<Self>) {
+        let color = hue_to_degrees(click.literal.color);
+        let picker = cx.new(|_| ColorPickerState::new(color));
+        self.color_picker = Some((click, picker));
+        cx.notify();
+    }
+
+    /// Write a color chosen in the built-in picker back to its literal and close it.
+    fn pick_color(&mut self, color: Hsla, cx: &mut Context<Self>) {
+        if let Some((click, _)) = self.color_picker.take() {
+            self.replace_color_literal(&click, hue_from_degrees(color), cx);
+            cx.notify();
+        }
+    }
+
+    fn close_color_picker(&mut self, cx: &mut Context<Self>) {
+        if self.color_picker.take().is_some() {
+            cx.notify();
+        }
+    }
+
+    /// Map a path written in the buffer to the image file to preview (see
+    /// `image_hovers`). The resolver gets the literal's text and the open file's
+    /// directory; by default paths are taken relative to that directory.
+    pub fn set_image_resolver(
+        &mut self,
+        resolver: impl Fn(&str, Option<&Path>) -> Option<PathBuf> + 'static,
+    ) {
+        self.image_resolver = Some(Rc::new(resolver));
+    }
+
+    /// Track the path literal under the mouse, loading its preview after a short pause.
+    fn update_image_hover(
+        &mut self,
+        target: Option<(usize, Range<usize>)>,
+        cx: &mut Context<Self>,
+    ) {
+        let current = self
+            .image_hover
+            .as_ref()
+            .map(|(line, cols, _)| (*line, cols.clone()));
+        if current == target {
+            return;
+        }
+        self.image_hover_task = None;
+        if let Some((_, _, Some(_))) = self.image_hover.take() {
+            cx.notify();
+        }
+        let Some((line, cols)) = target else {
+            return;
+        };
+        let text = self.line_text(line)[cols.clone()].to_string();
+        let base_dir = self.file_path.as_deref().and_then(Path::parent);
+        let path = match &self.image_resolver {
+            Some(resolver) => resolver(&text, base_dir),
+            None => resolve_image_path(&text, base_dir),
+        };
+        self.image_hover = Some((line, cols, None));
+        let Some(path) = path else {
+            return;
+        };
+        self.image_hover_task = Some(cx.spawn(async move |this, cx| {
+            Timer::after(IMAGE_HOVER_DELAY).await;
+            let Some(preview) = smol::unblock(move || ImagePreview::load(path)).await else {
+                return;
+            };
+            let _ = this.update(cx, |state, cx| {
+                if let Some((_, _, slot)) = state.image_hover.as_mut() {
+                    *slot = Some(preview);
+                    cx.notify();
+                }
+            });
+        }));
+    }
+
+    /// The loaded preview under the mouse and the point below its literal.
+    fn image_hover_overlay(&self) -> Option<(ImagePreview, Point<Pixels>)> {
+        let (line, cols, preview) = self.image_hover.as_ref()?;
+        let preview = preview.clone()?;
+        let origin = self.text_origin(Position::new(*line, cols.start))?;
+        Some((preview, point(origin.x, origin.y + self.line_height)))
+    }
+
+    /// Window coordinates of the top-left corner of the character at `pos`, at the
+    /// current scroll offset, or `None` while its line is folded away.
+    ///
+    /// `last_bounds` is from the last paint; the scroll offset may have changed since,
+    /// so the vertical position is rebased onto the current one.
+    fn text_origin(&self, pos: Position) -> Option<Point<Pixels>> {
+        let bounds = self.last_bounds?;
+        let row = self.buffer_line_to_display_row(pos.line)?;
+        let gutter_width = self.gutter_width();
+        let x = self
+            .line_layouts
+            .get(&pos.line)
+            .map_or(px(8.4) * pos.col as f32, |layout| {
+                layout.x_for_index(pos.col)
+            });
+        let scrolled = self.scroll_handle.offset().y - self.painted_scroll_y;
+        Some(point(
+            bounds.left() + gutter_width + x - self.scroll_offset_x,
+            bounds.top() + scrolled + px(12.0) + self.line_height * row as f32,
+        ))
+    }
+
+    /// Show `view` next to the text at `pos`. It stays with that text as the buffer
+    /// scrolls and is edited, hides while the text is folded or scrolled out of view,
+    /// and is dropped when the whole content is replaced. Use it for completion menus,
+    /// code-action menus, rename boxes and other popovers tied to a spot in the code.
+    pub fn anchor_overlay(
+        &mut self,
+        pos: Position,
+        placement: OverlayPlacement,
+        view: impl Into<AnyView>,
+        cx: &mut Context<Self>,
+    ) -> OverlayId {
+        let id = OverlayId(self.next_overlay_id);
+        self.next_overlay_id += 1;
+        self.anchored_overlays.push(AnchoredOverlay {
+            id,
+            offset: self.pos_to_byte_offset(pos),
+            placement,
+            view: view.into(),
+        });
+        cx.notify();
+        id
+    }
+
+    /// Re-anchor an overlay, e.g. a completion menu following the word being typed.
+    pub fn move_overlay(&mut self, id: OverlayId, pos: Position, cx: &mut Context<Self>) {
+        let offset = self.pos_to_byte_offset(pos);
+        if let Some(overlay) = self.anchored_overlays.iter_mut().find(|o| o.id == id) {
+            overlay.offset = offset;
+            cx.notify();
+        }
+    }
+
+    pub fn remove_overlay(&mut self, id: OverlayId, cx: &mut Context<Self>) -> bool {
+        let len = self.anchored_overlays.len();
+        self.anchored_overlays.retain(|overlay| overlay.id != id);
+        let removed = self.anchored_overlays.len() != len;
+        if removed {
+            cx.notify();
+        }
+        removed
+    }
+
+    /// Where an overlay's anchor is now, after the edits since it was placed.
+    pub fn overlay_position(&self, id: OverlayId) -> Option<Position> {
+        self.anchored_overlays
+            .iter()
+            .find(|overlay| overlay.id == id)
+            .map(|overlay| self.byte_offset_to_pos(overlay.offset))
+    }
+
+    /// The overlays on screen, with the window point each hangs from: the bottom of
+    /// its line when placed below, the top when placed above.
+    fn visible_anchored_overlays(&self) -> Vec<(AnyView, OverlayPlacement, Point<Pixels>)> {
+        let viewport = self.scroll_handle.bounds();
+        self.anchored_overlays
+            .iter()
+            .filter_map(|overlay| {
+                let origin = self.text_origin(self.byte_offset_to_pos(overlay.offset))?;
+                let line_bottom = origin.y + self.line_height;
+                if line_bottom <= viewport.top() || origin.y >= viewport.bottom() {
+                    return None;
+                }
+                let position = match overlay.placement {
+                    OverlayPlacement::Below => point(origin.x, line_bottom),
+                    OverlayPlacement::Above => origin,
+                };
+                Some((overlay.view.clone(), overlay.placement, position))
+            })
+            .collect()
+    }
+
+    /// The literal whose swatch is at `x`, in the line's text coordinates, with the
+    /// swatch's left edge.
+    fn color_swatch_at(&self, line: usize, x: Pixels) -> Option<(ColorLiteral, Pixels)> {
+        let layout = self
+            .line_layouts
+            .get(&line)
+            .filter(|layout| layout.window.is_none())?;
+        find_color_literals(&self.line_text(line))
+            .into_iter()
+            .find_map(|literal| {
+                let (left, right) = layout.color_swatch_bounds(literal.range.start);
+                (x >= left && x < right).then_some((literal, left))
+            })
+    }
+
+    /// Replace the gutter decorations from `source`, e.g. `"breakpoints"`, leaving other
+    /// sources' alone. Decorations sit between the line number and the fold chevron and
+    /// hide a code action lightbulb on the same line; where sources overlap, the one set
+    /// first is drawn. Like diagnostics they don't follow edits.
+    pub fn set_gutter_decorations(
+        &mut self,
+        source: impl Into<SharedString>,
+        decorations: Vec<GutterDecoration>,
+        cx: &mut Context<Self>,
+    ) {
+        let source = source.into();
+        match self
+            .gutter_decorations
+            .iter_mut()
+            .find(|(existing, _)| *existing == source)
+        {
+            Some((_, existing)) => *existing = decorations,
+            None => self.gutter_decorations.push((source, decorations)),
+        }
+        cx.notify();
+    }
+
+    pub fn clear_gutter_decorations(&mut self, source: &str, cx: &mut Context<Self>) {
+        self.gutter_decorations
+            .retain(|(existing, _)| existing.as_ref() != source);
+        cx.notify();
+    }
+
+    /// The decoration drawn on `line`, if any.
+    pub fn gutter_decoration_at(&self, line: usize) -> Option<&GutterDecoration> {
+        self.gutter_decorations
+            .iter()
+            .flat_map(|(_, decorations)| decorations)
+            .find(|decoration| decoration.line == line)
+    }
+
+    /// Called when the decoration slot of a line is clicked and no decoration there has
+    /// its own handler, e.g. to add a breakpoint.
+    pub fn on_gutter_click(
+        &mut self,
+        handler: impl Fn(&GutterClick, &mut Window, &mut App) + 'static,
+    ) {
+        self.on_gutter_click = Some(Rc::new(handler));
+    }
+
+    /// Add a lane to the gutter, replacing any lane with the same id.
+    pub fn add_gutter_lane(&mut self, lane: GutterLane, cx: &mut Context<Self>) {
+        self.gutter_lanes.retain(|existing| existing.id != lane.id);
+        self.gutter_lanes.push(lane);
+        self.gutter_lanes.sort_by_key(|lane| lane.order);
+        cx.notify();
+    }
+
+    /// Remove the lane with `id`, which may be one of the built-in lanes such as
+    /// [`GutterLane::FOLDS`].
+    pub fn remove_gutter_lane(&mut self, id: &str, cx: &mut Context<Self>) {
+        self.gutter_lanes.retain(|lane| lane.id.as_ref() != id);
+        cx.notify();
+    }
+
+    pub fn gutter_lanes(&self) -> &[GutterLane] {
+        &self.gutter_lanes
+    }
+
+    /// Width of the gutter: its lanes, or a margin while line numbers are hidden.
+    pub fn gutter_width(&self) -> Pixels {
+        if self.show_line_numbers {
+            self.gutter_lanes
+                .iter()
+                .fold(px(0.0), |width, lane| width + lane.width)
+                .max(px(12.0))
+        } else {
+            px(12.0)
+        }
+    }
+
+    /// Where the lane with `id` starts, measured from the editor's left edge.
+    fn gutter_lane_left(&self, id: &str) -> Option<Pixels> {
+        let mut left = px(0.0);
+        for lane in &self.gutter_lanes {
+            if lane.id.as_ref() == id {
+                return Some(left);
+            }
+            left += lane.width;
+        }
+        None
+    }
+
+    /// The lane under `x`, measured from the editor's left edge, and where it starts.
+    fn gutter_lane_at(&self, x: Pixels) -> Option<(GutterLane, Pixels)> {
+        if !self.show_line_numbers {
+            return None;
+        }
+        let mut left = px(0.0);
+        for lane in &self.gutter_lanes {
+            if x >= left && x < left + lane.width {
+                return Some((lane.clone(), left));
+            }
+            left += lane.width;
+        }
+        None
+    }
+
+    /// Scroll `a` and `b` together, such as the two sides of a diff or a translation
+    /// next to its source. Scrolling either one moves the other; with `sync_cursor`,
+    /// moving either cursor to another line moves the other's to the matching line.
+    /// Linking a pair again replaces its mode, e.g. with a new alignment after edits.
+    pub fn link_scroll(
+        a: &Entity<Self>,
+        b: &Entity<Self>,
+        mode: ScrollSyncMode,
+        sync_cursor: bool,
+        cx: &mut App,
+    ) {
+        if a.entity_id() == b.entity_id() {
+            return;
+        }
+        let (forward, backward) = match mode {
+            ScrollSyncMode::Proportional => (None, None),
+            ScrollSyncMode::Aligned(pairs) => {
+                let reversed = pairs.iter().map(|&(a, b)| (b, a)).collect();
+                (Some(pairs), Some(reversed))
+            }
+        };
+        for (this, peer, alignment) in [(a, b, forward), (b, a, backward)] {
+            let peer = peer.downgrade();
+            this.update(cx, |this, cx| {
+                this.scroll_links
+                    .retain(|link| link.peer.entity_id() != peer.entity_id());
+                this.scroll_links.push(ScrollLink {
+                    peer,
+                    alignment,
+                    sync_cursor,
+                });
+                this.synced_scroll_y = this.scroll_handle.offset().y;
+                this.synced_cursor_line = Some(this.cursor.line);
+                cx.notify();
+            });
+        }
+    }
+
+    /// Stop following, and being followed by, every linked editor.
+    pub fn unlink_scroll(&mut self, cx: &mut Context<Self>) {
+        let this = cx.entity_id();
+        for link in std::mem::take(&mut self.scroll_links) {
+            if let Some(peer) = link.peer.upgrade() {
+                peer.update(cx, |peer, _| {
+                    peer.scroll_links
+                        .retain(|link| link.peer.entity_id() != this)
+                });
+            }
+        }
+    }
+
+    /// Publish cursor, selection and scroll changes made since the last call.
+    fn emit_selection_events(&mut self, cx: &mut Context<Self>) {
+        let scroll_y = self.scroll_handle.offset().y;
+        if (scroll_y - self.reported_scroll_y).abs() > px(0.5) {
+            self.reported_scroll_y = scroll_y;
+            cx.emit(BufferEvent::Scrolled {
+                top_line: self.scroll_top_line(),
+            });
+        }
+        if self.cursor != self.reported_cursor {
+            self.reported_cursor = self.cursor;
+            cx.emit(BufferEvent::CursorMoved {
+                position: self.cursor,
+            });
+        }
+        if self.reported_selections.0 != self.selection
+            || self.reported_selections.1 != self.extra_selections
+        {
+            self.reported_selections = (self.selection, self.extra_selections.clone());
+            cx.emit(BufferEvent::SelectionChanged {
+                selections: self.selections(),
+            });
+        }
+    }
+
+    /// Top of the viewport as a fractional buffer line.
+    pub fn scroll_top_line(&self) -> f32 {
+        let row = (-self.scroll_handle.offset().y / self.line_height).max(0.0);
+        let display_lines = self.display_lines();
+        match display_lines.get(row.floor() as usize) {
+            Some(&line) => line as f32 + row.fract(),
+            None => self.total_lines() as f32,
+        }
+    }
+
+    /// Push this editor's scroll position and cursor line to linked editors if either
+    /// changed since the last sync. Run on paint, which follows any scroll.
+    fn sync_linked_editors(&mut self, cx: &mut Context<Self>) {
+        if self.scroll_links.is_empty() {
+            return;
+        }
+        let offset_y = self.scroll_handle.offset().y;
+        let scrolled = (offset_y - self.synced_scroll_y).abs() > px(0.5);
+        let cursor_line = self.cursor.line;
+        let cursor_moved = self.synced_cursor_line != Some(cursor_line);
+        if !scrolled && !cursor_moved {
+            return;
+        }
+        self.synced_scroll_y = offset_y;
+        self.synced_cursor_line = Some(cursor_line);
+
+        let max_offset = self.scroll_handle.max_offset().height;
+        let fraction = if max_offset > px(0.0) {
+            (-offset_y / max_offset).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let top_line = self.scroll_top_line();
+
+        self.scroll_links
+            .retain(|link| link.peer.upgrade().is_some());
+        for link in &self.scroll_links {
+            let Some(peer) = link.peer.upgrade() else {
+                continue;
+            };
+            peer.update(cx, |peer, cx| {
+                if scrolled {
+                    let peer_max = peer.scroll_handle.max_offset().height;
+                    let target = match &link.alignment {
+                        None => -(peer_max * fraction),
+                        Some(pairs) => {
+                            let line = map_aligned_line(pairs, top_line);
+                            let display_lines = peer.display_lines();
+                            let row = match display_lines.binary_search(&(line.floor() as usize)) {
+                                Ok(row) | Err(row) => row as f32 + line.fract(),
+                            };
+                            -(peer.line_height * row)
+                        }
+                    };
+                    let target = target.max(-peer_max).min(px(0.0));
+                    let peer_offset = peer.scroll_handle.offset();
+                    peer.scroll_handle.set_offset(point(peer_offset.x, target));
+                    peer.synced_scroll_y = target;
+                }
+                if cursor_moved && link.sync_cursor {
+                    let line = match &link.alignment {
+                        None => cursor_line,
+                        Some(pairs) => map_aligned_line(pairs, cursor_line as f32).round() as usize,
+                    };
+                    let line = line.min(peer.total_lines().saturating_sub(1));
+                    if peer.cursor.line != line {
+                        peer.selection = None;
+                        peer.cursor = Position::new(line, peer.cursor.col.min(peer.line_len(line)));
+                    }
+                    peer.synced_cursor_line = Some(peer.cursor.line);
+                }
+                cx.notify();
+            });
+        }
+    }
+
+    /// Replace the change markers shown in the gutter: green bars for added lines, blue
+    /// for modified ones and a red wedge where lines were deleted. Like diagnostics, the
+    /// hunks don't follow edits; send a fresh set after each change.
+    pub fn set_gutter_diff(&mut self, mut hunks: Vec<DiffHunk>, cx: &mut Context<Self>) {
+        hunks.sort_by_key(|hunk| (hunk.lines.start, hunk.lines.end));
+        self.diff_hunks = hunks;
+        cx.notify();
+    }
+
+    pub fn gutter_diff(&self) -> &[DiffHunk] {
+        &self.diff_hunks
+    }
+
+    pub fn hunk_at_line(&self, line: usize) -> Option<&DiffHunk> {
+        let line_count = self.rope.len_lines();
+        let candidates = self
+            .diff_hunks
+            .partition_point(|hunk| hunk.lines.start <= line);
+        self.diff_hunks[..candidates]
+            .iter()
+            .rev()
+            .find(|hunk| hunk.covers_line(line, line_count))
+            .or_else(|| {
+                // A deletion at the end of the file starts past the last line.
+                self.diff_hunks[candidates..]
+                    .iter()
+                    .find(|hunk| hunk.covers_line(line, line_count))
+            })
+    }
+
+    /// The hunk on the cursor's line, for actions like "Revert Hunk".
+    pub fn hunk_at_cursor(&self) -> Option<&DiffHunk> {
+        self.hunk_at_line(self.cursor.line)
+    }
+
+    /// Called when a change marker in the gutter is clicked, with the window position
+    /// below the marker where a hunk popover can open.
+    pub fn on_diff_hunk_click(
+        &mut self,
+        handler: impl Fn(&DiffHunk, Point<Pixels>, &mut Window, &mut App) + 'static,
+    ) {
+        self.on_diff_hunk_click = Some(Rc::new(handler));
+    }
+
+    /// Replace the lines of `hunk` with `base_text`, the hunk's base-version content
+    /// including its trailing newline, as one undo step.
+    pub fn revert_hunk(&mut self, hunk: &DiffHunk, base_text: &str, cx: &mut Context<Self>) {
+        if self.read_only {
+            return;
+        }
+        let line_count = self.rope.len_lines();
+        let start = self.rope.line_to_byte(hunk.lines.start.min(line_count));
+        let end = self.rope.line_to_byte(hunk.lines.end.min(line_count));
+        let base_text = normalize_line_endings(base_text);
+        self.replace_bytes(start..end, &base_text, start, cx);
+    }
+
+    /// Replace a byte range of the buffer as one undo step, collapsing the selection to
+    /// a cursor at the byte offset `cursor` (in the edited buffer).
+    fn replace_bytes(
+        &mut self,
+        range: Range<usize>,
+        text: &str,
+        cursor: usize,
+        cx: &mut Context<Self>,
+    ) {
+        let Range { start, end } = range;
+        let old_end_position = self.byte_to_ts_point(end);
+        self.begin_undo_transaction();
+        if end > start {
+            let deleted: String = self.rope.byte_slice(start..end).into();
+            self.push_undo(EditOp::Delete {
+                byte_offset: start,
+                text: deleted,
+            });
+            self.rope_remove(start, end, cx);
+        }
+        if !text.is_empty() {
+            self.push_undo(EditOp::Insert {
+                byte_offset: start,
+                text: text.to_string(),
+            });
+            self.rope_insert(start, text, cx);
+        }
+        self.clear_extra_cursors();
+        self.selection = None;
+        self.cursor = self.byte_offset_to_pos(cursor);
+        self.end_undo_transaction();
+        self.mark_modified();
+        self.update_syntax_tree_incremental(start, end, start + text.len(), old_end_position, cx);
+        self.invalidate_after_edit();
+        cx.notify();
+    }
+
+    /// Apply a batch of edits, each a byte range of the current buffer and the text to
+    /// put in its place, as a single undo step, e.g. a formatter's output or a
+    /// workspace edit. Cursors, selections, folds and diagnostics move with the text
+    /// around them.
+    ///
+    /// Returns `false` without changing anything if the buffer is read-only, or if a
+    /// range overlaps another, falls outside the buffer or splits a character.
+    /// Insertions at the same offset are applied in the order given.
+    pub fn apply_edits(
+        &mut self,
+        edits: Vec<(Range<usize>, String)>,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let mut edits: Vec<(Range<usize>, String)> = edits
+            .into_iter()
+            .map(|(range, text)| (range, normalize_line_endings(&text).into_owned()))
+            .filter(|(range, text)| !range.is_empty() || !text.is_empty())
+            .collect();
+        edits.sort_by_key(|(range, _)| (range.start, range.end));
+
+        let rope = &self.rope;
+        let on_boundary = |byte: usize| {
+            byte <= rope.len_bytes() && rope.char_to_byte(rope.byte_to_char(byte)) == byte
+        };
+        let in_bounds = edits.iter().all(|(range, _)| {
+            range.start <= range.end && on_boundary(range.start) && on_boundary(range.end)
+        });
+        let disjoint = edits
+            .windows(2)
+            .all(|pair| pair[0].0.end <= pair[1].0.start);
+        if self.read_only || !in_bounds || !disjoint {
+            return false;
+        }
+        if edits.is_empty() {
+            return true;
+        }
+
+        let map = |byte: usize| map_through_edits(byte, &edits);
+        let selections: Vec<(usize, usize)> = self
+            .selections()
+            .iter()
+            .map(|sel| {
+                let anchor = self.pos_to_byte_offset(sel.anchor);
+                (map(anchor), map(self.pos_to_byte_offset(sel.cursor)))
+            })
+            .collect();
+        let diagnostics: Vec<(usize, usize)> = self
+            .diagnostics
+            .iter()
+            .map(|diag| {
+                let start = Position::new(diag.start_line as usize, diag.start_col as usize);
+                let end = Position::new(diag.end_line as usize, diag.end_col as usize);
+                (
+                    map(self.pos_to_byte_offset(start)),
+                    map(self.pos_to_byte_offset(end)),
+                )
+            })
+            .collect();
+        let line_count = self.rope.len_lines();
+        let folds: Vec<(usize, usize)> = self
+            .folded
+            .iter()
+            .map(|fold| {
+                let start = self.rope.line_to_byte(fold.start_line.min(line_count));
+                let end = self.rope.line_to_byte(fold.end_line.min(line_count));
+                (map(start), map(end))
+            })
+            .collect();
+
+        self.begin_undo_transaction();
+        for (range, text) in edits.iter().rev() {
+            if !range.is_empty() {
+                let deleted: String = self.rope.byte_slice(range.clone()).into();
+                self.push_undo(EditOp::Delete {
+                    byte_offset: range.start,
+                    text: deleted,
+                });
+                self.rope_remove(range.start, range.end, cx);
+            }
+            if !text.is_empty() {
+                self.push_undo(EditOp::Insert {
+                    byte_offset: range.start,
+                    text: text.clone(),
+                });
+                self.rope_insert(range.start, text, cx);
+            }
+        }
+
+        let selections: Vec<Selection> = selections
+            .into_iter()
+            .map(|(anchor, cursor)| {
+                Selection::new(
+                    self.byte_offset_to_pos(anchor),
+                    self.byte_offset_to_pos(cursor),
+                )
+            })
+            .collect();
+        self.restore_selections(&selections);
+        let diagnostics: Vec<(Position, Position)> = diagnostics
+            .into_iter()
+            .map(|(start, end)| (self.byte_offset_to_pos(start), self.byte_offset_to_pos(end)))
+            .collect();
+        for (diag, (start, end)) in self.diagnostics.iter_mut().zip(diagnostics) {
+            diag.start_line = start.line as u32;
+            diag.start_col = start.col as u32;
+            diag.end_line = end.line as u32;
+            diag.end_col = end.col as u32;
+        }
+        for (fold, (start, end)) in self.folded.iter_mut().zip(folds) {
+            fold.start_line = self.rope.byte_to_line(start);
+            fold.end_line = self.rope.byte_to_line(end);
+        }
+
+        self.end_undo_transaction();
+        self.mark_modified();
+        self.update_syntax_tree();
+        self.invalidate_folds();
+        self.invalidate_after_edit();
+        cx.notify();
+        true
+    }
+
+    pub fn diagnostics_at_line(&self, line: usize) -> Vec<&EditorDiagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.start_line as usize <= line && line <= d.end_line as usize)
             .collect()
     }
 
@@ -726,6 +2999,14 @@ impl EditorState {
         self.rope.to_string()
     }
 
+    /// The text in a byte range of the buffer, clamped to it.
+    pub fn text_for_range(&self, range: Range<usize>) -> String {
+        let len = self.rope.len_bytes();
+        self.rope
+            .byte_slice(range.start.min(len)..range.end.min(len))
+            .into()
+    }
+
     pub fn is_empty(&self) -> bool {
         self.rope.len_bytes() == 0 || (self.rope.len_bytes() == 1 && self.rope.len_lines() <= 1)
     }
@@ -753,6 +3034,46 @@ impl EditorState {
         self.file_path.as_ref()
     }
 
+    /// Line ending detected when the content was loaded, used when saving.
+    pub fn line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+
+    /// Save with `line_ending` from now on. The buffer is unchanged but counts as
+    /// modified so the file gets rewritten.
+    pub fn convert_line_endings(&mut self, line_ending: LineEnding, cx: &mut Context<Self>) {
+        if self.line_ending == line_ending {
+            return;
+        }
+        self.line_ending = line_ending;
+        self.is_modified = true;
+        cx.notify();
+    }
+
+    /// Encoding detected when the file was loaded, used when saving.
+    pub fn encoding(&self) -> TextEncoding {
+        self.encoding
+    }
+
+    /// Save with `encoding` from now on. Like [`Self::convert_line_endings`], this marks
+    /// the buffer modified.
+    pub fn set_encoding(&mut self, encoding: TextEncoding, cx: &mut Context<Self>) {
+        if self.encoding == encoding {
+            return;
+        }
+        self.encoding = encoding;
+        self.is_modified = true;
+        cx.notify();
+    }
+
+    /// Reload the file decoded as `encoding`, for when detection guessed wrong.
+    /// Unsaved changes are discarded.
+    pub fn reopen_with_encoding(&mut self, encoding: TextEncoding, cx: &mut Context<Self>) {
+        if let Some(path) = self.file_path.clone() {
+            self.load_file_with_encoding(path, Some(encoding), cx);
+        }
+    }
+
     pub fn language(&self) -> Language {
         self.language
     }
@@ -788,89 +3109,118 @@ impl EditorState {
     }
 
     pub fn find_matching_bracket(&self) -> Option<(Position, Position)> {
-        let line_text = self.line_text(self.cursor.line);
-        let col = self.cursor.col.min(line_text.len());
-        let bytes = line_text.as_bytes();
+        let line_start = self.rope.line_to_byte(self.cursor.line);
+        let line_len = self.line_len(self.cursor.line);
+        let col = self.cursor.col.min(line_len);
 
         let check_positions: &[usize] = if col > 0 { &[col, col - 1] } else { &[col] };
 
         for &check_col in check_positions {
-            if check_col >= bytes.len() {
+            if check_col >= line_len {
                 continue;
             }
-            let ch = bytes[check_col] as char;
-            let (opener, closer, forward) = match ch {
-                '(' => ('(', ')', true),
-                '[' => ('[', ']', true),
-                '{' => ('{', '}', true),
-                ')' => ('(', ')', false),
-                ']' => ('[', ']', false),
-                '}' => ('{', '}', false),
+            let offset = line_start + check_col;
+            let (opener, closer, forward) = match self.rope.byte(offset) {
+                b'(' => (b'(', b')', true),
+                b'[' => (b'[', b']', true),
+                b'{' => (b'{', b'}', true),
+                b')' => (b'(', b')', false),
+                b']' => (b'[', b']', false),
+                b'}' => (b'{', b'}', false),
                 _ => continue,
             };
 
             let start_pos = Position::new(self.cursor.line, check_col);
-
+            // Brackets are ASCII, so scanning bytes never matches inside a multi-byte char.
+            let mut depth = 1usize;
             if forward {
-                let mut depth = 1i32;
-                let mut scan_line = self.cursor.line;
-                let mut scan_col = check_col + 1;
-                let total = self.total_lines();
-                while scan_line < total {
-                    let scan_text = self.line_text(scan_line);
-                    let scan_bytes = scan_text.as_bytes();
-                    while scan_col < scan_bytes.len() {
-                        let sc = scan_bytes[scan_col] as char;
-                        if sc == opener {
-                            depth += 1;
-                        } else if sc == closer {
-                            depth -= 1;
-                            if depth == 0 {
-                                return Some((start_pos, Position::new(scan_line, scan_col)));
-                            }
+                for (i, byte) in self.rope.bytes_at(offset + 1).enumerate() {
+                    if byte == opener {
+                        depth += 1;
+                    } else if byte == closer {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some((start_pos, self.byte_offset_to_pos(offset + 1 + i)));
                         }
-                        scan_col += 1;
                     }
-                    scan_line += 1;
-                    scan_col = 0;
                 }
             } else {
-                let mut depth = 1i32;
-                let mut scan_line = self.cursor.line;
-                let mut scan_col = check_col as i64 - 1;
-                loop {
-                    if scan_col < 0 {
-                        if scan_line == 0 {
-                            break;
-                        }
-                        scan_line -= 1;
-                        let prev_text = self.line_text(scan_line);
-                        scan_col = prev_text.len() as i64 - 1;
-                        continue;
-                    }
-                    let scan_text = self.line_text(scan_line);
-                    let scan_bytes = scan_text.as_bytes();
-                    if (scan_col as usize) < scan_bytes.len() {
-                        let sc = scan_bytes[scan_col as usize] as char;
-                        if sc == closer {
-                            depth += 1;
-                        } else if sc == opener {
-                            depth -= 1;
-                            if depth == 0 {
-                                return Some((
-                                    Position::new(scan_line, scan_col as usize),
-                                    start_pos,
-                                ));
-                            }
+                let mut bytes = self.rope.bytes_at(offset);
+                let mut at = offset;
+                while let Some(byte) = bytes.prev() {
+                    at -= 1;
+                    if byte == closer {
+                        depth += 1;
+                    } else if byte == opener {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some((self.byte_offset_to_pos(at), start_pos));
                         }
                     }
-                    scan_col -= 1;
                 }
             }
         }
         None
     }
 
+    /// `find_matching_bracket`, reused until the buffer or cursor changes.
+    fn cached_bracket_match(&mut self) -> Option<(Position, Position)> {
+        let key = (self.content_version, self.cursor);
+        if let Some((cached_key, result)) = self.bracket_match_cache {
+            if cached_key == key {
+                return result;
+            }
+        }
+        let result = self.find_matching_bracket();
+        self.bracket_match_cache = Some((key, result));
+        result
+    }
+
+    /// Whole-word occurrences of the word under the cursor on buffer `lines`, as
+    /// `(line, start_col, end_col)`. Reused until the buffer, cursor or lines change.
+    fn cached_word_occurrences(&mut self, lines: &[usize]) -> Rc<Vec<(usize, usize, usize)>> {
+        let key = (
+            self.content_version,
+            self.cursor,
+            lines.first().copied().unwrap_or(0)..lines.last().map_or(0, |line| line + 1),
+        );
+        if let Some((cached_key, occurrences)) = &self.word_occurrence_cache {
+            if *cached_key == key {
+                return occurrences.clone();
+            }
+        }
+        let occurrences = Rc::new(self.word_occurrences_on(lines));
+        self.word_occurrence_cache = Some((key, occurrences.clone()));
+        occurrences
+    }
+
+    fn word_occurrences_on(&self, lines: &[usize]) -> Vec<(usize, usize, usize)> {
+        let Some((word, _, _)) = self.word_under_cursor_full() else {
+            return Vec::new();
+        };
+        let is_word_byte = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+        let mut results = Vec::new();
+        for &line_idx in lines {
+            if line_idx >= self.rope.len_lines() {
+                continue;
+            }
+            let slice = self.rope.line(line_idx);
+            let slice = slice.byte_slice(..line_content_len(slice));
+            // Borrowed unless the line spans rope chunks.
+            let line_text: std::borrow::Cow<str> = slice.into();
+            let bytes = line_text.as_bytes();
+            for (start, _) in line_text.match_indices(word.as_str()) {
+                let end = start + word.len();
+                let before_ok = start == 0 || !is_word_byte(bytes[start - 1]);
+                let after_ok = end >= bytes.len() || !is_word_byte(bytes[end]);
+                if before_ok && after_ok {
+                    results.push((line_idx, start, end));
+                }
+            }
+        }
+        results
+    }
+
     pub fn word_under_cursor_full(&self) -> Option<(String, usize, usize)> {
         let line_text = self.line_text(self.cursor.line);
         if line_text.is_empty() {
@@ -1151,13 +3501,76 @@ impl EditorState {
         &self.folded
     }
 
+    pub fn session(&self) -> EditorSession {
+        EditorSession {
+            selections: self.selections(),
+            scroll_top_line: self.scroll_top_line(),
+            scroll_x: f32::from(self.scroll_offset_x),
+            folded: self.folded.clone(),
+        }
+    }
+
+    /// Put back a session saved with [`Self::session`]. Positions past the end of the
+    /// buffer, which may have changed on disk since, are clamped to it, and folds that
+    /// no longer fit are dropped.
+    pub fn restore_session(&mut self, session: &EditorSession, cx: &mut Context<Self>) {
+        let total = self.total_lines();
+        let clamp = |pos: Position| {
+            let line = pos.line.min(total.saturating_sub(1));
+            Position::new(line, pos.col.min(self.line_len(line)))
+        };
+        let mut selections: Vec<Selection> = session
+            .selections
+            .iter()
+            .map(|selection| Selection::new(clamp(selection.anchor), clamp(selection.cursor)))
+            .collect();
+        let primary = if selections.is_empty() {
+            Selection::new(Position::zero(), Position::zero())
+        } else {
+            selections.remove(0)
+        };
+        self.cursor = primary.cursor;
+        self.selection = (!primary.is_empty()).then_some(primary);
+        self.extra_selections = selections;
+        self.column_select = None;
+
+        self.folded = session
+            .folded
+            .iter()
+            .filter(|fold| fold.start_line < fold.end_line && fold.end_line < total)
+            .copied()
+            .collect();
+        self.invalidate_folds();
+
+        // Not clamped to the scroll extent, which is unknown until the first layout
+        // after a reopen; layout clamps it instead.
+        let top_line = session.scroll_top_line.max(0.0);
+        let display_row = self
+            .buffer_line_to_display_row(top_line.floor() as usize)
+            .map_or(0.0, |row| row as f32 + top_line.fract());
+        let offset_y = -(self.line_height * display_row);
+        self.scroll_handle
+            .set_offset(point(self.scroll_handle.offset().x, offset_y.min(px(0.0))));
+        self.scroll_offset_x = px(session.scroll_x.max(0.0));
+        self.reset_cursor_blink(cx);
+        cx.notify();
+    }
+
     pub fn scope_breadcrumbs(&self) -> Vec<(String, usize)> {
+        let byte_offset = self.pos_to_byte_offset(self.cursor);
+        self.enclosing_scopes(byte_offset)
+            .into_iter()
+            .map(|(name, lines)| (name, lines.start))
+            .collect()
+    }
+
+    /// Named scopes containing `byte_offset`, outermost first, with the lines each spans.
+    fn enclosing_scopes(&self, byte_offset: usize) -> Vec<(String, Range<usize>)> {
         let tree = match &self.syntax_tree {
             Some(t) => t,
             None => return Vec::new(),
         };
 
-        let byte_offset = self.pos_to_byte_offset(self.cursor);
         let ts_point = self.byte_to_ts_point(byte_offset);
         let mut node = match tree
             .root_node()
@@ -1167,13 +3580,13 @@ impl EditorState {
             None => return Vec::new(),
         };
 
-        let mut breadcrumbs = Vec::new();
+        let mut scopes = Vec::new();
         loop {
             let kind = node.kind();
             if Self::is_scope_kind(kind) {
                 if let Some(name) = Self::extract_scope_name(&node, &self.rope) {
-                    let line = node.start_position().row;
-                    breadcrumbs.push((name, line));
+                    let lines = node.start_position().row..node.end_position().row;
+                    scopes.push((name, lines));
                 }
             }
             match node.parent() {
@@ -1181,8 +3594,116 @@ impl EditorState {
                 None => break,
             }
         }
-        breadcrumbs.reverse();
-        breadcrumbs
+        scopes.reverse();
+        scopes
+    }
+
+    /// Definition lines to pin above the viewport when `first_display_row` is the top
+    /// visible row: each scope whose first line has scrolled off while its body still
+    /// continues below the header rows stacked above it.
+    fn sticky_scope_lines(&self, first_display_row: usize) -> Vec<usize> {
+        let display_lines = self.display_lines();
+        let Some(&top_line) = display_lines.get(first_display_row) else {
+            return Vec::new();
+        };
+        let mut lines = Vec::new();
+        for (_, scope) in self.enclosing_scopes(self.rope.line_to_byte(top_line)) {
+            if lines.len() >= STICKY_SCROLL_MAX_LINES {
+                break;
+            }
+            let covered_line = display_lines
+                .get(first_display_row + lines.len())
+                .copied()
+                .unwrap_or(top_line);
+            if scope.start >= covered_line || scope.end <= covered_line {
+                break;
+            }
+            lines.push(scope.start);
+        }
+        lines
+    }
+
+    /// Scroll a sticky header's scope into view with the cursor on its first line.
+    fn jump_to_scope_start(&mut self, line: usize, cx: &mut Context<Self>) {
+        let line_text = self.line_text(line);
+        let indent = line_text.len() - line_text.trim_start().len();
+        self.clear_extra_cursors();
+        self.selection = None;
+        let from = self.cursor;
+        self.cursor = Position::new(line, indent);
+        self.record_jump(from);
+        let display_row = self.buffer_line_to_display_row(line).unwrap_or(0);
+        let offset = self.scroll_handle.offset();
+        let max_offset = self.scroll_handle.max_offset().height;
+        let target_y = -(self.line_height * display_row as f32);
+        self.scroll_handle
+            .set_offset(point(offset.x, target_y.max(-max_offset).min(px(0.0))));
+        self.reset_cursor_blink(cx);
+        cx.notify();
+    }
+
+    /// Guides for the bracketed blocks overlapping buffer `lines`, each spanning the
+    /// lines between its brackets at the indentation of its opening line. The innermost
+    /// block around the cursor is marked active.
+    fn scope_guides_in(&self, lines: Range<usize>) -> Vec<ScopeGuide> {
+        let Some(tree) = &self.syntax_tree else {
+            return Vec::new();
+        };
+        let cursor_byte = self.pos_to_byte_offset(self.cursor);
+        let mut guides: Vec<ScopeGuide> = Vec::new();
+        let mut active: Option<(usize, usize)> = None;
+        let mut stack = vec![tree.root_node()];
+        while let Some(node) = stack.pop() {
+            let start = node.start_position().row;
+            let end = node.end_position().row;
+            if end < lines.start || start >= lines.end {
+                continue;
+            }
+            for i in 0..node.child_count() {
+                if let Some(child) = node.child(i) {
+                    stack.push(child);
+                }
+            }
+            if end <= start + 1 || !Self::is_bracketed(&node) {
+                continue;
+            }
+            if node.start_byte() < cursor_byte
+                && cursor_byte < node.end_byte()
+                && active.map_or(true, |(s, e)| start >= s && end <= e)
+            {
+                active = Some((start, end));
+            }
+            if guides.iter().any(|guide| guide.lines == (start + 1..end)) {
+                continue;
+            }
+            let line_text = self.line_text(start);
+            guides.push(ScopeGuide {
+                lines: start + 1..end,
+                column: line_text.len() - line_text.trim_start().len(),
+                active: false,
+            });
+        }
+        if let Some((start, end)) = active {
+            for guide in &mut guides {
+                guide.active = guide.lines == (start + 1..end);
+            }
+        }
+        guides
+    }
+
+    /// Whether `node` opens and closes with a matching bracket, e.g. a block or object.
+    fn is_bracketed(node: &tree_sitter::Node) -> bool {
+        let count = node.child_count();
+        if count < 2 {
+            return false;
+        }
+        let (Some(first), Some(last)) = (node.child(0), node.child(count - 1)) else {
+            return false;
+        };
+        matches!(
+            (first.kind(), last.kind()),
+            ("{", "}") | ("[", "]") | ("(", ")")
+        )
     }
 
     fn is_scope_kind(kind: &str) -> bool {
@@ -1270,50 +3791,268 @@ impl EditorState {
             .any(|&(o, c)| before == o as u8 && after == c as u8)
     }
 
-    pub fn cursor_screen_position(&self, line_height: Pixels) -> Option<Point<Pixels>> {
-        let bounds = self.last_bounds?;
-        let gutter_width = if self.show_line_numbers {
-            px(80.0)
-        } else {
-            px(12.0)
+    /// The window point below the cursor. To keep a popover there while the buffer
+    /// scrolls or changes, use [`Self::anchor_overlay`] instead.
+    pub fn cursor_screen_position(&self, line_height: Pixels) -> Option<Point<Pixels>> {
+        let origin = self.text_origin(self.cursor)?;
+        Some(point(origin.x, origin.y + line_height))
+    }
+
+    pub fn apply_completion(
+        &mut self,
+        trigger_col: usize,
+        insert_text: &str,
+        cx: &mut Context<Self>,
+    ) {
+        if self.read_only {
+            return;
+        }
+
+        let delete_count = self.cursor.col.saturating_sub(trigger_col);
+        if delete_count > 0 {
+            let start_pos = Position::new(self.cursor.line, trigger_col);
+            let end_pos = self.cursor;
+            self.delete_selection_internal(Selection::new(start_pos, end_pos), cx);
+        }
+
+        self.insert_text_at_cursor(insert_text, cx);
+        self.ensure_cursor_visible(cx);
+        cx.notify();
+    }
+
+    /// Like [`Self::apply_completion`], but `snippet` is expanded with tabstops. Use
+    /// this for snippet-kind completion items.
+    pub fn apply_snippet_completion(
+        &mut self,
+        trigger_col: usize,
+        snippet: &str,
+        cx: &mut Context<Self>,
+    ) {
+        if self.read_only {
+            return;
+        }
+
+        let delete_count = self.cursor.col.saturating_sub(trigger_col);
+        if delete_count > 0 {
+            let start_pos = Position::new(self.cursor.line, trigger_col);
+            let end_pos = self.cursor;
+            self.delete_selection_internal(Selection::new(start_pos, end_pos), cx);
+        }
+
+        self.insert_snippet(snippet, cx);
+    }
+
+    /// Insert snippet text at the cursor, replacing the selection, and select its
+    /// first tabstop. Tab and Shift-Tab then move between tabstops; mirrored tabstops
+    /// are edited together. Continuation lines inherit the current line's indent.
+    pub fn insert_snippet(&mut self, snippet: &str, cx: &mut Context<Self>) {
+        if self.read_only {
+            return;
+        }
+
+        self.clear_extra_cursors();
+        self.active_snippet = None;
+        if let Some(selection) = self.selection {
+            self.delete_selection_internal(selection, cx);
+        }
+
+        let indent: String = self
+            .line_text(self.cursor.line)
+            .chars()
+            .take_while(|c| *c == ' ' || *c == '\t')
+            .collect();
+        let snippet = Snippet::parse(snippet).indented(&indent);
+        let base = self.pos_to_byte_offset(self.cursor);
+        let text = snippet.text.clone();
+        self.insert_text_at_cursor(&text, cx);
+
+        self.active_snippet = Some(SnippetSession::new(snippet, base));
+        self.select_tabstop(0);
+        self.ensure_cursor_visible(cx);
+        cx.notify();
+    }
+
+    /// Whether a snippet's tabstops are active.
+    pub fn in_snippet(&self) -> bool {
+        self.active_snippet.is_some()
+    }
+
+    /// Rename the identifier under the cursor: highlight its whole-word occurrences and
+    /// open a box over it for the new name. Enter applies the rename as one undo step
+    /// and escape cancels it.
+    pub fn rename(&mut self, _: &Rename, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(range) = self.identifier_range_at(self.cursor) else {
+            return;
+        };
+        let word = self.line_text(self.cursor.line)[range].to_string();
+        let ranges = occurrence_ranges(&self.rope, &word, true);
+        self.start_rename(ranges, window, cx);
+    }
+
+    /// Open the rename box for byte `ranges` from an external provider, such as a
+    /// language server's references. The box opens over the range holding the cursor,
+    /// or the first one, and starts with its text.
+    pub fn start_rename(
+        &mut self,
+        mut ranges: Vec<Range<usize>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let len = self.rope.len_bytes();
+        ranges.retain(|range| range.start < range.end && range.end <= len);
+        ranges.sort_by_key(|range| range.start);
+        ranges.dedup();
+        if self.read_only || ranges.is_empty() {
+            return;
+        }
+        let cursor = self.pos_to_byte_offset(self.cursor);
+        let primary = ranges
+            .iter()
+            .position(|range| range.start <= cursor && cursor <= range.end)
+            .unwrap_or(0);
+        let original: String = self.rope.byte_slice(ranges[primary].clone()).into();
+
+        let input = cx.new(|cx| {
+            let mut input = InputState::new(cx);
+            input.select_on_focus = true;
+            input.trim_on_blur = false;
+            input
+        });
+        input.update(cx, |input, cx| {
+            input.set_value(original.clone(), window, cx)
+        });
+        let subscription = cx.subscribe_in(
+            &input,
+            window,
+            |state, _, event: &InputEvent, window, cx| match event {
+                InputEvent::Enter => state.confirm_rename(window, cx),
+                InputEvent::Blur => state.cancel_rename(window, cx),
+                InputEvent::Change => cx.notify(),
+                _ => {}
+            },
+        );
+        window.focus(&input.focus_handle(cx));
+        self.rename = Some(RenameSession {
+            ranges,
+            primary,
+            original,
+            input,
+            _subscription: subscription,
+        });
+        cx.notify();
+    }
+
+    /// Whether the rename box is open.
+    pub fn is_renaming(&self) -> bool {
+        self.rename.is_some()
+    }
+
+    /// Replace every occurrence with the name in the rename box and close it. An empty
+    /// or unchanged name just closes it.
+    pub fn confirm_rename(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(session) = self.rename.take() else {
+            return;
         };
-        let padding_top = px(12.0);
+        let name = session.input.read(cx).content().to_string();
+        if !name.is_empty() && name != session.original {
+            let edits = session
+                .ranges
+                .into_iter()
+                .map(|range| (range, name.clone()))
+                .collect();
+            self.apply_edits(edits, cx);
+        }
+        window.focus(&self.focus_handle);
+        cx.notify();
+    }
 
-        let cursor_y = bounds.top() + padding_top + line_height * (self.cursor.line as f32);
+    pub fn cancel_rename(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.rename.take().is_some() {
+            window.focus(&self.focus_handle);
+            cx.notify();
+        }
+    }
 
-        let cursor_x = if let Some(layout) = self.line_layouts.get(&self.cursor.line) {
-            let line_text = self.line_text(self.cursor.line);
-            let char_offset = self.cursor.col.min(line_text.len());
-            let x_offset = layout.x_for_index(char_offset);
-            bounds.left() + gutter_width + x_offset
-        } else {
-            let approx_char_width = px(8.4);
-            bounds.left() + gutter_width + approx_char_width * (self.cursor.col as f32)
+    /// Occurrences being renamed as (line, start byte column, end byte column).
+    fn rename_highlights(&self) -> Vec<(usize, usize, usize)> {
+        let Some(session) = &self.rename else {
+            return Vec::new();
         };
+        session
+            .ranges
+            .iter()
+            .filter_map(|range| {
+                let start = self.byte_offset_to_pos(range.start);
+                let end = self.byte_offset_to_pos(range.end);
+                (start.line == end.line).then_some((start.line, start.col, end.col))
+            })
+            .collect()
+    }
 
-        Some(Point::new(cursor_x, cursor_y + line_height))
+    /// The rename box's input, window position and occurrence count, while open.
+    fn rename_overlay(&self) -> Option<(Entity<InputState>, Point<Pixels>, usize)> {
+        let session = self.rename.as_ref()?;
+        let start = self.byte_offset_to_pos(session.ranges[session.primary].start);
+        let origin = self.text_origin(start)?;
+        let position = point(origin.x - px(6.0), origin.y - px(6.0));
+        Some((session.input.clone(), position, session.ranges.len()))
     }
 
-    pub fn apply_completion(
-        &mut self,
-        trigger_col: usize,
-        insert_text: &str,
-        cx: &mut Context<Self>,
-    ) {
-        if self.read_only {
+    /// Select every range of tabstop `index`. Reaching the final tabstop ends the
+    /// snippet session.
+    fn select_tabstop(&mut self, index: usize) {
+        let Some(session) = self.active_snippet.as_mut() else {
             return;
+        };
+        let Some(ranges) = session.tabstops.get(index).cloned() else {
+            return;
+        };
+        session.active = index;
+        if session.is_last(index) {
+            self.active_snippet = None;
         }
 
-        let delete_count = self.cursor.col.saturating_sub(trigger_col);
-        if delete_count > 0 {
-            let start_pos = Position::new(self.cursor.line, trigger_col);
-            let end_pos = self.cursor;
-            self.delete_selection_internal(Selection::new(start_pos, end_pos), cx);
+        let mut selections: Vec<Selection> = ranges
+            .iter()
+            .map(|range| {
+                Selection::new(
+                    self.byte_offset_to_pos(range.start),
+                    self.byte_offset_to_pos(range.end),
+                )
+            })
+            .collect();
+        if selections.is_empty() {
+            return;
         }
+        let primary = selections.remove(0);
+        self.column_select = None;
+        self.cursor = primary.cursor;
+        self.selection = (!primary.is_empty()).then_some(primary);
+        self.extra_selections = selections;
+    }
 
-        self.insert_text_at_cursor(insert_text, cx);
+    /// Move to the next (`forward`) or previous tabstop. Returns `false` when no
+    /// snippet is active around the cursor, ending any stale session.
+    fn jump_tabstop(&mut self, forward: bool, cx: &mut Context<Self>) -> bool {
+        let offset = self.pos_to_byte_offset(self.cursor);
+        let Some(session) = self.active_snippet.as_ref() else {
+            return false;
+        };
+        if !session.contains(offset) {
+            self.active_snippet = None;
+            return false;
+        }
+        let target = if forward {
+            session.active + 1
+        } else if session.active > 0 {
+            session.active - 1
+        } else {
+            return true;
+        };
+        self.select_tabstop(target);
         self.ensure_cursor_visible(cx);
         cx.notify();
+        true
     }
 
     fn line_text(&self, line: usize) -> String {
@@ -1332,19 +4071,113 @@ impl EditorState {
     }
 
     fn line_len(&self, line: usize) -> usize {
-        self.line_text(line).len()
+        if line >= self.rope.len_lines() {
+            return 0;
+        }
+        line_content_len(self.rope.line(line))
+    }
+
+    /// For a line longer than [`LONG_LINE_BYTES`], the part of it to shape while
+    /// `visible` columns are on screen: its starting column and text. `None` for lines
+    /// shaped whole.
+    fn long_line_window(&self, line: usize, visible: Range<usize>) -> Option<(usize, String)> {
+        let len = self.line_len(line);
+        if len <= LONG_LINE_BYTES {
+            return None;
+        }
+        let slice = self.rope.line(line);
+        let start =
+            (visible.start / LONG_LINE_WINDOW_STEP).saturating_sub(1) * LONG_LINE_WINDOW_STEP;
+        let end = (visible.end / LONG_LINE_WINDOW_STEP + 2) * LONG_LINE_WINDOW_STEP;
+        let start_char = slice.byte_to_char(start.min(len));
+        let end_char = slice.byte_to_char(end.min(len));
+        Some((
+            slice.char_to_byte(start_char),
+            slice.slice(start_char..end_char).to_string(),
+        ))
     }
 
-    fn rope_insert(&mut self, byte_offset: usize, text: &str) {
-        let char_offset = self.rope.byte_to_char(byte_offset.min(self.rope.len_bytes()));
+    fn rope_insert(&mut self, byte_offset: usize, text: &str, cx: &mut Context<Self>) {
+        let byte_offset = byte_offset.min(self.rope.len_bytes());
+        let char_offset = self.rope.byte_to_char(byte_offset);
         self.rope.insert(char_offset, text);
+        if let Some(session) = self.active_snippet.as_mut() {
+            session.on_insert(byte_offset, text.len());
+        }
+        for overlay in &mut self.anchored_overlays {
+            if byte_offset < overlay.offset {
+                overlay.offset += text.len();
+            }
+        }
+        if !self.semantic_tokens.is_empty() {
+            self.semantic_tokens.retain_mut(|span| {
+                if span.range.start >= byte_offset {
+                    span.range.start += text.len();
+                    span.range.end += text.len();
+                }
+                span.range.end <= byte_offset || span.range.start > byte_offset
+            });
+        }
+        if let Some(scope) = self.search_scope.as_mut() {
+            if byte_offset < scope.start {
+                scope.start += text.len();
+            }
+            if byte_offset <= scope.end {
+                scope.end += text.len();
+            }
+        }
+        cx.emit(BufferEvent::Edited {
+            range: byte_offset..byte_offset,
+            old_text: String::new(),
+            new_text: text.to_string(),
+        });
     }
 
-    fn rope_remove(&mut self, byte_start: usize, byte_end: usize) {
+    fn rope_remove(&mut self, byte_start: usize, byte_end: usize, cx: &mut Context<Self>) {
         let len = self.rope.len_bytes();
-        let char_start = self.rope.byte_to_char(byte_start.min(len));
-        let char_end = self.rope.byte_to_char(byte_end.min(len));
+        let (byte_start, byte_end) = (byte_start.min(len), byte_end.min(len));
+        let char_start = self.rope.byte_to_char(byte_start);
+        let char_end = self.rope.byte_to_char(byte_end);
+        let old_text: String = self.rope.byte_slice(byte_start..byte_end).into();
         self.rope.remove(char_start..char_end);
+        if let Some(session) = self.active_snippet.as_mut() {
+            session.on_remove(byte_start, byte_end);
+        }
+        for overlay in &mut self.anchored_overlays {
+            if overlay.offset > byte_start {
+                overlay.offset = overlay
+                    .offset
+                    .saturating_sub(byte_end - byte_start)
+                    .max(byte_start);
+            }
+        }
+        if !self.semantic_tokens.is_empty() {
+            let removed = byte_end - byte_start;
+            self.semantic_tokens.retain_mut(|span| {
+                if span.range.start >= byte_end {
+                    span.range.start -= removed;
+                    span.range.end -= removed;
+                    true
+                } else {
+                    span.range.end <= byte_start
+                }
+            });
+        }
+        if let Some(scope) = self.search_scope.as_mut() {
+            let shift = |offset: usize| {
+                if offset <= byte_start {
+                    offset
+                } else {
+                    offset.saturating_sub(byte_end - byte_start).max(byte_start)
+                }
+            };
+            *scope = shift(scope.start)..shift(scope.end);
+        }
+        cx.emit(BufferEvent::Edited {
+            range: byte_start..byte_end,
+            old_text,
+            new_text: String::new(),
+        });
     }
 
     fn total_lines(&self) -> usize {
@@ -1352,15 +4185,27 @@ impl EditorState {
     }
 
     pub fn set_content(&mut self, content: &str, cx: &mut Context<Self>) {
+        self.active_snippet = None;
+        self.marked_range = None;
+        self.anchored_overlays.clear();
+        self.semantic_tokens.clear();
+        self.line_ending = LineEnding::detect(content);
+        let content = normalize_line_endings(content);
+        let old_text = self.rope.to_string();
         self.rope = if content.is_empty() {
             Rope::from_str("\n")
         } else if content.ends_with('\n') {
-            Rope::from_str(content)
+            Rope::from_str(&content)
         } else {
             let mut s = content.to_string();
             s.push('\n');
             Rope::from_str(&s)
         };
+        cx.emit(BufferEvent::Edited {
+            range: 0..old_text.len(),
+            old_text,
+            new_text: self.rope.to_string(),
+        });
         self.cursor = Position::zero();
         self.selection = None;
         self.undo_stack.clear();
@@ -1382,13 +4227,69 @@ impl EditorState {
             self.highlight_query = lang
                 .highlight_query_source()
                 .filter(|src| !src.is_empty())
-                .and_then(|src| Query::new(&ts_lang, &src).ok());
+                .and_then(|src| Query::new(&ts_lang, &src).ok())
+                .map(Arc::new);
         } else {
             self.highlight_query = None;
         }
         self.update_syntax_tree();
+        self.resolve_settings();
+    }
+
+    /// Follow the shared [`LanguageSettings`]: apply the defaults, the overrides for the
+    /// buffer's language and the loaded file's `.editorconfig`, and apply them again
+    /// when the language, the file or the profiles change.
+    pub fn use_language_settings(&mut self, cx: &mut Context<Self>) {
+        self.language_settings = Some(cx.default_global::<LanguageSettings>().clone());
+        self.file_settings = self
+            .file_path
+            .as_deref()
+            .map(EditorSettingsOverride::from_editorconfig);
+        self._settings_subscription = Some(cx.observe_global::<LanguageSettings>(|this, cx| {
+            this.language_settings = Some(cx.global::<LanguageSettings>().clone());
+            this.resolve_settings();
+            cx.notify();
+        }));
+        self.resolve_settings();
+        cx.notify();
+    }
+
+    /// Apply `settings` once. An editor following [`Self::use_language_settings`]
+    /// replaces them on its next change of language or file.
+    pub fn apply_settings(&mut self, settings: EditorSettings, cx: &mut Context<Self>) {
+        self.set_settings(settings);
+        cx.notify();
+    }
+
+    /// The settings last applied, e.g. to check `format_on_save` before saving.
+    pub fn settings(&self) -> &EditorSettings {
+        &self.settings
+    }
+
+    pub fn tab_size(&self) -> usize {
+        self.tab_size
+    }
+
+    fn resolve_settings(&mut self) {
+        if let Some(language_settings) = &self.language_settings {
+            let settings = language_settings.resolve(self.language, self.file_settings.as_ref());
+            self.set_settings(settings);
+        }
     }
 
+    fn set_settings(&mut self, settings: EditorSettings) {
+        self.tab_size = settings.tab_size.max(1);
+        self.hard_tabs = settings.hard_tabs;
+        self.rulers = settings.rulers.clone();
+        self.auto_closing = settings.auto_closing;
+        self.settings = settings;
+        self.invalidate_all_caches();
+    }
+
+    /// While `check` returns true the editor leaves keys to the overlay. The editor
+    /// keeps `check` for its lifetime, so build it with
+    /// [`callbacks::weak_reader`](crate::callbacks::weak_reader) rather than
+    /// capturing the overlay's entity.
     pub fn set_overlay_active_check(&mut self, check: impl Fn(&App) -> bool + 'static) {
         self.overlay_active_check = Some(Box::new(check));
     }
@@ -1401,6 +4302,15 @@ impl EditorState {
     }
 
     pub fn load_file(&mut self, path: impl Into<PathBuf>, cx: &mut Context<Self>) {
+        self.load_file_with_encoding(path, None, cx);
+    }
+
+    fn load_file_with_encoding(
+        &mut self,
+        path: impl Into<PathBuf>,
+        encoding: Option<TextEncoding>,
+        cx: &mut Context<Self>,
+    ) {
         let path = path.into();
         let lang = Language::from_path(&path);
         self.language = lang;
@@ -1409,53 +4319,94 @@ impl EditorState {
             self.highlight_query = lang
                 .highlight_query_source()
                 .filter(|src| !src.is_empty())
-                .and_then(|src| Query::new(&ts_lang, &src).ok());
+                .and_then(|src| Query::new(&ts_lang, &src).ok())
+                .map(Arc::new);
         } else {
             self.highlight_query = None;
         }
+        if self.language_settings.is_some() {
+            self.file_settings = Some(EditorSettingsOverride::from_editorconfig(&path));
+            self.resolve_settings();
+        }
 
-        match std::fs::File::open(&path) {
-            Ok(file) => {
-                let reader = std::io::BufReader::new(file);
-                match Rope::from_reader(reader) {
-                    Ok(rope) => {
-                        self.file_path = Some(path);
-                        self.rope = rope;
-                        self.cursor = Position::zero();
-                        self.selection = None;
-                        self.undo_stack.clear();
-                        self.redo_stack.clear();
-                        self.is_modified = false;
-                        self.invalidate_all_caches();
-                        if self.rope.len_bytes() > 50_000 {
-                            self.parse_async(cx);
-                        } else {
-                            self.update_syntax_tree();
-                        }
-                        cx.notify();
-                    }
-                    Err(_) => {
-                        self.file_path = Some(path);
-                        self.set_content("", cx);
-                        self.is_modified = false;
-                    }
+        match std::fs::read(&path) {
+            Ok(bytes) => {
+                let encoding = encoding.unwrap_or_else(|| TextEncoding::detect(&bytes));
+                let text = match encoding {
+                    TextEncoding::Utf8 => String::from_utf8(bytes)
+                        .unwrap_or_else(|err| String::from_utf8_lossy(err.as_bytes()).into_owned()),
+                    _ => encoding.decode(&bytes),
+                };
+                self.encoding = encoding;
+                self.line_ending = LineEnding::detect(&text);
+                self.file_path = Some(path);
+                self.anchored_overlays.clear();
+                self.semantic_tokens.clear();
+                let old_text = self.rope.to_string();
+                self.rope = Rope::from_str(&normalize_line_endings(&text));
+                cx.emit(BufferEvent::Edited {
+                    range: 0..old_text.len(),
+                    old_text,
+                    new_text: self.rope.to_string(),
+                });
+                self.cursor = Position::zero();
+                self.selection = None;
+                self.undo_stack.clear();
+                self.redo_stack.clear();
+                self.is_modified = false;
+                self.invalidate_all_caches();
+                if self.rope.len_bytes() > 50_000 {
+                    self.parse_async(cx);
+                } else {
+                    self.update_syntax_tree();
                 }
+                cx.notify();
             }
             Err(_) => {
                 self.file_path = Some(path);
+                self.encoding = TextEncoding::default();
                 self.set_content("", cx);
                 self.is_modified = false;
             }
         }
     }
 
+    /// Write the buffer to `path` in the buffer's encoding and line ending. Returns
+    /// false, leaving any existing file untouched, if the text can't be represented in
+    /// the encoding.
     pub fn save_to_file(&mut self, path: impl Into<PathBuf>, cx: &mut Context<Self>) -> bool {
         let path = path.into();
+        let encoding = self.encoding;
+        let encoded = if encoding == TextEncoding::Utf8 && self.line_ending == LineEnding::Lf {
+            None
+        } else {
+            let mut bytes = encoding.bom().to_vec();
+            let encoded = self
+                .rope
+                .chunks()
+                .try_for_each(|chunk| match self.line_ending {
+                    LineEnding::Lf => encoding.encode_into(chunk, &mut bytes),
+                    LineEnding::CrLf => {
+                        encoding.encode_into(&chunk.replace('\n', "\r\n"), &mut bytes)
+                    }
+                });
+            if let Err(err) = encoded {
+                tracing::warn!(%err, %encoding, "cannot save in this encoding");
+                return false;
+            }
+            Some(bytes)
+        };
+
         match std::fs::File::create(&path) {
             Ok(file) => {
                 let mut writer = std::io::BufWriter::new(file);
-                match self.rope.write_to(&mut writer) {
+                let written = match &encoded {
+                    Some(bytes) => std::io::Write::write_all(&mut writer, bytes),
+                    None => self.rope.write_to(&mut writer),
+                };
+                match written.and_then(|()| std::io::Write::flush(&mut writer)) {
                     Ok(()) => {
+                        cx.emit(BufferEvent::Saved { path: path.clone() });
                         self.file_path = Some(path);
                         self.is_modified = false;
                         cx.notify();
@@ -1477,18 +4428,8 @@ impl EditorState {
     }
 
     fn update_syntax_tree(&mut self) {
-        let rope = &self.rope;
-        self.syntax_tree = self.parser.parse_with_options(
-            &mut |byte_idx, _pos| -> &[u8] {
-                if byte_idx >= rope.len_bytes() {
-                    return &[];
-                }
-                let (chunk, start, _, _) = rope.chunk_at_byte(byte_idx);
-                &chunk.as_bytes()[byte_idx - start..]
-            },
-            None,
-            None,
-        );
+        let _span = tracing::debug_span!("editor.parse", bytes = self.rope.len_bytes()).entered();
+        self.syntax_tree = parse_rope(&mut self.parser, &self.rope, None);
     }
 
     fn byte_to_ts_point(&self, byte_offset: usize) -> TSPoint {
@@ -1521,15 +4462,27 @@ impl EditorState {
     }
 
     fn parse_async(&mut self, cx: &mut Context<Self>) {
-        let content = self.rope.to_string();
+        let rope = self.rope.clone();
         let lang = self.language;
         self.syntax_tree = None;
         let (tx, rx) = smol::channel::bounded(1);
         std::thread::spawn(move || {
+            let _span = tracing::debug_span!(
+                "editor.parse",
+                language = ?lang,
+                bytes = rope.len_bytes(),
+                background = true
+            )
+            .entered();
             let mut parser = Parser::new();
             if let Some(ts_lang) = lang.tree_sitter_language() {
-                let _ = parser.set_language(&ts_lang);
-                let tree = parser.parse(&content, None);
+                if let Err(err) = parser.set_language(&ts_lang) {
+                    tracing::warn!(error = %err, "failed to load grammar");
+                }
+                let tree = parse_rope(&mut parser, &rope, None);
+                if tree.is_none() {
+                    tracing::warn!("parse produced no syntax tree");
+                }
                 let _ = tx.send_blocking(tree);
             }
         });
@@ -1549,18 +4502,15 @@ impl EditorState {
     }
 
     fn schedule_reparse(&mut self, cx: &mut Context<Self>) {
-        let entity = cx.entity().clone();
-        self.reparse_task = Some(cx.spawn(async move |_, cx| {
+        self.reparse_task = Some(cx.spawn(async move |this, cx| {
             Timer::after(Duration::from_millis(50)).await;
-            let _ = cx.update(|cx| {
-                entity.update(cx, |state, cx| {
-                    state.update_syntax_tree_incremental_now();
-                    state.compute_fold_ranges();
-                    // Only invalidate highlights — line layouts are still valid
-                    // since text content hasn't changed (only syntax tree updated).
-                    state.invalidate_after_edit();
-                    cx.notify();
-                });
+            let _ = this.update(cx, |state, cx| {
+                state.update_syntax_tree_incremental_now();
+                state.compute_fold_ranges();
+                // Only invalidate highlights — line layouts are still valid
+                // since text content hasn't changed (only syntax tree updated).
+                state.invalidate_after_edit();
+                cx.notify();
             });
         }));
     }
@@ -1569,21 +4519,12 @@ impl EditorState {
         if self.syntax_tree.is_none() {
             return;
         }
-        let rope = &self.rope;
-        self.syntax_tree = self.parser.parse_with_options(
-            &mut |byte_idx, _pos| -> &[u8] {
-                if byte_idx >= rope.len_bytes() {
-                    return &[];
-                }
-                let (chunk, start, _, _) = rope.chunk_at_byte(byte_idx);
-                &chunk.as_bytes()[byte_idx - start..]
-            },
-            self.syntax_tree.as_ref(),
-            None,
-        );
+        let _span = tracing::trace_span!("editor.reparse", bytes = self.rope.len_bytes()).entered();
+        self.syntax_tree = parse_rope(&mut self.parser, &self.rope, self.syntax_tree.as_ref());
     }
 
-    fn pos_to_byte_offset(&self, pos: Position) -> usize {
+    /// Byte offset of `pos`, clamped to the buffer.
+    pub fn pos_to_byte_offset(&self, pos: Position) -> usize {
         if pos.line >= self.rope.len_lines() {
             return self.rope.len_bytes();
         }
@@ -1592,7 +4533,7 @@ impl EditorState {
         line_start + min(pos.col, line_len)
     }
 
-    fn byte_offset_to_pos(&self, offset: usize) -> Position {
+    pub fn byte_offset_to_pos(&self, offset: usize) -> Position {
         let offset = min(offset, self.rope.len_bytes());
         let line = self.rope.byte_to_line(offset);
         let line_start = self.rope.line_to_byte(line);
@@ -1619,19 +4560,36 @@ impl EditorState {
     }
 
     fn insert_text_at_cursor(&mut self, text: &str, cx: &mut Context<Self>) {
-        if let Some(selection) = self.selection.take() {
+        let text = &*normalize_line_endings(text);
+        if self.has_multiple_cursors() {
+            let text = text.to_string();
+            self.edit_selections(
+                |state, sel| {
+                    let (start, end) = sel.range();
+                    Some((
+                        state.pos_to_byte_offset(start),
+                        state.pos_to_byte_offset(end),
+                        text.clone(),
+                    ))
+                },
+                cx,
+            );
+            return;
+        }
+
+        self.begin_undo_transaction();
+        if let Some(selection) = self.selection {
             self.delete_selection_internal(selection, cx);
         }
 
         let byte_offset = self.pos_to_byte_offset(self.cursor);
         let old_end_position = self.byte_to_ts_point(byte_offset);
-        self.undo_stack.push(EditOp::Insert {
+        self.push_undo(EditOp::Insert {
             byte_offset,
             text: text.to_string(),
         });
-        self.redo_stack.clear();
 
-        self.rope_insert(byte_offset, text);
+        self.rope_insert(byte_offset, text, cx);
         self.mark_modified();
 
         let new_end_byte = byte_offset + text.len();
@@ -1644,6 +4602,98 @@ impl EditorState {
             cx,
         );
         self.invalidate_after_edit();
+        self.end_undo_transaction();
+    }
+
+    /// Record an edit on the undo stack. Inside a transaction it joins the
+    /// transaction's step; otherwise a single typed or deleted character may extend
+    /// the previous step.
+    fn push_undo(&mut self, op: EditOp) {
+        let now = std::time::Instant::now();
+        self.redo_stack.clear();
+        if let Some(start) = self.undo_transaction_start {
+            if self.undo_stack.len() > start {
+                if let Some(last) = self.undo_stack.last_mut() {
+                    last.ops.push(op);
+                    last.last_edit = now;
+                    last.open = false;
+                }
+                return;
+            }
+        }
+        let coalesces = self
+            .undo_stack
+            .last()
+            .is_some_and(|last| last.coalesces_with(&op, now));
+        if coalesces {
+            if let Some(last) = self.undo_stack.last_mut() {
+                last.ops.push(op);
+                last.last_edit = now;
+            }
+            // Later edits in this transaction join the step the first one extended.
+            if let Some(start) = &mut self.undo_transaction_start {
+                *start = start.saturating_sub(1);
+            }
+        } else {
+            let selections_before = self
+                .undo_selections_before
+                .take()
+                .unwrap_or_else(|| self.selections());
+            self.undo_stack
+                .push(UndoTransaction::new(op, selections_before, now));
+        }
+    }
+
+    fn begin_undo_transaction(&mut self) {
+        if self.undo_transaction_depth == 0 {
+            self.undo_transaction_start = Some(self.undo_stack.len());
+            self.undo_selections_before = Some(self.selections());
+        }
+        self.undo_transaction_depth += 1;
+    }
+
+    /// Close a transaction. Closing the outermost one records the selections after it
+    /// on the step it created or extended, so call it once the cursor is in place.
+    fn end_undo_transaction(&mut self) {
+        self.undo_transaction_depth = self.undo_transaction_depth.saturating_sub(1);
+        if self.undo_transaction_depth > 0 {
+            return;
+        }
+        let start = self.undo_transaction_start.take();
+        self.undo_selections_before = None;
+        if start.is_some_and(|start| self.undo_stack.len() > start) {
+            let selections = self.selections();
+            if let Some(last) = self.undo_stack.last_mut() {
+                last.selections_after = selections;
+            }
+        }
+    }
+
+    /// Restore a recorded primary selection and extra cursors.
+    fn restore_selections(&mut self, selections: &[Selection]) {
+        let Some((primary, extra)) = selections.split_first() else {
+            return;
+        };
+        self.cursor = primary.cursor;
+        self.selection = (!primary.is_empty()).then_some(*primary);
+        self.extra_selections = extra.to_vec();
+        self.clamp_cursor();
+    }
+
+    /// Run `f` so that every edit it makes is undone and redone as a single step.
+    pub fn transact<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.begin_undo_transaction();
+        let result = f(self);
+        self.end_undo_transaction();
+        result
+    }
+
+    /// Stop the current typing run from absorbing further edits, so the next edit
+    /// starts a new undo step.
+    pub fn break_undo_group(&mut self) {
+        if let Some(last) = self.undo_stack.last_mut() {
+            last.open = false;
+        }
     }
 
     fn delete_selection_internal(&mut self, selection: Selection, cx: &mut Context<Self>) {
@@ -1658,16 +4708,18 @@ impl EditorState {
 
         let old_end_position = self.byte_to_ts_point(end_offset);
         let deleted: String = self.rope.byte_slice(start_offset..end_offset).into();
-        self.undo_stack.push(EditOp::Delete {
+        self.begin_undo_transaction();
+        self.push_undo(EditOp::Delete {
             byte_offset: start_offset,
             text: deleted,
         });
-        self.redo_stack.clear();
 
-        self.rope_remove(start_offset, end_offset);
+        self.rope_remove(start_offset, end_offset, cx);
         self.mark_modified();
+        self.selection = None;
         self.cursor = start;
         self.clamp_cursor();
+        self.end_undo_transaction();
         self.update_syntax_tree_incremental(
             start_offset,
             end_offset,
@@ -1745,70 +4797,343 @@ impl EditorState {
         utf16_offset
     }
 
-    fn offset_from_utf16(&self, utf16_offset: usize) -> usize {
-        let mut utf16_count = 0;
-        let mut byte_offset = 0;
-        for ch_idx in 0..self.rope.len_chars() {
-            if utf16_count >= utf16_offset {
-                break;
+    fn offset_from_utf16(&self, utf16_offset: usize) -> usize {
+        let mut utf16_count = 0;
+        let mut byte_offset = 0;
+        for ch_idx in 0..self.rope.len_chars() {
+            if utf16_count >= utf16_offset {
+                break;
+            }
+            let ch = self.rope.char(ch_idx);
+            utf16_count += ch.len_utf16();
+            byte_offset += ch.len_utf8();
+        }
+        byte_offset
+    }
+
+    fn range_to_utf16(&self, range: &Range<usize>) -> Range<usize> {
+        self.offset_to_utf16(range.start)..self.offset_to_utf16(range.end)
+    }
+
+    fn range_from_utf16(&self, range: &Range<usize>) -> Range<usize> {
+        self.offset_from_utf16(range.start)..self.offset_from_utf16(range.end)
+    }
+
+    pub fn has_multiple_cursors(&self) -> bool {
+        !self.extra_selections.is_empty()
+    }
+
+    /// All selections, primary first. Empty selections represent bare cursors.
+    pub fn selections(&self) -> Vec<Selection> {
+        let primary = self
+            .selection
+            .unwrap_or_else(|| Selection::new(self.cursor, self.cursor));
+        std::iter::once(primary)
+            .chain(self.extra_selections.iter().copied())
+            .collect()
+    }
+
+    pub fn clear_extra_cursors(&mut self) {
+        self.extra_selections.clear();
+        self.column_select = None;
+    }
+
+    fn byte_col_to_char(&self, line: usize, byte_col: usize) -> usize {
+        let text = self.line_text(line);
+        let byte_col = byte_col.min(text.len());
+        text.get(..byte_col)
+            .map(|prefix| prefix.chars().count())
+            .unwrap_or(byte_col)
+    }
+
+    fn char_col_to_byte(&self, line: usize, char_col: usize) -> usize {
+        let text = self.line_text(line);
+        text.char_indices()
+            .nth(char_col)
+            .map(|(byte, _)| byte)
+            .unwrap_or(text.len())
+    }
+
+    fn approx_char_width(&self) -> Pixels {
+        self.line_layouts
+            .iter()
+            .filter(|(_, layout)| layout.inlays.is_empty() && layout.window.is_none())
+            .find_map(|(line, layout)| {
+                let chars = self.line_text(*line).chars().count();
+                (chars > 0 && layout.width > px(0.0)).then(|| layout.width / chars as f32)
+            })
+            .unwrap_or(px(self.font_size * 0.6))
+    }
+
+    /// Select a rectangular block. `anchor` and `head` use character columns, so the
+    /// block stays aligned on lines containing multi-byte characters.
+    pub fn set_column_selection(
+        &mut self,
+        anchor: Position,
+        head: Position,
+        cx: &mut Context<Self>,
+    ) {
+        let max_line = self.total_lines().saturating_sub(1);
+        let anchor = Position::new(anchor.line.min(max_line), anchor.col);
+        let head = Position::new(head.line.min(max_line), head.col);
+        self.column_select = Some((anchor, head));
+
+        let (first, last) = if anchor.line <= head.line {
+            (anchor.line, head.line)
+        } else {
+            (head.line, anchor.line)
+        };
+        let mut primary = None;
+        let mut extras = Vec::new();
+        for line in first..=last {
+            let sel = Selection::new(
+                Position::new(line, self.char_col_to_byte(line, anchor.col)),
+                Position::new(line, self.char_col_to_byte(line, head.col)),
+            );
+            if line == head.line {
+                primary = Some(sel);
+            } else {
+                extras.push(sel);
+            }
+        }
+        if let Some(primary) = primary {
+            self.cursor = primary.cursor;
+            self.selection = (!primary.is_empty()).then_some(primary);
+        }
+        self.extra_selections = extras;
+        self.ensure_cursor_visible(cx);
+        cx.notify();
+    }
+
+    fn column_select_head(&self) -> (Position, Position) {
+        self.column_select.unwrap_or_else(|| {
+            let pos = Position::new(
+                self.cursor.line,
+                self.byte_col_to_char(self.cursor.line, self.cursor.col),
+            );
+            (pos, pos)
+        })
+    }
+
+    pub fn select_column_up(&mut self, _: &SelectColumnUp, _: &mut Window, cx: &mut Context<Self>) {
+        let (anchor, head) = self.column_select_head();
+        let head = Position::new(head.line.saturating_sub(1), head.col);
+        self.set_column_selection(anchor, head, cx);
+    }
+
+    pub fn select_column_down(
+        &mut self,
+        _: &SelectColumnDown,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let (anchor, head) = self.column_select_head();
+        let head = Position::new(head.line + 1, head.col);
+        self.set_column_selection(anchor, head, cx);
+    }
+
+    pub fn select_column_left(
+        &mut self,
+        _: &SelectColumnLeft,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let (anchor, head) = self.column_select_head();
+        let head = Position::new(head.line, head.col.saturating_sub(1));
+        self.set_column_selection(anchor, head, cx);
+    }
+
+    pub fn select_column_right(
+        &mut self,
+        _: &SelectColumnRight,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let (anchor, head) = self.column_select_head();
+        let (first, last) = (anchor.line.min(head.line), anchor.line.max(head.line));
+        let widest = (first..=last)
+            .map(|line| self.line_text(line).chars().count())
+            .max()
+            .unwrap_or(0);
+        let head = Position::new(head.line, (head.col + 1).min(widest));
+        self.set_column_selection(anchor, head, cx);
+    }
+
+    /// Apply one edit per selection. `edit` returns the byte range to replace and the
+    /// replacement text, or `None` to leave that selection untouched. Edits are applied
+    /// back to front so earlier offsets stay valid; every selection collapses to a cursor
+    /// after its replacement.
+    fn edit_selections(
+        &mut self,
+        edit: impl Fn(&Self, Selection) -> Option<(usize, usize, String)>,
+        cx: &mut Context<Self>,
+    ) {
+        let selections = self.selections();
+        let mut edits: Vec<(usize, usize, usize, String)> = selections
+            .iter()
+            .enumerate()
+            .map(|(idx, sel)| match edit(self, *sel) {
+                Some((start, end, text)) => (idx, start, end, text),
+                None => {
+                    let offset = self.pos_to_byte_offset(sel.cursor);
+                    (idx, offset, offset, String::new())
+                }
+            })
+            .collect();
+        edits.sort_by_key(|(_, start, _, _)| *start);
+
+        // Drop edits overlapping an earlier one.
+        let mut last_end = 0;
+        edits.retain(|(_, start, end, _)| {
+            if *start < last_end {
+                return false;
+            }
+            last_end = *end;
+            true
+        });
+
+        let mut new_offsets = vec![None; selections.len()];
+        let mut delta: isize = 0;
+        for (idx, start, end, text) in &edits {
+            let new_start = (*start as isize + delta) as usize;
+            new_offsets[*idx] = Some(new_start + text.len());
+            delta += text.len() as isize - (*end - *start) as isize;
+        }
+
+        let mut changed = false;
+        self.begin_undo_transaction();
+        for (_, start, end, text) in edits.iter().rev() {
+            if start < end {
+                let deleted: String = self.rope.byte_slice(*start..*end).into();
+                self.push_undo(EditOp::Delete {
+                    byte_offset: *start,
+                    text: deleted,
+                });
+                self.rope_remove(*start, *end, cx);
+                changed = true;
+            }
+            if !text.is_empty() {
+                self.push_undo(EditOp::Insert {
+                    byte_offset: *start,
+                    text: text.clone(),
+                });
+                self.rope_insert(*start, text, cx);
+                changed = true;
             }
-            let ch = self.rope.char(ch_idx);
-            utf16_count += ch.len_utf16();
-            byte_offset += ch.len_utf8();
         }
-        byte_offset
-    }
+        if !changed {
+            self.end_undo_transaction();
+            return;
+        }
 
-    fn range_to_utf16(&self, range: &Range<usize>) -> Range<usize> {
-        self.offset_to_utf16(range.start)..self.offset_to_utf16(range.end)
+        let mut cursors: Vec<Position> = new_offsets
+            .into_iter()
+            .flatten()
+            .map(|offset| self.byte_offset_to_pos(offset))
+            .collect();
+        cursors.dedup();
+        self.selection = None;
+        self.column_select = None;
+        if !cursors.is_empty() {
+            self.cursor = cursors.remove(0);
+        }
+        self.extra_selections = cursors
+            .into_iter()
+            .filter(|pos| *pos != self.cursor)
+            .map(|pos| Selection::new(pos, pos))
+            .collect();
+        self.clamp_cursor();
+        self.end_undo_transaction();
+        self.mark_modified();
+        self.update_syntax_tree();
+        self.invalidate_after_edit();
+        self.ensure_cursor_visible(cx);
+        cx.notify();
     }
 
-    fn range_from_utf16(&self, range: &Range<usize>) -> Range<usize> {
-        self.offset_from_utf16(range.start)..self.offset_from_utf16(range.end)
+    /// Paste with several cursors: one clipboard line per cursor when the counts
+    /// match, otherwise the full text at every cursor.
+    fn paste_distributed(&mut self, text: &str, cx: &mut Context<Self>) {
+        let lines: Vec<&str> = text.lines().collect();
+        let mut order: Vec<usize> = (0..self.selections().len()).collect();
+        let selections = self.selections();
+        order.sort_by_key(|idx| selections[*idx].range().0);
+        if lines.len() != order.len() {
+            self.insert_text_at_cursor(text, cx);
+            return;
+        }
+        let mut per_selection = vec![String::new(); order.len()];
+        for (rank, idx) in order.into_iter().enumerate() {
+            per_selection[idx] = lines[rank].to_string();
+        }
+        self.edit_selections(
+            |state, sel| {
+                let idx = state
+                    .selections()
+                    .iter()
+                    .position(|candidate| *candidate == sel)?;
+                let (start, end) = sel.range();
+                Some((
+                    state.pos_to_byte_offset(start),
+                    state.pos_to_byte_offset(end),
+                    per_selection[idx].clone(),
+                ))
+            },
+            cx,
+        );
     }
 
     pub fn undo(&mut self, _: &Undo, _: &mut Window, cx: &mut Context<Self>) {
-        if let Some(op) = self.undo_stack.pop() {
-            match &op {
-                EditOp::Insert { byte_offset, text } => {
-                    let end = byte_offset + text.len();
-                    self.rope_remove(*byte_offset, end);
-                    self.cursor = self.byte_offset_to_pos(*byte_offset);
-                    self.redo_stack.push(op);
-                }
-                EditOp::Delete { byte_offset, text } => {
-                    self.rope_insert(*byte_offset, text);
-                    self.cursor = self.byte_offset_to_pos(*byte_offset + text.len());
-                    self.redo_stack.push(op);
+        if let Some(mut transaction) = self.undo_stack.pop() {
+            self.clear_extra_cursors();
+            for op in transaction.ops.iter().rev() {
+                match op {
+                    EditOp::Insert { byte_offset, text } => {
+                        let end = byte_offset + text.len();
+                        self.rope_remove(*byte_offset, end, cx);
+                        self.cursor = self.byte_offset_to_pos(*byte_offset);
+                    }
+                    EditOp::Delete { byte_offset, text } => {
+                        self.rope_insert(*byte_offset, text, cx);
+                        self.cursor = self.byte_offset_to_pos(*byte_offset + text.len());
+                    }
                 }
             }
             self.selection = None;
+            self.restore_selections(&transaction.selections_before);
+            transaction.open = false;
+            self.redo_stack.push(transaction);
             self.mark_modified();
             self.update_syntax_tree();
             self.invalidate_after_edit();
+            self.ensure_cursor_visible(cx);
             cx.notify();
         }
     }
 
     pub fn redo(&mut self, _: &Redo, _: &mut Window, cx: &mut Context<Self>) {
-        if let Some(op) = self.redo_stack.pop() {
-            match &op {
-                EditOp::Insert { byte_offset, text } => {
-                    self.rope_insert(*byte_offset, text);
-                    self.cursor = self.byte_offset_to_pos(*byte_offset + text.len());
-                    self.undo_stack.push(op);
-                }
-                EditOp::Delete { byte_offset, text } => {
-                    let end = byte_offset + text.len();
-                    self.rope_remove(*byte_offset, end);
-                    self.cursor = self.byte_offset_to_pos(*byte_offset);
-                    self.undo_stack.push(op);
+        if let Some(transaction) = self.redo_stack.pop() {
+            self.clear_extra_cursors();
+            for op in &transaction.ops {
+                match op {
+                    EditOp::Insert { byte_offset, text } => {
+                        self.rope_insert(*byte_offset, text, cx);
+                        self.cursor = self.byte_offset_to_pos(*byte_offset + text.len());
+                    }
+                    EditOp::Delete { byte_offset, text } => {
+                        let end = byte_offset + text.len();
+                        self.rope_remove(*byte_offset, end, cx);
+                        self.cursor = self.byte_offset_to_pos(*byte_offset);
+                    }
                 }
             }
             self.selection = None;
+            self.restore_selections(&transaction.selections_after);
+            self.undo_stack.push(transaction);
             self.mark_modified();
             self.update_syntax_tree();
             self.invalidate_after_edit();
+            self.ensure_cursor_visible(cx);
             cx.notify();
         }
     }
@@ -1818,6 +5143,7 @@ impl EditorState {
             cx.propagate();
             return;
         }
+        self.clear_extra_cursors();
         if self.cursor.line > 0 {
             self.cursor.line -= 1;
             self.clamp_cursor();
@@ -1831,6 +5157,7 @@ impl EditorState {
             cx.propagate();
             return;
         }
+        self.clear_extra_cursors();
         if self.cursor.line < self.total_lines() - 1 {
             self.cursor.line += 1;
             self.clamp_cursor();
@@ -1840,6 +5167,7 @@ impl EditorState {
     }
 
     pub fn move_left(&mut self, _: &MoveLeft, _: &mut Window, cx: &mut Context<Self>) {
+        self.clear_extra_cursors();
         if self.cursor.col > 0 {
             self.cursor.col -= 1;
         } else if self.cursor.line > 0 {
@@ -1851,6 +5179,7 @@ impl EditorState {
     }
 
     pub fn move_right(&mut self, _: &MoveRight, _: &mut Window, cx: &mut Context<Self>) {
+        self.clear_extra_cursors();
         let line_len = self.line_len(self.cursor.line);
         if self.cursor.col < line_len {
             self.cursor.col += 1;
@@ -1863,12 +5192,14 @@ impl EditorState {
     }
 
     pub fn move_word_left(&mut self, _: &MoveWordLeft, _: &mut Window, cx: &mut Context<Self>) {
+        self.clear_extra_cursors();
         self.cursor = self.find_word_boundary_left(self.cursor);
         self.selection = None;
         cx.notify();
     }
 
     pub fn move_word_right(&mut self, _: &MoveWordRight, _: &mut Window, cx: &mut Context<Self>) {
+        self.clear_extra_cursors();
         self.cursor = self.find_word_boundary_right(self.cursor);
         self.selection = None;
         cx.notify();
@@ -1880,12 +5211,14 @@ impl EditorState {
         _: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        self.clear_extra_cursors();
         self.cursor.col = 0;
         self.selection = None;
         cx.notify();
     }
 
     pub fn move_to_line_end(&mut self, _: &MoveToLineEnd, _: &mut Window, cx: &mut Context<Self>) {
+        self.clear_extra_cursors();
         self.cursor.col = self.line_len(self.cursor.line);
         self.selection = None;
         cx.notify();
@@ -1897,19 +5230,26 @@ impl EditorState {
         _: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        self.clear_extra_cursors();
+        let from = self.cursor;
         self.cursor = Position::zero();
         self.selection = None;
+        self.record_jump(from);
         cx.notify();
     }
 
     pub fn move_to_doc_end(&mut self, _: &MoveToDocEnd, _: &mut Window, cx: &mut Context<Self>) {
+        self.clear_extra_cursors();
+        let from = self.cursor;
         let last = self.total_lines() - 1;
         self.cursor = Position::new(last, self.line_len(last));
         self.selection = None;
+        self.record_jump(from);
         cx.notify();
     }
 
     pub fn page_up(&mut self, _: &PageUp, _: &mut Window, cx: &mut Context<Self>) {
+        self.clear_extra_cursors();
         let page_size = 30;
         self.cursor.line = self.cursor.line.saturating_sub(page_size);
         self.clamp_cursor();
@@ -1918,6 +5258,7 @@ impl EditorState {
     }
 
     pub fn page_down(&mut self, _: &PageDown, _: &mut Window, cx: &mut Context<Self>) {
+        self.clear_extra_cursors();
         let page_size = 30;
         self.cursor.line = min(self.cursor.line + page_size, self.total_lines() - 1);
         self.clamp_cursor();
@@ -1932,6 +5273,7 @@ impl EditorState {
     }
 
     pub fn select_up(&mut self, _: &SelectUp, _: &mut Window, cx: &mut Context<Self>) {
+        self.clear_extra_cursors();
         self.start_selection_if_needed();
         if self.cursor.line > 0 {
             self.cursor.line -= 1;
@@ -1944,6 +5286,7 @@ impl EditorState {
     }
 
     pub fn select_down(&mut self, _: &SelectDown, _: &mut Window, cx: &mut Context<Self>) {
+        self.clear_extra_cursors();
         self.start_selection_if_needed();
         if self.cursor.line < self.total_lines() - 1 {
             self.cursor.line += 1;
@@ -1956,6 +5299,7 @@ impl EditorState {
     }
 
     pub fn select_left(&mut self, _: &SelectLeft, _: &mut Window, cx: &mut Context<Self>) {
+        self.clear_extra_cursors();
         self.start_selection_if_needed();
         if self.cursor.col > 0 {
             self.cursor.col -= 1;
@@ -1970,6 +5314,7 @@ impl EditorState {
     }
 
     pub fn select_right(&mut self, _: &SelectRight, _: &mut Window, cx: &mut Context<Self>) {
+        self.clear_extra_cursors();
         self.start_selection_if_needed();
         let line_len = self.line_len(self.cursor.line);
         if self.cursor.col < line_len {
@@ -1990,6 +5335,7 @@ impl EditorState {
         _: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        self.clear_extra_cursors();
         self.start_selection_if_needed();
         self.cursor.col = 0;
         if let Some(ref mut sel) = self.selection {
@@ -2004,6 +5350,7 @@ impl EditorState {
         _: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        self.clear_extra_cursors();
         self.start_selection_if_needed();
         self.cursor.col = self.line_len(self.cursor.line);
         if let Some(ref mut sel) = self.selection {
@@ -2013,6 +5360,7 @@ impl EditorState {
     }
 
     pub fn select_all(&mut self, _: &SelectAll, _: &mut Window, cx: &mut Context<Self>) {
+        self.clear_extra_cursors();
         let start = Position::zero();
         let last = self.total_lines() - 1;
         let end = Position::new(last, self.line_len(last));
@@ -2021,11 +5369,131 @@ impl EditorState {
         cx.notify();
     }
 
+    /// Selects the word under a bare cursor, or adds the next occurrence of the primary
+    /// selection's text as the new primary selection, wrapping at the end of the buffer.
+    pub fn select_next_occurrence(
+        &mut self,
+        _: &SelectNextOccurrence,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let primary = match self.selection {
+            Some(sel) if !sel.is_empty() => sel,
+            _ => {
+                if self.select_word_at_cursor() {
+                    cx.notify();
+                }
+                return;
+            }
+        };
+        let taken: Vec<Range<usize>> = self
+            .selections()
+            .iter()
+            .map(|sel| self.selection_byte_range(sel))
+            .collect();
+        let from = self.pos_to_byte_offset(primary.range().1);
+        let occurrences = self.occurrences_of(&primary);
+        let Some(next) = occurrences
+            .iter()
+            .find(|range| range.start >= from && !taken.contains(range))
+            .or_else(|| occurrences.iter().find(|range| !taken.contains(range)))
+        else {
+            return;
+        };
+
+        let next = Selection::new(
+            self.byte_offset_to_pos(next.start),
+            self.byte_offset_to_pos(next.end),
+        );
+        self.extra_selections.push(primary);
+        self.column_select = None;
+        self.cursor = next.cursor;
+        self.selection = Some(next);
+        self.ensure_cursor_visible(cx);
+        cx.notify();
+    }
+
+    /// Selects every occurrence of the primary selection's text, or of the word under a
+    /// bare cursor.
+    pub fn select_all_occurrences(
+        &mut self,
+        _: &SelectAllOccurrences,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.selection.map_or(true, |sel| sel.is_empty()) && !self.select_word_at_cursor() {
+            return;
+        }
+        let Some(primary) = self.selection else {
+            return;
+        };
+        let primary_range = self.selection_byte_range(&primary);
+        self.extra_selections = self
+            .occurrences_of(&primary)
+            .into_iter()
+            .filter(|range| *range != primary_range)
+            .map(|range| {
+                Selection::new(
+                    self.byte_offset_to_pos(range.start),
+                    self.byte_offset_to_pos(range.end),
+                )
+            })
+            .collect();
+        self.column_select = None;
+        cx.notify();
+    }
+
+    fn select_word_at_cursor(&mut self) -> bool {
+        let Some(range) = self.identifier_range_at(self.cursor) else {
+            return false;
+        };
+        let line = self.cursor.line;
+        let word = Selection::new(
+            Position::new(line, range.start),
+            Position::new(line, range.end),
+        );
+        self.occurrence_word = Some(self.line_text(line)[range].to_string());
+        self.clear_extra_cursors();
+        self.cursor = word.cursor;
+        self.selection = Some(word);
+        true
+    }
+
+    fn selection_byte_range(&self, selection: &Selection) -> Range<usize> {
+        let (start, end) = selection.range();
+        self.pos_to_byte_offset(start)..self.pos_to_byte_offset(end)
+    }
+
+    /// Byte ranges of every occurrence of `selection`'s text, in buffer order.
+    fn occurrences_of(&self, selection: &Selection) -> Vec<Range<usize>> {
+        let query = self.get_selection_text(selection);
+        let whole_word = self.occurrence_word.as_deref() == Some(query.as_str());
+        occurrence_ranges(&self.rope, &query, whole_word)
+    }
+
     pub fn backspace(&mut self, _: &Backspace, _: &mut Window, cx: &mut Context<Self>) {
         if self.read_only {
             return;
         }
-        if let Some(selection) = self.selection.take() {
+        if self.has_multiple_cursors() {
+            self.edit_selections(
+                |state, sel| {
+                    let (start, end) = sel.range();
+                    let end = state.pos_to_byte_offset(end);
+                    if !sel.is_empty() {
+                        return Some((state.pos_to_byte_offset(start), end, String::new()));
+                    }
+                    if start.col == 0 {
+                        return None;
+                    }
+                    let char_idx = state.rope.byte_to_char(end);
+                    Some((state.rope.char_to_byte(char_idx - 1), end, String::new()))
+                },
+                cx,
+            );
+            return;
+        }
+        if let Some(selection) = self.selection {
             self.delete_selection_internal(selection, cx);
             cx.notify();
             return;
@@ -2035,7 +5503,7 @@ impl EditorState {
             return;
         }
 
-        let delete_pair = self.is_between_auto_close_pair();
+        let delete_pair = self.auto_closing && self.is_between_auto_close_pair();
         let char_idx = self.rope.byte_to_char(offset);
         let prev_char_byte = self.rope.char_to_byte(char_idx.saturating_sub(1));
         let del_start = prev_char_byte;
@@ -2052,14 +5520,15 @@ impl EditorState {
 
         let old_end_position = self.byte_to_ts_point(del_end);
         let deleted: String = self.rope.byte_slice(del_start..del_end).into();
-        self.undo_stack.push(EditOp::Delete {
+        self.begin_undo_transaction();
+        self.push_undo(EditOp::Delete {
             byte_offset: del_start,
             text: deleted,
         });
-        self.redo_stack.clear();
-        self.rope_remove(del_start, del_end);
+        self.rope_remove(del_start, del_end, cx);
         self.mark_modified();
         self.cursor = self.byte_offset_to_pos(del_start);
+        self.end_undo_transaction();
         self.update_syntax_tree_incremental(del_start, del_end, del_start, old_end_position, cx);
         self.invalidate_after_edit();
         cx.notify();
@@ -2069,7 +5538,25 @@ impl EditorState {
         if self.read_only {
             return;
         }
-        if let Some(selection) = self.selection.take() {
+        if self.has_multiple_cursors() {
+            self.edit_selections(
+                |state, sel| {
+                    let (start, end) = sel.range();
+                    let start = state.pos_to_byte_offset(start);
+                    if !sel.is_empty() {
+                        return Some((start, state.pos_to_byte_offset(end), String::new()));
+                    }
+                    if end.col >= state.line_len(end.line) {
+                        return None;
+                    }
+                    let char_idx = state.rope.byte_to_char(start);
+                    Some((start, state.rope.char_to_byte(char_idx + 1), String::new()))
+                },
+                cx,
+            );
+            return;
+        }
+        if let Some(selection) = self.selection {
             self.delete_selection_internal(selection, cx);
             cx.notify();
             return;
@@ -2087,12 +5574,13 @@ impl EditorState {
         let del_end = min(next_char_byte, self.rope.len_bytes());
         let old_end_position = self.byte_to_ts_point(del_end);
         let deleted: String = self.rope.byte_slice(offset..del_end).into();
-        self.undo_stack.push(EditOp::Delete {
+        self.begin_undo_transaction();
+        self.push_undo(EditOp::Delete {
             byte_offset: offset,
             text: deleted,
         });
-        self.redo_stack.clear();
-        self.rope_remove(offset, del_end);
+        self.rope_remove(offset, del_end, cx);
+        self.end_undo_transaction();
         self.mark_modified();
         self.update_syntax_tree_incremental(offset, del_end, offset, old_end_position, cx);
         self.invalidate_after_edit();
@@ -2111,14 +5599,15 @@ impl EditorState {
         let end_offset = self.pos_to_byte_offset(self.cursor);
         let old_end_position = self.byte_to_ts_point(end_offset);
         let deleted: String = self.rope.byte_slice(start_offset..end_offset).into();
-        self.undo_stack.push(EditOp::Delete {
+        self.begin_undo_transaction();
+        self.push_undo(EditOp::Delete {
             byte_offset: start_offset,
             text: deleted,
         });
-        self.redo_stack.clear();
-        self.rope_remove(start_offset, end_offset);
+        self.rope_remove(start_offset, end_offset, cx);
         self.mark_modified();
         self.cursor = word_start;
+        self.end_undo_transaction();
         self.update_syntax_tree_incremental(
             start_offset,
             end_offset,
@@ -2143,13 +5632,11 @@ impl EditorState {
         let before_cursor = &line_text[..self.cursor.col.min(line_text.len())];
         let after_cursor = &line_text[self.cursor.col.min(line_text.len())..];
 
-        let base_indent = before_cursor.len() - before_cursor.trim_start().len();
+        let indent_str = &before_cursor[..before_cursor.len() - before_cursor.trim_start().len()];
+        let unit = self.indent_unit(&line_text);
         let trimmed = before_cursor.trim_end();
         let increase = matches!(trimmed.as_bytes().last(), Some(b'{' | b'(' | b'[' | b':'));
 
-        let indent_str = " ".repeat(base_indent);
-        let extra_indent = " ".repeat(self.tab_size);
-
         let after_trimmed = after_cursor.trim_start();
         let between_pair = increase
             && !after_trimmed.is_empty()
@@ -2159,21 +5646,60 @@ impl EditorState {
             );
 
         if between_pair {
-            let text = format!("\n{}{}\n{}", indent_str, extra_indent, indent_str);
+            let text = format!("\n{}{}\n{}", indent_str, unit, indent_str);
             self.insert_text_at_cursor(&text, cx);
             let target_line = self.cursor.line - 1;
-            let target_col = base_indent + self.tab_size;
+            let target_col = indent_str.len() + unit.len();
             self.cursor = Position::new(target_line, target_col);
         } else if increase {
-            let text = format!("\n{}{}", indent_str, extra_indent);
+            let text = format!("\n{}{}", indent_str, unit);
             self.insert_text_at_cursor(&text, cx);
         } else {
-            let text = format!("\n{}", indent_str);
+            let line_start = self.rope.line_to_byte(self.cursor.line);
+            let closer = (!after_trimmed.is_empty())
+                .then_some(line_start + line_text.len() - after_trimmed.len());
+            let indent = self
+                .syntax_newline_indent(line_start + trimmed.len(), closer, &unit)
+                .unwrap_or_else(|| indent_str.to_string());
+            let text = format!("\n{}", indent);
             self.insert_text_at_cursor(&text, cx);
         }
         self.ensure_cursor_visible(cx);
     }
 
+    /// Indentation for a line break at `byte` from the enclosing syntax scope. `closer`
+    /// is the offset of the first non-blank character after the cursor, if any.
+    fn syntax_newline_indent(
+        &mut self,
+        byte: usize,
+        closer: Option<usize>,
+        unit: &str,
+    ) -> Option<String> {
+        self.update_syntax_tree_incremental_now();
+        let tree = self.syntax_tree.as_ref()?;
+        match newline_indent(tree, self.language, byte)? {
+            NewlineIndent::Inside { row, close } => {
+                let row_text = self.line_text(row);
+                let base = &row_text[..row_text.len() - row_text.trim_start().len()];
+                if closer.is_some() && close == closer {
+                    Some(base.to_string())
+                } else {
+                    Some(format!("{}{}", base, unit))
+                }
+            }
+            NewlineIndent::Dedent if closer.is_none() => {
+                let line_text = self.line_text(self.cursor.line);
+                let indent = &line_text[..line_text.len() - line_text.trim_start().len()];
+                let outdented = indent.strip_suffix('\t').unwrap_or_else(|| {
+                    let spaces = indent.len() - indent.trim_end_matches(' ').len();
+                    &indent[..indent.len() - spaces.min(self.tab_size)]
+                });
+                Some(outdented.to_string())
+            }
+            NewlineIndent::Dedent => None,
+        }
+    }
+
     pub fn tab(&mut self, _: &Tab, _: &mut Window, cx: &mut Context<Self>) {
         if self.is_overlay_active(cx) {
             cx.propagate();
@@ -2182,11 +5708,269 @@ impl EditorState {
         if self.read_only {
             return;
         }
-        let spaces = " ".repeat(self.tab_size);
-        self.insert_text_at_cursor(&spaces, cx);
+        if self.jump_tabstop(true, cx) {
+            return;
+        }
+        if self
+            .selection
+            .is_some_and(|sel| sel.anchor.line != sel.cursor.line)
+        {
+            self.indent_lines(cx);
+            return;
+        }
+        let unit = self.indent_unit(&self.line_text(self.cursor.line));
+        self.insert_text_at_cursor(&unit, cx);
+    }
+
+    /// Moves to the previous snippet tabstop, or outdents like [`Outdent`] otherwise.
+    pub fn shift_tab(&mut self, _: &ShiftTab, _: &mut Window, cx: &mut Context<Self>) {
+        if self.is_overlay_active(cx) {
+            cx.propagate();
+            return;
+        }
+        if self.read_only {
+            return;
+        }
+        if !self.jump_tabstop(false, cx) {
+            self.outdent_lines(cx);
+        }
+    }
+
+    pub fn indent(&mut self, _: &Indent, _: &mut Window, cx: &mut Context<Self>) {
+        if self.is_overlay_active(cx) {
+            cx.propagate();
+            return;
+        }
+        if self.read_only {
+            return;
+        }
+        self.indent_lines(cx);
+    }
+
+    pub fn outdent(&mut self, _: &Outdent, _: &mut Window, cx: &mut Context<Self>) {
+        if self.is_overlay_active(cx) {
+            cx.propagate();
+            return;
+        }
+        if self.read_only {
+            return;
+        }
+        self.outdent_lines(cx);
+    }
+
+    /// The lines covered by the selection, or the cursor line. A selection ending at
+    /// the start of a line doesn't include that line.
+    fn selected_line_range(&self) -> (usize, usize) {
+        match self.selection {
+            Some(sel) if !sel.is_empty() => {
+                let (start, end) = sel.range();
+                let last = if end.col == 0 && end.line > start.line {
+                    end.line - 1
+                } else {
+                    end.line
+                };
+                (start.line, last)
+            }
+            _ => (self.cursor.line, self.cursor.line),
+        }
+    }
+
+    /// One level of indentation for `line_text`: a tab if the line is indented with
+    /// tabs, otherwise `tab_size` spaces.
+    fn indent_unit(&self, line_text: &str) -> String {
+        if self.hard_tabs || line_text.starts_with('\t') {
+            "\t".to_string()
+        } else {
+            " ".repeat(self.tab_size)
+        }
+    }
+
+    fn indent_lines(&mut self, cx: &mut Context<Self>) {
+        let (first_line, last_line) = self.selected_line_range();
+        let edits = (first_line..=last_line)
+            .filter_map(|line| {
+                let text = self.line_text(line);
+                // Blank lines are left alone unless they are the only line.
+                if first_line != last_line && text.trim().is_empty() {
+                    return None;
+                }
+                Some((line, 0, 0, self.indent_unit(&text)))
+            })
+            .collect();
+        self.apply_line_edits(edits, cx);
+    }
+
+    fn outdent_lines(&mut self, cx: &mut Context<Self>) {
+        let (first_line, last_line) = self.selected_line_range();
+        let tab_size = self.tab_size.max(1);
+        let edits = (first_line..=last_line)
+            .filter_map(|line| {
+                let text = self.line_text(line);
+                let removed = if text.starts_with('\t') {
+                    1
+                } else {
+                    text.bytes()
+                        .take(tab_size)
+                        .take_while(|&b| b == b' ')
+                        .count()
+                };
+                (removed > 0).then_some((line, 0, removed, String::new()))
+            })
+            .collect();
+        self.apply_line_edits(edits, cx);
+    }
+
+    pub fn toggle_comment(&mut self, _: &ToggleComment, _: &mut Window, cx: &mut Context<Self>) {
+        if self.is_overlay_active(cx) {
+            cx.propagate();
+            return;
+        }
+        if self.read_only {
+            return;
+        }
+
+        let (first_line, last_line) = self.selected_line_range();
+
+        let tokens = self.language.comment_tokens();
+        let lines: Vec<(usize, String)> = (first_line..=last_line)
+            .map(|line| (line, self.line_text(line)))
+            .filter(|(_, text)| !text.trim().is_empty())
+            .collect();
+        if lines.is_empty() {
+            return;
+        }
+
+        // (line, col, removed_len, inserted)
+        let mut edits: Vec<(usize, usize, usize, String)> = Vec::new();
+        if let Some(token) = tokens.line {
+            let all_commented = lines
+                .iter()
+                .all(|(_, text)| text.trim_start().starts_with(token));
+            if all_commented {
+                for (line, text) in &lines {
+                    let indent = text.len() - text.trim_start().len();
+                    let after = &text[indent + token.len()..];
+                    let removed = token.len() + usize::from(after.starts_with(' '));
+                    edits.push((*line, indent, removed, String::new()));
+                }
+            } else {
+                let min_indent = lines
+                    .iter()
+                    .map(|(_, text)| text.len() - text.trim_start().len())
+                    .min()
+                    .unwrap_or(0);
+                for (line, _) in &lines {
+                    edits.push((*line, min_indent, 0, format!("{} ", token)));
+                }
+            }
+        } else if let Some((open, close)) = tokens.block {
+            let all_commented = lines.iter().all(|(_, text)| {
+                let trimmed = text.trim();
+                trimmed.len() >= open.len() + close.len()
+                    && trimmed.starts_with(open)
+                    && trimmed.ends_with(close)
+            });
+            for (line, text) in &lines {
+                let indent = text.len() - text.trim_start().len();
+                let content_end = text.trim_end().len();
+                if all_commented {
+                    let inner = &text[indent + open.len()..content_end - close.len()];
+                    let open_removed = open.len() + usize::from(inner.starts_with(' '));
+                    let close_removed =
+                        close.len() + usize::from(inner.len() > 1 && inner.ends_with(' '));
+                    edits.push((
+                        *line,
+                        content_end - close_removed,
+                        close_removed,
+                        String::new(),
+                    ));
+                    edits.push((*line, indent, open_removed, String::new()));
+                } else {
+                    edits.push((*line, content_end, 0, format!(" {}", close)));
+                    edits.push((*line, indent, 0, format!("{} ", open)));
+                }
+            }
+        } else {
+            return;
+        }
+
+        self.apply_line_edits(edits, cx);
+    }
+
+    /// Apply (line, byte column, removed length, inserted text) edits as one undo step,
+    /// moving the cursor and selection with the text around them.
+    fn apply_line_edits(
+        &mut self,
+        mut edits: Vec<(usize, usize, usize, String)>,
+        cx: &mut Context<Self>,
+    ) {
+        if edits.is_empty() {
+            return;
+        }
+        edits.sort_by(|a, b| (b.0, b.1).cmp(&(a.0, a.1)));
+
+        let shift = |pos: Position, edits: &[(usize, usize, usize, String)]| -> Position {
+            let mut col = pos.col;
+            for (line, edit_col, removed, inserted) in edits {
+                if *line != pos.line || col < *edit_col {
+                    continue;
+                }
+                if col >= edit_col + removed {
+                    col = col - removed + inserted.len();
+                } else {
+                    col = *edit_col;
+                }
+            }
+            Position::new(pos.line, col)
+        };
+        let new_cursor = shift(self.cursor, &edits);
+        let new_selection = self
+            .selection
+            .map(|sel| Selection::new(shift(sel.anchor, &edits), shift(sel.cursor, &edits)));
+
+        self.begin_undo_transaction();
+        for (line, col, removed, inserted) in &edits {
+            let byte_offset = self.rope.line_to_byte(*line) + col;
+            if *removed > 0 {
+                let deleted: String = self
+                    .rope
+                    .byte_slice(byte_offset..byte_offset + removed)
+                    .into();
+                self.push_undo(EditOp::Delete {
+                    byte_offset,
+                    text: deleted,
+                });
+                self.rope_remove(byte_offset, byte_offset + removed, cx);
+            }
+            if !inserted.is_empty() {
+                self.push_undo(EditOp::Insert {
+                    byte_offset,
+                    text: inserted.clone(),
+                });
+                self.rope_insert(byte_offset, inserted, cx);
+            }
+        }
+        self.cursor = new_cursor;
+        self.selection = new_selection;
+        self.end_undo_transaction();
+        self.mark_modified();
+        self.update_syntax_tree();
+        self.invalidate_after_edit();
+        cx.notify();
     }
 
     pub fn copy(&mut self, _: &Copy, _: &mut Window, cx: &mut Context<Self>) {
+        if self.has_multiple_cursors() {
+            let mut selections = self.selections();
+            selections.sort_by_key(|sel| sel.range().0);
+            let text = selections
+                .iter()
+                .map(|sel| self.get_selection_text(sel))
+                .collect::<Vec<_>>()
+                .join("\n");
+            cx.write_to_clipboard(ClipboardItem::new_string(text));
+            return;
+        }
         if let Some(selection) = &self.selection {
             let text = self.get_selection_text(selection);
             cx.write_to_clipboard(ClipboardItem::new_string(text));
@@ -2197,7 +5981,7 @@ impl EditorState {
         if self.read_only {
             return;
         }
-        if let Some(selection) = self.selection.take() {
+        if let Some(selection) = self.selection {
             let text = self.get_selection_text(&selection);
             cx.write_to_clipboard(ClipboardItem::new_string(text));
             self.delete_selection_internal(selection, cx);
@@ -2211,11 +5995,41 @@ impl EditorState {
         }
         if let Some(item) = cx.read_from_clipboard() {
             if let Some(text) = item.text() {
+                if self.has_multiple_cursors() {
+                    self.paste_distributed(&text, cx);
+                    return;
+                }
+                if text.contains('\n') && self.language != Language::Plain {
+                    let text = self.reindent_for_paste(&text);
+                    self.insert_text_at_cursor(&text, cx);
+                    return;
+                }
                 self.insert_text_at_cursor(&text, cx);
             }
         }
     }
 
+    /// `text` with its lines shifted to the indentation where it is being pasted.
+    fn reindent_for_paste(&self, text: &str) -> String {
+        let text = normalize_line_endings(text);
+        let start = self.selection.map_or(self.cursor, |sel| sel.range().0);
+        let line_text = self.line_text(start.line);
+        let before = &line_text[..start.col.min(line_text.len())];
+        let at_line_start = before.trim_start().is_empty();
+        let indent = if at_line_start {
+            before
+        } else {
+            &line_text[..line_text.len() - line_text.trim_start().len()]
+        };
+        reindent_paste(
+            &text,
+            indent_width(indent, self.tab_size),
+            at_line_start,
+            self.tab_size,
+            self.indent_unit(&line_text) == "\t",
+        )
+    }
+
     pub fn selection_text(&self) -> Option<String> {
         self.selection
             .as_ref()
@@ -2264,71 +6078,62 @@ impl EditorState {
         let query_owned = self.search_query.clone();
         let use_regex = self.search_use_regex;
         let case_sensitive = self.search_case_sensitive;
-        let entity = cx.entity().clone();
+        let scope = self.search_scope.clone();
 
         // Cancel any in-flight search
-        self.search_task = Some(cx.spawn(async move |_, cx| {
+        self.search_task = Some(cx.spawn(async move |this, cx| {
             // Wait for user to stop typing
             Timer::after(Duration::from_millis(200)).await;
 
-            // Snapshot content and cursor on the main thread, then search in background
-            let search_input = cx.update(|cx| {
-                let state = entity.read(cx);
-                let content = state.rope.to_string();
+            // Snapshot the rope (a cheap, shared clone) and cursor, then search in background
+            let search_input = this.read_with(cx, |state, _| {
                 let cursor_byte = state.pos_to_byte_offset(state.cursor);
-                (content, cursor_byte)
+                (state.rope.clone(), cursor_byte)
             });
 
-            let Ok((content, cursor_byte)) = search_input else { return };
+            let Ok((rope, cursor_byte)) = search_input else {
+                return;
+            };
 
             let matches = smol::unblock(move || {
-                let mut results = Vec::new();
-                if use_regex {
-                    let pattern = if case_sensitive {
-                        query_owned.to_string()
-                    } else {
-                        format!("(?i){}", query_owned)
-                    };
-                    if let Ok(re) = Regex::new(&pattern) {
-                        for m in re.find_iter(&content) {
-                            results.push((m.start(), m.end()));
-                        }
-                    }
-                } else {
-                    let (haystack, needle): (String, String) = if case_sensitive {
-                        (content.to_string(), query_owned.to_string())
-                    } else {
-                        (content.to_lowercase(), query_owned.to_lowercase())
-                    };
-                    let needle_len = needle.len();
-                    let mut start = 0;
-                    while let Some(pos) = haystack[start..].find(&needle) {
-                        let match_start = start + pos;
-                        let match_end = match_start + needle_len;
-                        results.push((match_start, match_end));
-                        start = match_start + 1;
-                    }
+                let _span = tracing::debug_span!(
+                    "editor.search",
+                    regex = use_regex,
+                    case_sensitive,
+                    query_len = query_owned.len()
+                )
+                .entered();
+                let mut results: Vec<(usize, usize)> =
+                    rope_search_regex(&query_owned, use_regex, case_sensitive)
+                        .map(|regex| {
+                            rope_matches(&rope, &regex)
+                                .into_iter()
+                                .map(|m| (m.start, m.end))
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                if let Some(scope) = scope {
+                    results.retain(|&(start, end)| start >= scope.start && end <= scope.end);
                 }
+                tracing::debug!(matches = results.len(), "search finished");
                 results
             })
             .await;
 
-            let _ = cx.update(|cx| {
-                entity.update(cx, |state, cx| {
-                    state.search_matches = matches;
-                    if !state.search_matches.is_empty() {
-                        let idx = state
-                            .search_matches
-                            .iter()
-                            .position(|(s, _)| *s >= cursor_byte)
-                            .unwrap_or(0);
-                        state.current_match_idx = Some(idx);
-                        state.scroll_to_match(idx);
-                    } else {
-                        state.current_match_idx = None;
-                    }
-                    cx.notify();
-                });
+            let _ = this.update(cx, |state, cx| {
+                state.search_matches = matches;
+                if !state.search_matches.is_empty() {
+                    let idx = state
+                        .search_matches
+                        .iter()
+                        .position(|(s, _)| *s >= cursor_byte)
+                        .unwrap_or(0);
+                    state.current_match_idx = Some(idx);
+                    state.scroll_to_match(idx);
+                } else {
+                    state.current_match_idx = None;
+                }
+                cx.notify();
             });
         }));
     }
@@ -2343,8 +6148,10 @@ impl EditorState {
         };
         self.current_match_idx = Some(next);
         let (start, _) = self.search_matches[next];
+        let from = self.cursor;
         self.cursor = self.byte_offset_to_pos(start);
         self.selection = None;
+        self.record_jump(from);
         self.scroll_to_match(next);
         cx.notify();
     }
@@ -2359,8 +6166,10 @@ impl EditorState {
         };
         self.current_match_idx = Some(prev);
         let (start, _) = self.search_matches[prev];
+        let from = self.cursor;
         self.cursor = self.byte_offset_to_pos(start);
         self.selection = None;
+        self.record_jump(from);
         self.scroll_to_match(prev);
         cx.notify();
     }
@@ -2374,19 +6183,22 @@ impl EditorState {
             _ => return,
         };
         let (start, end) = self.search_matches[idx];
+        let expanded = self.expand_replacements(&[(start, end)], replacement);
+        let replacement = expanded[0].as_str();
         let old_end_position = self.byte_to_ts_point(end.min(self.rope.len_bytes()));
         let deleted: String = self.rope.byte_slice(start..end).into();
-        self.undo_stack.push(EditOp::Delete {
+        self.begin_undo_transaction();
+        self.push_undo(EditOp::Delete {
             byte_offset: start,
             text: deleted,
         });
-        self.rope_remove(start, end);
-        self.undo_stack.push(EditOp::Insert {
+        self.rope_remove(start, end, cx);
+        self.push_undo(EditOp::Insert {
             byte_offset: start,
             text: replacement.to_string(),
         });
-        self.rope_insert(start, replacement);
-        self.redo_stack.clear();
+        self.rope_insert(start, replacement, cx);
+        self.end_undo_transaction();
         self.mark_modified();
         let new_end = start + replacement.len();
         self.update_syntax_tree_incremental(start, end, new_end, old_end_position, cx);
@@ -2400,20 +6212,23 @@ impl EditorState {
             return;
         }
         let matches: Vec<_> = self.search_matches.iter().rev().copied().collect();
-        for (start, end) in matches {
+        let _span = tracing::debug_span!("editor.replace_all", count = matches.len()).entered();
+        let replacements = self.expand_replacements(&matches, replacement);
+        self.begin_undo_transaction();
+        for ((start, end), replacement) in matches.into_iter().zip(&replacements) {
             let deleted: String = self.rope.byte_slice(start..end).into();
-            self.undo_stack.push(EditOp::Delete {
+            self.push_undo(EditOp::Delete {
                 byte_offset: start,
                 text: deleted,
             });
-            self.rope_remove(start, end);
-            self.undo_stack.push(EditOp::Insert {
+            self.rope_remove(start, end, cx);
+            self.push_undo(EditOp::Insert {
                 byte_offset: start,
                 text: replacement.to_string(),
             });
-            self.rope_insert(start, replacement);
+            self.rope_insert(start, replacement, cx);
         }
-        self.redo_stack.clear();
+        self.end_undo_transaction();
         self.mark_modified();
         self.update_syntax_tree();
         self.invalidate_after_edit();
@@ -2421,12 +6236,72 @@ impl EditorState {
         self.find_all(&query, cx);
     }
 
+    /// The text replacing each of `matches`. In regex mode `$1`, `${name}` and `$0` in
+    /// `replacement` expand to the match's capture groups and `$$` to a literal `$`;
+    /// otherwise it is used as is.
+    fn expand_replacements(&self, matches: &[(usize, usize)], replacement: &str) -> Vec<String> {
+        let regex = self
+            .search_use_regex
+            .then(|| search_regex(&self.search_query, self.search_case_sensitive).ok())
+            .flatten();
+        let Some(regex) = regex else {
+            return vec![replacement.to_string(); matches.len()];
+        };
+        matches
+            .iter()
+            .map(|&(start, end)| {
+                // Capture groups are read from just the lines holding the match.
+                let first_line = self.rope.byte_to_line(start);
+                let last_line = self.rope.byte_to_line(end);
+                let context_start = self.rope.line_to_byte(first_line);
+                let context_end = self.rope.line_to_byte(last_line + 1);
+                let context: std::borrow::Cow<str> =
+                    self.rope.byte_slice(context_start..context_end).into();
+                let (start, end) = (start - context_start, end - context_start);
+                let mut expanded = String::new();
+                match regex.captures_at(&context, start) {
+                    Some(caps) if caps.get(0).is_some_and(|m| m.range() == (start..end)) => {
+                        caps.expand(replacement, &mut expanded);
+                    }
+                    _ => expanded.push_str(replacement),
+                }
+                expanded
+            })
+            .collect()
+    }
+
+    /// Restrict find/replace to the current selection, or lift the restriction. Has no
+    /// effect without a non-empty selection.
+    pub fn set_search_in_selection(&mut self, enabled: bool, cx: &mut Context<Self>) {
+        self.search_scope = if enabled {
+            self.selection
+                .filter(|sel| !sel.is_empty())
+                .map(|sel| self.selection_byte_range(&sel))
+        } else {
+            None
+        };
+        if !self.search_query.is_empty() {
+            let query = self.search_query.clone();
+            self.find_all(&query, cx);
+        } else {
+            cx.notify();
+        }
+    }
+
+    pub fn search_in_selection(&self) -> bool {
+        self.search_scope.is_some()
+    }
+
     /// Full invalidation — clears all caches. Use for structural changes
     /// (file load, language change, fold/unfold).
     fn invalidate_all_caches(&mut self) {
         self.line_layouts.clear();
         self.line_content_hashes.clear();
         self.highlight_cache_version = u64::MAX;
+        self.highlight_request = None;
+        self.highlight_task = None;
+        self.bracket_match_cache = None;
+        self.word_occurrence_cache = None;
     }
 
     /// Invalidation for text edits. Clears all caches since line indices
@@ -2435,6 +6310,96 @@ impl EditorState {
         self.line_layouts.clear();
         self.line_content_hashes.clear();
         self.highlight_cache_version = u64::MAX;
+        self.highlight_request = None;
+        self.highlight_task = None;
+        self.bracket_match_cache = None;
+        self.word_occurrence_cache = None;
+    }
+
+    /// Highlight the visible lines again on the next frame, keeping the current spans
+    /// until the new ones arrive.
+    fn refresh_highlights(&mut self, cx: &mut Context<Self>) {
+        self.highlight_cache_version = u64::MAX;
+        self.highlight_request = None;
+        self.highlight_task = None;
+        cx.notify();
+    }
+
+    /// Run the highlight query over buffer `lines` on a background thread. The result
+    /// replaces `cached_highlight_spans` if the buffer is unchanged when it arrives;
+    /// until then the previous spans keep being painted.
+    fn request_highlights(&mut self, lines: Vec<usize>, cx: &mut Context<Self>) {
+        let (Some(&first), Some(&last)) = (lines.first(), lines.last()) else {
+            return;
+        };
+        let request = (self.content_version, first, last + 1);
+        if self.highlight_request == Some(request) {
+            return;
+        }
+        let (Some(tree), Some(query)) = (self.syntax_tree.clone(), self.highlight_query.clone())
+        else {
+            self.cached_highlight_spans.clear();
+            self.spelling_errors.clear();
+            self.highlight_cache_version = request.0;
+            self.highlight_cache_first_line = request.1;
+            self.highlight_cache_last_line = request.2;
+            return;
+        };
+        self.highlight_request = Some(request);
+        let rope = self.rope.clone();
+        let spell_checker = self.spell_checker.clone();
+        let markdown = self.language == Language::Markdown;
+        self.highlight_task = Some(cx.spawn(async move |this, cx| {
+            let (captures, misspellings) = {
+                let query = query.clone();
+                smol::unblock(move || {
+                    let captures = highlight_captures(&rope, &tree, &query, &lines);
+                    let misspellings = match spell_checker {
+                        Some(checker) => {
+                            let prose = prose_ranges(&rope, &query, &captures, &lines, markdown);
+                            find_misspellings(&rope, &prose, &checker)
+                        }
+                        None => Vec::new(),
+                    };
+                    (captures, misspellings)
+                })
+                .await
+            };
+            let _ = this.update(cx, |state, cx| {
+                if state.content_version != request.0 {
+                    return;
+                }
+                state.cached_highlight_spans = state.color_captures(&query, captures);
+                state.spelling_errors = misspellings;
+                state.highlight_cache_version = request.0;
+                state.highlight_cache_first_line = request.1;
+                state.highlight_cache_last_line = request.2;
+                state.highlight_request = None;
+                // Lines shaped with the previous spans are shaped again with these.
+                state.line_content_hashes.clear();
+                cx.notify();
+            });
+        }));
+    }
+
+    fn color_captures(&self, query: &Query, captures: Vec<CaptureSpan>) -> Vec<HighlightSpan> {
+        let names = query.capture_names();
+        captures
+            .into_iter()
+            .map(|capture| HighlightSpan {
+                line: capture.line,
+                start_col: capture.start_col,
+                end_col: capture.end_col,
+                color: self.capture_color(names[capture.capture]),
+            })
+            .collect()
+    }
+
+    fn capture_color(&self, name: &str) -> Hsla {
+        match &self.syntax_color_fn {
+            Some(color_fn) => color_fn(name),
+            None => highlight_color_for_capture(name),
+        }
     }
 
     pub fn invalidate_line_layouts(&mut self, cx: &mut Context<Self>) {
@@ -2469,13 +6434,86 @@ impl EditorState {
         }
     }
 
+    /// Remember `from` as a place to go back to if the cursor has since moved far
+    /// enough from it. A jump from near the newest entry replaces it.
+    fn record_jump(&mut self, from: Position) {
+        if self.cursor.line.abs_diff(from.line) < NAV_JUMP_MIN_LINES {
+            return;
+        }
+        if self
+            .nav_back
+            .last()
+            .is_some_and(|last| last.line.abs_diff(from.line) < NAV_JUMP_MIN_LINES)
+        {
+            self.nav_back.pop();
+        }
+        self.nav_back.push(from);
+        if self.nav_back.len() > NAV_HISTORY_LIMIT {
+            self.nav_back.remove(0);
+        }
+        self.nav_forward.clear();
+    }
+
+    pub fn can_navigate_back(&self) -> bool {
+        !self.nav_back.is_empty()
+    }
+
+    pub fn can_navigate_forward(&self) -> bool {
+        !self.nav_forward.is_empty()
+    }
+
+    /// Return to where the cursor was before its most recent jump (goto line, search,
+    /// a click far away, go-to-definition).
+    pub fn navigate_back(&mut self, _: &NavigateBack, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(target) = self.nav_back.pop() {
+            self.nav_forward.push(self.cursor);
+            self.move_to_location(target, cx);
+        }
+    }
+
+    pub fn navigate_forward(
+        &mut self,
+        _: &NavigateForward,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(target) = self.nav_forward.pop() {
+            self.nav_back.push(self.cursor);
+            self.move_to_location(target, cx);
+        }
+    }
+
+    /// Move the cursor to a recorded location, which edits may have left past the end
+    /// of its line or the buffer.
+    fn move_to_location(&mut self, position: Position, cx: &mut Context<Self>) {
+        self.clear_extra_cursors();
+        self.selection = None;
+        self.cursor = position;
+        self.clamp_cursor();
+        self.reset_cursor_blink(cx);
+        self.ensure_cursor_visible(cx);
+        cx.notify();
+    }
+
     pub fn goto_line(&mut self, line: usize, cx: &mut Context<Self>) {
         let target = line
             .saturating_sub(1)
             .min(self.total_lines().saturating_sub(1));
+        let from = self.cursor;
         self.cursor = Position::new(target, 0);
         self.selection = None;
-        self.ensure_cursor_visible(cx);
+        self.record_jump(from);
+        self.reveal_cursor(true, cx);
+    }
+
+    /// Scroll vertically to offset `y`, easing there when `smooth_scroll` is on.
+    fn scroll_to_y(&mut self, y: Pixels) {
+        let target = point(self.scroll_handle.offset().x, y);
+        if self.smooth_scroll {
+            self.smooth_scroller.scroll_to(&self.scroll_handle, target);
+        } else {
+            self.scroll_handle.set_offset(target);
+        }
     }
 
     fn scroll_to_match(&mut self, idx: usize) {
@@ -2501,21 +6539,22 @@ impl EditorState {
         }
 
         if (new_offset_y - offset.y).abs() > px(0.0) {
-            self.scroll_handle.set_offset(point(offset.x, new_offset_y));
+            self.scroll_to_y(new_offset_y);
         }
     }
 
     fn ensure_cursor_visible(&mut self, cx: &mut Context<Self>) {
+        self.reveal_cursor(false, cx);
+    }
+
+    /// Scroll the cursor into view, easing there if `animate` and `smooth_scroll` are set.
+    fn reveal_cursor(&mut self, animate: bool, cx: &mut Context<Self>) {
         let line_height = self.line_height;
         let padding_top = px(12.0);
         let viewport_bounds = self.scroll_handle.bounds();
         let viewport_height = viewport_bounds.size.height;
         let viewport_width = viewport_bounds.size.width;
-        let gutter_width = if self.show_line_numbers {
-            px(80.0)
-        } else {
-            px(12.0)
-        };
+        let gutter_width = self.gutter_width();
         let content_width = viewport_width - gutter_width;
         let offset = self.scroll_handle.offset();
         let mut new_offset_y = offset.y;
@@ -2536,7 +6575,11 @@ impl EditorState {
         new_offset_y = new_offset_y.max(-max_offset).min(px(0.0));
 
         if (new_offset_y - offset.y).abs() > px(0.0) {
-            self.scroll_handle.set_offset(point(offset.x, new_offset_y));
+            if animate {
+                self.scroll_to_y(new_offset_y);
+            } else {
+                self.scroll_handle.set_offset(point(offset.x, new_offset_y));
+            }
         }
 
         if let Some(layout) = self.line_layouts.get(&self.cursor.line) {
@@ -2556,11 +6599,7 @@ impl EditorState {
 
     pub fn scroll_horizontal(&mut self, delta: Pixels, cx: &mut Context<Self>) {
         let viewport_bounds = self.scroll_handle.bounds();
-        let gutter_width = if self.show_line_numbers {
-            px(80.0)
-        } else {
-            px(12.0)
-        };
+        let gutter_width = self.gutter_width();
         let content_width = viewport_bounds.size.width - gutter_width;
         let max_scroll = (self.max_line_width - content_width + px(40.0)).max(px(0.0));
 
@@ -2613,70 +6652,58 @@ impl EditorState {
     }
 
     fn start_autoscroll(&mut self, cx: &mut Context<Self>) {
-        let entity = cx.entity().clone();
         let line_height = self.line_height;
-        self.autoscroll_task = Some(cx.spawn(async move |_, cx| loop {
+        self.autoscroll_task = Some(cx.spawn(async move |this, cx| loop {
             Timer::after(Duration::from_millis(50)).await;
-            let should_continue = cx
-                .update(|cx| {
-                    entity.update(cx, |state, cx| {
-                        if !state.is_selecting {
-                            return false;
-                        }
-                        let Some(mouse_pos) = state.last_mouse_pos else {
-                            return true;
-                        };
-                        let Some(bounds) = state.last_bounds else {
-                            return true;
-                        };
+            let should_continue = this
+                .update(cx, |state, cx| {
+                    if !state.is_selecting {
+                        return false;
+                    }
+                    let Some(mouse_pos) = state.last_mouse_pos else {
+                        return true;
+                    };
+                    let Some(bounds) = state.last_bounds else {
+                        return true;
+                    };
 
-                        let viewport_bounds = state.scroll_handle.bounds();
-                        if viewport_bounds.size.height == px(0.0) {
-                            return true;
-                        }
-                        let viewport_top = viewport_bounds.top();
-                        let viewport_bottom = viewport_bounds.bottom();
-                        let mouse_y = mouse_pos.y;
-                        let edge_zone = line_height * 1.5;
-                        let mut scrolled = false;
-
-                        if mouse_y < viewport_top + edge_zone {
-                            let speed = ((viewport_top + edge_zone - mouse_y) / edge_zone)
-                                .max(0.5)
-                                .min(5.0);
-                            let offset = state.scroll_handle.offset();
-                            let new_y = (offset.y + line_height * speed).min(px(0.0));
-                            state.scroll_handle.set_offset(point(offset.x, new_y));
-                            scrolled = true;
-                        } else if mouse_y > viewport_bottom - edge_zone {
-                            let speed = ((mouse_y - (viewport_bottom - edge_zone)) / edge_zone)
-                                .max(0.5)
-                                .min(5.0);
-                            let offset = state.scroll_handle.offset();
-                            let max_offset = state.scroll_handle.max_offset().height;
-                            let new_y = (offset.y - line_height * speed).max(-max_offset);
-                            state.scroll_handle.set_offset(point(offset.x, new_y));
-                            scrolled = true;
-                        }
+                    let viewport_bounds = state.scroll_handle.bounds();
+                    if viewport_bounds.size.height == px(0.0) {
+                        return true;
+                    }
+                    let viewport_top = viewport_bounds.top();
+                    let viewport_bottom = viewport_bounds.bottom();
+                    let mouse_y = mouse_pos.y;
+                    let edge_zone = line_height * 1.5;
+                    let mut scrolled = false;
+
+                    if mouse_y < viewport_top + edge_zone {
+                        let speed = ((viewport_top + edge_zone - mouse_y) / edge_zone)
+                            .max(0.5)
+                            .min(5.0);
+                        let offset = state.scroll_handle.offset();
+                        let new_y = (offset.y + line_height * speed).min(px(0.0));
+                        state.scroll_handle.set_offset(point(offset.x, new_y));
+                        scrolled = true;
+                    } else if mouse_y > viewport_bottom - edge_zone {
+                        let speed = ((mouse_y - (viewport_bottom - edge_zone)) / edge_zone)
+                            .max(0.5)
+                            .min(5.0);
+                        let offset = state.scroll_handle.offset();
+                        let max_offset = state.scroll_handle.max_offset().height;
+                        let new_y = (offset.y - line_height * speed).max(-max_offset);
+                        state.scroll_handle.set_offset(point(offset.x, new_y));
+                        scrolled = true;
+                    }
 
-                        if scrolled {
-                            let gutter_width = state.last_mouse_gutter_width;
-                            let pos = state.position_for_mouse(
-                                mouse_pos,
-                                bounds,
-                                gutter_width,
-                                line_height,
-                            );
-                            if let Some(ref mut sel) = state.selection {
-                                sel.cursor = pos;
-                            } else {
-                                state.selection = Some(Selection::new(state.cursor, pos));
-                            }
-                            state.cursor = pos;
-                            cx.notify();
-                        }
-                        true
-                    })
+                    if scrolled {
+                        let gutter_width = state.last_mouse_gutter_width;
+                        let pos =
+                            state.position_for_mouse(mouse_pos, bounds, gutter_width, line_height);
+                        state.extend_drag_selection(pos);
+                        cx.notify();
+                    }
+                    true
                 })
                 .unwrap_or(false);
             if !should_continue {
@@ -2691,9 +6718,10 @@ impl EditorState {
         bounds: Bounds<Pixels>,
         gutter_width: Pixels,
         line_height: Pixels,
-        _window: &Window,
+        window: &Window,
         cx: &mut Context<Self>,
     ) {
+        activity::note_activity(cx);
         let click_x = event.position.x - bounds.left();
         let padding_top = px(12.0);
         let display_row = ((event.position.y - bounds.top() - padding_top) / line_height)
@@ -2702,30 +6730,152 @@ impl EditorState {
         let dl = self.display_lines();
         let click_line = dl.get(display_row).copied().unwrap_or(0);
 
-        if click_x >= gutter_width - px(16.0) && click_x <= gutter_width {
-            if self.fold_ranges.iter().any(|f| f.start_line == click_line) {
-                self.toggle_fold_at_line(click_line, cx);
+        if !self.sticky_header_lines.is_empty() {
+            let viewport_top = bounds.top() - self.scroll_handle.offset().y;
+            let header_row = ((event.position.y - viewport_top) / line_height).floor();
+            if header_row >= 0.0 {
+                if let Some(&line) = self.sticky_header_lines.get(header_row as usize) {
+                    self.jump_to_scope_start(line, cx);
+                    return;
+                }
+            }
+        }
+
+        if let Some((lane, lane_left)) = self.gutter_lane_at(click_x) {
+            let row_bottom = bounds.top() + padding_top + line_height * (display_row + 1) as f32;
+            let anchor = point(bounds.left() + lane_left, row_bottom);
+            match &lane.kind {
+                GutterLaneKind::Folds => {
+                    if self.fold_ranges.iter().any(|f| f.start_line == click_line) {
+                        self.toggle_fold_at_line(click_line, cx);
+                        return;
+                    }
+                }
+                GutterLaneKind::Diff => {
+                    if let (Some(hunk), Some(handler)) = (
+                        self.hunk_at_line(click_line).cloned(),
+                        self.on_diff_hunk_click.clone(),
+                    ) {
+                        let anchor = point(bounds.left(), row_bottom);
+                        window.defer(cx, move |window, cx| handler(&hunk, anchor, window, cx));
+                        return;
+                    }
+                }
+                GutterLaneKind::Decorations => {
+                    let decoration = self.gutter_decoration_at(click_line).cloned();
+                    if decoration.is_some() || !self.code_action_lines.contains(&click_line) {
+                        let handler = decoration
+                            .and_then(|decoration| decoration.on_click)
+                            .or_else(|| self.on_gutter_click.clone());
+                        if let Some(handler) = handler {
+                            let click = GutterClick {
+                                line: click_line,
+                                position: anchor,
+                            };
+                            window.defer(cx, move |window, cx| handler(&click, window, cx));
+                            return;
+                        }
+                    } else {
+                        if self.cursor.line != click_line {
+                            self.clear_extra_cursors();
+                            self.selection = None;
+                            self.cursor = Position::new(click_line, 0);
+                        }
+                        self.request_code_actions(Some(anchor), window, cx);
+                        cx.notify();
+                        return;
+                    }
+                }
+                GutterLaneKind::Custom(_) => {
+                    if let Some(handler) = lane.on_click.clone() {
+                        let click = GutterClick {
+                            line: click_line,
+                            position: anchor,
+                        };
+                        window.defer(cx, move |window, cx| handler(&click, window, cx));
+                        return;
+                    }
+                }
+                GutterLaneKind::LineNumbers => {}
+            }
+        }
+
+        let swatch_clickable = self.on_color_swatch_click.is_some() || !self.read_only;
+        if self.color_swatches && swatch_clickable && click_x >= gutter_width {
+            let text_x = click_x - gutter_width + self.scroll_offset_x;
+            if let Some((literal, left)) = self.color_swatch_at(click_line, text_x) {
+                let row_bottom =
+                    bounds.top() + padding_top + line_height * (display_row + 1) as f32;
+                let click = ColorSwatchClick {
+                    line: click_line,
+                    literal,
+                    position: point(
+                        bounds.left() + gutter_width + left - self.scroll_offset_x,
+                        row_bottom,
+                    ),
+                };
+                match self.on_color_swatch_click.clone() {
+                    Some(handler) => {
+                        window.defer(cx, move |window, cx| handler(&click, window, cx))
+                    }
+                    None => self.open_color_picker(click, cx),
+                }
                 return;
             }
         }
 
         let pos = self.position_for_mouse(event.position, bounds, gutter_width, line_height);
 
-        let now = std::time::Instant::now();
-        let is_double_click = if let Some(last_time) = self.last_click_time {
-            now.duration_since(last_time).as_millis() < 500
-        } else {
-            false
-        };
-        self.last_click_time = Some(now);
+        if event.modifiers.secondary() && click_x >= gutter_width {
+            if let (Some(handler), Some(request)) = (
+                self.on_navigate_request.clone(),
+                self.navigate_request_at(pos, event.position),
+            ) {
+                self.clear_extra_cursors();
+                self.selection = None;
+                let from = self.cursor;
+                self.cursor = pos;
+                self.record_jump(from);
+                self.navigate_hover = None;
+                window.defer(cx, move |window, cx| handler(&request, window, cx));
+                cx.notify();
+                return;
+            }
+        }
 
-        if is_double_click {
-            self.selection = Some(Selection::new(
-                Position::new(pos.line, 0),
-                Position::new(pos.line, self.line_len(pos.line)),
-            ));
-            self.cursor = Position::new(pos.line, self.line_len(pos.line));
+        self.drag_granularity = DragGranularity::Char;
+        if event.click_count >= 2 && !event.modifiers.shift && !event.modifiers.alt {
+            self.clear_extra_cursors();
+            let (start, end) = if event.click_count == 2 {
+                let word = self.word_range_at(pos);
+                let start = Position::new(pos.line, word.start);
+                let end = Position::new(pos.line, word.end);
+                self.drag_granularity = DragGranularity::Word { start, end };
+                (start, end)
+            } else {
+                self.drag_granularity = DragGranularity::Line(pos.line);
+                (
+                    Position::new(pos.line, 0),
+                    self.line_selection_end(pos.line),
+                )
+            };
+            self.selection = Some(Selection::new(start, end));
+            self.cursor = end;
+            self.is_selecting = true;
+            self.last_mouse_pos = Some(event.position);
+            self.last_mouse_gutter_width = gutter_width;
+            self.start_autoscroll(cx);
+        } else if event.modifiers.alt {
+            let origin = Position::new(pos.line, self.byte_col_to_char(pos.line, pos.col));
+            self.clear_extra_cursors();
+            self.column_select = Some((origin, origin));
+            self.cursor = pos;
+            self.selection = None;
+            self.is_selecting = true;
+            self.last_mouse_pos = Some(event.position);
+            self.last_mouse_gutter_width = gutter_width;
         } else if event.modifiers.shift {
+            self.clear_extra_cursors();
             if let Some(ref mut sel) = self.selection {
                 sel.cursor = pos;
                 self.cursor = pos;
@@ -2734,8 +6884,11 @@ impl EditorState {
                 self.cursor = pos;
             }
         } else {
+            self.clear_extra_cursors();
+            let from = self.cursor;
             self.cursor = pos;
             self.selection = None;
+            self.record_jump(from);
             self.is_selecting = true;
             self.last_mouse_pos = Some(event.position);
             self.last_mouse_gutter_width = gutter_width;
@@ -2754,6 +6907,11 @@ impl EditorState {
         _window: &Window,
         cx: &mut Context<Self>,
     ) {
+        if self.dragging_minimap {
+            self.on_minimap_mouse_move(event, cx);
+            return;
+        }
+
         if self.dragging_h_scrollbar {
             if event.pressed_button != Some(MouseButton::Left) {
                 self.dragging_h_scrollbar = false;
@@ -2762,11 +6920,7 @@ impl EditorState {
             }
             let max_w = self.max_line_width;
             let vp = self.scroll_handle.bounds();
-            let gw = if self.show_line_numbers {
-                px(80.0)
-            } else {
-                px(12.0)
-            };
+            let gw = self.gutter_width();
             let cw = vp.size.width - gw;
             let scroll_range = max_w - cw;
 
@@ -2780,6 +6934,30 @@ impl EditorState {
             return;
         }
 
+        if self.on_navigate_request.is_some() && !self.is_selecting {
+            let over_text = event.position.x - bounds.left() >= gutter_width;
+            let hover = if event.modifiers.secondary() && over_text {
+                let pos =
+                    self.position_for_mouse(event.position, bounds, gutter_width, line_height);
+                self.identifier_range_at(pos).map(|range| (pos.line, range))
+            } else {
+                None
+            };
+            self.update_navigate_hover(hover, cx);
+        }
+
+        if self.image_hovers && !self.is_selecting {
+            let over_text = event.position.x - bounds.left() >= gutter_width;
+            let target = if over_text && event.pressed_button.is_none() {
+                let pos =
+                    self.position_for_mouse(event.position, bounds, gutter_width, line_height);
+                path_literal_at(&self.line_text(pos.line), pos.col).map(|cols| (pos.line, cols))
+            } else {
+                None
+            };
+            self.update_image_hover(target, cx);
+        }
+
         if !self.is_selecting || event.pressed_button != Some(MouseButton::Left) {
             if self.is_selecting && event.pressed_button != Some(MouseButton::Left) {
                 self.is_selecting = false;
@@ -2792,19 +6970,153 @@ impl EditorState {
         self.last_mouse_pos = Some(event.position);
         self.last_mouse_gutter_width = gutter_width;
 
+        if let Some((anchor, _)) = self.column_select {
+            let pos = self.position_for_mouse(event.position, bounds, gutter_width, line_height);
+            let relative_x = event.position.x - bounds.left() - gutter_width + self.scroll_offset_x;
+            let char_col = (relative_x / self.approx_char_width()).round().max(0.0) as usize;
+            self.set_column_selection(anchor, Position::new(pos.line, char_col), cx);
+            return;
+        }
+
         let pos = self.position_for_mouse(event.position, bounds, gutter_width, line_height);
-        if let Some(ref mut sel) = self.selection {
-            sel.cursor = pos;
+        self.extend_drag_selection(pos);
+        self.ensure_cursor_visible(cx);
+    }
+
+    /// Move the dragged end of the selection to `pos`, rounded out to whole words or
+    /// lines when the drag started with a double or triple click.
+    fn extend_drag_selection(&mut self, pos: Position) {
+        let (anchor, cursor) = match self.drag_granularity {
+            DragGranularity::Char => {
+                let anchor = self.selection.map_or(self.cursor, |sel| sel.anchor);
+                (anchor, pos)
+            }
+            DragGranularity::Word { start, end } => {
+                let word = self.word_range_at(pos);
+                if pos < start {
+                    (end, Position::new(pos.line, word.start))
+                } else if pos > end {
+                    (start, Position::new(pos.line, word.end))
+                } else {
+                    (start, end)
+                }
+            }
+            DragGranularity::Line(line) => {
+                if pos.line < line {
+                    (self.line_selection_end(line), Position::new(pos.line, 0))
+                } else {
+                    (Position::new(line, 0), self.line_selection_end(pos.line))
+                }
+            }
+        };
+        self.selection = Some(Selection::new(anchor, cursor));
+        self.cursor = cursor;
+    }
+
+    /// Byte columns of the run of word characters, whitespace or punctuation under
+    /// `pos`. At the end of a line, or just after a word, the word to the left counts.
+    fn word_range_at(&self, pos: Position) -> Range<usize> {
+        #[derive(PartialEq)]
+        enum CharKind {
+            Word,
+            Whitespace,
+            Punctuation,
+        }
+        let kind = |c: char| {
+            if c.is_alphanumeric() || c == '_' {
+                CharKind::Word
+            } else if c.is_whitespace() {
+                CharKind::Whitespace
+            } else {
+                CharKind::Punctuation
+            }
+        };
+
+        let line_text = self.line_text(pos.line);
+        let col = pos.col.min(line_text.len());
+        let before = line_text[..col].chars().next_back();
+        let after = line_text[col..].chars().next();
+        let target = match (before, after) {
+            (Some(b), Some(a)) if kind(a) != CharKind::Word && kind(b) == CharKind::Word => kind(b),
+            (_, Some(a)) => kind(a),
+            (Some(b), None) => kind(b),
+            (None, None) => return col..col,
+        };
+
+        let start = line_text[..col]
+            .char_indices()
+            .rev()
+            .take_while(|&(_, c)| kind(c) == target)
+            .last()
+            .map_or(col, |(i, _)| i);
+        let end = line_text[col..]
+            .char_indices()
+            .find(|&(_, c)| kind(c) != target)
+            .map_or(line_text.len(), |(i, _)| col + i);
+        start..end
+    }
+
+    /// Where a whole-line selection of `line` ends: the start of the next line, or the
+    /// end of the buffer on the last line.
+    fn line_selection_end(&self, line: usize) -> Position {
+        if line + 1 < self.total_lines() {
+            Position::new(line + 1, 0)
         } else {
-            self.selection = Some(Selection::new(self.cursor, pos));
+            Position::new(line, self.line_len(line))
         }
-        self.cursor = pos;
-        self.ensure_cursor_visible(cx);
+    }
+
+    /// Vertical offset of the minimap content. When the document is taller than
+    /// the minimap, the minimap scrolls proportionally with the editor.
+    fn minimap_offset(&self, minimap_height: Pixels) -> Pixels {
+        let content_height = px(MINIMAP_ROW_HEIGHT) * self.display_line_count() as f32;
+        let overflow = content_height - minimap_height;
+        let max_scroll = self.scroll_handle.max_offset().height;
+        if overflow <= px(0.0) || max_scroll <= px(0.0) {
+            return px(0.0);
+        }
+        let ratio = (-self.scroll_handle.offset().y / max_scroll).clamp(0.0, 1.0);
+        overflow * ratio
+    }
+
+    fn scroll_to_minimap_y(&mut self, y: Pixels, cx: &mut Context<Self>) {
+        let Some(minimap_bounds) = self.minimap_bounds else {
+            return;
+        };
+        let offset = self.minimap_offset(minimap_bounds.size.height);
+        let row = ((y - minimap_bounds.top() + offset) / px(MINIMAP_ROW_HEIGHT)).max(0.0);
+        let padding_top = px(12.0);
+        let viewport_height = self.scroll_handle.bounds().size.height;
+        let target_y = padding_top + self.line_height * row - viewport_height / 2.0;
+        let max_offset = self.scroll_handle.max_offset().height;
+        let new_offset_y = (-target_y).max(-max_offset).min(px(0.0));
+        let current = self.scroll_handle.offset();
+        self.scroll_handle
+            .set_offset(point(current.x, new_offset_y));
+        cx.notify();
+    }
+
+    fn on_minimap_mouse_down(&mut self, event: &MouseDownEvent, cx: &mut Context<Self>) {
+        self.dragging_minimap = true;
+        self.scroll_to_minimap_y(event.position.y, cx);
+    }
+
+    fn on_minimap_mouse_move(&mut self, event: &MouseMoveEvent, cx: &mut Context<Self>) {
+        if !self.dragging_minimap {
+            return;
+        }
+        if event.pressed_button != Some(MouseButton::Left) {
+            self.dragging_minimap = false;
+            cx.notify();
+            return;
+        }
+        self.scroll_to_minimap_y(event.position.y, cx);
     }
 
     fn on_mouse_up(&mut self, _: &MouseUpEvent, _: &mut Window, cx: &mut Context<Self>) {
         self.is_selecting = false;
         self.dragging_h_scrollbar = false;
+        self.dragging_minimap = false;
         self.autoscroll_task = None;
         self.last_mouse_pos = None;
         cx.notify();
@@ -2866,8 +7178,9 @@ impl EntityInputHandler for EditorState {
             .map(|range| self.range_to_utf16(range))
     }
 
-    fn unmark_text(&mut self, _window: &mut Window, _cx: &mut Context<Self>) {
+    fn unmark_text(&mut self, _window: &mut Window, cx: &mut Context<Self>) {
         self.marked_range = None;
+        cx.notify();
     }
 
     fn replace_text_in_range(
@@ -2880,6 +7193,11 @@ impl EntityInputHandler for EditorState {
         if self.read_only {
             return;
         }
+        if self.has_multiple_cursors() && self.marked_range.is_none() {
+            self.insert_text_at_cursor(new_text, cx);
+            cx.notify();
+            return;
+        }
         let range_utf8 = range_utf16
             .as_ref()
             .map(|r| self.range_from_utf16(r))
@@ -2899,22 +7217,29 @@ impl EntityInputHandler for EditorState {
             let start_pos = self.byte_offset_to_pos(range.start);
             let end_pos = self.byte_offset_to_pos(range.end);
 
+            // Replacing a range and typing into it, including an auto-closed pair, is
+            // one undo step.
+            let committing = self.marked_range.is_some();
+            self.begin_undo_transaction();
             if start_pos != end_pos {
                 self.delete_selection_internal(Selection::new(start_pos, end_pos), cx);
             }
 
-            if new_text.len() == 1 && self.selection.is_none() {
+            // A character committed from a composition is typed as is, never paired.
+            if new_text.len() == 1 && self.selection.is_none() && !committing && self.auto_closing {
                 let ch = new_text.chars().next().unwrap();
 
                 if let Some(closer) = self.closing_char_for(ch) {
                     let pair_text = format!("{}{}", ch, closer);
                     self.insert_text_at_cursor(&pair_text, cx);
                     self.cursor.col = self.cursor.col.saturating_sub(1);
+                    self.end_undo_transaction();
                     self.marked_range = None;
                     return;
                 }
 
                 if self.should_skip_closing_char(ch) {
+                    self.end_undo_transaction();
                     self.cursor.col += 1;
                     self.marked_range = None;
                     cx.notify();
@@ -2923,6 +7248,7 @@ impl EntityInputHandler for EditorState {
             }
 
             self.insert_text_at_cursor(new_text, cx);
+            self.end_undo_transaction();
         }
         self.marked_range = None;
     }
@@ -2939,16 +7265,27 @@ impl EntityInputHandler for EditorState {
             return;
         }
 
+        // Each update replaces the composition so far, or starts one over the selection.
         let range_utf8 = range_utf16
             .map(|r| self.range_from_utf16(&r))
-            .unwrap_or_else(|| {
-                let cursor_offset = self.pos_to_byte_offset(self.cursor);
-                cursor_offset..cursor_offset
+            .or_else(|| self.marked_range.clone())
+            .unwrap_or_else(|| match &self.selection {
+                Some(sel) => {
+                    let start = self.pos_to_byte_offset(sel.anchor);
+                    let end = self.pos_to_byte_offset(sel.cursor);
+                    start.min(end)..start.max(end)
+                }
+                None => {
+                    let cursor_offset = self.pos_to_byte_offset(self.cursor);
+                    cursor_offset..cursor_offset
+                }
             });
 
         let start_pos = self.byte_offset_to_pos(range_utf8.start);
         let end_pos = self.byte_offset_to_pos(range_utf8.end);
 
+        self.selection = None;
+        self.cursor = start_pos;
         if start_pos != end_pos {
             self.delete_selection_internal(Selection::new(start_pos, end_pos), cx);
         }
@@ -2957,9 +7294,8 @@ impl EntityInputHandler for EditorState {
         self.insert_text_at_cursor(new_text, cx);
         let insert_end = self.pos_to_byte_offset(self.cursor);
 
-        if !new_text.is_empty() {
-            self.marked_range = Some(insert_start..insert_end);
-        }
+        // Empty text cancels the composition.
+        self.marked_range = (!new_text.is_empty()).then_some(insert_start..insert_end);
 
         if let Some(new_sel_utf16) = new_selected_range_utf16 {
             let new_sel_utf8 = self.range_from_utf16(&new_sel_utf16);
@@ -2971,14 +7307,31 @@ impl EntityInputHandler for EditorState {
         cx.notify();
     }
 
+    /// Where the input method puts its candidate window: the glyphs of `range_utf16`,
+    /// up to the end of its first line.
     fn bounds_for_range(
         &mut self,
-        _range_utf16: Range<usize>,
+        range_utf16: Range<usize>,
         _bounds: Bounds<Pixels>,
         _window: &mut Window,
         _cx: &mut Context<Self>,
     ) -> Option<Bounds<Pixels>> {
-        self.last_bounds
+        let range = self.range_from_utf16(&range_utf16);
+        let start = self.byte_offset_to_pos(range.start);
+        let end = self.byte_offset_to_pos(range.end);
+        let end = if end.line == start.line {
+            end
+        } else {
+            Position::new(start.line, self.line_len(start.line))
+        };
+        let (Some(origin), Some(end_origin)) = (self.text_origin(start), self.text_origin(end))
+        else {
+            return self.last_bounds;
+        };
+        Some(Bounds::new(
+            origin,
+            size(end_origin.x - origin.x, self.line_height),
+        ))
     }
 
     fn character_index_for_point(
@@ -2988,11 +7341,7 @@ impl EntityInputHandler for EditorState {
         _cx: &mut Context<Self>,
     ) -> Option<usize> {
         if let Some(bounds) = self.last_bounds {
-            let gutter_width = if self.show_line_numbers {
-                px(80.0)
-            } else {
-                px(12.0)
-            };
+            let gutter_width = self.gutter_width();
             let line_height = self.line_height;
             let pos = self.position_for_mouse(point, bounds, gutter_width, line_height);
             let offset = self.pos_to_byte_offset(pos);
@@ -3050,7 +7399,9 @@ impl Element for EditorElement {
         let num_lines = self.state.read(cx).display_line_count();
         let content_height = padding_top + padding_bottom + (line_height * num_lines as f32);
         let viewport_height = self.state.read(cx).scroll_handle.bounds().size.height;
-        let overscroll = if viewport_height > line_height * 5.0 {
+        let overscroll = if !self.state.read(cx).scroll_past_end {
+            px(0.0)
+        } else if viewport_height > line_height * 5.0 {
             viewport_height / 2.0
         } else {
             px(100.0)
@@ -3074,15 +7425,9 @@ impl Element for EditorElement {
         cx: &mut App,
     ) -> Self::PrepaintState {
         let state = self.state.read(cx);
-        let show_line_numbers = state.show_line_numbers;
-        let line_height = state.line_height;
         PrepaintState {
-            gutter_width: if show_line_numbers {
-                px(80.0)
-            } else {
-                px(12.0)
-            },
-            line_height,
+            gutter_width: state.gutter_width(),
+            line_height: state.line_height,
         }
     }
 
@@ -3102,6 +7447,7 @@ impl Element for EditorElement {
         let line_height = prepaint.line_height;
         let gutter_width = prepaint.gutter_width;
         let font_size = self.state.read(cx).font_size;
+        let scale_factor = window.scale_factor();
 
         window.handle_input(
             &focus_handle,
@@ -3109,8 +7455,11 @@ impl Element for EditorElement {
             cx,
         );
 
-        self.state.update(cx, |state, _| {
+        self.state.update(cx, |state, cx| {
             state.last_bounds = Some(bounds);
+            state.painted_scroll_y = state.scroll_handle.offset().y;
+            state.emit_selection_events(cx);
+            state.sync_linked_editors(cx);
         });
 
         let scroll_offset = self.state.read(cx).scroll_handle.offset();
@@ -3138,10 +7487,32 @@ impl Element for EditorElement {
         let (cursor, selection, show_line_numbers, scroll_offset_x) = {
             let state = self.state.read(cx);
             (
-                state.cursor,
-                state.selection,
-                state.show_line_numbers,
-                state.scroll_offset_x,
+                state.cursor,
+                state.selection,
+                state.show_line_numbers,
+                state.scroll_offset_x,
+            )
+        };
+        let extra_selections = self.state.read(cx).extra_selections.clone();
+        let code_action_lines = self.state.read(cx).code_action_lines.clone();
+        let gutter_decorations: HashMap<usize, (SharedString, Hsla)> = {
+            let s = self.state.read(cx);
+            let mut by_line = HashMap::new();
+            for decoration in s.gutter_decorations.iter().flat_map(|(_, d)| d) {
+                by_line
+                    .entry(decoration.line)
+                    .or_insert_with(|| (decoration.icon.clone(), decoration.color));
+            }
+            by_line
+        };
+        let (diff_hunks, diff_added_color, diff_modified_color, diff_deleted_color, line_count) = {
+            let s = self.state.read(cx);
+            (
+                s.diff_hunks.clone(),
+                s.diff_added_color.unwrap_or(hsla(0.36, 0.55, 0.48, 1.0)),
+                s.diff_modified_color.unwrap_or(hsla(0.58, 0.65, 0.58, 1.0)),
+                s.diff_deleted_color.unwrap_or(hsla(0.0, 0.70, 0.58, 1.0)),
+                s.rope.len_lines(),
             )
         };
 
@@ -3155,9 +7526,11 @@ impl Element for EditorElement {
             indent_guide_color,
             indent_guide_active_color,
             fold_marker_color,
+            ruler_color,
             tab_size,
             folded_ranges,
             fold_ranges,
+            rulers,
         ) = {
             let s = self.state.read(cx);
             (
@@ -3167,7 +7540,7 @@ impl Element for EditorElement {
                 s.line_number_active_color_override
                     .unwrap_or(theme.tokens.foreground),
                 s.current_line_color_override
-                    .unwrap_or(hsla(0.0, 0.0, 1.0, 0.06)),
+                    .unwrap_or(theme.tokens.editor_line_highlight),
                 s.bracket_match_color_override
                     .unwrap_or(hsla(0.58, 0.70, 0.65, 0.60)),
                 s.word_highlight_color_override
@@ -3178,9 +7551,11 @@ impl Element for EditorElement {
                     .unwrap_or(hsla(0.0, 0.0, 1.0, 0.15)),
                 s.fold_marker_color_override
                     .unwrap_or(theme.tokens.muted_foreground),
+                s.ruler_color_override.unwrap_or(hsla(0.0, 0.0, 1.0, 0.10)),
                 s.tab_size,
                 s.folded.clone(),
                 s.fold_ranges.clone(),
+                s.rulers.clone(),
             )
         };
 
@@ -3188,45 +7563,69 @@ impl Element for EditorElement {
         let is_single_cursor =
             selection.is_none() || selection.as_ref().map(|s| s.is_empty()).unwrap_or(true);
 
-        if is_focused && is_single_cursor {
-            if let Some(display_row) = buf_to_disp(cursor.line) {
-                if display_row >= first_visible_display_row
-                    && display_row < last_visible_display_row
-                {
-                    let hl_y = bounds.top() + padding_top + line_height * display_row as f32;
-                    window.paint_quad(fill(
-                        Bounds::new(
-                            point(bounds.left(), hl_y),
-                            size(bounds.size.width, line_height),
-                        ),
-                        current_line_color,
-                    ));
+        let line_highlight = self.state.read(cx).line_highlight;
+        let highlights_line =
+            is_focused && is_single_cursor && line_highlight != LineHighlight::None;
+        let current_line_y = buf_to_disp(cursor.line)
+            .filter(|row| {
+                highlights_line
+                    && *row >= first_visible_display_row
+                    && *row < last_visible_display_row
+            })
+            .map(|row| bounds.top() + padding_top + line_height * row as f32);
+        if let Some(hl_y) = current_line_y {
+            match line_highlight {
+                LineHighlight::Full => window.paint_quad(fill(
+                    Bounds::new(
+                        point(bounds.left(), hl_y),
+                        size(bounds.size.width, line_height),
+                    ),
+                    current_line_color,
+                )),
+                LineHighlight::Border => {
+                    let border_color = self
+                        .state
+                        .read(cx)
+                        .current_line_color_override
+                        .unwrap_or(theme.tokens.editor_line_highlight_border);
+                    let rule = snap_length(px(1.0), scale_factor);
+                    for y in [hl_y, hl_y + line_height - rule] {
+                        window.paint_quad(fill(
+                            Bounds::new(
+                                point(bounds.left() + gutter_width, y).snap_to_device(scale_factor),
+                                size(bounds.size.width - gutter_width, rule),
+                            ),
+                            border_color,
+                        ));
+                    }
                 }
+                // The gutter is painted over the text area's background, so its
+                // highlight goes on after it.
+                LineHighlight::Gutter | LineHighlight::None => {}
             }
         }
 
-        // Cache highlight spans — only recompute when content changes or visible range shifts
+        // Highlight spans are computed in the background, with some rows either side of
+        // the viewport so small scrolls reuse them; stale spans paint until they arrive.
         let content_version = self.state.read(cx).content_version;
         let first_buf = visible_buffer_lines.first().copied().unwrap_or(0);
         let last_buf = visible_buffer_lines.last().copied().unwrap_or(0) + 1;
-        {
+        let needs_rehighlight = {
             let state = self.state.read(cx);
-            let needs_rehighlight = state.highlight_cache_version != content_version
+            state.highlight_cache_version != content_version
                 || first_buf < state.highlight_cache_first_line
-                || last_buf > state.highlight_cache_last_line;
-            if needs_rehighlight {
-                let spans = self.collect_highlight_spans_for_lines(visible_buffer_lines, cx);
-                self.state.update(cx, |state, _| {
-                    state.cached_highlight_spans = spans;
-                    state.highlight_cache_version = content_version;
-                    state.highlight_cache_first_line = first_buf;
-                    state.highlight_cache_last_line = last_buf;
-                });
-            }
+                || last_buf > state.highlight_cache_last_line
+        };
+        if needs_rehighlight && !visible_buffer_lines.is_empty() {
+            let first_row = first_visible_display_row.saturating_sub(HIGHLIGHT_OVERSCAN_ROWS);
+            let last_row = (last_visible_display_row + HIGHLIGHT_OVERSCAN_ROWS).min(display_count);
+            let lines = display_lines_vec[first_row..last_row].to_vec();
+            self.state
+                .update(cx, |state, cx| state.request_highlights(lines, cx));
         }
 
         let text_style = window.text_style();
-        let mut shaped_layouts: Vec<(usize, Option<ShapedLine>, u64)> =
+        let mut shaped_layouts: Vec<(usize, Option<DisplayLineLayout>, u64)> =
             Vec::with_capacity(visible_buffer_lines.len());
         let mut max_line_width = px(0.0);
 
@@ -3246,6 +7645,28 @@ impl Element for EditorElement {
             shaped_space.x_for_index(1)
         };
 
+        let text_left = bounds.left() + gutter_width;
+        for column in &rulers {
+            let ruler_x = text_left + char_width * *column as f32 - scroll_offset_x;
+            if ruler_x < text_left || ruler_x > bounds.right() {
+                continue;
+            }
+            window.paint_quad(fill(
+                Bounds::new(
+                    point(ruler_x, bounds.top()).snap_to_device(scale_factor),
+                    size(snap_length(px(1.0), scale_factor), bounds.size.height),
+                ),
+                ruler_color,
+            ));
+        }
+
+        let scope_guides = {
+            let state = self.state.read(cx);
+            state
+                .scope_guides
+                .then(|| state.scope_guides_in(first_buf..last_buf))
+        };
+
         let cursor_indent = if tab_size > 0 {
             let cursor_line_text = self.state.read(cx).line_text(cursor.line);
             let cursor_leading = cursor_line_text.len() - cursor_line_text.trim_start().len();
@@ -3254,18 +7675,57 @@ impl Element for EditorElement {
             0
         };
 
+        let visible_columns = {
+            let first = (scroll_offset_x / char_width).max(0.0) as usize;
+            let count = ((bounds.size.width - gutter_width) / char_width)
+                .max(0.0)
+                .ceil() as usize;
+            first..first + count + 1
+        };
+
         for display_row in first_visible_display_row..last_visible_display_row {
             let line_idx = display_lines_vec[display_row];
             let y = bounds.top() + padding_top + line_height * display_row as f32;
 
-            let line_text = self.state.read(cx).line_text(line_idx);
-            let leading_spaces = line_text.len() - line_text.trim_start().len();
-            let indent_levels = if tab_size > 0 {
+            let long_line = self
+                .state
+                .read(cx)
+                .long_line_window(line_idx, visible_columns.clone());
+            let (window_start, line_text) = match long_line {
+                Some((start, text)) => (Some(start), text),
+                None => (None, self.state.read(cx).line_text(line_idx)),
+            };
+            // A window's text starts mid-line, so its leading whitespace isn't indentation.
+            let leading_spaces = match window_start {
+                Some(start) if start > 0 => 0,
+                _ => line_text.len() - line_text.trim_start().len(),
+            };
+            let indent_levels = if tab_size > 0 && scope_guides.is_none() {
                 leading_spaces / tab_size
             } else {
                 0
             };
 
+            for guide in scope_guides.iter().flatten() {
+                if !guide.lines.contains(&line_idx) {
+                    continue;
+                }
+                let guide_x = bounds.left() + gutter_width + char_width * guide.column as f32
+                    - scroll_offset_x;
+                let color = if guide.active && is_focused {
+                    indent_guide_active_color
+                } else {
+                    indent_guide_color
+                };
+                window.paint_quad(fill(
+                    Bounds::new(
+                        point(guide_x, y).snap_to_device(scale_factor),
+                        size(snap_length(px(1.0), scale_factor), line_height),
+                    ),
+                    color,
+                ));
+            }
+
             for level in 0..indent_levels {
                 let guide_x = bounds.left() + gutter_width + char_width * (level * tab_size) as f32
                     - scroll_offset_x;
@@ -3275,7 +7735,10 @@ impl Element for EditorElement {
                     indent_guide_color
                 };
                 window.paint_quad(fill(
-                    Bounds::new(point(guide_x, y), size(px(1.0), line_height)),
+                    Bounds::new(
+                        point(guide_x, y).snap_to_device(scale_factor),
+                        size(snap_length(px(1.0), scale_factor), line_height),
+                    ),
                     color,
                 ));
             }
@@ -3285,6 +7748,7 @@ impl Element for EditorElement {
                 use std::hash::{Hash, Hasher};
                 let mut hasher = std::collections::hash_map::DefaultHasher::new();
                 line_text.hash(&mut hasher);
+                window_start.hash(&mut hasher);
                 hasher.finish()
             };
             let cached_layout = {
@@ -3295,45 +7759,125 @@ impl Element for EditorElement {
                     None
                 }
             };
+            let color_literals = if self.state.read(cx).color_swatches && window_start.is_none() {
+                find_color_literals(&line_text)
+            } else {
+                Vec::new()
+            };
+            let line_origin = point(bounds.left() + gutter_width - scroll_offset_x, y);
+
             if let Some(cached) = cached_layout {
-                let line_width = cached.x_for_index(cached.len());
+                let line_width = cached.width();
                 if line_width > max_line_width {
                     max_line_width = line_width;
                 }
-                let _ = cached.paint(
-                    point(bounds.left() + gutter_width - scroll_offset_x, y),
+                cached.paint(line_origin, line_height, window, cx);
+                Self::paint_color_swatches(
+                    &cached,
+                    &color_literals,
+                    line_origin,
                     line_height,
+                    theme.tokens.border,
                     window,
-                    cx,
                 );
                 continue;
             }
 
-            if line_text.is_empty() {
+            let line_hints = if window_start.is_some() {
+                Vec::new()
+            } else {
+                self.state.read(cx).inlay_hints_at_line(line_idx).to_vec()
+            };
+            if line_text.is_empty() && line_hints.is_empty() {
                 shaped_layouts.push((line_idx, None, line_hash));
                 continue;
             }
 
-            let highlight_spans = &self.state.read(cx).cached_highlight_spans;
-            let text_runs =
-                self.build_text_runs(&line_text, line_idx, highlight_spans, &text_style, &theme);
+            let state = self.state.read(cx);
+            let semantic_spans = state.semantic_spans(line_idx);
+            let text_runs = match window_start {
+                Some(start) => slice_text_runs(
+                    self.build_text_runs(
+                        state.line_len(line_idx),
+                        line_idx,
+                        &state.cached_highlight_spans,
+                        &semantic_spans,
+                        &text_style,
+                        &theme,
+                    ),
+                    start..start + line_text.len(),
+                ),
+                None => self.build_text_runs(
+                    line_text.len(),
+                    line_idx,
+                    &state.cached_highlight_spans,
+                    &semantic_spans,
+                    &text_style,
+                    &theme,
+                ),
+            };
+            let (display_text, text_runs, inlays) =
+                if line_hints.is_empty() && color_literals.is_empty() {
+                    (line_text, text_runs, Vec::new())
+                } else {
+                    let hint_run = TextRun {
+                        len: 0,
+                        font: text_style.font(),
+                        color: theme.tokens.muted_foreground,
+                        background_color: Some(theme.tokens.muted.opacity(0.6)),
+                        underline: None,
+                        strikethrough: None,
+                    };
+                    let swatch_run = TextRun {
+                        background_color: None,
+                        ..hint_run.clone()
+                    };
+                    // Swatches sort before hints at the same column so they sit
+                    // directly against their literal.
+                    let mut pending: Vec<(usize, String, TextRun)> = color_literals
+                        .iter()
+                        .map(|literal| {
+                            let text = COLOR_SWATCH_TEXT.to_string();
+                            (literal.range.start, text, swatch_run.clone())
+                        })
+                        .collect();
+                    pending.extend(
+                        line_hints
+                            .iter()
+                            .map(|hint| (hint.position.col, hint.display_text(), hint_run.clone())),
+                    );
+                    pending.sort_by_key(|(col, ..)| *col);
+                    splice_inlay_hints(&line_text, text_runs, pending)
+                };
 
-            let line_len = line_text.len();
-            let shaped =
-                window
-                    .text_system()
-                    .shape_line(line_text.into(), font_size, &text_runs, None);
+            let shaped = DisplayLineLayout {
+                shaped: window.text_system().shape_line(
+                    display_text.into(),
+                    font_size,
+                    &text_runs,
+                    None,
+                ),
+                inlays,
+                window: window_start.map(|start| LineWindow {
+                    start,
+                    line_len: self.state.read(cx).line_len(line_idx),
+                    advance: char_width,
+                }),
+            };
 
-            let line_width = shaped.x_for_index(line_len);
+            let line_width = shaped.width();
             if line_width > max_line_width {
                 max_line_width = line_width;
             }
 
-            let _ = shaped.paint(
-                point(bounds.left() + gutter_width - scroll_offset_x, y),
+            shaped.paint(line_origin, line_height, window, cx);
+            Self::paint_color_swatches(
+                &shaped,
+                &color_literals,
+                line_origin,
                 line_height,
+                theme.tokens.border,
                 window,
-                cx,
             );
 
             shaped_layouts.push((line_idx, Some(shaped), line_hash));
@@ -3375,71 +7919,160 @@ impl Element for EditorElement {
                 transform: Default::default(),
                 blend_mode: Default::default(),
             });
+            if let (Some(hl_y), LineHighlight::Full | LineHighlight::Gutter) =
+                (current_line_y, line_highlight)
+            {
+                window.paint_quad(fill(
+                    Bounds::new(point(bounds.left(), hl_y), size(gutter_width, line_height)),
+                    current_line_color,
+                ));
+            }
 
+            let first_line_number = self.state.read(cx).first_line_number;
+            let lanes = self.state.read(cx).gutter_lanes.clone();
             let mut line_num_buf2 = String::with_capacity(8);
             for display_row in first_visible_display_row..last_visible_display_row {
                 let line_idx = display_lines_vec[display_row];
                 let y = bounds.top() + padding_top + line_height * display_row as f32;
                 let is_current_line = line_idx == cursor.line;
-                let num_color = if is_current_line && is_focused {
-                    line_num_active_color
-                } else {
-                    line_num_color
-                };
-                line_num_buf2.clear();
-                use std::fmt::Write;
-                let _ = write!(line_num_buf2, "{:>4}", line_idx + 1);
-                let num_font = if is_current_line && is_focused {
-                    let mut f = text_style.font();
-                    f.weight = FontWeight::BOLD;
-                    f
-                } else {
-                    text_style.font()
-                };
-                let line_num_run = TextRun {
-                    len: line_num_buf2.len(),
-                    font: num_font,
-                    color: num_color,
-                    background_color: None,
-                    underline: None,
-                    strikethrough: None,
-                };
-                let shaped = window.text_system().shape_line(
-                    SharedString::from(line_num_buf2.clone()),
-                    font_size,
-                    &[line_num_run],
-                    None,
-                );
-                let _ = shaped.paint(point(bounds.left() + px(6.0), y), line_height, window, cx);
-
-                let fold_start = fold_ranges.iter().any(|f| f.start_line == line_idx);
-                let is_folded = folded_ranges.iter().any(|f| f.start_line == line_idx);
-                if fold_start {
-                    let icon_name = if is_folded {
-                        "chevron-right"
-                    } else {
-                        "chevron-down"
+                let mut lane_x = bounds.left();
+                for lane in &lanes {
+                    let cell = GutterCell {
+                        line: line_idx,
+                        bounds: Bounds::new(point(lane_x, y), size(lane.width, line_height)),
+                        is_current: is_current_line && is_focused,
                     };
-                    let icon_path = SharedString::from(resolve_icon_path(icon_name));
-                    let icon_size = px(16.0);
-                    let icon_x = bounds.left() + gutter_width - px(18.0);
-                    let icon_y = y + (line_height - icon_size) / 2.0;
-                    let icon_bounds =
-                        Bounds::new(point(icon_x, icon_y), size(icon_size, icon_size));
-                    let _ = window.paint_svg(
-                        icon_bounds,
-                        icon_path,
-                        TransformationMatrix::default(),
-                        fold_marker_color,
-                        cx,
-                    );
+                    lane_x += lane.width;
+                    match &lane.kind {
+                        GutterLaneKind::LineNumbers => {
+                            let num_color = if cell.is_current {
+                                line_num_active_color
+                            } else {
+                                line_num_color
+                            };
+                            line_num_buf2.clear();
+                            use std::fmt::Write;
+                            let _ = write!(line_num_buf2, "{:>4}", line_idx + first_line_number);
+                            let num_font = if cell.is_current {
+                                let mut f = text_style.font();
+                                f.weight = FontWeight::BOLD;
+                                f
+                            } else {
+                                text_style.font()
+                            };
+                            let line_num_run = TextRun {
+                                len: line_num_buf2.len(),
+                                font: num_font,
+                                color: num_color,
+                                background_color: None,
+                                underline: None,
+                                strikethrough: None,
+                            };
+                            let shaped = window.text_system().shape_line(
+                                SharedString::from(line_num_buf2.clone()),
+                                font_size,
+                                &[line_num_run],
+                                None,
+                            );
+                            let _ = shaped.paint(cell.bounds.origin, line_height, window, cx);
+                        }
+                        GutterLaneKind::Folds => {
+                            if !fold_ranges.iter().any(|f| f.start_line == line_idx) {
+                                continue;
+                            }
+                            let is_folded = folded_ranges.iter().any(|f| f.start_line == line_idx);
+                            let icon_name = if is_folded {
+                                "chevron-right"
+                            } else {
+                                "chevron-down"
+                            };
+                            let icon_path = SharedString::from(resolve_icon_path(icon_name));
+                            let icon_size = px(16.0);
+                            let icon_y = y + (line_height - icon_size) / 2.0;
+                            let icon_bounds = Bounds::new(
+                                point(cell.bounds.left(), icon_y),
+                                size(icon_size, icon_size),
+                            );
+                            let _ = window.paint_svg(
+                                icon_bounds,
+                                icon_path,
+                                TransformationMatrix::default(),
+                                fold_marker_color,
+                                cx,
+                            );
+                        }
+                        GutterLaneKind::Diff => {
+                            let Some(hunk) = diff_hunks
+                                .iter()
+                                .find(|hunk| hunk.covers_line(line_idx, line_count))
+                            else {
+                                continue;
+                            };
+                            let left = cell.bounds.left();
+                            let bar = size(px(3.0), line_height);
+                            let marker = match hunk.kind {
+                                DiffHunkKind::Added => fill(
+                                    Bounds::new(point(left + px(1.0), y), bar),
+                                    diff_added_color,
+                                ),
+                                DiffHunkKind::Modified => fill(
+                                    Bounds::new(point(left + px(1.0), y), bar),
+                                    diff_modified_color,
+                                ),
+                                DiffHunkKind::Deleted => {
+                                    // Drawn on the boundary where the lines were removed.
+                                    let edge_y = if hunk.lines.start >= line_count {
+                                        y + line_height
+                                    } else {
+                                        y
+                                    };
+                                    let wedge = size(px(6.0), px(4.0));
+                                    fill(
+                                        Bounds::new(point(left, edge_y - px(2.0)), wedge),
+                                        diff_deleted_color,
+                                    )
+                                }
+                            };
+                            window.paint_quad(marker);
+                        }
+                        GutterLaneKind::Decorations => {
+                            let slot_icon = match gutter_decorations.get(&line_idx) {
+                                Some((icon, color)) => Some((resolve_icon_path(icon), *color)),
+                                None if code_action_lines.contains(&line_idx) => Some((
+                                    resolve_icon_path("lightbulb"),
+                                    hsla(0.12, 0.90, 0.55, 1.0),
+                                )),
+                                None => None,
+                            };
+                            if let Some((icon_path, color)) = slot_icon {
+                                let icon_size = px(14.0);
+                                let icon_bounds = Bounds::new(
+                                    point(
+                                        cell.bounds.left() + px(2.0),
+                                        y + (line_height - icon_size) / 2.0,
+                                    ),
+                                    size(icon_size, icon_size),
+                                );
+                                let _ = window.paint_svg(
+                                    icon_bounds,
+                                    SharedString::from(icon_path),
+                                    TransformationMatrix::default(),
+                                    color,
+                                    cx,
+                                );
+                            }
+                        }
+                        GutterLaneKind::Custom(paint) => paint(&cell, window, cx),
+                    }
                 }
             }
         }
 
         if is_focused && is_single_cursor {
-            let word_occurrences = self.find_word_occurrences(visible_buffer_lines, cx);
-            for (occ_line, occ_start, occ_end) in &word_occurrences {
+            let word_occurrences = self.state.update(cx, |state, _| {
+                state.cached_word_occurrences(visible_buffer_lines)
+            });
+            for (occ_line, occ_start, occ_end) in word_occurrences.iter() {
                 if let Some(dr) = buf_to_disp(*occ_line) {
                     if let Some(layout) = self.state.read(cx).line_layouts.get(occ_line) {
                         let occ_y = bounds.top() + padding_top + line_height * dr as f32;
@@ -3460,14 +8093,49 @@ impl Element for EditorElement {
             }
         }
 
-        let sel_color = self
-            .state
-            .read(cx)
-            .selection_color_override
-            .unwrap_or(theme.tokens.primary.opacity(0.25));
+        for (occ_line, occ_start, occ_end) in self.state.read(cx).rename_highlights() {
+            if let Some(dr) = buf_to_disp(occ_line) {
+                if let Some(layout) = self.state.read(cx).line_layouts.get(&occ_line) {
+                    let occ_y = bounds.top() + padding_top + line_height * dr as f32;
+                    let x_start = layout.x_for_index(occ_start);
+                    let x_end = layout.x_for_index(occ_end);
+                    window.paint_quad(
+                        fill(
+                            Bounds::new(
+                                point(
+                                    bounds.left() + gutter_width + x_start - scroll_offset_x,
+                                    occ_y,
+                                ),
+                                size(x_end - x_start, line_height),
+                            ),
+                            word_highlight_color,
+                        )
+                        .border_widths(px(1.0))
+                        .border_color(theme.tokens.primary.opacity(0.6)),
+                    );
+                }
+            }
+        }
+
+        let (sel_color, sel_radius) = {
+            let state = self.state.read(cx);
+            (
+                state
+                    .selection_color_override
+                    .unwrap_or(theme.tokens.editor_selection),
+                state
+                    .selection_corner_radius
+                    .unwrap_or(theme.tokens.radius_sm),
+            )
+        };
 
-        if let Some(selection) = &selection {
+        for selection in selection.iter().chain(extra_selections.iter()) {
             let (start, end) = selection.range();
+            // Rows of the selection on consecutive display rows are painted as one
+            // shape; folds and the viewport edges split it.
+            let mut run_top = px(0.0);
+            let mut run: Vec<(Pixels, Pixels)> = Vec::new();
+            let mut last_row = None;
             for line_idx in start.line..=end.line {
                 let dr = match buf_to_disp(line_idx) {
                     Some(d) => d,
@@ -3476,7 +8144,15 @@ impl Element for EditorElement {
                 if dr < first_visible_display_row || dr >= last_visible_display_row {
                     continue;
                 }
-                let line_y = bounds.top() + padding_top + line_height * dr as f32;
+                if last_row.is_some_and(|row| row + 1 != dr) {
+                    paint_selection_run(&run, run_top, line_height, sel_radius, sel_color, window);
+                    run.clear();
+                }
+                if run.is_empty() {
+                    run_top = bounds.top() + padding_top + line_height * dr as f32;
+                }
+                last_row = Some(dr);
+
                 let line_len = self.state.read(cx).line_len(line_idx);
                 let start_col = if line_idx == start.line { start.col } else { 0 };
                 let end_col = if line_idx == end.line {
@@ -3484,24 +8160,20 @@ impl Element for EditorElement {
                 } else {
                     line_len
                 };
-
-                let (sel_x, sel_width) =
+                let (x_start, mut x_end) =
                     if let Some(layout) = self.state.read(cx).line_layouts.get(&line_idx) {
-                        let x_start = layout.x_for_index(start_col);
-                        let x_end = layout.x_for_index(end_col);
-                        (
-                            bounds.left() + gutter_width + x_start - scroll_offset_x,
-                            x_end - x_start,
-                        )
+                        (layout.x_for_index(start_col), layout.x_for_index(end_col))
                     } else {
-                        (bounds.left() + gutter_width - scroll_offset_x, px(0.0))
+                        (px(0.0), px(0.0))
                     };
-
-                window.paint_quad(fill(
-                    Bounds::new(point(sel_x, line_y), size(sel_width, line_height)),
-                    sel_color,
-                ));
+                if line_idx != end.line {
+                    // Show the selected line break, so empty lines read as selected.
+                    x_end += char_width / 2.0;
+                }
+                let left = bounds.left() + gutter_width - scroll_offset_x;
+                run.push((left + x_start, left + x_end));
             }
+            paint_selection_run(&run, run_top, line_height, sel_radius, sel_color, window);
         }
 
         {
@@ -3560,7 +8232,10 @@ impl Element for EditorElement {
         }
 
         if is_focused {
-            if let Some((pos_a, pos_b)) = self.state.read(cx).find_matching_bracket() {
+            let bracket_match = self
+                .state
+                .update(cx, |state, _| state.cached_bracket_match());
+            if let Some((pos_a, pos_b)) = bracket_match {
                 for pos in [pos_a, pos_b] {
                     if let Some(dr) = buf_to_disp(pos.line) {
                         if dr >= first_visible_display_row && dr < last_visible_display_row {
@@ -3644,8 +8319,9 @@ impl Element for EditorElement {
                                 point(
                                     bounds.left() + gutter_width + x_start - scroll_offset_x,
                                     diag_y,
-                                ),
-                                size(underline_width, px(2.0)),
+                                )
+                                .snap_to_device(scale_factor),
+                                size(underline_width, snap_length(px(2.0), scale_factor)),
                             ),
                             underline_color,
                         ));
@@ -3674,12 +8350,127 @@ impl Element for EditorElement {
             }
         }
 
+        {
+            let state = self.state.read(cx);
+            let color = state.spelling_color.unwrap_or(hsla(0.58, 0.75, 0.6, 1.0));
+            let dot = snap_length(px(2.0), scale_factor);
+            for misspelling in &state.spelling_errors {
+                let Some(dr) = buf_to_disp(misspelling.line) else {
+                    continue;
+                };
+                if dr < first_visible_display_row || dr >= last_visible_display_row {
+                    continue;
+                }
+                let Some(layout) = state.line_layouts.get(&misspelling.line) else {
+                    continue;
+                };
+                let x_start = layout.x_for_index(misspelling.range.start);
+                let x_end = layout.x_for_index(misspelling.range.end);
+                let y = bounds.top() + padding_top + line_height * (dr as f32 + 1.0) - dot;
+                let left = bounds.left() + gutter_width - scroll_offset_x;
+                // Dotted: one dot every two dot widths.
+                let mut x = x_start;
+                while x + dot <= x_end {
+                    window.paint_quad(fill(
+                        Bounds::new(
+                            point(left + x, y).snap_to_device(scale_factor),
+                            size(dot, dot),
+                        ),
+                        color,
+                    ));
+                    x += dot * 2.0;
+                }
+            }
+        }
+
+        if let Some(marked) = self.state.read(cx).marked_range.clone() {
+            let state = self.state.read(cx);
+            let color = state.composition_color.unwrap_or(theme.tokens.foreground);
+            let thickness = snap_length(px(1.0), scale_factor);
+            let start = state.byte_offset_to_pos(marked.start);
+            let end = state.byte_offset_to_pos(marked.end);
+            for line in start.line..=end.line {
+                let Some(dr) = buf_to_disp(line) else {
+                    continue;
+                };
+                if dr < first_visible_display_row || dr >= last_visible_display_row {
+                    continue;
+                }
+                let Some(layout) = state.line_layouts.get(&line) else {
+                    continue;
+                };
+                let start_col = if line == start.line { start.col } else { 0 };
+                let end_col = if line == end.line {
+                    end.col
+                } else {
+                    state.line_len(line)
+                };
+                let x_start = layout.x_for_index(start_col);
+                let x_end = layout.x_for_index(end_col);
+                let left = bounds.left() + gutter_width - scroll_offset_x;
+                let top = bounds.top() + padding_top + line_height * dr as f32;
+                window.paint_quad(fill(
+                    Bounds::new(
+                        point(left + x_start, top).snap_to_device(scale_factor),
+                        size(x_end - x_start, line_height),
+                    ),
+                    color.opacity(0.1),
+                ));
+                window.paint_quad(fill(
+                    Bounds::new(
+                        point(left + x_start, top + line_height - thickness * 2.0)
+                            .snap_to_device(scale_factor),
+                        size(x_end - x_start, thickness),
+                    ),
+                    color,
+                ));
+            }
+        }
+
+        if let Some((hover_line, hover_cols)) = self.state.read(cx).navigate_hover.clone() {
+            let visible = buf_to_disp(hover_line)
+                .filter(|dr| *dr >= first_visible_display_row && *dr < last_visible_display_row);
+            if let (Some(dr), Some(layout)) =
+                (visible, self.state.read(cx).line_layouts.get(&hover_line))
+            {
+                let x_start = layout.x_for_index(hover_cols.start);
+                let x_end = layout.x_for_index(hover_cols.end);
+                let underline_y =
+                    bounds.top() + padding_top + line_height * (dr + 1) as f32 - px(3.0);
+                window.paint_quad(fill(
+                    Bounds::new(
+                        point(
+                            bounds.left() + gutter_width + x_start - scroll_offset_x,
+                            underline_y,
+                        )
+                        .snap_to_device(scale_factor),
+                        size(x_end - x_start, snap_length(px(1.0), scale_factor)),
+                    ),
+                    theme.tokens.primary,
+                ));
+            }
+        }
+
         if is_focused {
+            activity::track_window(window, cx);
             let cursor_moved = {
                 let state = self.state.read(cx);
                 state.last_blink_cursor != cursor
             };
-            if cursor_moved {
+            let reduced_motion = crate::animations::reduced_motion(cx);
+            let blinks = self.state.read(cx).cursor_blink_interval.is_some() && !reduced_motion;
+            if !blinks || !activity::should_animate(window, cx) {
+                // Hold the cursor solid while it doesn't blink or nobody's looking. The
+                // editor re-renders when the window comes back to front or the user
+                // returns, which restarts the blink below.
+                let state = self.state.read(cx);
+                if state.blink_task.is_some() || !state.cursor_visible {
+                    self.state.update(cx, |state, _| {
+                        state.blink_task = None;
+                        state.cursor_visible = true;
+                    });
+                }
+            } else if cursor_moved {
                 self.state.update(cx, |state, cx| {
                     state.last_blink_cursor = cursor;
                     state.reset_cursor_blink(cx);
@@ -3692,37 +8483,200 @@ impl Element for EditorElement {
 
             let cursor_visible = self.state.read(cx).cursor_visible;
             if cursor_visible {
-                if let Some(cursor_display_row) = buf_to_disp(cursor.line) {
-                    let total = self.state.read(cx).total_lines();
-                    let cursor_col = if cursor.line < total {
-                        cursor.col.min(self.state.read(cx).line_len(cursor.line))
-                    } else {
-                        0
+                let (shape, cursor_width, smooth_caret) = {
+                    let state = self.state.read(cx);
+                    (state.cursor_shape, state.cursor_width, state.smooth_caret)
+                };
+                let cursor_draw_color = self
+                    .state
+                    .read(cx)
+                    .cursor_color_override
+                    .unwrap_or(theme.tokens.primary);
+                let text_origin = point(
+                    bounds.left() + gutter_width - scroll_offset_x,
+                    bounds.top() + padding_top,
+                );
+                let cursors =
+                    std::iter::once(cursor).chain(extra_selections.iter().map(|sel| sel.cursor));
+                for (ix, cursor) in cursors.enumerate() {
+                    let Some(cursor_display_row) = buf_to_disp(cursor.line) else {
+                        continue;
                     };
-                    let cursor_y =
-                        bounds.top() + padding_top + line_height * cursor_display_row as f32;
-                    let cursor_x = if let Some(layout) =
-                        self.state.read(cx).line_layouts.get(&cursor.line)
-                    {
-                        bounds.left() + gutter_width + layout.x_for_index(cursor_col)
-                            - scroll_offset_x
+                    let state = self.state.read(cx);
+                    let cursor_col = if cursor.line < state.total_lines() {
+                        cursor.col.min(state.line_len(cursor.line))
                     } else {
-                        bounds.left() + gutter_width - scroll_offset_x
+                        0
                     };
+                    let layout = state.line_layouts.get(&cursor.line);
+                    let cursor_x = layout.map_or(px(0.0), |layout| layout.x_for_index(cursor_col));
+                    let char_advance = layout
+                        .zip(
+                            state
+                                .line_text(cursor.line)
+                                .get(cursor_col..)
+                                .and_then(|rest| rest.chars().next()),
+                        )
+                        .map(|(layout, ch)| {
+                            layout.x_for_index(cursor_col + ch.len_utf8()) - cursor_x
+                        })
+                        .filter(|advance| *advance > px(0.0))
+                        .unwrap_or(char_width);
+                    let mut position = point(cursor_x, line_height * cursor_display_row as f32);
+
+                    // Only the primary caret glides; extra carets move together with
+                    // it and would only blur.
+                    if ix == 0 {
+                        let glide = self.state.read(cx).caret_glide;
+                        if smooth_caret && !reduced_motion {
+                            let glide = match glide {
+                                Some(glide) if glide.to == position => glide,
+                                Some(glide) => CaretGlide {
+                                    from: glide.position().0,
+                                    to: position,
+                                    started: std::time::Instant::now(),
+                                },
+                                None => CaretGlide {
+                                    from: position,
+                                    to: position,
+                                    started: std::time::Instant::now(),
+                                },
+                            };
+                            let (drawn, moving) = glide.position();
+                            if moving {
+                                window.request_animation_frame();
+                            }
+                            position = drawn;
+                            self.state
+                                .update(cx, |state, _| state.caret_glide = Some(glide));
+                        } else if glide.is_some() {
+                            self.state.update(cx, |state, _| state.caret_glide = None);
+                        }
+                    }
 
-                    let cursor_draw_color = self
-                        .state
-                        .read(cx)
-                        .cursor_color_override
-                        .unwrap_or(theme.tokens.primary);
+                    let origin = text_origin + position;
+                    let (quad_bounds, color) = match shape {
+                        CursorShape::Bar => (
+                            Bounds::new(
+                                origin.snap_to_device(scale_factor),
+                                size(snap_length(cursor_width, scale_factor), line_height),
+                            ),
+                            cursor_draw_color,
+                        ),
+                        CursorShape::Block => (
+                            Bounds::new(
+                                origin.snap_to_device(scale_factor),
+                                size(char_advance, line_height),
+                            ),
+                            cursor_draw_color.opacity(0.5),
+                        ),
+                        CursorShape::Underline => {
+                            let thickness = snap_length(cursor_width, scale_factor);
+                            (
+                                Bounds::new(
+                                    point(origin.x, origin.y + line_height - thickness)
+                                        .snap_to_device(scale_factor),
+                                    size(char_advance, thickness),
+                                ),
+                                cursor_draw_color,
+                            )
+                        }
+                    };
+                    window.paint_quad(fill(quad_bounds, color));
+                }
+            }
+        } else if self.state.read(cx).blink_task.is_some() {
+            self.state.update(cx, |state, _| state.blink_task = None);
+        }
 
+        let sticky_lines = if self.state.read(cx).sticky_scroll {
+            self.state
+                .read(cx)
+                .sticky_scope_lines(first_visible_display_row)
+        } else {
+            Vec::new()
+        };
+        if !sticky_lines.is_empty() {
+            let viewport_top = bounds.top() - scroll_offset.y;
+            let header_width = bounds.size.width;
+            let numbers_left = self
+                .state
+                .read(cx)
+                .gutter_lane_left(GutterLane::LINE_NUMBERS);
+            let spans = self.collect_highlight_spans_for_lines(&sticky_lines, cx);
+            for (row, &line_idx) in sticky_lines.iter().enumerate() {
+                let y = viewport_top + line_height * row as f32;
+                window.paint_quad(fill(
+                    Bounds::new(point(bounds.left(), y), size(header_width, line_height)),
+                    theme.tokens.background,
+                ));
+                let line_text = self.state.read(cx).line_text(line_idx);
+                if !line_text.is_empty() {
+                    let semantic_spans = self.state.read(cx).semantic_spans(line_idx);
+                    let runs = self.build_text_runs(
+                        line_text.len(),
+                        line_idx,
+                        &spans,
+                        &semantic_spans,
+                        &text_style,
+                        &theme,
+                    );
+                    let shaped =
+                        window
+                            .text_system()
+                            .shape_line(line_text.into(), font_size, &runs, None);
+                    let _ = shaped.paint(
+                        point(bounds.left() + gutter_width - scroll_offset_x, y),
+                        line_height,
+                        window,
+                        cx,
+                    );
+                }
+                if show_line_numbers {
                     window.paint_quad(fill(
-                        Bounds::new(point(cursor_x, cursor_y), size(px(2.0), line_height)),
-                        cursor_draw_color,
+                        Bounds::new(point(bounds.left(), y), size(gutter_width, line_height)),
+                        gutter_bg_color,
                     ));
+                    let number = format!("{:>4}", line_idx + self.state.read(cx).first_line_number);
+                    let number_run = TextRun {
+                        len: number.len(),
+                        font: text_style.font(),
+                        color: line_num_color,
+                        background_color: None,
+                        underline: None,
+                        strikethrough: None,
+                    };
+                    let shaped = window.text_system().shape_line(
+                        number.into(),
+                        font_size,
+                        &[number_run],
+                        None,
+                    );
+                    if let Some(numbers_left) = numbers_left {
+                        let _ = shaped.paint(
+                            point(bounds.left() + numbers_left, y),
+                            line_height,
+                            window,
+                            cx,
+                        );
+                    }
                 }
             }
+            window.paint_quad(fill(
+                Bounds::new(
+                    point(
+                        bounds.left(),
+                        viewport_top + line_height * sticky_lines.len() as f32,
+                    )
+                    .snap_to_device(scale_factor),
+                    size(header_width, snap_length(px(1.0), scale_factor)),
+                ),
+                theme.tokens.border,
+            ));
         }
+        self.state.update(cx, |state, _| {
+            state.sticky_header_lines = sticky_lines;
+        });
     }
 }
 
@@ -3733,143 +8687,323 @@ struct HighlightSpan {
     color: Hsla,
 }
 
-impl EditorElement {
-    fn find_word_occurrences(
-        &self,
-        visible_lines: &[usize],
-        cx: &App,
-    ) -> Vec<(usize, usize, usize)> {
-        let state = self.state.read(cx);
-        let word = match state.word_under_cursor_full() {
-            Some((w, _, _)) => w,
-            None => return Vec::new(),
-        };
-        let mut results = Vec::new();
-        for &line_idx in visible_lines {
-            let line_text = state.line_text(line_idx);
-            let mut search_from = 0;
-            while let Some(pos) = line_text[search_from..].find(&word) {
-                let abs_start = search_from + pos;
-                let abs_end = abs_start + word.len();
-                let before_ok = abs_start == 0
-                    || !line_text.as_bytes()[abs_start - 1].is_ascii_alphanumeric()
-                        && line_text.as_bytes()[abs_start - 1] != b'_';
-                let after_ok = abs_end >= line_text.len()
-                    || !line_text.as_bytes()[abs_end].is_ascii_alphanumeric()
-                        && line_text.as_bytes()[abs_end] != b'_';
-                if before_ok && after_ok {
-                    results.push((line_idx, abs_start, abs_end));
-                }
-                search_from = abs_start + 1;
+/// A highlight capture on one line, before it is given a color. `capture` indexes the
+/// query's capture names.
+struct CaptureSpan {
+    line: usize,
+    start_col: usize,
+    end_col: usize,
+    capture: usize,
+}
+
+/// Byte length of a rope line without its line ending.
+/// Paint the selection on consecutive rows starting at `top`, given as each row's left
+/// and right edge, as one outline with rounded corners. Every row after the first
+/// starts at the same x, as a selection's rows do.
+fn paint_selection_run(
+    rows: &[(Pixels, Pixels)],
+    top: Pixels,
+    line_height: Pixels,
+    radius: Pixels,
+    color: Hsla,
+    window: &mut Window,
+) {
+    let mut rows = rows;
+    if let [rest @ .., (start, end)] = rows {
+        if end <= start {
+            // A selection ending at the start of a line selects nothing on it.
+            rows = rest;
+        }
+    }
+    let (Some(&(first_start, first_end)), Some(&(last_start, _))) = (rows.first(), rows.last())
+    else {
+        return;
+    };
+    let radius = radius.min(line_height / 2.0);
+    if radius <= px(0.0) {
+        for (ix, &(start, end)) in rows.iter().enumerate() {
+            window.paint_quad(fill(
+                Bounds::new(
+                    point(start, top + line_height * ix as f32),
+                    size(end - start, line_height),
+                ),
+                color,
+            ));
+        }
+        return;
+    }
+
+    let curve_height = point(px(0.0), radius);
+    let curve_width = |start: Pixels, end: Pixels| point(radius.min((end - start) / 2.0), px(0.0));
+    let first_top_left = point(first_start, top);
+    let first_top_right = point(first_end, top);
+    let top_curve_width = curve_width(first_start, first_end);
+
+    let mut path = PathBuilder::fill();
+    path.move_to(first_top_right - top_curve_width);
+    path.curve_to(first_top_right + curve_height, first_top_right);
+
+    // Down the right side, turning at each step in or out.
+    for (ix, &(start, end)) in rows.iter().enumerate() {
+        let bottom_right = point(end, top + line_height * (ix + 1) as f32);
+        match rows.get(ix + 1) {
+            Some(&(_, next_end)) if next_end < end => {
+                let width = curve_width(next_end, end);
+                let next_top_right = point(next_end, bottom_right.y);
+                path.line_to(bottom_right - curve_height);
+                path.curve_to(bottom_right - width, bottom_right);
+                path.line_to(next_top_right + width);
+                path.curve_to(next_top_right + curve_height, next_top_right);
+            }
+            Some(&(_, next_end)) if next_end > end => {
+                let width = curve_width(end, next_end);
+                let next_top_right = point(next_end, bottom_right.y);
+                path.line_to(bottom_right - curve_height);
+                path.curve_to(bottom_right + width, bottom_right);
+                path.line_to(next_top_right - width);
+                path.curve_to(next_top_right + curve_height, next_top_right);
+            }
+            Some(_) => path.line_to(bottom_right),
+            None => {
+                let width = curve_width(start, end);
+                let bottom_left = point(start, bottom_right.y);
+                path.line_to(bottom_right - curve_height);
+                path.curve_to(bottom_right - width, bottom_right);
+                path.line_to(bottom_left + width);
+                path.curve_to(bottom_left - curve_height, bottom_left);
             }
         }
-        results
     }
 
-    fn collect_highlight_spans_for_lines(
-        &self,
-        visible_lines: &[usize],
-        cx: &App,
-    ) -> Vec<HighlightSpan> {
-        if visible_lines.is_empty() {
-            return Vec::new();
-        }
-
-        let state = self.state.read(cx);
-        let tree = match &state.syntax_tree {
-            Some(t) => t,
-            None => return Vec::new(),
-        };
-
-        let query = match &state.highlight_query {
-            Some(q) => q,
-            None => return Vec::new(),
-        };
+    // Up the left side, which only steps where the first row starts further in.
+    if first_start > last_start {
+        let width = curve_width(last_start, first_start);
+        let second_top_left = point(last_start, top + line_height);
+        let first_bottom_left = point(first_start, second_top_left.y);
+        path.line_to(second_top_left + curve_height);
+        path.curve_to(second_top_left + width, second_top_left);
+        path.line_to(first_bottom_left - width);
+        path.curve_to(first_bottom_left - curve_height, first_bottom_left);
+    }
+    path.line_to(first_top_left + curve_height);
+    path.curve_to(first_top_left + top_curve_width, first_top_left);
+    path.line_to(first_top_right - top_curve_width);
 
-        let rope = &state.rope;
-        let total_lines = rope.len_lines();
-        let mut spans = Vec::new();
+    if let Ok(path) = path.build() {
+        window.paint_path(path, color);
+    }
+}
 
-        let mut chunk_start = 0usize;
-        while chunk_start < visible_lines.len() {
-            let mut chunk_end = chunk_start;
-            while chunk_end + 1 < visible_lines.len()
-                && visible_lines[chunk_end + 1] == visible_lines[chunk_end] + 1
-            {
-                chunk_end += 1;
-            }
+fn line_content_len(line: RopeSlice) -> usize {
+    let mut len = line.len_bytes();
+    for terminator in [b'\n', b'\r'] {
+        if len > 0 && line.byte(len - 1) == terminator {
+            len -= 1;
+        }
+    }
+    len
+}
 
-            let first_line = visible_lines[chunk_start];
-            let last_line = visible_lines[chunk_end] + 1;
+/// Captures of the highlight `query` on buffer `lines` (ascending), split per line.
+/// Runs without the editor state so it can move to a background thread.
+fn highlight_captures(
+    rope: &Rope,
+    tree: &Tree,
+    query: &Query,
+    lines: &[usize],
+) -> Vec<CaptureSpan> {
+    let total_lines = rope.len_lines();
+    let line_end = |line: usize| rope.line_to_byte(line) + line_content_len(rope.line(line));
+    let mut spans = Vec::new();
+
+    let mut chunk_start = 0usize;
+    while chunk_start < lines.len() {
+        let mut chunk_end = chunk_start;
+        while chunk_end + 1 < lines.len() && lines[chunk_end + 1] == lines[chunk_end] + 1 {
+            chunk_end += 1;
+        }
+
+        let first_line = lines[chunk_start];
+        let last_line = (lines[chunk_end] + 1).min(total_lines);
+        let first_byte = rope.line_to_byte(first_line.min(total_lines));
+        let last_byte = if last_line < total_lines {
+            rope.line_to_byte(last_line)
+        } else {
+            rope.len_bytes()
+        };
 
-            let first_byte = rope.line_to_byte(first_line);
-            let last_byte = if last_line < total_lines {
-                rope.line_to_byte(last_line)
-            } else {
-                rope.len_bytes()
-            };
+        let mut cursor = QueryCursor::new();
+        cursor.set_byte_range(first_byte..last_byte);
 
-            let mut cursor = QueryCursor::new();
-            cursor.set_byte_range(first_byte..last_byte);
+        let mut matches = cursor.matches(query, tree.root_node(), |node: tree_sitter::Node| {
+            let range = node.byte_range();
+            let text: String = rope
+                .byte_slice(range.start..range.end.min(rope.len_bytes()))
+                .into();
+            std::iter::once(text)
+        });
 
-            let mut matches = cursor.matches(query, tree.root_node(), |node: tree_sitter::Node| {
-                let range = node.byte_range();
-                let text: String = rope
-                    .byte_slice(range.start..range.end.min(rope.len_bytes()))
-                    .into();
-                std::iter::once(text)
-            });
+        while let Some(m) = matches.next() {
+            for capture in m.captures {
+                let start_byte = capture.node.start_byte();
+                let end_byte = capture.node.end_byte();
+                let start_line = rope.byte_to_line(start_byte.min(rope.len_bytes()));
+                let end_line = rope.byte_to_line(end_byte.min(rope.len_bytes().saturating_sub(1)));
+
+                for line in start_line.max(first_line)..end_line.min(last_line - 1) + 1 {
+                    let line_start_byte = rope.line_to_byte(line);
+                    let span_start = start_byte.max(line_start_byte) - line_start_byte;
+                    let span_end = end_byte.min(line_end(line)).saturating_sub(line_start_byte);
+                    if span_start < span_end {
+                        spans.push(CaptureSpan {
+                            line,
+                            start_col: span_start,
+                            end_col: span_end,
+                            capture: capture.index as usize,
+                        });
+                    }
+                }
+            }
+        }
 
-            while let Some(m) = matches.next() {
-                for capture in m.captures {
-                    let capture_name = &query.capture_names()[capture.index as usize];
-                    let node = capture.node;
-                    let start_byte = node.start_byte();
-                    let end_byte = node.end_byte();
-                    let color = if let Some(ref color_fn) = state.syntax_color_fn {
-                        color_fn(capture_name)
-                    } else {
-                        highlight_color_for_capture(capture_name)
-                    };
+        chunk_start = chunk_end + 1;
+    }
 
-                    let start_line = rope.byte_to_line(start_byte);
-                    let end_line =
-                        rope.byte_to_line(end_byte.min(rope.len_bytes().saturating_sub(1)));
+    spans
+}
 
-                    for line in start_line..=end_line {
-                        if line < first_line || line >= last_line {
-                            continue;
-                        }
-                        let line_start_byte = rope.line_to_byte(line);
-                        let line_text = state.line_text(line);
-                        let line_end_byte = line_start_byte + line_text.len();
-
-                        let span_start = start_byte.max(line_start_byte) - line_start_byte;
-                        let span_end = end_byte.min(line_end_byte) - line_start_byte;
-
-                        if span_start < span_end {
-                            spans.push(HighlightSpan {
-                                line,
-                                start_col: span_start,
-                                end_col: span_end,
-                                color,
-                            });
-                        }
+/// Byte ranges to spell check on each of `lines`: comment and string captures, or in
+/// Markdown everything but code, links and URLs. Sorted and disjoint per line.
+fn prose_ranges(
+    rope: &Rope,
+    query: &Query,
+    captures: &[CaptureSpan],
+    lines: &[usize],
+    markdown: bool,
+) -> Vec<(usize, Vec<Range<usize>>)> {
+    let names = query.capture_names();
+    let is_prose = |name: &str| name.starts_with("comment") || name == "string";
+    let is_code = |name: &str| {
+        ["text.literal", "markup.raw", "text.uri", "markup.link"]
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+    };
+
+    let mut by_line: HashMap<usize, Vec<Range<usize>>> = HashMap::new();
+    for capture in captures {
+        let name = names[capture.capture];
+        if (markdown && is_code(name)) || (!markdown && is_prose(name)) {
+            by_line
+                .entry(capture.line)
+                .or_default()
+                .push(capture.start_col..capture.end_col);
+        }
+    }
+
+    lines
+        .iter()
+        .filter(|&&line| line < rope.len_lines())
+        .filter_map(|&line| {
+            let mut ranges = by_line.remove(&line).unwrap_or_default();
+            ranges.sort_by_key(|range| range.start);
+            let mut merged: Vec<Range<usize>> = Vec::new();
+            for range in ranges {
+                match merged.last_mut() {
+                    Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                    _ => merged.push(range),
+                }
+            }
+            if markdown {
+                // Check the gaps between the code spans instead.
+                let len = line_content_len(rope.line(line));
+                let mut gaps = Vec::new();
+                let mut start = 0;
+                for range in merged.into_iter().chain(Some(len..len)) {
+                    if range.start > start {
+                        gaps.push(start..range.start);
                     }
+                    start = start.max(range.end);
                 }
+                merged = gaps;
+            }
+            (!merged.is_empty()).then_some((line, merged))
+        })
+        .collect()
+}
+
+fn find_misspellings(
+    rope: &Rope,
+    prose: &[(usize, Vec<Range<usize>>)],
+    checker: &SpellChecker,
+) -> Vec<Misspelling> {
+    let mut misspellings = Vec::new();
+    for (line, ranges) in prose {
+        let text = rope.line(*line).to_string();
+        for range in ranges {
+            let Some(prose) = text.get(range.clone()) else {
+                continue;
+            };
+            for word in misspelled_words(prose, checker) {
+                let word = range.start + word.start..range.start + word.end;
+                misspellings.push(Misspelling {
+                    line: *line,
+                    word: text[word.clone()].to_string(),
+                    range: word,
+                });
             }
+        }
+    }
+    misspellings
+}
 
-            chunk_start = chunk_end + 1;
+impl EditorElement {
+    /// Paint a chip of each literal's color into its placeholder in `layout`.
+    fn paint_color_swatches(
+        layout: &DisplayLineLayout,
+        literals: &[ColorLiteral],
+        origin: Point<Pixels>,
+        line_height: Pixels,
+        border_color: Hsla,
+        window: &mut Window,
+    ) {
+        for literal in literals {
+            let (left, right) = layout.color_swatch_bounds(literal.range.start);
+            let chip = (line_height * 0.6).min(right - left - px(2.0)).max(px(4.0));
+            let chip_origin = point(
+                origin.x + left + (right - left - chip) / 2.0,
+                origin.y + (line_height - chip) / 2.0,
+            );
+            window.paint_quad(PaintQuad {
+                bounds: Bounds::new(chip_origin, size(chip, chip))
+                    .snap_to_device(window.scale_factor()),
+                corner_radii: Corners::all(px(2.0)),
+                background: literal.color.into(),
+                border_widths: Edges::all(px(1.0)),
+                border_color,
+                border_style: BorderStyle::default(),
+                continuous_corners: false,
+                transform: Default::default(),
+                blend_mode: Default::default(),
+            });
         }
+    }
 
-        spans
+    /// Highlight a few lines inline, for sticky headers outside the cached range.
+    fn collect_highlight_spans_for_lines(&self, lines: &[usize], cx: &App) -> Vec<HighlightSpan> {
+        let state = self.state.read(cx);
+        let (Some(tree), Some(query)) = (&state.syntax_tree, &state.highlight_query) else {
+            return Vec::new();
+        };
+        let captures = highlight_captures(&state.rope, tree, query, lines);
+        state.color_captures(query, captures)
     }
 
     fn build_text_runs(
         &self,
-        line_text: &str,
+        text_len: usize,
         line_idx: usize,
         highlight_spans: &[HighlightSpan],
+        semantic_spans: &[(Range<usize>, Hsla)],
         text_style: &gpui::TextStyle,
         theme: &crate::theme::Theme,
     ) -> Vec<TextRun> {
@@ -3878,10 +9012,16 @@ impl EditorElement {
             .filter(|s| s.line == line_idx)
             .collect();
         line_spans.sort_by_key(|s| s.start_col);
+        let line_spans = layer_spans(
+            line_spans
+                .into_iter()
+                .map(|span| (span.start_col..span.end_col, span.color)),
+            semantic_spans,
+        );
 
         if line_spans.is_empty() {
             return vec![TextRun {
-                len: line_text.len(),
+                len: text_len,
                 font: text_style.font(),
                 color: theme.tokens.foreground,
                 background_color: None,
@@ -3890,13 +9030,12 @@ impl EditorElement {
             }];
         }
 
-        let text_len = line_text.len();
         let mut runs = Vec::new();
         let mut pos = 0;
 
-        for span in &line_spans {
-            let start = span.start_col.min(text_len).max(pos);
-            let end = span.end_col.min(text_len);
+        for (range, color) in &line_spans {
+            let start = range.start.min(text_len).max(pos);
+            let end = range.end.min(text_len);
             if end <= start {
                 continue;
             }
@@ -3913,7 +9052,7 @@ impl EditorElement {
             runs.push(TextRun {
                 len: end - start,
                 font: text_style.font(),
-                color: span.color,
+                color: *color,
                 background_color: None,
                 underline: None,
                 strikethrough: None,
@@ -3948,6 +9087,135 @@ impl EditorElement {
     }
 }
 
+struct MinimapElement {
+    state: Entity<EditorState>,
+}
+
+impl IntoElement for MinimapElement {
+    type Element = Self;
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl Element for MinimapElement {
+    type RequestLayoutState = ();
+    type PrepaintState = ();
+
+    fn id(&self) -> Option<ElementId> {
+        None
+    }
+
+    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&gpui::InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let mut layout_style = gpui::Style::default();
+        layout_style.size.width = relative(1.).into();
+        layout_style.size.height = relative(1.).into();
+        (window.request_layout(layout_style, [], cx), ())
+    }
+
+    fn prepaint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&gpui::InspectorElementId>,
+        _bounds: Bounds<Pixels>,
+        _request_layout: &mut Self::RequestLayoutState,
+        _window: &mut Window,
+        _cx: &mut App,
+    ) -> Self::PrepaintState {
+    }
+
+    fn paint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&gpui::InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        _request_layout: &mut Self::RequestLayoutState,
+        _prepaint: &mut Self::PrepaintState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let theme = use_theme();
+        self.state.update(cx, |state, _| {
+            state.minimap_bounds = Some(bounds);
+        });
+
+        let state = self.state.read(cx);
+        let row_height = px(MINIMAP_ROW_HEIGHT);
+        let char_width = px(MINIMAP_CHAR_WIDTH);
+        let offset = state.minimap_offset(bounds.size.height);
+        let display_lines = state.display_lines();
+        let first_row = (offset / row_height).floor().max(0.0) as usize;
+        let row_count = (bounds.size.height / row_height).ceil() as usize + 1;
+        let last_row = min(first_row + row_count, display_lines.len());
+        let text_color = theme.tokens.foreground.opacity(0.35);
+        let max_cols = (bounds.size.width / char_width).floor() as usize;
+
+        window.with_content_mask(Some(ContentMask { bounds }), |window| {
+            window.paint_quad(fill(bounds, theme.tokens.muted.opacity(0.15)));
+
+            for row in first_row..last_row {
+                let line_idx = display_lines[row];
+                let y = bounds.top() + row_height * row as f32 - offset;
+                if line_idx == state.cursor.line {
+                    window.paint_quad(fill(
+                        Bounds::new(point(bounds.left(), y), size(bounds.size.width, row_height)),
+                        theme.tokens.primary.opacity(0.35),
+                    ));
+                }
+
+                let line_text = state.line_text(line_idx);
+                let mut run_start: Option<usize> = None;
+                let mut col = 0usize;
+                for ch in line_text.chars().chain(std::iter::once(' ')) {
+                    if col > max_cols {
+                        break;
+                    }
+                    if ch.is_whitespace() {
+                        if let Some(start) = run_start.take() {
+                            window.paint_quad(fill(
+                                Bounds::new(
+                                    point(bounds.left() + char_width * start as f32, y),
+                                    size(char_width * (col - start) as f32, row_height * 0.8),
+                                ),
+                                text_color,
+                            ));
+                        }
+                    } else if run_start.is_none() {
+                        run_start = Some(col);
+                    }
+                    col += if ch == '\t' { state.tab_size.max(1) } else { 1 };
+                }
+            }
+
+            let padding_top = px(12.0);
+            let viewport_height = state.scroll_handle.bounds().size.height;
+            let scroll_y = -state.scroll_handle.offset().y;
+            let first_visible = ((scroll_y - padding_top) / state.line_height).max(0.0);
+            let visible_rows = viewport_height / state.line_height;
+            let slider_y = bounds.top() + row_height * first_visible - offset;
+            let slider_height = (row_height * visible_rows).max(px(8.0));
+            let slider_opacity = if state.dragging_minimap { 0.16 } else { 0.08 };
+            window.paint_quad(fill(
+                Bounds::new(
+                    point(bounds.left(), slider_y),
+                    size(bounds.size.width, slider_height),
+                ),
+                theme.tokens.foreground.opacity(slider_opacity),
+            ));
+        });
+    }
+}
+
 #[derive(IntoElement)]
 pub struct Editor {
     state: Entity<EditorState>,
@@ -3967,7 +9235,9 @@ pub struct Editor {
     indent_guide_color: Option<Hsla>,
     indent_guide_active_color: Option<Hsla>,
     fold_marker_color: Option<Hsla>,
+    ruler_color: Option<Hsla>,
     syntax_color_fn: Option<Box<dyn Fn(&str) -> Hsla>>,
+    show_minimap: bool,
 }
 
 impl Editor {
@@ -3990,7 +9260,9 @@ impl Editor {
             indent_guide_color: None,
             indent_guide_active_color: None,
             fold_marker_color: None,
+            ruler_color: None,
             syntax_color_fn: None,
+            show_minimap: false,
         }
     }
 
@@ -4024,6 +9296,145 @@ impl Editor {
         self
     }
 
+    /// Keep the first line of each enclosing function, impl or class pinned to the top
+    /// of the editor while its body is scrolled. Clicking a pinned line jumps to it.
+    pub fn sticky_scroll(self, enabled: bool, cx: &mut App) -> Self {
+        self.state.update(cx, |state, cx| {
+            state.sticky_scroll = enabled;
+            cx.notify();
+        });
+        self
+    }
+
+    /// Replace indent guides with guides computed from the syntax tree: one line per
+    /// bracketed block, spanning exactly its body, with the block around the cursor
+    /// drawn in the active guide color. Needs a language with a tree-sitter grammar.
+    pub fn scope_guides(self, enabled: bool, cx: &mut App) -> Self {
+        self.state.update(cx, |state, cx| {
+            state.scope_guides = enabled;
+            cx.notify();
+        });
+        self
+    }
+
+    /// Ease mouse-wheel notches, `goto_line` and search-match jumps over about 120ms
+    /// instead of snapping. Trackpad scrolling is already continuous and is left alone,
+    /// and everything snaps while `crate::animations::reduced_motion` is set.
+    pub fn smooth_scroll(self, enabled: bool, cx: &mut App) -> Self {
+        self.state.update(cx, |state, _| {
+            state.smooth_scroll = enabled;
+            if !enabled {
+                state.smooth_scroller.stop();
+            }
+        });
+        self
+    }
+
+    /// Add a lane to the gutter, e.g. breakpoints or test-run buttons. See
+    /// [`GutterLane`].
+    pub fn gutter_lane(self, lane: GutterLane, cx: &mut App) -> Self {
+        self.state
+            .update(cx, |state, cx| state.add_gutter_lane(lane, cx));
+        self
+    }
+
+    /// How to mark the line holding the cursor: fill it (the default), fill only its
+    /// gutter, rule it above and below, or not at all. Colors come from the theme's
+    /// `editor_line_highlight` tokens unless `current_line_color` is set.
+    pub fn line_highlight(self, mode: LineHighlight, cx: &mut App) -> Self {
+        self.state.update(cx, |state, cx| {
+            state.line_highlight = mode;
+            cx.notify();
+        });
+        self
+    }
+
+    /// Round the corners of selections by `radius`, the theme's `radius_sm` by
+    /// default. Zero paints square corners.
+    pub fn selection_corner_radius(self, radius: Pixels, cx: &mut App) -> Self {
+        self.state.update(cx, |state, cx| {
+            state.selection_corner_radius = Some(radius);
+            cx.notify();
+        });
+        self
+    }
+
+    /// Draw the caret as a bar (the default), a block over the character or an
+    /// underline.
+    pub fn cursor_shape(self, shape: CursorShape, cx: &mut App) -> Self {
+        self.state.update(cx, |state, cx| {
+            state.cursor_shape = shape;
+            cx.notify();
+        });
+        self
+    }
+
+    /// Width of a bar caret, or thickness of an underline one. Defaults to 2px.
+    pub fn cursor_width(self, width: Pixels, cx: &mut App) -> Self {
+        self.state.update(cx, |state, cx| {
+            state.cursor_width = width;
+            cx.notify();
+        });
+        self
+    }
+
+    /// Time between caret blinks, 500ms by default; `None` keeps the caret solid, as
+    /// does `crate::animations::reduced_motion`.
+    pub fn cursor_blink(self, interval: Option<Duration>, cx: &mut App) -> Self {
+        self.state.update(cx, |state, cx| {
+            if state.cursor_blink_interval != interval {
+                state.cursor_blink_interval = interval;
+                // Restarted at the new rate on the next paint.
+                state.blink_task = None;
+                cx.notify();
+            }
+        });
+        self
+    }
+
+    /// Glide the caret to where it moves over about 90ms instead of jumping. It jumps
+    /// anyway while `crate::animations::reduced_motion` is set.
+    pub fn smooth_caret(self, enabled: bool, cx: &mut App) -> Self {
+        self.state.update(cx, |state, _| {
+            state.smooth_caret = enabled;
+            if !enabled {
+                state.caret_glide = None;
+            }
+        });
+        self
+    }
+
+    /// Show a color chip before `#rrggbb`, `rgb()` and `hsl()` literals. Clicks on a chip
+    /// go to `EditorState::on_color_swatch_click`.
+    pub fn color_swatches(self, enabled: bool, cx: &mut App) -> Self {
+        self.state.update(cx, |state, cx| {
+            state.color_swatches = enabled;
+            state.invalidate_line_layouts(cx);
+        });
+        self
+    }
+
+    /// Show a thumbnail, with its dimensions and file size, when hovering an image path
+    /// in a string literal or markdown link. See `EditorState::set_image_resolver`.
+    pub fn image_hovers(self, enabled: bool, cx: &mut App) -> Self {
+        self.state.update(cx, |state, cx| {
+            state.image_hovers = enabled;
+            if !enabled {
+                state.update_image_hover(None, cx);
+            }
+        });
+        self
+    }
+
+    /// Draw thin vertical guides at the given columns, e.g. `vec![80, 100]`.
+    pub fn rulers(self, columns: Vec<usize>, cx: &mut App) -> Self {
+        self.state.update(cx, |state, cx| {
+            state.rulers = columns;
+            cx.notify();
+        });
+        self
+    }
+
     pub fn cursor_color(mut self, color: Hsla) -> Self {
         self.cursor_color = Some(color);
         self
@@ -4080,11 +9491,23 @@ impl Editor {
         self
     }
 
+    pub fn ruler_color(mut self, color: Hsla) -> Self {
+        self.ruler_color = Some(color);
+        self
+    }
+
     pub fn syntax_color_fn(mut self, f: impl Fn(&str) -> Hsla + 'static) -> Self {
         self.syntax_color_fn = Some(Box::new(f));
         self
     }
 
+    /// Show a scaled-down overview of the buffer on the right side of the editor.
+    /// Clicking or dragging on the minimap scrolls the editor.
+    pub fn minimap(mut self, show: bool) -> Self {
+        self.show_minimap = show;
+        self
+    }
+
     pub fn get_content(&self, cx: &App) -> String {
         self.state.read(cx).content()
     }
@@ -4112,6 +9535,7 @@ impl RenderOnce for Editor {
             state.indent_guide_color_override = self.indent_guide_color;
             state.indent_guide_active_color_override = self.indent_guide_active_color;
             state.fold_marker_color_override = self.fold_marker_color;
+            state.ruler_color_override = self.ruler_color;
             state.syntax_color_fn = syn_fn;
         });
         let theme = use_theme();
@@ -4124,6 +9548,14 @@ impl RenderOnce for Editor {
         let min_height = self.min_lines.map(|lines| px(lines as f32 * 20.0));
         let max_height = self.max_lines.map(|lines| px(lines as f32 * 20.0));
         let scroll_handle = self.state.read(cx).scroll_handle.clone();
+        let smooth_scroller = {
+            let state = self.state.read(cx);
+            state.smooth_scroll.then(|| state.smooth_scroller.clone())
+        };
+        let rename = self.state.read(cx).rename_overlay();
+        let anchored_overlays = self.state.read(cx).visible_anchored_overlays();
+        let color_picker = self.state.read(cx).color_picker.clone();
+        let image_hover = self.state.read(cx).image_hover_overlay();
 
         let mut base = div()
             .id(("editor", self.state.entity_id()))
@@ -4183,18 +9615,32 @@ impl RenderOnce for Editor {
             .on_action(window.listener_for(&self.state, EditorState::delete_word))
             .on_action(window.listener_for(&self.state, EditorState::enter))
             .on_action(window.listener_for(&self.state, EditorState::tab))
+            .on_action(window.listener_for(&self.state, EditorState::shift_tab))
+            .on_action(window.listener_for(&self.state, EditorState::show_code_actions))
+            .on_action(window.listener_for(&self.state, EditorState::rename))
+            .on_action(window.listener_for(&self.state, EditorState::navigate_back))
+            .on_action(window.listener_for(&self.state, EditorState::navigate_forward))
             .on_action(window.listener_for(&self.state, EditorState::copy))
             .on_action(window.listener_for(&self.state, EditorState::cut))
             .on_action(window.listener_for(&self.state, EditorState::paste))
             .on_action(window.listener_for(&self.state, EditorState::undo))
             .on_action(window.listener_for(&self.state, EditorState::redo))
+            .on_action(window.listener_for(&self.state, EditorState::toggle_comment))
+            .on_action(window.listener_for(&self.state, EditorState::indent))
+            .on_action(window.listener_for(&self.state, EditorState::outdent))
+            .on_action(window.listener_for(&self.state, EditorState::select_next_occurrence))
+            .on_action(window.listener_for(&self.state, EditorState::select_all_occurrences))
+            .on_action(window.listener_for(&self.state, EditorState::select_column_up))
+            .on_action(window.listener_for(&self.state, EditorState::select_column_down))
+            .on_action(window.listener_for(&self.state, EditorState::select_column_left))
+            .on_action(window.listener_for(&self.state, EditorState::select_column_right))
             .on_mouse_down(MouseButton::Left, {
                 let state = self.state.clone();
                 move |event: &MouseDownEvent, window: &mut Window, cx: &mut App| {
                     let (bounds, gutter_width, line_height) = {
                         let s = state.read(cx);
                         let b = s.last_bounds.unwrap_or_default();
-                        let gw = if s.show_line_numbers { px(80.0) } else { px(12.0) };
+                        let gw = s.gutter_width();
                         let lh = s.line_height;
                         (b, gw, lh)
                     };
@@ -4210,7 +9656,7 @@ impl RenderOnce for Editor {
                     let (bounds, gutter_width, line_height) = {
                         let s = state.read(cx);
                         let b = s.last_bounds.unwrap_or_default();
-                        let gw = if s.show_line_numbers { px(80.0) } else { px(12.0) };
+                        let gw = s.gutter_width();
                         let lh = s.line_height;
                         (b, gw, lh)
                     };
@@ -4223,6 +9669,22 @@ impl RenderOnce for Editor {
                 MouseButton::Left,
                 window.listener_for(&self.state, EditorState::on_mouse_up),
             )
+            .on_hover({
+                let state = self.state.clone();
+                move |hovered: &bool, _window: &mut Window, cx: &mut App| {
+                    if !hovered {
+                        state.update(cx, |s, cx| s.update_image_hover(None, cx));
+                    }
+                }
+            })
+            .on_modifiers_changed({
+                let state = self.state.clone();
+                move |event: &ModifiersChangedEvent, _window: &mut Window, cx: &mut App| {
+                    if !event.modifiers.secondary() {
+                        state.update(cx, |s, cx| s.update_navigate_hover(None, cx));
+                    }
+                }
+            })
             .on_scroll_wheel({
                 let state = self.state.clone();
                 move |event: &ScrollWheelEvent, _window: &mut Window, cx: &mut App| {
@@ -4242,15 +9704,169 @@ impl RenderOnce for Editor {
                     .flex()
                     .flex_col()
                     .size_full()
-                    .child(div().flex_1().overflow_hidden().child(
-                        scrollable_vertical(self.state.clone())
-                            .with_scroll_handle(scroll_handle),
-                    ))
+                    .child(
+                        div()
+                            .flex_1()
+                            .flex()
+                            .flex_row()
+                            .overflow_hidden()
+                            .child(
+                                div().flex_1().h_full().overflow_hidden().child(
+                                    scrollable_vertical(self.state.clone())
+                                        .with_scroll_handle(scroll_handle)
+                                        .when_some(smooth_scroller, |this, smooth| {
+                                            this.with_smooth_scroll(smooth)
+                                        }),
+                                ),
+                            )
+                            .when(self.show_minimap, |this| {
+                                this.child(minimap_column(self.state.clone()))
+                            }),
+                    )
                     .child(HorizontalScrollbar::new(self.state.clone(), cx)),
             )
+            .when_some(rename, |this, (input, position, count)| {
+                this.child(deferred(
+                    anchored()
+                        .position(position)
+                        .child(rename_box(&input, count)),
+                ))
+            })
+            .when_some(color_picker, |this, (click, picker)| {
+                this.child(deferred(
+                    anchored()
+                        .position(click.position)
+                        .snap_to_window_with_margin(Edges::all(px(8.0)))
+                        .child(color_picker_popover(self.state.clone(), picker)),
+                ))
+            })
+            .when_some(image_hover, |this, (preview, position)| {
+                this.child(deferred(
+                    anchored()
+                        .position(position)
+                        .snap_to_window_with_margin(Edges::all(px(8.0)))
+                        .child(image_preview_card(preview)),
+                ))
+            })
+            .children(
+                anchored_overlays
+                    .into_iter()
+                    .map(|(view, placement, position)| {
+                        let corner = match placement {
+                            OverlayPlacement::Below => Corner::TopLeft,
+                            OverlayPlacement::Above => Corner::BottomLeft,
+                        };
+                        deferred(
+                            anchored()
+                                .anchor(corner)
+                                .position(position)
+                                .snap_to_window_with_margin(Edges::all(px(8.0)))
+                                .child(view),
+                        )
+                    }),
+            )
     }
 }
 
+fn color_picker_popover(
+    editor: Entity<EditorState>,
+    picker: Entity<ColorPickerState>,
+) -> impl IntoElement {
+    let theme = use_theme();
+    let dismiss = editor.clone();
+    div()
+        .occlude()
+        .mt_1()
+        .p_3()
+        .bg(theme.tokens.popover)
+        .border_1()
+        .border_color(theme.tokens.border)
+        .rounded(theme.tokens.radius_md)
+        .shadow_lg()
+        .on_mouse_down_out(move |_, _, cx| {
+            dismiss.update(cx, |state, cx| state.close_color_picker(cx));
+        })
+        .child(
+            ColorPickerPanel::new(picker).on_change(move |color, _, cx| {
+                editor.update(cx, |state, cx| state.pick_color(color, cx));
+            }),
+        )
+}
+
+fn image_preview_card(preview: ImagePreview) -> impl IntoElement {
+    let theme = use_theme();
+    let details = preview.details();
+    div()
+        .occlude()
+        .flex()
+        .flex_col()
+        .gap_1()
+        .mt_1()
+        .p_2()
+        .bg(theme.tokens.popover)
+        .border_1()
+        .border_color(theme.tokens.border)
+        .rounded(theme.tokens.radius_md)
+        .shadow_lg()
+        .child(
+            img(preview.path)
+                .max_w(px(240.0))
+                .max_h(px(160.0))
+                .object_fit(ObjectFit::Contain),
+        )
+        .child(
+            div()
+                .text_xs()
+                .text_color(theme.tokens.muted_foreground)
+                .child(details),
+        )
+}
+
+fn rename_box(input: &Entity<InputState>, count: usize) -> impl IntoElement {
+    let theme = use_theme();
+    div()
+        .occlude()
+        .flex()
+        .flex_col()
+        .gap_1()
+        .p_1()
+        .w(px(240.0))
+        .bg(theme.tokens.popover)
+        .border_1()
+        .border_color(theme.tokens.border)
+        .rounded(theme.tokens.radius_md)
+        .shadow_lg()
+        .child(Input::new(input).size(InputSize::Sm))
+        .child(
+            div()
+                .px_1()
+                .text_xs()
+                .text_color(theme.tokens.muted_foreground)
+                .child(if count == 1 {
+                    "1 occurrence".to_string()
+                } else {
+                    format!("{} occurrences", count)
+                }),
+        )
+}
+
+fn minimap_column(state: Entity<EditorState>) -> impl IntoElement {
+    div()
+        .id("editor-minimap")
+        .flex_shrink_0()
+        .w(px(MINIMAP_WIDTH))
+        .h_full()
+        .cursor(CursorStyle::Arrow)
+        .on_mouse_down(MouseButton::Left, {
+            let state = state.clone();
+            move |event: &MouseDownEvent, _window, cx| {
+                cx.stop_propagation();
+                state.update(cx, |s, cx| s.on_minimap_mouse_down(event, cx));
+            }
+        })
+        .child(MinimapElement { state })
+}
+
 struct HorizontalScrollbar {
     state: Entity<EditorState>,
     needs_scrollbar: bool,
@@ -4264,11 +9880,7 @@ impl HorizontalScrollbar {
         let max_width = s.max_line_width;
         let scroll_x = s.scroll_offset_x;
         let viewport_bounds = s.scroll_handle.bounds();
-        let gutter_width = if s.show_line_numbers {
-            px(80.0)
-        } else {
-            px(12.0)
-        };
+        let gutter_width = s.gutter_width();
         let content_width = viewport_bounds.size.width - gutter_width;
         let needs_scrollbar = max_width > content_width && content_width > px(0.0);
 
@@ -4320,11 +9932,7 @@ impl IntoElement for HorizontalScrollbar {
                         s.dragging_h_scrollbar = true;
                         let max_w = s.max_line_width;
                         let vp = s.scroll_handle.bounds();
-                        let gw = if s.show_line_numbers {
-                            px(80.0)
-                        } else {
-                            px(12.0)
-                        };
+                        let gw = s.gutter_width();
                         let cw = vp.size.width - gw;
                         let scroll_range = max_w - cw;
 
@@ -4473,3 +10081,31 @@ impl IntoElement for VerticalScrollbar {
             .into_any_element()
     }
 }
+
+/// Entry points into the editor's private hot paths for `benches/editor.rs` and the
+/// `bench` example. Not part of the public API.
+#[doc(hidden)]
+pub mod bench {
+    use super::*;
+
+    /// Matches of a plain, case-sensitive `query`, found the way the search bar does.
+    pub fn search(rope: &Rope, query: &str) -> usize {
+        rope_search_regex(query, false, true).map_or(0, |regex| rope_matches(rope, &regex).len())
+    }
+
+    /// Parse `rope` and compile the highlight query for `language`, or `None` if its
+    /// grammar isn't built in.
+    pub fn parse(language: Language, rope: &Rope) -> Option<(Tree, Query)> {
+        let ts_lang = language.tree_sitter_language()?;
+        let mut parser = Parser::new();
+        parser.set_language(&ts_lang).ok()?;
+        let query = Query::new(&ts_lang, &language.highlight_query_source()?).ok()?;
+        Some((parse_rope(&mut parser, rope, None)?, query))
+    }
+
+    /// Highlight spans on `lines`, collected as a frame does for the visible lines.
+    pub fn highlight_spans(rope: &Rope, tree: &Tree, query: &Query, lines: Range<usize>) -> usize {
+        let lines: Vec<usize> = lines.collect();
+        highlight_captures(rope, tree, query, &lines).len()
+    }
+}
@@ -0,0 +1,307 @@
+//! Remappable key bindings for the editor.
+//!
+//! [`init`](crate::components::editor::init) binds [`EditorKeymap::defaults`]. An
+//! application can start from those, or from [`EditorKeymap::empty`], apply the user's
+//! overrides and install the result with [`set_keymap`] at any time. Actions are named
+//! as in [`EDITOR_ACTIONS`], with or without the `editor::` namespace, and keystrokes may
+//! be chords such as `"ctrl-k ctrl-c"`.
+
+use crate::components::editor::{action_binding, EDITOR_ACTIONS};
+use gpui::{App, Global, KeyBinding, Keystroke, NoAction, SharedString};
+
+/// Context the editor's bindings apply in unless a binding names another.
+const EDITOR_CONTEXT: &str = "Editor";
+
+/// Built-in bindings as (keystrokes, action).
+const DEFAULT_BINDINGS: &[(&str, &str)] = &[
+    ("up", "MoveUp"),
+    ("down", "MoveDown"),
+    ("left", "MoveLeft"),
+    ("right", "MoveRight"),
+    ("home", "MoveToLineStart"),
+    ("end", "MoveToLineEnd"),
+    #[cfg(target_os = "macos")]
+    ("alt-left", "MoveWordLeft"),
+    #[cfg(not(target_os = "macos"))]
+    ("ctrl-left", "MoveWordLeft"),
+    #[cfg(target_os = "macos")]
+    ("alt-right", "MoveWordRight"),
+    #[cfg(not(target_os = "macos"))]
+    ("ctrl-right", "MoveWordRight"),
+    #[cfg(target_os = "macos")]
+    ("cmd-up", "MoveToDocStart"),
+    #[cfg(not(target_os = "macos"))]
+    ("ctrl-home", "MoveToDocStart"),
+    #[cfg(target_os = "macos")]
+    ("cmd-down", "MoveToDocEnd"),
+    #[cfg(not(target_os = "macos"))]
+    ("ctrl-end", "MoveToDocEnd"),
+    ("pageup", "PageUp"),
+    ("pagedown", "PageDown"),
+    ("shift-up", "SelectUp"),
+    ("shift-down", "SelectDown"),
+    ("shift-left", "SelectLeft"),
+    ("shift-right", "SelectRight"),
+    ("shift-home", "SelectToLineStart"),
+    ("shift-end", "SelectToLineEnd"),
+    #[cfg(target_os = "macos")]
+    ("cmd-a", "SelectAll"),
+    #[cfg(not(target_os = "macos"))]
+    ("ctrl-a", "SelectAll"),
+    #[cfg(target_os = "macos")]
+    ("cmd-d", "SelectNextOccurrence"),
+    #[cfg(not(target_os = "macos"))]
+    ("ctrl-d", "SelectNextOccurrence"),
+    #[cfg(target_os = "macos")]
+    ("cmd-shift-l", "SelectAllOccurrences"),
+    #[cfg(not(target_os = "macos"))]
+    ("ctrl-shift-l", "SelectAllOccurrences"),
+    ("backspace", "Backspace"),
+    ("delete", "Delete"),
+    #[cfg(target_os = "macos")]
+    ("alt-backspace", "DeleteWord"),
+    #[cfg(not(target_os = "macos"))]
+    ("ctrl-backspace", "DeleteWord"),
+    ("enter", "Enter"),
+    ("tab", "Tab"),
+    ("shift-tab", "ShiftTab"),
+    #[cfg(target_os = "macos")]
+    ("cmd-]", "Indent"),
+    #[cfg(not(target_os = "macos"))]
+    ("ctrl-]", "Indent"),
+    #[cfg(target_os = "macos")]
+    ("cmd-[", "Outdent"),
+    #[cfg(not(target_os = "macos"))]
+    ("ctrl-[", "Outdent"),
+    #[cfg(target_os = "macos")]
+    ("cmd-c", "Copy"),
+    #[cfg(not(target_os = "macos"))]
+    ("ctrl-c", "Copy"),
+    #[cfg(target_os = "macos")]
+    ("cmd-x", "Cut"),
+    #[cfg(not(target_os = "macos"))]
+    ("ctrl-x", "Cut"),
+    #[cfg(target_os = "macos")]
+    ("cmd-v", "Paste"),
+    #[cfg(not(target_os = "macos"))]
+    ("ctrl-v", "Paste"),
+    #[cfg(target_os = "macos")]
+    ("cmd-z", "Undo"),
+    #[cfg(not(target_os = "macos"))]
+    ("ctrl-z", "Undo"),
+    #[cfg(target_os = "macos")]
+    ("cmd-shift-z", "Redo"),
+    #[cfg(not(target_os = "macos"))]
+    ("ctrl-shift-z", "Redo"),
+    #[cfg(target_os = "macos")]
+    ("cmd-/", "ToggleComment"),
+    #[cfg(not(target_os = "macos"))]
+    ("ctrl-/", "ToggleComment"),
+    ("shift-alt-up", "SelectColumnUp"),
+    ("shift-alt-down", "SelectColumnDown"),
+    ("shift-alt-left", "SelectColumnLeft"),
+    ("shift-alt-right", "SelectColumnRight"),
+    #[cfg(target_os = "macos")]
+    ("cmd-.", "ShowCodeActions"),
+    #[cfg(not(target_os = "macos"))]
+    ("ctrl-.", "ShowCodeActions"),
+    ("f2", "Rename"),
+    ("ctrl--", "NavigateBack"),
+    ("ctrl-shift--", "NavigateForward"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditorKeyBinding {
+    pub keystrokes: SharedString,
+    /// Action name without the `editor::` namespace.
+    pub action: SharedString,
+    /// Key context predicate, `"Editor"` when not given.
+    pub context: SharedString,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditorKeymapError {
+    UnknownAction(SharedString),
+    InvalidKeystroke(SharedString),
+}
+
+impl std::fmt::Display for EditorKeymapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EditorKeymapError::UnknownAction(action) => {
+                write!(f, "unknown editor action `{}`", action)
+            }
+            EditorKeymapError::InvalidKeystroke(keystroke) => {
+                write!(f, "invalid keystroke `{}`", keystroke)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EditorKeymapError {}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EditorKeymap {
+    bindings: Vec<EditorKeyBinding>,
+}
+
+impl EditorKeymap {
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// The built-in bindings for this platform.
+    pub fn defaults() -> Self {
+        let mut keymap = Self::empty();
+        for (keystrokes, action) in DEFAULT_BINDINGS {
+            keymap.push(keystrokes, action, None);
+        }
+        keymap
+    }
+
+    pub fn bindings(&self) -> &[EditorKeyBinding] {
+        &self.bindings
+    }
+
+    /// Keystrokes bound to `action` in any context.
+    pub fn keystrokes_for(&self, action: &str) -> Vec<SharedString> {
+        let action = action_name(action);
+        self.bindings
+            .iter()
+            .filter(|binding| binding.action.as_ref() == action)
+            .map(|binding| binding.keystrokes.clone())
+            .collect()
+    }
+
+    /// Bind `keystrokes` to `action`, replacing whatever they were bound to in the same
+    /// context. Other keystrokes bound to `action` keep working; see
+    /// [`Self::unbind_action`] to move a binding rather than add one.
+    pub fn bind(
+        &mut self,
+        keystrokes: &str,
+        action: &str,
+        context: Option<&str>,
+    ) -> Result<(), EditorKeymapError> {
+        let action = action_name(action);
+        if !EDITOR_ACTIONS.contains(&action) {
+            return Err(EditorKeymapError::UnknownAction(action.to_string().into()));
+        }
+        let keystrokes = normalize_keystrokes(keystrokes)?;
+        self.unbind(&keystrokes, context);
+        self.push(&keystrokes, action, context);
+        Ok(())
+    }
+
+    /// Remove the binding for `keystrokes` in `context`, if any.
+    pub fn unbind(&mut self, keystrokes: &str, context: Option<&str>) {
+        let keystrokes = normalize_keystrokes(keystrokes).unwrap_or_default();
+        let context = context.unwrap_or(EDITOR_CONTEXT);
+        self.bindings.retain(|binding| {
+            binding.keystrokes.as_ref() != keystrokes || binding.context.as_ref() != context
+        });
+    }
+
+    /// Remove every binding for `action`.
+    pub fn unbind_action(&mut self, action: &str) {
+        let action = action_name(action);
+        self.bindings
+            .retain(|binding| binding.action.as_ref() != action);
+    }
+
+    fn push(&mut self, keystrokes: &str, action: &str, context: Option<&str>) {
+        self.bindings.push(EditorKeyBinding {
+            keystrokes: keystrokes.to_string().into(),
+            action: action.to_string().into(),
+            context: context.unwrap_or(EDITOR_CONTEXT).to_string().into(),
+        });
+    }
+}
+
+/// The keymap most recently installed with [`set_keymap`].
+#[derive(Default)]
+struct ActiveEditorKeymap(EditorKeymap);
+
+impl Global for ActiveEditorKeymap {}
+
+/// Install `keymap` for every editor. Keystrokes the previous keymap bound and this one
+/// doesn't are disabled, so removed defaults stop working too.
+pub fn set_keymap(keymap: EditorKeymap, cx: &mut App) {
+    let previous = std::mem::take(&mut cx.default_global::<ActiveEditorKeymap>().0);
+    let removed = previous.bindings.iter().filter(|old| {
+        !keymap
+            .bindings
+            .iter()
+            .any(|new| new.keystrokes == old.keystrokes && new.context == old.context)
+    });
+    let mut key_bindings: Vec<KeyBinding> = removed
+        .map(|old| KeyBinding::new(&old.keystrokes, NoAction, Some(old.context.as_ref())))
+        .collect();
+    key_bindings.extend(keymap.bindings.iter().filter_map(|binding| {
+        action_binding(
+            &binding.action,
+            &binding.keystrokes,
+            Some(binding.context.as_ref()),
+        )
+    }));
+    cx.bind_keys(key_bindings);
+    cx.set_global(ActiveEditorKeymap(keymap));
+}
+
+/// The keymap currently installed.
+pub fn keymap(cx: &App) -> EditorKeymap {
+    cx.try_global::<ActiveEditorKeymap>()
+        .map(|active| active.0.clone())
+        .unwrap_or_default()
+}
+
+fn action_name(action: &str) -> &str {
+    action.strip_prefix("editor::").unwrap_or(action)
+}
+
+/// `keystrokes` with single spaces between chord parts, after checking each parses.
+fn normalize_keystrokes(keystrokes: &str) -> Result<String, EditorKeymapError> {
+    let parts: Vec<&str> = keystrokes.split_whitespace().collect();
+    if parts.is_empty() || parts.iter().any(|part| Keystroke::parse(part).is_err()) {
+        return Err(EditorKeymapError::InvalidKeystroke(
+            keystrokes.to_string().into(),
+        ));
+    }
+    Ok(parts.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bind_replaces_same_keystrokes() {
+        let mut keymap = EditorKeymap::empty();
+        keymap
+            .bind("ctrl-k  ctrl-c", "editor::ToggleComment", None)
+            .unwrap();
+        keymap.bind("ctrl-k ctrl-c", "SelectAll", None).unwrap();
+        keymap
+            .bind("ctrl-k ctrl-c", "Undo", Some("Editor && renaming"))
+            .unwrap();
+        assert_eq!(keymap.bindings().len(), 2);
+        assert!(keymap.keystrokes_for("ToggleComment").is_empty());
+        assert_eq!(
+            keymap.keystrokes_for("SelectAll"),
+            vec![SharedString::from("ctrl-k ctrl-c")]
+        );
+
+        assert_eq!(
+            keymap.bind("f13", "Frobnicate", None),
+            Err(EditorKeymapError::UnknownAction("Frobnicate".into()))
+        );
+        keymap.unbind_action("Undo");
+        assert_eq!(keymap.bindings().len(), 1);
+    }
+
+    #[test]
+    fn defaults_name_real_actions() {
+        for (_, action) in DEFAULT_BINDINGS {
+            assert!(EDITOR_ACTIONS.contains(action), "{}", action);
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Editor settings with per-language profiles.
+//!
+//! Settings resolve in three layers: the user's defaults, then the overrides for the
+//! buffer's language, then what the file's `.editorconfig` says. An editor that calls
+//! [`EditorState::use_language_settings`](super::editor::EditorState::use_language_settings)
+//! follows the resolved settings, and resolves them again when its language changes,
+//! a file is loaded or the profiles here are edited.
+//!
+//! ```rust,ignore
+//! editor_settings::set_defaults(EditorSettings { rulers: vec![100], ..Default::default() }, cx);
+//! editor_settings::set_language_override(
+//!     Language::Python,
+//!     EditorSettingsOverride { format_on_save: Some(true), ..Default::default() },
+//!     cx,
+//! );
+//! editor_settings::set_language_override(
+//!     Language::Go,
+//!     EditorSettingsOverride { hard_tabs: Some(true), ..Default::default() },
+//!     cx,
+//! );
+//!
+//! let go = EditorSettings::for_language(Language::Go, cx);
+//! ```
+
+use crate::components::editor::Language;
+use crate::components::editorconfig;
+use gpui::{App, Global};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Settings as an editor applies them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditorSettings {
+    /// Columns per indentation level, and the width of a tab.
+    pub tab_size: usize,
+    /// Indent with tabs rather than `tab_size` spaces.
+    pub hard_tabs: bool,
+    /// Wrap long lines to the viewport. [`EditorState`](super::editor::EditorState)
+    /// scrolls horizontally either way; this is for views that wrap, e.g. previews.
+    pub soft_wrap: bool,
+    pub rulers: Vec<usize>,
+    /// Run the app's formatter before saving. The editor only records this; the app
+    /// checks it in its save path and applies the formatter's edits.
+    pub format_on_save: bool,
+    /// Whether the language pairs brackets and quotes as they're typed. Applying the
+    /// settings sets the editor's own `auto_closing` switch from this.
+    pub auto_closing: bool,
+}
+
+impl Default for EditorSettings {
+    fn default() -> Self {
+        Self {
+            tab_size: 4,
+            hard_tabs: false,
+            soft_wrap: false,
+            rulers: Vec::new(),
+            format_on_save: false,
+            auto_closing: true,
+        }
+    }
+}
+
+impl EditorSettings {
+    /// The user's defaults with the overrides for `language` applied.
+    pub fn for_language(language: Language, cx: &App) -> Self {
+        cx.try_global::<LanguageSettings>()
+            .map(|settings| settings.resolve(language, None))
+            .unwrap_or_default()
+    }
+
+    /// The settings for the file at `path`: defaults, its language's overrides, then
+    /// its `.editorconfig`. Reads the `.editorconfig` files above it.
+    pub fn for_file(path: &Path, cx: &App) -> Self {
+        let file = EditorSettingsOverride::from_editorconfig(path);
+        cx.try_global::<LanguageSettings>()
+            .map(|settings| settings.resolve(Language::from_path(path), Some(&file)))
+            .unwrap_or_else(|| {
+                let mut settings = Self::default();
+                file.apply_to(&mut settings);
+                settings
+            })
+    }
+}
+
+/// Settings a language or file changes; `None` keeps what the layer below says.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EditorSettingsOverride {
+    pub tab_size: Option<usize>,
+    pub hard_tabs: Option<bool>,
+    pub soft_wrap: Option<bool>,
+    pub rulers: Option<Vec<usize>>,
+    pub format_on_save: Option<bool>,
+    pub auto_closing: Option<bool>,
+}
+
+impl EditorSettingsOverride {
+    pub fn apply_to(&self, settings: &mut EditorSettings) {
+        if let Some(tab_size) = self.tab_size {
+            settings.tab_size = tab_size.max(1);
+        }
+        if let Some(hard_tabs) = self.hard_tabs {
+            settings.hard_tabs = hard_tabs;
+        }
+        if let Some(soft_wrap) = self.soft_wrap {
+            settings.soft_wrap = soft_wrap;
+        }
+        if let Some(rulers) = &self.rulers {
+            settings.rulers = rulers.clone();
+        }
+        if let Some(format_on_save) = self.format_on_save {
+            settings.format_on_save = format_on_save;
+        }
+        if let Some(auto_closing) = self.auto_closing {
+            settings.auto_closing = auto_closing;
+        }
+    }
+
+    /// What the `.editorconfig` files above `path` set: `indent_style`, `indent_size`,
+    /// `tab_width` and `max_line_length`, which becomes a ruler.
+    pub fn from_editorconfig(path: &Path) -> Self {
+        Self::from_editorconfig_properties(&editorconfig::properties_for_file(path))
+    }
+
+    pub fn from_editorconfig_properties(properties: &HashMap<String, String>) -> Self {
+        let get = |key: &str| properties.get(key).map(String::as_str);
+        let tab_width = get("tab_width").and_then(|width| width.parse().ok());
+        let tab_size = match get("indent_size") {
+            Some("tab") => tab_width,
+            Some(size) => size.parse().ok().or(tab_width),
+            None => tab_width,
+        };
+        let rulers = match get("max_line_length") {
+            Some("off") => Some(Vec::new()),
+            Some(length) => length.parse().ok().map(|length| vec![length]),
+            None => None,
+        };
+        Self {
+            tab_size,
+            hard_tabs: match get("indent_style") {
+                Some("tab") => Some(true),
+                Some("space") => Some(false),
+                _ => None,
+            },
+            rulers,
+            ..Default::default()
+        }
+    }
+}
+
+/// The user's defaults and per-language overrides, shared by every editor.
+#[derive(Debug, Clone, Default)]
+pub struct LanguageSettings {
+    pub defaults: EditorSettings,
+    pub languages: HashMap<Language, EditorSettingsOverride>,
+}
+
+impl Global for LanguageSettings {}
+
+impl LanguageSettings {
+    /// Defaults, then `language`'s overrides, then `file`'s.
+    pub fn resolve(
+        &self,
+        language: Language,
+        file: Option<&EditorSettingsOverride>,
+    ) -> EditorSettings {
+        let mut settings = self.defaults.clone();
+        if let Some(language) = self.languages.get(&language) {
+            language.apply_to(&mut settings);
+        }
+        if let Some(file) = file {
+            file.apply_to(&mut settings);
+        }
+        settings
+    }
+}
+
+pub fn set_defaults(defaults: EditorSettings, cx: &mut App) {
+    cx.default_global::<LanguageSettings>();
+    cx.update_global::<LanguageSettings, _>(|settings, _| settings.defaults = defaults);
+}
+
+pub fn set_language_override(language: Language, settings: EditorSettingsOverride, cx: &mut App) {
+    cx.default_global::<LanguageSettings>();
+    cx.update_global::<LanguageSettings, _>(|languages, _| {
+        languages.languages.insert(language, settings);
+    });
+}
+
+pub fn clear_language_override(language: Language, cx: &mut App) {
+    cx.default_global::<LanguageSettings>();
+    cx.update_global::<LanguageSettings, _>(|languages, _| {
+        languages.languages.remove(&language);
+    });
+}
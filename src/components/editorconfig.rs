@@ -0,0 +1,272 @@
+//! Reading `.editorconfig` files.
+//!
+//! The properties for a file come from every `.editorconfig` in its directory and the
+//! directories above it, up to one that declares `root = true`. Within a file, later
+//! sections override earlier ones, and nearer files override those further up.
+//! Section globs follow the EditorConfig spec: `*` doesn't cross `/`, `**` does, `?`
+//! is one character, `[abc]` / `[!abc]` a set, and `{a,b}` / `{1..3}` alternatives. A
+//! glob without a `/` matches the file name in any directory below the config.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EditorConfig {
+    root: bool,
+    sections: Vec<Section>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Section {
+    glob: String,
+    properties: Vec<(String, String)>,
+}
+
+impl EditorConfig {
+    /// Parse the text of an `.editorconfig`. Lines that don't parse are skipped, as
+    /// the spec asks. Keys and values are lower-cased.
+    pub fn parse(text: &str) -> Self {
+        let mut config = Self::default();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if let Some(glob) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                config.sections.push(Section {
+                    glob: glob.to_string(),
+                    properties: Vec::new(),
+                });
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let key = key.trim().to_lowercase();
+            let value = value.trim().to_lowercase();
+            match config.sections.last_mut() {
+                Some(section) => section.properties.push((key, value)),
+                None if key == "root" => config.root = value == "true",
+                None => {}
+            }
+        }
+        config
+    }
+
+    /// Whether lookups stop at this file rather than going on to parent directories.
+    pub fn is_root(&self) -> bool {
+        self.root
+    }
+
+    /// The properties for `relative`, a `/`-separated path from this file's directory.
+    pub fn properties_for(&self, relative: &str) -> HashMap<String, String> {
+        let mut properties = HashMap::new();
+        for section in &self.sections {
+            if glob_matches(&section.glob, relative) {
+                properties.extend(section.properties.iter().cloned());
+            }
+        }
+        properties
+    }
+}
+
+/// The properties for the file at `path` from the `.editorconfig` files above it.
+/// Files that can't be read are skipped.
+pub fn properties_for_file(path: &Path) -> HashMap<String, String> {
+    let mut configs = Vec::new();
+    for dir in path.ancestors().skip(1) {
+        let Ok(text) = std::fs::read_to_string(dir.join(".editorconfig")) else {
+            continue;
+        };
+        let config = EditorConfig::parse(&text);
+        let root = config.is_root();
+        configs.push((dir, config));
+        if root {
+            break;
+        }
+    }
+
+    let mut properties = HashMap::new();
+    for (dir, config) in configs.iter().rev() {
+        let Ok(relative) = path.strip_prefix(dir) else {
+            continue;
+        };
+        let relative: Vec<_> = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect();
+        properties.extend(config.properties_for(&relative.join("/")));
+    }
+    properties
+}
+
+/// Whether a section glob matches `path`, relative to the config's directory.
+pub fn glob_matches(glob: &str, path: &str) -> bool {
+    let glob = match glob.strip_prefix('/') {
+        Some(anchored) => anchored.to_string(),
+        None if glob.contains('/') => glob.to_string(),
+        None => format!("**/{}", glob),
+    };
+    let path: Vec<char> = path.chars().collect();
+    expand_braces(&glob).iter().any(|alternative| {
+        let glob: Vec<char> = alternative.chars().collect();
+        // `**/` also matches no directory at all.
+        matches(&glob, &path) || glob.starts_with(&['*', '*', '/']) && matches(&glob[3..], &path)
+    })
+}
+
+/// Every alternative of the outermost `{...}` groups, with `{1..3}` as `1`, `2`, `3`.
+fn expand_braces(glob: &str) -> Vec<String> {
+    let Some(open) = glob.find('{') else {
+        return vec![glob.to_string()];
+    };
+    let mut depth = 0;
+    let mut close = None;
+    let mut splits = Vec::new();
+    for (ix, ch) in glob[open..].char_indices() {
+        match ch {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(open + ix);
+                    break;
+                }
+            }
+            ',' if depth == 1 => splits.push(open + ix),
+            _ => {}
+        }
+    }
+    let Some(close) = close else {
+        return vec![glob.to_string()];
+    };
+
+    let inner = &glob[open + 1..close];
+    let alternatives: Vec<String> = if splits.is_empty() {
+        match inner
+            .split_once("..")
+            .and_then(|(start, end)| Some((start.parse::<i64>().ok()?, end.parse::<i64>().ok()?)))
+        {
+            Some((start, end)) => (start.min(end)..=start.max(end))
+                .map(|n| n.to_string())
+                .collect(),
+            // A single word in braces is taken literally.
+            None => vec![format!("{{{}}}", inner)],
+        }
+    } else {
+        let mut bounds = vec![open];
+        bounds.extend(&splits);
+        bounds.push(close);
+        bounds
+            .windows(2)
+            .map(|pair| glob[pair[0] + 1..pair[1]].to_string())
+            .collect()
+    };
+
+    let (prefix, suffix) = (&glob[..open], &glob[close + 1..]);
+    alternatives
+        .iter()
+        .flat_map(|alternative| expand_braces(&format!("{}{}{}", prefix, alternative, suffix)))
+        .collect()
+}
+
+fn matches(glob: &[char], path: &[char]) -> bool {
+    match glob.first() {
+        None => path.is_empty(),
+        Some('*') if glob.get(1) == Some(&'*') => {
+            let rest = &glob[2..];
+            (0..=path.len()).any(|skip| matches(rest, &path[skip..]))
+        }
+        Some('*') => {
+            let rest = &glob[1..];
+            let segment = path.iter().position(|&ch| ch == '/').unwrap_or(path.len());
+            (0..=segment).any(|skip| matches(rest, &path[skip..]))
+        }
+        Some('?') => path.first().is_some_and(|&ch| ch != '/') && matches(&glob[1..], &path[1..]),
+        Some('[') => match glob.iter().position(|&ch| ch == ']') {
+            Some(end) if end > 1 => {
+                let (negated, set) = match glob[1] {
+                    '!' => (true, &glob[2..end]),
+                    _ => (false, &glob[1..end]),
+                };
+                path.first()
+                    .is_some_and(|&ch| ch != '/' && in_set(set, ch) != negated)
+                    && matches(&glob[end + 1..], &path[1..])
+            }
+            _ => path.first() == Some(&'[') && matches(&glob[1..], &path[1..]),
+        },
+        Some('\\') if glob.len() > 1 => {
+            path.first() == Some(&glob[1]) && matches(&glob[2..], &path[1..])
+        }
+        Some(&ch) => path.first() == Some(&ch) && matches(&glob[1..], &path[1..]),
+    }
+}
+
+/// Whether `ch` is in a `[...]` set such as `abc` or `a-z`.
+fn in_set(set: &[char], ch: char) -> bool {
+    let mut ix = 0;
+    while ix < set.len() {
+        if ix + 2 < set.len() && set[ix + 1] == '-' {
+            if (set[ix]..=set[ix + 2]).contains(&ch) {
+                return true;
+            }
+            ix += 3;
+        } else {
+            if set[ix] == ch {
+                return true;
+            }
+            ix += 1;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn globs() {
+        assert!(glob_matches("*", "src/main.rs"));
+        assert!(glob_matches("*.rs", "main.rs"));
+        assert!(glob_matches("*.rs", "src/deep/main.rs"));
+        assert!(!glob_matches("src/*.rs", "src/deep/main.rs"));
+        assert!(glob_matches("src/**.rs", "src/deep/main.rs"));
+        assert!(glob_matches("/Makefile", "Makefile"));
+        assert!(!glob_matches("/Makefile", "sub/Makefile"));
+        assert!(glob_matches("*.{js,ts}", "web/app.ts"));
+        assert!(!glob_matches("*.{js,ts}", "web/app.rs"));
+        assert!(glob_matches("file{1..3}.txt", "file2.txt"));
+        assert!(!glob_matches("file{1..3}.txt", "file4.txt"));
+        assert!(glob_matches("[Mm]akefile", "makefile"));
+        assert!(glob_matches("*.[!o]", "lib.c"));
+        assert!(!glob_matches("*.[!o]", "lib.o"));
+        assert!(glob_matches("?.md", "a.md"));
+    }
+
+    #[test]
+    fn later_sections_win() {
+        let config = EditorConfig::parse(
+            "root = true\n\
+             # defaults\n\
+             [*]\n\
+             indent_style = space\n\
+             indent_size = 4\n\
+             \n\
+             [*.{yml,yaml}]\n\
+             indent_size = 2\n\
+             \n\
+             [Makefile]\n\
+             Indent_Style = TAB\n",
+        );
+        assert!(config.is_root());
+
+        let yaml = config.properties_for("ci/build.yml");
+        assert_eq!(yaml.get("indent_size").map(String::as_str), Some("2"));
+        assert_eq!(yaml.get("indent_style").map(String::as_str), Some("space"));
+
+        let make = config.properties_for("Makefile");
+        assert_eq!(make.get("indent_style").map(String::as_str), Some("tab"));
+        assert_eq!(make.get("indent_size").map(String::as_str), Some("4"));
+    }
+}
@@ -1,8 +1,15 @@
+//! FileUpload component - A drop zone for files dragged in from the OS, or picked with
+//! the system file dialog when clicked.
+//!
+//! Files are checked against the accepted extensions and MIME types and the size
+//! limit; accepted ones are listed with remove buttons and reported through
+//! [`FileUpload::on_files_changed`], rejected ones through [`FileUpload::on_error`].
+
 use crate::components::icon::Icon;
 use crate::components::spinner::{Spinner, SpinnerSize, SpinnerVariant};
 use crate::theme::use_theme;
 use gpui::{prelude::FluentBuilder as _, *};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
 #[derive(Clone, Debug)]
@@ -31,19 +38,7 @@ impl SelectedFile {
             "png" | "jpg" | "jpeg" | "gif" | "bmp" | "webp" | "svg" | "ico"
         );
 
-        let mime_type = match extension.as_str() {
-            "png" => Some("image/png".to_string()),
-            "jpg" | "jpeg" => Some("image/jpeg".to_string()),
-            "gif" => Some("image/gif".to_string()),
-            "bmp" => Some("image/bmp".to_string()),
-            "webp" => Some("image/webp".to_string()),
-            "svg" => Some("image/svg+xml".to_string()),
-            "pdf" => Some("application/pdf".to_string()),
-            "txt" => Some("text/plain".to_string()),
-            "json" => Some("application/json".to_string()),
-            "zip" => Some("application/zip".to_string()),
-            _ => None,
-        };
+        let mime_type = mime_type_for_extension(&extension).map(str::to_string);
 
         let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
 
@@ -73,6 +68,34 @@ impl SelectedFile {
     }
 }
 
+/// The MIME type usually meant by a lowercase file extension.
+pub fn mime_type_for_extension(extension: &str) -> Option<&'static str> {
+    Some(match extension {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "bmp" => "image/bmp",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "pdf" => "application/pdf",
+        "doc" => "application/msword",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "rtf" => "application/rtf",
+        "txt" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "json" => "application/json",
+        "zip" => "application/zip",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "mp4" => "video/mp4",
+        "mov" => "video/quicktime",
+        _ => return None,
+    })
+}
+
 #[derive(Clone, Debug)]
 pub struct FileUploadError {
     pub file_name: String,
@@ -177,6 +200,9 @@ impl FileUploadSize {
 #[derive(Clone, Debug)]
 pub struct FileTypeFilter {
     pub extensions: Vec<String>,
+    /// MIME types such as `application/pdf`, or whole families such as `image/*`,
+    /// matched against the type guessed from a file's extension.
+    pub mime_types: Vec<String>,
     pub label: String,
 }
 
@@ -184,10 +210,23 @@ impl FileTypeFilter {
     pub fn new(extensions: Vec<&str>, label: impl Into<String>) -> Self {
         Self {
             extensions: extensions.into_iter().map(|s| s.to_string()).collect(),
+            mime_types: Vec::new(),
             label: label.into(),
         }
     }
 
+    /// A filter by MIME type, e.g. `FileTypeFilter::mime(vec!["image/*"], "Images")`.
+    pub fn mime(mime_types: Vec<&str>, label: impl Into<String>) -> Self {
+        Self::new(vec![], label).with_mime_types(mime_types)
+    }
+
+    /// Also accept files of these MIME types.
+    pub fn with_mime_types(mut self, mime_types: Vec<&str>) -> Self {
+        self.mime_types
+            .extend(mime_types.into_iter().map(|s| s.to_lowercase()));
+        self
+    }
+
     pub fn images() -> Self {
         Self::new(
             vec!["png", "jpg", "jpeg", "gif", "bmp", "webp", "svg"],
@@ -203,19 +242,148 @@ impl FileTypeFilter {
         Self::new(vec![], "All files")
     }
 
-    fn matches(&self, path: &PathBuf) -> bool {
-        if self.extensions.is_empty() {
+    fn matches(&self, path: &Path) -> bool {
+        if self.extensions.is_empty() && self.mime_types.is_empty() {
             return true;
         }
 
-        path.extension()
-            .and_then(|e| e.to_str())
-            .map(|ext| {
-                self.extensions
-                    .iter()
-                    .any(|allowed| allowed.eq_ignore_ascii_case(ext))
+        let Some(ext) = path.extension().and_then(|e| e.to_str()) else {
+            return false;
+        };
+        if self
+            .extensions
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(ext))
+        {
+            return true;
+        }
+        let Some(mime) = mime_type_for_extension(&ext.to_lowercase()) else {
+            return false;
+        };
+        self.mime_types
+            .iter()
+            .any(|allowed| match allowed.strip_suffix("/*") {
+                Some(family) => mime.split('/').next() == Some(family),
+                None => allowed == mime,
             })
-            .unwrap_or(false)
+    }
+
+    /// What the filter accepts, for messages: "png, jpg, image/*".
+    fn accepted(&self) -> String {
+        if self.extensions.is_empty() && self.mime_types.is_empty() {
+            return "all files".to_string();
+        }
+        self.extensions
+            .iter()
+            .chain(&self.mime_types)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// What a [`FileUpload`] accepts, checked for every picked or dropped file.
+#[derive(Clone)]
+struct AcceptRules {
+    multiple: bool,
+    file_types: Option<FileTypeFilter>,
+    max_file_size: Option<u64>,
+}
+
+impl AcceptRules {
+    fn check(&self, path: &Path) -> Result<(), String> {
+        if path.is_dir() {
+            return Err("Folders can't be added".to_string());
+        }
+
+        if let Some(ref filter) = self.file_types {
+            if !filter.matches(path) {
+                return Err(format!(
+                    "File type not allowed. Accepted: {}",
+                    filter.accepted()
+                ));
+            }
+        }
+
+        if let Some(max_size) = self.max_file_size {
+            let file_size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+            if file_size > max_size {
+                let max_mb = max_size as f64 / (1024.0 * 1024.0);
+                return Err(format!("File exceeds maximum size of {:.1} MB", max_mb));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+type FilesChangedHandler = Rc<dyn Fn(&[SelectedFile], &mut Window, &mut App)>;
+type ErrorHandler = Rc<dyn Fn(&FileUploadError, &mut Window, &mut App)>;
+
+/// Add the files at `paths` that pass `rules`, replacing the current file unless
+/// `rules.multiple`, and record an error for each one that doesn't. Errors from an
+/// earlier batch are cleared.
+fn accept_paths(
+    state: &Entity<FileUploadState>,
+    paths: Vec<PathBuf>,
+    rules: &AcceptRules,
+    on_files_changed: Option<&FilesChangedHandler>,
+    on_error: Option<&ErrorHandler>,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let mut accepted = Vec::new();
+    let mut errors = Vec::new();
+    for path in paths {
+        match rules.check(&path) {
+            Ok(()) => accepted.push(SelectedFile::new(path)),
+            Err(message) => errors.push(FileUploadError {
+                file_name: path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                message,
+            }),
+        }
+    }
+    if !rules.multiple {
+        accepted.truncate(1);
+    }
+
+    let changed = state.update(cx, |state, cx| {
+        state.clear_errors();
+        state.is_dragging = false;
+        for error in &errors {
+            state.add_error(error.clone());
+        }
+        let mut changed = false;
+        for file in accepted {
+            if !rules.multiple {
+                state.clear_files();
+            } else if state
+                .files
+                .iter()
+                .any(|existing| existing.path == file.path)
+            {
+                continue;
+            }
+            state.add_file(file);
+            changed = true;
+        }
+        cx.notify();
+        changed
+    });
+
+    if let Some(handler) = on_error {
+        for error in &errors {
+            handler(error, window, cx);
+        }
+    }
+    if changed {
+        if let Some(handler) = on_files_changed {
+            let files = state.read(cx).files.clone();
+            handler(&files, window, cx);
+        }
     }
 }
 
@@ -233,8 +401,8 @@ pub struct FileUpload {
     show_previews: bool,
     placeholder_text: Option<SharedString>,
     placeholder_icon: Option<SharedString>,
-    on_files_changed: Option<Rc<dyn Fn(&[SelectedFile], &mut Window, &mut App)>>,
-    on_error: Option<Rc<dyn Fn(&FileUploadError, &mut Window, &mut App)>>,
+    on_files_changed: Option<FilesChangedHandler>,
+    on_error: Option<ErrorHandler>,
     style: StyleRefinement,
 }
 
@@ -333,29 +501,6 @@ impl FileUpload {
         self.on_error = Some(Rc::new(handler));
         self
     }
-
-    fn _validate_file(&self, path: &PathBuf) -> Result<(), String> {
-        if let Some(ref filter) = self.file_types {
-            if !filter.matches(path) {
-                let allowed = if filter.extensions.is_empty() {
-                    "all files".to_string()
-                } else {
-                    filter.extensions.join(", ")
-                };
-                return Err(format!("File type not allowed. Accepted: {}", allowed));
-            }
-        }
-
-        if let Some(max_size) = self.max_file_size {
-            let file_size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
-            if file_size > max_size {
-                let max_mb = max_size as f64 / (1024.0 * 1024.0);
-                return Err(format!("File exceeds maximum size of {:.1} MB", max_mb));
-            }
-        }
-
-        Ok(())
-    }
 }
 
 impl Styled for FileUpload {
@@ -402,11 +547,15 @@ impl RenderOnce for FileUpload {
             .unwrap_or_else(|| "cloud-upload".into());
 
         let state_entity = self.state.clone();
-        let multiple = self.multiple;
         let max_file_size = self.max_file_size;
         let file_types = self.file_types.clone();
         let on_files_changed = self.on_files_changed.clone();
-        let _on_error = self.on_error.clone();
+        let on_error = self.on_error.clone();
+        let rules = AcceptRules {
+            multiple: self.multiple,
+            file_types: self.file_types.clone(),
+            max_file_size: self.max_file_size,
+        };
 
         let show_file_list = self.show_file_list;
         let show_previews = self.show_previews;
@@ -499,14 +648,15 @@ impl RenderOnce for FileUpload {
                                         div()
                                             .text_size(px(12.0))
                                             .text_color(theme.tokens.muted_foreground)
-                                            .child(if filter.extensions.is_empty() {
-                                                "All file types supported".to_string()
-                                            } else {
-                                                format!(
-                                                    "Accepted: {}",
-                                                    filter.extensions.join(", ")
-                                                )
-                                            }),
+                                            .child(
+                                                if filter.extensions.is_empty()
+                                                    && filter.mime_types.is_empty()
+                                                {
+                                                    "All file types supported".to_string()
+                                                } else {
+                                                    format!("Accepted: {}", filter.accepted())
+                                                },
+                                            ),
                                     )
                                 })
                                 .when_some(max_file_size, |this, max_size| {
@@ -520,92 +670,65 @@ impl RenderOnce for FileUpload {
                                 }),
                         )
                     })
-                    .on_click({
-                        let state_entity = state_entity.clone();
-                        let file_types = file_types.clone();
-                        let max_file_size = max_file_size;
-                        move |_, window, cx| {
-                            if disabled {
-                                return;
+                    .when(!disabled, |this| {
+                        let drag_border = theme.tokens.primary;
+                        let drag_bg = theme.tokens.primary.opacity(0.1);
+                        this.drag_over::<ExternalPaths>(move |style, _, _, _| {
+                            style.border_color(drag_border).bg(drag_bg)
+                        })
+                        .on_drop({
+                            let state_entity = state_entity.clone();
+                            let rules = rules.clone();
+                            let on_files_changed = on_files_changed.clone();
+                            let on_error = on_error.clone();
+                            move |paths: &ExternalPaths, window, cx| {
+                                accept_paths(
+                                    &state_entity,
+                                    paths.paths().to_vec(),
+                                    &rules,
+                                    on_files_changed.as_ref(),
+                                    on_error.as_ref(),
+                                    window,
+                                    cx,
+                                );
                             }
-
-                            let receiver = cx.prompt_for_paths(PathPromptOptions {
-                                files: true,
-                                directories: false,
-                                multiple,
-                                prompt: Some("Select files".into()),
-                            });
-
+                        })
+                        .on_click({
                             let state_entity = state_entity.clone();
-                            let file_types = file_types.clone();
-
-                            window
-                                .spawn(cx, async move |cx| {
-                                    if let Ok(Ok(Some(paths))) = receiver.await {
-                                        for path in paths {
-                                            let file_name = path
-                                                .file_name()
-                                                .map(|n| n.to_string_lossy().to_string())
-                                                .unwrap_or_default();
-
-                                            if let Some(ref filter) = file_types {
-                                                if !filter.matches(&path) {
-                                                    let error = FileUploadError {
-                                                        file_name: file_name.clone(),
-                                                        message: format!(
-                                                            "File type not allowed. Accepted: {}",
-                                                            filter.extensions.join(", ")
-                                                        ),
-                                                    };
-                                                    let state_entity = state_entity.clone();
-                                                    cx.update(|_, cx| {
-                                                        state_entity.update(cx, |state, _| {
-                                                            state.add_error(error);
-                                                        });
-                                                    })
-                                                    .ok();
-                                                    continue;
-                                                }
-                                            }
-
-                                            if let Some(max_size) = max_file_size {
-                                                let file_size = std::fs::metadata(&path)
-                                                    .map(|m| m.len())
-                                                    .unwrap_or(0);
-                                                if file_size > max_size {
-                                                    let max_mb =
-                                                        max_size as f64 / (1024.0 * 1024.0);
-                                                    let error = FileUploadError {
-                                                        file_name: file_name.clone(),
-                                                        message: format!(
-                                                            "File exceeds maximum size of {:.1} MB",
-                                                            max_mb
-                                                        ),
-                                                    };
-                                                    let state_entity = state_entity.clone();
-                                                    cx.update(|_, cx| {
-                                                        state_entity.update(cx, |state, _| {
-                                                            state.add_error(error);
-                                                        });
-                                                    })
-                                                    .ok();
-                                                    continue;
-                                                }
-                                            }
-
-                                            let selected_file = SelectedFile::new(path);
-                                            let state_entity = state_entity.clone();
-                                            cx.update(|_, cx| {
-                                                state_entity.update(cx, |state, _| {
-                                                    state.add_file(selected_file);
-                                                });
+                            let on_files_changed = on_files_changed.clone();
+                            let on_error = on_error.clone();
+                            move |_, window, cx| {
+                                let receiver = cx.prompt_for_paths(PathPromptOptions {
+                                    files: true,
+                                    directories: false,
+                                    multiple: rules.multiple,
+                                    prompt: Some("Select files".into()),
+                                });
+
+                                let state_entity = state_entity.clone();
+                                let rules = rules.clone();
+                                let on_files_changed = on_files_changed.clone();
+                                let on_error = on_error.clone();
+                                window
+                                    .spawn(cx, async move |cx| {
+                                        if let Ok(Ok(Some(paths))) = receiver.await {
+                                            cx.update(|window, cx| {
+                                                accept_paths(
+                                                    &state_entity,
+                                                    paths,
+                                                    &rules,
+                                                    on_files_changed.as_ref(),
+                                                    on_error.as_ref(),
+                                                    window,
+                                                    cx,
+                                                );
                                             })
                                             .ok();
                                         }
-                                    }
-                                })
-                                .detach();
-                        }
+                                    })
+                                    .detach();
+                            }
+                        })
                     }),
             )
             .when(!errors.is_empty(), |this| {
@@ -756,8 +879,9 @@ impl RenderOnce for FileUpload {
                                                 .color(theme.tokens.muted_foreground),
                                         )
                                         .on_click(move |_, window, cx| {
-                                            state_entity.update(cx, |state, _| {
+                                            state_entity.update(cx, |state, cx| {
                                                 state.remove_file(index);
+                                                cx.notify();
                                             });
                                             if let Some(ref handler) = on_files_changed {
                                                 let files = state_entity.read(cx).files.clone();
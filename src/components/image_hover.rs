@@ -0,0 +1,238 @@
+//! Image previews for paths written in source text.
+//!
+//! The editor looks for a string literal or a markdown link target under the mouse,
+//! resolves it to a file (relative to the open file by default) and, when it is an
+//! image, shows a thumbnail with the image's dimensions and file size. Dimensions are
+//! read from the file header, so previews never decode the whole image.
+
+use std::fs::File;
+use std::io::Read;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "bmp", "svg", "ico"];
+/// Enough of a file to find the dimensions in every supported header. JPEG's can sit
+/// behind large metadata segments, in which case the dimensions are left unknown.
+const HEADER_LEN: usize = 64 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImagePreview {
+    pub path: PathBuf,
+    /// Width and height in pixels, when the header could be read.
+    pub dimensions: Option<(u32, u32)>,
+    pub file_size: u64,
+}
+
+impl ImagePreview {
+    /// Stat and read the header of `path`. `None` if it can't be opened.
+    pub fn load(path: PathBuf) -> Option<Self> {
+        let mut file = File::open(&path).ok()?;
+        let file_size = file.metadata().ok()?.len();
+        let mut header = Vec::with_capacity(HEADER_LEN.min(file_size as usize));
+        (&mut file)
+            .take(HEADER_LEN as u64)
+            .read_to_end(&mut header)
+            .ok()?;
+        Some(Self {
+            dimensions: image_dimensions(&header),
+            path,
+            file_size,
+        })
+    }
+
+    /// Dimensions, when known, and file size, e.g. `640 × 480 · 12.4 KB`.
+    pub fn details(&self) -> String {
+        let size = format_size(self.file_size);
+        match self.dimensions {
+            Some((width, height)) => format!("{} × {} · {}", width, height, size),
+            None => size,
+        }
+    }
+}
+
+fn format_size(size: u64) -> String {
+    const KB: u64 = 1024;
+    const MB: u64 = KB * 1024;
+
+    if size >= MB {
+        format!("{:.1} MB", size as f64 / MB as f64)
+    } else if size >= KB {
+        format!("{:.1} KB", size as f64 / KB as f64)
+    } else {
+        format!("{} B", size)
+    }
+}
+
+/// Whether `path` names a file type the preview can show.
+pub fn is_image_path(path: &str) -> bool {
+    let path = path.split(['?', '#']).next().unwrap_or(path);
+    Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            IMAGE_EXTENSIONS
+                .iter()
+                .any(|known| ext.eq_ignore_ascii_case(known))
+        })
+}
+
+/// The default resolver: an image path relative to `base_dir` (the open file's
+/// directory) or absolute. URLs are left alone. Whether the file exists is checked
+/// when the preview loads, off the main thread.
+pub fn resolve_image_path(text: &str, base_dir: Option<&Path>) -> Option<PathBuf> {
+    if !is_image_path(text) || text.contains("://") {
+        return None;
+    }
+    let text = text.split(['?', '#']).next().unwrap_or(text);
+    let path = Path::new(text);
+    match base_dir {
+        Some(dir) if path.is_relative() => Some(dir.join(path)),
+        _ => Some(path.to_path_buf()),
+    }
+}
+
+/// Byte range of the path-like text around byte column `col` of `line`: a markdown
+/// link target (`[alt](path)`) or the contents of a quoted string.
+pub(crate) fn path_literal_at(line: &str, col: usize) -> Option<Range<usize>> {
+    let bytes = line.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b']' && bytes.get(i + 1) == Some(&b'(') {
+            let start = i + 2;
+            let end = start + line[start..].find(')')?;
+            if (start..=end).contains(&col) && end > start {
+                let target = &line[start..end];
+                // A title after the target: `[alt](path "title")`.
+                let len = target.find(' ').unwrap_or(target.len());
+                return Some(start..start + len);
+            }
+            i = end + 1;
+            continue;
+        }
+        if matches!(bytes[i], b'"' | b'\'' | b'`') {
+            let quote = bytes[i];
+            let start = i + 1;
+            let Some(len) = bytes[start..].iter().position(|&b| b == quote) else {
+                break;
+            };
+            let end = start + len;
+            if (start..=end).contains(&col) && end > start {
+                return Some(start..end);
+            }
+            i = end + 1;
+            continue;
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Width and height from the header of a PNG, GIF, JPEG, BMP or WebP file.
+pub(crate) fn image_dimensions(header: &[u8]) -> Option<(u32, u32)> {
+    let be32 = |at: usize| -> Option<u32> {
+        Some(u32::from_be_bytes(header.get(at..at + 4)?.try_into().ok()?))
+    };
+    let le16 = |at: usize| -> Option<u32> {
+        Some(u16::from_le_bytes(header.get(at..at + 2)?.try_into().ok()?) as u32)
+    };
+    let be16 = |at: usize| -> Option<u32> {
+        Some(u16::from_be_bytes(header.get(at..at + 2)?.try_into().ok()?) as u32)
+    };
+    let le32 = |at: usize| -> Option<u32> {
+        Some(u32::from_le_bytes(header.get(at..at + 4)?.try_into().ok()?))
+    };
+
+    if header.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some((be32(16)?, be32(20)?));
+    }
+    if header.starts_with(b"GIF87a") || header.starts_with(b"GIF89a") {
+        return Some((le16(6)?, le16(8)?));
+    }
+    if header.starts_with(b"BM") {
+        return Some((le32(18)?, (le32(22)? as i32).unsigned_abs()));
+    }
+    if header.starts_with(b"RIFF") && header.get(8..12) == Some(b"WEBP") {
+        return match header.get(12..16)? {
+            b"VP8 " => Some((le16(26)? & 0x3fff, le16(28)? & 0x3fff)),
+            b"VP8L" => {
+                let bits = le32(21)?;
+                Some(((bits & 0x3fff) + 1, ((bits >> 14) & 0x3fff) + 1))
+            }
+            b"VP8X" => {
+                let width = le32(24)? & 0xff_ffff;
+                let height = le32(27)? & 0xff_ffff;
+                Some((width + 1, height + 1))
+            }
+            _ => None,
+        };
+    }
+    if header.starts_with(&[0xff, 0xd8]) {
+        // Walk the segments to the first start-of-frame marker.
+        let mut at = 2;
+        while at + 4 <= header.len() {
+            if header[at] != 0xff {
+                return None;
+            }
+            let marker = header[at + 1];
+            let len = be16(at + 2)? as usize;
+            let is_frame = matches!(marker, 0xc0..=0xcf) && !matches!(marker, 0xc4 | 0xc8 | 0xcc);
+            if is_frame {
+                return Some((be16(at + 7)?, be16(at + 5)?));
+            }
+            at += 2 + len;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_paths_in_strings_and_links() {
+        let line = r#"let logo = load("assets/logo.png");"#;
+        let col = line.find("logo.png").unwrap();
+        assert_eq!(
+            path_literal_at(line, col).map(|r| &line[r]),
+            Some("assets/logo.png")
+        );
+        assert_eq!(path_literal_at(line, 2), None);
+
+        let line = r#"See ![diagram](img/flow.svg "Flow") and "text"."#;
+        let col = line.find("flow").unwrap();
+        assert_eq!(
+            path_literal_at(line, col).map(|r| &line[r]),
+            Some("img/flow.svg")
+        );
+        assert!(is_image_path("img/flow.svg"));
+        assert!(is_image_path("icon.PNG?v=2"));
+        assert!(!is_image_path("main.rs"));
+    }
+
+    #[test]
+    fn reads_header_dimensions() {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        png.extend_from_slice(&640u32.to_be_bytes());
+        png.extend_from_slice(&480u32.to_be_bytes());
+        assert_eq!(image_dimensions(&png), Some((640, 480)));
+
+        let mut gif = b"GIF89a".to_vec();
+        gif.extend_from_slice(&[32, 0, 16, 0]);
+        assert_eq!(image_dimensions(&gif), Some((32, 16)));
+
+        let jpeg = [
+            0xff, 0xd8, 0xff, 0xe0, 0x00, 0x04, 0x00, 0x00, 0xff, 0xc0, 0x00, 0x11, 0x08, 0x01,
+            0x2c, 0x00, 0xc8,
+        ];
+        assert_eq!(image_dimensions(&jpeg), Some((200, 300)));
+        assert_eq!(image_dimensions(b"not an image"), None);
+
+        let preview = ImagePreview {
+            path: PathBuf::from("logo.png"),
+            dimensions: Some((640, 480)),
+            file_size: 12_700,
+        };
+        assert_eq!(preview.details(), "640 × 480 · 12.4 KB");
+    }
+}
@@ -2,9 +2,10 @@
 
 use crate::animations::{easings, shake_offset};
 use crate::components::icon::Icon;
+pub use crate::components::input_mask::MaskPattern;
 pub use crate::components::input_state::{
     Backspace, Copy, Cut, Delete, End, Enter, Escape, Home, InputEvent, InputMask, InputState,
-    InputType, Left, Paste, Right, SelectAll, SelectLeft, SelectRight, ShiftTab, Tab,
+    InputType, Left, Paste, Redo, Right, SelectAll, SelectLeft, SelectRight, ShiftTab, Tab, Undo,
     ValidationError, ValidationRules,
 };
 use crate::layout::{HStack, VStack};
@@ -43,6 +44,14 @@ pub fn init(cx: &mut App) {
         KeyBinding::new("cmd-v", Paste, Some("Input")),
         #[cfg(not(target_os = "macos"))]
         KeyBinding::new("ctrl-v", Paste, Some("Input")),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-z", Undo, Some("Input")),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-z", Undo, Some("Input")),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-shift-z", Redo, Some("Input")),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-shift-z", Redo, Some("Input")),
         KeyBinding::new("escape", Escape, Some("Input")),
     ]);
 }
@@ -78,6 +87,7 @@ pub struct Input {
 
     // Enhanced features
     input_type: Option<InputType>,
+    mask: Option<InputMask>,
     validation_rules: Option<ValidationRules>,
     helper_text: Option<SharedString>,
     show_character_count: bool,
@@ -120,6 +130,7 @@ impl Input {
 
             // Enhanced features
             input_type: None,
+            mask: None,
             validation_rules: None,
             helper_text: None,
             show_character_count: false,
@@ -252,6 +263,13 @@ impl Input {
         self
     }
 
+    /// Format the value as it is typed, e.g. `InputMask::Custom("##/##/####")`. Takes
+    /// precedence over the mask an input type implies.
+    pub fn mask(mut self, mask: InputMask) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+
     /// Set validation rules
     pub fn validation_rules(mut self, rules: ValidationRules) -> Self {
         self.validation_rules = Some(rules);
@@ -461,6 +479,9 @@ impl RenderOnce for Input {
                     _ => {}
                 }
             }
+            if let Some(mask) = self.mask {
+                state.input_mask = mask;
+            }
 
             if let Some(mut rules) = self.validation_rules.clone() {
                 if let Some(ref custom_validator) = self.custom_validator {
@@ -693,6 +714,8 @@ impl RenderOnce for Input {
                             .on_action(window.listener_for(&self.state, InputState::copy))
                             .on_action(window.listener_for(&self.state, InputState::cut))
                             .on_action(window.listener_for(&self.state, InputState::paste))
+                            .on_action(window.listener_for(&self.state, InputState::undo))
+                            .on_action(window.listener_for(&self.state, InputState::redo))
                             .on_action(window.listener_for(&self.state, InputState::enter))
                             .on_action(window.listener_for(&self.state, InputState::tab))
                             .on_action(window.listener_for(&self.state, InputState::shift_tab))
@@ -0,0 +1,192 @@
+//! Undo history for single-line text inputs.
+//!
+//! Inputs hold little text, so each undo step is a snapshot of the whole value and its
+//! selection. Consecutive single-character typing or deleting within
+//! [`UNDO_GROUP_INTERVAL`] joins one step, the same grouping the code editor uses.
+
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+/// The longest pause that still continues the current undo step. Shared with the code
+/// editor so inputs and editors group typing alike.
+pub const UNDO_GROUP_INTERVAL: Duration = Duration::from_millis(500);
+const UNDO_LIMIT: usize = 100;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct InputSnapshot {
+    pub text: String,
+    pub selection: Range<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum EditKind {
+    Typing,
+    Deleting,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct LastEdit {
+    kind: EditKind,
+    /// Cursor offset after the edit, where a continuing edit has to start.
+    cursor: usize,
+    at: Instant,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct InputHistory {
+    undo: Vec<InputSnapshot>,
+    redo: Vec<InputSnapshot>,
+    last: Option<LastEdit>,
+}
+
+impl InputHistory {
+    /// Note an edit about to replace `range` of `before.text` with `inserted`.
+    pub fn record(
+        &mut self,
+        before: InputSnapshot,
+        range: Range<usize>,
+        inserted: &str,
+        now: Instant,
+    ) {
+        let single_char = |text: &str| text.chars().count() == 1;
+        let edit = if range.is_empty() && single_char(inserted) {
+            Some(LastEdit {
+                kind: EditKind::Typing,
+                cursor: range.start + inserted.len(),
+                at: now,
+            })
+        } else if inserted.is_empty() && single_char(&before.text[range.clone()]) {
+            Some(LastEdit {
+                kind: EditKind::Deleting,
+                cursor: range.start,
+                at: now,
+            })
+        } else {
+            None
+        };
+
+        let continues = match (self.last, edit) {
+            (Some(last), Some(edit)) if last.kind == edit.kind => {
+                let recent = now.saturating_duration_since(last.at) <= UNDO_GROUP_INTERVAL;
+                let adjacent = match edit.kind {
+                    // A space after a word starts the next step.
+                    EditKind::Typing => {
+                        range.start == last.cursor
+                            && !(inserted.starts_with(char::is_whitespace)
+                                && !before.text[..range.start].ends_with(char::is_whitespace))
+                    }
+                    // Backspace moves the cursor left; forward delete leaves it.
+                    EditKind::Deleting => range.end == last.cursor || range.start == last.cursor,
+                };
+                recent && adjacent
+            }
+            _ => false,
+        };
+
+        if !continues {
+            self.push(before);
+        }
+        self.redo.clear();
+        self.last = edit;
+    }
+
+    /// Start a step that nothing will join, e.g. before an IME composition.
+    pub fn checkpoint(&mut self, before: InputSnapshot) {
+        self.push(before);
+        self.redo.clear();
+        self.last = None;
+    }
+
+    fn push(&mut self, snapshot: InputSnapshot) {
+        self.undo.push(snapshot);
+        if self.undo.len() > UNDO_LIMIT {
+            self.undo.remove(0);
+        }
+    }
+
+    /// The state to restore, given the current one to redo back to.
+    pub fn undo(&mut self, current: InputSnapshot) -> Option<InputSnapshot> {
+        let previous = self.undo.pop()?;
+        self.redo.push(current);
+        self.last = None;
+        Some(previous)
+    }
+
+    pub fn redo(&mut self, current: InputSnapshot) -> Option<InputSnapshot> {
+        let next = self.redo.pop()?;
+        self.undo.push(current);
+        self.last = None;
+        Some(next)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.last = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(text: &str) -> InputSnapshot {
+        InputSnapshot {
+            text: text.to_string(),
+            selection: text.len()..text.len(),
+        }
+    }
+
+    fn type_text(history: &mut InputHistory, text: &mut String, typed: &str, now: Instant) {
+        for ch in typed.chars() {
+            let at = text.len();
+            history.record(snapshot(text), at..at, &ch.to_string(), now);
+            text.push(ch);
+        }
+    }
+
+    #[test]
+    fn coalesces_typing_by_word() {
+        let mut history = InputHistory::default();
+        let mut text = String::new();
+        let now = Instant::now();
+        type_text(&mut history, &mut text, "hello world", now);
+
+        let restored = history.undo(snapshot(&text)).unwrap();
+        assert_eq!(restored.text, "hello");
+        let restored = history.undo(snapshot(&restored.text)).unwrap();
+        assert_eq!(restored.text, "");
+        assert!(!history.can_undo());
+
+        let redone = history.redo(snapshot("")).unwrap();
+        assert_eq!(redone.text, "hello");
+        assert!(history.can_redo());
+    }
+
+    #[test]
+    fn pauses_and_clears_start_new_steps() {
+        let mut history = InputHistory::default();
+        let mut text = String::new();
+        let now = Instant::now();
+        type_text(&mut history, &mut text, "ab", now);
+        type_text(&mut history, &mut text, "cd", now + Duration::from_secs(1));
+        history.record(snapshot(&text), 0..text.len(), "", now);
+        text.clear();
+
+        assert_eq!(history.undo(snapshot(&text)).unwrap().text, "abcd");
+        assert_eq!(history.undo(snapshot("abcd")).unwrap().text, "ab");
+        assert!(history.can_redo());
+
+        // A new edit drops the redo steps.
+        history.record(snapshot("ab"), 2..2, "x", now);
+        assert!(!history.can_redo());
+    }
+}
@@ -0,0 +1,292 @@
+//! Mask patterns for inputs that format as the user types.
+//!
+//! A pattern is a run of slots and literals: `#` takes a digit, `A` a letter, `*` a
+//! letter or digit, and any other character is written as is (`\` makes the next one
+//! literal too). `(###) ###-####` turns `5551234567` into `(555) 123-4567`.
+//!
+//! The text the user typed into the slots is the raw value; the same text with the
+//! literals filled in is the formatted value. Literals are only written once a slot
+//! before them is filled, and then eagerly up to the next slot, so typing `12` into
+//! `##/##/####` gives `12/` with the cursor ready for the day.
+
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Slot {
+    Digit,
+    Letter,
+    Alphanumeric,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Slot(Slot),
+    Literal(char),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaskPattern {
+    tokens: Vec<Token>,
+    uppercase: bool,
+}
+
+impl MaskPattern {
+    pub fn new(pattern: &str) -> Self {
+        let mut tokens = Vec::new();
+        let mut chars = pattern.chars();
+        while let Some(ch) = chars.next() {
+            tokens.push(match ch {
+                '#' => Token::Slot(Slot::Digit),
+                'A' => Token::Slot(Slot::Letter),
+                '*' => Token::Slot(Slot::Alphanumeric),
+                '\\' => Token::Literal(chars.next().unwrap_or('\\')),
+                _ => Token::Literal(ch),
+            });
+        }
+        Self {
+            tokens,
+            uppercase: false,
+        }
+    }
+
+    /// Upper-case letters as they are typed, e.g. for IBANs or licence keys.
+    pub fn uppercase(mut self) -> Self {
+        self.uppercase = true;
+        self
+    }
+
+    /// How many characters the raw value holds when every slot is filled.
+    pub fn slot_count(&self) -> usize {
+        self.tokens
+            .iter()
+            .filter(|token| matches!(token, Token::Slot(_)))
+            .count()
+    }
+
+    pub fn is_complete(&self, formatted: &str) -> bool {
+        self.raw_chars(formatted).len() == self.slot_count()
+    }
+
+    /// The characters of `text` that fill slots, without the literals.
+    pub fn raw(&self, text: &str) -> String {
+        self.raw_chars(text).into_iter().map(|(_, ch)| ch).collect()
+    }
+
+    /// `text` fitted into the pattern. Characters that no slot takes are dropped, so
+    /// both raw and already formatted text give the same result.
+    pub fn format(&self, text: &str) -> String {
+        self.layout(&self.raw(text)).0
+    }
+
+    /// Replace `range` of the formatted `text` with `insert` and reformat, returning the
+    /// new text and where the cursor goes. The cursor follows the last character put
+    /// into a slot and, after typing, skips the literals after it, so typing a literal
+    /// that is already there just moves past it.
+    pub fn edit(&self, text: &str, range: Range<usize>, insert: &str) -> (String, usize) {
+        let raw = self.raw_chars(text);
+        let before: Vec<char> = raw
+            .iter()
+            .filter(|(ix, _)| *ix < range.start)
+            .map(|(_, ch)| *ch)
+            .collect();
+        let after = raw
+            .iter()
+            .filter(|(ix, _)| *ix >= range.end)
+            .map(|(_, ch)| *ch);
+
+        let slots: Vec<Slot> = self
+            .tokens
+            .iter()
+            .filter_map(|token| match token {
+                Token::Slot(slot) => Some(*slot),
+                Token::Literal(_) => None,
+            })
+            .collect();
+        let mut inserted = Vec::new();
+        for ch in insert.chars() {
+            let Some(slot) = slots.get(before.len() + inserted.len()) else {
+                break;
+            };
+            if let Some(ch) = self.accept(*slot, ch) {
+                inserted.push(ch);
+            }
+        }
+
+        let cursor_slots = before.len() + inserted.len();
+        let combined: String = before
+            .iter()
+            .chain(inserted.iter())
+            .copied()
+            .chain(after)
+            .collect();
+        // Reflow: a character pulled back into a slot of another kind is dropped.
+        let raw = self.raw(&combined);
+        let (formatted, slot_ends) = self.layout(&raw);
+        let cursor = match cursor_slots.min(slot_ends.len()) {
+            0 => 0,
+            n => {
+                let end = slot_ends[n - 1];
+                if insert.is_empty() {
+                    end
+                } else {
+                    // Past the literals written after the slot, up to the next one.
+                    slot_ends.get(n).map_or(formatted.len(), |next_end| {
+                        let next = &formatted[end..*next_end];
+                        end + next.len() - next.chars().last().map_or(0, char::len_utf8)
+                    })
+                }
+            }
+        };
+        (formatted, cursor)
+    }
+
+    /// Where backspace at `offset` should delete from: the start of the slot character
+    /// before it, stepping over literals.
+    pub fn previous_boundary(&self, text: &str, offset: usize) -> usize {
+        self.raw_chars(text)
+            .into_iter()
+            .rev()
+            .find(|(ix, _)| *ix < offset)
+            .map_or(0, |(ix, _)| ix)
+    }
+
+    /// Where forward delete at `offset` should delete to: the end of the slot
+    /// character after it, stepping over literals.
+    pub fn next_boundary(&self, text: &str, offset: usize) -> usize {
+        self.raw_chars(text)
+            .into_iter()
+            .find(|(ix, _)| *ix >= offset)
+            .map_or(text.len(), |(ix, ch)| ix + ch.len_utf8())
+    }
+
+    fn accept(&self, slot: Slot, ch: char) -> Option<char> {
+        let accepted = match slot {
+            Slot::Digit => ch.is_ascii_digit(),
+            Slot::Letter => ch.is_alphabetic(),
+            Slot::Alphanumeric => ch.is_alphanumeric(),
+        };
+        if !accepted {
+            None
+        } else if self.uppercase {
+            Some(ch.to_uppercase().next().unwrap_or(ch))
+        } else {
+            Some(ch)
+        }
+    }
+
+    /// The slot characters of `text` with their byte offsets. Literals of the pattern
+    /// are matched where they appear and skipped where they don't, so this reads raw
+    /// and formatted text alike.
+    fn raw_chars(&self, text: &str) -> Vec<(usize, char)> {
+        let mut raw = Vec::new();
+        let mut tokens = self.tokens.iter().peekable();
+        'chars: for (ix, ch) in text.char_indices() {
+            loop {
+                match tokens.peek() {
+                    Some(Token::Literal(literal)) => {
+                        tokens.next();
+                        if *literal == ch {
+                            continue 'chars;
+                        }
+                    }
+                    Some(Token::Slot(slot)) => {
+                        if let Some(ch) = self.accept(*slot, ch) {
+                            raw.push((ix, ch));
+                            tokens.next();
+                        }
+                        continue 'chars;
+                    }
+                    None => break 'chars,
+                }
+            }
+        }
+        raw
+    }
+
+    /// Format `raw`, which has to fit the slots, returning the text and the byte
+    /// offset after each slot character in it.
+    fn layout(&self, raw: &str) -> (String, Vec<usize>) {
+        let mut formatted = String::new();
+        let mut slot_ends = Vec::new();
+        let mut pending = String::new();
+        let mut raw = raw.chars();
+        for token in &self.tokens {
+            match token {
+                Token::Literal(literal) => pending.push(*literal),
+                Token::Slot(_) => {
+                    let Some(ch) = raw.next() else {
+                        break;
+                    };
+                    formatted.push_str(&pending);
+                    pending.clear();
+                    formatted.push(ch);
+                    slot_ends.push(formatted.len());
+                }
+            }
+        }
+        if !formatted.is_empty() {
+            formatted.push_str(&pending);
+        }
+        (formatted, slot_ends)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn phone() -> MaskPattern {
+        MaskPattern::new("(###) ###-####")
+    }
+
+    #[test]
+    fn formats_raw_and_formatted_text_alike() {
+        let phone = phone();
+        assert_eq!(phone.format("5551234567"), "(555) 123-4567");
+        assert_eq!(phone.format("(555) 123-4567"), "(555) 123-4567");
+        assert_eq!(phone.format("555123"), "(555) 123-");
+        assert_eq!(phone.format(""), "");
+        assert_eq!(phone.raw("(555) 123-4567"), "5551234567");
+        assert!(phone.is_complete("(555) 123-4567"));
+    }
+
+    #[test]
+    fn typing_skips_literals() {
+        let date = MaskPattern::new("##/##/####");
+        let mut text = String::new();
+        let mut cursor = 0;
+        for ch in ["1", "2", "/", "3", "1"] {
+            (text, cursor) = date.edit(&text, cursor..cursor, ch);
+        }
+        assert_eq!((text.as_str(), cursor), ("12/31/", 6));
+
+        // Typing into the middle pushes the rest along and lands after the literal.
+        let (text, cursor) = date.edit("12/31/2024", 1..1, "0");
+        assert_eq!((text.as_str(), cursor), ("10/23/1202", 3));
+    }
+
+    #[test]
+    fn backspace_steps_over_literals() {
+        let phone = phone();
+        let text = "(555) 123-";
+        let start = phone.previous_boundary(text, text.len());
+        assert_eq!(start, 8);
+        let (text, cursor) = phone.edit(text, start..text.len(), "");
+        assert_eq!((text.as_str(), cursor), ("(555) 12", 8));
+
+        let end = phone.next_boundary("(555) 123-4567", 4);
+        let (text, cursor) = phone.edit("(555) 123-4567", 4..end, "");
+        assert_eq!((text.as_str(), cursor), ("(555) 234-567", 4));
+    }
+
+    #[test]
+    fn letters_and_escapes() {
+        let iban = MaskPattern::new("AA## **** ****").uppercase();
+        assert_eq!(iban.format("gb82west"), "GB82 WEST ");
+        assert_eq!(iban.format("1b82"), "B");
+
+        let tagged = MaskPattern::new("\\#-###");
+        assert_eq!(tagged.format("42"), "#-42");
+        assert_eq!(tagged.raw("#-42"), "42");
+    }
+}
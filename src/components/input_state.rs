@@ -1,3 +1,5 @@
+use crate::components::input_history::{InputHistory, InputSnapshot};
+use crate::components::input_mask::MaskPattern;
 use crate::theme::use_theme;
 /// Interactive text input state management with validation and accessibility
 ///
@@ -40,6 +42,8 @@ actions!(
         Tab,
         ShiftTab,
         Escape,
+        Undo,
+        Redo,
     ]
 );
 
@@ -127,10 +131,29 @@ pub enum InputMask {
     Date,
     /// Time: HH:MM
     Time,
-    /// Custom mask pattern
+    /// IBAN in groups of four, letters upper-cased: GB82 WEST 1234 ...
+    Iban,
+    /// Custom mask pattern: `#` digit, `A` letter, `*` either, e.g. `##-AAA-####`.
+    /// See [`MaskPattern`].
     Custom(&'static str),
 }
 
+impl InputMask {
+    pub fn pattern(&self) -> Option<MaskPattern> {
+        match self {
+            InputMask::None => None,
+            InputMask::Phone => Some(MaskPattern::new("(###) ###-####")),
+            InputMask::CreditCard => Some(MaskPattern::new("#### #### #### #### ###")),
+            InputMask::Date => Some(MaskPattern::new("##/##/####")),
+            InputMask::Time => Some(MaskPattern::new("##:##")),
+            InputMask::Iban => {
+                Some(MaskPattern::new("AA## **** **** **** **** **** **** **").uppercase())
+            }
+            InputMask::Custom(pattern) => Some(MaskPattern::new(pattern)),
+        }
+    }
+}
+
 /// Events emitted by the InputState
 #[derive(Clone, Debug)]
 pub enum InputEvent {
@@ -177,6 +200,7 @@ pub struct InputState {
     pub shake_triggered: bool,
     pub(crate) shake_count: u32,
     cursor_position_override: Option<usize>,
+    history: InputHistory,
 }
 
 impl EventEmitter<InputEvent> for InputState {}
@@ -215,6 +239,7 @@ impl InputState {
             shake_triggered: false,
             shake_count: 0,
             cursor_position_override: None,
+            history: InputHistory::default(),
         }
     }
 
@@ -297,11 +322,37 @@ impl InputState {
         cx.notify();
     }
 
+    /// Format the value with `mask` as it is typed. See [`InputMask`].
+    pub fn mask(mut self, mask: InputMask) -> Self {
+        self.input_mask = mask;
+        if let Some(pattern) = mask.pattern() {
+            self.content = pattern.format(&self.content).into();
+            let len = self.content.len();
+            self.selected_range = len..len;
+        }
+        self
+    }
+
     pub fn content(&self) -> &str {
         &self.content
     }
 
-    /// Set the text content with validation
+    /// The value as typed, without the literals a mask adds: `5551234567` for
+    /// `(555) 123-4567`. The same as [`Self::content`] without a mask.
+    pub fn raw_value(&self) -> String {
+        match self.input_mask.pattern() {
+            Some(pattern) => pattern.raw(&self.content),
+            None => self.content.to_string(),
+        }
+    }
+
+    /// The value as shown, with a mask's literals.
+    pub fn formatted_value(&self) -> &str {
+        &self.content
+    }
+
+    /// Set the text content with validation. Replacing the value is an undo step, so
+    /// clearing a field can be undone.
     pub fn set_value(
         &mut self,
         value: impl Into<SharedString>,
@@ -309,7 +360,15 @@ impl InputState {
         cx: &mut Context<Self>,
     ) {
         let value = value.into();
-        let filtered_value = self.filter_input(&value);
+        let mut filtered_value = self.filter_input(&value);
+        if let Some(pattern) = self.input_mask.pattern() {
+            filtered_value = pattern.format(&filtered_value);
+        }
+        if filtered_value == self.content.as_ref() {
+            return;
+        }
+        self.marked_range = None;
+        self.selected_range = 0..self.content.len();
         self.replace_text_in_range(None, &filtered_value, window, cx);
         let len = filtered_value.len();
         self.selected_range = len..len;
@@ -445,61 +504,6 @@ impl InputState {
         }
     }
 
-    /// Apply input mask formatting
-    fn apply_mask(&mut self, input: &str) -> String {
-        match self.input_mask {
-            InputMask::Phone => {
-                let digits: String = input.chars().filter(|c| c.is_ascii_digit()).collect();
-                if digits.len() <= 3 {
-                    digits
-                } else if digits.len() <= 6 {
-                    format!("({}) {}", &digits[0..3], &digits[3..])
-                } else if digits.len() <= 10 {
-                    format!("({}) {}-{}", &digits[0..3], &digits[3..6], &digits[6..])
-                } else {
-                    format!("({}) {}-{}", &digits[0..3], &digits[3..6], &digits[6..10])
-                }
-            }
-            InputMask::CreditCard => {
-                let digits: String = input.chars().filter(|c| c.is_ascii_digit()).collect();
-                digits
-                    .chars()
-                    .enumerate()
-                    .map(|(i, c)| {
-                        if i > 0 && i % 4 == 0 {
-                            format!(" {}", c)
-                        } else {
-                            c.to_string()
-                        }
-                    })
-                    .collect::<String>()
-            }
-            InputMask::Date => {
-                let digits: String = input.chars().filter(|c| c.is_ascii_digit()).collect();
-                if digits.len() <= 2 {
-                    digits
-                } else if digits.len() <= 4 {
-                    format!("{}/{}", &digits[0..2], &digits[2..])
-                } else if digits.len() <= 8 {
-                    format!("{}/{}/{}", &digits[0..2], &digits[2..4], &digits[4..])
-                } else {
-                    format!("{}/{}/{}", &digits[0..2], &digits[2..4], &digits[4..8])
-                }
-            }
-            InputMask::Time => {
-                let digits: String = input.chars().filter(|c| c.is_ascii_digit()).collect();
-                if digits.len() <= 2 {
-                    digits
-                } else if digits.len() <= 4 {
-                    format!("{}:{}", &digits[0..2], &digits[2..])
-                } else {
-                    format!("{}:{}", &digits[0..2], &digits[2..4])
-                }
-            }
-            _ => input.to_string(),
-        }
-    }
-
     fn validate_email(&self, email: &str) -> Result<(), ValidationError> {
         if !EMAIL_REGEX.is_match(email) {
             return Err(ValidationError {
@@ -695,18 +699,98 @@ impl InputState {
 
     pub fn backspace(&mut self, _: &Backspace, window: &mut Window, cx: &mut Context<Self>) {
         if self.selected_range.is_empty() {
-            self.select_to(self.previous_boundary(self.cursor_offset()), cx)
+            let offset = self.cursor_offset();
+            let start = match self.input_mask.pattern() {
+                Some(pattern) => pattern.previous_boundary(&self.content, offset),
+                None => self.previous_boundary(offset),
+            };
+            self.select_to(start, cx)
         }
         self.replace_text_in_range(None, "", window, cx)
     }
 
     pub fn delete(&mut self, _: &Delete, window: &mut Window, cx: &mut Context<Self>) {
         if self.selected_range.is_empty() {
-            self.select_to(self.next_boundary(self.cursor_offset()), cx)
+            let offset = self.cursor_offset();
+            let end = match self.input_mask.pattern() {
+                Some(pattern) => pattern.next_boundary(&self.content, offset),
+                None => self.next_boundary(offset),
+            };
+            self.select_to(end, cx)
         }
         self.replace_text_in_range(None, "", window, cx)
     }
 
+    pub fn undo(&mut self, _: &Undo, _: &mut Window, cx: &mut Context<Self>) {
+        if self.marked_range.is_some() {
+            return;
+        }
+        if let Some(snapshot) = self.history.undo(self.snapshot()) {
+            self.restore(snapshot, cx);
+        }
+    }
+
+    pub fn redo(&mut self, _: &Redo, _: &mut Window, cx: &mut Context<Self>) {
+        if self.marked_range.is_some() {
+            return;
+        }
+        if let Some(snapshot) = self.history.redo(self.snapshot()) {
+            self.restore(snapshot, cx);
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    /// Forget the undo history, e.g. after the form it belongs to is saved.
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// Put back an earlier value, such as the last saved one, as a single undo step.
+    /// Unlike `set_value` the value is taken as is, without filtering or masking.
+    pub fn revert_to(&mut self, value: impl Into<SharedString>, cx: &mut Context<Self>) {
+        let value = value.into();
+        if value == self.content {
+            return;
+        }
+        self.history.checkpoint(self.snapshot());
+        let len = value.len();
+        self.restore(
+            InputSnapshot {
+                text: value.to_string(),
+                selection: len..len,
+            },
+            cx,
+        );
+    }
+
+    fn snapshot(&self) -> InputSnapshot {
+        InputSnapshot {
+            text: self.content.to_string(),
+            selection: self.selected_range.clone(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: InputSnapshot, cx: &mut Context<Self>) {
+        self.content = snapshot.text.into();
+        self.selected_range = snapshot.selection;
+        self.selection_reversed = false;
+        self.marked_range = None;
+
+        if self.validate_on_change {
+            self.validate(cx).ok();
+        }
+
+        cx.emit(InputEvent::Change);
+        cx.notify();
+    }
+
     pub fn tab(&mut self, _: &Tab, window: &mut Window, cx: &mut Context<Self>) {
         window.focus_next();
         cx.emit(InputEvent::Tab);
@@ -996,20 +1080,23 @@ impl EntityInputHandler for InputState {
             .map(|range_utf16| self.range_from_utf16(range_utf16))
             .or(self.marked_range.clone())
             .unwrap_or(self.selected_range.clone());
+        // A composition being committed was recorded when it started.
+        let composing = self.marked_range.is_some();
+        let before = self.snapshot();
 
         let filtered_text = self.filter_input(new_text);
-        let formatted_text = if self.input_mask != InputMask::None {
-            self.apply_mask(&filtered_text)
-        } else {
-            filtered_text
-        };
 
-        if let Some(max_length) = self.validation_rules.max_length {
-            let new_length = self.content.len() - (range.end - range.start) + formatted_text.len();
+        if let Some(pattern) = self.input_mask.pattern() {
+            // The mask bounds the length; the edit is reformatted as a whole.
+            let (content, cursor) = pattern.edit(&self.content, range.clone(), &filtered_text);
+            self.content = content.into();
+            self.selected_range = cursor..cursor;
+        } else if let Some(max_length) = self.validation_rules.max_length {
+            let new_length = self.content.len() - (range.end - range.start) + filtered_text.len();
             if new_length > max_length {
                 let allowed_length =
                     max_length.saturating_sub(self.content.len() - (range.end - range.start));
-                let truncated: String = formatted_text.chars().take(allowed_length).collect();
+                let truncated: String = filtered_text.chars().take(allowed_length).collect();
 
                 self.content = (self.content[0..range.start].to_owned()
                     + &truncated
@@ -1018,23 +1105,33 @@ impl EntityInputHandler for InputState {
                 self.selected_range = range.start + truncated.len()..range.start + truncated.len();
             } else {
                 self.content = (self.content[0..range.start].to_owned()
-                    + &formatted_text
+                    + &filtered_text
                     + &self.content[range.end..])
                     .into();
                 self.selected_range =
-                    range.start + formatted_text.len()..range.start + formatted_text.len();
+                    range.start + filtered_text.len()..range.start + filtered_text.len();
             }
         } else {
             self.content = (self.content[0..range.start].to_owned()
-                + &formatted_text
+                + &filtered_text
                 + &self.content[range.end..])
                 .into();
             self.selected_range =
-                range.start + formatted_text.len()..range.start + formatted_text.len();
+                range.start + filtered_text.len()..range.start + filtered_text.len();
         }
 
         self.marked_range.take();
 
+        if !composing && self.content.as_ref() != before.text {
+            // A mask can leave the cursor before the edit, after deleting only literals.
+            let inserted = self
+                .content
+                .get(range.start..self.selected_range.end)
+                .unwrap_or("");
+            self.history
+                .record(before, range, inserted, std::time::Instant::now());
+        }
+
         if self.validate_on_change {
             self.validate(cx).ok();
         }
@@ -1056,6 +1153,9 @@ impl EntityInputHandler for InputState {
             .map(|range_utf16| self.range_from_utf16(range_utf16))
             .or(self.marked_range.clone())
             .unwrap_or(self.selected_range.clone());
+        if self.marked_range.is_none() {
+            self.history.checkpoint(self.snapshot());
+        }
 
         let filtered_text = self.filter_input(new_text);
         self.content =
@@ -0,0 +1,415 @@
+//! Rotary knob for audio and creative tools.
+//!
+//! Drag up or right to increase (shift for fine control), scroll, or use the arrow,
+//! page and home/end keys while focused. Double-click resets to the default value.
+
+use crate::theme::use_theme;
+use crate::util::track_window_drag;
+use gpui::{prelude::FluentBuilder as _, *};
+use std::f32::consts::PI;
+
+/// Angle of the minimum value, clockwise from twelve o'clock. The maximum mirrors it.
+const START_ANGLE: f32 = -0.75 * PI;
+const SWEEP: f32 = 1.5 * PI;
+/// Pointer travel, in pixels, that sweeps the full range.
+const DRAG_RANGE: f32 = 200.0;
+/// Scroll distance, in pixels, per step.
+const SCROLL_STEP: f32 = 16.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KnobEvent {
+    Change(f32),
+}
+
+pub struct KnobState {
+    min: f32,
+    max: f32,
+    value: f32,
+    default_value: f32,
+    step: f32,
+    focus_handle: FocusHandle,
+    /// Last pointer position and unstepped value while dragging.
+    drag: Option<(Point<Pixels>, f32)>,
+    scroll_remainder: f32,
+}
+
+impl EventEmitter<KnobEvent> for KnobState {}
+
+impl KnobState {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        Self {
+            min: 0.0,
+            max: 100.0,
+            value: 0.0,
+            default_value: 0.0,
+            step: 1.0,
+            focus_handle: cx.focus_handle(),
+            drag: None,
+            scroll_remainder: 0.0,
+        }
+    }
+
+    pub fn min(&self) -> f32 {
+        self.min
+    }
+
+    pub fn max(&self) -> f32 {
+        self.max
+    }
+
+    pub fn set_range(&mut self, min: f32, max: f32, cx: &mut Context<Self>) {
+        self.min = min.min(max);
+        self.max = max.max(min);
+        self.default_value = self.default_value.clamp(self.min, self.max);
+        self.set_value(self.value, cx);
+        cx.notify();
+    }
+
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    /// Set the value, clamped and snapped to the step, emitting [`KnobEvent::Change`]
+    /// if it changed.
+    pub fn set_value(&mut self, value: f32, cx: &mut Context<Self>) {
+        let clamped = value.clamp(self.min, self.max);
+        let stepped = (((clamped - self.min) / self.step).round() * self.step + self.min)
+            .clamp(self.min, self.max);
+
+        if (self.value - stepped).abs() > f32::EPSILON {
+            self.value = stepped;
+            cx.emit(KnobEvent::Change(stepped));
+            cx.notify();
+        }
+    }
+
+    /// Value restored by double-clicking the knob.
+    pub fn set_default_value(&mut self, value: f32) {
+        self.default_value = value.clamp(self.min, self.max);
+    }
+
+    pub fn step(&self) -> f32 {
+        self.step
+    }
+
+    pub fn set_step(&mut self, step: f32, cx: &mut Context<Self>) {
+        self.step = step.abs().max(f32::EPSILON);
+        cx.notify();
+    }
+
+    fn normalized(&self) -> f32 {
+        if self.max == self.min {
+            return 0.0;
+        }
+        ((self.value - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+    }
+
+    fn nudge(&mut self, steps: f32, cx: &mut Context<Self>) {
+        self.set_value(self.value + steps * self.step, cx);
+    }
+
+    fn drag_to(&mut self, position: Point<Pixels>, fine: bool, cx: &mut Context<Self>) {
+        let Some((last, raw)) = self.drag else {
+            return;
+        };
+        let travel = (position.x - last.x) / px(1.0) - (position.y - last.y) / px(1.0);
+        let scale = if fine { 0.1 } else { 1.0 };
+        let raw =
+            (raw + travel / DRAG_RANGE * (self.max - self.min) * scale).clamp(self.min, self.max);
+        self.drag = Some((position, raw));
+        self.set_value(raw, cx);
+    }
+
+    fn scroll(&mut self, delta: ScrollDelta, cx: &mut Context<Self>) {
+        let y = match delta {
+            ScrollDelta::Pixels(p) => p.y / px(1.0),
+            ScrollDelta::Lines(l) => l.y * SCROLL_STEP,
+        };
+        self.scroll_remainder += y;
+        let steps = (self.scroll_remainder / SCROLL_STEP).trunc();
+        if steps != 0.0 {
+            self.scroll_remainder -= steps * SCROLL_STEP;
+            self.nudge(steps, cx);
+        }
+    }
+
+    fn handle_key(&mut self, key: &str, cx: &mut Context<Self>) -> bool {
+        let page = ((self.max - self.min) / self.step / 10.0).max(1.0).round();
+        match key {
+            "up" | "right" => self.nudge(1.0, cx),
+            "down" | "left" => self.nudge(-1.0, cx),
+            "pageup" => self.nudge(page, cx),
+            "pagedown" => self.nudge(-page, cx),
+            "home" => self.set_value(self.min, cx),
+            "end" => self.set_value(self.max, cx),
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl Focusable for KnobState {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for KnobState {
+    fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+        div()
+    }
+}
+
+#[derive(IntoElement)]
+pub struct Knob {
+    state: Entity<KnobState>,
+    size: Pixels,
+    label: Option<SharedString>,
+    show_value: bool,
+    disabled: bool,
+    style: StyleRefinement,
+}
+
+impl Knob {
+    pub fn new(state: Entity<KnobState>) -> Self {
+        Self {
+            state,
+            size: px(48.0),
+            label: None,
+            show_value: false,
+            disabled: false,
+            style: StyleRefinement::default(),
+        }
+    }
+
+    /// Diameter of the dial.
+    pub fn size(mut self, size: Pixels) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn label(mut self, label: impl Into<SharedString>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    pub fn show_value(mut self, show: bool) -> Self {
+        self.show_value = show;
+        self
+    }
+
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+}
+
+impl Styled for Knob {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+impl RenderOnce for Knob {
+    fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let theme = use_theme();
+        let user_style = self.style;
+        let state = self.state.read(cx);
+        let focus_handle = state.focus_handle.clone();
+        let is_focused = focus_handle.is_focused(window);
+        let normalized = state.normalized();
+        let value_text = format!("{:.prec$}", state.value, prec = step_precision(state.step));
+        let dragging = state.drag.is_some();
+        let disabled = self.disabled;
+
+        let track_color = theme.tokens.muted;
+        let value_color = if disabled {
+            theme.tokens.muted_foreground
+        } else {
+            theme.tokens.primary
+        };
+        let pointer_color = theme.tokens.foreground;
+        let size = self.size;
+
+        div()
+            .flex()
+            .flex_col()
+            .items_center()
+            .gap_1()
+            .when(disabled, |this| this.opacity(0.5))
+            .map(|mut this| {
+                this.style().refine(&user_style);
+                this
+            })
+            .child(
+                div()
+                    .id(("knob", self.state.entity_id()))
+                    .size(size)
+                    .rounded_full()
+                    .when(is_focused && !disabled, |this| {
+                        this.shadow(smallvec::smallvec![theme.tokens.focus_ring_light()])
+                    })
+                    .when(!disabled, |this| {
+                        this.track_focus(&focus_handle.tab_index(0).tab_stop(true))
+                            .cursor(CursorStyle::ResizeUpDown)
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                window.listener_for(
+                                    &self.state,
+                                    |state, event: &MouseDownEvent, window, cx| {
+                                        window.focus(&state.focus_handle);
+                                        if event.click_count == 2 {
+                                            state.set_value(state.default_value, cx);
+                                        }
+                                        state.drag = Some((event.position, state.value));
+                                        cx.notify();
+                                        cx.stop_propagation();
+                                    },
+                                ),
+                            )
+                            .on_scroll_wheel(window.listener_for(
+                                &self.state,
+                                |state, event: &ScrollWheelEvent, _, cx| {
+                                    state.scroll(event.delta, cx);
+                                    cx.stop_propagation();
+                                },
+                            ))
+                            .on_key_down(window.listener_for(
+                                &self.state,
+                                |state, event: &KeyDownEvent, _, cx| {
+                                    if state.handle_key(&event.keystroke.key, cx) {
+                                        cx.stop_propagation();
+                                    }
+                                },
+                            ))
+                    })
+                    .child({
+                        let knob = self.state.clone();
+                        canvas(
+                            |_, _, _| {},
+                            move |bounds, _, window, _| {
+                                paint_knob(
+                                    bounds,
+                                    normalized,
+                                    track_color,
+                                    value_color,
+                                    pointer_color,
+                                    window,
+                                );
+                                if dragging {
+                                    track_window_drag(
+                                        knob,
+                                        window,
+                                        |state, event, cx| {
+                                            state.drag_to(event.position, event.modifiers.shift, cx)
+                                        },
+                                        |state, cx| {
+                                            state.drag = None;
+                                            cx.notify();
+                                        },
+                                    );
+                                }
+                            },
+                        )
+                        .size_full()
+                    }),
+            )
+            .when(self.show_value, |this| {
+                this.child(
+                    div()
+                        .text_xs()
+                        .text_color(theme.tokens.foreground)
+                        .child(value_text),
+                )
+            })
+            .when_some(self.label, |this, label| {
+                this.child(
+                    div()
+                        .text_xs()
+                        .text_color(theme.tokens.muted_foreground)
+                        .child(label),
+                )
+            })
+    }
+}
+
+fn paint_knob(
+    bounds: Bounds<Pixels>,
+    normalized: f32,
+    track_color: Hsla,
+    value_color: Hsla,
+    pointer_color: Hsla,
+    window: &mut Window,
+) {
+    let center = bounds.center();
+    let radius = bounds.size.width.min(bounds.size.height) / 2.0;
+    let width = (radius * 0.16).max(px(2.0));
+    let arc_radius = radius - width / 2.0;
+    let angle = START_ANGLE + SWEEP * normalized;
+
+    paint_arc(
+        center,
+        arc_radius,
+        START_ANGLE,
+        START_ANGLE + SWEEP,
+        width,
+        track_color,
+        window,
+    );
+    if normalized > 0.0 {
+        paint_arc(
+            center,
+            arc_radius,
+            START_ANGLE,
+            angle,
+            width,
+            value_color,
+            window,
+        );
+    }
+
+    let mut pointer = PathBuilder::stroke(width * 0.75);
+    pointer.move_to(point_on_circle(center, arc_radius * 0.3, angle));
+    pointer.line_to(point_on_circle(center, arc_radius - width, angle));
+    if let Ok(path) = pointer.build() {
+        window.paint_path(path, pointer_color);
+    }
+}
+
+fn paint_arc(
+    center: Point<Pixels>,
+    radius: Pixels,
+    from: f32,
+    to: f32,
+    width: Pixels,
+    color: Hsla,
+    window: &mut Window,
+) {
+    let segments = (((to - from).abs() / 0.05).ceil() as usize).max(1);
+    let mut builder = PathBuilder::stroke(width);
+    builder.move_to(point_on_circle(center, radius, from));
+    for i in 1..=segments {
+        let angle = from + (to - from) * i as f32 / segments as f32;
+        builder.line_to(point_on_circle(center, radius, angle));
+    }
+    if let Ok(path) = builder.build() {
+        window.paint_path(path, color);
+    }
+}
+
+/// `angle` is clockwise from twelve o'clock.
+fn point_on_circle(center: Point<Pixels>, radius: Pixels, angle: f32) -> Point<Pixels> {
+    point(
+        center.x + radius * angle.sin(),
+        center.y - radius * angle.cos(),
+    )
+}
+
+/// Decimal places needed to show multiples of `step`.
+pub(crate) fn step_precision(step: f32) -> usize {
+    if step >= 1.0 {
+        0
+    } else {
+        (-step.log10()).ceil().clamp(0.0, 6.0) as usize
+    }
+}
@@ -0,0 +1,443 @@
+//! Named layout presets, such as "Coding", "Review" or "Presentation", and switching
+//! between them.
+//!
+//! A preset says which panels are shown and how big they are. Panels are wrapped in
+//! [`PresetPanel`] under an id the presets refer to. Switching presets animates each
+//! panel from its old size to its new one, and hidden panels shrink away. Panels a
+//! preset doesn't mention keep their current layout.
+//!
+//! Switch with [`apply`], the [`NextLayoutPreset`] and [`PreviousLayoutPreset`] actions
+//! or the entries from [`palette_commands`]. When the user resizes or toggles a panel
+//! themselves, report it with [`set_panel_size`] or [`set_panel_visible`], so
+//! [`save_current`] can capture the layout as a new preset.
+//!
+//! The app keeps presets between sessions by saving [`presets`] and [`active_name`]
+//! with its other settings and handing them to [`load`] at startup.
+//!
+//! ```rust,ignore
+//! layout_presets::load(
+//!     vec![
+//!         LayoutPreset::new("Coding").show("sidebar", px(260.0)).show("terminal", px(220.0)),
+//!         LayoutPreset::new("Review").show("sidebar", px(340.0)).hide("terminal"),
+//!         LayoutPreset::new("Presentation").hide("sidebar").hide("terminal"),
+//!     ],
+//!     saved_active.as_deref(),
+//!     cx,
+//! );
+//!
+//! div()
+//!     .flex()
+//!     .size_full()
+//!     .child(PresetPanel::new("sidebar", Axis::Horizontal, sidebar))
+//!     .child(
+//!         div()
+//!             .flex()
+//!             .flex_col()
+//!             .flex_1()
+//!             .child(editor)
+//!             .child(PresetPanel::new("terminal", Axis::Vertical, terminal)),
+//!     )
+//! ```
+
+use crate::animations::{durations, easings, lerp_f32, reduced_motion};
+use crate::overlays::command_palette::Command;
+use gpui::{prelude::FluentBuilder as _, *};
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+actions!(layout_presets, [NextLayoutPreset, PreviousLayoutPreset]);
+
+/// How a preset lays out one panel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PanelLayout {
+    pub visible: bool,
+    /// Width for a panel in a row, height for one in a column. `None` keeps the size
+    /// it had, or lets the panel size itself.
+    pub size: Option<Pixels>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayoutPreset {
+    pub name: SharedString,
+    panels: BTreeMap<SharedString, PanelLayout>,
+}
+
+impl LayoutPreset {
+    pub fn new(name: impl Into<SharedString>) -> Self {
+        Self {
+            name: name.into(),
+            panels: BTreeMap::new(),
+        }
+    }
+
+    /// Show `panel` at `size`.
+    pub fn show(self, panel: impl Into<SharedString>, size: impl Into<Pixels>) -> Self {
+        self.panel(
+            panel,
+            PanelLayout {
+                visible: true,
+                size: Some(size.into()),
+            },
+        )
+    }
+
+    /// Hide `panel`. It comes back at its old size when a preset shows it without one.
+    pub fn hide(self, panel: impl Into<SharedString>) -> Self {
+        self.panel(
+            panel,
+            PanelLayout {
+                visible: false,
+                size: None,
+            },
+        )
+    }
+
+    pub fn panel(mut self, panel: impl Into<SharedString>, layout: PanelLayout) -> Self {
+        self.panels.insert(panel.into(), layout);
+        self
+    }
+
+    pub fn panels(&self) -> impl Iterator<Item = (&SharedString, &PanelLayout)> {
+        self.panels.iter()
+    }
+
+    pub fn layout_of(&self, panel: &str) -> Option<PanelLayout> {
+        self.panels.get(panel).copied()
+    }
+}
+
+struct LayoutPresets {
+    presets: Vec<LayoutPreset>,
+    active: Option<SharedString>,
+    /// Each panel's layout as last applied or reported.
+    current: HashMap<SharedString, PanelLayout>,
+    /// Each panel's drawn size when the running transition began.
+    from: HashMap<SharedString, Option<Pixels>>,
+    switched_at: Option<Instant>,
+    transition: Duration,
+}
+
+impl Default for LayoutPresets {
+    fn default() -> Self {
+        Self {
+            presets: Vec::new(),
+            active: None,
+            current: HashMap::new(),
+            from: HashMap::new(),
+            switched_at: None,
+            transition: durations::NORMAL,
+        }
+    }
+}
+
+impl Global for LayoutPresets {}
+
+impl LayoutPresets {
+    /// Eased progress of the running transition, `None` once it has finished.
+    fn progress(&self) -> Option<f32> {
+        let elapsed = self.switched_at?.elapsed();
+        (elapsed < self.transition)
+            .then(|| easings::ease_out_cubic(elapsed.as_secs_f32() / self.transition.as_secs_f32()))
+    }
+
+    /// The size `panel` is drawn at right now: zero while hidden, `None` when it sizes
+    /// itself.
+    fn drawn_size(&self, panel: &str) -> Option<Pixels> {
+        let target = self.current.get(panel).and_then(|layout| {
+            if layout.visible {
+                layout.size
+            } else {
+                Some(px(0.0))
+            }
+        });
+        match (
+            self.progress(),
+            self.from.get(panel).copied().flatten(),
+            target,
+        ) {
+            (Some(t), Some(from), Some(to)) => Some(px(lerp_f32(from.0, to.0, t))),
+            _ => target,
+        }
+    }
+
+    /// Start animating from what's on screen now to the layout about to be set.
+    fn begin_transition(&mut self, animate: bool) {
+        if !animate {
+            self.switched_at = None;
+            self.from.clear();
+            return;
+        }
+        self.from = self
+            .current
+            .keys()
+            .map(|panel| (panel.clone(), self.drawn_size(panel)))
+            .collect();
+        self.switched_at = Some(Instant::now());
+    }
+
+    fn set_layout(&mut self, panel: SharedString, layout: PanelLayout) {
+        let size = layout
+            .size
+            .or_else(|| self.current.get(&panel).and_then(|current| current.size));
+        self.current.insert(
+            panel,
+            PanelLayout {
+                visible: layout.visible,
+                size,
+            },
+        );
+    }
+}
+
+fn settings(cx: &App) -> Option<&LayoutPresets> {
+    cx.try_global::<LayoutPresets>()
+}
+
+fn update(cx: &mut App, f: impl FnOnce(&mut LayoutPresets, bool)) {
+    let animate = !reduced_motion(cx);
+    cx.default_global::<LayoutPresets>();
+    cx.update_global::<LayoutPresets, _>(|presets, _| f(presets, animate));
+    cx.refresh_windows();
+}
+
+pub fn init(cx: &mut App) {
+    cx.on_action(|_: &NextLayoutPreset, cx| cycle(1, cx));
+    cx.on_action(|_: &PreviousLayoutPreset, cx| cycle(-1, cx));
+}
+
+/// Replace the presets, e.g. with the ones saved last session, and switch to `active`
+/// without animating.
+pub fn load(presets: Vec<LayoutPreset>, active: Option<&str>, cx: &mut App) {
+    update(cx, |state, _| {
+        state.presets = presets;
+        state.active = None;
+        state.begin_transition(false);
+        let preset = active
+            .and_then(|name| state.presets.iter().find(|p| *p.name == *name))
+            .cloned();
+        if let Some(preset) = preset {
+            for (panel, layout) in preset.panels {
+                state.set_layout(panel, layout);
+            }
+            state.active = Some(preset.name);
+        }
+    });
+}
+
+pub fn presets(cx: &App) -> Vec<LayoutPreset> {
+    settings(cx).map_or(Vec::new(), |state| state.presets.clone())
+}
+
+/// The name of the preset last switched to. Resizing a panel by hand doesn't change
+/// it.
+pub fn active_name(cx: &App) -> Option<SharedString> {
+    settings(cx).and_then(|state| state.active.clone())
+}
+
+/// Add `preset`, replacing any preset with the same name.
+pub fn add(preset: LayoutPreset, cx: &mut App) {
+    update(cx, |state, _| {
+        match state.presets.iter_mut().find(|p| p.name == preset.name) {
+            Some(existing) => *existing = preset,
+            None => state.presets.push(preset),
+        }
+    });
+}
+
+pub fn remove(name: &str, cx: &mut App) {
+    update(cx, |state, _| {
+        state.presets.retain(|preset| *preset.name != *name);
+        if state.active.as_deref() == Some(name) {
+            state.active = None;
+        }
+    });
+}
+
+/// Switch to the preset called `name`, animating the panels to it. Returns false if
+/// there is no such preset.
+pub fn apply(name: &str, cx: &mut App) -> bool {
+    let Some(preset) = settings(cx)
+        .and_then(|state| state.presets.iter().find(|preset| *preset.name == *name))
+        .cloned()
+    else {
+        return false;
+    };
+    update(cx, |state, animate| {
+        state.begin_transition(animate);
+        for (panel, layout) in preset.panels {
+            state.set_layout(panel, layout);
+        }
+        state.active = Some(preset.name);
+    });
+    true
+}
+
+/// Switch to the preset `offset` places after the active one, wrapping around.
+fn cycle(offset: isize, cx: &mut App) {
+    let Some(state) = settings(cx) else {
+        return;
+    };
+    let count = state.presets.len() as isize;
+    if count == 0 {
+        return;
+    }
+    let index = state
+        .active
+        .as_ref()
+        .and_then(|active| state.presets.iter().position(|p| &p.name == active))
+        .map_or(if offset > 0 { -1 } else { 0 }, |index| index as isize);
+    let name = state.presets[(index + offset).rem_euclid(count) as usize]
+        .name
+        .clone();
+    apply(&name, cx);
+}
+
+/// Capture the current layout of every panel as a preset called `name`.
+pub fn save_current(name: impl Into<SharedString>, cx: &mut App) {
+    let mut preset = LayoutPreset::new(name);
+    if let Some(state) = settings(cx) {
+        preset.panels = state
+            .current
+            .iter()
+            .map(|(panel, layout)| (panel.clone(), *layout))
+            .collect();
+    }
+    add(preset, cx);
+}
+
+/// Record that the user resized `panel`, e.g. from a resize handle's callback. Applies
+/// at once, without a transition.
+pub fn set_panel_size(panel: impl Into<SharedString>, size: impl Into<Pixels>, cx: &mut App) {
+    let panel = panel.into();
+    let size = size.into();
+    update(cx, |state, _| {
+        let visible = state
+            .current
+            .get(&panel)
+            .map(|layout| layout.visible)
+            .unwrap_or(true);
+        state.from.remove(&panel);
+        state.current.insert(
+            panel,
+            PanelLayout {
+                visible,
+                size: Some(size),
+            },
+        );
+    });
+}
+
+/// Show or hide `panel`, animating like a preset switch.
+pub fn set_panel_visible(panel: impl Into<SharedString>, visible: bool, cx: &mut App) {
+    let panel = panel.into();
+    update(cx, |state, animate| {
+        state.begin_transition(animate);
+        state.set_layout(
+            panel,
+            PanelLayout {
+                visible,
+                size: None,
+            },
+        );
+    });
+}
+
+/// The layout `panel` is switching to, for apps that size it through their own state,
+/// such as a [`ResizableState`](crate::components::resizable::ResizableState).
+pub fn panel_layout(panel: &str, cx: &App) -> Option<PanelLayout> {
+    settings(cx).and_then(|state| state.current.get(panel).copied())
+}
+
+/// How long panels take to move to a new preset. 300ms by default; everything snaps
+/// while [`reduced_motion`] is set.
+pub fn set_transition(duration: Duration, cx: &mut App) {
+    cx.default_global::<LayoutPresets>().transition = duration;
+}
+
+/// A "Layout: <name>" palette entry per preset.
+pub fn palette_commands(cx: &App) -> Vec<Command> {
+    settings(cx)
+        .map_or(&[][..], |state| state.presets.as_slice())
+        .iter()
+        .map(|preset| {
+            let name = preset.name.clone();
+            Command::new(format!("layout.{}", name), format!("Layout: {}", name))
+                .category("Layout")
+                .on_select(move |_, cx| {
+                    apply(&name, cx);
+                })
+        })
+        .collect()
+}
+
+/// Run `f` whenever the presets, the active preset or a panel's layout change.
+pub fn observe<T: 'static>(
+    cx: &mut Context<T>,
+    f: impl FnMut(&mut T, &mut Context<T>) + 'static,
+) -> Subscription {
+    cx.default_global::<LayoutPresets>();
+    cx.observe_global::<LayoutPresets>(f)
+}
+
+/// A panel sized by the active layout preset. See the module docs.
+#[derive(IntoElement)]
+pub struct PresetPanel {
+    id: SharedString,
+    axis: Axis,
+    child: AnyElement,
+}
+
+impl PresetPanel {
+    /// `child` laid out as panel `id`. `axis` is the direction of the row or column
+    /// the panel sits in, and so the direction it's sized in.
+    pub fn new(id: impl Into<SharedString>, axis: Axis, child: impl IntoElement) -> Self {
+        Self {
+            id: id.into(),
+            axis,
+            child: child.into_any_element(),
+        }
+    }
+}
+
+impl RenderOnce for PresetPanel {
+    fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let Some(state) = settings(cx) else {
+            return self.child;
+        };
+        let layout = state.current.get(&self.id).copied();
+        let transitioning = state.progress().is_some();
+        if transitioning {
+            window.request_animation_frame();
+        }
+        if layout.is_some_and(|layout| !layout.visible) && !transitioning {
+            return Empty.into_any_element();
+        }
+        let Some(drawn) = state.drawn_size(&self.id) else {
+            return self.child;
+        };
+        // The content keeps its full size while the panel grows or shrinks around it,
+        // so it's clipped rather than reflowed on every frame.
+        let full = layout
+            .and_then(|layout| layout.size)
+            .into_iter()
+            .chain(state.from.get(&self.id).copied().flatten())
+            .fold(drawn, |full, size| full.max(size));
+        let horizontal = self.axis == Axis::Horizontal;
+        div()
+            .flex()
+            .flex_none()
+            .overflow_hidden()
+            .when(horizontal, |this| this.h_full().w(drawn))
+            .when(!horizontal, |this| this.w_full().h(drawn))
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .flex_none()
+                    .when(horizontal, |this| this.h_full().w(full))
+                    .when(!horizontal, |this| this.w_full().h(full))
+                    .child(self.child),
+            )
+            .into_any_element()
+    }
+}
@@ -1,5 +1,6 @@
 //! Meteors effect - animated diagonal line streaks across a container.
 
+use crate::activity;
 use gpui::prelude::FluentBuilder as _;
 use gpui::*;
 use std::time::Duration;
@@ -14,6 +15,7 @@ impl MeteorState {
             cx.background_executor()
                 .timer(Duration::from_millis(100))
                 .await;
+            activity::wait_until_pollable(cx).await;
             let result = this.update(cx, |state, cx| {
                 state.version = state.version.wrapping_add(1);
                 cx.notify();
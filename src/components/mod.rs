@@ -1,6 +1,9 @@
 //! UI components module.
 
 pub mod alert;
+#[cfg(feature = "secrets")]
+pub mod api_key_input;
+pub mod async_view;
 pub mod audio_player;
 pub mod button;
 pub mod icon;
@@ -11,25 +14,37 @@ pub mod text;
 // Re-export commonly used types
 pub use icon::{IconSize, IconVariant};
 pub use icon_source::IconSource;
+pub mod auto_indent;
 pub mod checkbox;
 pub mod confirm_dialog;
 pub mod drag_drop;
 pub mod editor;
+pub mod editor_keymap;
+pub mod editor_settings;
+pub mod editorconfig;
 pub mod input;
+pub mod input_history;
+pub mod input_mask;
 pub mod input_state;
 pub mod keyboard_shortcuts;
 pub mod label;
+pub mod multi_select;
 pub mod progress;
 pub mod radio;
 pub mod rating;
 pub mod resizable;
 pub mod scrollable;
 pub mod scrollbar;
+pub mod scrubber;
 pub mod search_input;
 pub mod select;
 pub mod separator;
 pub mod skeleton;
 pub mod slider;
+pub mod snippet;
+pub mod soft_wrap;
+pub mod spell_check;
+pub mod text_encoding;
 pub mod text_field;
 pub mod textarea;
 pub mod toggle;
@@ -41,22 +56,30 @@ pub mod avatar_group;
 pub mod calendar;
 pub mod carousel;
 pub mod collapsible;
+pub mod color_literal;
 pub mod color_picker;
 pub mod combobox;
 pub mod countdown;
 pub mod date_picker;
+pub mod date_range_picker;
+pub mod diff_editor;
 pub mod dropdown;
 pub mod empty_state;
 pub mod file_upload;
 pub mod form;
 pub mod hotkey_input;
+pub mod image_hover;
 pub mod image_viewer;
 pub mod inline_edit;
+pub mod knob;
+pub mod layout_presets;
 pub mod mention_input;
+pub mod multi_buffer;
 pub mod navigation_menu;
 pub mod notification_center;
 pub mod number_input;
 pub mod otp_input;
+pub mod output_panel;
 pub mod pagination;
 pub mod range_slider;
 pub mod ripple;
@@ -65,9 +88,14 @@ pub mod spinner;
 pub mod split_pane;
 pub mod stepper;
 pub mod tag_input;
+pub mod text_diff;
 pub mod time_picker;
+pub mod time_range_slider;
 pub mod timeline;
+pub mod type_ahead;
 pub mod video_player;
+pub mod xy_pad;
+pub mod zen_mode;
 
 pub mod animated_collapsible;
 pub mod animated_counter;
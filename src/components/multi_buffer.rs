@@ -0,0 +1,358 @@
+//! Excerpts of several editor buffers in one scrolling view.
+//!
+//! `MultiBuffer` stacks line ranges from any number of [`EditorState`]s, each under a
+//! header naming its file, e.g. every search result or every diagnostic in a project.
+//! Every excerpt is an editor of its own and can be edited in place: its edits are
+//! written through to the source buffer, and edits made to the source show up in the
+//! excerpt.
+
+use crate::components::editor::{BufferEvent, Editor, EditorState, Position};
+use crate::theme::use_theme;
+use gpui::{prelude::FluentBuilder as _, *};
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExcerptId(usize);
+
+type OpenHandler = Rc<dyn Fn(&Entity<EditorState>, Position, &mut Window, &mut App)>;
+
+struct Excerpt {
+    id: ExcerptId,
+    source: Entity<EditorState>,
+    title: SharedString,
+    /// The source bytes shown, whole lines, moved along by the source's edits.
+    range: Range<usize>,
+    editor: Entity<EditorState>,
+    /// Edits this excerpt wrote to the source, still to come back as source events.
+    source_echoes: VecDeque<(Range<usize>, String)>,
+    /// Source edits copied into `editor`, still to come back as its events.
+    excerpt_echoes: VecDeque<(Range<usize>, String)>,
+    _subscriptions: [Subscription; 2],
+}
+
+pub struct MultiBuffer {
+    excerpts: Vec<Excerpt>,
+    next_id: usize,
+    scroll_handle: ScrollHandle,
+    on_open: Option<OpenHandler>,
+    style: StyleRefinement,
+}
+
+impl MultiBuffer {
+    pub fn new(_cx: &mut Context<Self>) -> Self {
+        Self {
+            excerpts: Vec::new(),
+            next_id: 0,
+            scroll_handle: ScrollHandle::new(),
+            on_open: None,
+            style: StyleRefinement::default(),
+        }
+    }
+
+    /// Called when an excerpt's header is clicked, with its source buffer and first
+    /// line, e.g. to open the file there.
+    pub fn on_open(
+        mut self,
+        handler: impl Fn(&Entity<EditorState>, Position, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.on_open = Some(Rc::new(handler));
+        self
+    }
+
+    /// Show buffer `lines` of `source` below the existing excerpts.
+    pub fn push_excerpt(
+        &mut self,
+        source: &Entity<EditorState>,
+        lines: Range<usize>,
+        cx: &mut Context<Self>,
+    ) -> ExcerptId {
+        let id = ExcerptId(self.next_id);
+        self.next_id += 1;
+
+        let state = source.read(cx);
+        let range = state.pos_to_byte_offset(Position::new(lines.start, 0))
+            ..state.pos_to_byte_offset(Position::new(lines.end, 0));
+        let text = state.text_for_range(range.clone());
+        let language = state.language();
+        let first_line = state.byte_offset_to_pos(range.start).line;
+        let title: SharedString = state
+            .file_path()
+            .and_then(|path| path.file_name())
+            .map_or("untitled".into(), |name| {
+                name.to_string_lossy().into_owned().into()
+            });
+
+        let editor = cx.new(|cx| {
+            let mut state = EditorState::new(cx);
+            state.set_language(language);
+            state.set_content(&text, cx);
+            state.first_line_number = first_line + 1;
+            state.scroll_past_end = false;
+            state
+        });
+        let _subscriptions = [
+            cx.subscribe(&editor, move |this, _, event, cx| {
+                this.excerpt_edited(id, event, cx)
+            }),
+            cx.subscribe(source, move |this, _, event, cx| {
+                this.source_edited(id, event, cx)
+            }),
+        ];
+        self.excerpts.push(Excerpt {
+            id,
+            source: source.clone(),
+            title,
+            range,
+            editor,
+            source_echoes: VecDeque::new(),
+            excerpt_echoes: VecDeque::new(),
+            _subscriptions,
+        });
+        cx.notify();
+        id
+    }
+
+    pub fn remove_excerpt(&mut self, id: ExcerptId, cx: &mut Context<Self>) {
+        self.excerpts.retain(|excerpt| excerpt.id != id);
+        cx.notify();
+    }
+
+    pub fn clear(&mut self, cx: &mut Context<Self>) {
+        self.excerpts.clear();
+        cx.notify();
+    }
+
+    pub fn excerpt_ids(&self) -> Vec<ExcerptId> {
+        self.excerpts.iter().map(|excerpt| excerpt.id).collect()
+    }
+
+    /// The source lines an excerpt covers now, after edits on either side.
+    pub fn excerpt_lines(&self, id: ExcerptId, cx: &App) -> Option<Range<usize>> {
+        let excerpt = self.excerpts.iter().find(|excerpt| excerpt.id == id)?;
+        let source = excerpt.source.read(cx);
+        let start = source.byte_offset_to_pos(excerpt.range.start).line;
+        let end = source.byte_offset_to_pos(excerpt.range.end).line;
+        Some(start..end)
+    }
+
+    /// The editor showing an excerpt, e.g. to move its cursor to a search match.
+    pub fn excerpt_editor(&self, id: ExcerptId) -> Option<&Entity<EditorState>> {
+        self.excerpts
+            .iter()
+            .find(|excerpt| excerpt.id == id)
+            .map(|excerpt| &excerpt.editor)
+    }
+
+    fn excerpt_mut(&mut self, id: ExcerptId) -> Option<&mut Excerpt> {
+        self.excerpts.iter_mut().find(|excerpt| excerpt.id == id)
+    }
+
+    /// Write an edit made in the excerpt through to its source.
+    fn excerpt_edited(&mut self, id: ExcerptId, event: &BufferEvent, cx: &mut Context<Self>) {
+        let BufferEvent::Edited {
+            range, new_text, ..
+        } = event
+        else {
+            return;
+        };
+        let Some(excerpt) = self.excerpt_mut(id) else {
+            return;
+        };
+        if pop_echo(&mut excerpt.excerpt_echoes, range, new_text) {
+            return;
+        }
+
+        let start = excerpt.range.start;
+        let source_range = start + range.start..start + range.end;
+        excerpt
+            .source_echoes
+            .push_back((source_range.clone(), new_text.clone()));
+        let edit = vec![(source_range, new_text.clone())];
+        let applied = excerpt
+            .source
+            .update(cx, |source, cx| source.apply_edits(edit, cx));
+        if !applied {
+            // A read-only source: put the excerpt back the way the source has it.
+            excerpt.source_echoes.pop_back();
+            self.reload(id, cx);
+        }
+    }
+
+    /// Follow an edit made to the source: shift the excerpt, or copy the edit in.
+    fn source_edited(&mut self, id: ExcerptId, event: &BufferEvent, cx: &mut Context<Self>) {
+        let BufferEvent::Edited {
+            range, new_text, ..
+        } = event
+        else {
+            return;
+        };
+        let Some(excerpt) = self.excerpt_mut(id) else {
+            return;
+        };
+        let resize = |end: usize| end - range.len() + new_text.len();
+        if pop_echo(&mut excerpt.source_echoes, range, new_text) {
+            excerpt.range.end = resize(excerpt.range.end);
+            return;
+        }
+
+        if range.end <= excerpt.range.start {
+            excerpt.range = resize(excerpt.range.start)..resize(excerpt.range.end);
+            let first_line = excerpt
+                .source
+                .read(cx)
+                .byte_offset_to_pos(excerpt.range.start)
+                .line;
+            excerpt.editor.update(cx, |editor, cx| {
+                if editor.first_line_number != first_line + 1 {
+                    editor.first_line_number = first_line + 1;
+                    cx.notify();
+                }
+            });
+        } else if range.start >= excerpt.range.end {
+            // After the excerpt, nothing to follow.
+        } else if range.start >= excerpt.range.start && range.end <= excerpt.range.end {
+            let start = excerpt.range.start;
+            let local = range.start - start..range.end - start;
+            excerpt
+                .excerpt_echoes
+                .push_back((local.clone(), new_text.clone()));
+            let edit = vec![(local, new_text.clone())];
+            excerpt
+                .editor
+                .update(cx, |editor, cx| editor.apply_edits(edit, cx));
+            excerpt.range.end = resize(excerpt.range.end);
+        } else {
+            // The edit straddles a boundary: grow the excerpt over it to whole lines.
+            let source = excerpt.source.read(cx);
+            let start = excerpt.range.start.min(range.start);
+            let end = resize(excerpt.range.end.max(range.end));
+            let start_line = source.byte_offset_to_pos(start).line;
+            let end_pos = source.byte_offset_to_pos(end);
+            let end_line = end_pos.line + usize::from(end_pos.col > 0);
+            excerpt.range = source.pos_to_byte_offset(Position::new(start_line, 0))
+                ..source.pos_to_byte_offset(Position::new(end_line, 0));
+            self.reload(id, cx);
+        }
+    }
+
+    /// Replace the excerpt's text with its source range.
+    fn reload(&mut self, id: ExcerptId, cx: &mut Context<Self>) {
+        let Some(excerpt) = self.excerpt_mut(id) else {
+            return;
+        };
+        let source = excerpt.source.read(cx);
+        let text = source.text_for_range(excerpt.range.clone());
+        let first_line = source.byte_offset_to_pos(excerpt.range.start).line;
+        let old_len = excerpt.editor.read(cx).content().len();
+        if old_len > 0 {
+            excerpt
+                .excerpt_echoes
+                .push_back((0..old_len, String::new()));
+        }
+        if !text.is_empty() {
+            excerpt.excerpt_echoes.push_back((0..0, text.clone()));
+        }
+        excerpt.editor.update(cx, |editor, cx| {
+            editor.first_line_number = first_line + 1;
+            editor.apply_edits(vec![(0..old_len, text)], cx);
+        });
+    }
+}
+
+/// Whether `range` and `text` are the edit expected next, which is then taken off.
+fn pop_echo(
+    echoes: &mut VecDeque<(Range<usize>, String)>,
+    range: &Range<usize>,
+    text: &str,
+) -> bool {
+    let matches = echoes
+        .front()
+        .is_some_and(|(echo_range, echo_text)| echo_range == range && echo_text == text);
+    if matches {
+        echoes.pop_front();
+    }
+    matches
+}
+
+impl Styled for MultiBuffer {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+impl Render for MultiBuffer {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = use_theme();
+        let user_style = self.style.clone();
+
+        let sections = self
+            .excerpts
+            .iter()
+            .map(|excerpt| {
+                let state = excerpt.editor.read(cx);
+                let lines = state.display_line_count();
+                // The editor pads its text by 12px above and below.
+                let height = state.line_height * lines as f32 + px(24.0);
+                let first_line = state.first_line_number;
+                let line_label = format!(
+                    "lines {}–{}",
+                    first_line,
+                    first_line + lines.saturating_sub(1)
+                );
+                let source = excerpt.source.clone();
+
+                let header = div()
+                    .id(("excerpt-header", excerpt.id.0))
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .px(px(12.0))
+                    .py(px(4.0))
+                    .bg(theme.tokens.muted)
+                    .border_b_1()
+                    .border_color(theme.tokens.border)
+                    .text_size(px(12.0))
+                    .child(
+                        div()
+                            .text_color(theme.tokens.foreground)
+                            .child(excerpt.title.clone()),
+                    )
+                    .child(
+                        div()
+                            .text_color(theme.tokens.muted_foreground)
+                            .child(line_label),
+                    )
+                    .when_some(self.on_open.clone(), |this, on_open| {
+                        this.cursor_pointer().on_click(move |_, window, cx| {
+                            on_open(&source, Position::new(first_line - 1, 0), window, cx)
+                        })
+                    });
+
+                div()
+                    .flex()
+                    .flex_col()
+                    .border_b_1()
+                    .border_color(theme.tokens.border)
+                    .child(header)
+                    .child(Editor::new(&excerpt.editor).show_border(false).h(height))
+            })
+            .collect::<Vec<_>>();
+
+        div()
+            .id("multi-buffer")
+            .flex()
+            .flex_col()
+            .size_full()
+            .overflow_y_scroll()
+            .track_scroll(&self.scroll_handle)
+            .bg(theme.tokens.background)
+            .map(|this| {
+                let mut div = this;
+                div.style().refine(&user_style);
+                div
+            })
+            .children(sections)
+    }
+}
@@ -0,0 +1,719 @@
+//! MultiSelect component - Dropdown for picking several options, shown as chips.
+//!
+//! The field shows each selected option as a removable chip, collapsing the ones past
+//! [`MultiSelect::max_visible_chips`] into a "+3 more" chip. The dropdown filters its
+//! options as the user types, toggles an option on click or enter, and has "Select
+//! all" and "Clear" actions. With [`MultiSelect::max_selections`] set, the remaining
+//! options are disabled once the limit is reached.
+
+use crate::components::icon::Icon;
+use crate::components::scrollable::scrollable_vertical;
+use crate::components::select::SelectOption;
+use crate::overlays::dismiss::dismiss_layer;
+use crate::theme::use_theme;
+use gpui::{prelude::*, *};
+use std::rc::Rc;
+
+actions!(
+    multi_select,
+    [
+        MultiSelectUp,
+        MultiSelectDown,
+        MultiSelectToggle,
+        MultiSelectCancel
+    ]
+);
+
+const CONTEXT: &str = "MultiSelect";
+const DROPDOWN_MARGIN: Pixels = px(4.0);
+
+#[derive(Clone, Debug)]
+pub enum MultiSelectEvent {
+    Change,
+}
+
+pub struct MultiSelect<T: Clone + 'static> {
+    focus_handle: FocusHandle,
+    options: Vec<SelectOption<T>>,
+    /// Indices into `options`, in the order they were picked.
+    selected: Vec<usize>,
+    highlighted_index: Option<usize>,
+    placeholder: Option<SharedString>,
+    open: bool,
+    disabled: bool,
+    max_selections: Option<usize>,
+    max_visible_chips: Option<usize>,
+    search_query: String,
+    on_change: Option<Rc<dyn Fn(&[T], &mut Window, &mut App) + 'static>>,
+    bounds: Bounds<Pixels>,
+    style: StyleRefinement,
+}
+
+impl<T: Clone + 'static> MultiSelect<T> {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        Self {
+            focus_handle: cx.focus_handle(),
+            options: Vec::new(),
+            selected: Vec::new(),
+            highlighted_index: None,
+            placeholder: None,
+            open: false,
+            disabled: false,
+            max_selections: None,
+            max_visible_chips: Some(3),
+            search_query: String::new(),
+            on_change: None,
+            bounds: Bounds::default(),
+            style: StyleRefinement::default(),
+        }
+    }
+
+    pub fn options(mut self, options: Vec<SelectOption<T>>) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// The initially selected options, by index.
+    pub fn selected_indices(mut self, indices: impl IntoIterator<Item = usize>) -> Self {
+        self.selected = Vec::new();
+        for index in indices {
+            if index < self.options.len() && !self.selected.contains(&index) {
+                self.selected.push(index);
+            }
+        }
+        self
+    }
+
+    pub fn placeholder(mut self, placeholder: impl Into<SharedString>) -> Self {
+        self.placeholder = Some(placeholder.into());
+        self
+    }
+
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+
+    /// The most options that can be selected at once.
+    pub fn max_selections(mut self, max: usize) -> Self {
+        self.max_selections = Some(max);
+        self
+    }
+
+    /// How many chips the field shows before collapsing the rest into "+N more".
+    /// Defaults to 3; `None` shows every chip, wrapping onto more lines.
+    pub fn max_visible_chips(mut self, max: Option<usize>) -> Self {
+        self.max_visible_chips = max;
+        self
+    }
+
+    /// Called with the selected values, in the order they were picked, after every
+    /// change.
+    pub fn on_change(mut self, f: impl Fn(&[T], &mut Window, &mut App) + 'static) -> Self {
+        self.on_change = Some(Rc::new(f));
+        self
+    }
+
+    /// Replace the options, dropping selected indices that are out of range.
+    pub fn set_options(&mut self, options: Vec<SelectOption<T>>, cx: &mut Context<Self>) {
+        self.options = options;
+        let len = self.options.len();
+        self.selected.retain(|&index| index < len);
+        self.highlighted_index = None;
+        cx.notify();
+    }
+
+    /// Indices of the selected options, in the order they were picked.
+    pub fn selected(&self) -> &[usize] {
+        &self.selected
+    }
+
+    pub fn selected_values(&self) -> Vec<T> {
+        self.selected
+            .iter()
+            .filter_map(|&index| self.options.get(index))
+            .map(|option| option.value.clone())
+            .collect()
+    }
+
+    pub fn is_selected(&self, index: usize) -> bool {
+        self.selected.contains(&index)
+    }
+
+    fn at_limit(&self) -> bool {
+        self.max_selections
+            .is_some_and(|max| self.selected.len() >= max)
+    }
+
+    fn filtered_options(&self) -> Vec<(usize, &SelectOption<T>)> {
+        let query = self.search_query.to_lowercase();
+        self.options
+            .iter()
+            .enumerate()
+            .filter(|(_, option)| query.is_empty() || option.label.to_lowercase().contains(&query))
+            .collect()
+    }
+
+    pub fn toggle_option(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        if index >= self.options.len() {
+            return;
+        }
+        if let Some(pos) = self.selected.iter().position(|&i| i == index) {
+            self.selected.remove(pos);
+        } else if self.at_limit() {
+            return;
+        } else {
+            self.selected.push(index);
+        }
+        self.changed(window, cx);
+    }
+
+    pub fn remove_option(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(pos) = self.selected.iter().position(|&i| i == index) {
+            self.selected.remove(pos);
+            self.changed(window, cx);
+        }
+    }
+
+    /// Select every option matching the search, up to the selection limit.
+    pub fn select_all(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let matching: Vec<usize> = self
+            .filtered_options()
+            .into_iter()
+            .map(|(index, _)| index)
+            .collect();
+        let before = self.selected.len();
+        for index in matching {
+            if self.at_limit() {
+                break;
+            }
+            if !self.selected.contains(&index) {
+                self.selected.push(index);
+            }
+        }
+        if self.selected.len() != before {
+            self.changed(window, cx);
+        }
+    }
+
+    pub fn clear_all(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.selected.is_empty() {
+            self.selected.clear();
+            self.changed(window, cx);
+        }
+    }
+
+    fn changed(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(MultiSelectEvent::Change);
+        cx.notify();
+        if let Some(on_change) = self.on_change.clone() {
+            let values = self.selected_values();
+            on_change(&values, window, cx);
+        }
+    }
+
+    fn toggle_dropdown(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.disabled {
+            return;
+        }
+        if self.open {
+            self.close_dropdown(cx);
+        } else {
+            self.open = true;
+            window.focus(&self.focus_handle);
+            self.highlighted_index = self.filtered_options().first().map(|(index, _)| *index);
+            cx.notify();
+        }
+    }
+
+    fn close_dropdown(&mut self, cx: &mut Context<Self>) {
+        self.open = false;
+        self.highlighted_index = None;
+        self.search_query.clear();
+        cx.notify();
+    }
+
+    fn move_highlight(&mut self, delta: isize, cx: &mut Context<Self>) {
+        let filtered = self.filtered_options();
+        if filtered.is_empty() {
+            return;
+        }
+        let len = filtered.len() as isize;
+        let current = self
+            .highlighted_index
+            .and_then(|idx| filtered.iter().position(|(index, _)| *index == idx));
+        let next = match current {
+            Some(pos) => (pos as isize + delta).rem_euclid(len),
+            None if delta < 0 => len - 1,
+            None => 0,
+        };
+        self.highlighted_index = Some(filtered[next as usize].0);
+        cx.notify();
+    }
+
+    fn select_up(&mut self, _: &MultiSelectUp, _: &mut Window, cx: &mut Context<Self>) {
+        if self.open {
+            self.move_highlight(-1, cx);
+        }
+    }
+
+    fn select_down(&mut self, _: &MultiSelectDown, _: &mut Window, cx: &mut Context<Self>) {
+        if self.open {
+            self.move_highlight(1, cx);
+        }
+    }
+
+    fn toggle_highlighted(
+        &mut self,
+        _: &MultiSelectToggle,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match (self.open, self.highlighted_index) {
+            (true, Some(index)) => self.toggle_option(index, window, cx),
+            (true, None) => {}
+            (false, _) => self.toggle_dropdown(window, cx),
+        }
+    }
+
+    fn cancel(&mut self, _: &MultiSelectCancel, _: &mut Window, cx: &mut Context<Self>) {
+        if self.open {
+            self.close_dropdown(cx);
+        }
+    }
+
+    fn handle_key(&mut self, event: &KeyDownEvent, window: &mut Window, cx: &mut Context<Self>) {
+        let keystroke = &event.keystroke;
+        if keystroke.modifiers.control || keystroke.modifiers.platform {
+            return;
+        }
+        if keystroke.key == "backspace" {
+            if self.search_query.pop().is_none() {
+                if let Some(&last) = self.selected.last() {
+                    self.remove_option(last, window, cx);
+                }
+            }
+        } else if let Some(ch) = keystroke
+            .key_char
+            .as_ref()
+            .filter(|ch| ch.chars().count() == 1 && !ch.chars().any(char::is_control))
+        {
+            self.search_query.push_str(ch);
+            self.open = true;
+        } else {
+            return;
+        }
+        self.highlighted_index = self.filtered_options().first().map(|(index, _)| *index);
+        cx.stop_propagation();
+        cx.notify();
+    }
+
+    fn render_chip(&self, index: usize, cx: &mut Context<Self>) -> AnyElement {
+        let theme = use_theme();
+        let label = self.options[index].label.clone();
+        div()
+            .id(("multi-select-chip", index))
+            .flex()
+            .flex_shrink_0()
+            .items_center()
+            .gap(px(4.0))
+            .pl(px(8.0))
+            .pr(px(4.0))
+            .py(px(2.0))
+            .bg(theme.tokens.primary.opacity(0.1))
+            .text_color(theme.tokens.primary)
+            .rounded(px(4.0))
+            .text_size(px(13.0))
+            .child(label)
+            .when(!self.disabled, |chip| {
+                chip.child(
+                    div()
+                        .id(("multi-select-chip-remove", index))
+                        .flex()
+                        .items_center()
+                        .justify_center()
+                        .size(px(16.0))
+                        .rounded(px(2.0))
+                        .cursor_pointer()
+                        .hover(|style| style.bg(theme.tokens.primary.opacity(0.2)))
+                        .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+                        .on_click(cx.listener(move |this, _, window, cx| {
+                            this.remove_option(index, window, cx);
+                        }))
+                        .child(Icon::new("x").size(px(12.0)).color(theme.tokens.primary)),
+                )
+            })
+            .into_any_element()
+    }
+
+    fn render_option(
+        &self,
+        index: usize,
+        option: &SelectOption<T>,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let theme = use_theme();
+        let selected = self.is_selected(index);
+        let disabled = !selected && self.at_limit();
+        let highlighted = self.highlighted_index == Some(index);
+
+        div()
+            .id(("multi-select-option", index))
+            .flex()
+            .items_center()
+            .gap(px(8.0))
+            .px(px(12.0))
+            .py(px(8.0))
+            .text_size(px(14.0))
+            .text_color(theme.tokens.popover_foreground)
+            .when(highlighted, |row| row.bg(theme.tokens.accent))
+            .when(disabled, |row| row.opacity(0.5))
+            .when(!disabled, |row| {
+                row.cursor(CursorStyle::PointingHand)
+                    .hover(|style| style.bg(theme.tokens.accent))
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(move |this, _, window, cx| {
+                            this.highlighted_index = Some(index);
+                            this.toggle_option(index, window, cx);
+                        }),
+                    )
+            })
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .justify_center()
+                    .size(px(16.0))
+                    .rounded(px(4.0))
+                    .border_1()
+                    .border_color(if selected {
+                        theme.tokens.primary
+                    } else {
+                        theme.tokens.input
+                    })
+                    .when(selected, |check| {
+                        check.bg(theme.tokens.primary).child(
+                            Icon::new("check")
+                                .size(px(12.0))
+                                .color(theme.tokens.primary_foreground),
+                        )
+                    }),
+            )
+            .when_some(option.icon.as_ref(), |row, icon| {
+                row.child(
+                    Icon::new(icon.clone())
+                        .size(px(14.0))
+                        .color(theme.tokens.muted_foreground),
+                )
+            })
+            .child(option.label.clone())
+            .into_any_element()
+    }
+
+    fn render_dropdown(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = use_theme();
+        let filtered = self.filtered_options();
+        let entity = cx.entity();
+
+        let action = |id: &'static str, label: &'static str, enabled: bool| {
+            div()
+                .id(id)
+                .px(px(8.0))
+                .py(px(2.0))
+                .rounded(theme.tokens.radius_sm)
+                .text_size(px(12.0))
+                .text_color(if enabled {
+                    theme.tokens.primary
+                } else {
+                    theme.tokens.muted_foreground
+                })
+                .when(enabled, |this| {
+                    this.cursor_pointer()
+                        .hover(|style| style.bg(theme.tokens.accent))
+                })
+                .child(label)
+        };
+        let can_select_more = !self.at_limit()
+            && filtered
+                .iter()
+                .any(|(index, _)| !self.selected.contains(index));
+        let can_clear = !self.selected.is_empty();
+        let count = match self.max_selections {
+            Some(max) => format!("{} of {} selected", self.selected.len(), max),
+            None => format!("{} selected", self.selected.len()),
+        };
+
+        let mut current_group: Option<SharedString> = None;
+        let mut rows = Vec::new();
+        for (index, option) in &filtered {
+            if option.group != current_group {
+                current_group = option.group.clone();
+                if let Some(group) = &option.group {
+                    rows.push(
+                        div()
+                            .px(px(12.0))
+                            .pt(px(12.0))
+                            .pb(px(4.0))
+                            .text_size(px(11.0))
+                            .font_weight(FontWeight::SEMIBOLD)
+                            .text_color(theme.tokens.muted_foreground)
+                            .child(group.clone())
+                            .into_any_element(),
+                    );
+                }
+            }
+            rows.push(self.render_option(*index, option, cx));
+        }
+
+        div()
+            .occlude()
+            .mt(DROPDOWN_MARGIN)
+            .bg(theme.tokens.popover)
+            .border_1()
+            .border_color(theme.tokens.border)
+            .rounded(theme.tokens.radius_md)
+            .shadow_lg()
+            .overflow_hidden()
+            .font_family(theme.tokens.font_family.clone())
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .justify_between()
+                    .px(px(12.0))
+                    .py(px(6.0))
+                    .border_b_1()
+                    .border_color(theme.tokens.border)
+                    .text_size(px(13.0))
+                    .child(
+                        div()
+                            .text_color(if self.search_query.is_empty() {
+                                theme.tokens.muted_foreground
+                            } else {
+                                theme.tokens.foreground
+                            })
+                            .child(if self.search_query.is_empty() {
+                                SharedString::from("Type to search...")
+                            } else {
+                                SharedString::from(self.search_query.clone())
+                            }),
+                    )
+                    .child(
+                        div()
+                            .text_size(px(12.0))
+                            .text_color(theme.tokens.muted_foreground)
+                            .child(count),
+                    ),
+            )
+            .child(
+                div()
+                    .flex()
+                    .gap(px(4.0))
+                    .px(px(4.0))
+                    .py(px(4.0))
+                    .border_b_1()
+                    .border_color(theme.tokens.border)
+                    .child(
+                        action("multi-select-all", "Select all", can_select_more).when(
+                            can_select_more,
+                            |this| {
+                                let entity = entity.clone();
+                                this.on_click(move |_, window, cx| {
+                                    entity.update(cx, |this, cx| this.select_all(window, cx));
+                                })
+                            },
+                        ),
+                    )
+                    .child(action("multi-select-clear", "Clear", can_clear).when(
+                        can_clear,
+                        |this| {
+                            let entity = entity.clone();
+                            this.on_click(move |_, window, cx| {
+                                entity.update(cx, |this, cx| this.clear_all(window, cx));
+                            })
+                        },
+                    )),
+            )
+            .child(
+                div().max_h(px(280.0)).child(scrollable_vertical(
+                    div()
+                        .py(px(4.0))
+                        .when(filtered.is_empty(), |this| {
+                            this.child(
+                                div()
+                                    .px(px(12.0))
+                                    .py(px(16.0))
+                                    .flex()
+                                    .justify_center()
+                                    .text_size(px(13.0))
+                                    .text_color(theme.tokens.muted_foreground)
+                                    .child("No results found"),
+                            )
+                        })
+                        .children(rows),
+                )),
+            )
+    }
+}
+
+impl<T: Clone + 'static> Styled for MultiSelect<T> {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+impl<T: Clone + 'static> Render for MultiSelect<T> {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = use_theme();
+        let user_style = self.style.clone();
+        let open = self.open;
+        let bounds = self.bounds;
+
+        let visible = self
+            .max_visible_chips
+            .unwrap_or(usize::MAX)
+            .min(self.selected.len());
+        let hidden = self.selected.len() - visible;
+        let chips: Vec<AnyElement> = self.selected[..visible]
+            .iter()
+            .map(|&index| self.render_chip(index, cx))
+            .collect();
+
+        let field = div()
+            .id("multi-select-trigger")
+            .relative()
+            .flex()
+            .items_center()
+            .gap(px(8.0))
+            .min_h(px(40.0))
+            .px(px(8.0))
+            .py(px(4.0))
+            .bg(theme.tokens.background)
+            .border_1()
+            .border_color(if open {
+                theme.tokens.ring
+            } else {
+                theme.tokens.input
+            })
+            .rounded(theme.tokens.radius_md)
+            .text_size(px(14.0))
+            .font_family(theme.tokens.font_family.clone())
+            .when(self.disabled, |field| field.opacity(0.5))
+            .when(!self.disabled, |field| {
+                field
+                    .cursor(CursorStyle::PointingHand)
+                    .hover(|style| style.border_color(theme.tokens.ring))
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(|this, _, window, cx| this.toggle_dropdown(window, cx)),
+                    )
+            })
+            .child(
+                div()
+                    .flex()
+                    .flex_1()
+                    .flex_wrap()
+                    .items_center()
+                    .gap(px(4.0))
+                    .when(self.selected.is_empty(), |this| {
+                        this.px(px(4.0))
+                            .text_color(theme.tokens.muted_foreground)
+                            .child(
+                                self.placeholder
+                                    .clone()
+                                    .unwrap_or_else(|| "Select...".into()),
+                            )
+                    })
+                    .children(chips)
+                    .when(hidden > 0, |this| {
+                        this.child(
+                            div()
+                                .px(px(8.0))
+                                .py(px(2.0))
+                                .rounded(px(4.0))
+                                .bg(theme.tokens.muted)
+                                .text_size(px(13.0))
+                                .text_color(theme.tokens.muted_foreground)
+                                .child(format!("+{} more", hidden)),
+                        )
+                    }),
+            )
+            .child(
+                Icon::new(if open { "chevron-up" } else { "chevron-down" })
+                    .size(px(14.0))
+                    .color(theme.tokens.muted_foreground),
+            )
+            .child({
+                let entity = cx.entity();
+                canvas(
+                    move |bounds, _, cx| entity.update(cx, |this, _| this.bounds = bounds),
+                    |_, _, _, _| {},
+                )
+                .absolute()
+                .size_full()
+            });
+
+        div()
+            .relative()
+            .w_full()
+            .key_context(CONTEXT)
+            .track_focus(&self.focus_handle)
+            .when(!self.disabled, |this| {
+                this.on_action(cx.listener(Self::select_up))
+                    .on_action(cx.listener(Self::select_down))
+                    .on_action(cx.listener(Self::toggle_highlighted))
+                    .on_action(cx.listener(Self::cancel))
+                    .on_key_down(cx.listener(Self::handle_key))
+            })
+            .child(field)
+            .when(open, |this| {
+                let entity = cx.entity();
+                this.child(
+                    deferred(
+                        anchored()
+                            .snap_to_window_with_margin(Edges::all(DROPDOWN_MARGIN))
+                            .child(
+                                div()
+                                    .occlude()
+                                    .w(bounds.size.width)
+                                    .child(
+                                        dismiss_layer("multi-select-dismiss", move |_, cx| {
+                                            entity.update(cx, |this, cx| {
+                                                if this.open {
+                                                    this.close_dropdown(cx);
+                                                }
+                                            })
+                                        })
+                                        .inside(bounds),
+                                    )
+                                    .child(self.render_dropdown(cx)),
+                            ),
+                    )
+                    .with_priority(1),
+                )
+            })
+            .map(|this| {
+                let mut div = this;
+                div.style().refine(&user_style);
+                div
+            })
+    }
+}
+
+pub fn init_multi_select(cx: &mut App) {
+    cx.bind_keys([
+        KeyBinding::new("up", MultiSelectUp, Some(CONTEXT)),
+        KeyBinding::new("down", MultiSelectDown, Some(CONTEXT)),
+        KeyBinding::new("enter", MultiSelectToggle, Some(CONTEXT)),
+        KeyBinding::new("escape", MultiSelectCancel, Some(CONTEXT)),
+    ]);
+}
+
+impl<T: Clone + 'static> Focusable for MultiSelect<T> {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl<T: Clone + 'static> EventEmitter<MultiSelectEvent> for MultiSelect<T> {}
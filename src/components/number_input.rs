@@ -1,3 +1,10 @@
+//! Numeric input with stepper buttons.
+//!
+//! When focused, up and down step the value, with shift or page up/down taking large
+//! steps, and home and end jump to the minimum and maximum. Every change is clamped to
+//! the range, and in [`NumberInputMode::Integer`] rounded to a whole number.
+
+use crate::components::scrubber::{scrub_tracker, start_scrub, ScrubDrag, ScrubRange};
 use crate::theme::use_theme;
 use gpui::{prelude::FluentBuilder as _, *};
 use std::rc::Rc;
@@ -9,12 +16,23 @@ pub enum NumberInputSize {
     Lg,
 }
 
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum NumberInputMode {
+    /// Fractional values, shown with the state's precision.
+    #[default]
+    Float,
+    /// Whole numbers only; values and steps are rounded.
+    Integer,
+}
+
 pub struct NumberInputState {
     value: f64,
     min: Option<f64>,
     max: Option<f64>,
     step: f64,
+    large_step: Option<f64>,
     precision: usize,
+    mode: NumberInputMode,
     focus_handle: FocusHandle,
 }
 
@@ -25,7 +43,9 @@ impl NumberInputState {
             min: None,
             max: None,
             step: 1.0,
+            large_step: None,
             precision: 0,
+            mode: NumberInputMode::Float,
             focus_handle: cx.focus_handle(),
         }
     }
@@ -36,7 +56,9 @@ impl NumberInputState {
             min: None,
             max: None,
             step: 1.0,
+            large_step: None,
             precision: 0,
+            mode: NumberInputMode::Float,
             focus_handle: cx.focus_handle(),
         }
     }
@@ -66,16 +88,70 @@ impl NumberInputState {
         self.step = step.max(0.001);
     }
 
+    /// Step for shift+arrow and page up/down. Defaults to ten steps.
+    pub fn set_large_step(&mut self, step: f64) {
+        self.large_step = Some(step.max(self.step));
+    }
+
     pub fn set_precision(&mut self, precision: usize) {
         self.precision = precision;
     }
 
+    pub fn mode(&self) -> NumberInputMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: NumberInputMode, cx: &mut Context<Self>) {
+        self.mode = mode;
+        self.value = self.clamp_value(self.value);
+        cx.notify();
+    }
+
     pub fn increment(&mut self, cx: &mut Context<Self>) {
-        self.set_value(self.value + self.step, cx);
+        self.nudge(1.0, false, cx);
     }
 
     pub fn decrement(&mut self, cx: &mut Context<Self>) {
-        self.set_value(self.value - self.step, cx);
+        self.nudge(-1.0, false, cx);
+    }
+
+    fn effective_step(&self, large: bool) -> f64 {
+        let step = if large {
+            self.large_step.unwrap_or(self.step * 10.0)
+        } else {
+            self.step
+        };
+        match self.mode {
+            NumberInputMode::Integer => step.round().max(1.0),
+            NumberInputMode::Float => step,
+        }
+    }
+
+    fn nudge(&mut self, steps: f64, large: bool, cx: &mut Context<Self>) {
+        let value = self.value + steps * self.effective_step(large);
+        // Drop the float error that repeated steps like 0.1 pile up.
+        let scale = 10f64.powi(self.precision.max(step_decimals(self.step)) as i32);
+        self.set_value((value * scale).round() / scale, cx);
+    }
+
+    /// Handle a key pressed while focused; returns whether it was used.
+    fn handle_key(&mut self, key: &str, shift: bool, cx: &mut Context<Self>) -> bool {
+        match key {
+            "up" => self.nudge(1.0, shift, cx),
+            "down" => self.nudge(-1.0, shift, cx),
+            "pageup" => self.nudge(1.0, true, cx),
+            "pagedown" => self.nudge(-1.0, true, cx),
+            "home" => match self.min {
+                Some(min) => self.set_value(min, cx),
+                None => return false,
+            },
+            "end" => match self.max {
+                Some(max) => self.set_value(max, cx),
+                None => return false,
+            },
+            _ => return false,
+        }
+        true
     }
 
     pub fn can_increment(&self) -> bool {
@@ -87,7 +163,10 @@ impl NumberInputState {
     }
 
     fn clamp_value(&self, value: f64) -> f64 {
-        let mut v = value;
+        let mut v = match self.mode {
+            NumberInputMode::Integer => value.round(),
+            NumberInputMode::Float => value,
+        };
         if let Some(min) = self.min {
             v = v.max(min);
         }
@@ -98,14 +177,21 @@ impl NumberInputState {
     }
 
     fn format_value(&self) -> String {
-        if self.precision == 0 {
-            format!("{}", self.value as i64)
-        } else {
-            format!("{:.prec$}", self.value, prec = self.precision)
-        }
+        let precision = match self.mode {
+            NumberInputMode::Integer => 0,
+            NumberInputMode::Float => self.precision,
+        };
+        format!("{:.prec$}", self.value, prec = precision)
     }
 }
 
+/// Decimal places in `step`, e.g. 2 for 0.25.
+fn step_decimals(step: f64) -> usize {
+    let text = format!("{}", step);
+    text.split_once('.')
+        .map_or(0, |(_, decimals)| decimals.len())
+}
+
 impl Focusable for NumberInputState {
     fn focus_handle(&self, _: &App) -> FocusHandle {
         self.focus_handle.clone()
@@ -125,7 +211,9 @@ pub struct NumberInput {
     placeholder: Option<SharedString>,
     disabled: bool,
     show_buttons: bool,
+    scrubbable: bool,
     on_change: Option<Rc<dyn Fn(f64, &mut Window, &mut App)>>,
+    format: Option<Rc<dyn Fn(f64) -> String>>,
     style: StyleRefinement,
 }
 
@@ -137,7 +225,9 @@ impl NumberInput {
             placeholder: None,
             disabled: false,
             show_buttons: true,
+            scrubbable: true,
             on_change: None,
+            format: None,
             style: StyleRefinement::default(),
         }
     }
@@ -162,10 +252,23 @@ impl NumberInput {
         self
     }
 
+    /// Whether dragging horizontally on the value changes it (shift = coarse, alt = fine).
+    pub fn scrubbable(mut self, scrubbable: bool) -> Self {
+        self.scrubbable = scrubbable;
+        self
+    }
+
     pub fn on_change(mut self, handler: impl Fn(f64, &mut Window, &mut App) + 'static) -> Self {
         self.on_change = Some(Rc::new(handler));
         self
     }
+
+    /// Display the value as `format` renders it, e.g. with a unit or thousands
+    /// separators, instead of with the state's precision.
+    pub fn format(mut self, format: impl Fn(f64) -> String + 'static) -> Self {
+        self.format = Some(Rc::new(format));
+        self
+    }
 }
 
 impl Styled for NumberInput {
@@ -179,12 +282,33 @@ impl RenderOnce for NumberInput {
         let theme = use_theme();
         let user_style = self.style;
         let state_data = self.state.read(cx);
-        let value_text = state_data.format_value();
+        let value_text = match &self.format {
+            Some(format) => format(state_data.value),
+            None => state_data.format_value(),
+        };
         let can_increment = state_data.can_increment();
         let can_decrement = state_data.can_decrement();
         let focus_handle = state_data.focus_handle(cx);
         let is_focused = focus_handle.is_focused(window);
+        let value = state_data.value;
+        let scrub_range = ScrubRange {
+            step: state_data.step,
+            pixels_per_step: 4.0,
+            min: state_data.min,
+            max: state_data.max,
+            precision: match state_data.mode {
+                NumberInputMode::Integer => 0,
+                NumberInputMode::Float => state_data.precision,
+            },
+        };
         let state = self.state.clone();
+        let scrubbable = self.scrubbable && !self.disabled;
+        let scrub = window.use_keyed_state(
+            ("number-input-scrub", self.state.entity_id()),
+            cx,
+            |_, _| None::<ScrubDrag>,
+        );
+        let scrubbing = scrub.read(cx).is_some_and(|drag| drag.is_active());
 
         let (height, padding_x, text_size, button_size) = match self.size {
             NumberInputSize::Sm => (px(32.0), px(8.0), px(13.0), px(24.0)),
@@ -201,6 +325,7 @@ impl RenderOnce for NumberInput {
             })
             .child(
                 div()
+                    .id(("number-input", self.state.entity_id()))
                     .flex()
                     .items_center()
                     .h(height)
@@ -213,6 +338,32 @@ impl RenderOnce for NumberInput {
                     })
                     .rounded(theme.tokens.radius_md)
                     .when(self.disabled, |d| d.opacity(0.5))
+                    .when(!self.disabled, |d| {
+                        let state = state.clone();
+                        let on_change = self.on_change.clone();
+                        let focus_on_click = focus_handle.clone();
+                        d.track_focus(&focus_handle.tab_index(0).tab_stop(true))
+                            .on_mouse_down(MouseButton::Left, move |_, window, _| {
+                                window.focus(&focus_on_click)
+                            })
+                            .on_key_down(move |event, window, cx| {
+                                let keystroke = &event.keystroke;
+                                let before = state.read(cx).value;
+                                let handled = state.update(cx, |s, cx| {
+                                    s.handle_key(&keystroke.key, keystroke.modifiers.shift, cx)
+                                });
+                                if !handled {
+                                    return;
+                                }
+                                cx.stop_propagation();
+                                let value = state.read(cx).value;
+                                if value != before {
+                                    if let Some(ref handler) = on_change {
+                                        handler(value, window, cx);
+                                    }
+                                }
+                            })
+                    })
                     .when(self.show_buttons, {
                         let state = state.clone();
                         let on_change = self.on_change.clone();
@@ -262,6 +413,8 @@ impl RenderOnce for NumberInput {
                     })
                     .child(
                         div()
+                            .id("value")
+                            .relative()
                             .flex_1()
                             .flex()
                             .items_center()
@@ -272,6 +425,36 @@ impl RenderOnce for NumberInput {
                             .text_size(text_size)
                             .text_color(theme.tokens.foreground)
                             .font_family(theme.tokens.font_family.clone())
+                            .when(scrubbable, |d| {
+                                let on_scrub: Rc<dyn Fn(f64, &mut Window, &mut App)> = {
+                                    let state = state.clone();
+                                    let on_change = self.on_change.clone();
+                                    Rc::new(move |value, window, cx| {
+                                        state.update(cx, |s, cx| {
+                                            s.set_value(value, cx);
+                                            if let Some(ref handler) = on_change {
+                                                handler(s.value, window, cx);
+                                            }
+                                        });
+                                    })
+                                };
+                                d.cursor(CursorStyle::ResizeLeftRight)
+                                    .when(scrubbing, |d| d.bg(theme.tokens.accent.opacity(0.5)))
+                                    .on_mouse_down(MouseButton::Left, {
+                                        let scrub = scrub.clone();
+                                        move |event, window, cx| {
+                                            start_scrub(
+                                                &scrub,
+                                                scrub_range,
+                                                event.position.x,
+                                                value,
+                                                window,
+                                                cx,
+                                            );
+                                        }
+                                    })
+                                    .child(scrub_tracker(scrub.clone(), scrub_range, on_scrub))
+                            })
                             .child(value_text),
                     )
                     .when(self.show_buttons, {
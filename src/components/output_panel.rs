@@ -0,0 +1,459 @@
+//! Output channels with a multiplexed, read-only output panel.
+//!
+//! `OutputChannels` is a registry of named streams (like VS Code's output channels).
+//! Any subsystem can obtain an `OutputChannel` handle and append text to it — from any
+//! thread. Appends are buffered and flushed to the UI on a fixed interval, so a chatty
+//! producer can't flood the UI thread with notifications.
+
+use crate::{
+    components::{
+        button::{Button, ButtonSize, ButtonVariant},
+        dropdown::{Dropdown, DropdownItem, DropdownState},
+        icon::Icon,
+        input::Input,
+        input_state::{InputEvent, InputState},
+        text::caption,
+    },
+    navigation::virtual_list::v_virtual_list,
+    theme::use_theme,
+};
+use gpui::{prelude::FluentBuilder as _, *};
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const DEFAULT_MAX_LINES: usize = 10_000;
+const FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+
+struct BufferedLine {
+    text: String,
+    /// `text` lowercased, filled in the first time a search looks at the line.
+    lowercase: Option<String>,
+}
+
+struct ChannelBuffer {
+    lines: VecDeque<BufferedLine>,
+    partial: String,
+    max_lines: usize,
+    dirty: bool,
+}
+
+impl ChannelBuffer {
+    fn push_line(&mut self, line: String) {
+        self.lines.push_back(BufferedLine {
+            text: line,
+            lowercase: None,
+        });
+        while self.lines.len() > self.max_lines {
+            self.lines.pop_front();
+        }
+    }
+
+    fn append(&mut self, text: &str) {
+        let mut rest = text;
+        while let Some(newline) = rest.find('\n') {
+            let mut line = std::mem::take(&mut self.partial);
+            line.push_str(rest[..newline].trim_end_matches('\r'));
+            self.push_line(line);
+            rest = &rest[newline + 1..];
+        }
+        self.partial.push_str(rest);
+        self.dirty = true;
+    }
+}
+
+/// A cheap, cloneable, thread-safe handle to a named output stream.
+#[derive(Clone)]
+pub struct OutputChannel {
+    name: SharedString,
+    buffer: Arc<Mutex<ChannelBuffer>>,
+}
+
+impl OutputChannel {
+    fn new(name: SharedString, max_lines: usize) -> Self {
+        Self {
+            name,
+            buffer: Arc::new(Mutex::new(ChannelBuffer {
+                lines: VecDeque::new(),
+                partial: String::new(),
+                max_lines,
+                dirty: false,
+            })),
+        }
+    }
+
+    pub fn name(&self) -> &SharedString {
+        &self.name
+    }
+
+    /// Append raw text. Text after the last newline is kept as a pending partial line.
+    pub fn append(&self, text: &str) {
+        if let Ok(mut buffer) = self.buffer.lock() {
+            buffer.append(text);
+        }
+    }
+
+    pub fn append_line(&self, line: &str) {
+        if let Ok(mut buffer) = self.buffer.lock() {
+            buffer.append(line);
+            buffer.append("\n");
+        }
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut buffer) = self.buffer.lock() {
+            buffer.lines.clear();
+            buffer.partial.clear();
+            buffer.dirty = true;
+        }
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.buffer
+            .lock()
+            .map(|b| b.lines.len() + usize::from(!b.partial.is_empty()))
+            .unwrap_or(0)
+    }
+
+    /// Snapshot of all lines, including a trailing partial line if any.
+    pub fn lines(&self) -> Vec<String> {
+        self.buffer
+            .lock()
+            .map(|b| {
+                let mut lines: Vec<String> = b.lines.iter().map(|l| l.text.clone()).collect();
+                if !b.partial.is_empty() {
+                    lines.push(b.partial.clone());
+                }
+                lines
+            })
+            .unwrap_or_default()
+    }
+
+    /// Lines containing `query`, which must already be lowercase. Each line is
+    /// lowercased once and kept, so searching again as output streams in only
+    /// lowercases the new lines.
+    fn lines_matching(&self, query: &str) -> Vec<String> {
+        self.buffer
+            .lock()
+            .map(|mut b| {
+                let mut lines: Vec<String> = b
+                    .lines
+                    .iter_mut()
+                    .filter_map(|line| {
+                        let lowercase = line
+                            .lowercase
+                            .get_or_insert_with(|| line.text.to_lowercase());
+                        lowercase.contains(query).then(|| line.text.clone())
+                    })
+                    .collect();
+                if !b.partial.is_empty() && b.partial.to_lowercase().contains(query) {
+                    lines.push(b.partial.clone());
+                }
+                lines
+            })
+            .unwrap_or_default()
+    }
+
+    fn take_dirty(&self) -> bool {
+        self.buffer
+            .lock()
+            .map(|mut b| std::mem::replace(&mut b.dirty, false))
+            .unwrap_or(false)
+    }
+}
+
+/// Registry of named output channels, observed by `OutputPanel`.
+pub struct OutputChannels {
+    channels: Vec<OutputChannel>,
+    active: usize,
+    max_lines: usize,
+    _flush_task: Task<()>,
+}
+
+impl OutputChannels {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        let flush_task = cx.spawn(async |this, cx| loop {
+            smol::Timer::after(FLUSH_INTERVAL).await;
+            let ok = this
+                .update(cx, |channels, cx| {
+                    let mut any_dirty = false;
+                    for channel in &channels.channels {
+                        any_dirty |= channel.take_dirty();
+                    }
+                    if any_dirty {
+                        cx.notify();
+                    }
+                })
+                .is_ok();
+            if !ok {
+                break;
+            }
+        });
+
+        Self {
+            channels: Vec::new(),
+            active: 0,
+            max_lines: DEFAULT_MAX_LINES,
+            _flush_task: flush_task,
+        }
+    }
+
+    /// Maximum number of lines retained per channel created after this call.
+    pub fn max_lines(mut self, max_lines: usize) -> Self {
+        self.max_lines = max_lines.max(1);
+        self
+    }
+
+    /// Get the channel with the given name, creating it if it doesn't exist yet.
+    pub fn channel(
+        &mut self,
+        name: impl Into<SharedString>,
+        cx: &mut Context<Self>,
+    ) -> OutputChannel {
+        let name = name.into();
+        if let Some(existing) = self.channels.iter().find(|c| c.name == name) {
+            return existing.clone();
+        }
+        let channel = OutputChannel::new(name, self.max_lines);
+        self.channels.push(channel.clone());
+        cx.notify();
+        channel
+    }
+
+    pub fn remove_channel(&mut self, name: &str, cx: &mut Context<Self>) {
+        self.channels.retain(|c| c.name.as_ref() != name);
+        self.active = self.active.min(self.channels.len().saturating_sub(1));
+        cx.notify();
+    }
+
+    pub fn channel_names(&self) -> Vec<SharedString> {
+        self.channels.iter().map(|c| c.name.clone()).collect()
+    }
+
+    pub fn active_channel(&self) -> Option<&OutputChannel> {
+        self.channels.get(self.active)
+    }
+
+    pub fn set_active_channel(&mut self, name: &str, cx: &mut Context<Self>) {
+        if let Some(idx) = self.channels.iter().position(|c| c.name.as_ref() == name) {
+            self.active = idx;
+            cx.notify();
+        }
+    }
+
+    pub fn clear_active(&mut self, cx: &mut Context<Self>) {
+        if let Some(channel) = self.channels.get(self.active) {
+            channel.clear();
+        }
+        cx.notify();
+    }
+}
+
+/// Read-only view over `OutputChannels` with a channel switcher, search and clear.
+pub struct OutputPanel {
+    channels: Entity<OutputChannels>,
+    channel_dropdown: Entity<DropdownState>,
+    search_input: Entity<InputState>,
+    search_query: String,
+    visible_lines: Rc<Vec<String>>,
+    scroll_handle: ScrollHandle,
+    follow_tail: bool,
+    style: StyleRefinement,
+}
+
+impl OutputPanel {
+    pub fn new(
+        channels: Entity<OutputChannels>,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let channel_dropdown = cx.new(DropdownState::new);
+        let search_input = cx.new(|cx| InputState::new(cx).placeholder("Filter output..."));
+
+        cx.observe(&channels, |this, _, cx| {
+            this.refresh_lines(cx);
+            cx.notify();
+        })
+        .detach();
+
+        cx.subscribe(&search_input, |this, input, event, cx| {
+            if let InputEvent::Change = event {
+                this.search_query = input.read(cx).content().to_string();
+                this.refresh_lines(cx);
+                cx.notify();
+            }
+        })
+        .detach();
+
+        let mut panel = Self {
+            channels,
+            channel_dropdown,
+            search_input,
+            search_query: String::new(),
+            visible_lines: Rc::new(Vec::new()),
+            scroll_handle: ScrollHandle::new(),
+            follow_tail: true,
+            style: StyleRefinement::default(),
+        };
+        panel.refresh_lines(cx);
+        panel
+    }
+
+    /// Keep the view scrolled to the newest output as lines arrive.
+    pub fn follow_tail(mut self, follow: bool) -> Self {
+        self.follow_tail = follow;
+        self
+    }
+
+    fn refresh_lines(&mut self, cx: &mut Context<Self>) {
+        let query = self.search_query.to_lowercase();
+        let lines = self
+            .channels
+            .read(cx)
+            .active_channel()
+            .map(|c| {
+                if query.is_empty() {
+                    c.lines()
+                } else {
+                    c.lines_matching(&query)
+                }
+            })
+            .unwrap_or_default();
+        self.visible_lines = Rc::new(lines);
+        if self.follow_tail {
+            self.scroll_handle.scroll_to_bottom();
+        }
+    }
+}
+
+impl Styled for OutputPanel {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+impl Render for OutputPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = use_theme();
+        let user_style = self.style.clone();
+        let channels = self.channels.clone();
+        let active_name = channels
+            .read(cx)
+            .active_channel()
+            .map(|c| c.name.clone())
+            .unwrap_or_else(|| "No output".into());
+
+        let dropdown_items: Vec<DropdownItem> = channels
+            .read(cx)
+            .channel_names()
+            .into_iter()
+            .map(|name| {
+                let channels = channels.clone();
+                let target = name.clone();
+                DropdownItem::new(name.clone(), name).on_click(move |_, cx| {
+                    channels.update(cx, |c, cx| c.set_active_channel(&target, cx));
+                })
+            })
+            .collect();
+
+        let line_height = px(18.0);
+        let item_sizes = Rc::new(vec![size(px(0.0), line_height); self.visible_lines.len()]);
+        let is_empty = self.visible_lines.is_empty();
+
+        div()
+            .flex()
+            .flex_col()
+            .size_full()
+            .bg(theme.tokens.background)
+            .map(|this| {
+                let mut div = this;
+                div.style().refine(&user_style);
+                div
+            })
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap(px(8.0))
+                    .px(px(8.0))
+                    .py(px(6.0))
+                    .border_b_1()
+                    .border_color(theme.tokens.border)
+                    .child(
+                        Dropdown::new(
+                            self.channel_dropdown.clone(),
+                            div()
+                                .flex()
+                                .items_center()
+                                .gap(px(6.0))
+                                .px(px(8.0))
+                                .py(px(4.0))
+                                .rounded(theme.tokens.radius_sm)
+                                .border_1()
+                                .border_color(theme.tokens.border)
+                                .text_size(px(12.0))
+                                .text_color(theme.tokens.foreground)
+                                .child(active_name)
+                                .child(
+                                    Icon::new("chevron-down")
+                                        .size(px(12.0))
+                                        .color(theme.tokens.muted_foreground),
+                                ),
+                        )
+                        .items(dropdown_items),
+                    )
+                    .child(div().flex_1().child(Input::new(&self.search_input)))
+                    .child(
+                        Button::new("output-clear", "Clear")
+                            .variant(ButtonVariant::Ghost)
+                            .size(ButtonSize::Sm)
+                            .on_click({
+                                let channels = channels.clone();
+                                move |_, _, cx| {
+                                    channels.update(cx, |c, cx| c.clear_active(cx));
+                                }
+                            }),
+                    ),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .overflow_hidden()
+                    .font_family(theme.tokens.font_mono.clone())
+                    .text_size(px(12.0))
+                    .when(is_empty, |this| {
+                        this.child(
+                            div()
+                                .p(px(12.0))
+                                .child(caption("No output").color(theme.tokens.muted_foreground)),
+                        )
+                    })
+                    .when(!is_empty, |this| {
+                        this.child(
+                            v_virtual_list(
+                                cx.entity(),
+                                "output-lines",
+                                item_sizes,
+                                move |panel, range, _window, _cx| {
+                                    let theme = use_theme();
+                                    range
+                                        .map(|ix| {
+                                            div()
+                                                .h(line_height)
+                                                .px(px(8.0))
+                                                .whitespace_nowrap()
+                                                .text_color(theme.tokens.foreground)
+                                                .child(SharedString::from(
+                                                    panel.visible_lines[ix].clone(),
+                                                ))
+                                        })
+                                        .collect::<Vec<_>>()
+                                },
+                            )
+                            .track_scroll(&self.scroll_handle),
+                        )
+                    }),
+            )
+    }
+}
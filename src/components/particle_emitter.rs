@@ -1,5 +1,6 @@
 //! Lightweight particle system using canvas painting.
 
+use crate::activity;
 use gpui::{prelude::FluentBuilder as _, *};
 use std::time::Duration;
 
@@ -169,6 +170,7 @@ impl ParticleEmitterState {
                 cx.background_executor()
                     .timer(Duration::from_millis(16))
                     .await;
+                activity::wait_until_pollable(cx).await;
 
                 _ = this.update(cx, |state, cx| {
                     if !state.running {
@@ -1,4 +1,13 @@
-use crate::components::slider::{SliderAxis, SliderSize};
+//! RangeSlider component - Two thumbs picking a `(start, end)` range.
+//!
+//! The thumbs can't pass each other: dragging one into the other stops it there, or
+//! [`RangeSliderState::set_min_gap`] short of it. Values snap to multiples of the step
+//! counted from `min`, and the track between the thumbs is filled.
+
+use crate::components::slider::{
+    key_step, page_steps, Decrement, DecrementPage, Increment, IncrementPage, MoveToMax, MoveToMin,
+    SliderAxis, SliderSize, KEY_CONTEXT,
+};
 use crate::theme::use_theme;
 use gpui::{prelude::*, *};
 use std::rc::Rc;
@@ -16,8 +25,12 @@ pub struct RangeSliderState {
     start_value: f32,
     end_value: f32,
     step: f32,
+    /// Smallest distance kept between the thumbs.
+    min_gap: f32,
     focus_handle: FocusHandle,
     active_thumb: ActiveThumb,
+    /// The thumb the arrow keys move: whichever was grabbed last.
+    key_thumb: ActiveThumb,
     bounds: Bounds<Pixels>,
 }
 
@@ -29,8 +42,10 @@ impl RangeSliderState {
             start_value: 25.0,
             end_value: 75.0,
             step: 1.0,
+            min_gap: 0.0,
             focus_handle: cx.focus_handle(),
             active_thumb: ActiveThumb::None,
+            key_thumb: ActiveThumb::Start,
             bounds: Bounds::default(),
         }
     }
@@ -70,8 +85,8 @@ impl RangeSliderState {
     }
 
     pub fn set_start_value(&mut self, value: f32, cx: &mut Context<Self>) {
-        let clamped = value.clamp(self.min, self.end_value);
-        let stepped = ((clamped / self.step).round() * self.step).clamp(self.min, self.end_value);
+        let upper = (self.end_value - self.min_gap).max(self.min);
+        let stepped = self.snap(value, self.min, upper);
 
         if (self.start_value - stepped).abs() > f32::EPSILON {
             self.start_value = stepped;
@@ -80,8 +95,8 @@ impl RangeSliderState {
     }
 
     pub fn set_end_value(&mut self, value: f32, cx: &mut Context<Self>) {
-        let clamped = value.clamp(self.start_value, self.max);
-        let stepped = ((clamped / self.step).round() * self.step).clamp(self.start_value, self.max);
+        let lower = (self.start_value + self.min_gap).min(self.max);
+        let stepped = self.snap(value, lower, self.max);
 
         if (self.end_value - stepped).abs() > f32::EPSILON {
             self.end_value = stepped;
@@ -90,13 +105,8 @@ impl RangeSliderState {
     }
 
     pub fn set_range(&mut self, start: f32, end: f32, cx: &mut Context<Self>) {
-        let clamped_start = start.clamp(self.min, self.max);
-        let clamped_end = end.clamp(clamped_start, self.max);
-
-        let stepped_start =
-            ((clamped_start / self.step).round() * self.step).clamp(self.min, self.max);
-        let stepped_end =
-            ((clamped_end / self.step).round() * self.step).clamp(stepped_start, self.max);
+        let (stepped_start, stepped_end) =
+            snap_range(start, end, (self.min, self.max), self.step, self.min_gap);
 
         let changed = (self.start_value - stepped_start).abs() > f32::EPSILON
             || (self.end_value - stepped_end).abs() > f32::EPSILON;
@@ -108,6 +118,10 @@ impl RangeSliderState {
         }
     }
 
+    fn snap(&self, value: f32, lower: f32, upper: f32) -> f32 {
+        snap(value, self.min, self.step, lower, upper)
+    }
+
     pub fn step(&self) -> f32 {
         self.step
     }
@@ -117,6 +131,18 @@ impl RangeSliderState {
         cx.notify();
     }
 
+    pub fn min_gap(&self) -> f32 {
+        self.min_gap
+    }
+
+    /// Keep the thumbs at least `gap` apart, e.g. a one-night minimum stay. Moves the
+    /// end thumb if they're closer now.
+    pub fn set_min_gap(&mut self, gap: f32, cx: &mut Context<Self>) {
+        self.min_gap = gap.max(0.0);
+        let (start, end) = (self.start_value, self.end_value);
+        self.set_range(start, end, cx);
+    }
+
     fn start_percentage(&self) -> f32 {
         if self.max == self.min {
             return 0.0;
@@ -153,6 +179,18 @@ impl RangeSliderState {
         self.min + percentage * (self.max - self.min)
     }
 
+    /// The thumb a press at `value` grabs: the nearer one, or when they're stacked, the
+    /// one that can move toward it.
+    fn nearest_thumb(&self, value: f32) -> ActiveThumb {
+        let start_dist = (value - self.start_value).abs();
+        let end_dist = (value - self.end_value).abs();
+        if start_dist < end_dist || (start_dist == end_dist && value < self.start_value) {
+            ActiveThumb::Start
+        } else {
+            ActiveThumb::End
+        }
+    }
+
     fn update_from_position(&mut self, position: Point<Pixels>, cx: &mut Context<Self>) {
         let new_value = self.value_from_position(position);
 
@@ -160,18 +198,14 @@ impl RangeSliderState {
             ActiveThumb::Start => self.set_start_value(new_value, cx),
             ActiveThumb::End => self.set_end_value(new_value, cx),
             ActiveThumb::None => {
-                let start_dist = (new_value - self.start_value).abs();
-                let end_dist = (new_value - self.end_value).abs();
-
-                if start_dist <= end_dist {
-                    self.active_thumb = ActiveThumb::Start;
-                    self.set_start_value(new_value, cx);
-                } else {
-                    self.active_thumb = ActiveThumb::End;
-                    self.set_end_value(new_value, cx);
+                self.active_thumb = self.nearest_thumb(new_value);
+                match self.active_thumb {
+                    ActiveThumb::Start => self.set_start_value(new_value, cx),
+                    _ => self.set_end_value(new_value, cx),
                 }
             }
         }
+        self.key_thumb = self.active_thumb;
     }
 
     fn update_from_position_vertical(&mut self, position: Point<Pixels>, cx: &mut Context<Self>) {
@@ -181,21 +215,65 @@ impl RangeSliderState {
             ActiveThumb::Start => self.set_start_value(new_value, cx),
             ActiveThumb::End => self.set_end_value(new_value, cx),
             ActiveThumb::None => {
-                let start_dist = (new_value - self.start_value).abs();
-                let end_dist = (new_value - self.end_value).abs();
-
-                if start_dist <= end_dist {
-                    self.active_thumb = ActiveThumb::Start;
-                    self.set_start_value(new_value, cx);
-                } else {
-                    self.active_thumb = ActiveThumb::End;
-                    self.set_end_value(new_value, cx);
+                self.active_thumb = self.nearest_thumb(new_value);
+                match self.active_thumb {
+                    ActiveThumb::Start => self.set_start_value(new_value, cx),
+                    _ => self.set_end_value(new_value, cx),
                 }
             }
         }
+        self.key_thumb = self.active_thumb;
+    }
+
+    fn move_key_thumb_to(&mut self, value: f32, cx: &mut Context<Self>) {
+        match self.key_thumb {
+            ActiveThumb::End => self.set_end_value(value, cx),
+            _ => self.set_start_value(value, cx),
+        }
+    }
+
+    fn nudge(&mut self, steps: f32, cx: &mut Context<Self>) {
+        let value = match self.key_thumb {
+            ActiveThumb::End => self.end_value,
+            _ => self.start_value,
+        };
+        let step = key_step(self.min, self.max, self.step);
+        self.move_key_thumb_to(value + steps * step, cx);
+    }
+
+    fn page(&mut self, pages: f32, cx: &mut Context<Self>) {
+        self.nudge(pages * page_steps(self.min, self.max, self.step), cx);
     }
 }
 
+/// Round `value` to the nearest `step` counted from `min`, staying within
+/// `lower..=upper`. Should rounding leave `lower` above `upper`, `upper` wins.
+fn snap(value: f32, min: f32, step: f32, lower: f32, upper: f32) -> f32 {
+    let lower = lower.min(upper);
+    if step <= 0.0 {
+        return value.clamp(lower, upper);
+    }
+    let snapped = min + ((value - min) / step).round() * step;
+    if snapped > upper {
+        snapped - step
+    } else if snapped < lower {
+        snapped + step
+    } else {
+        snapped
+    }
+    .clamp(lower, upper)
+}
+
+/// Both ends of a range within `min..=max`, snapped to `step` and at least `gap`
+/// apart where the range allows.
+fn snap_range(start: f32, end: f32, (min, max): (f32, f32), step: f32, gap: f32) -> (f32, f32) {
+    let (start, end) = (start.min(end), start.max(end));
+    let gap = gap.min(max - min);
+    let stepped_start = snap(start, min, step, min, max - gap);
+    let lower = (stepped_start + gap).min(max);
+    (stepped_start, snap(end, min, step, lower, max))
+}
+
 impl Focusable for RangeSliderState {
     fn focus_handle(&self, _: &App) -> FocusHandle {
         self.focus_handle.clone()
@@ -273,12 +351,45 @@ impl Styled for RangeSlider {
 }
 
 impl RangeSlider {
+    /// Run `apply` for action `A`, reporting the new range if it changed.
+    fn action_listener<A: Action>(
+        &self,
+        window: &mut Window,
+        apply: fn(&mut RangeSliderState, &mut Context<RangeSliderState>),
+    ) -> impl Fn(&A, &mut Window, &mut App) {
+        let on_change = self.on_change.clone();
+        window.listener_for(&self.state, move |state, _: &A, window, cx| {
+            let before = state.range();
+            apply(state, cx);
+            if state.range() != before {
+                if let Some(ref handler) = on_change {
+                    handler(state.start_value, state.end_value, window, cx);
+                }
+            }
+        })
+    }
+
+    /// Keys move the last grabbed thumb; the thumbs still can't pass each other.
+    fn key_actions(&self, div: Div, window: &mut Window) -> Div {
+        div.key_context(KEY_CONTEXT)
+            .on_action(self.action_listener::<Increment>(window, |s, cx| s.nudge(1.0, cx)))
+            .on_action(self.action_listener::<Decrement>(window, |s, cx| s.nudge(-1.0, cx)))
+            .on_action(self.action_listener::<IncrementPage>(window, |s, cx| s.page(1.0, cx)))
+            .on_action(self.action_listener::<DecrementPage>(window, |s, cx| s.page(-1.0, cx)))
+            .on_action(
+                self.action_listener::<MoveToMin>(window, |s, cx| s.move_key_thumb_to(s.min, cx)),
+            )
+            .on_action(
+                self.action_listener::<MoveToMax>(window, |s, cx| s.move_key_thumb_to(s.max, cx)),
+            )
+    }
+
     fn render_horizontal(
         self,
         window: &mut Window,
         theme: crate::theme::Theme,
         focus_handle: FocusHandle,
-        is_focused: bool,
+        ring_on: Option<ActiveThumb>,
         start_percentage: f32,
         end_percentage: f32,
         start_value: f32,
@@ -320,10 +431,8 @@ impl RangeSlider {
                     .flex()
                     .items_center()
                     .when(!self.disabled, |this| {
-                        this.track_focus(&focus_handle.tab_index(0).tab_stop(true))
-                    })
-                    .when(is_focused && !self.disabled, |this| {
-                        this.shadow(smallvec::smallvec![focus_ring])
+                        let this = this.track_focus(&focus_handle.tab_index(0).tab_stop(true));
+                        self.key_actions(this, window)
                     })
                     .rounded(theme.tokens.radius_md)
                     .child(
@@ -381,12 +490,20 @@ impl RangeSlider {
                                         .cursor(CursorStyle::PointingHand)
                                 }
                             })
+                            .when(ring_on == Some(ActiveThumb::Start), {
+                                let shadows = smallvec::smallvec![
+                                    theme.tokens.shadow_sm.clone(),
+                                    focus_ring.clone()
+                                ];
+                                move |this| this.shadow(shadows)
+                            })
                             .when(!self.disabled, |this| {
                                 this.on_mouse_down(
                                     MouseButton::Left,
                                     window.listener_for(
                                         &state_clone,
                                         move |state, e: &MouseDownEvent, window, cx| {
+                                            window.focus(&state.focus_handle);
                                             state.active_thumb = ActiveThumb::Start;
                                             state.update_from_position(e.position, cx);
 
@@ -427,12 +544,20 @@ impl RangeSlider {
                                         .cursor(CursorStyle::PointingHand)
                                 }
                             })
+                            .when(ring_on == Some(ActiveThumb::End), {
+                                let shadows = smallvec::smallvec![
+                                    theme.tokens.shadow_sm.clone(),
+                                    focus_ring.clone()
+                                ];
+                                move |this| this.shadow(shadows)
+                            })
                             .when(!self.disabled, |this| {
                                 this.on_mouse_down(
                                     MouseButton::Left,
                                     window.listener_for(
                                         &state_clone,
                                         move |state, e: &MouseDownEvent, window, cx| {
+                                            window.focus(&state.focus_handle);
                                             state.active_thumb = ActiveThumb::End;
                                             state.update_from_position(e.position, cx);
 
@@ -513,7 +638,7 @@ impl RangeSlider {
         window: &mut Window,
         theme: crate::theme::Theme,
         focus_handle: FocusHandle,
-        is_focused: bool,
+        ring_on: Option<ActiveThumb>,
         start_percentage: f32,
         end_percentage: f32,
         start_value: f32,
@@ -557,10 +682,8 @@ impl RangeSlider {
                     .items_center()
                     .justify_center()
                     .when(!self.disabled, |this| {
-                        this.track_focus(&focus_handle.tab_index(0).tab_stop(true))
-                    })
-                    .when(is_focused && !self.disabled, |this| {
-                        this.shadow(smallvec::smallvec![focus_ring])
+                        let this = this.track_focus(&focus_handle.tab_index(0).tab_stop(true));
+                        self.key_actions(this, window)
                     })
                     .rounded(theme.tokens.radius_md)
                     .child(
@@ -618,12 +741,20 @@ impl RangeSlider {
                                         .cursor(CursorStyle::PointingHand)
                                 }
                             })
+                            .when(ring_on == Some(ActiveThumb::Start), {
+                                let shadows = smallvec::smallvec![
+                                    theme.tokens.shadow_sm.clone(),
+                                    focus_ring.clone()
+                                ];
+                                move |this| this.shadow(shadows)
+                            })
                             .when(!self.disabled, |this| {
                                 this.on_mouse_down(
                                     MouseButton::Left,
                                     window.listener_for(
                                         &state_clone,
                                         move |state, e: &MouseDownEvent, window, cx| {
+                                            window.focus(&state.focus_handle);
                                             state.active_thumb = ActiveThumb::Start;
                                             state.update_from_position_vertical(e.position, cx);
 
@@ -664,12 +795,20 @@ impl RangeSlider {
                                         .cursor(CursorStyle::PointingHand)
                                 }
                             })
+                            .when(ring_on == Some(ActiveThumb::End), {
+                                let shadows = smallvec::smallvec![
+                                    theme.tokens.shadow_sm.clone(),
+                                    focus_ring.clone()
+                                ];
+                                move |this| this.shadow(shadows)
+                            })
                             .when(!self.disabled, |this| {
                                 this.on_mouse_down(
                                     MouseButton::Left,
                                     window.listener_for(
                                         &state_clone,
                                         move |state, e: &MouseDownEvent, window, cx| {
+                                            window.focus(&state.focus_handle);
                                             state.active_thumb = ActiveThumb::End;
                                             state.update_from_position_vertical(e.position, cx);
 
@@ -751,7 +890,8 @@ impl RenderOnce for RangeSlider {
         let theme = use_theme();
         let state = self.state.read(cx);
         let focus_handle = state.focus_handle(cx);
-        let is_focused = focus_handle.is_focused(window);
+        let ring_on =
+            (focus_handle.is_focused(window) && !self.disabled).then_some(state.key_thumb);
         let start_percentage = state.start_percentage();
         let end_percentage = state.end_percentage();
         let start_value = state.start_value;
@@ -783,7 +923,7 @@ impl RenderOnce for RangeSlider {
                 window,
                 theme,
                 focus_handle,
-                is_focused,
+                ring_on,
                 start_percentage,
                 end_percentage,
                 start_value,
@@ -801,7 +941,7 @@ impl RenderOnce for RangeSlider {
                 window,
                 theme,
                 focus_handle,
-                is_focused,
+                ring_on,
                 start_percentage,
                 end_percentage,
                 start_value,
@@ -818,3 +958,25 @@ impl RenderOnce for RangeSlider {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snap_range_keeps_the_gap_at_the_top_end() {
+        // `max - gap + gap` rounds above `max` in f32.
+        let (start, end) = snap_range(1.17, 1.17, (0.0, 1.17), 0.0, 0.088);
+        assert!(start <= end && end <= 1.17);
+        assert_eq!(snap(5.0, 0.0, 1.0, 3.0, 2.0), 2.0);
+
+        assert_eq!(
+            snap_range(80.0, 20.0, (0.0, 100.0), 5.0, 10.0),
+            (20.0, 80.0)
+        );
+        assert_eq!(
+            snap_range(100.0, 100.0, (0.0, 100.0), 5.0, 10.0),
+            (90.0, 100.0)
+        );
+    }
+}
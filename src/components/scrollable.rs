@@ -1,12 +1,142 @@
 //! Scrollable component with visible scrollbars.
+//!
+//! With smooth scrolling on, mouse-wheel notches and programmatic jumps made through a
+//! [`SmoothScroll`] ease to their target instead of snapping. Trackpads already deliver
+//! continuous pixel deltas, so those are left as they are.
 
 use super::scrollbar::{Scrollbar, ScrollbarAxis, ScrollbarState};
+use crate::animations::{easings, reduced_motion};
 use gpui::{
-    div, relative, AnyElement, App, Bounds, Div, Element, ElementId, GlobalElementId,
-    InspectorElementId, InteractiveElement, Interactivity, IntoElement, LayoutId, ParentElement,
-    Pixels, Position, ScrollHandle, SharedString, Stateful, StatefulInteractiveElement, Style,
-    StyleRefinement, Styled, Window,
+    div, point, px, relative, AnyElement, App, Bounds, DispatchPhase, Div, Element, ElementId,
+    GlobalElementId, InspectorElementId, InteractiveElement, Interactivity, IntoElement, LayoutId,
+    ParentElement, Pixels, Point, Position, ScrollDelta, ScrollHandle, ScrollWheelEvent,
+    SharedString, Stateful, StatefulInteractiveElement, Style, StyleRefinement, Styled, Window,
 };
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// How long a smooth scroll takes to reach its target.
+pub const SMOOTH_SCROLL_DURATION: Duration = Duration::from_millis(120);
+
+#[derive(Clone, Copy, Debug)]
+struct ScrollAnimation {
+    from: Point<Pixels>,
+    to: Point<Pixels>,
+    start: Instant,
+}
+
+impl ScrollAnimation {
+    /// The offset at `now`, and whether the target has been reached.
+    fn sample(&self, now: Instant) -> (Point<Pixels>, bool) {
+        let t = now.saturating_duration_since(self.start).as_secs_f32()
+            / SMOOTH_SCROLL_DURATION.as_secs_f32();
+        if t >= 1.0 {
+            return (self.to, true);
+        }
+        let eased = easings::ease_out_cubic(t);
+        let offset = point(
+            self.from.x + (self.to.x - self.from.x) * eased,
+            self.from.y + (self.to.y - self.from.y) * eased,
+        );
+        (offset, false)
+    }
+}
+
+#[derive(Default)]
+struct SmoothScrollState {
+    animation: Option<ScrollAnimation>,
+    /// The offset this controller last left the handle at.
+    settled: Option<Point<Pixels>>,
+    /// Whether the latest wheel event over the view moved in whole lines.
+    wheel_lines: bool,
+}
+
+/// Eases a [`ScrollHandle`] towards its target offset over [`SMOOTH_SCROLL_DURATION`].
+///
+/// Pass one to [`Scrollable::with_smooth_scroll`] to animate mouse-wheel notches, and
+/// call [`SmoothScroll::scroll_to`] for animated jumps. Scrolls started while one is
+/// running continue from the current position, so quick wheel notches accumulate.
+/// Everything snaps immediately while [`reduced_motion`] is set.
+#[derive(Clone, Default)]
+pub struct SmoothScroll(Rc<RefCell<SmoothScrollState>>);
+
+impl SmoothScroll {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Animate `handle` to `offset` (negative, like [`ScrollHandle::offset`]).
+    pub fn scroll_to(&self, handle: &ScrollHandle, offset: Point<Pixels>) {
+        let mut state = self.0.borrow_mut();
+        let from = handle.offset();
+        state.settled = Some(from);
+        state.animation = Some(ScrollAnimation {
+            from,
+            to: offset,
+            start: Instant::now(),
+        });
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.0.borrow().animation.is_some()
+    }
+
+    /// Cancel a running animation, leaving the handle where it is.
+    pub fn stop(&self) {
+        self.0.borrow_mut().animation = None;
+    }
+
+    fn note_wheel(&self, delta: &ScrollDelta) {
+        self.0.borrow_mut().wheel_lines = matches!(delta, ScrollDelta::Lines(_));
+    }
+
+    /// Move `handle` to this frame's offset. Returns true while an animation is running.
+    ///
+    /// A wheel notch scrolls the handle in one step during event dispatch; that jump is
+    /// undone here and replayed as an animation. Any other outside change, such as a
+    /// scrollbar drag, cancels the animation.
+    fn step(&self, handle: &ScrollHandle, reduced: bool) -> bool {
+        let mut state = self.0.borrow_mut();
+        let now = Instant::now();
+        let current = handle.offset();
+        if let Some(settled) = state.settled.filter(|settled| *settled != current) {
+            state.animation = if state.wheel_lines && !reduced {
+                let jump = current - settled;
+                let to = state
+                    .animation
+                    .map_or(current, |animation| animation.to + jump);
+                Some(ScrollAnimation {
+                    from: settled,
+                    to,
+                    start: now,
+                })
+            } else {
+                None
+            };
+        }
+        state.wheel_lines = false;
+
+        let Some(mut animation) = state.animation else {
+            state.settled = Some(current);
+            return false;
+        };
+        let max = handle.max_offset();
+        animation.to = point(
+            animation.to.x.max(-max.width).min(px(0.0)),
+            animation.to.y.max(-max.height).min(px(0.0)),
+        );
+        let (offset, done) = if reduced {
+            (animation.to, true)
+        } else {
+            animation.sample(now)
+        };
+        handle.set_offset(offset);
+        state.settled = Some(offset);
+        state.animation = (!done).then_some(animation);
+        !done
+    }
+}
 
 /// A scroll view with visible scrollbars
 pub struct Scrollable<E> {
@@ -15,6 +145,8 @@ pub struct Scrollable<E> {
     axis: ScrollbarAxis,
     always_show_scrollbars: bool,
     external_scroll_handle: Option<ScrollHandle>,
+    smooth_scroll: bool,
+    external_smooth_scroll: Option<SmoothScroll>,
     _element: Stateful<Div>,
 }
 
@@ -32,6 +164,8 @@ where
             axis,
             always_show_scrollbars: false,
             external_scroll_handle: None,
+            smooth_scroll: false,
+            external_smooth_scroll: None,
         }
     }
 
@@ -57,6 +191,43 @@ where
         self
     }
 
+    /// Ease mouse-wheel scrolling instead of jumping a notch at a time.
+    pub fn smooth_scroll(mut self) -> Self {
+        self.smooth_scroll = true;
+        self
+    }
+
+    /// Like [`Self::smooth_scroll`], sharing `smooth` so the owner can also animate
+    /// jumps with [`SmoothScroll::scroll_to`].
+    pub fn with_smooth_scroll(mut self, smooth: SmoothScroll) -> Self {
+        self.smooth_scroll = true;
+        self.external_smooth_scroll = Some(smooth);
+        self
+    }
+
+    /// The scroll handle and, if enabled, the smooth scroller driving it.
+    fn smooth_scroll_parts(
+        &mut self,
+        id: &GlobalElementId,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<(SmoothScroll, ScrollHandle)> {
+        if !self.smooth_scroll {
+            return None;
+        }
+        self.with_element_state(id, window, cx, |this, state, _, _| {
+            let smooth = this
+                .external_smooth_scroll
+                .clone()
+                .unwrap_or_else(|| state.smooth.clone());
+            let handle = this
+                .external_scroll_handle
+                .clone()
+                .unwrap_or_else(|| state.handle.clone());
+            Some((smooth, handle))
+        })
+    }
+
     fn with_element_state<R>(
         &mut self,
         id: &GlobalElementId,
@@ -78,6 +249,7 @@ where
 pub struct ScrollViewState {
     state: ScrollbarState,
     handle: ScrollHandle,
+    smooth: SmoothScroll,
 }
 
 impl Default for ScrollViewState {
@@ -85,6 +257,7 @@ impl Default for ScrollViewState {
         Self {
             handle: ScrollHandle::new(),
             state: ScrollbarState::default(),
+            smooth: SmoothScroll::new(),
         }
     }
 }
@@ -227,27 +400,41 @@ where
 
     fn prepaint(
         &mut self,
-        _: Option<&GlobalElementId>,
+        id: Option<&GlobalElementId>,
         _: Option<&InspectorElementId>,
         _: Bounds<Pixels>,
         element: &mut Self::RequestLayoutState,
         window: &mut Window,
         cx: &mut App,
     ) -> Self::PrepaintState {
+        if let Some((smooth, handle)) = self.smooth_scroll_parts(id.unwrap(), window, cx) {
+            if smooth.step(&handle, reduced_motion(cx)) {
+                window.request_animation_frame();
+            }
+        }
         element.prepaint(window, cx);
         ScrollViewState::default()
     }
 
     fn paint(
         &mut self,
-        _: Option<&GlobalElementId>,
+        id: Option<&GlobalElementId>,
         _: Option<&InspectorElementId>,
-        _: Bounds<Pixels>,
+        bounds: Bounds<Pixels>,
         element: &mut Self::RequestLayoutState,
         _: &mut Self::PrepaintState,
         window: &mut Window,
         cx: &mut App,
     ) {
+        if let Some((smooth, _)) = self.smooth_scroll_parts(id.unwrap(), window, cx) {
+            // Capture runs outer views first, before any scroll happens, so record the
+            // kind of delta for `SmoothScroll::step` in the next frame.
+            window.on_mouse_event(move |event: &ScrollWheelEvent, phase, _, _| {
+                if phase == DispatchPhase::Capture && bounds.contains(&event.position) {
+                    smooth.note_wheel(&event.delta);
+                }
+            });
+        }
         element.paint(window, cx)
     }
 }
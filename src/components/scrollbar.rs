@@ -10,6 +10,7 @@ use gpui::{
 };
 
 use crate::theme::use_theme;
+use crate::util::SnapToDevicePixels;
 
 pub(crate) const WIDTH: Pixels = px(12.0);
 const MIN_THUMB_SIZE: f32 = 48.;
@@ -498,11 +499,16 @@ impl Element for Scrollbar {
 
                     window.set_cursor_style(CursorStyle::default(), &state.bar_hitbox);
 
+                    let scale_factor = window.scale_factor();
                     window.paint_layer(hitbox_bounds, |cx| {
-                        cx.paint_quad(fill(state.bounds, state.bg));
+                        cx.paint_quad(fill(state.bounds.snap_to_device(scale_factor), state.bg));
 
                         cx.paint_quad(
-                            fill(state.thumb_fill_bounds, state.thumb_bg).corner_radii(radius),
+                            fill(
+                                state.thumb_fill_bounds.snap_to_device(scale_factor),
+                                state.thumb_bg,
+                            )
+                            .corner_radii(radius),
                         );
                     });
 
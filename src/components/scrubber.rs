@@ -0,0 +1,320 @@
+//! Drag-to-change ("scrubbing") for numeric values.
+//!
+//! Dragging horizontally on a [`Scrubber`] label, or on the value of a
+//! [`NumberInput`](super::number_input::NumberInput), changes the value live. Holding
+//! shift moves in coarse steps and alt in fine ones; the modifiers can be pressed or
+//! released mid-drag without the value jumping. Once started, a scrub keeps tracking
+//! the pointer anywhere in the window until the button is released.
+
+use crate::theme::use_theme;
+use gpui::{prelude::FluentBuilder as _, *};
+use std::rc::Rc;
+
+/// Pointer travel, in pixels, before a press turns into a scrub.
+const DRAG_THRESHOLD: f32 = 3.0;
+const COARSE_FACTOR: f64 = 10.0;
+const FINE_FACTOR: f64 = 0.1;
+
+/// How pointer movement maps onto a value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct ScrubRange {
+    pub step: f64,
+    pub pixels_per_step: f32,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub precision: usize,
+}
+
+impl ScrubRange {
+    fn start(&self, x: Pixels, value: f64) -> ScrubDrag {
+        ScrubDrag {
+            origin_x: x,
+            last_x: x,
+            raw: value,
+            value,
+            active: false,
+        }
+    }
+
+    /// Advance `drag` to pointer position `x`, returning the new value if it changed.
+    fn scrub(&self, drag: &mut ScrubDrag, x: Pixels, modifiers: &Modifiers) -> Option<f64> {
+        if !drag.active {
+            if ((x - drag.origin_x) / px(1.0)).abs() < DRAG_THRESHOLD {
+                return None;
+            }
+            drag.active = true;
+        }
+
+        let factor = if modifiers.shift {
+            COARSE_FACTOR
+        } else if modifiers.alt {
+            FINE_FACTOR
+        } else {
+            1.0
+        };
+        let increment = self.step * factor;
+        let dx = ((x - drag.last_x) / px(1.0)) as f64;
+        drag.last_x = x;
+        drag.raw = self.clamp(drag.raw + dx / self.pixels_per_step as f64 * increment);
+
+        // Coarse drags move faster but still land on whole steps.
+        let snap = self.step * factor.min(1.0);
+        let value = self.clamp(self.round((drag.raw / snap).round() * snap));
+        if value == drag.value {
+            return None;
+        }
+        drag.value = value;
+        Some(value)
+    }
+
+    fn clamp(&self, value: f64) -> f64 {
+        let value = self.min.map_or(value, |min| value.max(min));
+        self.max.map_or(value, |max| value.min(max))
+    }
+
+    /// Drop floating point noise below the displayed precision.
+    fn round(&self, value: f64) -> f64 {
+        let scale = 10f64.powi(self.precision as i32);
+        (value * scale).round() / scale
+    }
+}
+
+/// An in-progress press or scrub.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct ScrubDrag {
+    origin_x: Pixels,
+    last_x: Pixels,
+    /// Unrounded value, so slow drags accumulate rather than snapping back.
+    raw: f64,
+    value: f64,
+    active: bool,
+}
+
+impl ScrubDrag {
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+}
+
+/// Begin tracking a press at `x` on a value of `value`.
+pub(crate) fn start_scrub(
+    drag: &Entity<Option<ScrubDrag>>,
+    range: ScrubRange,
+    x: Pixels,
+    value: f64,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    drag.update(cx, |drag, _| *drag = Some(range.start(x, value)));
+    window.refresh();
+}
+
+/// A canvas that, while `drag` holds a press, follows the pointer window-wide and
+/// calls `on_scrub` with each new value. Place it as a child of the scrubbed element.
+pub(crate) fn scrub_tracker(
+    drag: Entity<Option<ScrubDrag>>,
+    range: ScrubRange,
+    on_scrub: Rc<dyn Fn(f64, &mut Window, &mut App)>,
+) -> impl IntoElement {
+    canvas(
+        |_, _, _| {},
+        move |_, _, window, cx| {
+            if drag.read(cx).is_none() {
+                return;
+            }
+
+            window.on_mouse_event({
+                let drag = drag.clone();
+                move |event: &MouseMoveEvent, phase, window, cx| {
+                    if !phase.bubble() {
+                        return;
+                    }
+                    let value = drag.update(cx, |drag, _| {
+                        let drag = drag.as_mut()?;
+                        range.scrub(drag, event.position.x, &event.modifiers)
+                    });
+                    if let Some(value) = value {
+                        on_scrub(value, window, cx);
+                        window.refresh();
+                    }
+                }
+            });
+
+            window.on_mouse_event(move |_: &MouseUpEvent, phase, window, cx| {
+                if phase.bubble() {
+                    drag.update(cx, |drag, _| *drag = None);
+                    window.refresh();
+                }
+            });
+        },
+    )
+    .absolute()
+    .size_full()
+}
+
+/// A numeric label that changes its value when dragged horizontally.
+///
+/// The value is controlled: render it from your own state and update that state in
+/// [`on_change`](Self::on_change), which fires on every change during the drag.
+#[derive(IntoElement)]
+pub struct Scrubber {
+    id: ElementId,
+    value: f64,
+    range: ScrubRange,
+    label: Option<AnyElement>,
+    disabled: bool,
+    on_change: Option<Rc<dyn Fn(f64, &mut Window, &mut App)>>,
+    style: StyleRefinement,
+}
+
+impl Scrubber {
+    pub fn new(id: impl Into<ElementId>, value: f64) -> Self {
+        Self {
+            id: id.into(),
+            value,
+            range: ScrubRange {
+                step: 1.0,
+                pixels_per_step: 4.0,
+                min: None,
+                max: None,
+                precision: 0,
+            },
+            label: None,
+            disabled: false,
+            on_change: None,
+            style: StyleRefinement::default(),
+        }
+    }
+
+    /// Value change per `pixels_per_step` of travel without modifiers.
+    pub fn step(mut self, step: f64) -> Self {
+        self.range.step = step.abs().max(f64::EPSILON);
+        self
+    }
+
+    pub fn pixels_per_step(mut self, pixels: f32) -> Self {
+        self.range.pixels_per_step = pixels.max(1.0);
+        self
+    }
+
+    pub fn min(mut self, min: f64) -> Self {
+        self.range.min = Some(min);
+        self
+    }
+
+    pub fn max(mut self, max: f64) -> Self {
+        self.range.max = Some(max);
+        self
+    }
+
+    /// Decimal places shown and kept while scrubbing.
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.range.precision = precision;
+        self
+    }
+
+    /// Custom content in place of the formatted value.
+    pub fn label(mut self, label: impl IntoElement) -> Self {
+        self.label = Some(label.into_any_element());
+        self
+    }
+
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+
+    pub fn on_change(mut self, handler: impl Fn(f64, &mut Window, &mut App) + 'static) -> Self {
+        self.on_change = Some(Rc::new(handler));
+        self
+    }
+}
+
+impl Styled for Scrubber {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+impl RenderOnce for Scrubber {
+    fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let theme = use_theme();
+        let user_style = self.style;
+        let drag = window.use_keyed_state(self.id.clone(), cx, |_, _| None::<ScrubDrag>);
+        let scrubbing = drag.read(cx).is_some_and(|drag| drag.is_active());
+        let range = self.range;
+        let value = self.value;
+        let label = self.label.unwrap_or_else(|| {
+            format!("{:.prec$}", value, prec = range.precision).into_any_element()
+        });
+
+        div()
+            .id(self.id)
+            .relative()
+            .px_1()
+            .rounded(theme.tokens.radius_sm)
+            .text_color(theme.tokens.foreground)
+            .font_family(theme.tokens.font_family.clone())
+            .when(self.disabled, |this| this.opacity(0.5))
+            .when(!self.disabled, |this| {
+                this.cursor(CursorStyle::ResizeLeftRight)
+                    .hover(|style| style.bg(theme.tokens.accent.opacity(0.5)))
+                    .when(scrubbing, |this| this.bg(theme.tokens.accent))
+                    .on_mouse_down(MouseButton::Left, {
+                        let drag = drag.clone();
+                        move |event, window, cx| {
+                            start_scrub(&drag, range, event.position.x, value, window, cx);
+                            cx.stop_propagation();
+                        }
+                    })
+                    .when_some(self.on_change, |this, on_change| {
+                        this.child(scrub_tracker(drag, range, on_change))
+                    })
+            })
+            .child(label)
+            .map(|mut this| {
+                this.style().refine(&user_style);
+                this
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scrub_to(range: &ScrubRange, drag: &mut ScrubDrag, x: f32, modifiers: Modifiers) -> f64 {
+        range.scrub(drag, px(x), &modifiers);
+        drag.value
+    }
+
+    #[test]
+    fn modifiers_change_speed_mid_drag() {
+        let range = ScrubRange {
+            step: 1.0,
+            pixels_per_step: 2.0,
+            min: Some(0.0),
+            max: Some(100.0),
+            precision: 1,
+        };
+        let mut drag = range.start(px(100.0), 50.0);
+        let plain = Modifiers::default();
+        let shift = Modifiers {
+            shift: true,
+            ..Default::default()
+        };
+        let alt = Modifiers {
+            alt: true,
+            ..Default::default()
+        };
+
+        assert_eq!(scrub_to(&range, &mut drag, 102.0, plain), 50.0);
+        assert!(!drag.is_active());
+        assert_eq!(scrub_to(&range, &mut drag, 110.0, plain), 55.0);
+        assert_eq!(scrub_to(&range, &mut drag, 112.0, shift), 65.0);
+        assert_eq!(scrub_to(&range, &mut drag, 114.0, alt), 65.1);
+        assert_eq!(scrub_to(&range, &mut drag, 116.0, plain), 66.0);
+        assert_eq!(scrub_to(&range, &mut drag, 200.0, shift), 100.0);
+        assert_eq!(scrub_to(&range, &mut drag, 198.0, plain), 99.0);
+    }
+}
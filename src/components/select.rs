@@ -3,6 +3,8 @@
 use crate::components::icon::Icon;
 use crate::components::icon_source::IconSource;
 use crate::components::scrollable::scrollable_vertical;
+use crate::components::type_ahead::{TypeAhead, TypeAheadAction};
+use crate::overlays::dismiss::dismiss_layer;
 use crate::theme::use_theme;
 use gpui::{prelude::*, *};
 
@@ -56,6 +58,7 @@ pub struct Select<T: Clone + 'static> {
     clearable: bool,
     loading: bool,
     search_query: String,
+    type_ahead: TypeAhead,
     on_change: Option<Box<dyn Fn(&T, &mut Window, &mut App) + Send + Sync + 'static>>,
     bounds: Bounds<Pixels>,
     leading_icon: Option<IconSource>,
@@ -76,6 +79,7 @@ impl<T: Clone + 'static> Select<T> {
             clearable: false,
             loading: false,
             search_query: String::new(),
+            type_ahead: TypeAhead::new(),
             on_change: None,
             bounds: Bounds::default(),
             leading_icon: None,
@@ -132,6 +136,14 @@ impl<T: Clone + 'static> Select<T> {
         self
     }
 
+    /// Replace the options, keeping the selection if it is still in range.
+    pub fn set_options(&mut self, options: Vec<SelectOption<T>>, cx: &mut Context<Self>) {
+        self.options = options;
+        self.selected_index = self.selected_index.filter(|&i| i < self.options.len());
+        self.highlighted_index = self.selected_index;
+        cx.notify();
+    }
+
     pub fn selected_value(&self) -> Option<&T> {
         self.selected_index
             .and_then(|i| self.options.get(i))
@@ -144,11 +156,20 @@ impl<T: Clone + 'static> Select<T> {
             .map(|opt| &opt.label)
     }
 
+    /// The search field's text, or the type-ahead filter's when not searchable.
+    fn query(&self) -> &str {
+        if self.searchable {
+            &self.search_query
+        } else {
+            self.type_ahead.filter().unwrap_or_default()
+        }
+    }
+
     fn filtered_options(&self) -> Vec<(usize, &SelectOption<T>)> {
-        if self.search_query.is_empty() {
+        if self.query().is_empty() {
             self.options.iter().enumerate().collect()
         } else {
-            let query_lower = self.search_query.to_lowercase();
+            let query_lower = self.query().to_lowercase();
             self.options
                 .iter()
                 .enumerate()
@@ -172,9 +193,36 @@ impl<T: Clone + 'static> Select<T> {
         self.open = false;
         self.highlighted_index = self.selected_index;
         self.search_query.clear();
+        self.type_ahead.reset();
         cx.notify();
     }
 
+    /// Move the highlight by type-ahead, or re-filter through the `/` filter.
+    fn handle_type_ahead(&mut self, keystroke: &Keystroke, cx: &mut Context<Self>) -> bool {
+        let Some(action) = self.type_ahead.handle_key(keystroke) else {
+            return false;
+        };
+        let filtered = self.filtered_options();
+        let next = match action {
+            TypeAheadAction::Jump => {
+                let labels: Vec<&str> =
+                    filtered.iter().map(|(_, opt)| opt.label.as_ref()).collect();
+                let current = self
+                    .highlighted_index
+                    .and_then(|idx| filtered.iter().position(|(orig_idx, _)| *orig_idx == idx));
+                self.type_ahead
+                    .find(&labels, current)
+                    .map(|pos| filtered[pos].0)
+            }
+            TypeAheadAction::FilterChanged => filtered.first().map(|(idx, _)| *idx),
+        };
+        if next.is_some() {
+            self.highlighted_index = next;
+        }
+        cx.notify();
+        true
+    }
+
     fn clear_selection(&mut self, _window: &mut Window, cx: &mut Context<Self>) {
         self.selected_index = None;
         self.highlighted_index = None;
@@ -407,7 +455,8 @@ impl<T: Clone + 'static> Render for Select<T> {
             });
 
         let searchable = self.searchable;
-        let search_query: SharedString = self.search_query.clone().into();
+        let show_search = searchable || self.type_ahead.is_filtering();
+        let search_query: SharedString = self.query().to_string().into();
 
         div()
             .relative()
@@ -436,13 +485,16 @@ impl<T: Clone + 'static> Render for Select<T> {
                     }
                 }))
             })
-            .on_mouse_down_out(cx.listener(|this, _, _, cx| {
-                if this.open {
-                    this.close_dropdown(cx);
-                }
-            }))
+            .when(open && !searchable, |this: Div| {
+                this.on_key_down(cx.listener(|this, event: &KeyDownEvent, _, cx| {
+                    if this.handle_type_ahead(&event.keystroke, cx) {
+                        cx.stop_propagation();
+                    }
+                }))
+            })
             .child(trigger)
             .when(open, |this| {
+                let entity = cx.entity();
                 this.child(
                     deferred(
                         anchored()
@@ -451,6 +503,16 @@ impl<T: Clone + 'static> Render for Select<T> {
                                 div()
                                     .occlude()
                                     .w(bounds.size.width)
+                                    .child(
+                                        dismiss_layer("select-dismiss", move |_, cx| {
+                                            entity.update(cx, |this, cx| {
+                                                if this.open {
+                                                    this.close_dropdown(cx);
+                                                }
+                                            })
+                                        })
+                                        .inside(bounds),
+                                    )
                                     .child(
                                         div()
                                             .occlude()
@@ -465,7 +527,7 @@ impl<T: Clone + 'static> Render for Select<T> {
                                                 div()
                                                     .flex()
                                                     .flex_col()
-                                                    .when(searchable, |this| {
+                                                    .when(show_search, |this| {
                                                         this.child(
                                                             div()
                                                                 .px(px(12.0))
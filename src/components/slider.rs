@@ -2,6 +2,57 @@ use crate::theme::use_theme;
 use gpui::{prelude::*, *};
 use std::rc::Rc;
 
+actions!(
+    slider,
+    [
+        Increment,
+        Decrement,
+        IncrementPage,
+        DecrementPage,
+        MoveToMin,
+        MoveToMax
+    ]
+);
+
+pub(crate) const KEY_CONTEXT: &str = "Slider";
+
+/// Keyboard control for [`Slider`] and
+/// [`RangeSlider`](crate::components::range_slider::RangeSlider): arrows move a step,
+/// Page Up/Down a tenth of the range and Home/End to the ends. Up and right increase on
+/// either axis.
+pub fn init(cx: &mut App) {
+    cx.bind_keys([
+        KeyBinding::new("up", Increment, Some(KEY_CONTEXT)),
+        KeyBinding::new("right", Increment, Some(KEY_CONTEXT)),
+        KeyBinding::new("down", Decrement, Some(KEY_CONTEXT)),
+        KeyBinding::new("left", Decrement, Some(KEY_CONTEXT)),
+        KeyBinding::new("pageup", IncrementPage, Some(KEY_CONTEXT)),
+        KeyBinding::new("pagedown", DecrementPage, Some(KEY_CONTEXT)),
+        KeyBinding::new("home", MoveToMin, Some(KEY_CONTEXT)),
+        KeyBinding::new("end", MoveToMax, Some(KEY_CONTEXT)),
+    ]);
+}
+
+/// How far one arrow key press moves a slider with `step` over `min..max`: a step, or a
+/// hundredth of the range when the slider is continuous.
+pub(crate) fn key_step(min: f32, max: f32, step: f32) -> f32 {
+    if step > 0.0 {
+        step
+    } else {
+        (max - min) / 100.0
+    }
+}
+
+/// Key steps per Page Up/Down, about a tenth of the range.
+pub(crate) fn page_steps(min: f32, max: f32, step: f32) -> f32 {
+    let step = key_step(min, max, step);
+    if step > 0.0 {
+        ((max - min) / step / 10.0).max(1.0).round()
+    } else {
+        1.0
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum SliderSize {
     Sm,
@@ -90,7 +141,12 @@ impl SliderState {
 
     pub fn set_value(&mut self, value: f32, cx: &mut Context<Self>) {
         let clamped = value.clamp(self.min, self.max);
-        let stepped = ((clamped / self.step).round() * self.step).clamp(self.min, self.max);
+        let stepped = if self.step > 0.0 {
+            (self.min + ((clamped - self.min) / self.step).round() * self.step)
+                .clamp(self.min, self.max)
+        } else {
+            clamped
+        };
 
         if (self.value - stepped).abs() > f32::EPSILON {
             self.value = stepped;
@@ -114,6 +170,15 @@ impl SliderState {
         ((self.value - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
     }
 
+    fn nudge(&mut self, steps: f32, cx: &mut Context<Self>) {
+        let step = key_step(self.min, self.max, self.step);
+        self.set_value(self.value + steps * step, cx);
+    }
+
+    fn page(&mut self, pages: f32, cx: &mut Context<Self>) {
+        self.nudge(pages * page_steps(self.min, self.max, self.step), cx);
+    }
+
     fn update_from_position(&mut self, position: Point<Pixels>, cx: &mut Context<Self>) {
         let track_width = self.bounds.size.width;
         if track_width <= px(0.0) {
@@ -215,6 +280,34 @@ impl Styled for Slider {
 }
 
 impl Slider {
+    /// Run `apply` for action `A`, reporting the new value if it changed.
+    fn action_listener<A: Action>(
+        &self,
+        window: &mut Window,
+        apply: fn(&mut SliderState, &mut Context<SliderState>),
+    ) -> impl Fn(&A, &mut Window, &mut App) {
+        let on_change = self.on_change.clone();
+        window.listener_for(&self.state, move |state, _: &A, window, cx| {
+            let before = state.value;
+            apply(state, cx);
+            if state.value != before {
+                if let Some(ref handler) = on_change {
+                    handler(state.value, window, cx);
+                }
+            }
+        })
+    }
+
+    fn key_actions(&self, div: Div, window: &mut Window) -> Div {
+        div.key_context(KEY_CONTEXT)
+            .on_action(self.action_listener::<Increment>(window, |s, cx| s.nudge(1.0, cx)))
+            .on_action(self.action_listener::<Decrement>(window, |s, cx| s.nudge(-1.0, cx)))
+            .on_action(self.action_listener::<IncrementPage>(window, |s, cx| s.page(1.0, cx)))
+            .on_action(self.action_listener::<DecrementPage>(window, |s, cx| s.page(-1.0, cx)))
+            .on_action(self.action_listener::<MoveToMin>(window, |s, cx| s.set_value(s.min, cx)))
+            .on_action(self.action_listener::<MoveToMax>(window, |s, cx| s.set_value(s.max, cx)))
+    }
+
     fn render_horizontal(
         self,
         window: &mut Window,
@@ -250,10 +343,8 @@ impl Slider {
                     .flex()
                     .items_center()
                     .when(!self.disabled, |this| {
-                        this.track_focus(&focus_handle.tab_index(0).tab_stop(true))
-                    })
-                    .when(is_focused && !self.disabled, |this| {
-                        this.shadow(smallvec::smallvec![focus_ring])
+                        let this = this.track_focus(&focus_handle.tab_index(0).tab_stop(true));
+                        self.key_actions(this, window)
                     })
                     .rounded(theme.tokens.radius_md)
                     .child(
@@ -308,12 +399,16 @@ impl Slider {
                                 this.shadow(smallvec::smallvec![theme.tokens.shadow_sm])
                                     .cursor(CursorStyle::PointingHand)
                             })
+                            .when(is_focused && !self.disabled, |this| {
+                                this.shadow(smallvec::smallvec![theme.tokens.shadow_sm, focus_ring])
+                            })
                             .when(!self.disabled, |this| {
                                 this.on_mouse_down(
                                     MouseButton::Left,
                                     window.listener_for(
                                         &state_clone,
                                         move |state, e: &MouseDownEvent, window, cx| {
+                                            window.focus(&state.focus_handle);
                                             state.is_dragging = true;
                                             state.update_from_position(e.position, cx);
 
@@ -422,10 +517,8 @@ impl Slider {
                     .items_center()
                     .justify_center()
                     .when(!self.disabled, |this| {
-                        this.track_focus(&focus_handle.tab_index(0).tab_stop(true))
-                    })
-                    .when(is_focused && !self.disabled, |this| {
-                        this.shadow(smallvec::smallvec![focus_ring])
+                        let this = this.track_focus(&focus_handle.tab_index(0).tab_stop(true));
+                        self.key_actions(this, window)
                     })
                     .rounded(theme.tokens.radius_md)
                     .child(
@@ -480,12 +573,16 @@ impl Slider {
                                 this.shadow(smallvec::smallvec![theme.tokens.shadow_sm])
                                     .cursor(CursorStyle::PointingHand)
                             })
+                            .when(is_focused && !self.disabled, |this| {
+                                this.shadow(smallvec::smallvec![theme.tokens.shadow_sm, focus_ring])
+                            })
                             .when(!self.disabled, |this| {
                                 this.on_mouse_down(
                                     MouseButton::Left,
                                     window.listener_for(
                                         &state_clone,
                                         move |state, e: &MouseDownEvent, window, cx| {
+                                            window.focus(&state.focus_handle);
                                             state.is_dragging = true;
                                             state.update_from_position_vertical(e.position, cx);
 
@@ -0,0 +1,361 @@
+//! Snippet parsing and tabstop tracking for the editor.
+//!
+//! Supports the common subset of the LSP / TextMate snippet syntax:
+//! `$1`, `${1:placeholder}` (placeholders may nest), `${1|one,two|}` (the first
+//! choice is inserted), `$0` for the final cursor position, and `\$`, `\}`, `\\`
+//! escapes. Repeated indices are mirrors: they start with the placeholder text and
+//! are edited together. Anything else after a `$` is inserted literally.
+
+use std::collections::BTreeMap;
+use std::iter::Peekable;
+use std::ops::Range;
+use std::str::Chars;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tabstop {
+    pub index: usize,
+    /// Byte ranges into [`Snippet::text`]; more than one range means mirrors.
+    pub ranges: Vec<Range<usize>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snippet {
+    pub text: String,
+    /// Tabstops in visiting order: `$1`, `$2`, … and `$0` last. There is always a
+    /// final `$0` stop; it defaults to the end of the text.
+    pub tabstops: Vec<Tabstop>,
+}
+
+enum Token {
+    Text(String),
+    Tabstop {
+        index: usize,
+        placeholder: Option<Vec<Token>>,
+    },
+}
+
+impl Snippet {
+    pub fn parse(source: &str) -> Self {
+        let tokens = parse_tokens(&mut source.chars().peekable(), false);
+
+        let mut defaults = BTreeMap::new();
+        collect_defaults(&tokens, &mut defaults);
+
+        let mut text = String::new();
+        let mut stops = Vec::new();
+        render(&tokens, &defaults, &mut text, &mut stops);
+
+        let mut grouped: BTreeMap<usize, Vec<Range<usize>>> = BTreeMap::new();
+        for (index, range) in stops {
+            grouped.entry(index).or_default().push(range);
+        }
+        let final_ranges = grouped
+            .remove(&0)
+            .unwrap_or_else(|| vec![text.len()..text.len()]);
+        let mut tabstops: Vec<Tabstop> = grouped
+            .into_iter()
+            .map(|(index, ranges)| Tabstop { index, ranges })
+            .collect();
+        tabstops.push(Tabstop {
+            index: 0,
+            ranges: final_ranges,
+        });
+
+        Self { text, tabstops }
+    }
+
+    /// Prefix every line after the first with `indent`, so a multi-line snippet keeps
+    /// the indentation of the line it is inserted on.
+    pub fn indented(mut self, indent: &str) -> Self {
+        if indent.is_empty() || !self.text.contains('\n') {
+            return self;
+        }
+        let newlines: Vec<usize> = self.text.match_indices('\n').map(|(i, _)| i).collect();
+        let shift =
+            |offset: usize| offset + indent.len() * newlines.partition_point(|&n| n < offset);
+        for tabstop in &mut self.tabstops {
+            for range in &mut tabstop.ranges {
+                *range = shift(range.start)..shift(range.end);
+            }
+        }
+        self.text = self.text.replace('\n', &format!("\n{}", indent));
+        self
+    }
+}
+
+fn take_number(chars: &mut Peekable<Chars>) -> Option<usize> {
+    let mut digits = String::new();
+    while let Some(c) = chars.peek().copied().filter(char::is_ascii_digit) {
+        digits.push(c);
+        chars.next();
+    }
+    digits.parse().ok()
+}
+
+fn parse_tokens(chars: &mut Peekable<Chars>, nested: bool) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            '}' if nested => break,
+            '\\' => {
+                chars.next();
+                match chars.peek() {
+                    Some(&escaped @ ('$' | '}' | '\\')) => {
+                        text.push(escaped);
+                        chars.next();
+                    }
+                    _ => text.push('\\'),
+                }
+            }
+            '$' => {
+                chars.next();
+                let tabstop = if chars.peek().is_some_and(char::is_ascii_digit) {
+                    take_number(chars).map(|index| Token::Tabstop {
+                        index,
+                        placeholder: None,
+                    })
+                } else if chars.peek() == Some(&'{')
+                    && chars.clone().nth(1).is_some_and(|c| c.is_ascii_digit())
+                {
+                    chars.next();
+                    parse_braced(chars)
+                } else {
+                    None
+                };
+                match tabstop {
+                    Some(tabstop) => {
+                        if !text.is_empty() {
+                            tokens.push(Token::Text(std::mem::take(&mut text)));
+                        }
+                        tokens.push(tabstop);
+                    }
+                    None => text.push('$'),
+                }
+            }
+            _ => {
+                text.push(c);
+                chars.next();
+            }
+        }
+    }
+
+    if !text.is_empty() {
+        tokens.push(Token::Text(text));
+    }
+    tokens
+}
+
+/// Parse the rest of `${N…}` after the opening brace.
+fn parse_braced(chars: &mut Peekable<Chars>) -> Option<Token> {
+    let index = take_number(chars)?;
+    let placeholder = match chars.next() {
+        Some(':') => {
+            let inner = parse_tokens(chars, true);
+            chars.next();
+            Some(inner)
+        }
+        Some('|') => {
+            let mut choices = String::new();
+            for c in chars.by_ref() {
+                if c == '|' {
+                    break;
+                }
+                choices.push(c);
+            }
+            if chars.peek() == Some(&'}') {
+                chars.next();
+            }
+            let first = choices.split(',').next().unwrap_or_default().to_string();
+            Some(vec![Token::Text(first)])
+        }
+        _ => None,
+    };
+    Some(Token::Tabstop { index, placeholder })
+}
+
+/// The plain text of each index's first placeholder, used to fill its mirrors.
+fn collect_defaults(tokens: &[Token], defaults: &mut BTreeMap<usize, String>) {
+    for token in tokens {
+        if let Token::Tabstop {
+            index,
+            placeholder: Some(inner),
+        } = token
+        {
+            if !defaults.contains_key(index) {
+                let mut text = String::new();
+                render(inner, &BTreeMap::new(), &mut text, &mut Vec::new());
+                defaults.insert(*index, text);
+            }
+            collect_defaults(inner, defaults);
+        }
+    }
+}
+
+fn render(
+    tokens: &[Token],
+    defaults: &BTreeMap<usize, String>,
+    out: &mut String,
+    stops: &mut Vec<(usize, Range<usize>)>,
+) {
+    for token in tokens {
+        match token {
+            Token::Text(text) => out.push_str(text),
+            Token::Tabstop { index, placeholder } => {
+                let start = out.len();
+                match placeholder {
+                    Some(inner) => render(inner, defaults, out, stops),
+                    None => out.push_str(defaults.get(index).map_or("", String::as_str)),
+                }
+                stops.push((*index, start..out.len()));
+            }
+        }
+    }
+}
+
+/// Tabstops of an inserted snippet, kept in sync with buffer edits until the final
+/// stop is reached.
+#[derive(Debug, Clone)]
+pub(crate) struct SnippetSession {
+    /// Absolute byte ranges per tabstop, in visiting order.
+    pub tabstops: Vec<Vec<Range<usize>>>,
+    pub active: usize,
+}
+
+impl SnippetSession {
+    pub fn new(snippet: Snippet, base: usize) -> Self {
+        let tabstops = snippet
+            .tabstops
+            .into_iter()
+            .map(|t| {
+                t.ranges
+                    .into_iter()
+                    .map(|r| base + r.start..base + r.end)
+                    .collect()
+            })
+            .collect();
+        Self {
+            tabstops,
+            active: 0,
+        }
+    }
+
+    pub fn is_last(&self, index: usize) -> bool {
+        index + 1 >= self.tabstops.len()
+    }
+
+    /// Whether `offset` lies within the span covered by the snippet's tabstops.
+    pub fn contains(&self, offset: usize) -> bool {
+        let mut ranges = self.tabstops.iter().flatten();
+        let Some(first) = ranges.next() else {
+            return false;
+        };
+        let (start, end) = ranges.fold((first.start, first.end), |(start, end), r| {
+            (start.min(r.start), end.max(r.end))
+        });
+        (start..=end).contains(&offset)
+    }
+
+    /// Text inserted inside (or at the edges of) the active tabstop extends it; other
+    /// tabstops only move.
+    pub fn on_insert(&mut self, offset: usize, len: usize) {
+        for (index, ranges) in self.tabstops.iter_mut().enumerate() {
+            let active = index == self.active;
+            for range in ranges {
+                if offset < range.start || (!active && offset == range.start) {
+                    range.start += len;
+                    range.end += len;
+                } else if offset < range.end || (active && offset == range.end) {
+                    range.end += len;
+                }
+            }
+        }
+    }
+
+    pub fn on_remove(&mut self, start: usize, end: usize) {
+        let map = |x: usize| {
+            if x <= start {
+                x
+            } else if x >= end {
+                x - (end - start)
+            } else {
+                start
+            }
+        };
+        for range in self.tabstops.iter_mut().flatten() {
+            *range = map(range.start)..map(range.end);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranges(snippet: &Snippet) -> Vec<(usize, Vec<Range<usize>>)> {
+        snippet
+            .tabstops
+            .iter()
+            .map(|t| (t.index, t.ranges.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_plain_and_placeholder_tabstops() {
+        let snippet = Snippet::parse("fn ${1:name}($2) {\n    $0\n}");
+        assert_eq!(snippet.text, "fn name() {\n    \n}");
+        assert_eq!(
+            ranges(&snippet),
+            vec![(1, vec![3..7]), (2, vec![8..8]), (0, vec![16..16])]
+        );
+    }
+
+    #[test]
+    fn final_tabstop_defaults_to_end() {
+        let snippet = Snippet::parse("println!(\"$1\");");
+        assert_eq!(snippet.text, "println!(\"\");");
+        assert_eq!(ranges(&snippet), vec![(1, vec![10..10]), (0, vec![13..13])]);
+    }
+
+    #[test]
+    fn mirrors_share_the_placeholder_text() {
+        let snippet = Snippet::parse("<${1:div}></$1>");
+        assert_eq!(snippet.text, "<div></div>");
+        assert_eq!(ranges(&snippet)[0], (1, vec![1..4, 7..10]));
+    }
+
+    #[test]
+    fn nested_choice_and_escapes() {
+        let snippet = Snippet::parse("${1:a ${2:b}} ${3|x,y|} \\$4 $HOME");
+        assert_eq!(snippet.text, "a b x $4 $HOME");
+        assert_eq!(
+            ranges(&snippet),
+            vec![
+                (1, vec![0..3]),
+                (2, vec![2..3]),
+                (3, vec![4..5]),
+                (0, vec![14..14])
+            ]
+        );
+    }
+
+    #[test]
+    fn indented_shifts_following_lines() {
+        let snippet = Snippet::parse("if $1 {\n\t$0\n}").indented("  ");
+        assert_eq!(snippet.text, "if  {\n  \t\n  }");
+        assert_eq!(ranges(&snippet), vec![(1, vec![3..3]), (0, vec![9..9])]);
+    }
+
+    #[test]
+    fn session_grows_active_tabstop_and_moves_others() {
+        let mut session = SnippetSession::new(Snippet::parse("<${1:div}></$1>$2"), 10);
+        // Typing "span" over both mirrors, applied back to front like the editor does.
+        session.on_remove(17, 20);
+        session.on_insert(17, 4);
+        session.on_remove(11, 14);
+        session.on_insert(11, 4);
+        assert_eq!(session.tabstops[0], vec![11..15, 18..22]);
+        assert_eq!(session.tabstops[1], vec![23..23]);
+    }
+}
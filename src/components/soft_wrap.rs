@@ -0,0 +1,93 @@
+//! Soft wrapping of shaped text at word boundaries.
+//!
+//! The text is shaped once and measured with its own glyph positions, so wrapping
+//! needs no second pass through the text system. Lines break after whitespace where
+//! they can and inside a word only when the word is wider than the line.
+
+use std::ops::Range;
+
+/// Split `text` into rows no wider than `width`, given the x offset of each byte index
+/// in its shaped layout. Each row's range includes the whitespace it ends with, which
+/// is allowed to run past `width`. Every row holds at least one character, however
+/// narrow `width` is, and empty text is one empty row.
+pub fn wrap_line(text: &str, width: f32, x_for_index: impl Fn(usize) -> f32) -> Vec<Range<usize>> {
+    let mut rows = Vec::new();
+    let mut start = 0;
+    while x_for_index(text.len()) - x_for_index(start) > width {
+        let fits = |end: usize| x_for_index(end) - x_for_index(start) <= width;
+        let rest = &text[start..];
+
+        // The last break after whitespace whose row, without that whitespace, fits.
+        let mut word_break = None;
+        let mut chars = rest.char_indices().peekable();
+        while let Some((ix, ch)) = chars.next() {
+            let next_is_word = chars.peek().is_some_and(|(_, next)| !next.is_whitespace());
+            if ch.is_whitespace() && next_is_word {
+                let trimmed = start + rest[..ix].trim_end().len();
+                if trimmed > start && !fits(trimmed) {
+                    break;
+                }
+                word_break = Some(start + ix + ch.len_utf8());
+            }
+        }
+
+        let end = word_break.unwrap_or_else(|| {
+            let mut end = start;
+            for (ix, ch) in rest.char_indices() {
+                let next = start + ix + ch.len_utf8();
+                if end > start && !fits(next) {
+                    break;
+                }
+                end = next;
+            }
+            end
+        });
+        rows.push(start..end);
+        start = end;
+    }
+    if start < text.len() || rows.is_empty() {
+        rows.push(start..text.len());
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rows of `text` in a monospace font one unit per byte.
+    fn rows(text: &str, width: f32) -> Vec<&str> {
+        wrap_line(text, width, |ix| ix as f32)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect()
+    }
+
+    #[test]
+    fn breaks_after_whitespace() {
+        assert_eq!(
+            rows("the quick brown fox", 10.0),
+            vec!["the quick ", "brown fox"]
+        );
+        assert_eq!(
+            rows("the quick brown fox", 9.0),
+            vec!["the quick ", "brown fox"]
+        );
+        assert_eq!(
+            rows("the quick brown fox", 8.0),
+            vec!["the ", "quick ", "brown ", "fox"]
+        );
+        assert_eq!(rows("short", 10.0), vec!["short"]);
+        assert_eq!(rows("", 10.0), vec![""]);
+    }
+
+    #[test]
+    fn splits_words_wider_than_the_row() {
+        assert_eq!(
+            rows("abcdefghij xy", 4.0),
+            vec!["abcd", "efgh", "ij ", "xy"]
+        );
+        assert_eq!(rows("abc", 0.0), vec!["a", "b", "c"]);
+        assert_eq!(rows("héllo", 2.0), vec!["h", "é", "ll", "o"]);
+    }
+}
@@ -0,0 +1,285 @@
+//! Spell checking for the prose in source files: comments, strings and Markdown text.
+//!
+//! [`SpellChecker`] holds a word list plus the words a user has added. The editor
+//! scans the visible lines with [`misspelled_words`] and underlines what it finds.
+//! Identifiers that end up in comments (`camelCase`, `snake_case`, `CONSTANTS`) and
+//! URLs or paths are skipped rather than flagged.
+
+use std::collections::HashSet;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// A word list to check against. Cloning is cheap: the list itself is shared and only
+/// the added words are copied.
+#[derive(Clone, Debug, Default)]
+pub struct SpellChecker {
+    dictionary: Arc<HashSet<String>>,
+    added: HashSet<String>,
+}
+
+/// A word not in the dictionary, on one buffer line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Misspelling {
+    pub line: usize,
+    /// Byte columns of the word on its line.
+    pub range: Range<usize>,
+    pub word: String,
+}
+
+impl SpellChecker {
+    pub fn new<S: AsRef<str>>(words: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            dictionary: Arc::new(
+                words
+                    .into_iter()
+                    .map(|word| normalize(word.as_ref()))
+                    .filter(|word| !word.is_empty())
+                    .collect(),
+            ),
+            added: HashSet::new(),
+        }
+    }
+
+    /// Read a word list with one word per line, such as `/usr/share/dict/words` or a
+    /// Hunspell `.dic` file. Affix flags after a `/` are dropped (only the stems are
+    /// used), as are blank lines and `#` comments.
+    pub fn from_word_list(list: &str) -> Self {
+        Self::new(
+            list.lines()
+                .map(|line| line.split('/').next().unwrap_or("").trim())
+                .filter(|word| !word.is_empty() && !word.starts_with('#')),
+        )
+    }
+
+    /// Accept `word` from now on, e.g. from a context menu's "Add to dictionary".
+    pub fn add_word(&mut self, word: &str) {
+        let word = normalize(word);
+        if !word.is_empty() {
+            self.added.insert(word);
+        }
+    }
+
+    pub fn remove_word(&mut self, word: &str) {
+        self.added.remove(&normalize(word));
+    }
+
+    /// The words added with [`Self::add_word`], to persist between sessions.
+    pub fn added_words(&self) -> impl Iterator<Item = &str> {
+        self.added.iter().map(String::as_str)
+    }
+
+    pub fn is_correct(&self, word: &str) -> bool {
+        let word = normalize(word);
+        let known = |word: &str| self.dictionary.contains(word) || self.added.contains(word);
+        known(&word)
+            || word
+                .strip_suffix("'s")
+                .is_some_and(|stem| !stem.is_empty() && known(stem))
+    }
+
+    /// Up to `limit` known words within two edits of `word`, closest first, written in
+    /// the same case as `word`.
+    pub fn suggestions(&self, word: &str, limit: usize) -> Vec<String> {
+        let target: Vec<char> = normalize(word).chars().collect();
+        let mut candidates: Vec<(usize, usize, &str)> = self
+            .dictionary
+            .iter()
+            .chain(self.added.iter())
+            .filter_map(|candidate| {
+                let chars: Vec<char> = candidate.chars().collect();
+                let distance = edit_distance(&target, &chars, 2)?;
+                let length_diff = chars.len().abs_diff(target.len());
+                Some((distance, length_diff, candidate.as_str()))
+            })
+            .filter(|(distance, _, _)| *distance > 0)
+            .collect();
+        candidates.sort_unstable();
+        candidates.dedup_by_key(|(_, _, candidate)| *candidate);
+        candidates
+            .into_iter()
+            .take(limit)
+            .map(|(_, _, candidate)| match_case(candidate, word))
+            .collect()
+    }
+}
+
+/// Byte ranges of the misspelled words in `text`.
+pub fn misspelled_words(text: &str, checker: &SpellChecker) -> Vec<Range<usize>> {
+    let mut misspelled = Vec::new();
+    for chunk in chunks(text) {
+        if !is_prose(&text[chunk.clone()]) {
+            continue;
+        }
+        for word in words(&text[chunk.clone()]) {
+            let word = chunk.start + word.start..chunk.start + word.end;
+            if is_checkable(&text[word.clone()]) && !checker.is_correct(&text[word.clone()]) {
+                misspelled.push(word);
+            }
+        }
+    }
+    misspelled
+}
+
+/// Whitespace-separated runs of `text`.
+fn chunks(text: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    text.split(char::is_whitespace)
+        .filter(|chunk| !chunk.is_empty())
+        .map(move |chunk| {
+            let start = chunk.as_ptr() as usize - text.as_ptr() as usize;
+            start..start + chunk.len()
+        })
+}
+
+/// False for URLs, paths, e-mail addresses and dotted names like `std::io` or
+/// `foo.bar`, which aren't checked.
+fn is_prose(chunk: &str) -> bool {
+    if chunk.contains("://") || chunk.contains("::") || chunk.contains(['@', '/', '\\']) {
+        return false;
+    }
+    let chars: Vec<char> = chunk.chars().collect();
+    !chars.windows(3).any(|window| {
+        window[0].is_alphanumeric() && window[1] == '.' && window[2].is_alphanumeric()
+    })
+}
+
+/// Runs of letters, digits, underscores and inner apostrophes.
+fn words(chunk: &str) -> Vec<Range<usize>> {
+    let is_word_char = |ch: char| ch.is_alphanumeric() || ch == '_' || is_apostrophe(ch);
+    let mut words = Vec::new();
+    let mut start = None;
+    for (ix, ch) in chunk.char_indices().chain(Some((chunk.len(), ' '))) {
+        match (start, is_word_char(ch)) {
+            (None, true) => start = Some(ix),
+            (Some(word_start), false) => {
+                let word = &chunk[word_start..ix];
+                let trimmed = word.trim_matches(is_apostrophe);
+                if !trimmed.is_empty() {
+                    let lead = word.len() - word.trim_start_matches(is_apostrophe).len();
+                    words.push(word_start + lead..word_start + lead + trimmed.len());
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+/// Whether `word` looks like a prose word rather than an identifier or acronym.
+fn is_checkable(word: &str) -> bool {
+    if word.chars().count() < 2
+        || !word
+            .chars()
+            .all(|ch| ch.is_alphabetic() || is_apostrophe(ch))
+    {
+        return false;
+    }
+    // Only the first letter may be upper case: `camelCase` and `HTTP` are skipped.
+    !word.chars().skip(1).any(char::is_uppercase)
+}
+
+fn is_apostrophe(ch: char) -> bool {
+    ch == '\'' || ch == '\u{2019}'
+}
+
+fn normalize(word: &str) -> String {
+    word.trim()
+        .chars()
+        .map(|ch| if is_apostrophe(ch) { '\'' } else { ch })
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// `suggestion` capitalized like `original`.
+fn match_case(suggestion: &str, original: &str) -> String {
+    let mut letters = original.chars().filter(|ch| ch.is_alphabetic());
+    match letters.next() {
+        Some(first) if first.is_uppercase() => {
+            if original.chars().count() > 1 && letters.all(char::is_uppercase) {
+                suggestion.to_uppercase()
+            } else {
+                let mut chars = suggestion.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default()
+            }
+        }
+        _ => suggestion.to_string(),
+    }
+}
+
+/// Edit distance counting adjacent transpositions as one edit, or `None` if it is
+/// over `max`.
+fn edit_distance(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+    let mut before_prev: Vec<usize> = Vec::new();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (prev[j] + 1).min(row[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(before_prev[j - 2] + 1);
+            }
+        }
+        if row.iter().all(|&distance| distance > max) {
+            return None;
+        }
+        before_prev = std::mem::replace(&mut prev, row);
+    }
+    Some(prev[b.len()]).filter(|&distance| distance <= max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checker() -> SpellChecker {
+        SpellChecker::from_word_list(
+            "the\nquick\nbrown\nfox/S\n# comment\ndon't\nreceive\nrecipe\n",
+        )
+    }
+
+    fn flagged<'a>(text: &'a str, checker: &SpellChecker) -> Vec<&'a str> {
+        misspelled_words(text, checker)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect()
+    }
+
+    #[test]
+    fn flags_unknown_words() {
+        let checker = checker();
+        assert_eq!(flagged("The quikc brown fox's", &checker), vec!["quikc"]);
+        assert_eq!(flagged("// don’t 'recieve'", &checker), vec!["recieve"]);
+    }
+
+    #[test]
+    fn skips_identifiers_and_urls() {
+        let checker = checker();
+        let text = "the fooBar HTTP snake_case x2 https://exmple.com std::vec foo.bar a";
+        assert!(flagged(text, &checker).is_empty());
+    }
+
+    #[test]
+    fn added_words_are_accepted() {
+        let mut checker = checker();
+        assert_eq!(flagged("the gpui fox", &checker), vec!["gpui"]);
+        checker.add_word("GPUI");
+        assert!(flagged("the gpui fox", &checker).is_empty());
+        assert_eq!(checker.added_words().collect::<Vec<_>>(), vec!["gpui"]);
+    }
+
+    #[test]
+    fn suggests_close_words_in_matching_case() {
+        let checker = checker();
+        assert_eq!(checker.suggestions("recieve", 3), vec!["receive", "recipe"]);
+        assert_eq!(checker.suggestions("Teh", 1), vec!["The"]);
+        assert_eq!(checker.suggestions("QUIK", 1), vec!["QUICK"]);
+        assert!(checker.suggestions("zzzzzz", 3).is_empty());
+    }
+}
@@ -1,22 +1,43 @@
+//! TagInput component - Free-form tags typed into a field.
+//!
+//! Enter or a comma turns the typed text into a tag chip, and backspace in an empty
+//! field removes the last tag. Pasting comma-separated text adds each part. Tags go
+//! through the validator set with [`TagInputState::set_validator`], which can rewrite
+//! or reject them, and duplicates are refused, ignoring case if asked. The reason the
+//! last tag was refused is shown under the field.
+//!
+//! Suggestions matching the typed text are offered in a dropdown: up and down pick
+//! one, enter or a click adds it, escape hides the list.
+
 use crate::theme::use_theme;
 use gpui::{prelude::FluentBuilder as _, *};
 use std::rc::Rc;
 
+const DROPDOWN_MARGIN: Pixels = px(4.0);
+/// Most suggestions shown at once.
+const MAX_SUGGESTIONS: usize = 8;
+
+type TagValidator = Rc<dyn Fn(&str) -> Result<SharedString, SharedString>>;
+
 pub struct TagInputState {
     tags: Vec<SharedString>,
     input_value: String,
     focus_handle: FocusHandle,
     max_tags: Option<usize>,
+    ignore_case: bool,
+    validator: Option<TagValidator>,
+    /// Why the last tag was refused, until the user types again.
+    error: Option<SharedString>,
+    suggestions: Vec<SharedString>,
+    /// Index into `matching_suggestions`.
+    highlighted: Option<usize>,
+    suggestions_dismissed: bool,
+    bounds: Bounds<Pixels>,
 }
 
 impl TagInputState {
     pub fn new(cx: &mut Context<Self>) -> Self {
-        Self {
-            tags: Vec::new(),
-            input_value: String::new(),
-            focus_handle: cx.focus_handle(),
-            max_tags: None,
-        }
+        Self::with_tags(cx, Vec::<SharedString>::new())
     }
 
     pub fn with_tags(cx: &mut Context<Self>, tags: Vec<impl Into<SharedString>>) -> Self {
@@ -25,6 +46,13 @@ impl TagInputState {
             input_value: String::new(),
             focus_handle: cx.focus_handle(),
             max_tags: None,
+            ignore_case: false,
+            validator: None,
+            error: None,
+            suggestions: Vec::new(),
+            highlighted: None,
+            suggestions_dismissed: false,
+            bounds: Bounds::default(),
         }
     }
 
@@ -37,27 +65,64 @@ impl TagInputState {
         cx.notify();
     }
 
+    /// Add `tag` after validation, returning false and recording [`Self::error`] if
+    /// it's refused.
     pub fn add_tag(&mut self, tag: impl Into<SharedString>, cx: &mut Context<Self>) -> bool {
         let tag = tag.into();
-        if tag.is_empty() {
-            return false;
-        }
-        if self.tags.iter().any(|t| t.as_ref() == tag.as_ref()) {
-            return false;
+        let added = match self.check_tag(tag.trim()) {
+            Ok(tag) => {
+                self.tags.push(tag);
+                self.error = None;
+                true
+            }
+            Err(error) => {
+                self.error = error;
+                false
+            }
+        };
+        cx.notify();
+        added
+    }
+
+    /// The tag to add for `text`, or why it's refused. Empty text is refused without a
+    /// message.
+    fn check_tag(&self, text: &str) -> Result<SharedString, Option<SharedString>> {
+        if text.is_empty() {
+            return Err(None);
         }
         if let Some(max) = self.max_tags {
             if self.tags.len() >= max {
-                return false;
+                return Err(Some(format!("At most {} tags", max).into()));
             }
         }
-        self.tags.push(tag);
-        cx.notify();
-        true
+        let tag = match &self.validator {
+            Some(validator) => validator(text).map_err(Some)?,
+            None => SharedString::from(text.to_string()),
+        };
+        if tag.is_empty() {
+            return Err(None);
+        }
+        if self.contains(&tag) {
+            return Err(Some(format!("\"{}\" is already added", tag).into()));
+        }
+        Ok(tag)
+    }
+
+    /// Whether `tag` is already added, ignoring case if [`Self::set_ignore_case`] is on.
+    pub fn contains(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| {
+            if self.ignore_case {
+                t.to_lowercase() == tag.to_lowercase()
+            } else {
+                t.as_ref() == tag
+            }
+        })
     }
 
     pub fn remove_tag(&mut self, index: usize, cx: &mut Context<Self>) {
         if index < self.tags.len() {
             self.tags.remove(index);
+            self.error = None;
             cx.notify();
         }
     }
@@ -65,12 +130,14 @@ impl TagInputState {
     pub fn remove_last_tag(&mut self, cx: &mut Context<Self>) {
         if !self.tags.is_empty() {
             self.tags.pop();
+            self.error = None;
             cx.notify();
         }
     }
 
     pub fn clear_tags(&mut self, cx: &mut Context<Self>) {
         self.tags.clear();
+        self.error = None;
         cx.notify();
     }
 
@@ -80,6 +147,7 @@ impl TagInputState {
 
     pub fn set_input_value(&mut self, value: impl Into<String>, cx: &mut Context<Self>) {
         self.input_value = value.into();
+        self.highlighted = None;
         cx.notify();
     }
 
@@ -92,15 +160,180 @@ impl TagInputState {
         cx.notify();
     }
 
+    /// Treat tags differing only in case as duplicates.
+    pub fn set_ignore_case(&mut self, ignore_case: bool, cx: &mut Context<Self>) {
+        self.ignore_case = ignore_case;
+        cx.notify();
+    }
+
+    /// Check each tag before it's added. `validator` gets the trimmed text and returns
+    /// the tag to add, e.g. lowercased, or the message to show instead.
+    pub fn set_validator(
+        &mut self,
+        validator: impl Fn(&str) -> Result<SharedString, SharedString> + 'static,
+    ) {
+        self.validator = Some(Rc::new(validator));
+    }
+
+    /// Why the last tag was refused, if it was.
+    pub fn error(&self) -> Option<&SharedString> {
+        self.error.as_ref()
+    }
+
+    pub fn suggestions(&self) -> &[SharedString] {
+        &self.suggestions
+    }
+
+    pub fn set_suggestions(
+        &mut self,
+        suggestions: Vec<impl Into<SharedString>>,
+        cx: &mut Context<Self>,
+    ) {
+        self.suggestions = suggestions.into_iter().map(|s| s.into()).collect();
+        self.highlighted = None;
+        cx.notify();
+    }
+
+    /// Suggestions containing the typed text and not yet added, those starting with it
+    /// first.
+    pub fn matching_suggestions(&self) -> Vec<SharedString> {
+        let query = self.input_value.trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let mut matches: Vec<(bool, &SharedString)> = self
+            .suggestions
+            .iter()
+            .filter(|suggestion| !self.contains(suggestion))
+            .filter_map(|suggestion| {
+                let lower = suggestion.to_lowercase();
+                lower
+                    .contains(&query)
+                    .then(|| (!lower.starts_with(&query), suggestion))
+            })
+            .collect();
+        matches.sort_by_key(|(later, _)| *later);
+        matches
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, suggestion)| suggestion.clone())
+            .collect()
+    }
+
+    fn suggestions_open(&self) -> bool {
+        !self.suggestions_dismissed && !self.matching_suggestions().is_empty()
+    }
+
+    /// Add the highlighted suggestion, or else the typed text, as a tag.
     pub fn commit_input(&mut self, cx: &mut Context<Self>) -> bool {
-        let value = self.input_value.trim().to_string();
+        let value = match self.highlighted {
+            Some(ix) => self.matching_suggestions().get(ix).cloned(),
+            None => None,
+        }
+        .unwrap_or_else(|| self.input_value.trim().to_string().into());
         if self.add_tag(value, cx) {
             self.input_value.clear();
+            self.highlighted = None;
             true
         } else {
             false
         }
     }
+
+    /// Add each comma- or newline-separated part of `text`, as when it's pasted.
+    /// Returns whether any tag was added.
+    pub fn add_tags_from_text(&mut self, text: &str, cx: &mut Context<Self>) -> bool {
+        let mut added = false;
+        let mut error = None;
+        for part in text.split([',', '\n']) {
+            if self.add_tag(part.to_string(), cx) {
+                added = true;
+            } else if self.error.is_some() {
+                error = self.error.clone();
+            }
+        }
+        self.error = error;
+        added
+    }
+
+    fn move_highlight(&mut self, down: bool) {
+        let count = self.matching_suggestions().len();
+        if count == 0 {
+            return;
+        }
+        self.suggestions_dismissed = false;
+        self.highlighted = Some(match (self.highlighted, down) {
+            (None, true) => 0,
+            (None, false) => count - 1,
+            (Some(ix), true) => (ix + 1) % count,
+            (Some(ix), false) => (ix + count - 1) % count,
+        });
+    }
+
+    /// Handle a key typed into the field. Returns whether the tags changed.
+    fn handle_key(&mut self, event: &KeyDownEvent, cx: &mut Context<Self>) -> bool {
+        let keystroke = &event.keystroke;
+        let mut changed = false;
+        if keystroke.modifiers.control || keystroke.modifiers.platform {
+            if keystroke.key != "v" {
+                return false;
+            }
+            let Some(text) = cx.read_from_clipboard().and_then(|item| item.text()) else {
+                return false;
+            };
+            if text.contains([',', '\n']) {
+                let pending = std::mem::take(&mut self.input_value);
+                changed = self.add_tags_from_text(&format!("{}{}", pending, text), cx);
+            } else {
+                self.input_value.push_str(&text);
+                self.error = None;
+            }
+        } else {
+            match keystroke.key.as_str() {
+                "enter" => {
+                    if self.input_value.trim().is_empty() {
+                        // Leave enter in an empty field to the form, e.g. to submit it.
+                        return false;
+                    }
+                    changed = self.commit_input(cx);
+                }
+                "backspace" => {
+                    if self.input_value.pop().is_none() {
+                        changed = !self.tags.is_empty();
+                        self.remove_last_tag(cx);
+                    }
+                    self.error = None;
+                    self.highlighted = None;
+                }
+                "up" | "down" => {
+                    if self.matching_suggestions().is_empty() {
+                        return false;
+                    }
+                    self.move_highlight(keystroke.key == "down");
+                }
+                "escape" => {
+                    if !self.suggestions_open() {
+                        return false;
+                    }
+                    self.suggestions_dismissed = true;
+                    self.highlighted = None;
+                }
+                _ => match keystroke.key_char.as_deref() {
+                    Some(",") => changed = self.commit_input(cx),
+                    Some(ch) if ch.chars().count() == 1 && !ch.chars().any(char::is_control) => {
+                        self.input_value.push_str(ch);
+                        self.error = None;
+                        self.highlighted = None;
+                        self.suggestions_dismissed = false;
+                    }
+                    _ => return false,
+                },
+            }
+        }
+        cx.stop_propagation();
+        cx.notify();
+        changed
+    }
 }
 
 impl Focusable for TagInputState {
@@ -120,7 +353,7 @@ pub struct TagInput {
     state: Entity<TagInputState>,
     placeholder: SharedString,
     disabled: bool,
-    suggestions: Vec<SharedString>,
+    suggestions: Option<Vec<SharedString>>,
     on_change: Option<Rc<dyn Fn(&[SharedString], &mut Window, &mut App)>>,
     style: StyleRefinement,
 }
@@ -131,7 +364,7 @@ impl TagInput {
             state,
             placeholder: "Add tag...".into(),
             disabled: false,
-            suggestions: Vec::new(),
+            suggestions: None,
             on_change: None,
             style: StyleRefinement::default(),
         }
@@ -147,8 +380,10 @@ impl TagInput {
         self
     }
 
+    /// Completions offered while typing. Replaces those set with
+    /// [`TagInputState::set_suggestions`].
     pub fn suggestions(mut self, suggestions: Vec<impl Into<SharedString>>) -> Self {
-        self.suggestions = suggestions.into_iter().map(|s| s.into()).collect();
+        self.suggestions = Some(suggestions.into_iter().map(|s| s.into()).collect());
         self
     }
 
@@ -171,14 +406,61 @@ impl RenderOnce for TagInput {
     fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
         let theme = use_theme();
         let user_style = self.style;
+        if let Some(suggestions) = self.suggestions {
+            self.state.update(cx, |state, _| {
+                if state.suggestions != suggestions {
+                    state.suggestions = suggestions;
+                    state.highlighted = None;
+                }
+            });
+        }
         let state_data = self.state.read(cx);
         let tags = state_data.tags.clone();
         let input_value = state_data.input_value.clone();
+        let error = state_data.error.clone();
         let focus_handle = state_data.focus_handle(cx);
         let is_focused = focus_handle.is_focused(window);
+        let matches = if is_focused && !self.disabled && state_data.suggestions_open() {
+            state_data.matching_suggestions()
+        } else {
+            Vec::new()
+        };
+        let highlighted = state_data.highlighted;
+        let bounds = state_data.bounds;
         let state = self.state.clone();
 
+        let suggestion_rows: Vec<_> = matches
+            .into_iter()
+            .enumerate()
+            .map(|(ix, suggestion)| {
+                let state = state.clone();
+                let on_change = self.on_change.clone();
+                div()
+                    .id(("tag-input-suggestion", ix))
+                    .px(px(12.0))
+                    .py(px(6.0))
+                    .text_size(px(14.0))
+                    .text_color(theme.tokens.popover_foreground)
+                    .cursor_pointer()
+                    .when(highlighted == Some(ix), |row| row.bg(theme.tokens.accent))
+                    .hover(|style| style.bg(theme.tokens.accent))
+                    .on_mouse_down(MouseButton::Left, move |_, window, cx| {
+                        cx.stop_propagation();
+                        state.update(cx, |s, cx| {
+                            s.highlighted = Some(ix);
+                            if s.commit_input(cx) {
+                                if let Some(ref handler) = on_change {
+                                    handler(&s.tags, window, cx);
+                                }
+                            }
+                        });
+                    })
+                    .child(suggestion)
+            })
+            .collect();
+
         div()
+            .relative()
             .map(|this| {
                 let mut div = this;
                 div.style().refine(&user_style);
@@ -187,6 +469,7 @@ impl RenderOnce for TagInput {
             .child(
                 div()
                     .id("tag-input-container")
+                    .relative()
                     .flex()
                     .flex_wrap()
                     .items_center()
@@ -196,7 +479,9 @@ impl RenderOnce for TagInput {
                     .py(px(6.0))
                     .bg(theme.tokens.background)
                     .border_1()
-                    .border_color(if is_focused {
+                    .border_color(if error.is_some() {
+                        theme.tokens.destructive
+                    } else if is_focused {
                         theme.tokens.ring
                     } else {
                         theme.tokens.input
@@ -204,7 +489,19 @@ impl RenderOnce for TagInput {
                     .rounded(theme.tokens.radius_md)
                     .when(self.disabled, |d| d.opacity(0.5))
                     .when(!self.disabled, |d| {
+                        let state = state.clone();
+                        let on_change = self.on_change.clone();
                         d.track_focus(&focus_handle.tab_index(0).tab_stop(true))
+                            .cursor_text()
+                            .on_key_down(move |event, window, cx| {
+                                state.update(cx, |s, cx| {
+                                    if s.handle_key(event, cx) {
+                                        if let Some(ref handler) = on_change {
+                                            handler(&s.tags, window, cx);
+                                        }
+                                    }
+                                });
+                            })
                     })
                     .children(tags.iter().enumerate().map(|(idx, tag)| {
                         let state_for_remove = state.clone();
@@ -245,67 +542,76 @@ impl RenderOnce for TagInput {
                                 )
                             })
                     }))
-                    .when(!self.disabled, {
-                        let state_for_input = state.clone();
-                        let on_change = self.on_change.clone();
-                        let placeholder = self.placeholder.clone();
-
-                        move |container| {
-                            container.child(
-                                div().flex_1().min_w(px(80.0)).child(
-                                    div()
-                                        .id("tag-input-field")
-                                        .min_w(px(60.0))
-                                        .text_size(px(14.0))
-                                        .text_color(if input_value.is_empty() {
-                                            theme.tokens.muted_foreground
-                                        } else {
-                                            theme.tokens.foreground
-                                        })
-                                        .font_family(theme.tokens.font_family.clone())
-                                        .on_key_down({
-                                            let state = state_for_input.clone();
-                                            let on_change = on_change.clone();
-                                            move |event, window, cx| match event
-                                                .keystroke
-                                                .key
-                                                .as_str()
-                                            {
-                                                "enter" => {
-                                                    state.update(cx, |s, cx| {
-                                                        if s.commit_input(cx) {
-                                                            if let Some(ref handler) = on_change {
-                                                                handler(&s.tags, window, cx);
-                                                            }
-                                                        }
-                                                    });
-                                                    cx.stop_propagation();
-                                                }
-                                                "backspace" => {
-                                                    state.update(cx, |s, cx| {
-                                                        if s.input_value.is_empty()
-                                                            && !s.tags.is_empty()
-                                                        {
-                                                            s.remove_last_tag(cx);
-                                                            if let Some(ref handler) = on_change {
-                                                                handler(&s.tags, window, cx);
-                                                            }
-                                                            cx.stop_propagation();
-                                                        }
-                                                    });
-                                                }
-                                                _ => {}
-                                            }
-                                        })
-                                        .child(if input_value.is_empty() {
-                                            placeholder.to_string()
-                                        } else {
-                                            input_value
-                                        }),
-                                ),
-                            )
-                        }
+                    .when(!self.disabled, |container| {
+                        container.child(
+                            div()
+                                .id("tag-input-field")
+                                .flex_1()
+                                .flex()
+                                .items_center()
+                                .min_w(px(80.0))
+                                .text_size(px(14.0))
+                                .font_family(theme.tokens.font_family.clone())
+                                .when(!input_value.is_empty(), |field| {
+                                    field
+                                        .text_color(theme.tokens.foreground)
+                                        .child(input_value.clone())
+                                })
+                                .when(is_focused, |field| {
+                                    field.child(
+                                        div().w(px(1.0)).h(px(16.0)).bg(theme.tokens.foreground),
+                                    )
+                                })
+                                .when(input_value.is_empty(), |field| {
+                                    field
+                                        .text_color(theme.tokens.muted_foreground)
+                                        .child(self.placeholder.clone())
+                                }),
+                        )
+                    })
+                    .child({
+                        let state = state.clone();
+                        canvas(
+                            move |bounds, _, cx| state.update(cx, |s, _| s.bounds = bounds),
+                            |_, _, _, _| {},
+                        )
+                        .absolute()
+                        .size_full()
                     }),
             )
+            .when_some(error, |this, error| {
+                this.child(
+                    div()
+                        .mt(px(4.0))
+                        .text_size(px(12.0))
+                        .text_color(theme.tokens.destructive)
+                        .font_family(theme.tokens.font_family.clone())
+                        .child(error),
+                )
+            })
+            .when(!suggestion_rows.is_empty(), |this| {
+                this.child(
+                    deferred(
+                        anchored()
+                            .position(bounds.bottom_left())
+                            .snap_to_window_with_margin(Edges::all(DROPDOWN_MARGIN))
+                            .child(
+                                div()
+                                    .occlude()
+                                    .mt(DROPDOWN_MARGIN)
+                                    .w(bounds.size.width)
+                                    .py(px(4.0))
+                                    .bg(theme.tokens.popover)
+                                    .border_1()
+                                    .border_color(theme.tokens.border)
+                                    .rounded(theme.tokens.radius_md)
+                                    .shadow_lg()
+                                    .font_family(theme.tokens.font_family.clone())
+                                    .children(suggestion_rows),
+                            ),
+                    )
+                    .with_priority(1),
+                )
+            })
     }
 }
@@ -0,0 +1,576 @@
+//! Line and word diffs laid out for side-by-side display.
+//!
+//! `diff_texts` compares two texts line by line (Myers' algorithm) and pairs the
+//! result into rows: unchanged lines sit next to each other, a removed block sits next
+//! to the added block that replaced it, and whatever one side has left over faces an
+//! empty filler. Paired lines also carry word-level ranges of what changed within them.
+//! `parse_unified_diff` produces the same rows from a patch, and `fold_rows` collapses
+//! long unchanged runs down to a few lines of context.
+
+use std::collections::HashSet;
+use std::ops::Range;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffRowKind {
+    Unchanged,
+    Removed,
+    Added,
+    /// A removed line paired with the added line that replaced it.
+    Changed,
+    /// Lines a unified diff left out between hunks.
+    Skipped(usize),
+}
+
+/// One side of a row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffSide {
+    /// 1-based line number in its text.
+    pub line: usize,
+    pub text: String,
+    /// Byte ranges of `text` that differ from the other side, for changed rows.
+    pub changes: Vec<Range<usize>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffRow {
+    pub kind: DiffRowKind,
+    /// `None` for filler opposite an added line.
+    pub old: Option<DiffSide>,
+    /// `None` for filler opposite a removed line.
+    pub new: Option<DiffSide>,
+}
+
+/// A visible entry after folding.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiffItem {
+    Row(usize),
+    /// Hidden unchanged rows; expanding it shows them all.
+    Fold(Range<usize>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Edit {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Rows for a side-by-side diff of `old` and `new`.
+pub fn diff_texts(old: &str, new: &str) -> Vec<DiffRow> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let mut builder = RowBuilder::new(1, 1);
+    let (mut o, mut n) = (0, 0);
+    for edit in diff_slices(&old_lines, &new_lines) {
+        match edit {
+            Edit::Equal => {
+                builder.unchanged(old_lines[o], new_lines[n]);
+                o += 1;
+                n += 1;
+            }
+            Edit::Delete => {
+                builder.removed(old_lines[o]);
+                o += 1;
+            }
+            Edit::Insert => {
+                builder.added(new_lines[n]);
+                n += 1;
+            }
+        }
+    }
+    builder.finish()
+}
+
+/// Rows for a unified diff, such as `git diff` output. File headers are skipped and
+/// the lines between hunks become `Skipped` rows.
+pub fn parse_unified_diff(diff: &str) -> Vec<DiffRow> {
+    let mut rows = Vec::new();
+    let mut builder: Option<RowBuilder> = None;
+    // Old and new lines the current hunk has yet to list, from its header.
+    let (mut old_left, mut new_left) = (0, 0);
+    // Next old line after the previous hunk, to size the gap before the next one.
+    let mut old_end = 1;
+
+    for line in diff.lines() {
+        if old_left == 0 && new_left == 0 {
+            if let Some(hunk) = builder.take() {
+                old_end = hunk.old_line;
+                rows.extend(hunk.finish());
+            }
+            let Some(header) = parse_hunk_header(line) else {
+                // A new file's header resets the gap count.
+                if line.starts_with("diff ") || line.starts_with("--- ") {
+                    old_end = 1;
+                }
+                continue;
+            };
+            if header.old_start > old_end {
+                rows.push(DiffRow {
+                    kind: DiffRowKind::Skipped(header.old_start - old_end),
+                    old: None,
+                    new: None,
+                });
+            }
+            (old_left, new_left) = (header.old_len, header.new_len);
+            builder = Some(RowBuilder::new(header.old_start, header.new_start));
+            continue;
+        }
+        let Some(hunk) = builder.as_mut() else {
+            continue;
+        };
+        match line.as_bytes().first() {
+            Some(b'-') => {
+                hunk.removed(&line[1..]);
+                old_left = old_left.saturating_sub(1);
+            }
+            Some(b'+') => {
+                hunk.added(&line[1..]);
+                new_left = new_left.saturating_sub(1);
+            }
+            // "\ No newline at end of file".
+            Some(b'\\') => {}
+            // Some tools strip the space from empty context lines.
+            _ => {
+                let text = line.get(1..).unwrap_or("");
+                hunk.unchanged(text, text);
+                old_left = old_left.saturating_sub(1);
+                new_left = new_left.saturating_sub(1);
+            }
+        }
+    }
+    if let Some(hunk) = builder {
+        rows.extend(hunk.finish());
+    }
+    rows
+}
+
+struct HunkHeader {
+    old_start: usize,
+    old_len: usize,
+    new_start: usize,
+    new_len: usize,
+}
+
+/// `@@ -12,7 +12,9 @@`. An empty range's start names the line before it, so it is
+/// bumped to the line the hunk begins at.
+fn parse_hunk_header(line: &str) -> Option<HunkHeader> {
+    let rest = line.strip_prefix("@@ -")?;
+    let (ranges, _) = rest.split_once(" @@")?;
+    let (old, new) = ranges.split_once(" +")?;
+    let range = |range: &str| -> Option<(usize, usize)> {
+        let (start, len) = match range.split_once(',') {
+            Some((start, len)) => (start.parse::<usize>().ok()?, len.parse::<usize>().ok()?),
+            None => (range.parse().ok()?, 1),
+        };
+        Some((if len == 0 { start + 1 } else { start }, len))
+    };
+    let (old_start, old_len) = range(old)?;
+    let (new_start, new_len) = range(new)?;
+    Some(HunkHeader {
+        old_start,
+        old_len,
+        new_start,
+        new_len,
+    })
+}
+
+/// Pairs runs of removed and added lines as they arrive.
+struct RowBuilder {
+    rows: Vec<DiffRow>,
+    removed: Vec<DiffSide>,
+    added: Vec<DiffSide>,
+    old_line: usize,
+    new_line: usize,
+}
+
+impl RowBuilder {
+    fn new(old_line: usize, new_line: usize) -> Self {
+        Self {
+            rows: Vec::new(),
+            removed: Vec::new(),
+            added: Vec::new(),
+            old_line,
+            new_line,
+        }
+    }
+
+    fn side(line: usize, text: &str) -> DiffSide {
+        DiffSide {
+            line,
+            text: text.to_string(),
+            changes: Vec::new(),
+        }
+    }
+
+    fn unchanged(&mut self, old: &str, new: &str) {
+        self.flush();
+        self.rows.push(DiffRow {
+            kind: DiffRowKind::Unchanged,
+            old: Some(Self::side(self.old_line, old)),
+            new: Some(Self::side(self.new_line, new)),
+        });
+        self.old_line += 1;
+        self.new_line += 1;
+    }
+
+    fn removed(&mut self, text: &str) {
+        // A removal after additions starts a new block.
+        if !self.added.is_empty() {
+            self.flush();
+        }
+        self.removed.push(Self::side(self.old_line, text));
+        self.old_line += 1;
+    }
+
+    fn added(&mut self, text: &str) {
+        self.added.push(Self::side(self.new_line, text));
+        self.new_line += 1;
+    }
+
+    fn flush(&mut self) {
+        let mut removed = std::mem::take(&mut self.removed).into_iter();
+        let mut added = std::mem::take(&mut self.added).into_iter();
+        loop {
+            let row = match (removed.next(), added.next()) {
+                (Some(mut old), Some(mut new)) => {
+                    let (old_changes, new_changes) = word_diff(&old.text, &new.text);
+                    old.changes = old_changes;
+                    new.changes = new_changes;
+                    DiffRow {
+                        kind: DiffRowKind::Changed,
+                        old: Some(old),
+                        new: Some(new),
+                    }
+                }
+                (Some(old), None) => DiffRow {
+                    kind: DiffRowKind::Removed,
+                    old: Some(old),
+                    new: None,
+                },
+                (None, Some(new)) => DiffRow {
+                    kind: DiffRowKind::Added,
+                    old: None,
+                    new: Some(new),
+                },
+                (None, None) => break,
+            };
+            self.rows.push(row);
+        }
+    }
+
+    fn finish(mut self) -> Vec<DiffRow> {
+        self.flush();
+        self.rows
+    }
+}
+
+/// Byte ranges that differ between two versions of a line, compared word by word.
+/// Adjacent changed words, and the whitespace between them, merge into one range.
+pub fn word_diff(old: &str, new: &str) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let old_tokens = tokenize(old);
+    let new_tokens = tokenize(new);
+    let old_words: Vec<&str> = old_tokens.iter().map(|r| &old[r.clone()]).collect();
+    let new_words: Vec<&str> = new_tokens.iter().map(|r| &new[r.clone()]).collect();
+
+    let mut old_changes: Vec<Range<usize>> = Vec::new();
+    let mut new_changes: Vec<Range<usize>> = Vec::new();
+    let (mut o, mut n) = (0, 0);
+    for edit in diff_slices(&old_words, &new_words) {
+        let (changes, token) = match edit {
+            Edit::Equal => {
+                // Whitespace between two changes joins them.
+                let gap_o = old_words[o].trim().is_empty();
+                let gap_n = new_words[n].trim().is_empty();
+                if gap_o && gap_n {
+                    extend_if_adjacent(&mut old_changes, &old_tokens[o]);
+                    extend_if_adjacent(&mut new_changes, &new_tokens[n]);
+                }
+                o += 1;
+                n += 1;
+                continue;
+            }
+            Edit::Delete => {
+                o += 1;
+                (&mut old_changes, old_tokens[o - 1].clone())
+            }
+            Edit::Insert => {
+                n += 1;
+                (&mut new_changes, new_tokens[n - 1].clone())
+            }
+        };
+        match changes.last_mut() {
+            Some(last) if last.end == token.start => last.end = token.end,
+            _ => changes.push(token),
+        }
+    }
+    trim_trailing_space(old, &mut old_changes);
+    trim_trailing_space(new, &mut new_changes);
+    (old_changes, new_changes)
+}
+
+fn extend_if_adjacent(changes: &mut [Range<usize>], token: &Range<usize>) {
+    if let Some(last) = changes.last_mut() {
+        if last.end == token.start {
+            last.end = token.end;
+        }
+    }
+}
+
+/// Undo whitespace `extend_if_adjacent` added that no change followed.
+fn trim_trailing_space(text: &str, changes: &mut [Range<usize>]) {
+    for range in changes.iter_mut() {
+        let trimmed = text[range.clone()].trim_end().len();
+        if trimmed > 0 {
+            range.end = range.start + trimmed;
+        }
+    }
+}
+
+/// Words, whitespace runs and single punctuation characters.
+fn tokenize(text: &str) -> Vec<Range<usize>> {
+    #[derive(PartialEq)]
+    enum Class {
+        Word,
+        Space,
+        Other,
+    }
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            Class::Word
+        } else if c.is_whitespace() {
+            Class::Space
+        } else {
+            Class::Other
+        }
+    };
+
+    let mut tokens: Vec<Range<usize>> = Vec::new();
+    let mut current: Option<(Class, usize)> = None;
+    for (ix, c) in text.char_indices() {
+        let next = class(c);
+        match &current {
+            Some((kind, _)) if *kind == next && next != Class::Other => continue,
+            Some((_, start)) => tokens.push(*start..ix),
+            None => {}
+        }
+        current = Some((next, ix));
+    }
+    if let Some((_, start)) = current {
+        tokens.push(start..text.len());
+    }
+    tokens
+}
+
+/// Edit script turning `a` into `b`, after setting aside their common prefix and
+/// suffix so the quadratic part only sees the region that changed.
+fn diff_slices<T: PartialEq>(a: &[T], b: &[T]) -> Vec<Edit> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+
+    let mut edits = vec![Edit::Equal; prefix];
+    edits.extend(myers(
+        &a[prefix..a.len() - suffix],
+        &b[prefix..b.len() - suffix],
+    ));
+    edits.resize(edits.len() + suffix, Edit::Equal);
+    edits
+}
+
+/// Myers' O(ND) shortest edit script, keeping each round's frontier to backtrack.
+fn myers<T: PartialEq>(a: &[T], b: &[T]) -> Vec<Edit> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = n + m;
+    if max == 0 {
+        return Vec::new();
+    }
+    let offset = max + 1;
+    let ix = |k: isize| (k + offset) as usize;
+    let mut v = vec![0isize; 2 * max as usize + 3];
+    let mut trace = Vec::new();
+
+    'search: for d in 0..=max {
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[ix(k - 1)] < v[ix(k + 1)]) {
+                v[ix(k + 1)]
+            } else {
+                v[ix(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[ix(k)] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && v[ix(k - 1)] < v[ix(k + 1)]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = v[ix(prev_k)];
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            edits.push(Edit::Equal);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            edits.push(if x == prev_x {
+                Edit::Insert
+            } else {
+                Edit::Delete
+            });
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    edits.reverse();
+    edits
+}
+
+/// The rows to show with unchanged runs collapsed to `context` lines around each
+/// change. A fold whose first row is in `expanded` stays open; a fold never hides a
+/// single line, since its placeholder would take the same space.
+pub fn fold_rows(rows: &[DiffRow], context: usize, expanded: &HashSet<usize>) -> Vec<DiffItem> {
+    let mut items = Vec::with_capacity(rows.len());
+    let mut ix = 0;
+    while ix < rows.len() {
+        if rows[ix].kind != DiffRowKind::Unchanged {
+            items.push(DiffItem::Row(ix));
+            ix += 1;
+            continue;
+        }
+        let start = ix;
+        while ix < rows.len() && rows[ix].kind == DiffRowKind::Unchanged {
+            ix += 1;
+        }
+        let end = ix;
+        let leading = if start == 0 { 0 } else { context };
+        let trailing = if end == rows.len() { 0 } else { context };
+        let hidden = start + leading..end.saturating_sub(trailing);
+        if hidden.len() < 2 || expanded.contains(&hidden.start) {
+            items.extend((start..end).map(DiffItem::Row));
+        } else {
+            items.extend((start..hidden.start).map(DiffItem::Row));
+            let after = hidden.end;
+            items.push(DiffItem::Fold(hidden));
+            items.extend((after..end).map(DiffItem::Row));
+        }
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(rows: &[DiffRow]) -> Vec<DiffRowKind> {
+        rows.iter().map(|row| row.kind).collect()
+    }
+
+    #[test]
+    fn pairs_changed_lines_and_fills_the_rest() {
+        let old = "a\nb\nc\nd\n";
+        let new = "a\nB\nx\nc\n";
+        let rows = diff_texts(old, new);
+        assert_eq!(
+            kinds(&rows),
+            vec![
+                DiffRowKind::Unchanged,
+                DiffRowKind::Changed,
+                DiffRowKind::Added,
+                DiffRowKind::Unchanged,
+                DiffRowKind::Removed,
+            ]
+        );
+        assert_eq!(rows[2].new.as_ref().map(|s| s.line), Some(3));
+        assert!(rows[2].old.is_none());
+        assert_eq!(rows[4].old.as_ref().map(|s| s.line), Some(4));
+    }
+
+    #[test]
+    fn highlights_changed_words() {
+        let old = "let total = price * count;";
+        let new = "let total = price * quantity + tax;";
+        let (old_changes, new_changes) = word_diff(old, new);
+        assert_eq!(
+            old_changes
+                .iter()
+                .map(|r| &old[r.clone()])
+                .collect::<Vec<_>>(),
+            vec!["count"]
+        );
+        assert_eq!(
+            new_changes
+                .iter()
+                .map(|r| &new[r.clone()])
+                .collect::<Vec<_>>(),
+            vec!["quantity + tax"]
+        );
+    }
+
+    #[test]
+    fn parses_unified_diffs() {
+        let diff = "\
+diff --git a/lib.rs b/lib.rs
+--- a/lib.rs
++++ b/lib.rs
+@@ -3,3 +3,3 @@ fn main() {
+ one
+-two
++TWO
+ three
+@@ -20,2 +20,3 @@
+ nineteen
++inserted
+ twenty
+";
+        let rows = parse_unified_diff(diff);
+        assert_eq!(
+            kinds(&rows),
+            vec![
+                DiffRowKind::Skipped(2),
+                DiffRowKind::Unchanged,
+                DiffRowKind::Changed,
+                DiffRowKind::Unchanged,
+                DiffRowKind::Skipped(14),
+                DiffRowKind::Unchanged,
+                DiffRowKind::Added,
+                DiffRowKind::Unchanged,
+            ]
+        );
+        assert_eq!(rows[6].new.as_ref().map(|s| s.line), Some(21));
+        assert_eq!(rows[7].old.as_ref().map(|s| s.line), Some(21));
+    }
+
+    #[test]
+    fn folds_long_unchanged_runs() {
+        let old: String = (0..20).map(|i| format!("{}\n", i)).collect();
+        let new = old.replace("10\n", "ten\n");
+        let rows = diff_texts(&old, &new);
+        let items = fold_rows(&rows, 2, &HashSet::new());
+        assert_eq!(items.first(), Some(&DiffItem::Fold(0..8)));
+        assert_eq!(items.last(), Some(&DiffItem::Fold(13..20)));
+        assert_eq!(items.len(), 7);
+
+        let expanded: HashSet<usize> = [0].into_iter().collect();
+        let items = fold_rows(&rows, 2, &expanded);
+        assert_eq!(items.first(), Some(&DiffItem::Row(0)));
+    }
+}
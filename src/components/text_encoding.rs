@@ -0,0 +1,270 @@
+//! Character encodings for files opened in the editor.
+//!
+//! Detection looks for a byte order mark, then recognizes BOM-less UTF-16 by its
+//! pattern of zero bytes, then accepts valid UTF-8, and otherwise falls back to
+//! Windows-1252, which decodes any byte sequence. Decoding never fails; encoding fails
+//! only when the text has characters the target encoding can't represent.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub enum TextEncoding {
+    #[default]
+    Utf8,
+    /// UTF-8 starting with the `EF BB BF` byte order mark.
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    Windows1252,
+    /// ISO-8859-1: each byte is the code point of the same value.
+    Latin1,
+}
+
+/// Windows-1252 characters for bytes `0x80..=0x9F`. The five unassigned bytes map to
+/// the C1 control of the same value, as browsers do.
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž', '\u{8F}',
+    '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}', 'ž', 'Ÿ',
+];
+
+/// How much of a file is sampled when looking for BOM-less UTF-16.
+const UTF16_SAMPLE: usize = 4096;
+
+/// A character the target encoding has no representation for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncodeError {
+    pub character: char,
+    /// Byte offset of the character in the text being encoded.
+    pub offset: usize,
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "'{}' (U+{:04X}) at byte {} can't be encoded",
+            self.character, self.character as u32, self.offset
+        )
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+impl TextEncoding {
+    pub const ALL: [TextEncoding; 6] = [
+        TextEncoding::Utf8,
+        TextEncoding::Utf8Bom,
+        TextEncoding::Utf16Le,
+        TextEncoding::Utf16Be,
+        TextEncoding::Windows1252,
+        TextEncoding::Latin1,
+    ];
+
+    /// Guess the encoding of `bytes`.
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
+            return TextEncoding::Utf8Bom;
+        }
+        if bytes.starts_with(&[0xFF, 0xFE]) {
+            return TextEncoding::Utf16Le;
+        }
+        if bytes.starts_with(&[0xFE, 0xFF]) {
+            return TextEncoding::Utf16Be;
+        }
+        if let Some(encoding) = detect_utf16_without_bom(bytes) {
+            return encoding;
+        }
+        if std::str::from_utf8(bytes).is_ok() {
+            return TextEncoding::Utf8;
+        }
+        TextEncoding::Windows1252
+    }
+
+    /// Short name for status bars.
+    pub fn label(&self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Utf8Bom => "UTF-8 with BOM",
+            TextEncoding::Utf16Le => "UTF-16 LE",
+            TextEncoding::Utf16Be => "UTF-16 BE",
+            TextEncoding::Windows1252 => "Windows 1252",
+            TextEncoding::Latin1 => "ISO 8859-1",
+        }
+    }
+
+    /// Byte order mark written at the start of a file, empty if the encoding has none.
+    pub fn bom(&self) -> &'static [u8] {
+        match self {
+            TextEncoding::Utf8Bom => &[0xEF, 0xBB, 0xBF],
+            TextEncoding::Utf16Le => &[0xFF, 0xFE],
+            TextEncoding::Utf16Be => &[0xFE, 0xFF],
+            _ => &[],
+        }
+    }
+
+    /// Decode `bytes`, skipping a byte order mark. Invalid sequences become U+FFFD.
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        let bytes = bytes.strip_prefix(self.bom()).unwrap_or(bytes);
+        match self {
+            TextEncoding::Utf8 | TextEncoding::Utf8Bom => {
+                let bytes = bytes
+                    .strip_prefix(TextEncoding::Utf8Bom.bom())
+                    .unwrap_or(bytes);
+                String::from_utf8_lossy(bytes).into_owned()
+            }
+            TextEncoding::Utf16Le | TextEncoding::Utf16Be => {
+                let little_endian = *self == TextEncoding::Utf16Le;
+                let units = bytes.chunks(2).map(|pair| match pair {
+                    [a, b] if little_endian => u16::from_le_bytes([*a, *b]),
+                    [a, b] => u16::from_be_bytes([*a, *b]),
+                    _ => 0xFFFD,
+                });
+                char::decode_utf16(units)
+                    .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                    .collect()
+            }
+            TextEncoding::Windows1252 => bytes
+                .iter()
+                .map(|&byte| match byte {
+                    0x80..=0x9F => WINDOWS_1252_HIGH[(byte - 0x80) as usize],
+                    _ => byte as char,
+                })
+                .collect(),
+            TextEncoding::Latin1 => bytes.iter().map(|&byte| byte as char).collect(),
+        }
+    }
+
+    /// Encode `text` without a byte order mark; see [`TextEncoding::encode_with_bom`].
+    pub fn encode(&self, text: &str) -> Result<Vec<u8>, EncodeError> {
+        let mut bytes = Vec::with_capacity(text.len());
+        self.encode_into(text, &mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Encode `text`, starting with the byte order mark if the encoding has one.
+    pub fn encode_with_bom(&self, text: &str) -> Result<Vec<u8>, EncodeError> {
+        let mut bytes = self.bom().to_vec();
+        self.encode_into(text, &mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Append the encoding of `text` to `out`, for writing a document in chunks.
+    pub fn encode_into(&self, text: &str, out: &mut Vec<u8>) -> Result<(), EncodeError> {
+        match self {
+            TextEncoding::Utf8 | TextEncoding::Utf8Bom => out.extend_from_slice(text.as_bytes()),
+            TextEncoding::Utf16Le => {
+                out.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+            }
+            TextEncoding::Utf16Be => {
+                out.extend(text.encode_utf16().flat_map(u16::to_be_bytes));
+            }
+            TextEncoding::Windows1252 => {
+                for (offset, character) in text.char_indices() {
+                    let byte = match character as u32 {
+                        code @ (0x00..=0x7F | 0xA0..=0xFF) => Some(code as u8),
+                        _ => WINDOWS_1252_HIGH
+                            .iter()
+                            .position(|&c| c == character)
+                            .map(|index| 0x80 + index as u8),
+                    };
+                    out.push(byte.ok_or(EncodeError { character, offset })?);
+                }
+            }
+            TextEncoding::Latin1 => {
+                for (offset, character) in text.char_indices() {
+                    let byte = u8::try_from(character as u32)
+                        .map_err(|_| EncodeError { character, offset })?;
+                    out.push(byte);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for TextEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// Text that is mostly ASCII has a zero in every other byte when stored as UTF-16.
+fn detect_utf16_without_bom(bytes: &[u8]) -> Option<TextEncoding> {
+    let sample = &bytes[..bytes.len().min(UTF16_SAMPLE) & !1];
+    let pairs = sample.len() / 2;
+    if pairs < 2 {
+        return None;
+    }
+    let (mut even_zeros, mut odd_zeros) = (0, 0);
+    for pair in sample.chunks_exact(2) {
+        even_zeros += (pair[0] == 0) as usize;
+        odd_zeros += (pair[1] == 0) as usize;
+    }
+    let mostly = |count: usize| count * 10 >= pairs * 7;
+    let rarely = |count: usize| count * 10 <= pairs;
+    if mostly(odd_zeros) && rarely(even_zeros) {
+        Some(TextEncoding::Utf16Le)
+    } else if mostly(even_zeros) && rarely(odd_zeros) {
+        Some(TextEncoding::Utf16Be)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_boms_and_utf8() {
+        assert_eq!(TextEncoding::detect(b"plain"), TextEncoding::Utf8);
+        assert_eq!(TextEncoding::detect(b""), TextEncoding::Utf8);
+        assert_eq!(
+            TextEncoding::detect(b"\xEF\xBB\xBFhi"),
+            TextEncoding::Utf8Bom
+        );
+        assert_eq!(TextEncoding::detect(b"\xFF\xFEh\0"), TextEncoding::Utf16Le);
+        assert_eq!(TextEncoding::detect(b"\xFE\xFF\0h"), TextEncoding::Utf16Be);
+        assert_eq!(
+            TextEncoding::detect("caf\u{e9}".as_bytes()),
+            TextEncoding::Utf8
+        );
+        assert_eq!(TextEncoding::detect(b"caf\xE9"), TextEncoding::Windows1252);
+    }
+
+    #[test]
+    fn detects_utf16_without_bom() {
+        let le = TextEncoding::Utf16Le.encode("hello world").unwrap();
+        let be = TextEncoding::Utf16Be.encode("hello world").unwrap();
+        assert_eq!(TextEncoding::detect(&le), TextEncoding::Utf16Le);
+        assert_eq!(TextEncoding::detect(&be), TextEncoding::Utf16Be);
+    }
+
+    #[test]
+    fn round_trips() {
+        let text = "naïve “quotes” – €5\n";
+        for encoding in TextEncoding::ALL {
+            if encoding == TextEncoding::Latin1 {
+                continue;
+            }
+            let bytes = encoding.encode_with_bom(text).unwrap();
+            assert_eq!(TextEncoding::detect(&bytes), encoding, "{}", encoding);
+            assert_eq!(encoding.decode(&bytes), text, "{}", encoding);
+        }
+        let latin1 = TextEncoding::Latin1.encode("café").unwrap();
+        assert_eq!(latin1, b"caf\xE9");
+        assert_eq!(TextEncoding::Latin1.decode(&latin1), "café");
+    }
+
+    #[test]
+    fn reports_unencodable_characters() {
+        assert_eq!(
+            TextEncoding::Windows1252.encode("ok 😀"),
+            Err(EncodeError {
+                character: '😀',
+                offset: 3
+            })
+        );
+        assert!(TextEncoding::Latin1.encode("€").is_err());
+    }
+}
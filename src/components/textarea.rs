@@ -1,9 +1,978 @@
 //! Textarea component - Multi-line text input component.
+//!
+//! Without a state the textarea only displays its `value`. Given a [`TextareaState`]
+//! it is editable: text soft-wraps at the textarea's width and, with `auto_grow`, the
+//! box grows with the wrapped rows between `min_rows` and `max_rows`, then scrolls.
+//!
+//! ```rust,ignore
+//! let message = cx.new(|cx| TextareaState::new(cx).placeholder("Write a message"));
+//!
+//! Textarea::new("message")
+//!     .state(&message)
+//!     .auto_grow(true)
+//!     .min_rows(1)
+//!     .max_rows(8)
+//!     .on_submit(|text, _, cx| send(text, cx))
+//! ```
+//!
+//! Enter inserts a newline and cmd-enter (ctrl-enter) submits, or with
+//! [`TextareaState::submit_on_enter`] enter submits and shift-enter inserts a newline.
 
 use crate::components::input::InputVariant;
+use crate::components::input_history::{InputHistory, InputSnapshot};
+use crate::components::soft_wrap::wrap_line;
 use crate::theme::use_theme;
 use gpui::{prelude::FluentBuilder as _, *};
+use std::ops::Range;
 use std::rc::Rc;
+use unicode_segmentation::UnicodeSegmentation;
+
+const LINE_HEIGHT: f32 = 20.0;
+const PADDING_Y: f32 = 8.0;
+
+actions!(
+    textarea,
+    [
+        Backspace,
+        Delete,
+        Left,
+        Right,
+        Up,
+        Down,
+        SelectLeft,
+        SelectRight,
+        SelectUp,
+        SelectDown,
+        SelectAll,
+        Home,
+        End,
+        Enter,
+        Newline,
+        Submit,
+        Copy,
+        Cut,
+        Paste,
+        Undo,
+        Redo,
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    cx.bind_keys([
+        KeyBinding::new("backspace", Backspace, Some("Textarea")),
+        KeyBinding::new("delete", Delete, Some("Textarea")),
+        KeyBinding::new("left", Left, Some("Textarea")),
+        KeyBinding::new("right", Right, Some("Textarea")),
+        KeyBinding::new("up", Up, Some("Textarea")),
+        KeyBinding::new("down", Down, Some("Textarea")),
+        KeyBinding::new("shift-left", SelectLeft, Some("Textarea")),
+        KeyBinding::new("shift-right", SelectRight, Some("Textarea")),
+        KeyBinding::new("shift-up", SelectUp, Some("Textarea")),
+        KeyBinding::new("shift-down", SelectDown, Some("Textarea")),
+        KeyBinding::new("home", Home, Some("Textarea")),
+        KeyBinding::new("end", End, Some("Textarea")),
+        KeyBinding::new("enter", Enter, Some("Textarea")),
+        KeyBinding::new("shift-enter", Newline, Some("Textarea")),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-enter", Submit, Some("Textarea")),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-enter", Submit, Some("Textarea")),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-a", SelectAll, Some("Textarea")),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-a", SelectAll, Some("Textarea")),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-c", Copy, Some("Textarea")),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-c", Copy, Some("Textarea")),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-x", Cut, Some("Textarea")),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-x", Cut, Some("Textarea")),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-v", Paste, Some("Textarea")),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-v", Paste, Some("Textarea")),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-z", Undo, Some("Textarea")),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-z", Undo, Some("Textarea")),
+        #[cfg(target_os = "macos")]
+        KeyBinding::new("cmd-shift-z", Redo, Some("Textarea")),
+        #[cfg(not(target_os = "macos"))]
+        KeyBinding::new("ctrl-shift-z", Redo, Some("Textarea")),
+    ]);
+}
+
+#[derive(Clone, Debug)]
+pub enum TextareaEvent {
+    Change,
+    Submit,
+}
+
+type TextHandler = Rc<dyn Fn(SharedString, &mut Window, &mut App)>;
+
+/// One row of wrapped text as last painted.
+struct VisualRow {
+    /// Byte range in the content, without the newline ending a hard line.
+    range: Range<usize>,
+    line: ShapedLine,
+    /// Whether the next row continues the same hard line.
+    wrapped: bool,
+}
+
+/// Editable multi-line text for a [`Textarea`].
+pub struct TextareaState {
+    focus_handle: FocusHandle,
+    content: String,
+    placeholder: SharedString,
+    pub submit_on_enter: bool,
+    disabled: bool,
+    selected_range: Range<usize>,
+    selection_reversed: bool,
+    marked_range: Option<Range<usize>>,
+    history: InputHistory,
+    rows: Vec<VisualRow>,
+    last_bounds: Option<Bounds<Pixels>>,
+    scroll_top: Pixels,
+    reveal_cursor: bool,
+    /// x to return to when moving up and down through shorter rows.
+    goal_x: Option<Pixels>,
+    is_selecting: bool,
+    on_change: Option<TextHandler>,
+    on_submit: Option<TextHandler>,
+}
+
+impl EventEmitter<TextareaEvent> for TextareaState {}
+
+impl TextareaState {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        Self {
+            focus_handle: cx.focus_handle(),
+            content: String::new(),
+            placeholder: "".into(),
+            submit_on_enter: false,
+            disabled: false,
+            selected_range: 0..0,
+            selection_reversed: false,
+            marked_range: None,
+            history: InputHistory::default(),
+            rows: Vec::new(),
+            last_bounds: None,
+            scroll_top: px(0.0),
+            reveal_cursor: false,
+            goal_x: None,
+            is_selecting: false,
+            on_change: None,
+            on_submit: None,
+        }
+    }
+
+    pub fn placeholder(mut self, placeholder: impl Into<SharedString>) -> Self {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    /// Submit on enter and insert newlines with shift-enter, as chat inputs do.
+    pub fn submit_on_enter(mut self, submit_on_enter: bool) -> Self {
+        self.submit_on_enter = submit_on_enter;
+        self
+    }
+
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// Replace the text, as one undo step, with the cursor at the end.
+    pub fn set_value(&mut self, value: impl Into<String>, cx: &mut Context<Self>) {
+        let value = value.into();
+        if value == self.content {
+            return;
+        }
+        self.history.checkpoint(self.snapshot());
+        self.content = value;
+        let len = self.content.len();
+        self.selected_range = len..len;
+        self.marked_range = None;
+        self.reveal_cursor = true;
+        cx.emit(TextareaEvent::Change);
+        cx.notify();
+    }
+
+    /// Rows the text took when last laid out, counting wrapped rows; at least one.
+    pub fn visual_row_count(&self) -> usize {
+        self.rows.len().max(1)
+    }
+
+    fn cursor_offset(&self) -> usize {
+        if self.selection_reversed {
+            self.selected_range.start
+        } else {
+            self.selected_range.end
+        }
+    }
+
+    fn snapshot(&self) -> InputSnapshot {
+        InputSnapshot {
+            text: self.content.clone(),
+            selection: self.selected_range.clone(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: InputSnapshot, window: &mut Window, cx: &mut Context<Self>) {
+        self.content = snapshot.text;
+        self.selected_range = snapshot.selection;
+        self.selection_reversed = false;
+        self.marked_range = None;
+        self.changed(window, cx);
+    }
+
+    /// Replace `range` with `text` as an undoable edit, leaving the cursor after it.
+    fn replace(
+        &mut self,
+        range: Range<usize>,
+        text: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let before = self.snapshot();
+        self.content.replace_range(range.clone(), text);
+        let cursor = range.start + text.len();
+        self.selected_range = cursor..cursor;
+        self.selection_reversed = false;
+        self.history
+            .record(before, range, text, std::time::Instant::now());
+        self.changed(window, cx);
+    }
+
+    fn changed(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.goal_x = None;
+        self.reveal_cursor = true;
+        cx.emit(TextareaEvent::Change);
+        if let Some(on_change) = self.on_change.clone() {
+            let value: SharedString = self.content.clone().into();
+            window.defer(cx, move |window, cx| on_change(value, window, cx));
+        }
+        cx.notify();
+    }
+
+    fn move_to(&mut self, offset: usize, cx: &mut Context<Self>) {
+        self.selected_range = offset..offset;
+        self.selection_reversed = false;
+        self.reveal_cursor = true;
+        cx.notify();
+    }
+
+    fn select_to(&mut self, offset: usize, cx: &mut Context<Self>) {
+        if self.selection_reversed {
+            self.selected_range.start = offset
+        } else {
+            self.selected_range.end = offset
+        };
+        if self.selected_range.end < self.selected_range.start {
+            self.selection_reversed = !self.selection_reversed;
+            self.selected_range = self.selected_range.end..self.selected_range.start;
+        }
+        self.reveal_cursor = true;
+        cx.notify();
+    }
+
+    fn previous_boundary(&self, offset: usize) -> usize {
+        self.content
+            .grapheme_indices(true)
+            .rev()
+            .find_map(|(idx, _)| (idx < offset).then_some(idx))
+            .unwrap_or(0)
+    }
+
+    fn next_boundary(&self, offset: usize) -> usize {
+        self.content
+            .grapheme_indices(true)
+            .find_map(|(idx, _)| (idx > offset).then_some(idx))
+            .unwrap_or(self.content.len())
+    }
+
+    /// The row showing `offset`. At a wrap the offset belongs to the row it starts.
+    fn row_for_offset(&self, offset: usize) -> Option<usize> {
+        self.rows.iter().rposition(|row| row.range.start <= offset)
+    }
+
+    fn x_for_offset(&self, row: usize, offset: usize) -> Pixels {
+        let row = &self.rows[row];
+        row.line
+            .x_for_index(offset.clamp(row.range.start, row.range.end) - row.range.start)
+    }
+
+    /// The offset in `row` closest to `x`, kept off a wrap so it stays on that row.
+    fn offset_in_row(&self, row: usize, x: Pixels) -> usize {
+        let visual = &self.rows[row];
+        let offset = visual.range.start + visual.line.closest_index_for_x(x);
+        if visual.wrapped && offset >= visual.range.end {
+            self.previous_boundary(visual.range.end)
+        } else {
+            offset.min(visual.range.end)
+        }
+    }
+
+    /// The offset one row up or down from the cursor, or the start or end of the text
+    /// past the first or last row.
+    fn vertical_target(&mut self, down: bool) -> usize {
+        let cursor = self.cursor_offset();
+        let Some(row) = self.row_for_offset(cursor) else {
+            return if down { self.content.len() } else { 0 };
+        };
+        let x = *self
+            .goal_x
+            .get_or_insert_with(|| self.x_for_offset(row, cursor));
+        let target = if down {
+            row + 1
+        } else {
+            match row.checked_sub(1) {
+                Some(target) => target,
+                None => return 0,
+            }
+        };
+        if target >= self.rows.len() {
+            return self.content.len();
+        }
+        self.offset_in_row(target, x)
+    }
+
+    fn index_for_position(&self, position: Point<Pixels>) -> usize {
+        let Some(bounds) = self.last_bounds else {
+            return self.content.len();
+        };
+        if self.rows.is_empty() {
+            return 0;
+        }
+        let y = position.y - bounds.top() + self.scroll_top;
+        let row = if y < px(0.0) {
+            0
+        } else {
+            ((y / px(LINE_HEIGHT)) as usize).min(self.rows.len() - 1)
+        };
+        self.offset_in_row(row, position.x - bounds.left())
+    }
+
+    pub fn backspace(&mut self, _: &Backspace, window: &mut Window, cx: &mut Context<Self>) {
+        if self.selected_range.is_empty() {
+            self.select_to(self.previous_boundary(self.cursor_offset()), cx)
+        }
+        if !self.selected_range.is_empty() {
+            self.replace(self.selected_range.clone(), "", window, cx)
+        }
+    }
+
+    pub fn delete(&mut self, _: &Delete, window: &mut Window, cx: &mut Context<Self>) {
+        if self.selected_range.is_empty() {
+            self.select_to(self.next_boundary(self.cursor_offset()), cx)
+        }
+        if !self.selected_range.is_empty() {
+            self.replace(self.selected_range.clone(), "", window, cx)
+        }
+    }
+
+    pub fn left(&mut self, _: &Left, _: &mut Window, cx: &mut Context<Self>) {
+        self.goal_x = None;
+        if self.selected_range.is_empty() {
+            self.move_to(self.previous_boundary(self.cursor_offset()), cx);
+        } else {
+            self.move_to(self.selected_range.start, cx)
+        }
+    }
+
+    pub fn right(&mut self, _: &Right, _: &mut Window, cx: &mut Context<Self>) {
+        self.goal_x = None;
+        if self.selected_range.is_empty() {
+            self.move_to(self.next_boundary(self.selected_range.end), cx);
+        } else {
+            self.move_to(self.selected_range.end, cx)
+        }
+    }
+
+    pub fn up(&mut self, _: &Up, _: &mut Window, cx: &mut Context<Self>) {
+        let target = self.vertical_target(false);
+        self.move_to(target, cx);
+    }
+
+    pub fn down(&mut self, _: &Down, _: &mut Window, cx: &mut Context<Self>) {
+        let target = self.vertical_target(true);
+        self.move_to(target, cx);
+    }
+
+    pub fn select_left(&mut self, _: &SelectLeft, _: &mut Window, cx: &mut Context<Self>) {
+        self.goal_x = None;
+        self.select_to(self.previous_boundary(self.cursor_offset()), cx);
+    }
+
+    pub fn select_right(&mut self, _: &SelectRight, _: &mut Window, cx: &mut Context<Self>) {
+        self.goal_x = None;
+        self.select_to(self.next_boundary(self.cursor_offset()), cx);
+    }
+
+    pub fn select_up(&mut self, _: &SelectUp, _: &mut Window, cx: &mut Context<Self>) {
+        let target = self.vertical_target(false);
+        self.select_to(target, cx);
+    }
+
+    pub fn select_down(&mut self, _: &SelectDown, _: &mut Window, cx: &mut Context<Self>) {
+        let target = self.vertical_target(true);
+        self.select_to(target, cx);
+    }
+
+    pub fn select_all(&mut self, _: &SelectAll, _: &mut Window, cx: &mut Context<Self>) {
+        self.selected_range = 0..self.content.len();
+        self.selection_reversed = false;
+        cx.notify();
+    }
+
+    /// To the start of the cursor's row.
+    pub fn home(&mut self, _: &Home, _: &mut Window, cx: &mut Context<Self>) {
+        self.goal_x = None;
+        let cursor = self.cursor_offset();
+        let start = match self.row_for_offset(cursor) {
+            Some(row) => self.rows[row].range.start,
+            None => self.content[..cursor].rfind('\n').map_or(0, |ix| ix + 1),
+        };
+        self.move_to(start, cx);
+    }
+
+    /// To the end of the cursor's row, before the space a wrapped row ends with.
+    pub fn end(&mut self, _: &End, _: &mut Window, cx: &mut Context<Self>) {
+        self.goal_x = None;
+        let cursor = self.cursor_offset();
+        let end = match self.row_for_offset(cursor) {
+            Some(row) if self.rows[row].wrapped => self.previous_boundary(self.rows[row].range.end),
+            Some(row) => self.rows[row].range.end,
+            None => self.content[cursor..]
+                .find('\n')
+                .map_or(self.content.len(), |ix| cursor + ix),
+        };
+        self.move_to(end, cx);
+    }
+
+    pub fn enter(&mut self, _: &Enter, window: &mut Window, cx: &mut Context<Self>) {
+        if self.submit_on_enter {
+            self.submit(&Submit, window, cx);
+        } else {
+            self.newline(&Newline, window, cx);
+        }
+    }
+
+    pub fn newline(&mut self, _: &Newline, window: &mut Window, cx: &mut Context<Self>) {
+        self.replace(self.selected_range.clone(), "\n", window, cx);
+    }
+
+    pub fn submit(&mut self, _: &Submit, window: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(TextareaEvent::Submit);
+        if let Some(on_submit) = self.on_submit.clone() {
+            let value: SharedString = self.content.clone().into();
+            window.defer(cx, move |window, cx| on_submit(value, window, cx));
+        }
+    }
+
+    pub fn copy(&mut self, _: &Copy, _: &mut Window, cx: &mut Context<Self>) {
+        if !self.selected_range.is_empty() {
+            cx.write_to_clipboard(ClipboardItem::new_string(
+                self.content[self.selected_range.clone()].to_string(),
+            ));
+        }
+    }
+
+    pub fn cut(&mut self, _: &Cut, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.selected_range.is_empty() {
+            cx.write_to_clipboard(ClipboardItem::new_string(
+                self.content[self.selected_range.clone()].to_string(),
+            ));
+            self.replace(self.selected_range.clone(), "", window, cx)
+        }
+    }
+
+    pub fn paste(&mut self, _: &Paste, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(text) = cx.read_from_clipboard().and_then(|item| item.text()) {
+            let text = text.replace("\r\n", "\n");
+            self.replace(self.selected_range.clone(), &text, window, cx);
+        }
+    }
+
+    pub fn undo(&mut self, _: &Undo, window: &mut Window, cx: &mut Context<Self>) {
+        if self.marked_range.is_some() {
+            return;
+        }
+        if let Some(snapshot) = self.history.undo(self.snapshot()) {
+            self.restore(snapshot, window, cx);
+        }
+    }
+
+    pub fn redo(&mut self, _: &Redo, window: &mut Window, cx: &mut Context<Self>) {
+        if self.marked_range.is_some() {
+            return;
+        }
+        if let Some(snapshot) = self.history.redo(self.snapshot()) {
+            self.restore(snapshot, window, cx);
+        }
+    }
+
+    fn on_mouse_down(
+        &mut self,
+        event: &MouseDownEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        window.focus(&self.focus_handle);
+        self.goal_x = None;
+        let offset = self.index_for_position(event.position);
+        if event.click_count >= 2 {
+            self.select_word_at(offset, cx);
+            return;
+        }
+        self.is_selecting = true;
+        if event.modifiers.shift {
+            self.select_to(offset, cx);
+        } else {
+            self.move_to(offset, cx);
+        }
+    }
+
+    fn select_word_at(&mut self, offset: usize, cx: &mut Context<Self>) {
+        let word = self
+            .content
+            .split_word_bound_indices()
+            .find(|(start, word)| *start <= offset && offset < start + word.len());
+        if let Some((start, word)) = word {
+            self.selected_range = start..start + word.len();
+            self.selection_reversed = false;
+            cx.notify();
+        }
+    }
+
+    fn on_mouse_up(&mut self, _: &MouseUpEvent, _: &mut Window, _: &mut Context<Self>) {
+        self.is_selecting = false;
+    }
+
+    fn on_mouse_move(&mut self, event: &MouseMoveEvent, _: &mut Window, cx: &mut Context<Self>) {
+        if self.is_selecting {
+            self.select_to(self.index_for_position(event.position), cx);
+        }
+    }
+
+    fn on_scroll(&mut self, event: &ScrollWheelEvent, window: &mut Window, cx: &mut Context<Self>) {
+        let delta = event.delta.pixel_delta(window.line_height()).y;
+        self.scroll_top = (self.scroll_top - delta).max(px(0.0));
+        cx.notify();
+    }
+
+    fn offset_from_utf16(&self, offset: usize) -> usize {
+        let mut utf8_offset = 0;
+        let mut utf16_count = 0;
+        for ch in self.content.chars() {
+            if utf16_count >= offset {
+                break;
+            }
+            utf16_count += ch.len_utf16();
+            utf8_offset += ch.len_utf8();
+        }
+        utf8_offset
+    }
+
+    fn offset_to_utf16(&self, offset: usize) -> usize {
+        self.content[..offset.min(self.content.len())]
+            .chars()
+            .map(char::len_utf16)
+            .sum()
+    }
+
+    fn range_to_utf16(&self, range: &Range<usize>) -> Range<usize> {
+        self.offset_to_utf16(range.start)..self.offset_to_utf16(range.end)
+    }
+
+    fn range_from_utf16(&self, range_utf16: &Range<usize>) -> Range<usize> {
+        self.offset_from_utf16(range_utf16.start)..self.offset_from_utf16(range_utf16.end)
+    }
+}
+
+impl EntityInputHandler for TextareaState {
+    fn text_for_range(
+        &mut self,
+        range_utf16: Range<usize>,
+        actual_range: &mut Option<Range<usize>>,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) -> Option<String> {
+        let range = self.range_from_utf16(&range_utf16);
+        actual_range.replace(self.range_to_utf16(&range));
+        Some(self.content[range].to_string())
+    }
+
+    fn selected_text_range(
+        &mut self,
+        _ignore_disabled_input: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) -> Option<UTF16Selection> {
+        Some(UTF16Selection {
+            range: self.range_to_utf16(&self.selected_range),
+            reversed: self.selection_reversed,
+        })
+    }
+
+    fn marked_text_range(
+        &self,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) -> Option<Range<usize>> {
+        self.marked_range
+            .as_ref()
+            .map(|range| self.range_to_utf16(range))
+    }
+
+    fn unmark_text(&mut self, _window: &mut Window, cx: &mut Context<Self>) {
+        self.marked_range = None;
+        cx.notify();
+    }
+
+    fn replace_text_in_range(
+        &mut self,
+        range_utf16: Option<Range<usize>>,
+        new_text: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let range = range_utf16
+            .as_ref()
+            .map(|range_utf16| self.range_from_utf16(range_utf16))
+            .or(self.marked_range.clone())
+            .unwrap_or(self.selected_range.clone());
+        let composing = self.marked_range.take().is_some();
+        if composing {
+            // The composition was checkpointed when it started.
+            self.content.replace_range(range.clone(), new_text);
+            let cursor = range.start + new_text.len();
+            self.selected_range = cursor..cursor;
+            self.changed(window, cx);
+        } else {
+            self.replace(range, new_text, window, cx);
+        }
+    }
+
+    fn replace_and_mark_text_in_range(
+        &mut self,
+        range_utf16: Option<Range<usize>>,
+        new_text: &str,
+        new_selected_range_utf16: Option<Range<usize>>,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let range = range_utf16
+            .as_ref()
+            .map(|range_utf16| self.range_from_utf16(range_utf16))
+            .or(self.marked_range.clone())
+            .unwrap_or(self.selected_range.clone());
+        if self.marked_range.is_none() {
+            self.history.checkpoint(self.snapshot());
+        }
+        self.content.replace_range(range.clone(), new_text);
+        self.marked_range =
+            (!new_text.is_empty()).then(|| range.start..range.start + new_text.len());
+        self.selected_range = new_selected_range_utf16
+            .as_ref()
+            .map(|range_utf16| {
+                let start = self.offset_to_utf16(range.start);
+                self.range_from_utf16(&(start + range_utf16.start..start + range_utf16.end))
+            })
+            .unwrap_or_else(|| range.start + new_text.len()..range.start + new_text.len());
+        self.reveal_cursor = true;
+        cx.notify();
+    }
+
+    fn bounds_for_range(
+        &mut self,
+        range_utf16: Range<usize>,
+        bounds: Bounds<Pixels>,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) -> Option<Bounds<Pixels>> {
+        let range = self.range_from_utf16(&range_utf16);
+        let row = self.row_for_offset(range.start)?;
+        let top = bounds.top() + px(LINE_HEIGHT) * row as f32 - self.scroll_top;
+        let end = range.end.min(self.rows[row].range.end);
+        Some(Bounds::from_corners(
+            point(bounds.left() + self.x_for_offset(row, range.start), top),
+            point(
+                bounds.left() + self.x_for_offset(row, end),
+                top + px(LINE_HEIGHT),
+            ),
+        ))
+    }
+
+    fn character_index_for_point(
+        &mut self,
+        point: Point<Pixels>,
+        _window: &mut Window,
+        _cx: &mut Context<Self>,
+    ) -> Option<usize> {
+        self.last_bounds?;
+        Some(self.offset_to_utf16(self.index_for_position(point)))
+    }
+}
+
+/// Paints the wrapped text, selection and cursor of a [`TextareaState`].
+struct TextareaElement {
+    state: Entity<TextareaState>,
+}
+
+struct TextareaPrepaint {
+    rows: Vec<VisualRow>,
+    placeholder: Option<ShapedLine>,
+    selections: Vec<PaintQuad>,
+    cursor: Option<PaintQuad>,
+    scroll_top: Pixels,
+}
+
+impl IntoElement for TextareaElement {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl Element for TextareaElement {
+    type RequestLayoutState = ();
+    type PrepaintState = TextareaPrepaint;
+
+    fn id(&self) -> Option<ElementId> {
+        None
+    }
+
+    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&gpui::InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let mut style = Style::default();
+        style.size.width = relative(1.).into();
+        style.size.height = relative(1.).into();
+        (window.request_layout(style, [], cx), ())
+    }
+
+    fn prepaint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&gpui::InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        _request_layout: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Self::PrepaintState {
+        let state = self.state.read(cx);
+        let style = window.text_style();
+        let font_size = style.font_size.to_pixels(window.rem_size());
+        let line_height = px(LINE_HEIGHT);
+        let run = |len: usize, underline: bool| TextRun {
+            len,
+            font: style.font(),
+            color: style.color,
+            background_color: None,
+            underline: underline.then_some(UnderlineStyle {
+                color: Some(style.color),
+                thickness: px(1.0),
+                wavy: false,
+            }),
+            strikethrough: None,
+        };
+        let shape = |text: &str, runs: &[TextRun], window: &mut Window| {
+            window.text_system().shape_line(
+                SharedString::from(text.to_string()),
+                font_size,
+                runs,
+                None,
+            )
+        };
+
+        let mut rows = Vec::new();
+        let mut line_start = 0;
+        for line_text in state.content.split('\n') {
+            let full = shape(line_text, &[run(line_text.len(), false)], window);
+            let wrapped = wrap_line(line_text, f32::from(bounds.size.width), |ix| {
+                f32::from(full.x_for_index(ix))
+            });
+            let last = wrapped.len() - 1;
+            for (ix, segment) in wrapped.into_iter().enumerate() {
+                let range = line_start + segment.start..line_start + segment.end;
+                let runs = match &state.marked_range {
+                    Some(marked) if marked.start < range.end && range.start < marked.end => {
+                        let start = marked.start.max(range.start) - range.start;
+                        let end = marked.end.min(range.end) - range.start;
+                        [
+                            run(start, false),
+                            run(end - start, true),
+                            run(range.len() - end, false),
+                        ]
+                        .into_iter()
+                        .filter(|run| run.len > 0)
+                        .collect()
+                    }
+                    _ => vec![run(range.len(), false)],
+                };
+                let line = shape(&state.content[range.clone()], &runs, window);
+                rows.push(VisualRow {
+                    range,
+                    line,
+                    wrapped: ix < last,
+                });
+            }
+            line_start += line_text.len() + 1;
+        }
+
+        let placeholder = state.content.is_empty().then(|| {
+            let placeholder = state.placeholder.as_ref();
+            let theme = use_theme();
+            let muted = TextRun {
+                color: theme.tokens.muted_foreground,
+                ..run(placeholder.len(), false)
+            };
+            shape(placeholder, &[muted], window)
+        });
+
+        let row_for = |offset: usize| rows.iter().rposition(|row| row.range.start <= offset);
+        let x_for = |row: &VisualRow, offset: usize| {
+            row.line
+                .x_for_index(offset.clamp(row.range.start, row.range.end) - row.range.start)
+        };
+        let cursor = state.cursor_offset();
+        let cursor_row = row_for(cursor).unwrap_or(0);
+
+        let content_height = line_height * rows.len() as f32;
+        let max_scroll = (content_height - bounds.size.height).max(px(0.0));
+        let mut scroll_top = state.scroll_top.min(max_scroll);
+        if state.reveal_cursor {
+            let cursor_top = line_height * cursor_row as f32;
+            if cursor_top < scroll_top {
+                scroll_top = cursor_top;
+            } else if cursor_top + line_height > scroll_top + bounds.size.height {
+                scroll_top = (cursor_top + line_height - bounds.size.height).max(px(0.0));
+            }
+        }
+        let row_top = |row: usize| bounds.top() + line_height * row as f32 - scroll_top;
+
+        let selection_color = use_theme().tokens.primary.opacity(0.25);
+        let selected = &state.selected_range;
+        let selections = if selected.is_empty() {
+            Vec::new()
+        } else {
+            rows.iter()
+                .enumerate()
+                .filter(|(_, row)| {
+                    selected.start <= row.range.end && row.range.start < selected.end
+                })
+                .map(|(ix, row)| {
+                    let left = x_for(row, selected.start);
+                    let mut right = x_for(row, selected.end);
+                    // Show a selected newline as a sliver past the end of the line.
+                    if !row.wrapped && selected.end > row.range.end {
+                        right += px(4.0);
+                    }
+                    fill(
+                        Bounds::from_corners(
+                            point(bounds.left() + left, row_top(ix)),
+                            point(bounds.left() + right, row_top(ix) + line_height),
+                        ),
+                        selection_color,
+                    )
+                })
+                .collect()
+        };
+
+        let cursor = rows.get(cursor_row).map(|row| {
+            fill(
+                Bounds::new(
+                    point(bounds.left() + x_for(row, cursor), row_top(cursor_row)),
+                    size(px(2.0), line_height),
+                ),
+                use_theme().tokens.primary,
+            )
+        });
+
+        TextareaPrepaint {
+            rows,
+            placeholder,
+            selections,
+            cursor,
+            scroll_top,
+        }
+    }
+
+    fn paint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&gpui::InspectorElementId>,
+        bounds: Bounds<Pixels>,
+        _request_layout: &mut Self::RequestLayoutState,
+        prepaint: &mut Self::PrepaintState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let focus_handle = self.state.read(cx).focus_handle.clone();
+        window.handle_input(
+            &focus_handle,
+            ElementInputHandler::new(bounds, self.state.clone()),
+            cx,
+        );
+
+        let line_height = px(LINE_HEIGHT);
+        let scroll_top = prepaint.scroll_top;
+        window.with_content_mask(Some(ContentMask { bounds }), |window| {
+            for selection in prepaint.selections.drain(..) {
+                window.paint_quad(selection);
+            }
+            if let Some(placeholder) = prepaint.placeholder.take() {
+                placeholder
+                    .paint(bounds.origin, line_height, window, cx)
+                    .ok();
+            }
+            for (ix, row) in prepaint.rows.iter().enumerate() {
+                let top = bounds.top() + line_height * ix as f32 - scroll_top;
+                if top + line_height < bounds.top() || top > bounds.bottom() {
+                    continue;
+                }
+                row.line
+                    .paint(point(bounds.left(), top), line_height, window, cx)
+                    .ok();
+            }
+            if focus_handle.is_focused(window) {
+                if let Some(cursor) = prepaint.cursor.take() {
+                    window.paint_quad(cursor);
+                }
+            }
+        });
+
+        let rows = std::mem::take(&mut prepaint.rows);
+        self.state.update(cx, |state, cx| {
+            let grew = state.rows.len() != rows.len();
+            state.rows = rows;
+            state.last_bounds = Some(bounds);
+            state.scroll_top = scroll_top;
+            state.reveal_cursor = false;
+            if grew {
+                // The textarea's height follows the row count, which is only known now.
+                cx.notify();
+            }
+        });
+    }
+}
+
+impl Focusable for TextareaState {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
 
 #[derive(IntoElement)]
 pub struct Textarea {
@@ -20,6 +989,8 @@ pub struct Textarea {
     resizable: bool,
     on_change: Option<Rc<dyn Fn(SharedString, &mut Window, &mut App)>>,
     on_blur: Option<Rc<dyn Fn(SharedString, &mut Window, &mut App)>>,
+    on_submit: Option<TextHandler>,
+    state: Option<Entity<TextareaState>>,
     style: StyleRefinement,
 }
 
@@ -39,10 +1010,18 @@ impl Textarea {
             resizable: true,
             on_change: None,
             on_blur: None,
+            on_submit: None,
+            state: None,
             style: StyleRefinement::default(),
         }
     }
 
+    /// Make the textarea editable, backed by `state`. `value` is then ignored.
+    pub fn state(mut self, state: &Entity<TextareaState>) -> Self {
+        self.state = Some(state.clone());
+        self
+    }
+
     pub fn value(mut self, value: impl Into<SharedString>) -> Self {
         self.value = value.into();
         self
@@ -109,10 +1088,26 @@ impl Textarea {
         self
     }
 
-    fn calculate_height(&self) -> Pixels {
-        let line_height = 20.0;
-        let padding_y = 8.0;
-        px(self.rows as f32 * line_height + padding_y * 2.0)
+    /// Called with the text on cmd-enter, or on enter with
+    /// [`TextareaState::submit_on_enter`]. Needs a [`Self::state`].
+    pub fn on_submit<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(SharedString, &mut Window, &mut App) + 'static,
+    {
+        self.on_submit = Some(Rc::new(callback));
+        self
+    }
+
+    fn calculate_height(&self, cx: &App) -> Pixels {
+        let rows = match &self.state {
+            Some(state) if self.auto_grow => {
+                let min = self.min_rows.unwrap_or(self.rows);
+                let max = self.max_rows.unwrap_or(usize::MAX).max(min);
+                state.read(cx).visual_row_count().max(min).min(max)
+            }
+            _ => self.rows,
+        };
+        px(rows as f32 * LINE_HEIGHT + PADDING_Y * 2.0)
     }
 }
 
@@ -123,10 +1118,21 @@ impl Styled for Textarea {
 }
 
 impl RenderOnce for Textarea {
-    fn render(self, _window: &mut Window, _cx: &mut App) -> impl IntoElement {
+    fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
         let theme = use_theme();
         let user_style = self.style.clone();
-        let height = self.calculate_height();
+        let height = self.calculate_height(cx);
+
+        if let Some(state) = &self.state {
+            let on_change = self.on_change.clone();
+            let on_submit = self.on_submit.clone();
+            let disabled = self.disabled;
+            state.update(cx, |state, _| {
+                state.on_change = on_change;
+                state.on_submit = on_submit;
+                state.disabled = disabled;
+            });
+        }
 
         let (bg_color, border_color, text_color) = if self.disabled {
             (
@@ -174,12 +1180,18 @@ impl RenderOnce for Textarea {
 
         let textarea_id = self.id.clone();
         let has_value = !self.value.is_empty();
+        let is_focused = self
+            .state
+            .as_ref()
+            .is_some_and(|state| state.read(cx).focus_handle.is_focused(window));
 
         div()
             .id(textarea_id)
             .w_full()
             .h(height)
-            .when(self.auto_grow, |this| this.min_h(height))
+            .when(self.auto_grow && self.state.is_none(), |this| {
+                this.min_h(height)
+            })
             .px(px(12.0))
             .py(px(8.0))
             .bg(bg_color)
@@ -195,27 +1207,92 @@ impl RenderOnce for Textarea {
                     })
                 })
             })
+            .when(is_focused && !self.disabled && !self.error, |this| {
+                this.border_color(theme.tokens.ring)
+            })
             .when(!self.resizable, |this| this)
+            .when_some(self.state.clone(), |this, state| {
+                this.key_context("Textarea")
+                    .track_focus(
+                        &state
+                            .read(cx)
+                            .focus_handle
+                            .clone()
+                            .tab_index(0)
+                            .tab_stop(true),
+                    )
+                    .when(!self.disabled, |this| {
+                        this.cursor(CursorStyle::IBeam)
+                            .on_action(window.listener_for(&state, TextareaState::backspace))
+                            .on_action(window.listener_for(&state, TextareaState::delete))
+                            .on_action(window.listener_for(&state, TextareaState::left))
+                            .on_action(window.listener_for(&state, TextareaState::right))
+                            .on_action(window.listener_for(&state, TextareaState::up))
+                            .on_action(window.listener_for(&state, TextareaState::down))
+                            .on_action(window.listener_for(&state, TextareaState::select_left))
+                            .on_action(window.listener_for(&state, TextareaState::select_right))
+                            .on_action(window.listener_for(&state, TextareaState::select_up))
+                            .on_action(window.listener_for(&state, TextareaState::select_down))
+                            .on_action(window.listener_for(&state, TextareaState::select_all))
+                            .on_action(window.listener_for(&state, TextareaState::home))
+                            .on_action(window.listener_for(&state, TextareaState::end))
+                            .on_action(window.listener_for(&state, TextareaState::enter))
+                            .on_action(window.listener_for(&state, TextareaState::newline))
+                            .on_action(window.listener_for(&state, TextareaState::submit))
+                            .on_action(window.listener_for(&state, TextareaState::copy))
+                            .on_action(window.listener_for(&state, TextareaState::cut))
+                            .on_action(window.listener_for(&state, TextareaState::paste))
+                            .on_action(window.listener_for(&state, TextareaState::undo))
+                            .on_action(window.listener_for(&state, TextareaState::redo))
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                window.listener_for(&state, TextareaState::on_mouse_down),
+                            )
+                            .on_mouse_up(
+                                MouseButton::Left,
+                                window.listener_for(&state, TextareaState::on_mouse_up),
+                            )
+                            .on_mouse_up_out(
+                                MouseButton::Left,
+                                window.listener_for(&state, TextareaState::on_mouse_up),
+                            )
+                            .on_mouse_move(
+                                window.listener_for(&state, TextareaState::on_mouse_move),
+                            )
+                    })
+                    .on_scroll_wheel(window.listener_for(&state, TextareaState::on_scroll))
+            })
             .map(|this| {
                 let mut div = this;
                 div.style().refine(&user_style);
                 div
             })
-            .child(
-                div()
-                    .size_full()
-                    .text_size(px(14.0))
-                    .font_family(theme.tokens.font_mono.clone())
-                    .text_color(text_color)
-                    .line_height(relative(1.4))
-                    .child(if has_value {
-                        self.value.to_string()
-                    } else {
-                        self.placeholder.to_string()
-                    })
-                    .when(!has_value, |this| {
-                        this.text_color(theme.tokens.muted_foreground)
-                    }),
-            )
+            .map(|this| match self.state.clone() {
+                Some(state) => this.child(
+                    div()
+                        .size_full()
+                        .text_size(px(14.0))
+                        .font_family(theme.tokens.font_mono.clone())
+                        .text_color(text_color)
+                        .line_height(px(LINE_HEIGHT))
+                        .child(TextareaElement { state }),
+                ),
+                None => this.child(
+                    div()
+                        .size_full()
+                        .text_size(px(14.0))
+                        .font_family(theme.tokens.font_mono.clone())
+                        .text_color(text_color)
+                        .line_height(relative(1.4))
+                        .child(if has_value {
+                            self.value.to_string()
+                        } else {
+                            self.placeholder.to_string()
+                        })
+                        .when(!has_value, |this| {
+                            this.text_color(theme.tokens.muted_foreground)
+                        }),
+                ),
+            })
     }
 }
@@ -0,0 +1,685 @@
+//! Time window selection over a zoomable scale, for media, chart brushing and logs.
+//!
+//! Drag either handle to resize the window or the window itself to move it; pressing
+//! elsewhere on the track moves the nearer handle there. A tooltip shows the time
+//! under the pointer while dragging. Scrolling with cmd (ctrl elsewhere) zooms the
+//! scale around the pointer, and plain scrolling pans it once zoomed in. While
+//! focused, the arrow keys move the window (ten times further with shift), `=` and
+//! `-` zoom, and `0` resets the zoom.
+
+use crate::theme::use_theme;
+use crate::util::track_window_drag;
+use gpui::{prelude::FluentBuilder as _, *};
+use std::rc::Rc;
+
+/// Pointer distance, in pixels, within which a press grabs a handle.
+const HANDLE_HIT: f32 = 8.0;
+/// Minimum horizontal space per tick label.
+const TICK_SPACING: f32 = 72.0;
+/// Zoom factor per 100 pixels of scroll, and per `=` / `-` press.
+const ZOOM_STEP: f64 = 1.5;
+/// The scale never zooms in further than this fraction of the extent.
+const MIN_VIEW_FRACTION: f64 = 1e-4;
+
+/// Tick intervals, in seconds, from milliseconds up to a day.
+const TICK_STEPS: &[f64] = &[
+    0.001, 0.002, 0.005, 0.01, 0.02, 0.05, 0.1, 0.2, 0.5, 1.0, 2.0, 5.0, 10.0, 15.0, 30.0, 60.0,
+    120.0, 300.0, 600.0, 900.0, 1800.0, 3600.0, 7200.0, 10800.0, 21600.0, 43200.0, 86400.0,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimeRangeEvent {
+    /// The selection changed, during a drag or programmatically.
+    Change { start: f64, end: f64 },
+    /// A drag or key press finished changing the selection, e.g. to seek media once.
+    Commit { start: f64, end: f64 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DragTarget {
+    Start,
+    End,
+    /// The whole window, grabbed `offset` seconds after its start.
+    Window {
+        offset: f64,
+    },
+}
+
+pub struct TimeRangeSliderState {
+    min: f64,
+    max: f64,
+    start: f64,
+    end: f64,
+    step: Option<f64>,
+    min_span: f64,
+    /// Visible part of the scale.
+    view: (f64, f64),
+    focus_handle: FocusHandle,
+    drag: Option<DragTarget>,
+    /// Time under the pointer during a drag, shown in the preview tooltip.
+    preview: Option<f64>,
+    bounds: Bounds<Pixels>,
+}
+
+impl EventEmitter<TimeRangeEvent> for TimeRangeSliderState {}
+
+impl TimeRangeSliderState {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        Self {
+            min: 0.0,
+            max: 60.0,
+            start: 0.0,
+            end: 60.0,
+            step: None,
+            min_span: 0.0,
+            view: (0.0, 60.0),
+            focus_handle: cx.focus_handle(),
+            drag: None,
+            preview: None,
+            bounds: Bounds::default(),
+        }
+    }
+
+    /// The full selectable range, in seconds.
+    pub fn extent(&self) -> (f64, f64) {
+        (self.min, self.max)
+    }
+
+    /// Set the selectable range, resetting the zoom and clamping the selection into it.
+    pub fn set_extent(&mut self, min: f64, max: f64, cx: &mut Context<Self>) {
+        self.min = min.min(max);
+        self.max = max.max(min);
+        self.view = (self.min, self.max);
+        self.set_selection(self.start, self.end, cx);
+        cx.notify();
+    }
+
+    pub fn selection(&self) -> (f64, f64) {
+        (self.start, self.end)
+    }
+
+    /// Select `start..end`, clamped to the extent and snapped to the step, emitting
+    /// [`TimeRangeEvent::Change`] if it changed.
+    pub fn set_selection(&mut self, start: f64, end: f64, cx: &mut Context<Self>) {
+        let snapped = |time: f64| (self.snap(time - self.min) + self.min).clamp(self.min, self.max);
+        let (start, end) = (snapped(start.min(end)), snapped(start.max(end)));
+        let span = (end - start).max(self.min_span).min(self.max - self.min);
+        let start = start.min(self.max - span);
+        self.apply(start, start + span, cx);
+    }
+
+    /// Snap selection edges to multiples of `step` from the start of the extent.
+    pub fn set_step(&mut self, step: Option<f64>, cx: &mut Context<Self>) {
+        self.step = step.map(|step| step.abs().max(f64::EPSILON));
+        self.set_selection(self.start, self.end, cx);
+        cx.notify();
+    }
+
+    /// Shortest selection the handles can be dragged to.
+    pub fn set_min_span(&mut self, span: f64, cx: &mut Context<Self>) {
+        self.min_span = span.max(0.0);
+        self.set_selection(self.start, self.end, cx);
+        cx.notify();
+    }
+
+    /// The visible part of the scale.
+    pub fn view(&self) -> (f64, f64) {
+        self.view
+    }
+
+    /// Show `start..end` of the scale, limited to the extent.
+    pub fn set_view(&mut self, start: f64, end: f64, cx: &mut Context<Self>) {
+        let span = (end - start).abs().max(self.min_view_span());
+        let view = (start.min(end), start.min(end) + span);
+        self.view = pan_view(view, (self.min, self.max), 0.0);
+        cx.notify();
+    }
+
+    /// Zoom in by `factor` (out when below 1), keeping `anchor` at the same place.
+    pub fn zoom(&mut self, factor: f64, anchor: f64, cx: &mut Context<Self>) {
+        self.view = zoom_view(
+            self.view,
+            (self.min, self.max),
+            factor,
+            anchor,
+            self.min_view_span(),
+        );
+        cx.notify();
+    }
+
+    pub fn reset_zoom(&mut self, cx: &mut Context<Self>) {
+        self.view = (self.min, self.max);
+        cx.notify();
+    }
+
+    fn min_view_span(&self) -> f64 {
+        ((self.max - self.min) * MIN_VIEW_FRACTION)
+            .max(self.step.unwrap_or(0.0))
+            .max(f64::EPSILON)
+    }
+
+    fn snap(&self, value: f64) -> f64 {
+        match self.step {
+            Some(step) => (value / step).round() * step,
+            None => value,
+        }
+    }
+
+    fn apply(&mut self, start: f64, end: f64, cx: &mut Context<Self>) {
+        let start = start.clamp(self.min, self.max);
+        let end = end.clamp(start, self.max);
+        if (start - self.start).abs() > f64::EPSILON || (end - self.end).abs() > f64::EPSILON {
+            self.start = start;
+            self.end = end;
+            cx.emit(TimeRangeEvent::Change { start, end });
+            cx.notify();
+        }
+    }
+
+    fn fraction(&self, time: f64) -> f32 {
+        let (view_start, view_end) = self.view;
+        if view_end <= view_start {
+            return 0.0;
+        }
+        ((time - view_start) / (view_end - view_start)) as f32
+    }
+
+    fn time_at(&self, x: Pixels) -> f64 {
+        let width = self.bounds.size.width;
+        if width <= px(0.0) {
+            return self.view.0;
+        }
+        let fraction = ((x - self.bounds.left()) / width).clamp(0.0, 1.0) as f64;
+        self.view.0 + fraction * (self.view.1 - self.view.0)
+    }
+
+    fn begin_drag(&mut self, position: Point<Pixels>, cx: &mut Context<Self>) {
+        let width = self.bounds.size.width / px(1.0);
+        let to_px = |time: f64| self.fraction(time) * width;
+        let x = (position.x - self.bounds.left()) / px(1.0);
+        let to_start = (x - to_px(self.start)).abs();
+        let to_end = (x - to_px(self.end)).abs();
+        let time = self.time_at(position.x);
+
+        let target = if to_start.min(to_end) <= HANDLE_HIT {
+            // Handles overlap on a collapsed window; pick by which side was pressed.
+            if to_start < to_end || (to_start == to_end && x < to_px(self.start)) {
+                DragTarget::Start
+            } else {
+                DragTarget::End
+            }
+        } else if self.start < time && time < self.end {
+            DragTarget::Window {
+                offset: time - self.start,
+            }
+        } else if time <= self.start {
+            DragTarget::Start
+        } else {
+            DragTarget::End
+        };
+        self.drag = Some(target);
+        self.drag_to(position.x, cx);
+    }
+
+    fn drag_to(&mut self, x: Pixels, cx: &mut Context<Self>) {
+        let Some(target) = self.drag else {
+            return;
+        };
+        let time = self.time_at(x);
+        self.preview = Some(time);
+        let span = self.end - self.start;
+        match target {
+            DragTarget::Start => {
+                let start = self.snap(time - self.min) + self.min;
+                self.apply(start.min(self.end - self.min_span), self.end, cx);
+            }
+            DragTarget::End => {
+                let end = self.snap(time - self.min) + self.min;
+                self.apply(self.start, end.max(self.start + self.min_span), cx);
+            }
+            DragTarget::Window { offset } => {
+                let start = self.snap(time - offset - self.min) + self.min;
+                let start = start.clamp(self.min, self.max - span);
+                self.apply(start, start + span, cx);
+            }
+        }
+        cx.notify();
+    }
+
+    fn end_drag(&mut self, cx: &mut Context<Self>) {
+        if self.drag.take().is_some() {
+            self.preview = None;
+            cx.emit(TimeRangeEvent::Commit {
+                start: self.start,
+                end: self.end,
+            });
+            cx.notify();
+        }
+    }
+
+    fn scroll(&mut self, event: &ScrollWheelEvent, cx: &mut Context<Self>) {
+        let delta = match event.delta {
+            ScrollDelta::Pixels(p) => p,
+            ScrollDelta::Lines(l) => point(px(l.x * 20.0), px(l.y * 20.0)),
+        };
+        if event.modifiers.secondary() {
+            let factor = ZOOM_STEP.powf((delta.y / px(1.0)) as f64 / 100.0);
+            self.zoom(factor, self.time_at(event.position.x), cx);
+        } else {
+            let dx = if delta.x.abs() > delta.y.abs() {
+                delta.x
+            } else {
+                delta.y
+            };
+            let width = self.bounds.size.width / px(1.0);
+            if width > 0.0 {
+                let shift = -(dx / px(1.0)) as f64 / width as f64 * (self.view.1 - self.view.0);
+                self.view = pan_view(self.view, (self.min, self.max), shift);
+                cx.notify();
+            }
+        }
+    }
+
+    fn handle_key(&mut self, keystroke: &Keystroke, cx: &mut Context<Self>) -> bool {
+        let step = self.step.unwrap_or((self.view.1 - self.view.0) * 0.01)
+            * if keystroke.modifiers.shift { 10.0 } else { 1.0 };
+        let center = (self.start + self.end) / 2.0;
+        match keystroke.key.as_str() {
+            "left" | "right" => {
+                let span = self.end - self.start;
+                let shift = if keystroke.key == "left" { -step } else { step };
+                let start = (self.start + shift).clamp(self.min, self.max - span);
+                if start != self.start {
+                    self.apply(start, start + span, cx);
+                    cx.emit(TimeRangeEvent::Commit {
+                        start: self.start,
+                        end: self.end,
+                    });
+                }
+            }
+            "=" | "+" => self.zoom(ZOOM_STEP, center, cx),
+            "-" => self.zoom(1.0 / ZOOM_STEP, center, cx),
+            "0" => self.reset_zoom(cx),
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl Focusable for TimeRangeSliderState {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for TimeRangeSliderState {
+    fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+        div()
+    }
+}
+
+#[derive(IntoElement)]
+pub struct TimeRangeSlider {
+    state: Entity<TimeRangeSliderState>,
+    ticks: bool,
+    disabled: bool,
+    format: Option<Rc<dyn Fn(f64) -> SharedString>>,
+    style: StyleRefinement,
+}
+
+impl TimeRangeSlider {
+    pub fn new(state: Entity<TimeRangeSliderState>) -> Self {
+        Self {
+            state,
+            ticks: true,
+            disabled: false,
+            format: None,
+            style: StyleRefinement::default(),
+        }
+    }
+
+    /// Whether tick marks and labels are drawn under the track.
+    pub fn ticks(mut self, ticks: bool) -> Self {
+        self.ticks = ticks;
+        self
+    }
+
+    /// Format times for tick labels and the drag tooltip, e.g. as wall-clock times
+    /// for logs. Defaults to `h:mm:ss` with as many decimals as the ticks need.
+    pub fn format(mut self, format: impl Fn(f64) -> SharedString + 'static) -> Self {
+        self.format = Some(Rc::new(format));
+        self
+    }
+
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+}
+
+impl Styled for TimeRangeSlider {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+impl RenderOnce for TimeRangeSlider {
+    fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let theme = use_theme();
+        let user_style = self.style;
+        let state = self.state.read(cx);
+        let focus_handle = state.focus_handle.clone();
+        let is_focused = focus_handle.is_focused(window);
+        let disabled = self.disabled;
+        let dragging = state.drag.is_some();
+
+        let (view_start, view_end) = state.view;
+        let width = state.bounds.size.width / px(1.0);
+        let max_ticks = (width / TICK_SPACING).max(2.0) as f64;
+        let tick = tick_step(view_end - view_start, max_ticks);
+        let format: Rc<dyn Fn(f64) -> SharedString> = match self.format {
+            Some(format) => format,
+            None => Rc::new(move |time: f64| format_timestamp(time, tick).into()),
+        };
+        let ticks: Vec<(f32, SharedString)> = if self.ticks {
+            let first = (view_start / tick).ceil() as i64;
+            let last = (view_end / tick).floor() as i64;
+            (first..=last)
+                .map(|i| i as f64 * tick)
+                .map(|time| (state.fraction(time), format(time)))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let start = state.fraction(state.start).clamp(0.0, 1.0);
+        let end = state.fraction(state.end).clamp(0.0, 1.0);
+        let preview = state
+            .preview
+            .map(|time| (state.fraction(time).clamp(0.0, 1.0), format(time)));
+        let zoomed = view_start > state.min || view_end < state.max;
+
+        let handle = |fraction: f32| {
+            div()
+                .absolute()
+                .top_0()
+                .bottom_0()
+                .left(relative(fraction))
+                .ml(px(-3.0))
+                .w(px(6.0))
+                .rounded(theme.tokens.radius_sm)
+                .bg(theme.tokens.primary)
+                .when(!disabled, |this| this.cursor(CursorStyle::ResizeLeftRight))
+        };
+        let centered_label = |fraction: f32, label: AnyElement| {
+            div()
+                .absolute()
+                .left(relative(fraction))
+                .w(px(0.0))
+                .flex()
+                .justify_center()
+                .child(label)
+        };
+
+        div()
+            .id(("time-range-slider", self.state.entity_id()))
+            .flex()
+            .flex_col()
+            .gap_1()
+            .w_full()
+            .when(disabled, |this| this.opacity(0.5))
+            .map(|mut this| {
+                this.style().refine(&user_style);
+                this
+            })
+            .child(
+                div()
+                    .id("track")
+                    .relative()
+                    .h(px(28.0))
+                    .w_full()
+                    .rounded(theme.tokens.radius_md)
+                    .bg(theme.tokens.muted.opacity(0.6))
+                    .border_1()
+                    .border_color(if zoomed {
+                        theme.tokens.primary.opacity(0.4)
+                    } else {
+                        theme.tokens.border
+                    })
+                    .when(is_focused && !disabled, |this| {
+                        this.shadow(smallvec::smallvec![theme.tokens.focus_ring_light()])
+                    })
+                    .when(!disabled, |this| {
+                        this.track_focus(&focus_handle.tab_index(0).tab_stop(true))
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                window.listener_for(
+                                    &self.state,
+                                    |state, event: &MouseDownEvent, window, cx| {
+                                        window.focus(&state.focus_handle);
+                                        state.begin_drag(event.position, cx);
+                                        cx.stop_propagation();
+                                    },
+                                ),
+                            )
+                            .on_scroll_wheel(window.listener_for(
+                                &self.state,
+                                |state, event: &ScrollWheelEvent, _, cx| {
+                                    state.scroll(event, cx);
+                                    cx.stop_propagation();
+                                },
+                            ))
+                            .on_key_down(window.listener_for(
+                                &self.state,
+                                |state, event: &KeyDownEvent, _, cx| {
+                                    if state.handle_key(&event.keystroke, cx) {
+                                        cx.stop_propagation();
+                                    }
+                                },
+                            ))
+                    })
+                    .child({
+                        let slider = self.state.clone();
+                        canvas(
+                            {
+                                let slider = slider.clone();
+                                move |bounds, _, cx| {
+                                    slider.update(cx, |state, _| state.bounds = bounds);
+                                }
+                            },
+                            move |_, _, window, _| {
+                                if dragging {
+                                    track_window_drag(
+                                        slider,
+                                        window,
+                                        |state, event, cx| state.drag_to(event.position.x, cx),
+                                        |state, cx| state.end_drag(cx),
+                                    );
+                                }
+                            },
+                        )
+                        .absolute()
+                        .size_full()
+                    })
+                    .child(
+                        div()
+                            .absolute()
+                            .top_0()
+                            .bottom_0()
+                            .left(relative(start))
+                            .w(relative(end - start))
+                            .bg(theme.tokens.primary.opacity(0.2))
+                            .border_t_1()
+                            .border_b_1()
+                            .border_color(theme.tokens.primary)
+                            .when(!disabled, |this| {
+                                this.cursor(if dragging {
+                                    CursorStyle::ClosedHand
+                                } else {
+                                    CursorStyle::OpenHand
+                                })
+                            }),
+                    )
+                    .child(handle(start))
+                    .child(handle(end))
+                    .when_some(preview, |this, (fraction, label)| {
+                        this.child(
+                            centered_label(
+                                fraction,
+                                div()
+                                    .px_2()
+                                    .py(px(2.0))
+                                    .rounded(theme.tokens.radius_sm)
+                                    .bg(theme.tokens.popover)
+                                    .border_1()
+                                    .border_color(theme.tokens.border)
+                                    .shadow(smallvec::smallvec![theme.tokens.shadow_sm.clone()])
+                                    .text_xs()
+                                    .text_color(theme.tokens.popover_foreground)
+                                    .whitespace_nowrap()
+                                    .child(label)
+                                    .into_any_element(),
+                            )
+                            .bottom_full()
+                            .mb(px(4.0)),
+                        )
+                    }),
+            )
+            .when(!ticks.is_empty(), |this| {
+                this.child(
+                    div().relative().h(px(18.0)).w_full().children(
+                        ticks
+                            .into_iter()
+                            .filter(|(fraction, _)| (0.0..=1.0).contains(fraction))
+                            .map(|(fraction, label)| {
+                                centered_label(
+                                    fraction,
+                                    div()
+                                        .flex()
+                                        .flex_col()
+                                        .items_center()
+                                        .child(div().w(px(1.0)).h(px(4.0)).bg(theme.tokens.border))
+                                        .child(
+                                            div()
+                                                .text_xs()
+                                                .text_color(theme.tokens.muted_foreground)
+                                                .whitespace_nowrap()
+                                                .child(label),
+                                        )
+                                        .into_any_element(),
+                                )
+                                .top_0()
+                            }),
+                    ),
+                )
+            })
+    }
+}
+
+/// The smallest tick interval that fits at most `max_ticks` ticks into `span` seconds.
+fn tick_step(span: f64, max_ticks: f64) -> f64 {
+    if span <= 0.0 {
+        return 1.0;
+    }
+    let min_step = span / max_ticks.max(1.0);
+    if let Some(step) = TICK_STEPS.iter().find(|step| **step >= min_step) {
+        return *step;
+    }
+    // Whole days beyond that, in 1-2-5 multiples.
+    let days = min_step / 86400.0;
+    let magnitude = 10f64.powf(days.log10().floor());
+    let nice = [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .find(|m| m * magnitude >= days)
+        .unwrap_or(10.0);
+    nice * magnitude * 86400.0
+}
+
+/// `m:ss` or `h:mm:ss`, with as many decimals as `step` needs.
+fn format_timestamp(seconds: f64, step: f64) -> String {
+    let decimals = if step >= 1.0 {
+        0
+    } else {
+        (-step.log10()).ceil().clamp(0.0, 3.0) as usize
+    };
+    let sign = if seconds < 0.0 { "-" } else { "" };
+    let scale = 10f64.powi(decimals as i32);
+    let scaled = (seconds.abs() * scale).round();
+    let whole = (scaled / scale).floor() as u64;
+    let fraction = scaled as u64 - whole * scale as u64;
+    let (hours, minutes, secs) = (whole / 3600, whole / 60 % 60, whole % 60);
+
+    let mut out = if hours > 0 {
+        format!("{}{}:{:02}:{:02}", sign, hours, minutes, secs)
+    } else {
+        format!("{}{}:{:02}", sign, minutes, secs)
+    };
+    if decimals > 0 {
+        out.push_str(&format!(".{:0width$}", fraction, width = decimals));
+    }
+    out
+}
+
+/// `view` zoomed by `factor` around `anchor`, kept inside `extent` and no narrower
+/// than `min_span`.
+fn zoom_view(
+    view: (f64, f64),
+    extent: (f64, f64),
+    factor: f64,
+    anchor: f64,
+    min_span: f64,
+) -> (f64, f64) {
+    let span = view.1 - view.0;
+    if span <= 0.0 || factor <= 0.0 {
+        return view;
+    }
+    let new_span = (span / factor).clamp(min_span.min(extent.1 - extent.0), extent.1 - extent.0);
+    let fraction = ((anchor - view.0) / span).clamp(0.0, 1.0);
+    let start = anchor - fraction * new_span;
+    pan_view((start, start + new_span), extent, 0.0)
+}
+
+/// `view` moved by `shift` seconds, stopping at the ends of `extent`.
+fn pan_view(view: (f64, f64), extent: (f64, f64), shift: f64) -> (f64, f64) {
+    let span = (view.1 - view.0).min(extent.1 - extent.0);
+    let start = (view.0 + shift).clamp(extent.0, extent.1 - span);
+    (start, start + span)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ticks_and_labels_follow_zoom() {
+        assert_eq!(tick_step(3600.0, 10.0), 600.0);
+        assert_eq!(tick_step(10.0, 10.0), 1.0);
+        assert_eq!(tick_step(0.3, 5.0), 0.1);
+        assert_eq!(tick_step(86400.0 * 30.0, 4.0), 86400.0 * 10.0);
+
+        assert_eq!(format_timestamp(75.0, 5.0), "1:15");
+        assert_eq!(format_timestamp(3725.0, 60.0), "1:02:05");
+        assert_eq!(format_timestamp(1.25, 0.05), "0:01.25");
+        assert_eq!(format_timestamp(59.96, 0.1), "1:00.0");
+    }
+
+    #[test]
+    fn zoom_keeps_anchor_and_stays_in_extent() {
+        let extent = (0.0, 100.0);
+        assert_eq!(
+            zoom_view((0.0, 100.0), extent, 2.0, 50.0, 1.0),
+            (25.0, 75.0)
+        );
+        assert_eq!(zoom_view((0.0, 100.0), extent, 2.0, 0.0, 1.0), (0.0, 50.0));
+        assert_eq!(zoom_view((0.0, 10.0), extent, 100.0, 5.0, 1.0), (4.5, 5.5));
+        assert_eq!(
+            zoom_view((80.0, 100.0), extent, 0.5, 100.0, 1.0),
+            (60.0, 100.0)
+        );
+        assert_eq!(
+            zoom_view((25.0, 75.0), extent, 0.1, 50.0, 1.0),
+            (0.0, 100.0)
+        );
+        assert_eq!(pan_view((60.0, 90.0), extent, 20.0), (70.0, 100.0));
+    }
+}
@@ -0,0 +1,252 @@
+//! Type-ahead find shared by lists, trees and selects.
+//!
+//! Printable keys typed in quick succession build a prefix, and the owning component
+//! moves to the next item whose label starts with it. A pause longer than the timeout
+//! starts a new prefix, and repeating a single letter cycles through the items that
+//! start with it. When enabled, `/` opens an inline filter instead, which narrows the
+//! items as you type until escape closes it.
+
+use gpui::Keystroke;
+use std::time::{Duration, Instant};
+
+/// Pause after which the next key starts a new prefix.
+pub const DEFAULT_TYPE_AHEAD_TIMEOUT: Duration = Duration::from_millis(1000);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TypeAheadAction {
+    /// The prefix grew; move to [`TypeAhead::find`]'s result.
+    Jump,
+    /// The inline filter opened, changed or closed; re-filter the items.
+    FilterChanged,
+}
+
+#[derive(Clone, Debug)]
+pub struct TypeAhead {
+    prefix: String,
+    last_key: Option<Instant>,
+    timeout: Duration,
+    filter_enabled: bool,
+    filter: Option<String>,
+}
+
+impl Default for TypeAhead {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TypeAhead {
+    pub fn new() -> Self {
+        Self {
+            prefix: String::new(),
+            last_key: None,
+            timeout: DEFAULT_TYPE_AHEAD_TIMEOUT,
+            filter_enabled: true,
+            filter: None,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Whether `/` opens the inline filter. On by default.
+    pub fn with_filter(mut self, enabled: bool) -> Self {
+        self.filter_enabled = enabled;
+        self
+    }
+
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// The inline filter's text, if it is open.
+    pub fn filter(&self) -> Option<&str> {
+        self.filter.as_deref()
+    }
+
+    pub fn is_filtering(&self) -> bool {
+        self.filter.is_some()
+    }
+
+    /// Forget the prefix and close the filter, e.g. when a menu closes.
+    pub fn reset(&mut self) {
+        self.prefix.clear();
+        self.last_key = None;
+        self.filter = None;
+    }
+
+    /// Feed a key press. Returns `None` for keys the owner should handle itself, such
+    /// as arrows, enter, and escape while the filter is closed.
+    pub fn handle_key(&mut self, keystroke: &Keystroke) -> Option<TypeAheadAction> {
+        let modifiers = &keystroke.modifiers;
+        let text = if modifiers.control || modifiers.platform || modifiers.function {
+            None
+        } else {
+            keystroke
+                .key_char
+                .as_deref()
+                .or((keystroke.key.chars().count() == 1).then_some(keystroke.key.as_str()))
+        };
+        self.handle_input(&keystroke.key, text, Instant::now())
+    }
+
+    fn handle_input(
+        &mut self,
+        key: &str,
+        text: Option<&str>,
+        now: Instant,
+    ) -> Option<TypeAheadAction> {
+        let text = text.filter(|text| !text.is_empty() && !text.chars().any(char::is_control));
+
+        if let Some(filter) = self.filter.as_mut() {
+            match key {
+                "escape" => self.filter = None,
+                "backspace" if filter.is_empty() => self.filter = None,
+                "backspace" => {
+                    filter.pop();
+                }
+                _ => filter.push_str(text?),
+            }
+            return Some(TypeAheadAction::FilterChanged);
+        }
+
+        let text = text?;
+        let expired = self.last_key.map_or(true, |last| {
+            now.saturating_duration_since(last) > self.timeout
+        });
+        if expired {
+            self.prefix.clear();
+        }
+        if self.prefix.is_empty() {
+            if text == "/" && self.filter_enabled {
+                self.filter = Some(String::new());
+                return Some(TypeAheadAction::FilterChanged);
+            }
+            // Space activates the focused item unless it continues a prefix.
+            if text.trim().is_empty() {
+                return None;
+            }
+        }
+        self.prefix.push_str(text);
+        self.last_key = Some(now);
+        Some(TypeAheadAction::Jump)
+    }
+
+    /// Index of the item to move to for the current prefix, searching `labels` from
+    /// `current` and wrapping around. A growing prefix stays on `current` while it
+    /// still matches; a repeated single letter moves past it.
+    pub fn find<S: AsRef<str>>(&self, labels: &[S], current: Option<usize>) -> Option<usize> {
+        let prefix = self.prefix.to_lowercase();
+        let mut chars = prefix.chars();
+        let first = chars.next()?;
+        let cycling = chars.all(|c| c == first);
+        let needle = if cycling {
+            &prefix[..first.len_utf8()]
+        } else {
+            prefix.as_str()
+        };
+
+        let len = labels.len();
+        let start = match current {
+            Some(current) if cycling => current + 1,
+            Some(current) => current,
+            None => 0,
+        };
+        (0..len).map(|offset| (start + offset) % len).find(|&ix| {
+            labels[ix]
+                .as_ref()
+                .trim_start()
+                .to_lowercase()
+                .starts_with(needle)
+        })
+    }
+
+    /// Whether `label` passes the inline filter. Everything does while it is closed.
+    pub fn matches_filter(&self, label: &str) -> bool {
+        match self.filter.as_deref() {
+            None | Some("") => true,
+            Some(filter) => label.to_lowercase().contains(&filter.to_lowercase()),
+        }
+    }
+}
+
+/// Which rows of a flattened tree to keep when filtering: those that match and every
+/// ancestor of one. `rows` are `(depth, matches)` in depth-first order.
+pub(crate) fn keep_with_ancestors(rows: &[(usize, bool)]) -> Vec<bool> {
+    let mut keep = vec![false; rows.len()];
+    // Depth of the last kept row; the next shallower row above it is its parent.
+    let mut depth = 0;
+    for (ix, &(level, matches)) in rows.iter().enumerate().rev() {
+        if matches || level < depth {
+            keep[ix] = true;
+            depth = level;
+        }
+    }
+    keep
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_jumps_and_cycles() {
+        let labels = ["apple", "Banana", "blueberry", "cherry", "blackberry"];
+        let mut type_ahead = TypeAhead::new();
+        let t0 = Instant::now();
+        let at = |ms| t0 + Duration::from_millis(ms);
+
+        type_ahead.handle_input("b", Some("b"), at(0));
+        assert_eq!(type_ahead.find(&labels, Some(0)), Some(1));
+        type_ahead.handle_input("l", Some("l"), at(100));
+        assert_eq!(type_ahead.find(&labels, Some(1)), Some(2));
+        type_ahead.handle_input("a", Some("a"), at(200));
+        assert_eq!(type_ahead.find(&labels, Some(2)), Some(4));
+
+        type_ahead.handle_input("b", Some("b"), at(2000));
+        assert_eq!(type_ahead.prefix(), "b");
+        assert_eq!(type_ahead.find(&labels, Some(4)), Some(1));
+        type_ahead.handle_input("b", Some("b"), at(2100));
+        assert_eq!(type_ahead.find(&labels, Some(1)), Some(2));
+
+        assert_eq!(type_ahead.handle_input("space", Some(" "), at(5000)), None);
+        assert_eq!(type_ahead.handle_input("down", None, at(5100)), None);
+    }
+
+    #[test]
+    fn slash_opens_filter() {
+        let mut type_ahead = TypeAhead::new();
+        let now = Instant::now();
+        assert_eq!(
+            type_ahead.handle_input("/", Some("/"), now),
+            Some(TypeAheadAction::FilterChanged)
+        );
+        type_ahead.handle_input("r", Some("r"), now);
+        type_ahead.handle_input("y", Some("y"), now);
+        assert!(type_ahead.matches_filter("Cherry"));
+        assert!(!type_ahead.matches_filter("apple"));
+        assert_eq!(type_ahead.handle_input("enter", None, now), None);
+        type_ahead.handle_input("escape", None, now);
+        assert!(!type_ahead.is_filtering());
+        assert!(type_ahead.matches_filter("apple"));
+    }
+
+    #[test]
+    fn keeps_ancestors_of_matches() {
+        let rows = [
+            (0, false),
+            (1, false),
+            (2, true),
+            (1, false),
+            (1, true),
+            (0, false),
+            (1, false),
+        ];
+        assert_eq!(
+            keep_with_ancestors(&rows),
+            vec![true, true, true, false, true, false, false]
+        );
+    }
+}
@@ -0,0 +1,364 @@
+//! Two-dimensional value picker, e.g. filter cutoff against resonance.
+//!
+//! Click or drag anywhere on the pad to move the handle; the drag keeps tracking
+//! outside the pad and clamps to its edges. Arrow keys move by one step (ten with
+//! shift) while focused.
+
+use crate::theme::use_theme;
+use crate::util::track_window_drag;
+use gpui::{prelude::FluentBuilder as _, *};
+
+/// Grid lines are only drawn when a step divides an axis into at most this many cells.
+const MAX_GRID_LINES: f32 = 24.0;
+/// Keyboard movement as a fraction of the range when an axis has no step.
+const DEFAULT_KEY_FRACTION: f32 = 0.01;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum XYPadEvent {
+    Change { x: f32, y: f32 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PadAxis {
+    min: f32,
+    max: f32,
+    step: Option<f32>,
+}
+
+impl PadAxis {
+    fn clamp_and_snap(&self, value: f32) -> f32 {
+        let value = value.clamp(self.min, self.max);
+        match self.step {
+            Some(step) => {
+                (((value - self.min) / step).round() * step + self.min).clamp(self.min, self.max)
+            }
+            None => value,
+        }
+    }
+
+    fn normalize(&self, value: f32) -> f32 {
+        if self.max == self.min {
+            return 0.0;
+        }
+        ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+    }
+
+    fn key_step(&self) -> f32 {
+        self.step
+            .unwrap_or((self.max - self.min) * DEFAULT_KEY_FRACTION)
+    }
+
+    /// Normalized positions of the snapping grid lines, if there are few enough to draw.
+    fn grid(&self) -> Vec<f32> {
+        let Some(step) = self.step else {
+            return Vec::new();
+        };
+        let cells = (self.max - self.min) / step;
+        if cells > MAX_GRID_LINES {
+            return Vec::new();
+        }
+        (1..cells.ceil() as usize)
+            .map(|i| (i as f32 * step) / (self.max - self.min))
+            .collect()
+    }
+}
+
+pub struct XYPadState {
+    x_axis: PadAxis,
+    y_axis: PadAxis,
+    x: f32,
+    y: f32,
+    focus_handle: FocusHandle,
+    dragging: bool,
+    bounds: Bounds<Pixels>,
+}
+
+impl EventEmitter<XYPadEvent> for XYPadState {}
+
+impl XYPadState {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        let axis = PadAxis {
+            min: 0.0,
+            max: 1.0,
+            step: None,
+        };
+        Self {
+            x_axis: axis,
+            y_axis: axis,
+            x: 0.5,
+            y: 0.5,
+            focus_handle: cx.focus_handle(),
+            dragging: false,
+            bounds: Bounds::default(),
+        }
+    }
+
+    pub fn value(&self) -> (f32, f32) {
+        (self.x, self.y)
+    }
+
+    /// Move the handle, clamping and snapping each axis, and emit
+    /// [`XYPadEvent::Change`] if it moved.
+    pub fn set_value(&mut self, x: f32, y: f32, cx: &mut Context<Self>) {
+        let x = self.x_axis.clamp_and_snap(x);
+        let y = self.y_axis.clamp_and_snap(y);
+        if (x - self.x).abs() > f32::EPSILON || (y - self.y).abs() > f32::EPSILON {
+            self.x = x;
+            self.y = y;
+            cx.emit(XYPadEvent::Change { x, y });
+            cx.notify();
+        }
+    }
+
+    pub fn set_x_range(&mut self, min: f32, max: f32, cx: &mut Context<Self>) {
+        self.x_axis.min = min.min(max);
+        self.x_axis.max = max.max(min);
+        self.set_value(self.x, self.y, cx);
+        cx.notify();
+    }
+
+    pub fn set_y_range(&mut self, min: f32, max: f32, cx: &mut Context<Self>) {
+        self.y_axis.min = min.min(max);
+        self.y_axis.max = max.max(min);
+        self.set_value(self.x, self.y, cx);
+        cx.notify();
+    }
+
+    /// Snap each axis to multiples of its step from the minimum. `None` moves freely.
+    pub fn set_snap(&mut self, x_step: Option<f32>, y_step: Option<f32>, cx: &mut Context<Self>) {
+        self.x_axis.step = x_step.map(|step| step.abs().max(f32::EPSILON));
+        self.y_axis.step = y_step.map(|step| step.abs().max(f32::EPSILON));
+        self.set_value(self.x, self.y, cx);
+        cx.notify();
+    }
+
+    fn update_from_position(&mut self, position: Point<Pixels>, cx: &mut Context<Self>) {
+        let size = self.bounds.size;
+        if size.width <= px(0.0) || size.height <= px(0.0) {
+            return;
+        }
+        let fx = ((position.x - self.bounds.left()) / size.width).clamp(0.0, 1.0);
+        let fy = 1.0 - ((position.y - self.bounds.top()) / size.height).clamp(0.0, 1.0);
+        let x = self.x_axis.min + fx * (self.x_axis.max - self.x_axis.min);
+        let y = self.y_axis.min + fy * (self.y_axis.max - self.y_axis.min);
+        self.set_value(x, y, cx);
+    }
+
+    fn handle_key(&mut self, keystroke: &Keystroke, cx: &mut Context<Self>) -> bool {
+        let scale = if keystroke.modifiers.shift { 10.0 } else { 1.0 };
+        let dx = self.x_axis.key_step() * scale;
+        let dy = self.y_axis.key_step() * scale;
+        let (x, y) = match keystroke.key.as_str() {
+            "left" => (self.x - dx, self.y),
+            "right" => (self.x + dx, self.y),
+            "up" => (self.x, self.y + dy),
+            "down" => (self.x, self.y - dy),
+            _ => return false,
+        };
+        self.set_value(x, y, cx);
+        true
+    }
+}
+
+impl Focusable for XYPadState {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for XYPadState {
+    fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+        div()
+    }
+}
+
+#[derive(IntoElement)]
+pub struct XYPad {
+    state: Entity<XYPadState>,
+    size: Size<Pixels>,
+    crosshair: bool,
+    disabled: bool,
+    style: StyleRefinement,
+}
+
+impl XYPad {
+    pub fn new(state: Entity<XYPadState>) -> Self {
+        Self {
+            state,
+            size: size(px(200.0), px(200.0)),
+            crosshair: true,
+            disabled: false,
+            style: StyleRefinement::default(),
+        }
+    }
+
+    pub fn size(mut self, width: Pixels, height: Pixels) -> Self {
+        self.size = size(width, height);
+        self
+    }
+
+    /// Whether lines through the handle are drawn to the pad's edges.
+    pub fn crosshair(mut self, crosshair: bool) -> Self {
+        self.crosshair = crosshair;
+        self
+    }
+
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+}
+
+impl Styled for XYPad {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+impl RenderOnce for XYPad {
+    fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let theme = use_theme();
+        let user_style = self.style;
+        let state = self.state.read(cx);
+        let focus_handle = state.focus_handle.clone();
+        let is_focused = focus_handle.is_focused(window);
+        let fx = state.x_axis.normalize(state.x);
+        let fy = state.y_axis.normalize(state.y);
+        let x_grid = state.x_axis.grid();
+        let y_grid = state.y_axis.grid();
+        let dragging = state.dragging;
+        let disabled = self.disabled;
+        let crosshair = self.crosshair;
+
+        let grid_color = theme.tokens.border.opacity(0.6);
+        let crosshair_color = theme.tokens.primary.opacity(0.5);
+        let handle_color = if disabled {
+            theme.tokens.muted_foreground
+        } else {
+            theme.tokens.primary
+        };
+        let handle_border = theme.tokens.background;
+
+        div()
+            .id(("xy-pad", self.state.entity_id()))
+            .relative()
+            .w(self.size.width)
+            .h(self.size.height)
+            .bg(theme.tokens.muted.opacity(0.4))
+            .border_1()
+            .border_color(theme.tokens.border)
+            .rounded(theme.tokens.radius_md)
+            .overflow_hidden()
+            .when(disabled, |this| this.opacity(0.5))
+            .when(is_focused && !disabled, |this| {
+                this.shadow(smallvec::smallvec![theme.tokens.focus_ring_light()])
+            })
+            .when(!disabled, |this| {
+                this.track_focus(&focus_handle.tab_index(0).tab_stop(true))
+                    .cursor(CursorStyle::Crosshair)
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        window.listener_for(
+                            &self.state,
+                            |state, event: &MouseDownEvent, window, cx| {
+                                window.focus(&state.focus_handle);
+                                state.dragging = true;
+                                state.update_from_position(event.position, cx);
+                                cx.notify();
+                                cx.stop_propagation();
+                            },
+                        ),
+                    )
+                    .on_key_down(window.listener_for(
+                        &self.state,
+                        |state, event: &KeyDownEvent, _, cx| {
+                            if state.handle_key(&event.keystroke, cx) {
+                                cx.stop_propagation();
+                            }
+                        },
+                    ))
+            })
+            .map(|mut this| {
+                this.style().refine(&user_style);
+                this
+            })
+            .child({
+                let pad = self.state.clone();
+                canvas(
+                    {
+                        let pad = pad.clone();
+                        move |bounds, _, cx| {
+                            pad.update(cx, |state, _| state.bounds = bounds);
+                        }
+                    },
+                    move |bounds, _, window, _| {
+                        for gx in &x_grid {
+                            let x = bounds.left() + bounds.size.width * *gx;
+                            window.paint_quad(fill(
+                                Bounds::new(
+                                    point(x, bounds.top()),
+                                    size(px(1.0), bounds.size.height),
+                                ),
+                                grid_color,
+                            ));
+                        }
+                        for gy in &y_grid {
+                            let y = bounds.bottom() - bounds.size.height * *gy;
+                            window.paint_quad(fill(
+                                Bounds::new(
+                                    point(bounds.left(), y),
+                                    size(bounds.size.width, px(1.0)),
+                                ),
+                                grid_color,
+                            ));
+                        }
+
+                        let handle = point(
+                            bounds.left() + bounds.size.width * fx,
+                            bounds.bottom() - bounds.size.height * fy,
+                        );
+                        if crosshair {
+                            window.paint_quad(fill(
+                                Bounds::new(
+                                    point(handle.x, bounds.top()),
+                                    size(px(1.0), bounds.size.height),
+                                ),
+                                crosshair_color,
+                            ));
+                            window.paint_quad(fill(
+                                Bounds::new(
+                                    point(bounds.left(), handle.y),
+                                    size(bounds.size.width, px(1.0)),
+                                ),
+                                crosshair_color,
+                            ));
+                        }
+                        let radius = px(7.0);
+                        window.paint_quad(
+                            fill(
+                                Bounds::centered_at(handle, size(radius * 2.0, radius * 2.0)),
+                                handle_color,
+                            )
+                            .corner_radii(radius)
+                            .border_widths(px(2.0))
+                            .border_color(handle_border),
+                        );
+
+                        if dragging {
+                            track_window_drag(
+                                pad,
+                                window,
+                                |state, event, cx| state.update_from_position(event.position, cx),
+                                |state, cx| {
+                                    state.dragging = false;
+                                    cx.notify();
+                                },
+                            );
+                        }
+                    },
+                )
+                .absolute()
+                .size_full()
+            })
+    }
+}
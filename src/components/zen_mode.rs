@@ -0,0 +1,192 @@
+//! Distraction-free mode: hides the app's chrome and centers the content.
+//!
+//! Zen mode is app-wide and toggled with the [`ToggleZenMode`] action, bound to
+//! `cmd-k z` / `ctrl-k z` by [`init`]. Mark what it should affect by wrapping it:
+//!
+//! - [`ZenMode::chrome`] for tab bars, status bars, toolbars and sidebars, which
+//!   render nothing while zen mode is on;
+//! - [`ZenMode::centered`] for the main content, kept to [`set_max_width`] in the
+//!   middle of the window;
+//! - [`ZenMode::pane`] for split panes, dimmed while focus is in another one.
+//!
+//! Nothing is taken out of the layout's own state, so leaving zen mode brings back
+//! the sidebars, split sizes and tabs exactly as they were.
+//!
+//! ```rust,ignore
+//! div()
+//!     .flex()
+//!     .size_full()
+//!     .child(ZenMode::chrome(sidebar))
+//!     .child(
+//!         div()
+//!             .flex()
+//!             .flex_col()
+//!             .flex_1()
+//!             .child(ZenMode::chrome(tab_bar))
+//!             .child(ZenMode::centered(editor))
+//!             .child(ZenMode::chrome(status_bar)),
+//!     )
+//! ```
+
+use gpui::{prelude::FluentBuilder as _, *};
+
+actions!(zen_mode, [ToggleZenMode, ExitZenMode]);
+
+const DEFAULT_MAX_WIDTH: Pixels = px(860.0);
+const DEFAULT_INACTIVE_OPACITY: f32 = 0.45;
+
+struct ZenModeSettings {
+    active: bool,
+    max_width: Pixels,
+    inactive_opacity: f32,
+}
+
+impl Default for ZenModeSettings {
+    fn default() -> Self {
+        Self {
+            active: false,
+            max_width: DEFAULT_MAX_WIDTH,
+            inactive_opacity: DEFAULT_INACTIVE_OPACITY,
+        }
+    }
+}
+
+impl Global for ZenModeSettings {}
+
+fn settings(cx: &App) -> Option<&ZenModeSettings> {
+    cx.try_global::<ZenModeSettings>()
+}
+
+pub fn init(cx: &mut App) {
+    cx.on_action(|_: &ToggleZenMode, cx| toggle(cx));
+    cx.on_action(|_: &ExitZenMode, cx| set_active(false, cx));
+    #[cfg(target_os = "macos")]
+    cx.bind_keys([KeyBinding::new("cmd-k z", ToggleZenMode, None)]);
+    #[cfg(not(target_os = "macos"))]
+    cx.bind_keys([KeyBinding::new("ctrl-k z", ToggleZenMode, None)]);
+}
+
+pub fn is_active(cx: &App) -> bool {
+    settings(cx).is_some_and(|settings| settings.active)
+}
+
+pub fn toggle(cx: &mut App) {
+    let active = !is_active(cx);
+    set_active(active, cx);
+}
+
+/// Enter or leave zen mode, redrawing every window.
+pub fn set_active(active: bool, cx: &mut App) {
+    if is_active(cx) == active {
+        return;
+    }
+    cx.default_global::<ZenModeSettings>();
+    cx.update_global::<ZenModeSettings, _>(|settings, _| settings.active = active);
+    cx.refresh_windows();
+}
+
+/// The widest [`ZenMode::centered`] content gets while zen mode is on. 860px by
+/// default, about 100 columns of code.
+pub fn set_max_width(width: impl Into<Pixels>, cx: &mut App) {
+    cx.default_global::<ZenModeSettings>().max_width = width.into();
+    cx.refresh_windows();
+}
+
+pub fn max_width(cx: &App) -> Pixels {
+    settings(cx).map_or(DEFAULT_MAX_WIDTH, |settings| settings.max_width)
+}
+
+/// The opacity of [`ZenMode::pane`]s without focus, from 0.0 to 1.0. Use 1.0 to turn
+/// dimming off.
+pub fn set_inactive_opacity(opacity: f32, cx: &mut App) {
+    cx.default_global::<ZenModeSettings>().inactive_opacity = opacity.clamp(0.0, 1.0);
+    cx.refresh_windows();
+}
+
+/// Run `f` when zen mode is entered or left, e.g. to go full screen or save the
+/// setting.
+pub fn observe<T: 'static>(
+    cx: &mut Context<T>,
+    f: impl FnMut(&mut T, &mut Context<T>) + 'static,
+) -> Subscription {
+    cx.default_global::<ZenModeSettings>();
+    cx.observe_global::<ZenModeSettings>(f)
+}
+
+enum ZenRole {
+    Chrome,
+    Centered,
+    Pane(FocusHandle),
+}
+
+/// Wraps part of the layout so zen mode can hide, center or dim it. See the module
+/// docs.
+#[derive(IntoElement)]
+pub struct ZenMode {
+    role: ZenRole,
+    child: AnyElement,
+}
+
+impl ZenMode {
+    /// Chrome hidden while zen mode is on. Laid out as `child` itself otherwise.
+    pub fn chrome(child: impl IntoElement) -> Self {
+        Self {
+            role: ZenRole::Chrome,
+            child: child.into_any_element(),
+        }
+    }
+
+    /// Content filling the available space, centered at the max width while zen mode is
+    /// on.
+    pub fn centered(child: impl IntoElement) -> Self {
+        Self {
+            role: ZenRole::Centered,
+            child: child.into_any_element(),
+        }
+    }
+
+    /// A pane dimmed while zen mode is on and `focus_handle` doesn't contain the focus.
+    pub fn pane(focus_handle: &FocusHandle, child: impl IntoElement) -> Self {
+        Self {
+            role: ZenRole::Pane(focus_handle.clone()),
+            child: child.into_any_element(),
+        }
+    }
+}
+
+impl RenderOnce for ZenMode {
+    fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let active = is_active(cx);
+        match self.role {
+            ZenRole::Chrome if active => Empty.into_any_element(),
+            ZenRole::Chrome => self.child,
+            ZenRole::Centered => div()
+                .flex()
+                .flex_1()
+                .size_full()
+                .justify_center()
+                .child(
+                    div()
+                        .flex()
+                        .flex_col()
+                        .size_full()
+                        .when(active, |this| this.max_w(max_width(cx)))
+                        .child(self.child),
+                )
+                .into_any_element(),
+            ZenRole::Pane(focus_handle) => {
+                let dimmed = active && !focus_handle.contains_focused(window, cx);
+                let opacity = settings(cx).map_or(DEFAULT_INACTIVE_OPACITY, |settings| {
+                    settings.inactive_opacity
+                });
+                div()
+                    .flex()
+                    .flex_col()
+                    .size_full()
+                    .when(dimmed, |this| this.opacity(opacity))
+                    .child(self.child)
+                    .into_any_element()
+            }
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! Panic capture for crash reporting.
+//!
+//! [`install_panic_hook`] records every panic as a [`CrashReport`] (message, location,
+//! thread and backtrace) and can append it to a crash log, then defers to the
+//! previously installed hook. Pair it with
+//! [`CrashOverlay`](crate::overlays::crash_overlay::CrashOverlay) or
+//! [`crash_boundary`](crate::overlays::crash_overlay::crash_boundary) to show the
+//! report instead of letting the app disappear silently.
+
+use once_cell::sync::Lazy;
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static LAST_CRASH: Lazy<Mutex<Option<CrashReport>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone)]
+pub struct CrashReport {
+    pub message: String,
+    /// `file:line:column` of the panic, if known.
+    pub location: Option<String>,
+    pub thread: Option<String>,
+    pub backtrace: String,
+    pub timestamp: SystemTime,
+}
+
+impl CrashReport {
+    fn from_payload(payload: &(dyn Any + Send)) -> Self {
+        Self {
+            message: payload_message(payload),
+            location: None,
+            thread: std::thread::current().name().map(str::to_string),
+            backtrace: String::new(),
+            timestamp: SystemTime::now(),
+        }
+    }
+
+    /// Plain-text report suitable for the clipboard or an issue tracker.
+    pub fn details(&self) -> String {
+        let mut details = format!("panic: {}\n", self.message);
+        if let Some(location) = &self.location {
+            details.push_str(&format!("location: {}\n", location));
+        }
+        if let Some(thread) = &self.thread {
+            details.push_str(&format!("thread: {}\n", thread));
+        }
+        if let Ok(elapsed) = self.timestamp.duration_since(UNIX_EPOCH) {
+            details.push_str(&format!("time: {} (unix)\n", elapsed.as_secs()));
+        }
+        details.push_str(&format!(
+            "version: {} {}\n",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION")
+        ));
+        if !self.backtrace.is_empty() {
+            details.push_str("\nbacktrace:\n");
+            details.push_str(&self.backtrace);
+        }
+        details
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CrashReporterConfig {
+    log_path: Option<PathBuf>,
+}
+
+impl CrashReporterConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append each report to this file.
+    pub fn log_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.log_path = Some(path.into());
+        self
+    }
+}
+
+fn payload_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+fn append_log(path: &PathBuf, report: &CrashReport) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "----\n{}", report.details())
+}
+
+/// Record panics as [`CrashReport`]s. Call once, early in `main`.
+pub fn install_panic_hook(config: CrashReporterConfig) {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let report = CrashReport {
+            message: payload_message(info.payload()),
+            location: info
+                .location()
+                .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+            thread: std::thread::current().name().map(str::to_string),
+            backtrace: Backtrace::force_capture().to_string(),
+            timestamp: SystemTime::now(),
+        };
+        if let Some(path) = &config.log_path {
+            let _ = append_log(path, &report);
+        }
+        if let Ok(mut last) = LAST_CRASH.lock() {
+            *last = Some(report);
+        }
+        previous(info);
+    }));
+}
+
+/// The most recent panic, if any.
+pub fn last_crash() -> Option<CrashReport> {
+    LAST_CRASH.lock().ok().and_then(|last| last.clone())
+}
+
+/// Clear and return the most recent panic, e.g. when the user dismisses the overlay.
+pub fn take_crash() -> Option<CrashReport> {
+    LAST_CRASH.lock().ok().and_then(|mut last| last.take())
+}
+
+/// Run `f`, turning a panic into the recorded [`CrashReport`].
+pub fn catch_crash<R>(f: impl FnOnce() -> R) -> Result<R, CrashReport> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        last_crash().unwrap_or_else(|| {
+            let report = CrashReport::from_payload(payload.as_ref());
+            if let Ok(mut last) = LAST_CRASH.lock() {
+                *last = Some(report.clone());
+            }
+            report
+        })
+    })
+}
+
+/// Relaunch the current executable with the same arguments and quit this instance.
+pub fn restart_app(cx: &mut gpui::App) {
+    if let Ok(exe) = std::env::current_exe() {
+        let _ = std::process::Command::new(exe)
+            .args(std::env::args_os().skip(1))
+            .spawn();
+    }
+    cx.quit();
+}
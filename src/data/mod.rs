@@ -0,0 +1,7 @@
+//! Data sources that back tables and lists with storage outside the app.
+
+pub mod sqlite;
+
+pub use sqlite::{
+    ColumnInfo, QueryEvent, QueryModel, QuerySource, SqlError, SqlRow, SqlValue, TableInfo,
+};
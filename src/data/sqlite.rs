@@ -0,0 +1,762 @@
+//! SQLite tables and queries as a paged data source for [`DataTable`].
+//!
+//! A [`QueryModel`] opens a database file read-only on its own thread and answers
+//! page requests with `LIMIT`/`OFFSET` queries, so a table with millions of rows only
+//! ever loads what is on screen. Sorting and searching are pushed down as `ORDER BY`
+//! and `LIKE` clauses instead of being applied to loaded rows, and column definitions
+//! come from the schema.
+//!
+//! ```rust,ignore
+//! let model = cx.new(|cx| {
+//!     QueryModel::new("app.db", QuerySource::table("events"), cx)
+//! });
+//! let table = QueryModel::data_table(&model, 200, cx);
+//!
+//! // list tables and views for a sidebar
+//! let tables = model.read(cx).tables(cx);
+//! ```
+
+use crate::display::data_table::{ColumnDef, DataTable, SortDirection};
+use futures::channel::oneshot;
+use gpui::*;
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags};
+use std::fmt;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SqlError {
+    /// The database file couldn't be opened.
+    Open(SharedString),
+    Query(SharedString),
+    /// The query thread has stopped.
+    Closed,
+}
+
+impl fmt::Display for SqlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SqlError::Open(message) => write!(f, "could not open database: {}", message),
+            SqlError::Query(message) => write!(f, "query failed: {}", message),
+            SqlError::Closed => write!(f, "database connection closed"),
+        }
+    }
+}
+
+impl std::error::Error for SqlError {}
+
+fn query_error(err: rusqlite::Error) -> SqlError {
+    SqlError::Query(err.to_string().into())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqlValue {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(SharedString),
+    Blob(Arc<[u8]>),
+}
+
+impl fmt::Display for SqlValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SqlValue::Null => write!(f, "NULL"),
+            SqlValue::Integer(value) => write!(f, "{}", value),
+            SqlValue::Real(value) => write!(f, "{}", value),
+            SqlValue::Text(value) => write!(f, "{}", value),
+            SqlValue::Blob(bytes) => write!(f, "<{} bytes>", bytes.len()),
+        }
+    }
+}
+
+impl From<ValueRef<'_>> for SqlValue {
+    fn from(value: ValueRef<'_>) -> Self {
+        match value {
+            ValueRef::Null => SqlValue::Null,
+            ValueRef::Integer(value) => SqlValue::Integer(value),
+            ValueRef::Real(value) => SqlValue::Real(value),
+            ValueRef::Text(bytes) => {
+                SqlValue::Text(String::from_utf8_lossy(bytes).into_owned().into())
+            }
+            ValueRef::Blob(bytes) => SqlValue::Blob(bytes.into()),
+        }
+    }
+}
+
+/// One result row, in column order. Cheap to clone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqlRow(Arc<[SqlValue]>);
+
+impl SqlRow {
+    pub fn get(&self, column: usize) -> Option<&SqlValue> {
+        self.0.get(column)
+    }
+
+    pub fn values(&self) -> &[SqlValue] {
+        &self.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnInfo {
+    pub name: SharedString,
+    /// The declared type, e.g. `INTEGER` or `VARCHAR(64)`. `None` for expressions in
+    /// a query and for untyped columns.
+    pub decl_type: Option<SharedString>,
+    pub not_null: bool,
+    pub primary_key: bool,
+}
+
+impl ColumnInfo {
+    /// Whether SQLite's type affinity rules make this an integer or real column.
+    pub fn is_numeric(&self) -> bool {
+        let Some(decl_type) = &self.decl_type else {
+            return false;
+        };
+        let decl_type = decl_type.to_ascii_lowercase();
+        if decl_type.contains("int") {
+            return true;
+        }
+        if ["char", "clob", "text", "blob"]
+            .iter()
+            .any(|text| decl_type.contains(text))
+        {
+            return false;
+        }
+        ["real", "floa", "doub", "num", "dec"]
+            .iter()
+            .any(|number| decl_type.contains(number))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableInfo {
+    pub name: SharedString,
+    pub is_view: bool,
+    pub columns: Vec<ColumnInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuerySource {
+    /// Every row of a table or view.
+    Table(SharedString),
+    /// The rows of a `SELECT` statement, which is wrapped in a subquery for paging,
+    /// sorting and filtering.
+    Sql(SharedString),
+}
+
+impl QuerySource {
+    pub fn table(name: impl Into<SharedString>) -> Self {
+        Self::Table(name.into())
+    }
+
+    pub fn sql(sql: impl Into<SharedString>) -> Self {
+        Self::Sql(sql.into())
+    }
+
+    fn from_clause(&self) -> String {
+        match self {
+            QuerySource::Table(name) => quote_identifier(name),
+            QuerySource::Sql(sql) => format!("({}) AS q", sql.trim().trim_end_matches(';')),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryEvent {
+    /// The columns changed; column definitions should be rebuilt.
+    SchemaChanged,
+    /// The sort, filter or source changed and the row count is known. Loaded pages
+    /// are out of date.
+    Reset {
+        total_rows: usize,
+    },
+    Failed(SqlError),
+}
+
+type Job = Box<dyn FnOnce(Result<&Connection, &SqlError>) + Send>;
+
+/// A thread that owns the connection and runs jobs in order. It exits when the model
+/// that owns the sender is dropped.
+struct Worker {
+    jobs: std::sync::mpsc::Sender<Job>,
+}
+
+impl Worker {
+    fn spawn(path: PathBuf) -> Self {
+        let (jobs, receiver) = std::sync::mpsc::channel::<Job>();
+        let spawned = std::thread::Builder::new()
+            .name("sqlite-query".into())
+            .spawn(move || {
+                let connection = Connection::open_with_flags(
+                    &path,
+                    OpenFlags::SQLITE_OPEN_READ_ONLY
+                        | OpenFlags::SQLITE_OPEN_URI
+                        | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+                )
+                .map_err(|err| SqlError::Open(err.to_string().into()));
+                for job in receiver {
+                    job(connection.as_ref());
+                }
+            });
+        if let Err(err) = spawned {
+            tracing::error!(%err, "failed to start SQLite query thread");
+        }
+        Self { jobs }
+    }
+
+    fn run<R: Send + 'static>(
+        &self,
+        job: impl FnOnce(&Connection) -> Result<R, SqlError> + Send + 'static,
+    ) -> impl Future<Output = Result<R, SqlError>> + Send + 'static {
+        let (tx, rx) = oneshot::channel();
+        let sent = self
+            .jobs
+            .send(Box::new(
+                move |connection: Result<&Connection, &SqlError>| {
+                    let _ = tx.send(connection.map_err(Clone::clone).and_then(job));
+                },
+            ))
+            .is_ok();
+        async move {
+            if !sent {
+                return Err(SqlError::Closed);
+            }
+            rx.await.map_err(|_| SqlError::Closed)?
+        }
+    }
+}
+
+/// SQL for one combination of source, sort and filter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct QueryPlan {
+    from: String,
+    order_by: Option<String>,
+    filter_columns: Vec<String>,
+    pattern: Option<String>,
+}
+
+impl QueryPlan {
+    fn new(
+        source: &QuerySource,
+        columns: &[ColumnInfo],
+        sort: Option<&(SharedString, SortDirection)>,
+        filter: &str,
+        filter_column: Option<&SharedString>,
+    ) -> Self {
+        let order_by = sort
+            .filter(|(name, _)| columns.iter().any(|column| &column.name == name))
+            .map(|(name, direction)| {
+                let direction = match direction {
+                    SortDirection::Ascending => "ASC",
+                    SortDirection::Descending => "DESC",
+                };
+                format!("{} {}", quote_identifier(name), direction)
+            });
+        let filter_columns = columns
+            .iter()
+            .filter(|column| filter_column.map_or(true, |name| &column.name == name))
+            .map(|column| quote_identifier(&column.name))
+            .collect::<Vec<_>>();
+        let pattern =
+            (!filter.is_empty() && !filter_columns.is_empty()).then(|| like_pattern(filter));
+        Self {
+            from: source.from_clause(),
+            order_by,
+            filter_columns,
+            pattern,
+        }
+    }
+
+    fn where_clause(&self) -> String {
+        if self.pattern.is_none() {
+            return String::new();
+        }
+        let conditions = self
+            .filter_columns
+            .iter()
+            .map(|column| format!("CAST({} AS TEXT) LIKE ?1 ESCAPE '\\'", column))
+            .collect::<Vec<_>>();
+        format!(" WHERE {}", conditions.join(" OR "))
+    }
+
+    fn count_sql(&self) -> String {
+        format!("SELECT COUNT(*) FROM {}{}", self.from, self.where_clause())
+    }
+
+    fn page_sql(&self, offset: usize, limit: usize) -> String {
+        let order_by = self
+            .order_by
+            .as_ref()
+            .map(|order_by| format!(" ORDER BY {}", order_by))
+            .unwrap_or_default();
+        format!(
+            "SELECT * FROM {}{}{} LIMIT {} OFFSET {}",
+            self.from,
+            self.where_clause(),
+            order_by,
+            limit,
+            offset
+        )
+    }
+
+    fn params(&self) -> Vec<String> {
+        self.pattern.iter().cloned().collect()
+    }
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// A `LIKE` pattern matching `text` anywhere, with wildcards in it escaped by `\`.
+fn like_pattern(text: &str) -> String {
+    let mut pattern = String::with_capacity(text.len() + 2);
+    pattern.push('%');
+    for ch in text.chars() {
+        if matches!(ch, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(ch);
+    }
+    pattern.push('%');
+    pattern
+}
+
+fn table_columns(connection: &Connection, table: &str) -> Result<Vec<ColumnInfo>, SqlError> {
+    let mut statement = connection
+        .prepare(&format!("PRAGMA table_info({})", quote_identifier(table)))
+        .map_err(query_error)?;
+    let columns = statement
+        .query_map([], |row| {
+            let decl_type: String = row.get(2)?;
+            Ok(ColumnInfo {
+                name: row.get::<_, String>(1)?.into(),
+                decl_type: (!decl_type.is_empty()).then(|| decl_type.into()),
+                not_null: row.get(3)?,
+                primary_key: row.get::<_, i64>(5)? > 0,
+            })
+        })
+        .map_err(query_error)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(query_error)?;
+    if columns.is_empty() {
+        return Err(SqlError::Query(format!("no such table: {}", table).into()));
+    }
+    Ok(columns)
+}
+
+fn source_columns(
+    connection: &Connection,
+    source: &QuerySource,
+) -> Result<Vec<ColumnInfo>, SqlError> {
+    match source {
+        QuerySource::Table(name) => table_columns(connection, name),
+        QuerySource::Sql(_) => {
+            let statement = connection
+                .prepare(&format!("SELECT * FROM {} LIMIT 0", source.from_clause()))
+                .map_err(query_error)?;
+            Ok(statement
+                .column_names()
+                .into_iter()
+                .map(|name| ColumnInfo {
+                    name: name.to_string().into(),
+                    decl_type: None,
+                    not_null: false,
+                    primary_key: false,
+                })
+                .collect())
+        }
+    }
+}
+
+fn read_rows(
+    connection: &Connection,
+    sql: &str,
+    params: &[String],
+) -> Result<Vec<SqlRow>, SqlError> {
+    let mut statement = connection.prepare_cached(sql).map_err(query_error)?;
+    let column_count = statement.column_count();
+    let mut rows = statement
+        .query(rusqlite::params_from_iter(params))
+        .map_err(query_error)?;
+    let mut result = Vec::new();
+    while let Some(row) = rows.next().map_err(query_error)? {
+        let values = (0..column_count)
+            .map(|index| row.get_ref(index).map(SqlValue::from))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(query_error)?;
+        result.push(SqlRow(values.into()));
+    }
+    Ok(result)
+}
+
+/// Rows of a SQLite table or query, loaded a page at a time on a background thread.
+pub struct QueryModel {
+    path: PathBuf,
+    source: QuerySource,
+    worker: Worker,
+    columns: Vec<ColumnInfo>,
+    plan: Option<QueryPlan>,
+    sort: Option<(SharedString, SortDirection)>,
+    filter: SharedString,
+    filter_column: Option<SharedString>,
+    total_rows: usize,
+    loading: bool,
+    error: Option<SqlError>,
+    generation: u64,
+    _reload: Option<Task<()>>,
+}
+
+impl EventEmitter<QueryEvent> for QueryModel {}
+
+impl QueryModel {
+    /// Open `path` read-only and start loading the schema and row count of `source`.
+    pub fn new(path: impl Into<PathBuf>, source: QuerySource, cx: &mut Context<Self>) -> Self {
+        let path = path.into();
+        let mut this = Self {
+            worker: Worker::spawn(path.clone()),
+            path,
+            source,
+            columns: Vec::new(),
+            plan: None,
+            sort: None,
+            filter: SharedString::default(),
+            filter_column: None,
+            total_rows: 0,
+            loading: false,
+            error: None,
+            generation: 0,
+            _reload: None,
+        };
+        this.reload(cx);
+        this
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn source(&self) -> &QuerySource {
+        &self.source
+    }
+
+    /// The columns of the source. Empty until the schema has loaded.
+    pub fn columns(&self) -> &[ColumnInfo] {
+        &self.columns
+    }
+
+    /// Rows matching the current filter.
+    pub fn total_rows(&self) -> usize {
+        self.total_rows
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.loading
+    }
+
+    pub fn error(&self) -> Option<&SqlError> {
+        self.error.as_ref()
+    }
+
+    pub fn set_source(&mut self, source: QuerySource, cx: &mut Context<Self>) {
+        self.source = source;
+        self.sort = None;
+        self.filter_column = None;
+        self.reload(cx);
+    }
+
+    /// Order rows by a column, or by the source's own order with `None`.
+    pub fn set_sort(
+        &mut self,
+        sort: Option<(SharedString, SortDirection)>,
+        cx: &mut Context<Self>,
+    ) {
+        if self.sort != sort {
+            self.sort = sort;
+            self.reload(cx);
+        }
+    }
+
+    /// Keep rows where `column`, or any column when `None`, contains `text`. Matching
+    /// is case-insensitive for ASCII.
+    pub fn set_filter(
+        &mut self,
+        text: impl Into<SharedString>,
+        column: Option<SharedString>,
+        cx: &mut Context<Self>,
+    ) {
+        let text = text.into();
+        if self.filter != text || self.filter_column != column {
+            self.filter = text;
+            self.filter_column = column;
+            self.reload(cx);
+        }
+    }
+
+    /// Reload the schema and row count, e.g. after the file was written to elsewhere.
+    pub fn reload(&mut self, cx: &mut Context<Self>) {
+        self.generation += 1;
+        let generation = self.generation;
+        self.plan = None;
+        self.loading = true;
+
+        let source = self.source.clone();
+        let sort = self.sort.clone();
+        let filter = self.filter.clone();
+        let filter_column = self.filter_column.clone();
+        let job = self.worker.run(move |connection| {
+            let columns = source_columns(connection, &source)?;
+            let plan = QueryPlan::new(
+                &source,
+                &columns,
+                sort.as_ref(),
+                &filter,
+                filter_column.as_ref(),
+            );
+            let total_rows: i64 = connection
+                .query_row(
+                    &plan.count_sql(),
+                    rusqlite::params_from_iter(plan.params()),
+                    |row| row.get(0),
+                )
+                .map_err(query_error)?;
+            Ok((columns, plan, total_rows.max(0) as usize))
+        });
+
+        self._reload = Some(cx.spawn(async move |this, cx| {
+            let result = job.await;
+            this.update(cx, |this, cx| {
+                if this.generation != generation {
+                    return;
+                }
+                this.loading = false;
+                match result {
+                    Ok((columns, plan, total_rows)) => {
+                        this.error = None;
+                        let schema_changed = this.columns != columns;
+                        this.columns = columns;
+                        this.plan = Some(plan);
+                        this.total_rows = total_rows;
+                        if schema_changed {
+                            cx.emit(QueryEvent::SchemaChanged);
+                        }
+                        cx.emit(QueryEvent::Reset { total_rows });
+                    }
+                    Err(err) => this.fail(err, cx),
+                }
+                cx.notify();
+            })
+            .ok();
+        }));
+        cx.notify();
+    }
+
+    /// Load `limit` rows starting at `offset` in the current order. Resolves to `None`
+    /// if the query failed or the sort, filter or source changed in the meantime.
+    pub fn fetch_rows(
+        &mut self,
+        offset: usize,
+        limit: usize,
+        cx: &mut Context<Self>,
+    ) -> Task<Option<Vec<SqlRow>>> {
+        let Some(plan) = &self.plan else {
+            return Task::ready(None);
+        };
+        let generation = self.generation;
+        let sql = plan.page_sql(offset, limit);
+        let params = plan.params();
+        let job = self
+            .worker
+            .run(move |connection| read_rows(connection, &sql, &params));
+        cx.spawn(async move |this, cx| {
+            let result = job.await;
+            this.update(cx, |this, cx| {
+                if this.generation != generation {
+                    return None;
+                }
+                match result {
+                    Ok(rows) => Some(rows),
+                    Err(err) => {
+                        this.fail(err, cx);
+                        None
+                    }
+                }
+            })
+            .ok()
+            .flatten()
+        })
+    }
+
+    /// The tables and views in the database with their columns, skipping SQLite's own.
+    pub fn tables(&self, cx: &App) -> Task<Result<Vec<TableInfo>, SqlError>> {
+        let job = self.worker.run(|connection| {
+            let mut statement = connection
+                .prepare(
+                    "SELECT name, type FROM sqlite_master \
+                     WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' \
+                     ORDER BY name",
+                )
+                .map_err(query_error)?;
+            let names = statement
+                .query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                })
+                .map_err(query_error)?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(query_error)?;
+            names
+                .into_iter()
+                .map(|(name, kind)| {
+                    Ok(TableInfo {
+                        columns: table_columns(connection, &name)?,
+                        is_view: kind == "view",
+                        name: name.into(),
+                    })
+                })
+                .collect()
+        });
+        cx.background_executor().spawn(job)
+    }
+
+    /// Column definitions for the current columns, with numeric columns narrower.
+    pub fn column_defs(&self) -> Vec<ColumnDef<SqlRow>> {
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| {
+                let width = if column.is_numeric() { 110.0 } else { 200.0 };
+                ColumnDef::new(
+                    column.name.clone(),
+                    column.name.clone(),
+                    move |row: &SqlRow| {
+                        row.get(index)
+                            .map(|value| SharedString::from(value.to_string()))
+                            .unwrap_or_default()
+                    },
+                )
+                .width(px(width))
+            })
+            .collect()
+    }
+
+    /// A virtual [`DataTable`] over `model`. It fetches pages of `page_size` rows as
+    /// they scroll into view, sends header sorting and the search box to the model,
+    /// and rebuilds its columns when the schema changes.
+    pub fn data_table(
+        model: &Entity<Self>,
+        page_size: usize,
+        cx: &mut App,
+    ) -> Entity<DataTable<SqlRow>> {
+        let (columns, total_rows) = {
+            let model = model.read(cx);
+            (model.column_defs(), model.total_rows)
+        };
+        cx.new(|cx| {
+            cx.subscribe(model, |table, model, event: &QueryEvent, cx| match event {
+                QueryEvent::SchemaChanged => {
+                    let columns = model.read(cx).column_defs();
+                    table.set_columns(columns, cx);
+                }
+                QueryEvent::Reset { total_rows } => table.virtual_reset(*total_rows, None, cx),
+                QueryEvent::Failed(_) => {}
+            })
+            .detach();
+
+            let fetch_model = model.clone();
+            let sort_model = model.clone();
+            let search_model = model.clone();
+            DataTable::new_virtual(total_rows, columns, page_size, cx)
+                .on_fetch_page(move |start, len, _window, cx| {
+                    let rows = fetch_model.update(cx, |model, cx| model.fetch_rows(start, len, cx));
+                    cx.spawn(async move |table, cx| {
+                        if let Some(rows) = rows.await {
+                            table
+                                .update(cx, |table, cx| table.set_page_data(start, rows, cx))
+                                .ok();
+                        }
+                    })
+                    .detach();
+                })
+                .on_sort_change(move |column, direction, cx| {
+                    sort_model.update(cx, |model, cx| {
+                        model.set_sort(Some((column, direction)), cx)
+                    });
+                })
+                .on_search_change(move |text, column, cx| {
+                    search_model.update(cx, |model, cx| model.set_filter(text, column, cx));
+                })
+        })
+    }
+
+    fn fail(&mut self, err: SqlError, cx: &mut Context<Self>) {
+        tracing::warn!(path = %self.path.display(), %err, "SQLite query failed");
+        self.error = Some(err.clone());
+        cx.emit(QueryEvent::Failed(err));
+        cx.notify();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str) -> ColumnInfo {
+        ColumnInfo {
+            name: name.to_string().into(),
+            decl_type: None,
+            not_null: false,
+            primary_key: false,
+        }
+    }
+
+    #[test]
+    fn builds_paged_queries() {
+        let columns = [column("id"), column("na\"me")];
+        let source = QuerySource::table("users");
+        let sort = (SharedString::from("na\"me"), SortDirection::Descending);
+
+        let plan = QueryPlan::new(&source, &columns, Some(&sort), "", None);
+        assert_eq!(plan.count_sql(), "SELECT COUNT(*) FROM \"users\"");
+        assert_eq!(
+            plan.page_sql(200, 100),
+            "SELECT * FROM \"users\" ORDER BY \"na\"\"me\" DESC LIMIT 100 OFFSET 200"
+        );
+        assert!(plan.params().is_empty());
+
+        let plan = QueryPlan::new(&source, &columns, None, "5%_", Some(&"id".into()));
+        assert_eq!(
+            plan.count_sql(),
+            "SELECT COUNT(*) FROM \"users\" WHERE CAST(\"id\" AS TEXT) LIKE ?1 ESCAPE '\\'"
+        );
+        assert_eq!(plan.params(), ["%5\\%\\_%"]);
+    }
+
+    #[test]
+    fn wraps_sql_sources_and_ignores_unknown_sort_columns() {
+        let source = QuerySource::sql("SELECT a, b FROM t;");
+        let sort = (SharedString::from("missing"), SortDirection::Ascending);
+        let plan = QueryPlan::new(&source, &[column("a"), column("b")], Some(&sort), "x", None);
+        assert_eq!(
+            plan.page_sql(0, 10),
+            "SELECT * FROM (SELECT a, b FROM t) AS q WHERE CAST(\"a\" AS TEXT) LIKE ?1 \
+             ESCAPE '\\' OR CAST(\"b\" AS TEXT) LIKE ?1 ESCAPE '\\' LIMIT 10 OFFSET 0"
+        );
+    }
+
+    #[test]
+    fn numeric_affinity() {
+        let typed = |decl_type: &str| ColumnInfo {
+            decl_type: Some(decl_type.to_string().into()),
+            ..column("c")
+        };
+        assert!(typed("INTEGER").is_numeric());
+        assert!(typed("DECIMAL(10,2)").is_numeric());
+        assert!(!typed("VARCHAR(20)").is_numeric());
+        assert!(!typed("BLOB").is_numeric());
+        assert!(!column("c").is_numeric());
+    }
+}
@@ -0,0 +1,330 @@
+//! Custom URL scheme activation (`myapp://open?file=notes.md`).
+//!
+//! [`register_scheme`] makes the OS launch the app for the scheme: through Launch
+//! Services on macOS, a `x-scheme-handler` desktop entry on Linux and the per-user
+//! registry on Windows. Activation URLs reach the app in one of three ways, and all of
+//! them end up in the handlers registered with [`on_deeplink`]:
+//!
+//! - macOS delivers them to the running app; call [`listen`] before `Application::run`.
+//! - Linux and Windows pass them as a command-line argument to a new process;
+//!   [`register_scheme`] picks them up from `std::env::args`.
+//! - Anything else, such as a URL forwarded from a second instance, goes through
+//!   [`deliver`].
+//!
+//! URLs that arrive before a matching handler is registered are queued until one is.
+//!
+//! ```rust,ignore
+//! let app = Application::new();
+//! deeplink::listen(&app);
+//! app.run(|cx| {
+//!     adabraka_ui::init(cx);
+//!     deeplink::register_scheme("myapp", cx).detach();
+//!     deeplink::on_deeplink("open", |link, cx| open_file(link.param("file"), cx), cx);
+//!     deeplink::on_deeplink("auth", |link, cx| finish_login(link.param("code"), cx), cx);
+//! });
+//! ```
+
+use crate::util::{parse_query, percent_decode};
+use gpui::*;
+use once_cell::sync::Lazy;
+use std::fmt;
+use std::rc::Rc;
+
+static INCOMING: Lazy<(
+    smol::channel::Sender<String>,
+    smol::channel::Receiver<String>,
+)> = Lazy::new(smol::channel::unbounded);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeepLinkError {
+    /// The text is not a `scheme://...` URL.
+    InvalidUrl(SharedString),
+    /// The URL uses a scheme other than the registered one.
+    UnknownScheme(SharedString),
+    /// The OS refused to register the scheme.
+    Registration(SharedString),
+}
+
+impl fmt::Display for DeepLinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeepLinkError::InvalidUrl(url) => write!(f, "invalid deep link: {}", url),
+            DeepLinkError::UnknownScheme(scheme) => write!(f, "unknown URL scheme: {}", scheme),
+            DeepLinkError::Registration(message) => {
+                write!(f, "failed to register URL scheme: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DeepLinkError {}
+
+/// A parsed activation URL. For `myapp://open/recent?file=a%20b.md#top` the action is
+/// `open`, the path `/recent`, the query `[("file", "a b.md")]` and the fragment `top`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeepLink {
+    pub url: String,
+    /// Lowercased scheme without `://`.
+    pub scheme: String,
+    /// The host part, used to pick the handler.
+    pub action: String,
+    pub path: String,
+    /// Percent-decoded query parameters in order of appearance.
+    pub query: Vec<(String, String)>,
+    pub fragment: Option<String>,
+}
+
+impl DeepLink {
+    pub fn parse(url: &str) -> Result<Self, DeepLinkError> {
+        let invalid = || DeepLinkError::InvalidUrl(url.to_string().into());
+        let url = url.trim();
+        let (scheme, rest) = url.split_once("://").ok_or_else(invalid)?;
+        if !is_valid_scheme(scheme) {
+            return Err(invalid());
+        }
+
+        let (rest, fragment) = match rest.split_once('#') {
+            Some((rest, fragment)) => (rest, Some(percent_decode(fragment, false))),
+            None => (rest, None),
+        };
+        let (rest, query) = match rest.split_once('?') {
+            Some((rest, query)) => (rest, parse_query(query)),
+            None => (rest, Vec::new()),
+        };
+        let (action, path) = match rest.find('/') {
+            Some(slash) => (&rest[..slash], &rest[slash..]),
+            None => (rest, ""),
+        };
+
+        Ok(Self {
+            url: url.to_string(),
+            scheme: scheme.to_ascii_lowercase(),
+            action: percent_decode(action, false),
+            path: percent_decode(path, false),
+            query,
+            fragment,
+        })
+    }
+
+    /// First value of the query parameter `key`.
+    pub fn param(&self, key: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+fn is_valid_scheme(scheme: &str) -> bool {
+    let mut chars = scheme.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+type DeepLinkHandler = Rc<dyn Fn(&DeepLink, &mut App)>;
+
+#[derive(Default)]
+struct DeepLinkRegistry {
+    scheme: Option<String>,
+    handlers: Vec<(SharedString, DeepLinkHandler)>,
+    fallback: Option<DeepLinkHandler>,
+    pending: Vec<DeepLink>,
+    _listener: Option<Task<()>>,
+}
+
+impl DeepLinkRegistry {
+    fn handler_for(&self, action: &str) -> Option<DeepLinkHandler> {
+        self.handlers
+            .iter()
+            .find(|(name, _)| name.as_ref() == action)
+            .map(|(_, handler)| handler.clone())
+            .or_else(|| self.fallback.clone())
+    }
+}
+
+impl Global for DeepLinkRegistry {}
+
+/// Forward URLs the OS opens the app with. Call before `Application::run`.
+pub fn listen(app: &Application) {
+    app.on_open_urls(|urls| {
+        for url in urls {
+            deliver(url);
+        }
+    });
+}
+
+/// Queue an activation URL for dispatch on the main thread. Safe to call from any thread.
+pub fn deliver(url: impl Into<String>) {
+    let _ = INCOMING.0.try_send(url.into());
+}
+
+/// Register `scheme` with the OS and start dispatching activation URLs, including any
+/// passed on this process's command line.
+pub fn register_scheme(scheme: impl Into<String>, cx: &mut App) -> Task<Result<(), DeepLinkError>> {
+    let scheme = scheme.into().to_ascii_lowercase();
+    ensure_listening(cx);
+    cx.default_global::<DeepLinkRegistry>().scheme = Some(scheme.clone());
+
+    let prefix = format!("{}://", scheme);
+    for arg in std::env::args().skip(1) {
+        if arg.to_ascii_lowercase().starts_with(&prefix) {
+            deliver(arg);
+        }
+    }
+
+    if !is_valid_scheme(&scheme) {
+        return Task::ready(Err(DeepLinkError::Registration(
+            format!("'{}' is not a valid scheme", scheme).into(),
+        )));
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let registration = cx.register_url_scheme(&scheme);
+        cx.background_executor().spawn(async move {
+            registration
+                .await
+                .map_err(|err| DeepLinkError::Registration(err.to_string().into()))
+        })
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        cx.background_executor()
+            .spawn(async move { register_with_os(&scheme) })
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn register_with_os(scheme: &str) -> Result<(), DeepLinkError> {
+    crate::file_associations::AppRegistration::new(format!("{}-url-handler", scheme), scheme)
+        .set_default_for_protocol(scheme)
+        .map_err(|err| DeepLinkError::Registration(err.to_string().into()))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn register_with_os(_scheme: &str) -> Result<(), DeepLinkError> {
+    Err(DeepLinkError::Registration(
+        "URL schemes are not supported on this platform".into(),
+    ))
+}
+
+fn ensure_listening(cx: &mut App) {
+    if cx
+        .try_global::<DeepLinkRegistry>()
+        .is_some_and(|registry| registry._listener.is_some())
+    {
+        return;
+    }
+    let receiver = INCOMING.1.clone();
+    let listener = cx.spawn(async move |cx| {
+        while let Ok(url) = receiver.recv().await {
+            if cx.update(|cx| dispatch_url(&url, cx)).is_err() {
+                break;
+            }
+        }
+    });
+    cx.default_global::<DeepLinkRegistry>()._listener = Some(listener);
+}
+
+/// Handle `action` links (the host part of the URL). Queued links for that action are
+/// dispatched immediately.
+pub fn on_deeplink(
+    action: impl Into<SharedString>,
+    handler: impl Fn(&DeepLink, &mut App) + 'static,
+    cx: &mut App,
+) {
+    ensure_listening(cx);
+    let action = action.into();
+    let registry = cx.default_global::<DeepLinkRegistry>();
+    registry.handlers.retain(|(name, _)| *name != action);
+    registry.handlers.push((action.clone(), Rc::new(handler)));
+    let pending = std::mem::take(&mut registry.pending);
+    let (ready, waiting): (Vec<_>, Vec<_>) = pending
+        .into_iter()
+        .partition(|link| link.action == action.as_ref());
+    registry.pending = waiting;
+    for link in ready {
+        dispatch(link, cx);
+    }
+}
+
+/// Handle links whose action has no dedicated handler.
+pub fn on_unhandled_deeplink(handler: impl Fn(&DeepLink, &mut App) + 'static, cx: &mut App) {
+    ensure_listening(cx);
+    let registry = cx.default_global::<DeepLinkRegistry>();
+    registry.fallback = Some(Rc::new(handler));
+    let pending = std::mem::take(&mut registry.pending);
+    for link in pending {
+        dispatch(link, cx);
+    }
+}
+
+/// Parse and dispatch `url` now.
+pub fn dispatch_url(url: &str, cx: &mut App) -> Result<(), DeepLinkError> {
+    let link = DeepLink::parse(url).map_err(|err| {
+        tracing::warn!(%err, "ignoring deep link");
+        err
+    })?;
+    let expected = cx
+        .try_global::<DeepLinkRegistry>()
+        .and_then(|registry| registry.scheme.clone());
+    if let Some(expected) = expected {
+        if link.scheme != expected {
+            tracing::warn!(scheme = %link.scheme, "ignoring deep link with unknown scheme");
+            return Err(DeepLinkError::UnknownScheme(link.scheme.into()));
+        }
+    }
+    dispatch(link, cx);
+    Ok(())
+}
+
+fn dispatch(link: DeepLink, cx: &mut App) {
+    let handler = cx
+        .default_global::<DeepLinkRegistry>()
+        .handler_for(&link.action);
+    match handler {
+        Some(handler) => {
+            tracing::debug!(action = %link.action, "dispatching deep link");
+            handler(&link, cx);
+        }
+        None => cx.default_global::<DeepLinkRegistry>().pending.push(link),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_action_path_query_and_fragment() {
+        let link = DeepLink::parse("MyApp://open/recent?file=a%20b.md&line=3#top").unwrap();
+        assert_eq!(link.scheme, "myapp");
+        assert_eq!(link.action, "open");
+        assert_eq!(link.path, "/recent");
+        assert_eq!(link.param("file"), Some("a b.md"));
+        assert_eq!(link.param("line"), Some("3"));
+        assert_eq!(link.fragment.as_deref(), Some("top"));
+    }
+
+    #[test]
+    fn decodes_plus_only_in_query() {
+        let link = DeepLink::parse("myapp://auth/a+b?code=x+y%2Bz&flag").unwrap();
+        assert_eq!(link.path, "/a+b");
+        assert_eq!(link.param("code"), Some("x y+z"));
+        assert_eq!(link.param("flag"), Some(""));
+    }
+
+    #[test]
+    fn keeps_malformed_escapes() {
+        let link = DeepLink::parse("myapp://open?file=100%&q=%zz").unwrap();
+        assert_eq!(link.param("file"), Some("100%"));
+        assert_eq!(link.param("q"), Some("%zz"));
+    }
+
+    #[test]
+    fn rejects_invalid_urls() {
+        assert!(DeepLink::parse("not a url").is_err());
+        assert!(DeepLink::parse("1app://open").is_err());
+        assert!(DeepLink::parse("://open").is_err());
+    }
+}
@@ -326,6 +326,9 @@ pub struct DataTable<T: Clone + 'static> {
         Box<dyn Fn(&T, SharedString, SharedString, &mut Window, &mut Context<Self>) + 'static>,
     >,
     on_fetch_page: Option<Box<dyn Fn(usize, usize, &mut Window, &mut Context<Self>) + 'static>>,
+    on_sort_change: Option<Box<dyn Fn(SharedString, SortDirection, &mut Context<Self>) + 'static>>,
+    on_search_change:
+        Option<Box<dyn Fn(SharedString, Option<SharedString>, &mut Context<Self>) + 'static>>,
     on_row_click: Option<Box<dyn Fn(usize, &T, &mut Window, &mut Context<Self>) + 'static>>,
     search_query: String,
     search_column: Option<usize>,
@@ -366,7 +369,7 @@ impl<T: Clone + 'static> DataTable<T> {
                     } else {
                         selected
                     };
-                    cx.notify();
+                    this.search_changed(cx);
                 }
             },
         )
@@ -392,6 +395,8 @@ impl<T: Clone + 'static> DataTable<T> {
             on_cell_edit: None,
             on_cell_double_click: None,
             on_fetch_page: None,
+            on_sort_change: None,
+            on_search_change: None,
             on_row_click: None,
             search_query: String::new(),
             search_column: None,
@@ -455,7 +460,7 @@ impl<T: Clone + 'static> DataTable<T> {
                     } else {
                         selected
                     };
-                    cx.notify();
+                    this.search_changed(cx);
                 }
             },
         )
@@ -481,6 +486,8 @@ impl<T: Clone + 'static> DataTable<T> {
             on_cell_edit: None,
             on_cell_double_click: None,
             on_fetch_page: None,
+            on_sort_change: None,
+            on_search_change: None,
             on_row_click: None,
             search_query: String::new(),
             search_column: None,
@@ -579,13 +586,68 @@ impl<T: Clone + 'static> DataTable<T> {
         self
     }
 
+    /// Set callback for when a column header is clicked to sort: (column_id, direction)
+    ///
+    /// Virtual tables can't sort rows they haven't loaded, so use this to re-query the
+    /// source in the new order and call `virtual_reset`.
+    pub fn on_sort_change<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(SharedString, SortDirection, &mut Context<Self>) + 'static,
+    {
+        self.on_sort_change = Some(Box::new(callback));
+        self
+    }
+
+    /// Set callback for when the search text or search column changes: (query, column_id)
+    ///
+    /// `column_id` is `None` when searching all columns. Like `on_sort_change`, this lets
+    /// a virtual table push filtering down to its source.
+    pub fn on_search_change<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(SharedString, Option<SharedString>, &mut Context<Self>) + 'static,
+    {
+        self.on_search_change = Some(Box::new(callback));
+        self
+    }
+
     pub fn set_search(&mut self, query: String, cx: &mut Context<Self>) {
         self.search_query = query;
-        cx.notify();
+        self.search_changed(cx);
     }
 
     pub fn set_search_column(&mut self, column_index: Option<usize>, cx: &mut Context<Self>) {
         self.search_column = column_index;
+        self.search_changed(cx);
+    }
+
+    /// Replace the column definitions, e.g. once a schema has been loaded. Sorting and
+    /// the search column are reset.
+    pub fn set_columns(&mut self, columns: Vec<ColumnDef<T>>, cx: &mut Context<Self>) {
+        let mut select_options = vec![SelectOption::new(usize::MAX, "All Columns")];
+        for (idx, column) in columns.iter().enumerate() {
+            select_options.push(SelectOption::new(idx, column.header.clone()));
+        }
+        self.search_column_select.update(cx, |select, cx| {
+            select.set_options(select_options, cx);
+        });
+
+        self.state.column_widths = columns.iter().map(|col| col.width).collect();
+        self.state.columns = columns;
+        self.state.sort_column = None;
+        self.state.sort_direction = SortDirection::Ascending;
+        self.search_column = None;
+        self.editing_cell = None;
+        cx.notify();
+    }
+
+    fn search_changed(&mut self, cx: &mut Context<Self>) {
+        if let Some(ref callback) = self.on_search_change {
+            let column_id = self
+                .search_column
+                .and_then(|idx| self.state.columns.get(idx))
+                .map(|column| column.id.clone());
+            callback(self.search_query.clone().into(), column_id, cx);
+        }
         cx.notify();
     }
 
@@ -795,7 +857,7 @@ impl<T: Clone + 'static> DataTable<T> {
                             move |value: SharedString, cx| {
                                 entity.update(cx, |this, cx| {
                                     this.search_query = value.to_string();
-                                    cx.notify();
+                                    this.search_changed(cx);
                                 });
                             }
                         }),
@@ -922,6 +984,10 @@ impl<T: Clone + 'static> DataTable<T> {
                             };
 
                             this.state.sort_by_column(col_idx, new_direction);
+                            if let Some(ref callback) = this.on_sort_change {
+                                let column_id = this.state.columns[col_idx].id.clone();
+                                callback(column_id, new_direction, cx);
+                            }
                             cx.notify();
                         }),
                     );
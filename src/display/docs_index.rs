@@ -0,0 +1,488 @@
+//! Full-text search over documentation pages.
+//!
+//! [`DocsIndex`] holds the plain text of each page, as produced by
+//! [`markdown_plain_text`] or [`html_plain_text`], and answers queries with the
+//! matching pages and a snippet around the first match. Every word of the query has
+//! to appear in the page; matches in the title rank above matches in the body.
+
+use std::ops::Range;
+
+/// Characters of context kept before the first match in a snippet.
+const SNIPPET_BEFORE: usize = 40;
+/// Characters kept from the first match on.
+const SNIPPET_AFTER: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocsSearchHit {
+    pub id: String,
+    pub score: usize,
+    /// Text around the first match in the body; empty when only the title matched.
+    pub snippet: String,
+    /// The matched word's byte range in `snippet`.
+    pub highlight: Range<usize>,
+}
+
+struct Entry {
+    id: String,
+    title: String,
+    text: String,
+    lower: FoldedText,
+}
+
+/// Lowercased text, with the offset in the original text of each byte.
+struct FoldedText {
+    title: String,
+    text: String,
+    text_offsets: Vec<usize>,
+}
+
+impl FoldedText {
+    fn new(title: &str, text: &str) -> Self {
+        let mut folded = String::with_capacity(text.len());
+        let mut text_offsets = Vec::with_capacity(text.len());
+        for (offset, ch) in text.char_indices() {
+            for lower in ch.to_lowercase() {
+                folded.push(lower);
+                text_offsets.resize(folded.len(), offset);
+            }
+        }
+        Self {
+            title: title.to_lowercase(),
+            text: folded,
+            text_offsets,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct DocsIndex {
+    entries: Vec<Entry>,
+}
+
+impl DocsIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a page, replacing any page with the same `id`.
+    pub fn insert(&mut self, id: impl Into<String>, title: impl Into<String>, text: String) {
+        let id = id.into();
+        let title = title.into();
+        let entry = Entry {
+            lower: FoldedText::new(&title, &text),
+            id,
+            title,
+            text,
+        };
+        match self.entries.iter_mut().find(|e| e.id == entry.id) {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    pub fn remove(&mut self, id: &str) {
+        self.entries.retain(|entry| entry.id != id);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Pages containing every word of `query`, best first, at most `limit` of them.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<DocsSearchHit> {
+        let query = query.to_lowercase();
+        let terms: Vec<&str> = query.split_whitespace().collect();
+        if terms.is_empty() {
+            return Vec::new();
+        }
+
+        let mut hits: Vec<DocsSearchHit> = self
+            .entries
+            .iter()
+            .filter_map(|entry| score(entry, &query, &terms))
+            .collect();
+        // Stable, so equal scores keep the order the pages were added in.
+        hits.sort_by_key(|hit| std::cmp::Reverse(hit.score));
+        hits.truncate(limit);
+        hits
+    }
+
+    /// The title of the page with `id`.
+    pub fn title(&self, id: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|entry| entry.id == id)
+            .map(|entry| entry.title.as_str())
+    }
+}
+
+fn score(entry: &Entry, query: &str, terms: &[&str]) -> Option<DocsSearchHit> {
+    let lower = &entry.lower;
+    let mut score = 0;
+    for term in terms {
+        let in_title = lower.title.contains(term);
+        let in_body = lower.text.matches(term).take(5).count();
+        if !in_title && in_body == 0 {
+            return None;
+        }
+        score += in_body + if in_title { 10 } else { 0 };
+    }
+    if lower.title.contains(query.trim()) {
+        score += 20;
+    }
+
+    let first = terms
+        .iter()
+        .filter_map(|term| lower.text.find(term).map(|start| start..start + term.len()))
+        .min_by_key(|range| range.start);
+    let (snippet, highlight) = match first {
+        Some(range) => snippet(entry, range),
+        None => (String::new(), 0..0),
+    };
+    Some(DocsSearchHit {
+        id: entry.id.clone(),
+        score,
+        snippet,
+        highlight,
+    })
+}
+
+/// The text around `folded` (a range in the lowercased text) on one line, with the
+/// match's range in it.
+fn snippet(entry: &Entry, folded: Range<usize>) -> (String, Range<usize>) {
+    let offsets = &entry.lower.text_offsets;
+    let text = &entry.text;
+    let start = offsets[folded.start];
+    let end = offsets
+        .get(folded.end)
+        .copied()
+        .unwrap_or(text.len())
+        .max(start);
+
+    let before: Vec<(usize, char)> = text[..start]
+        .char_indices()
+        .rev()
+        .take(SNIPPET_BEFORE)
+        .collect();
+    let from = before.last().map_or(start, |(offset, _)| *offset);
+    let to = text[start..]
+        .char_indices()
+        .nth(SNIPPET_AFTER)
+        .map_or(text.len(), |(offset, _)| start + offset)
+        .max(end);
+
+    let mut snippet = String::new();
+    if from > 0 {
+        snippet.push('…');
+    }
+    let lead = collapse_whitespace(&text[from..start]);
+    snippet.push_str(lead.trim_start());
+    let highlight_start = snippet.len();
+    snippet.push_str(&text[start..end]);
+    let highlight = highlight_start..snippet.len();
+    snippet.push_str(collapse_whitespace(&text[end..to]).trim_end());
+    if to < text.len() {
+        snippet.push('…');
+    }
+    (snippet, highlight)
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_space = false;
+    for ch in text.chars() {
+        if ch.is_whitespace() {
+            if !in_space {
+                out.push(' ');
+            }
+            in_space = true;
+        } else {
+            out.push(ch);
+            in_space = false;
+        }
+    }
+    out
+}
+
+/// The readable text of a markdown document: headings, paragraphs, list items and
+/// code, without the markup. Link and image targets are dropped, their text kept.
+pub fn markdown_plain_text(source: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    let mut in_fence = false;
+    for line in source.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            out.push_str(line.trim_end());
+            out.push('\n');
+            continue;
+        }
+        if is_rule(trimmed) {
+            continue;
+        }
+        let content = strip_block_markers(trimmed);
+        let text = strip_inline_markup(content);
+        let text = text.trim();
+        if !text.is_empty() {
+            out.push_str(text);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn is_rule(line: &str) -> bool {
+    let line = line.trim_end();
+    line.len() >= 3
+        && ['-', '*', '_', '=']
+            .iter()
+            .any(|marker| line.chars().all(|ch| ch == *marker || ch == ' '))
+}
+
+fn strip_block_markers(line: &str) -> &str {
+    let mut line = line;
+    loop {
+        let before = line;
+        line = line.trim_start_matches('#').trim_start();
+        line = line.trim_start_matches('>').trim_start();
+        for marker in ["- [ ] ", "- [x] ", "- [X] ", "- ", "* ", "+ "] {
+            if let Some(rest) = line.strip_prefix(marker) {
+                line = rest;
+            }
+        }
+        let digits = line.chars().take_while(char::is_ascii_digit).count();
+        if digits > 0 {
+            if let Some(rest) = line[digits..]
+                .strip_prefix(". ")
+                .or_else(|| line[digits..].strip_prefix(") "))
+            {
+                line = rest;
+            }
+        }
+        if line == before {
+            return line;
+        }
+    }
+}
+
+fn strip_inline_markup(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '*' | '_' | '`' | '~' => {}
+            '!' if chars.peek() == Some(&'[') => {}
+            '|' => out.push(' '),
+            ']' if chars.peek() == Some(&'(') => {
+                // Skip the link target.
+                let mut depth = 0;
+                for next in chars.by_ref() {
+                    match next {
+                        '(' => depth += 1,
+                        ')' if depth == 1 => break,
+                        ')' => depth -= 1,
+                        _ => {}
+                    }
+                }
+            }
+            '[' | ']' => {}
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    out.push(escaped);
+                }
+            }
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// The readable text of an HTML document, one line per block element. Scripts,
+/// styles and the head are skipped; the common entities are decoded.
+pub fn html_plain_text(source: &str) -> String {
+    const BLOCKS: &[&str] = &[
+        "p",
+        "div",
+        "br",
+        "li",
+        "tr",
+        "h1",
+        "h2",
+        "h3",
+        "h4",
+        "h5",
+        "h6",
+        "pre",
+        "section",
+        "article",
+        "blockquote",
+        "table",
+        "ul",
+        "ol",
+        "dt",
+        "dd",
+    ];
+    const SKIPPED: &[&str] = &["script", "style", "head"];
+
+    let mut out = String::with_capacity(source.len());
+    let mut rest = source;
+    let mut skipping: Option<String> = None;
+    while let Some(open) = rest.find('<') {
+        if skipping.is_none() {
+            push_text(&mut out, &rest[..open]);
+        }
+        let Some(close) = rest[open..].find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = &rest[open + 1..open + close];
+        rest = &rest[open + close + 1..];
+
+        let closing = tag.starts_with('/');
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|ch| ch.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+        if let Some(skipped) = &skipping {
+            if closing && *skipped == name {
+                skipping = None;
+            }
+        } else if !closing && SKIPPED.contains(&name.as_str()) && !tag.ends_with('/') {
+            skipping = Some(name);
+        } else if BLOCKS.contains(&name.as_str()) && !out.is_empty() && !out.ends_with('\n') {
+            out.push('\n');
+        }
+    }
+    if skipping.is_none() {
+        push_text(&mut out, rest);
+    }
+    let mut text: String = out
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    text
+}
+
+fn push_text(out: &mut String, text: &str) {
+    let text = collapse_whitespace(text);
+    out.push_str(&decode_entities(&text));
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markdown_plain_text_drops_markup() {
+        let source = "# Button\n\nA **clickable** [control](button.md).\n\n- one\n- [x] two\n\n---\n\n```rust\nButton::new(\"ok\")\n```\n";
+        assert_eq!(
+            markdown_plain_text(source),
+            "Button\nA clickable control.\none\ntwo\nButton::new(\"ok\")\n"
+        );
+    }
+
+    #[test]
+    fn html_plain_text_skips_scripts_and_splits_blocks() {
+        let source = "<html><head><title>x</title></head><body><h1>Tabs</h1><p>Use &lt;Tabs&gt; for\n   pages.</p><script>alert(1)</script><p>More</p></body></html>";
+        assert_eq!(
+            html_plain_text(source),
+            "Tabs\nUse <Tabs> for pages.\nMore\n"
+        );
+    }
+
+    fn index() -> DocsIndex {
+        let mut index = DocsIndex::new();
+        index.insert(
+            "select",
+            "Select",
+            "A dropdown for choosing one option from a list.\n".to_string(),
+        );
+        index.insert(
+            "combobox",
+            "Combobox",
+            "Like a select, with a text field to filter the options.\n".to_string(),
+        );
+        index.insert("tabs", "Tabs", "Switch between pages.\n".to_string());
+        index
+    }
+
+    #[test]
+    fn search_requires_every_term_and_ranks_titles_first() {
+        let index = index();
+        let ids: Vec<String> = index
+            .search("select", 10)
+            .into_iter()
+            .map(|hit| hit.id)
+            .collect();
+        assert_eq!(ids, ["select", "combobox"]);
+        assert!(index.search("select pages", 10).is_empty());
+        assert!(index.search("   ", 10).is_empty());
+        assert_eq!(index.search("OPTION", 1).len(), 1);
+    }
+
+    #[test]
+    fn snippet_highlights_the_first_match() {
+        let index = index();
+        let hit = &index.search("filter", 10)[0];
+        assert_eq!(hit.id, "combobox");
+        assert_eq!(&hit.snippet[hit.highlight.clone()], "filter");
+        assert!(hit.snippet.starts_with("Like a select"));
+    }
+
+    #[test]
+    fn snippet_is_trimmed_around_long_text() {
+        let mut index = DocsIndex::new();
+        let text = format!("{} needle {}", "word ".repeat(40), "tail ".repeat(40));
+        index.insert("long", "Long", text);
+        let hit = &index.search("NEEDLE", 10)[0];
+        assert!(hit.snippet.starts_with('…'));
+        assert!(hit.snippet.ends_with('…'));
+        assert_eq!(&hit.snippet[hit.highlight.clone()], "needle");
+    }
+
+    #[test]
+    fn snippet_keeps_matches_longer_than_the_context() {
+        let url = format!("https://example.com/{}", "segment/".repeat(20));
+        let mut index = DocsIndex::new();
+        index.insert("links", "Links", format!("See {} for more.", url));
+        let hit = &index.search(&url, 10)[0];
+        assert_eq!(&hit.snippet[hit.highlight.clone()], url);
+    }
+
+    #[test]
+    fn insert_replaces_pages_with_the_same_id() {
+        let mut index = index();
+        index.insert("tabs", "Tab Bar", "Switch views.\n".to_string());
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.title("tabs"), Some("Tab Bar"));
+        assert!(index.search("pages", 10).is_empty());
+        index.remove("tabs");
+        assert_eq!(index.title("tabs"), None);
+    }
+}
@@ -0,0 +1,614 @@
+//! In-app documentation browser.
+//!
+//! Pages are markdown or HTML bundled with the app, usually with `include_str!`, and
+//! added to a shared library with [`add_pages`]. A [`DocsViewer`] shows the library as
+//! a sidebar tree grouped by each page's section, searches the text of every page as
+//! the user types, and renders the selected page. [`crate_docs`] holds the kit's own
+//! guides.
+//!
+//! Components link to their page with a [`DocsLink`], a small "?" button that calls
+//! [`open`]. Every viewer switches to the page, and the handler set with [`on_open`]
+//! runs so the app can bring a viewer up if none is showing. Links between pages use
+//! `doc:<page id>`, e.g. `[see Tabs](doc:tabs)`.
+//!
+//! ```rust,ignore
+//! docs_viewer::add_pages(docs_viewer::crate_docs(), cx);
+//! docs_viewer::add_pages(
+//!     [DocPage::markdown("sync", "Sync", include_str!("../docs/sync.md")).section("Guides")],
+//!     cx,
+//! );
+//! docs_viewer::on_open(|window, cx| open_docs_panel(window, cx), cx);
+//!
+//! let viewer = cx.new(|cx| DocsViewer::new(window, cx));
+//!
+//! // next to a settings field:
+//! div().flex().child("Sync interval").child(DocsLink::new("sync"))
+//! ```
+
+use crate::components::icon::Icon;
+use crate::components::input::Input;
+use crate::components::input_state::{InputEvent, InputState};
+use crate::display::docs_index::{html_plain_text, markdown_plain_text, DocsIndex, DocsSearchHit};
+use crate::display::html::Html;
+use crate::display::markdown::Markdown;
+use crate::theme::use_theme;
+use gpui::{prelude::FluentBuilder as _, *};
+use std::collections::HashSet;
+use std::rc::Rc;
+
+/// Most results listed for a search.
+const MAX_RESULTS: usize = 50;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocFormat {
+    Markdown,
+    Html,
+}
+
+#[derive(Clone, Debug)]
+pub struct DocPage {
+    pub id: SharedString,
+    pub title: SharedString,
+    /// Where the page sits in the sidebar, `/`-separated, e.g. `"Components/Inputs"`.
+    /// Pages without a section are listed first, at the top level.
+    pub section: Option<SharedString>,
+    pub format: DocFormat,
+    pub source: SharedString,
+}
+
+impl DocPage {
+    pub fn markdown(
+        id: impl Into<SharedString>,
+        title: impl Into<SharedString>,
+        source: impl Into<SharedString>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            title: title.into(),
+            section: None,
+            format: DocFormat::Markdown,
+            source: source.into(),
+        }
+    }
+
+    pub fn html(
+        id: impl Into<SharedString>,
+        title: impl Into<SharedString>,
+        source: impl Into<SharedString>,
+    ) -> Self {
+        Self {
+            format: DocFormat::Html,
+            ..Self::markdown(id, title, source)
+        }
+    }
+
+    pub fn section(mut self, section: impl Into<SharedString>) -> Self {
+        self.section = Some(section.into());
+        self
+    }
+
+    fn plain_text(&self) -> String {
+        match self.format {
+            DocFormat::Markdown => markdown_plain_text(&self.source),
+            DocFormat::Html => html_plain_text(&self.source),
+        }
+    }
+}
+
+/// The layout guides shipped with the crate.
+pub fn crate_docs() -> Vec<DocPage> {
+    vec![
+        DocPage::markdown(
+            "layout-guide",
+            "Layout Guide",
+            include_str!("../../LAYOUT_GUIDE.md"),
+        )
+        .section("adabraka-ui"),
+        DocPage::markdown(
+            "layout-quick-reference",
+            "Layout Quick Reference",
+            include_str!("../../LAYOUT_QUICK_REFERENCE.md"),
+        )
+        .section("adabraka-ui"),
+    ]
+}
+
+#[derive(Default)]
+struct DocsLibrary {
+    pages: Vec<DocPage>,
+    index: DocsIndex,
+    /// The page last asked for with [`open`], numbered so viewers can tell a new
+    /// request for the same page from one they've already followed.
+    request: Option<(usize, SharedString)>,
+    on_open: Option<Rc<dyn Fn(&mut Window, &mut App)>>,
+}
+
+impl Global for DocsLibrary {}
+
+/// Add pages to the library, replacing pages with the same ids.
+pub fn add_pages(pages: impl IntoIterator<Item = DocPage>, cx: &mut App) {
+    cx.default_global::<DocsLibrary>();
+    cx.update_global::<DocsLibrary, _>(|library, _| {
+        for page in pages {
+            library.index.insert(
+                page.id.to_string(),
+                page.title.to_string(),
+                page.plain_text(),
+            );
+            match library.pages.iter_mut().find(|p| p.id == page.id) {
+                Some(existing) => *existing = page,
+                None => library.pages.push(page),
+            }
+        }
+    });
+}
+
+pub fn remove_page(id: &str, cx: &mut App) {
+    if !cx.has_global::<DocsLibrary>() {
+        return;
+    }
+    cx.update_global::<DocsLibrary, _>(|library, _| {
+        library.pages.retain(|page| page.id != id);
+        library.index.remove(id);
+    });
+}
+
+pub fn page(id: &str, cx: &App) -> Option<DocPage> {
+    cx.try_global::<DocsLibrary>()?
+        .pages
+        .iter()
+        .find(|page| page.id == id)
+        .cloned()
+}
+
+/// Run `handler` whenever a page is opened with [`open`], e.g. to show the docs
+/// panel. Replaces the handler set before.
+pub fn on_open(handler: impl Fn(&mut Window, &mut App) + 'static, cx: &mut App) {
+    cx.default_global::<DocsLibrary>().on_open = Some(Rc::new(handler));
+}
+
+/// Show the page `id` in every [`DocsViewer`] and run the [`on_open`] handler.
+/// Does nothing for an unknown page.
+pub fn open(id: &str, window: &mut Window, cx: &mut App) {
+    if page(id, cx).is_none() {
+        return;
+    }
+    let id = SharedString::from(id.to_string());
+    let on_open = cx.update_global::<DocsLibrary, _>(|library, _| {
+        let serial = library.request.as_ref().map_or(0, |(serial, _)| serial + 1);
+        library.request = Some((serial, id));
+        library.on_open.clone()
+    });
+    if let Some(on_open) = on_open {
+        on_open(window, cx);
+    }
+}
+
+/// The page id a link in a page points at: `doc:<id>`, or a relative link to a
+/// bundled file such as `tabs.md`, which is taken to be page `tabs`.
+fn linked_page(link: &str) -> Option<&str> {
+    if let Some(id) = link.strip_prefix("doc:") {
+        return Some(id);
+    }
+    if link.contains("://") || link.starts_with('#') || link.starts_with("mailto:") {
+        return None;
+    }
+    let file = link.split('#').next()?.rsplit('/').next()?;
+    file.strip_suffix(".md")
+        .or_else(|| file.strip_suffix(".html"))
+}
+
+/// A sidebar and page view over the documentation library. See the module docs.
+pub struct DocsViewer {
+    search: Entity<InputState>,
+    query: String,
+    selected: Option<SharedString>,
+    collapsed: HashSet<SharedString>,
+    seen_request: Option<usize>,
+    sidebar_width: Pixels,
+    style: StyleRefinement,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl DocsViewer {
+    pub fn new(_window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let search = cx.new(|cx| InputState::new(cx).placeholder("Search docs..."));
+        cx.default_global::<DocsLibrary>();
+        let subscriptions = vec![
+            cx.subscribe(&search, |this, search, event, cx| {
+                if let InputEvent::Change = event {
+                    this.query = search.read(cx).content().to_string();
+                    cx.notify();
+                }
+            }),
+            cx.observe_global::<DocsLibrary>(|this, cx| {
+                this.follow_request(cx);
+                cx.notify();
+            }),
+        ];
+        let mut viewer = Self {
+            search,
+            query: String::new(),
+            selected: None,
+            collapsed: HashSet::new(),
+            seen_request: None,
+            sidebar_width: px(240.0),
+            style: StyleRefinement::default(),
+            _subscriptions: subscriptions,
+        };
+        viewer.follow_request(cx);
+        viewer
+    }
+
+    pub fn sidebar_width(mut self, width: Pixels) -> Self {
+        self.sidebar_width = width;
+        self
+    }
+
+    /// Show page `id` in this viewer only.
+    pub fn select(&mut self, id: impl Into<SharedString>, cx: &mut Context<Self>) {
+        let id = id.into();
+        if page(&id, cx).is_some() {
+            self.selected = Some(id);
+            cx.notify();
+        }
+    }
+
+    pub fn selected(&self) -> Option<&SharedString> {
+        self.selected.as_ref()
+    }
+
+    fn follow_request(&mut self, cx: &mut Context<Self>) {
+        let Some((serial, id)) = cx.global::<DocsLibrary>().request.clone() else {
+            return;
+        };
+        if self.seen_request == Some(serial) {
+            return;
+        }
+        self.seen_request = Some(serial);
+        self.selected = Some(id);
+    }
+
+    fn toggle_section(&mut self, path: SharedString, cx: &mut Context<Self>) {
+        if !self.collapsed.remove(&path) {
+            self.collapsed.insert(path);
+        }
+        cx.notify();
+    }
+
+    fn render_page_row(
+        &self,
+        page: &DocPage,
+        depth: usize,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let theme = use_theme();
+        let selected = self.selected.as_ref() == Some(&page.id);
+        let id = page.id.clone();
+        div()
+            .id(ElementId::Name(format!("docs-page-{}", page.id).into()))
+            .pl(px(8.0 + depth as f32 * 12.0))
+            .pr(px(8.0))
+            .py(px(4.0))
+            .rounded(theme.tokens.radius_sm)
+            .text_size(px(13.0))
+            .cursor_pointer()
+            .when(selected, |this| {
+                this.bg(theme.tokens.accent)
+                    .text_color(theme.tokens.accent_foreground)
+            })
+            .when(!selected, |this| {
+                this.text_color(theme.tokens.foreground)
+                    .hover(|style| style.bg(theme.tokens.muted))
+            })
+            .child(page.title.clone())
+            .on_click(cx.listener(move |this, _, _, cx| this.select(id.clone(), cx)))
+    }
+
+    /// The pages under section `path` and its subsections, as sidebar rows.
+    fn render_tree(
+        &self,
+        pages: &[DocPage],
+        path: &str,
+        depth: usize,
+        rows: &mut Vec<AnyElement>,
+        cx: &mut Context<Self>,
+    ) {
+        let in_section = |page: &&DocPage| page.section.as_deref().unwrap_or("") == path;
+        for page in pages.iter().filter(in_section) {
+            rows.push(self.render_page_row(page, depth, cx).into_any_element());
+        }
+
+        let mut children: Vec<&str> = Vec::new();
+        for section in pages.iter().filter_map(|page| page.section.as_deref()) {
+            let rest = match path {
+                "" => Some(section),
+                _ => section
+                    .strip_prefix(path)
+                    .and_then(|rest| rest.strip_prefix('/')),
+            };
+            if let Some(child) = rest.and_then(|rest| rest.split('/').next()) {
+                if !children.contains(&child) {
+                    children.push(child);
+                }
+            }
+        }
+
+        let theme = use_theme();
+        for child in children {
+            let child_path: SharedString = match path {
+                "" => child.to_string().into(),
+                _ => format!("{}/{}", path, child).into(),
+            };
+            let collapsed = self.collapsed.contains(&child_path);
+            let toggle_path = child_path.clone();
+            rows.push(
+                div()
+                    .id(ElementId::Name(
+                        format!("docs-section-{}", child_path).into(),
+                    ))
+                    .flex()
+                    .items_center()
+                    .gap(px(4.0))
+                    .pl(px(4.0 + depth as f32 * 12.0))
+                    .py(px(4.0))
+                    .text_size(px(12.0))
+                    .font_weight(FontWeight::SEMIBOLD)
+                    .text_color(theme.tokens.muted_foreground)
+                    .cursor_pointer()
+                    .child(
+                        Icon::new(if collapsed {
+                            "chevron-right"
+                        } else {
+                            "chevron-down"
+                        })
+                        .size(px(12.0))
+                        .color(theme.tokens.muted_foreground),
+                    )
+                    .child(child.to_string())
+                    .on_click(cx.listener(move |this, _, _, cx| {
+                        this.toggle_section(toggle_path.clone(), cx)
+                    }))
+                    .into_any_element(),
+            );
+            if !collapsed {
+                self.render_tree(pages, &child_path, depth + 1, rows, cx);
+            }
+        }
+    }
+
+    fn render_hit(
+        &self,
+        hit: DocsSearchHit,
+        title: SharedString,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let theme = use_theme();
+        let selected = self.selected.as_deref() == Some(hit.id.as_str());
+        let id: SharedString = hit.id.clone().into();
+        let highlight = HighlightStyle {
+            color: Some(theme.tokens.foreground),
+            font_weight: Some(FontWeight::SEMIBOLD),
+            ..Default::default()
+        };
+        div()
+            .id(ElementId::Name(format!("docs-hit-{}", hit.id).into()))
+            .flex()
+            .flex_col()
+            .gap(px(2.0))
+            .px(px(8.0))
+            .py(px(6.0))
+            .rounded(theme.tokens.radius_sm)
+            .cursor_pointer()
+            .when(selected, |this| this.bg(theme.tokens.accent))
+            .when(!selected, |this| {
+                this.hover(|style| style.bg(theme.tokens.muted))
+            })
+            .child(
+                div()
+                    .text_size(px(13.0))
+                    .text_color(theme.tokens.foreground)
+                    .child(title),
+            )
+            .when(!hit.snippet.is_empty(), |this| {
+                this.child(
+                    div()
+                        .text_size(px(11.0))
+                        .text_color(theme.tokens.muted_foreground)
+                        .child(
+                            StyledText::new(hit.snippet.clone())
+                                .with_highlights(vec![(hit.highlight.clone(), highlight)]),
+                        ),
+                )
+            })
+            .on_click(cx.listener(move |this, _, _, cx| this.select(id.clone(), cx)))
+    }
+}
+
+impl Styled for DocsViewer {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+impl Render for DocsViewer {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = use_theme();
+        let user_style = self.style.clone();
+        let pages = cx.global::<DocsLibrary>().pages.clone();
+
+        let mut rows = Vec::new();
+        let query = self.query.trim();
+        if query.is_empty() {
+            self.render_tree(&pages, "", 0, &mut rows, cx);
+        } else {
+            let hits = cx.global::<DocsLibrary>().index.search(query, MAX_RESULTS);
+            if hits.is_empty() {
+                rows.push(
+                    div()
+                        .px(px(8.0))
+                        .py(px(6.0))
+                        .text_size(px(13.0))
+                        .text_color(theme.tokens.muted_foreground)
+                        .child("No matching pages")
+                        .into_any_element(),
+                );
+            }
+            for hit in hits {
+                let title = pages
+                    .iter()
+                    .find(|page| page.id.as_ref() == hit.id)
+                    .map(|page| page.title.clone())
+                    .unwrap_or_default();
+                rows.push(self.render_hit(hit, title, cx).into_any_element());
+            }
+        }
+
+        let selected = self
+            .selected
+            .as_ref()
+            .and_then(|id| pages.iter().position(|page| &page.id == id));
+        let content = match selected {
+            Some(ix) => {
+                let page = &pages[ix];
+                let on_link =
+                    |link: &str, window: &mut Window, cx: &mut App| match linked_page(link) {
+                        Some(id) => open(id, window, cx),
+                        None if !link.starts_with('#') => cx.open_url(link),
+                        None => {}
+                    };
+                let body = match page.format {
+                    DocFormat::Markdown => Markdown::new(page.source.clone())
+                        .on_link_click(on_link)
+                        .into_any_element(),
+                    DocFormat::Html => Html::new(page.source.clone())
+                        .on_link_click(on_link)
+                        .into_any_element(),
+                };
+                // Keyed by page, so each page opens scrolled to the top.
+                div()
+                    .id(("docs-content", ix))
+                    .flex_1()
+                    .h_full()
+                    .overflow_y_scroll()
+                    .px(px(32.0))
+                    .py(px(24.0))
+                    .child(body)
+                    .into_any_element()
+            }
+            None => div()
+                .flex_1()
+                .flex()
+                .flex_col()
+                .items_center()
+                .justify_center()
+                .gap(px(8.0))
+                .text_color(theme.tokens.muted_foreground)
+                .child(
+                    Icon::new("book-open")
+                        .size(px(32.0))
+                        .color(theme.tokens.muted_foreground),
+                )
+                .child(div().text_size(px(13.0)).child("Select a page"))
+                .into_any_element(),
+        };
+
+        div()
+            .flex()
+            .size_full()
+            .bg(theme.tokens.background)
+            .text_color(theme.tokens.foreground)
+            .font_family(theme.tokens.font_family.clone())
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .flex_shrink_0()
+                    .w(self.sidebar_width)
+                    .h_full()
+                    .border_r_1()
+                    .border_color(theme.tokens.border)
+                    .bg(theme.tokens.card)
+                    .child(
+                        div()
+                            .p(px(8.0))
+                            .border_b_1()
+                            .border_color(theme.tokens.border)
+                            .child(Input::new(&self.search).placeholder("Search docs...")),
+                    )
+                    .child(
+                        div()
+                            .id("docs-sidebar")
+                            .flex_1()
+                            .overflow_y_scroll()
+                            .p(px(4.0))
+                            .flex()
+                            .flex_col()
+                            .children(rows),
+                    ),
+            )
+            .child(content)
+            .map(|this| {
+                let mut div = this;
+                div.style().refine(&user_style);
+                div
+            })
+    }
+}
+
+/// A "?" button that opens the page `id` in the docs. See the module docs.
+#[derive(IntoElement)]
+pub struct DocsLink {
+    page: SharedString,
+    size: Pixels,
+    style: StyleRefinement,
+}
+
+impl DocsLink {
+    pub fn new(page: impl Into<SharedString>) -> Self {
+        Self {
+            page: page.into(),
+            size: px(14.0),
+            style: StyleRefinement::default(),
+        }
+    }
+
+    /// The icon's size. Defaults to 14px.
+    pub fn size(mut self, size: Pixels) -> Self {
+        self.size = size;
+        self
+    }
+}
+
+impl Styled for DocsLink {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+impl RenderOnce for DocsLink {
+    fn render(self, _window: &mut Window, _cx: &mut App) -> impl IntoElement {
+        let theme = use_theme();
+        let user_style = self.style;
+        let page = self.page;
+        div()
+            .id(ElementId::Name(format!("docs-link-{}", page).into()))
+            .flex()
+            .items_center()
+            .justify_center()
+            .rounded_full()
+            .cursor_pointer()
+            .hover(|style| style.opacity(0.7))
+            .child(
+                Icon::new("circle-question-mark")
+                    .size(self.size)
+                    .color(theme.tokens.muted_foreground),
+            )
+            .on_click(move |_, window, cx| open(&page, window, cx))
+            .map(|this| {
+                let mut div = this;
+                div.style().refine(&user_style);
+                div
+            })
+    }
+}
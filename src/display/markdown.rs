@@ -131,13 +131,17 @@ struct TableState {
 }
 
 #[cfg(feature = "markdown")]
-fn parse_markdown_with_urls(source: &str) -> Vec<RichBlock> {
+fn markdown_options() -> Options {
     let mut options = Options::empty();
     options.insert(Options::ENABLE_TABLES);
     options.insert(Options::ENABLE_STRIKETHROUGH);
     options.insert(Options::ENABLE_TASKLISTS);
+    options
+}
 
-    let parser = Parser::new_ext(source, options);
+#[cfg(feature = "markdown")]
+fn parse_markdown_with_urls(source: &str) -> Vec<RichBlock> {
+    let parser = Parser::new_ext(source, markdown_options());
     let events: Vec<Event> = parser.collect();
 
     let mut builder = UrlTrackingBlockBuilder::new();
@@ -145,6 +149,38 @@ fn parse_markdown_with_urls(source: &str) -> Vec<RichBlock> {
     builder.blocks
 }
 
+/// The document's top-level elements, each with the 0-based source line it starts on.
+/// An element can yield more than one block, e.g. a paragraph holding an image.
+#[cfg(feature = "markdown")]
+pub(crate) fn parse_markdown_sections(source: &str) -> Vec<(usize, Vec<RichBlock>)> {
+    let mut sections = Vec::new();
+    let mut events = Vec::new();
+    let mut depth = 0usize;
+    let (mut line, mut counted) = (0, 0);
+
+    for (event, range) in Parser::new_ext(source, markdown_options()).into_offset_iter() {
+        if depth == 0 && events.is_empty() {
+            line += source[counted..range.start].matches('\n').count();
+            counted = range.start;
+        }
+        match event {
+            Event::Start(_) => depth += 1,
+            Event::End(_) => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        events.push(event);
+        if depth == 0 {
+            let mut builder = UrlTrackingBlockBuilder::new();
+            builder.build(&events);
+            events.clear();
+            if !builder.blocks.is_empty() {
+                sections.push((line, builder.blocks));
+            }
+        }
+    }
+    sections
+}
+
 #[cfg(feature = "markdown")]
 struct UrlTrackingBlockBuilder {
     blocks: Vec<RichBlock>,
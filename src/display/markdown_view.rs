@@ -0,0 +1,218 @@
+//! Live markdown preview.
+//!
+//! `MarkdownView` renders a markdown document the same way as [`Markdown`], but keeps
+//! it as a view that can be updated in place. Paired with an editor it re-renders a
+//! moment after each edit, parsing off the main thread, and follows the editor's
+//! scroll position: the element the editor has at the top of its viewport is scrolled
+//! to the top of the preview.
+//!
+//! [`Markdown`]: crate::display::markdown::Markdown
+
+use crate::components::editor::{BufferEvent, EditorState};
+use crate::display::markdown::parse_markdown_sections;
+use crate::display::rich_text::{render_blocks, LinkClickHandler, RichBlock};
+use crate::theme::use_theme;
+use gpui::{prelude::FluentBuilder as _, *};
+use smol::Timer;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// Pause after an edit before the preview is re-parsed.
+const REPARSE_DELAY: Duration = Duration::from_millis(150);
+
+struct Section {
+    /// 0-based source line the section starts on.
+    line: usize,
+    blocks: Vec<RichBlock>,
+}
+
+pub struct MarkdownView {
+    sections: Rc<Vec<Section>>,
+    base_font_size: Option<Pixels>,
+    on_link_click: Option<LinkClickHandler>,
+    scroll_handle: ScrollHandle,
+    editor: Option<Entity<EditorState>>,
+    _editor_subscription: Option<Subscription>,
+    parse_task: Option<Task<()>>,
+    /// Editor top line to scroll to once the sections have been laid out.
+    pending_sync: Option<f32>,
+    /// Whether a render has already been skipped waiting for that layout.
+    sync_deferred: bool,
+    style: StyleRefinement,
+}
+
+impl MarkdownView {
+    pub fn new(_cx: &mut Context<Self>) -> Self {
+        Self {
+            sections: Rc::new(Vec::new()),
+            base_font_size: None,
+            on_link_click: None,
+            scroll_handle: ScrollHandle::new(),
+            editor: None,
+            _editor_subscription: None,
+            parse_task: None,
+            pending_sync: None,
+            sync_deferred: false,
+            style: StyleRefinement::default(),
+        }
+    }
+
+    pub fn base_font_size(mut self, size: Pixels) -> Self {
+        self.base_font_size = Some(size);
+        self
+    }
+
+    pub fn on_link_click(
+        mut self,
+        handler: impl Fn(&str, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.on_link_click = Some(Box::new(handler));
+        self
+    }
+
+    /// Replace the document, parsing it right away.
+    pub fn set_source(&mut self, source: &str, cx: &mut Context<Self>) {
+        self.parse_task = None;
+        self.sections = Rc::new(sections(source));
+        cx.notify();
+    }
+
+    /// Preview the text of `editor` and follow its scroll position, replacing any
+    /// editor paired before.
+    pub fn pair_with(&mut self, editor: &Entity<EditorState>, cx: &mut Context<Self>) {
+        let source = editor.read(cx).content();
+        self.set_source(&source, cx);
+        self._editor_subscription =
+            Some(cx.subscribe(editor, |this, editor, event, cx| match event {
+                BufferEvent::Edited { .. } => this.schedule_reparse(editor, cx),
+                BufferEvent::Scrolled { top_line } => {
+                    this.pending_sync = Some(*top_line);
+                    cx.notify();
+                }
+                _ => {}
+            }));
+        self.pending_sync = Some(editor.read(cx).scroll_top_line());
+        self.editor = Some(editor.clone());
+    }
+
+    pub fn unpair(&mut self) {
+        self.editor = None;
+        self._editor_subscription = None;
+        self.parse_task = None;
+    }
+
+    fn schedule_reparse(&mut self, editor: Entity<EditorState>, cx: &mut Context<Self>) {
+        self.parse_task = Some(cx.spawn(async move |this, cx| {
+            Timer::after(REPARSE_DELAY).await;
+            let Ok(source) = this.update(cx, |_, cx| editor.read(cx).content()) else {
+                return;
+            };
+            let sections = smol::unblock(move || parse_markdown_sections(&source)).await;
+            let _ = this.update(cx, |this, cx| {
+                this.sections = Rc::new(
+                    sections
+                        .into_iter()
+                        .map(|(line, blocks)| Section { line, blocks })
+                        .collect(),
+                );
+                if let Some(editor) = this.editor.as_ref() {
+                    this.pending_sync = Some(editor.read(cx).scroll_top_line());
+                }
+                cx.notify();
+            });
+        }));
+    }
+
+    /// Scroll so the section holding source line `top_line` sits at the top, offset
+    /// by how far the editor is into it. Needs the sections' layout from the last
+    /// frame; returns false until there is one.
+    fn scroll_to_source_line(&self, top_line: f32) -> bool {
+        let sections = &self.sections;
+        let ix = sections
+            .partition_point(|section| section.line as f32 <= top_line)
+            .saturating_sub(1);
+        let Some(section) = sections.get(ix) else {
+            return true;
+        };
+        let Some(bounds) = self.scroll_handle.bounds_for_item(ix) else {
+            return false;
+        };
+        let next_line = sections.get(ix + 1).map(|next| next.line as f32);
+        let fraction = match next_line {
+            Some(next) if next > section.line as f32 => {
+                ((top_line - section.line as f32) / (next - section.line as f32)).clamp(0.0, 1.0)
+            }
+            _ => 0.0,
+        };
+
+        let viewport = self.scroll_handle.bounds();
+        let offset = self.scroll_handle.offset();
+        let content_top = bounds.top() - viewport.top() - offset.y;
+        let target = (content_top + bounds.size.height * fraction)
+            .min(self.scroll_handle.max_offset().height)
+            .max(px(0.0));
+        self.scroll_handle.set_offset(point(offset.x, -target));
+        true
+    }
+}
+
+fn sections(source: &str) -> Vec<Section> {
+    parse_markdown_sections(source)
+        .into_iter()
+        .map(|(line, blocks)| Section { line, blocks })
+        .collect()
+}
+
+impl Styled for MarkdownView {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+impl Render for MarkdownView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = use_theme();
+        let user_style = self.style.clone();
+        let base_size = self.base_font_size.unwrap_or(px(14.0));
+
+        if let Some(top_line) = self.pending_sync {
+            if self.scroll_to_source_line(top_line) {
+                self.pending_sync = None;
+                self.sync_deferred = false;
+            } else if !self.sync_deferred {
+                // Lay the sections out first, then sync on the next frame.
+                self.sync_deferred = true;
+                cx.notify();
+            }
+        }
+
+        let sections = self.sections.iter().enumerate().map(|(ix, section)| {
+            let id_prefix = format!("md-view-{}", ix);
+            div().children(render_blocks(
+                &section.blocks,
+                base_size,
+                &self.on_link_click,
+                &id_prefix,
+            ))
+        });
+
+        // Sections are the scroll container's direct children so their bounds can be
+        // looked up by index when syncing.
+        div()
+            .id("markdown-view")
+            .flex()
+            .flex_col()
+            .size_full()
+            .p(px(16.0))
+            .overflow_y_scroll()
+            .track_scroll(&self.scroll_handle)
+            .font_family(theme.tokens.font_family.clone())
+            .text_color(theme.tokens.foreground)
+            .map(|this| {
+                let mut div = this;
+                div.style().refine(&user_style);
+                div
+            })
+            .children(sections)
+    }
+}
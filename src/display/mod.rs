@@ -5,7 +5,11 @@ pub mod badge;
 pub mod card;
 pub mod data_grid;
 pub mod data_table;
+pub mod docs_index;
+pub mod docs_viewer;
 pub mod html;
 pub mod markdown;
+#[cfg(feature = "markdown")]
+pub mod markdown_view;
 pub mod rich_text;
 pub mod table;
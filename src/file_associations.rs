@@ -0,0 +1,736 @@
+//! File-type associations and protocol handlers, for install or first-run setup and for
+//! "Set as default editor for .md" toggles in settings.
+//!
+//! Each platform records defaults differently:
+//!
+//! - **macOS**: Launch Services. The types must also be declared in the bundle's
+//!   `Info.plist` (`CFBundleDocumentTypes` / `CFBundleURLTypes`), and `app_id` must be
+//!   the bundle identifier.
+//! - **Linux**: a desktop entry in `$XDG_DATA_HOME/applications` plus `xdg-mime`.
+//! - **Windows**: a per-user ProgID under `HKCU\Software\Classes`. Windows 10 and later
+//!   keep the user's explicit choice, so claiming the default can fail with
+//!   [`AssociationError::RequiresUserAction`]; send the user to
+//!   [`open_default_apps_settings`] in that case.
+//!
+//! All functions block on the OS tools they call, so run them off the main thread:
+//!
+//! ```rust,ignore
+//! let app = AppRegistration::new("com.example.notes", "Notes");
+//! let markdown = FileAssociation::new("md").description("Markdown document");
+//! cx.background_executor()
+//!     .spawn(async move { app.set_default_for_file_type(&markdown) })
+//!     .detach();
+//! ```
+
+use gpui::SharedString;
+use std::fmt;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssociationError {
+    Io(SharedString),
+    /// A platform tool (`xdg-mime`, `reg`, ...) failed.
+    CommandFailed(SharedString),
+    /// The OS only lets the user change this default, through its settings UI.
+    RequiresUserAction,
+    Unsupported,
+}
+
+impl fmt::Display for AssociationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssociationError::Io(message) => write!(f, "I/O error: {}", message),
+            AssociationError::CommandFailed(message) => write!(f, "command failed: {}", message),
+            AssociationError::RequiresUserAction => {
+                write!(f, "the default app must be changed in system settings")
+            }
+            AssociationError::Unsupported => write!(f, "not supported on this platform"),
+        }
+    }
+}
+
+impl std::error::Error for AssociationError {}
+
+impl From<std::io::Error> for AssociationError {
+    fn from(err: std::io::Error) -> Self {
+        AssociationError::Io(err.to_string().into())
+    }
+}
+
+/// A file extension the app can open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileAssociation {
+    extension: String,
+    mime_type: Option<String>,
+    description: Option<String>,
+}
+
+impl FileAssociation {
+    /// `extension` without the leading dot, e.g. `"md"`.
+    pub fn new(extension: impl Into<String>) -> Self {
+        Self {
+            extension: extension
+                .into()
+                .trim_start_matches('.')
+                .to_ascii_lowercase(),
+            mime_type: None,
+            description: None,
+        }
+    }
+
+    /// MIME type used on Linux. Common extensions are known; others default to
+    /// `application/x-<extension>`.
+    pub fn mime_type(mut self, mime_type: impl Into<String>) -> Self {
+        self.mime_type = Some(mime_type.into());
+        self
+    }
+
+    /// Human-readable type name shown by the OS, e.g. "Markdown document".
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn extension(&self) -> &str {
+        &self.extension
+    }
+
+    fn resolved_mime_type(&self) -> String {
+        if let Some(mime_type) = &self.mime_type {
+            return mime_type.clone();
+        }
+        let known = match self.extension.as_str() {
+            "md" | "markdown" => "text/markdown",
+            "txt" | "text" => "text/plain",
+            "json" => "application/json",
+            "toml" => "application/toml",
+            "yaml" | "yml" => "application/yaml",
+            "html" | "htm" => "text/html",
+            "css" => "text/css",
+            "js" | "mjs" => "text/javascript",
+            "ts" => "text/x-typescript",
+            "rs" => "text/rust",
+            "py" => "text/x-python",
+            "csv" => "text/csv",
+            "xml" => "application/xml",
+            "svg" => "image/svg+xml",
+            "png" => "image/png",
+            "jpg" | "jpeg" => "image/jpeg",
+            _ => return format!("application/x-{}", self.extension),
+        };
+        known.to_string()
+    }
+}
+
+/// The app whose associations are being managed.
+#[derive(Debug, Clone)]
+pub struct AppRegistration {
+    app_id: String,
+    /// Shown in the Linux desktop entry.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    name: String,
+    /// Launched by Linux desktop entries and Windows registry commands. macOS launches
+    /// the bundle instead.
+    #[cfg_attr(not(any(target_os = "linux", target_os = "windows")), allow(dead_code))]
+    executable: Option<PathBuf>,
+}
+
+impl AppRegistration {
+    /// `app_id` is a reverse-DNS identifier; on macOS it must be the bundle identifier.
+    pub fn new(app_id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            app_id: app_id.into(),
+            name: name.into(),
+            executable: None,
+        }
+    }
+
+    /// Executable launched for files and links. Defaults to the current executable.
+    pub fn executable(mut self, path: impl Into<PathBuf>) -> Self {
+        self.executable = Some(path.into());
+        self
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "windows"))]
+    fn executable_path(&self) -> Result<PathBuf, AssociationError> {
+        match &self.executable {
+            Some(path) => Ok(path.clone()),
+            None => Ok(std::env::current_exe()?),
+        }
+    }
+
+    /// Register the app for `association` and make it the default handler.
+    pub fn set_default_for_file_type(
+        &self,
+        association: &FileAssociation,
+    ) -> Result<(), AssociationError> {
+        platform::set_default_for_file_type(self, association)
+    }
+
+    /// Stop handling `association`. The OS picks another default, if any.
+    pub fn remove_file_type(&self, association: &FileAssociation) -> Result<(), AssociationError> {
+        platform::remove_file_type(self, association)
+    }
+
+    pub fn is_default_for_file_type(&self, association: &FileAssociation) -> bool {
+        platform::is_default_for_file_type(self, association)
+    }
+
+    /// Register the app as the handler for `scheme://` links.
+    pub fn set_default_for_protocol(&self, scheme: &str) -> Result<(), AssociationError> {
+        platform::set_default_for_protocol(self, &scheme.to_ascii_lowercase())
+    }
+
+    pub fn is_default_for_protocol(&self, scheme: &str) -> bool {
+        platform::is_default_for_protocol(self, &scheme.to_ascii_lowercase())
+    }
+}
+
+/// Open the OS screen where users choose default apps, where one exists.
+pub fn open_default_apps_settings() -> Result<(), AssociationError> {
+    platform::open_default_apps_settings()
+}
+
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn run(program: &str, args: &[&str]) -> Result<String, AssociationError> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|err| AssociationError::CommandFailed(format!("{}: {}", program, err).into()))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(AssociationError::CommandFailed(
+            format!(
+                "{} exited with {}: {}",
+                program,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into(),
+        ))
+    }
+}
+
+/// `entry` with `mime_type` added to or removed from its `MimeType=` list.
+#[cfg(any(target_os = "linux", test))]
+fn with_mime_type(entry: &str, mime_type: &str, add: bool) -> String {
+    let mut mime_types: Vec<&str> = entry
+        .lines()
+        .find_map(|line| line.strip_prefix("MimeType="))
+        .map(|list| list.split(';').filter(|mime| !mime.is_empty()).collect())
+        .unwrap_or_default();
+    mime_types.retain(|existing| *existing != mime_type);
+    if add {
+        mime_types.push(mime_type);
+    }
+    let mime_line = format!("MimeType={};", mime_types.join(";"));
+
+    let mut lines: Vec<String> = entry
+        .lines()
+        .filter(|line| !line.starts_with("MimeType="))
+        .map(str::to_string)
+        .collect();
+    if !mime_types.is_empty() {
+        // Keep the key inside the [Desktop Entry] group, ahead of any action groups.
+        let group_end = lines
+            .iter()
+            .skip(1)
+            .position(|line| line.starts_with('['))
+            .map_or(lines.len(), |index| index + 1);
+        let insert_at = lines[..group_end]
+            .iter()
+            .rposition(|line| !line.trim().is_empty())
+            .map_or(group_end, |index| index + 1);
+        lines.insert(insert_at, mime_line);
+    }
+    let mut result = lines.join("\n");
+    result.push('\n');
+    result
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::*;
+    use std::path::Path;
+
+    fn applications_dir() -> Result<PathBuf, AssociationError> {
+        let data_home = std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+            .ok_or(AssociationError::Io("no home directory".into()))?;
+        let dir = data_home.join("applications");
+        std::fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+
+    fn desktop_name(app: &AppRegistration) -> String {
+        format!("{}.desktop", app.app_id)
+    }
+
+    /// Add or remove `mime_type` in the app's desktop entry. An entry installed by a
+    /// package is kept as is apart from its `MimeType` line; otherwise a hidden one is created.
+    fn update_desktop_entry(
+        app: &AppRegistration,
+        mime_type: &str,
+        add: bool,
+    ) -> Result<(), AssociationError> {
+        let dir = applications_dir()?;
+        let path = dir.join(desktop_name(app));
+        let entry = match std::fs::read_to_string(&path) {
+            Ok(entry) => entry,
+            Err(_) => format!(
+                "[Desktop Entry]\nType=Application\nName={}\nExec=\"{}\" %U\nTerminal=false\nNoDisplay=true\n",
+                app.name,
+                app.executable_path()?.display()
+            ),
+        };
+        std::fs::write(&path, with_mime_type(&entry, mime_type, add))?;
+        let _ = run("update-desktop-database", &[&dir.to_string_lossy()]);
+        Ok(())
+    }
+
+    fn set_default(app: &AppRegistration, mime_type: &str) -> Result<(), AssociationError> {
+        update_desktop_entry(app, mime_type, true)?;
+        run("xdg-mime", &["default", &desktop_name(app), mime_type]).map(|_| ())
+    }
+
+    fn is_default(app: &AppRegistration, mime_type: &str) -> bool {
+        run("xdg-mime", &["query", "default", mime_type])
+            .is_ok_and(|default| default.trim() == desktop_name(app))
+    }
+
+    pub fn set_default_for_file_type(
+        app: &AppRegistration,
+        association: &FileAssociation,
+    ) -> Result<(), AssociationError> {
+        set_default(app, &association.resolved_mime_type())
+    }
+
+    pub fn remove_file_type(
+        app: &AppRegistration,
+        association: &FileAssociation,
+    ) -> Result<(), AssociationError> {
+        update_desktop_entry(app, &association.resolved_mime_type(), false)
+    }
+
+    pub fn is_default_for_file_type(app: &AppRegistration, association: &FileAssociation) -> bool {
+        is_default(app, &association.resolved_mime_type())
+    }
+
+    pub fn set_default_for_protocol(
+        app: &AppRegistration,
+        scheme: &str,
+    ) -> Result<(), AssociationError> {
+        set_default(app, &format!("x-scheme-handler/{}", scheme))
+    }
+
+    pub fn is_default_for_protocol(app: &AppRegistration, scheme: &str) -> bool {
+        is_default(app, &format!("x-scheme-handler/{}", scheme))
+    }
+
+    pub fn open_default_apps_settings() -> Result<(), AssociationError> {
+        Err(AssociationError::Unsupported)
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::*;
+
+    const CLASSES: &str = r"HKCU\Software\Classes";
+
+    fn prog_id(app: &AppRegistration, extension: &str) -> String {
+        format!("{}.{}", app.app_id, extension)
+    }
+
+    fn reg_add(key: &str, name: Option<&str>, data: &str) -> Result<(), AssociationError> {
+        let mut args = vec!["add", key];
+        match name {
+            Some(name) => args.extend(["/v", name]),
+            None => args.push("/ve"),
+        }
+        args.extend(["/d", data, "/f"]);
+        run("reg", &args).map(|_| ())
+    }
+
+    /// Value `name` of `key` (the default value when `None`), if set.
+    fn reg_query(key: &str, name: Option<&str>) -> Option<String> {
+        let mut args = vec!["query", key];
+        match name {
+            Some(name) => args.extend(["/v", name]),
+            None => args.push("/ve"),
+        }
+        let output = run("reg", &args).ok()?;
+        output.lines().find_map(|line| {
+            let (_, value) = line.split_once("REG_SZ")?;
+            Some(value.trim().to_string())
+        })
+    }
+
+    fn open_command(app: &AppRegistration) -> Result<String, AssociationError> {
+        Ok(format!("\"{}\" \"%1\"", app.executable_path()?.display()))
+    }
+
+    pub fn set_default_for_file_type(
+        app: &AppRegistration,
+        association: &FileAssociation,
+    ) -> Result<(), AssociationError> {
+        let extension = association.extension();
+        let prog_id = prog_id(app, extension);
+        let prog_key = format!(r"{}\{}", CLASSES, prog_id);
+        let description = association
+            .description
+            .clone()
+            .unwrap_or_else(|| format!("{} file", extension.to_uppercase()));
+        reg_add(&prog_key, None, &description)?;
+        reg_add(
+            &format!(r"{}\shell\open\command", prog_key),
+            None,
+            &open_command(app)?,
+        )?;
+        let extension_key = format!(r"{}\.{}", CLASSES, extension);
+        reg_add(
+            &format!(r"{}\OpenWithProgids", extension_key),
+            Some(&prog_id),
+            "",
+        )?;
+        reg_add(&extension_key, None, &prog_id)?;
+        let _ = run("ie4uinit.exe", &["-show"]);
+
+        if is_default_for_file_type(app, association) {
+            Ok(())
+        } else {
+            Err(AssociationError::RequiresUserAction)
+        }
+    }
+
+    pub fn remove_file_type(
+        app: &AppRegistration,
+        association: &FileAssociation,
+    ) -> Result<(), AssociationError> {
+        let extension = association.extension();
+        let prog_id = prog_id(app, extension);
+        let extension_key = format!(r"{}\.{}", CLASSES, extension);
+        let _ = run(
+            "reg",
+            &[
+                "delete",
+                &format!(r"{}\OpenWithProgids", extension_key),
+                "/v",
+                &prog_id,
+                "/f",
+            ],
+        );
+        if reg_query(&extension_key, None).as_deref() == Some(prog_id.as_str()) {
+            run("reg", &["delete", &extension_key, "/ve", "/f"])?;
+        }
+        run(
+            "reg",
+            &["delete", &format!(r"{}\{}", CLASSES, prog_id), "/f"],
+        )
+        .map(|_| ())
+    }
+
+    pub fn is_default_for_file_type(app: &AppRegistration, association: &FileAssociation) -> bool {
+        let extension = association.extension();
+        let prog_id = prog_id(app, extension);
+        let user_choice = format!(
+            r"HKCU\Software\Microsoft\Windows\CurrentVersion\Explorer\FileExts\.{}\UserChoice",
+            extension
+        );
+        match reg_query(&user_choice, Some("ProgId")) {
+            Some(choice) => choice == prog_id,
+            None => {
+                reg_query(&format!(r"{}\.{}", CLASSES, extension), None).as_deref()
+                    == Some(prog_id.as_str())
+            }
+        }
+    }
+
+    pub fn set_default_for_protocol(
+        app: &AppRegistration,
+        scheme: &str,
+    ) -> Result<(), AssociationError> {
+        let key = format!(r"{}\{}", CLASSES, scheme);
+        reg_add(&key, None, &format!("URL:{}", scheme))?;
+        reg_add(&key, Some("URL Protocol"), "")?;
+        reg_add(
+            &format!(r"{}\shell\open\command", key),
+            None,
+            &open_command(app)?,
+        )
+    }
+
+    pub fn is_default_for_protocol(app: &AppRegistration, scheme: &str) -> bool {
+        let Ok(command) = open_command(app) else {
+            return false;
+        };
+        reg_query(&format!(r"{}\{}\shell\open\command", CLASSES, scheme), None)
+            .is_some_and(|registered| registered.eq_ignore_ascii_case(&command))
+    }
+
+    pub fn open_default_apps_settings() -> Result<(), AssociationError> {
+        run("cmd", &["/C", "start", "", "ms-settings:defaultapps"]).map(|_| ())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::*;
+    use std::ffi::{c_char, c_void, CString};
+
+    type CFStringRef = *const c_void;
+    type OSStatus = i32;
+
+    const UTF8: u32 = 0x0800_0100;
+    const ROLES_ALL: u32 = 0xFFFF_FFFF;
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFStringCreateWithCString(
+            allocator: *const c_void,
+            c_str: *const c_char,
+            encoding: u32,
+        ) -> CFStringRef;
+        fn CFStringGetCString(
+            string: CFStringRef,
+            buffer: *mut c_char,
+            buffer_size: isize,
+            encoding: u32,
+        ) -> u8;
+        fn CFRelease(cf: *const c_void);
+    }
+
+    #[link(name = "CoreServices", kind = "framework")]
+    extern "C" {
+        static kUTTagClassFilenameExtension: CFStringRef;
+        fn UTTypeCreatePreferredIdentifierForTag(
+            tag_class: CFStringRef,
+            tag: CFStringRef,
+            conforming_to: CFStringRef,
+        ) -> CFStringRef;
+        fn LSSetDefaultRoleHandlerForContentType(
+            content_type: CFStringRef,
+            role: u32,
+            handler_bundle_id: CFStringRef,
+        ) -> OSStatus;
+        fn LSCopyDefaultRoleHandlerForContentType(
+            content_type: CFStringRef,
+            role: u32,
+        ) -> CFStringRef;
+        fn LSSetDefaultHandlerForURLScheme(
+            scheme: CFStringRef,
+            handler_bundle_id: CFStringRef,
+        ) -> OSStatus;
+        fn LSCopyDefaultHandlerForURLScheme(scheme: CFStringRef) -> CFStringRef;
+    }
+
+    /// Owned `CFStringRef`, released on drop.
+    struct CfString(CFStringRef);
+
+    impl CfString {
+        fn new(text: &str) -> Option<Self> {
+            let c_text = CString::new(text).ok()?;
+            // SAFETY: `c_text` is a valid NUL-terminated string for the duration of the call.
+            let string =
+                unsafe { CFStringCreateWithCString(std::ptr::null(), c_text.as_ptr(), UTF8) };
+            Self::from_owned(string)
+        }
+
+        fn from_owned(string: CFStringRef) -> Option<Self> {
+            (!string.is_null()).then_some(Self(string))
+        }
+
+        fn to_string_lossy(&self) -> String {
+            let mut buffer = vec![0 as c_char; 1024];
+            // SAFETY: `self.0` is a live CFString and `buffer` has the advertised length.
+            let ok = unsafe {
+                CFStringGetCString(self.0, buffer.as_mut_ptr(), buffer.len() as isize, UTF8)
+            };
+            if ok == 0 {
+                return String::new();
+            }
+            // SAFETY: on success the buffer holds a NUL-terminated string.
+            unsafe { std::ffi::CStr::from_ptr(buffer.as_ptr()) }
+                .to_string_lossy()
+                .into_owned()
+        }
+    }
+
+    impl Drop for CfString {
+        fn drop(&mut self) {
+            // SAFETY: `self.0` was returned by a Create/Copy function and is released once.
+            unsafe { CFRelease(self.0) }
+        }
+    }
+
+    fn uti_for_extension(extension: &str) -> Option<CfString> {
+        let tag = CfString::new(extension)?;
+        // SAFETY: all arguments are valid CFStrings or null.
+        CfString::from_owned(unsafe {
+            UTTypeCreatePreferredIdentifierForTag(
+                kUTTagClassFilenameExtension,
+                tag.0,
+                std::ptr::null(),
+            )
+        })
+    }
+
+    fn bundle_id(app: &AppRegistration) -> Result<CfString, AssociationError> {
+        CfString::new(&app.app_id).ok_or(AssociationError::Unsupported)
+    }
+
+    fn check(status: OSStatus) -> Result<(), AssociationError> {
+        if status == 0 {
+            Ok(())
+        } else {
+            Err(AssociationError::CommandFailed(
+                format!("Launch Services error {}", status).into(),
+            ))
+        }
+    }
+
+    pub fn set_default_for_file_type(
+        app: &AppRegistration,
+        association: &FileAssociation,
+    ) -> Result<(), AssociationError> {
+        let uti =
+            uti_for_extension(association.extension()).ok_or(AssociationError::Unsupported)?;
+        let bundle_id = bundle_id(app)?;
+        // SAFETY: both arguments are live CFStrings.
+        check(unsafe { LSSetDefaultRoleHandlerForContentType(uti.0, ROLES_ALL, bundle_id.0) })
+    }
+
+    pub fn remove_file_type(
+        _app: &AppRegistration,
+        _association: &FileAssociation,
+    ) -> Result<(), AssociationError> {
+        Err(AssociationError::RequiresUserAction)
+    }
+
+    pub fn is_default_for_file_type(app: &AppRegistration, association: &FileAssociation) -> bool {
+        let Some(uti) = uti_for_extension(association.extension()) else {
+            return false;
+        };
+        // SAFETY: `uti` is a live CFString; the result follows the Copy rule.
+        CfString::from_owned(unsafe { LSCopyDefaultRoleHandlerForContentType(uti.0, ROLES_ALL) })
+            .is_some_and(|handler| handler.to_string_lossy().eq_ignore_ascii_case(&app.app_id))
+    }
+
+    pub fn set_default_for_protocol(
+        app: &AppRegistration,
+        scheme: &str,
+    ) -> Result<(), AssociationError> {
+        let scheme = CfString::new(scheme).ok_or(AssociationError::Unsupported)?;
+        let bundle_id = bundle_id(app)?;
+        // SAFETY: both arguments are live CFStrings.
+        check(unsafe { LSSetDefaultHandlerForURLScheme(scheme.0, bundle_id.0) })
+    }
+
+    pub fn is_default_for_protocol(app: &AppRegistration, scheme: &str) -> bool {
+        let Some(scheme) = CfString::new(scheme) else {
+            return false;
+        };
+        // SAFETY: `scheme` is a live CFString; the result follows the Copy rule.
+        CfString::from_owned(unsafe { LSCopyDefaultHandlerForURLScheme(scheme.0) })
+            .is_some_and(|handler| handler.to_string_lossy().eq_ignore_ascii_case(&app.app_id))
+    }
+
+    pub fn open_default_apps_settings() -> Result<(), AssociationError> {
+        Err(AssociationError::Unsupported)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+mod platform {
+    use super::*;
+
+    pub fn set_default_for_file_type(
+        _app: &AppRegistration,
+        _association: &FileAssociation,
+    ) -> Result<(), AssociationError> {
+        Err(AssociationError::Unsupported)
+    }
+
+    pub fn remove_file_type(
+        _app: &AppRegistration,
+        _association: &FileAssociation,
+    ) -> Result<(), AssociationError> {
+        Err(AssociationError::Unsupported)
+    }
+
+    pub fn is_default_for_file_type(
+        _app: &AppRegistration,
+        _association: &FileAssociation,
+    ) -> bool {
+        false
+    }
+
+    pub fn set_default_for_protocol(
+        _app: &AppRegistration,
+        _scheme: &str,
+    ) -> Result<(), AssociationError> {
+        Err(AssociationError::Unsupported)
+    }
+
+    pub fn is_default_for_protocol(_app: &AppRegistration, _scheme: &str) -> bool {
+        false
+    }
+
+    pub fn open_default_apps_settings() -> Result<(), AssociationError> {
+        Err(AssociationError::Unsupported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_extension() {
+        assert_eq!(FileAssociation::new(".MD").extension(), "md");
+    }
+
+    #[test]
+    fn resolves_mime_types() {
+        assert_eq!(
+            FileAssociation::new("md").resolved_mime_type(),
+            "text/markdown"
+        );
+        assert_eq!(
+            FileAssociation::new("notes").resolved_mime_type(),
+            "application/x-notes"
+        );
+        assert_eq!(
+            FileAssociation::new("md")
+                .mime_type("text/x-gfm")
+                .resolved_mime_type(),
+            "text/x-gfm"
+        );
+    }
+
+    #[test]
+    fn edits_desktop_entry_mime_types() {
+        let entry =
+            "[Desktop Entry]\nName=Notes\nMimeType=text/plain;\n\n[Desktop Action new]\nName=New\n";
+        let added = with_mime_type(entry, "text/markdown", true);
+        assert_eq!(
+            added,
+            "[Desktop Entry]\nName=Notes\nMimeType=text/plain;text/markdown;\n\n[Desktop Action new]\nName=New\n"
+        );
+        assert_eq!(with_mime_type(&added, "text/markdown", false), entry);
+        assert_eq!(
+            with_mime_type("[Desktop Entry]\nName=Notes\n", "text/markdown", true),
+            "[Desktop Entry]\nName=Notes\nMimeType=text/markdown;\n"
+        );
+        assert_eq!(
+            with_mime_type(
+                "[Desktop Entry]\nMimeType=text/plain;\n",
+                "text/plain",
+                false
+            ),
+            "[Desktop Entry]\n"
+        );
+    }
+}
@@ -1,4 +1,6 @@
 use futures::future::BoxFuture;
+#[cfg(feature = "http")]
+use futures::AsyncReadExt;
 use futures::FutureExt;
 use gpui::http_client::{AsyncBody, HttpClient, Request, Response};
 use std::sync::Arc;
@@ -49,9 +51,14 @@ impl HttpClient for SimpleHttpClient {
         req: Request<AsyncBody>,
     ) -> BoxFuture<'static, gpui::http_client::Result<Response<AsyncBody>>> {
         let client = self.client.clone();
-        let (parts, _body) = req.into_parts();
+        let (parts, mut body) = req.into_parts();
 
         async move {
+            let mut request_body = Vec::new();
+            body.read_to_end(&mut request_body)
+                .await
+                .map_err(|e| gpui::http_client::anyhow!("Failed to read request body: {}", e))?;
+
             let method_str = parts.method.as_str();
             let uri_str = parts.uri.to_string();
 
@@ -64,7 +71,11 @@ impl HttpClient for SimpleHttpClient {
             }
 
             let isahc_request = request_builder
-                .body(())
+                .body(if request_body.is_empty() {
+                    isahc::AsyncBody::empty()
+                } else {
+                    isahc::AsyncBody::from(request_body)
+                })
                 .map_err(|e| gpui::http_client::anyhow!("Failed to build request: {}", e))?;
 
             let mut response = client
@@ -75,11 +86,20 @@ impl HttpClient for SimpleHttpClient {
             let status = response.status();
             let headers = response.headers().clone();
 
-            use isahc::AsyncReadResponseExt;
-            let bytes = response
-                .bytes()
-                .await
-                .map_err(|e| gpui::http_client::anyhow!("Failed to read response body: {}", e))?;
+            // Event streams never finish, so hand their body over as it arrives.
+            let streaming = headers
+                .get("content-type")
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|value| value.starts_with("text/event-stream"));
+            let async_body = if streaming {
+                AsyncBody::from_reader(response.into_body())
+            } else {
+                use isahc::AsyncReadResponseExt;
+                let bytes = response.bytes().await.map_err(|e| {
+                    gpui::http_client::anyhow!("Failed to read response body: {}", e)
+                })?;
+                AsyncBody::from_bytes(bytes::Bytes::from(bytes))
+            };
 
             let mut builder = gpui::http_client::http::Response::builder().status(
                 gpui::http_client::http::StatusCode::from_u16(status.as_u16())
@@ -90,7 +110,6 @@ impl HttpClient for SimpleHttpClient {
                 builder = builder.header(key.as_str(), value.as_bytes());
             }
 
-            let async_body = AsyncBody::from_bytes(bytes::Bytes::from(bytes));
             let response = builder
                 .body(async_body)
                 .map_err(|e| gpui::http_client::anyhow!("Failed to build response: {}", e))?;
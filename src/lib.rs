@@ -78,15 +78,82 @@ pub mod virtual_list;
 /// Extension traits for common types
 pub mod util;
 
+/// Weak-capturing callbacks and a check for entities leaked through them
+pub mod callbacks;
+
+/// User idle and window activation, for pausing background work nobody sees
+pub mod activity;
+
+/// OKLCH color mixing, palette generation, WCAG contrast and gradients
+pub mod color;
+
 /// Font loading and registration
 pub mod fonts;
 
+/// Vector export of components as structured SVG
+pub mod svg_export;
+
 /// Icon configuration for custom asset paths
 pub mod icon_config;
 
 /// HTTP client for remote image loading
 pub mod http;
 
+/// Database-backed data sources: SQLite tables and queries for DataTable
+#[cfg(feature = "sqlite")]
+pub mod data;
+
+/// JSON resources and live WebSocket/SSE feeds bound to entities
+#[cfg(feature = "net")]
+pub mod net;
+
+/// Plugin host for third-party components and commands
+pub mod plugins;
+
+/// App-wide and subtree-scoped services looked up by type
+pub mod services;
+
+/// Opt-in usage telemetry with app-provided sinks
+pub mod telemetry;
+
+/// Panic capture and crash logs behind the crash overlay
+pub mod crash_reporter;
+
+/// Input recording to trace files and replay, for reproducing bug reports
+pub mod session_recording;
+
+/// Workspace trust and the restricted mode untrusted folders open in
+pub mod workspace_trust;
+
+/// Custom URL scheme registration and activation link dispatch
+pub mod deeplink;
+
+/// Single-instance enforcement with argument forwarding to the running app
+pub mod single_instance;
+
+/// File-type associations and protocol handler registration
+pub mod file_associations;
+
+/// OS keychain credential storage
+#[cfg(feature = "secrets")]
+pub mod secrets;
+
+/// OAuth 2.0 browser (PKCE) and device-code sign-in
+#[cfg(feature = "auth")]
+pub mod auth;
+
+/// Sandboxed WebAssembly extensions with capability-based permissions
+#[cfg(feature = "wasm-plugins")]
+pub mod wasm_plugins;
+
+/// Rhai scripting for user automation bound to keys and palette commands
+#[cfg(feature = "scripting")]
+pub mod scripting;
+
+/// Update checks, verified downloads, install and relaunch, with update UI
+#[cfg(feature = "updater")]
+pub mod updater;
+
 // Re-export commonly used icon configuration functions
 pub use icon_config::set_icon_base_path;
 
@@ -101,14 +168,27 @@ pub use http::{init_http, init_http_with_user_agent};
 pub fn init(cx: &mut gpui::App) {
     fonts::register_fonts(cx);
     http::init_http(cx);
+    plugins::init(cx);
+    activity::init(cx);
 
     components::input::init(cx);
     components::otp_input::init(cx);
+    components::textarea::init(cx);
+    components::date_picker::init(cx);
+    components::slider::init(cx);
     components::select::init_select(cx);
+    components::multi_select::init_multi_select(cx);
     components::combobox::init_combobox(cx);
     components::editor::init(cx);
+    components::zen_mode::init(cx);
+    components::layout_presets::init(cx);
     navigation::sidebar::init_sidebar(cx);
+    overlays::dismiss::init(cx);
     overlays::popover::init(cx);
     overlays::sheet::init_sheet(cx);
     overlays::alert_dialog::init_alert_dialog(cx);
+    #[cfg(feature = "debug-console")]
+    overlays::debug_console::init(cx);
+    #[cfg(feature = "state-inspector")]
+    overlays::state_inspector::init(cx);
 }
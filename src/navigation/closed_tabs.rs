@@ -0,0 +1,137 @@
+//! A stack of recently closed tabs, for "Reopen Closed Tab".
+//!
+//! Push a tab when it closes, with the editor session of the document it showed, and
+//! pop it on [`ReopenClosedTab`](super::tabs::ReopenClosedTab) to open it again where
+//! it was, with its cursor, scroll position and folds:
+//!
+//! ```rust,ignore
+//! // Closing:
+//! let session = editor.read(cx).session();
+//! self.closed.push(path, index, Some(session));
+//!
+//! // Reopening:
+//! if let Some(closed) = self.closed.pop() {
+//!     let editor = self.open_at(closed.item, closed.index, cx);
+//!     if let Some(session) = &closed.session {
+//!         editor.update(cx, |editor, cx| editor.restore_session(session, cx));
+//!     }
+//! }
+//! ```
+
+use crate::components::editor::EditorSession;
+use std::collections::VecDeque;
+
+/// How many closed tabs are remembered unless [`ClosedTabs::with_limit`] says otherwise.
+pub const DEFAULT_CLOSED_TAB_LIMIT: usize = 20;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClosedTab<T> {
+    /// What the tab showed, e.g. its file path or tab id.
+    pub item: T,
+    /// The tab's position when it closed.
+    pub index: usize,
+    pub session: Option<EditorSession>,
+}
+
+/// Closed tabs, most recent last. Once over the limit the oldest is forgotten.
+#[derive(Debug, Clone)]
+pub struct ClosedTabs<T> {
+    tabs: VecDeque<ClosedTab<T>>,
+    limit: usize,
+}
+
+impl<T> Default for ClosedTabs<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ClosedTabs<T> {
+    pub fn new() -> Self {
+        Self::with_limit(DEFAULT_CLOSED_TAB_LIMIT)
+    }
+
+    pub fn with_limit(limit: usize) -> Self {
+        Self {
+            tabs: VecDeque::new(),
+            limit,
+        }
+    }
+
+    pub fn push(&mut self, item: T, index: usize, session: Option<EditorSession>) {
+        if self.limit == 0 {
+            return;
+        }
+        while self.tabs.len() >= self.limit {
+            self.tabs.pop_front();
+        }
+        self.tabs.push_back(ClosedTab {
+            item,
+            index,
+            session,
+        });
+    }
+
+    /// Take the most recently closed tab.
+    pub fn pop(&mut self) -> Option<ClosedTab<T>> {
+        self.tabs.pop_back()
+    }
+
+    pub fn peek(&self) -> Option<&ClosedTab<T>> {
+        self.tabs.back()
+    }
+
+    /// Forget tabs that can't be reopened any more, e.g. for deleted files, or that are
+    /// open again by other means.
+    pub fn retain(&mut self, mut keep: impl FnMut(&T) -> bool) {
+        self.tabs.retain(|tab| keep(&tab.item));
+    }
+
+    pub fn len(&self) -> usize {
+        self.tabs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tabs.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.tabs.clear();
+    }
+
+    /// Closed tabs, most recent first, e.g. for a "Recently Closed" menu.
+    pub fn iter(&self) -> impl Iterator<Item = &ClosedTab<T>> {
+        self.tabs.iter().rev()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pops_most_recent_first() {
+        let mut closed = ClosedTabs::new();
+        closed.push("a.rs", 0, None);
+        closed.push("b.rs", 3, None);
+        assert_eq!(closed.peek().map(|tab| tab.item), Some("b.rs"));
+        let tab = closed.pop().unwrap();
+        assert_eq!((tab.item, tab.index), ("b.rs", 3));
+        assert_eq!(closed.pop().map(|tab| tab.item), Some("a.rs"));
+        assert!(closed.pop().is_none());
+    }
+
+    #[test]
+    fn forgets_oldest_over_limit() {
+        let mut closed = ClosedTabs::with_limit(2);
+        for item in ["a", "b", "c"] {
+            closed.push(item, 0, None);
+        }
+        let items: Vec<_> = closed.iter().map(|tab| tab.item).collect();
+        assert_eq!(items, vec!["c", "b"]);
+
+        closed.retain(|item| *item != "c");
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed.peek().map(|tab| tab.item), Some("b"));
+    }
+}
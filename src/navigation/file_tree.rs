@@ -1,9 +1,11 @@
 use crate::components::icon::Icon;
 use crate::components::icon_source::IconSource;
+use crate::components::type_ahead::{keep_with_ancestors, TypeAhead, TypeAheadAction};
 use crate::theme::use_theme;
 use gpui::{prelude::FluentBuilder as _, *};
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::sync::Arc;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -207,6 +209,16 @@ fn flatten_file_tree(
     flat
 }
 
+/// Paths of every directory in `nodes`, to flatten the whole tree for filtering.
+fn collect_directories(nodes: &[FileNode], paths: &mut HashSet<PathBuf>) {
+    for node in nodes {
+        if !node.children.is_empty() {
+            paths.insert(node.path.clone());
+            collect_directories(&node.children, paths);
+        }
+    }
+}
+
 fn format_size(size: u64) -> String {
     const KB: u64 = 1024;
     const MB: u64 = KB * 1024;
@@ -223,12 +235,60 @@ fn format_size(size: u64) -> String {
     }
 }
 
+/// Visible paths from `from` to `to` inclusive, in display order.
+fn path_range(visible: &[PathBuf], from: &Path, to: &Path) -> Vec<PathBuf> {
+    let (Some(a), Some(b)) = (
+        visible.iter().position(|p| p == from),
+        visible.iter().position(|p| p == to),
+    ) else {
+        return vec![to.to_path_buf()];
+    };
+    visible[a.min(b)..=a.max(b)].to_vec()
+}
+
 const ROW_HEIGHT: f32 = 28.0;
 
+/// Focus and range-selection state kept between renders.
+struct SelectionState {
+    focus_handle: FocusHandle,
+    /// Fixed end of a shift-extended range.
+    anchor: Option<PathBuf>,
+    /// Moving end of the range, where arrow keys continue from.
+    lead: Option<PathBuf>,
+    type_ahead: TypeAhead,
+}
+
+impl SelectionState {
+    /// Feed a key to type-ahead. `None` if it isn't type-ahead input, otherwise the
+    /// item jumped to, which becomes the whole selection.
+    fn type_ahead_key(
+        &mut self,
+        keystroke: &Keystroke,
+        visible: &[PathBuf],
+        names: &[String],
+    ) -> Option<Option<PathBuf>> {
+        if self.type_ahead.handle_key(keystroke)? == TypeAheadAction::FilterChanged {
+            return Some(None);
+        }
+        let current = self
+            .lead
+            .as_ref()
+            .and_then(|lead| visible.iter().position(|path| path == lead));
+        let next = visible.get(self.type_ahead.find(names, current)?)?.clone();
+        self.anchor = Some(next.clone());
+        self.lead = Some(next.clone());
+        Some(Some(next))
+    }
+}
+
+type SelectionHandler = Arc<dyn Fn(Vec<PathBuf>, &mut Window, &mut App) + Send + Sync>;
+
 #[derive(IntoElement)]
 pub struct FileTree {
+    id: ElementId,
     nodes: Vec<FileNode>,
     selected_path: Option<PathBuf>,
+    selected_paths: Vec<PathBuf>,
     expanded_paths: Vec<PathBuf>,
     show_hidden: bool,
     show_file_size: bool,
@@ -237,14 +297,17 @@ pub struct FileTree {
     on_toggle: Option<Arc<dyn Fn(&PathBuf, bool, &mut Window, &mut App) + Send + Sync>>,
     on_context_menu:
         Option<Arc<dyn Fn(&PathBuf, Point<Pixels>, &mut Window, &mut App) + Send + Sync>>,
+    on_selection_change: Option<SelectionHandler>,
     style: StyleRefinement,
 }
 
 impl FileTree {
     pub fn new() -> Self {
         Self {
+            id: "file-tree".into(),
             nodes: Vec::new(),
             selected_path: None,
+            selected_paths: Vec::new(),
             expanded_paths: Vec::new(),
             show_hidden: false,
             show_file_size: false,
@@ -252,10 +315,18 @@ impl FileTree {
             on_open: None,
             on_toggle: None,
             on_context_menu: None,
+            on_selection_change: None,
             style: StyleRefinement::default(),
         }
     }
 
+    /// Key for the tree's focus and range anchor. Needed when a window shows more than
+    /// one tree.
+    pub fn id(mut self, id: impl Into<ElementId>) -> Self {
+        self.id = id.into();
+        self
+    }
+
     pub fn nodes(mut self, mut nodes: Vec<FileNode>) -> Self {
         sort_file_nodes(&mut nodes);
         self.nodes = nodes;
@@ -267,6 +338,13 @@ impl FileTree {
         self
     }
 
+    /// All selected paths, for multi-selection. `selected_path` is treated as selected
+    /// too.
+    pub fn selected_paths(mut self, paths: Vec<PathBuf>) -> Self {
+        self.selected_paths = paths;
+        self
+    }
+
     pub fn expanded_paths(mut self, paths: Vec<PathBuf>) -> Self {
         self.expanded_paths = paths;
         self
@@ -306,6 +384,18 @@ impl FileTree {
         self
     }
 
+    /// Called with the new selection, in display order when it is a range, whenever a
+    /// click or key changes it. Cmd-click (ctrl-click elsewhere) toggles a path,
+    /// shift-click and shift+arrows extend a range, and cmd-a / ctrl-a selects every
+    /// visible item in the focused item's folder.
+    pub fn on_selection_change<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(Vec<PathBuf>, &mut Window, &mut App) + Send + Sync + 'static,
+    {
+        self.on_selection_change = Some(Arc::new(handler));
+        self
+    }
+
     pub fn on_context_menu<F>(mut self, handler: F) -> Self
     where
         F: Fn(&PathBuf, Point<Pixels>, &mut Window, &mut App) + Send + Sync + 'static,
@@ -328,31 +418,127 @@ impl Styled for FileTree {
 }
 
 impl RenderOnce for FileTree {
-    fn render(self, _window: &mut Window, _cx: &mut App) -> impl IntoElement {
+    fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
         let theme = use_theme();
         let user_style = self.style;
 
+        let selection_state = window.use_keyed_state(self.id.clone(), cx, |_, cx| SelectionState {
+            focus_handle: cx.focus_handle(),
+            anchor: None,
+            lead: None,
+            type_ahead: TypeAhead::new(),
+        });
+        let focus_handle = selection_state.read(cx).focus_handle.clone();
+        let filter = selection_state
+            .read(cx)
+            .type_ahead
+            .filter()
+            .map(str::to_string);
+
         let expanded_set: HashSet<PathBuf> = self.expanded_paths.into_iter().collect();
-        let flat_nodes = flatten_file_tree(&self.nodes, &expanded_set, 0, self.show_hidden);
+        let flat_nodes = match filter.as_deref().filter(|filter| !filter.is_empty()) {
+            Some(filter) => {
+                let filter = filter.to_lowercase();
+                let mut all_directories = HashSet::new();
+                collect_directories(&self.nodes, &mut all_directories);
+                let flat = flatten_file_tree(&self.nodes, &all_directories, 0, self.show_hidden);
+                let keep = keep_with_ancestors(
+                    &flat
+                        .iter()
+                        .map(|flat| (flat.level, flat.node.name.to_lowercase().contains(&filter)))
+                        .collect::<Vec<_>>(),
+                );
+                flat.into_iter()
+                    .zip(keep)
+                    .filter_map(|(flat, keep)| keep.then_some(flat))
+                    .collect()
+            }
+            None => flatten_file_tree(&self.nodes, &expanded_set, 0, self.show_hidden),
+        };
+        let visible: Rc<Vec<PathBuf>> = Rc::new(
+            flat_nodes
+                .iter()
+                .map(|flat| flat.node.path.clone())
+                .collect(),
+        );
+        let names: Vec<String> = flat_nodes
+            .iter()
+            .map(|flat| flat.node.name.clone())
+            .collect();
 
         let selected_path = self.selected_path;
+        let mut selection = self.selected_paths;
+        if let Some(path) = &selected_path {
+            if !selection.contains(path) {
+                selection.push(path.clone());
+            }
+        }
+        let selection = Rc::new(selection);
         let on_select = self.on_select;
         let on_open = self.on_open;
         let on_toggle = self.on_toggle;
         let on_context_menu = self.on_context_menu;
+        let on_selection_change = self.on_selection_change;
         let show_file_size = self.show_file_size;
 
         div()
+            .id(self.id)
+            .track_focus(&focus_handle)
             .flex()
             .flex_col()
             .w_full()
             .bg(gpui::transparent_black())
+            .on_key_down({
+                let visible = visible.clone();
+                let selection_state = selection_state.clone();
+                let on_select = on_select.clone();
+                let on_selection_change = on_selection_change.clone();
+                move |event, window, cx| {
+                    let type_ahead = selection_state.update(cx, |state, _| {
+                        state.type_ahead_key(&event.keystroke, &visible, &names)
+                    });
+                    if let Some(jumped) = type_ahead {
+                        if let Some(path) = jumped {
+                            if let Some(ref handler) = on_select {
+                                handler(&path, window, cx);
+                            }
+                            if let Some(ref handler) = on_selection_change {
+                                handler(vec![path], window, cx);
+                            }
+                        }
+                        window.refresh();
+                        cx.stop_propagation();
+                    } else if handle_selection_key(
+                        &event.keystroke,
+                        &visible,
+                        &selection_state,
+                        on_select.as_ref(),
+                        on_selection_change.as_ref(),
+                        window,
+                        cx,
+                    ) {
+                        cx.stop_propagation();
+                    }
+                }
+            })
             .map(|mut this| {
                 this.style().refine(&user_style);
                 this
             })
+            .when_some(filter, |this, filter| {
+                this.child(
+                    div()
+                        .mx(px(8.0))
+                        .px(px(8.0))
+                        .py(px(4.0))
+                        .text_size(px(12.0))
+                        .font_family(theme.tokens.font_mono.clone())
+                        .text_color(theme.tokens.muted_foreground)
+                        .child(format!("/{filter}")),
+                )
+            })
             .children(flat_nodes.into_iter().map(|flat_node| {
-                let is_selected = selected_path.as_ref() == Some(&flat_node.node.path);
+                let is_selected = selection.contains(&flat_node.node.path);
                 let is_expanded = expanded_set.contains(&flat_node.node.path);
                 let has_children =
                     !flat_node.node.children.is_empty() || flat_node.node.has_unloaded_children;
@@ -394,9 +580,52 @@ impl RenderOnce for FileTree {
                         let on_select = on_select.clone();
                         let on_toggle = on_toggle.clone();
                         let on_open = on_open.clone();
+                        let on_selection_change = on_selection_change.clone();
+                        let selection_state = selection_state.clone();
+                        let focus_handle = focus_handle.clone();
+                        let visible = visible.clone();
+                        let selection = selection.clone();
+                        let selected_path = selected_path.clone();
                         let is_dir = node.is_directory();
 
                         move |event, window, cx| {
+                            window.focus(&focus_handle);
+                            let modifiers = event.modifiers();
+                            if modifiers.shift || modifiers.secondary() {
+                                let paths = if modifiers.shift {
+                                    let anchor = selection_state
+                                        .read(cx)
+                                        .anchor
+                                        .clone()
+                                        .or_else(|| selected_path.clone())
+                                        .unwrap_or_else(|| path.clone());
+                                    path_range(&visible, &anchor, &path)
+                                } else if selection.contains(&path) {
+                                    selection.iter().filter(|p| **p != path).cloned().collect()
+                                } else {
+                                    let mut paths = selection.to_vec();
+                                    paths.push(path.clone());
+                                    paths
+                                };
+                                selection_state.update(cx, |state, _| {
+                                    if !modifiers.shift {
+                                        state.anchor = Some(path.clone());
+                                    }
+                                    state.lead = Some(path.clone());
+                                });
+                                if let Some(ref handler) = on_selection_change {
+                                    handler(paths, window, cx);
+                                }
+                                return;
+                            }
+
+                            selection_state.update(cx, |state, _| {
+                                state.anchor = Some(path.clone());
+                                state.lead = Some(path.clone());
+                            });
+                            if let Some(ref handler) = on_selection_change {
+                                handler(vec![path.clone()], window, cx);
+                            }
                             if let Some(ref handler) = on_select {
                                 handler(&path, window, cx);
                             }
@@ -475,3 +704,67 @@ impl RenderOnce for FileTree {
             }))
     }
 }
+
+/// Arrow keys move the selection, extending it with shift, and cmd-a / ctrl-a
+/// selects the focused item's folder. Returns whether the key was handled.
+fn handle_selection_key(
+    keystroke: &Keystroke,
+    visible: &[PathBuf],
+    state: &Entity<SelectionState>,
+    on_select: Option<&Arc<dyn Fn(&PathBuf, &mut Window, &mut App) + Send + Sync>>,
+    on_selection_change: Option<&SelectionHandler>,
+    window: &mut Window,
+    cx: &mut App,
+) -> bool {
+    let (anchor, lead) = {
+        let state = state.read(cx);
+        (state.anchor.clone(), state.lead.clone())
+    };
+    let lead_ix = lead
+        .as_ref()
+        .and_then(|lead| visible.iter().position(|path| path == lead));
+
+    let paths = match keystroke.key.as_str() {
+        "up" | "down" => {
+            let next = match lead_ix {
+                Some(ix) if keystroke.key == "up" => ix.saturating_sub(1),
+                Some(ix) => (ix + 1).min(visible.len().saturating_sub(1)),
+                None => 0,
+            };
+            let Some(next) = visible.get(next).cloned() else {
+                return false;
+            };
+            if keystroke.modifiers.shift {
+                let anchor = anchor.or(lead).unwrap_or_else(|| next.clone());
+                let paths = path_range(visible, &anchor, &next);
+                state.update(cx, |state, _| {
+                    state.anchor = Some(anchor);
+                    state.lead = Some(next);
+                });
+                paths
+            } else {
+                state.update(cx, |state, _| {
+                    state.anchor = Some(next.clone());
+                    state.lead = Some(next.clone());
+                });
+                if let Some(handler) = on_select {
+                    handler(&next, window, cx);
+                }
+                vec![next]
+            }
+        }
+        "a" if keystroke.modifiers.secondary() => match lead.as_deref().and_then(Path::parent) {
+            Some(folder) => visible
+                .iter()
+                .filter(|path| path.starts_with(folder) && path.as_path() != folder)
+                .cloned()
+                .collect(),
+            None => visible.to_vec(),
+        },
+        _ => return false,
+    };
+    if let Some(handler) = on_selection_change {
+        handler(paths, window, cx);
+    }
+    true
+}
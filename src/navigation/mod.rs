@@ -2,6 +2,7 @@
 
 pub mod app_menu;
 pub mod breadcrumbs;
+pub mod closed_tabs;
 pub mod file_tree;
 pub mod menu;
 pub mod sidebar;
@@ -6,7 +6,17 @@ use crate::theme::use_theme;
 use gpui::{prelude::FluentBuilder as _, *};
 use std::sync::Arc;
 
-actions!(tabs, [TabNext, TabPrevious, TabFirst, TabLast, TabClose]);
+actions!(
+    tabs,
+    [
+        TabNext,
+        TabPrevious,
+        TabFirst,
+        TabLast,
+        TabClose,
+        ReopenClosedTab
+    ]
+);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum TabVariant {
@@ -87,6 +97,7 @@ pub struct Tabs<T: Clone + PartialEq + 'static> {
     variant: TabVariant,
     on_change: Option<Arc<dyn Fn(&usize, &mut Window, &mut App) + Send + Sync + 'static>>,
     on_close: Option<Arc<dyn Fn(&T, &mut Window, &mut App) + Send + Sync + 'static>>,
+    on_reopen_closed: Option<Arc<dyn Fn(&mut Window, &mut App) + Send + Sync + 'static>>,
     style: StyleRefinement,
 }
 
@@ -105,6 +116,7 @@ impl<T: Clone + PartialEq + 'static> Tabs<T> {
             variant: TabVariant::default(),
             on_change: None,
             on_close: None,
+            on_reopen_closed: None,
             style: StyleRefinement::default(),
         }
     }
@@ -157,6 +169,17 @@ impl<T: Clone + PartialEq + 'static> Tabs<T> {
         self
     }
 
+    /// Handle [`ReopenClosedTab`] while focus is in the tabs, typically by popping a
+    /// [`ClosedTabs`](super::closed_tabs::ClosedTabs) stack the `on_close` handler
+    /// pushed to.
+    pub fn on_reopen_closed<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&mut Window, &mut App) + Send + Sync + 'static,
+    {
+        self.on_reopen_closed = Some(Arc::new(f));
+        self
+    }
+
     pub fn selected_tab_id(&self) -> Option<&T> {
         self.selected_index
             .and_then(|index| self.tabs.get(index))
@@ -403,7 +426,10 @@ impl<T: Clone + PartialEq + 'static> RenderOnce for Tabs<T> {
             );
         }
 
-        root.map(|this| {
+        root.when_some(self.on_reopen_closed, |this, on_reopen_closed| {
+            this.on_action(move |_: &ReopenClosedTab, window, cx| on_reopen_closed(window, cx))
+        })
+        .map(|this| {
             let mut div = this;
             div.style().refine(&user_style);
             div
@@ -418,5 +444,6 @@ pub fn init_tabs(cx: &mut App) {
         KeyBinding::new("home", TabFirst, Some("Tabs")),
         KeyBinding::new("end", TabLast, Some("Tabs")),
         KeyBinding::new("cmd-w", TabClose, Some("Tabs")),
+        KeyBinding::new("cmd-shift-t", ReopenClosedTab, None),
     ]);
 }
@@ -2,6 +2,8 @@
 
 use crate::components::icon::Icon;
 use crate::components::icon_source::IconSource;
+use crate::components::type_ahead::{keep_with_ancestors, TypeAhead, TypeAheadAction};
+use crate::navigation::virtual_list::v_virtual_list;
 use crate::theme::use_theme;
 use gpui::{prelude::*, *};
 use std::collections::{HashMap, HashSet};
@@ -600,6 +602,546 @@ impl<T: Clone + PartialEq + Eq + Hash + 'static> RenderOnce for TreeList<T> {
     }
 }
 
+/// A row of a [`TreeListState`], pointing into the node tree by child indices.
+#[derive(Clone)]
+struct TreeRow<T: Clone> {
+    id: T,
+    path: Vec<usize>,
+}
+
+impl<T: Clone> TreeRow<T> {
+    fn level(&self) -> usize {
+        self.path.len() - 1
+    }
+}
+
+fn node_at<'a, T: Clone>(nodes: &'a [TreeNode<T>], path: &[usize]) -> Option<&'a TreeNode<T>> {
+    let (first, rest) = path.split_first()?;
+    let node = nodes.get(*first)?;
+    if rest.is_empty() {
+        Some(node)
+    } else {
+        node_at(&node.children, rest)
+    }
+}
+
+fn node_at_mut<'a, T: Clone>(
+    nodes: &'a mut [TreeNode<T>],
+    path: &[usize],
+) -> Option<&'a mut TreeNode<T>> {
+    let (first, rest) = path.split_first()?;
+    let node = nodes.get_mut(*first)?;
+    if rest.is_empty() {
+        Some(node)
+    } else {
+        node_at_mut(&mut node.children, rest)
+    }
+}
+
+/// Append the visible rows of `nodes`, whose parent is at `parent_path`.
+fn flatten_rows<T: Clone + PartialEq + Eq + Hash>(
+    nodes: &[TreeNode<T>],
+    expanded: &HashSet<T>,
+    parent_path: &[usize],
+    rows: &mut Vec<TreeRow<T>>,
+) {
+    for (ix, node) in nodes.iter().enumerate() {
+        let mut path = parent_path.to_vec();
+        path.push(ix);
+        rows.push(TreeRow {
+            id: node.id.clone(),
+            path: path.clone(),
+        });
+        if expanded.contains(&node.id) {
+            flatten_rows(&node.children, expanded, &path, rows);
+        }
+    }
+}
+
+/// Append every row of `nodes` as if fully expanded, keeping only those whose label
+/// contains `filter` (lowercase) and their ancestors.
+fn filter_rows<T: Clone>(nodes: &[TreeNode<T>], filter: &str, rows: &mut Vec<TreeRow<T>>) {
+    fn walk<T: Clone>(
+        nodes: &[TreeNode<T>],
+        filter: &str,
+        parent_path: &[usize],
+        out: &mut Vec<(TreeRow<T>, bool)>,
+    ) {
+        for (ix, node) in nodes.iter().enumerate() {
+            let mut path = parent_path.to_vec();
+            path.push(ix);
+            let matches = node.label.to_lowercase().contains(filter);
+            out.push((
+                TreeRow {
+                    id: node.id.clone(),
+                    path: path.clone(),
+                },
+                matches,
+            ));
+            walk(&node.children, filter, &path, out);
+        }
+    }
+
+    let mut all = Vec::new();
+    walk(nodes, filter, &[], &mut all);
+    let keep = keep_with_ancestors(
+        &all.iter()
+            .map(|(row, matches)| (row.level(), *matches))
+            .collect::<Vec<_>>(),
+    );
+    rows.extend(
+        all.into_iter()
+            .zip(keep)
+            .filter_map(|((row, _), keep)| keep.then_some(row)),
+    );
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TreeListEvent<T> {
+    Select(T),
+    /// A node was expanded or collapsed. Nodes marked with lazy children can have them
+    /// supplied with [`TreeListState::set_children`] in response.
+    Toggle {
+        id: T,
+        expanded: bool,
+    },
+    ContextMenu {
+        id: T,
+        position: Point<Pixels>,
+    },
+}
+
+/// A tree that owns its nodes and renders only the rows in view, for trees with tens
+/// of thousands of nodes such as monorepo file trees.
+///
+/// Visible rows are kept as a flat list: expanding or collapsing a node splices its
+/// subtree in or out rather than re-flattening the whole tree. [`TreeList`] suits
+/// small trees driven entirely by the parent.
+///
+/// While focused, typing jumps to the next visible node starting with the typed text,
+/// and `/` opens a filter that shows matching nodes with their ancestors.
+pub struct TreeListState<T: Clone + PartialEq + Eq + Hash + 'static> {
+    nodes: Vec<TreeNode<T>>,
+    expanded: HashSet<T>,
+    selected: Option<T>,
+    rows: Vec<TreeRow<T>>,
+    item_sizes: Rc<Vec<Size<Pixels>>>,
+    scroll_handle: ScrollHandle,
+    focus_handle: FocusHandle,
+    type_ahead: TypeAhead,
+}
+
+impl<T: Clone + PartialEq + Eq + Hash + 'static> EventEmitter<TreeListEvent<T>>
+    for TreeListState<T>
+{
+}
+
+impl<T: Clone + PartialEq + Eq + Hash + 'static> TreeListState<T> {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        Self {
+            nodes: Vec::new(),
+            expanded: HashSet::new(),
+            selected: None,
+            rows: Vec::new(),
+            item_sizes: Rc::new(Vec::new()),
+            scroll_handle: ScrollHandle::new(),
+            focus_handle: cx.focus_handle(),
+            type_ahead: TypeAhead::new(),
+        }
+    }
+
+    /// Replace the whole tree. Expanded nodes that still exist stay expanded.
+    pub fn set_nodes(&mut self, nodes: Vec<TreeNode<T>>, cx: &mut Context<Self>) {
+        self.nodes = nodes;
+        self.rebuild_rows();
+        cx.notify();
+    }
+
+    /// Replace the children of `id`, e.g. once lazily loaded children arrive.
+    pub fn set_children(&mut self, id: &T, children: Vec<TreeNode<T>>, cx: &mut Context<Self>) {
+        let Some(ix) = self.row_index(id) else {
+            return;
+        };
+        if self.filter().is_some() {
+            let path = self.rows[ix].path.clone();
+            if let Some(node) = node_at_mut(&mut self.nodes, &path) {
+                node.children = children;
+                node.has_lazy_children = false;
+            }
+            self.rebuild_rows();
+            cx.notify();
+            return;
+        }
+        let path = self.rows[ix].path.clone();
+        let expanded = self.expanded.contains(id);
+        if expanded {
+            self.remove_subtree_rows(ix);
+        }
+        if let Some(node) = node_at_mut(&mut self.nodes, &path) {
+            node.children = children;
+            node.has_lazy_children = false;
+        }
+        if expanded {
+            self.insert_subtree_rows(ix);
+        }
+        self.sync_item_sizes();
+        cx.notify();
+    }
+
+    pub fn selected(&self) -> Option<&T> {
+        self.selected.as_ref()
+    }
+
+    pub fn set_selected(&mut self, id: Option<T>, cx: &mut Context<Self>) {
+        self.selected = id;
+        cx.notify();
+    }
+
+    pub fn is_expanded(&self, id: &T) -> bool {
+        self.expanded.contains(id)
+    }
+
+    /// Number of rows currently visible, i.e. nodes whose ancestors are all expanded.
+    pub fn visible_count(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn expand(&mut self, id: &T, cx: &mut Context<Self>) {
+        if let Some(ix) = self.row_index(id) {
+            self.set_row_expanded(ix, true, cx);
+        }
+    }
+
+    pub fn collapse(&mut self, id: &T, cx: &mut Context<Self>) {
+        if let Some(ix) = self.row_index(id) {
+            self.set_row_expanded(ix, false, cx);
+        }
+    }
+
+    pub fn toggle(&mut self, id: &T, cx: &mut Context<Self>) {
+        if let Some(ix) = self.row_index(id) {
+            let expanded = !self.expanded.contains(id);
+            self.set_row_expanded(ix, expanded, cx);
+        }
+    }
+
+    /// Expand every ancestor on `path`, a list of ids from a root node down to the
+    /// target, and scroll the target into view. Returns `false` if the path does not
+    /// lead to a node; ancestors found before that point stay expanded.
+    pub fn reveal_path(&mut self, path: &[T], cx: &mut Context<Self>) -> bool {
+        let Some((target, ancestors)) = path.split_last() else {
+            return false;
+        };
+        let mut search_from = 0;
+        for (depth, id) in ancestors.iter().enumerate() {
+            let Some(ix) = self.child_row(search_from, depth, id) else {
+                return false;
+            };
+            self.set_row_expanded(ix, true, cx);
+            search_from = ix + 1;
+        }
+        let Some(ix) = self.child_row(search_from, ancestors.len(), target) else {
+            return false;
+        };
+        self.scroll_to_row(ix);
+        cx.notify();
+        true
+    }
+
+    /// The inline filter's text, lowercased, while it narrows the rows.
+    fn filter(&self) -> Option<String> {
+        self.type_ahead
+            .filter()
+            .filter(|filter| !filter.is_empty())
+            .map(str::to_lowercase)
+    }
+
+    fn rebuild_rows(&mut self) {
+        self.rows.clear();
+        match self.filter() {
+            Some(filter) => filter_rows(&self.nodes, &filter, &mut self.rows),
+            None => flatten_rows(&self.nodes, &self.expanded, &[], &mut self.rows),
+        }
+        self.sync_item_sizes();
+    }
+
+    fn handle_key(&mut self, keystroke: &Keystroke, cx: &mut Context<Self>) -> bool {
+        match self.type_ahead.handle_key(keystroke) {
+            Some(TypeAheadAction::Jump) => {
+                let labels: Vec<&str> = self
+                    .rows
+                    .iter()
+                    .map(|row| {
+                        node_at(&self.nodes, &row.path).map_or("", |node| node.label.as_ref())
+                    })
+                    .collect();
+                let current = self.selected.as_ref().and_then(|id| self.row_index(id));
+                if let Some(ix) = self.type_ahead.find(&labels, current) {
+                    let id = self.rows[ix].id.clone();
+                    self.selected = Some(id.clone());
+                    self.scroll_to_row(ix);
+                    cx.emit(TreeListEvent::Select(id));
+                }
+            }
+            Some(TypeAheadAction::FilterChanged) => {
+                self.rebuild_rows();
+                if let Some(ix) = self.selected.as_ref().and_then(|id| self.row_index(id)) {
+                    self.scroll_to_row(ix);
+                }
+            }
+            None => return false,
+        }
+        cx.notify();
+        true
+    }
+
+    fn row_index(&self, id: &T) -> Option<usize> {
+        self.rows.iter().position(|row| &row.id == id)
+    }
+
+    /// The row for `id` at `level` within the run of rows starting at `from` that
+    /// shares a parent, which is all of them at the root.
+    fn child_row(&self, from: usize, level: usize, id: &T) -> Option<usize> {
+        self.rows[from.min(self.rows.len())..]
+            .iter()
+            .take_while(|row| row.level() >= level)
+            .position(|row| row.level() == level && &row.id == id)
+            .map(|offset| from + offset)
+    }
+
+    fn set_row_expanded(&mut self, ix: usize, expanded: bool, cx: &mut Context<Self>) {
+        let id = self.rows[ix].id.clone();
+        if self.expanded.contains(&id) == expanded {
+            return;
+        }
+        if self.filter().is_some() {
+            // Filtered rows show every match regardless of expansion.
+            if expanded {
+                self.expanded.insert(id.clone());
+            } else {
+                self.expanded.remove(&id);
+            }
+        } else if expanded {
+            self.expanded.insert(id.clone());
+            self.insert_subtree_rows(ix);
+        } else {
+            self.remove_subtree_rows(ix);
+            self.expanded.remove(&id);
+        }
+        self.sync_item_sizes();
+        cx.emit(TreeListEvent::Toggle { id, expanded });
+        cx.notify();
+    }
+
+    fn insert_subtree_rows(&mut self, ix: usize) {
+        let path = self.rows[ix].path.clone();
+        let Some(node) = node_at(&self.nodes, &path) else {
+            return;
+        };
+        let mut subtree = Vec::new();
+        flatten_rows(&node.children, &self.expanded, &path, &mut subtree);
+        self.rows.splice(ix + 1..ix + 1, subtree);
+    }
+
+    fn remove_subtree_rows(&mut self, ix: usize) {
+        let level = self.rows[ix].level();
+        let end = self.rows[ix + 1..]
+            .iter()
+            .position(|row| row.level() <= level)
+            .map_or(self.rows.len(), |offset| ix + 1 + offset);
+        self.rows.drain(ix + 1..end);
+    }
+
+    fn sync_item_sizes(&mut self) {
+        self.item_sizes = Rc::new(vec![size(px(0.), px(ROW_HEIGHT)); self.rows.len()]);
+    }
+
+    /// Scroll so row `ix` is centered, unless it is already fully in view.
+    fn scroll_to_row(&self, ix: usize) {
+        let viewport = self.scroll_handle.bounds().size.height;
+        let offset = self.scroll_handle.offset();
+        let top = px(ix as f32 * ROW_HEIGHT);
+        let bottom = top + px(ROW_HEIGHT);
+        if top >= -offset.y && bottom <= -offset.y + viewport {
+            return;
+        }
+        let content = px(self.rows.len() as f32 * ROW_HEIGHT);
+        let max_scroll = (content - viewport).max(px(0.));
+        let scroll = (top - (viewport - px(ROW_HEIGHT)) / 2.).clamp(px(0.), max_scroll);
+        self.scroll_handle.set_offset(point(offset.x, -scroll));
+    }
+
+    fn click_row(&mut self, ix: usize, cx: &mut Context<Self>) {
+        let Some(row) = self.rows.get(ix) else {
+            return;
+        };
+        let id = row.id.clone();
+        let has_children = node_at(&self.nodes, &row.path)
+            .is_some_and(|node| !node.children.is_empty() || node.has_lazy_children);
+        self.selected = Some(id.clone());
+        cx.emit(TreeListEvent::Select(id.clone()));
+        if has_children {
+            let expanded = !self.expanded.contains(&id);
+            self.set_row_expanded(ix, expanded, cx);
+        }
+        cx.notify();
+    }
+
+    fn render_row(&self, ix: usize, cx: &mut Context<Self>) -> AnyElement {
+        let theme = use_theme();
+        let row = &self.rows[ix];
+        let Some(node) = node_at(&self.nodes, &row.path) else {
+            return div().h(px(ROW_HEIGHT)).into_any_element();
+        };
+        let is_selected = self.selected.as_ref() == Some(&row.id);
+        let is_expanded = self.expanded.contains(&row.id);
+        let has_children = !node.children.is_empty() || node.has_lazy_children;
+        let indent = px((row.level() as f32) * 16.0);
+        let foreground = if is_selected {
+            theme.tokens.accent_foreground
+        } else if node.disabled {
+            theme.tokens.muted_foreground
+        } else {
+            theme.tokens.primary
+        };
+        // Keyed by node rather than position so row state survives rows shifting.
+        let key = {
+            use std::hash::Hasher;
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            row.id.hash(&mut hasher);
+            hasher.finish() as usize
+        };
+
+        div()
+            .id(("tree-row", key))
+            .w_full()
+            .h(px(ROW_HEIGHT))
+            .flex()
+            .items_center()
+            .px(px(8.0))
+            .pl(indent + px(8.0))
+            .cursor(if node.disabled {
+                CursorStyle::Arrow
+            } else {
+                CursorStyle::PointingHand
+            })
+            .when(is_selected, |this| this.bg(theme.tokens.accent))
+            .text_color(foreground)
+            .when(!node.disabled && !is_selected, |this| {
+                this.hover(|style| style.bg(theme.tokens.accent.opacity(0.5)))
+            })
+            .when(!node.disabled, |this| {
+                this.on_mouse_down(
+                    MouseButton::Left,
+                    cx.listener(move |tree, _: &MouseDownEvent, window, cx| {
+                        window.focus(&tree.focus_handle);
+                        tree.click_row(ix, cx)
+                    }),
+                )
+                .on_mouse_down(MouseButton::Right, {
+                    let id = row.id.clone();
+                    cx.listener(move |_, event: &MouseDownEvent, _, cx| {
+                        cx.emit(TreeListEvent::ContextMenu {
+                            id: id.clone(),
+                            position: event.position,
+                        });
+                    })
+                })
+            })
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap(px(8.0))
+                    .children(
+                        node.icon
+                            .as_ref()
+                            .map(|icon| Icon::new(icon.clone()).size(px(16.0)).color(foreground)),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_size(px(14.0))
+                            .font_family(theme.tokens.font_family.clone())
+                            .when(is_selected, |this| this.font_weight(FontWeight::SEMIBOLD))
+                            .child(node.label.clone()),
+                    )
+                    .when(has_children, |this| {
+                        this.child(
+                            div()
+                                .w(px(16.0))
+                                .h(px(16.0))
+                                .flex()
+                                .items_center()
+                                .justify_center()
+                                .child(
+                                    Icon::new(if is_expanded {
+                                        "arrow-down"
+                                    } else {
+                                        "arrow-right"
+                                    })
+                                    .size(px(12.0))
+                                    .color(theme.tokens.primary),
+                                ),
+                        )
+                    }),
+            )
+            .into_any_element()
+    }
+}
+
+impl<T: Clone + PartialEq + Eq + Hash + 'static> Focusable for TreeListState<T> {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl<T: Clone + PartialEq + Eq + Hash + 'static> Render for TreeListState<T> {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = use_theme();
+        let filter = self.type_ahead.filter().map(|filter| format!("/{filter}"));
+
+        div()
+            .size_full()
+            .flex()
+            .flex_col()
+            .bg(theme.tokens.background)
+            .track_focus(&self.focus_handle)
+            .on_key_down(cx.listener(|tree, event: &KeyDownEvent, _, cx| {
+                if tree.handle_key(&event.keystroke, cx) {
+                    cx.stop_propagation();
+                }
+            }))
+            .when_some(filter, |this, filter| {
+                this.child(
+                    div()
+                        .flex_shrink_0()
+                        .px(px(8.0))
+                        .py(px(4.0))
+                        .border_b_1()
+                        .border_color(theme.tokens.border)
+                        .text_size(px(13.0))
+                        .font_family(theme.tokens.font_mono.clone())
+                        .text_color(theme.tokens.foreground)
+                        .child(filter),
+                )
+            })
+            .child(
+                div().flex_1().min_h_0().child(
+                    v_virtual_list(
+                        cx.entity(),
+                        "tree-rows",
+                        self.item_sizes.clone(),
+                        |tree, range, _window, cx| {
+                            range.map(|ix| tree.render_row(ix, cx)).collect::<Vec<_>>()
+                        },
+                    )
+                    .track_scroll(&self.scroll_handle),
+                ),
+            )
+    }
+}
+
 #[derive(Clone)]
 pub struct ListItem<T: Clone> {
     pub id: T,
@@ -0,0 +1,587 @@
+//! Streaming updates from a WebSocket or server-sent events endpoint.
+//!
+//! A [`LiveFeed`] keeps the connection open on the background executor, reconnecting
+//! with exponential backoff, and turns each message into a value with the parse
+//! closure. Values are emitted as [`LiveFeedEvent::Message`] and pushed into any entity
+//! bound with [`LiveFeed::bind`], so a chart, log view or notification list can follow
+//! the stream without its own plumbing. [`ConnectionIndicator`] shows the connection
+//! state.
+//!
+//! ```rust,ignore
+//! let feed = cx.new(|cx| {
+//!     LiveFeed::new(
+//!         FeedSource::websocket("wss://example.com/prices"),
+//!         |text| serde_json::from_str::<Tick>(text).ok(),
+//!         cx,
+//!     )
+//!     .on_connect_message(r#"{"subscribe":"BTC-USD"}"#)
+//! });
+//! feed.update(cx, |feed, _| {
+//!     feed.bind(&chart, |chart, tick, cx| chart.push_point(tick.time, tick.price, cx));
+//! });
+//! ```
+//!
+//! Server-sent events are read through the app's HTTP client, which has to stream
+//! response bodies; the bundled `SimpleHttpClient` streams `text/event-stream` responses.
+
+use crate::theme::use_theme;
+use futures::{AsyncBufReadExt as _, SinkExt as _, StreamExt as _};
+use gpui::http_client::{AsyncBody, HttpClient, Request};
+use gpui::{prelude::FluentBuilder as _, *};
+use std::fmt;
+use std::hash::{BuildHasher as _, Hasher as _};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_tungstenite::tungstenite::client::IntoClientRequest as _;
+use async_tungstenite::tungstenite::Message;
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// Messages parsed ahead of the UI before the connection stops reading.
+const CHANNEL_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeedSource {
+    /// A `ws://` or `wss://` URL. Text frames are passed to the parse closure; binary
+    /// frames are passed when they are valid UTF-8.
+    WebSocket(SharedString),
+    /// An `http(s)://` URL serving `text/event-stream`. The `data` of each event is
+    /// passed to the parse closure, with multi-line data joined by newlines.
+    ServerSentEvents(SharedString),
+}
+
+impl FeedSource {
+    pub fn websocket(url: impl Into<SharedString>) -> Self {
+        Self::WebSocket(url.into())
+    }
+
+    pub fn sse(url: impl Into<SharedString>) -> Self {
+        Self::ServerSentEvents(url.into())
+    }
+
+    pub fn url(&self) -> &SharedString {
+        match self {
+            FeedSource::WebSocket(url) | FeedSource::ServerSentEvents(url) => url,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionStatus {
+    Connecting,
+    Connected,
+    /// The connection dropped; the next attempt starts after `retry_in`.
+    Reconnecting {
+        attempt: u32,
+        retry_in: Duration,
+        error: SharedString,
+    },
+    /// Stopped with [`LiveFeed::disconnect`].
+    Disconnected,
+}
+
+impl ConnectionStatus {
+    pub fn is_connected(&self) -> bool {
+        matches!(self, ConnectionStatus::Connected)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LiveFeedEvent<T> {
+    Message(T),
+    StatusChanged(ConnectionStatus),
+}
+
+enum FeedUpdate<T> {
+    Status(ConnectionStatus),
+    Message(T),
+}
+
+type ParseFn<T> = Arc<dyn Fn(&str) -> Option<T> + Send + Sync>;
+type Binding<T> = Box<dyn FnMut(&T, &mut App) -> bool>;
+
+/// A reconnecting WebSocket or server-sent events connection. Messages the parse
+/// closure returns `None` for are dropped.
+pub struct LiveFeed<T> {
+    source: FeedSource,
+    parse: ParseFn<T>,
+    headers: Vec<(SharedString, SharedString)>,
+    connect_messages: Vec<SharedString>,
+    status: ConnectionStatus,
+    bindings: Vec<Binding<T>>,
+    _task: Option<Task<()>>,
+}
+
+impl<T: 'static> EventEmitter<LiveFeedEvent<T>> for LiveFeed<T> {}
+
+impl<T: Send + 'static> LiveFeed<T> {
+    /// Create the feed and connect.
+    pub fn new(
+        source: FeedSource,
+        parse: impl Fn(&str) -> Option<T> + Send + Sync + 'static,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let mut this = Self {
+            source,
+            parse: Arc::new(parse),
+            headers: Vec::new(),
+            connect_messages: Vec::new(),
+            status: ConnectionStatus::Connecting,
+            bindings: Vec::new(),
+            _task: None,
+        };
+        this.connect(cx);
+        this
+    }
+
+    /// Send an extra header when connecting, e.g. `Authorization`.
+    pub fn header(mut self, name: impl Into<SharedString>, value: impl Into<SharedString>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Send `text` after every WebSocket (re)connect, e.g. a subscription request.
+    pub fn on_connect_message(mut self, text: impl Into<SharedString>) -> Self {
+        self.connect_messages.push(text.into());
+        self
+    }
+
+    pub fn source(&self) -> &FeedSource {
+        &self.source
+    }
+
+    pub fn status(&self) -> &ConnectionStatus {
+        &self.status
+    }
+
+    /// Apply every message to `target`. The binding is dropped with the target entity.
+    pub fn bind<V: 'static>(
+        &mut self,
+        target: &Entity<V>,
+        apply: impl Fn(&mut V, &T, &mut Context<V>) + 'static,
+    ) {
+        let target = target.downgrade();
+        self.bindings.push(Box::new(move |message, cx| {
+            target
+                .update(cx, |view, cx| apply(view, message, cx))
+                .is_ok()
+        }));
+    }
+
+    /// (Re)connect now, resetting the backoff.
+    pub fn connect(&mut self, cx: &mut Context<Self>) {
+        let (tx, rx) = smol::channel::bounded(CHANNEL_CAPACITY);
+        // The connection is set up when the task first runs, so builder options set
+        // after `new` apply to the first attempt too.
+        self._task = Some(cx.spawn(async move |this, cx| {
+            let Ok(connection) = this.update(cx, |this, cx| {
+                let client = cx.http_client();
+                let source = this.source.clone();
+                let headers = this.headers.clone();
+                let connect_messages = this.connect_messages.clone();
+                let parse = this.parse.clone();
+                cx.background_executor().spawn(async move {
+                    run_connection(client, source, headers, connect_messages, parse, tx).await
+                })
+            }) else {
+                return;
+            };
+            while let Ok(update) = rx.recv().await {
+                if this.update(cx, |this, cx| this.apply(update, cx)).is_err() {
+                    break;
+                }
+            }
+            drop(connection);
+        }));
+        self.set_status(ConnectionStatus::Connecting, cx);
+    }
+
+    /// Close the connection and stop reconnecting.
+    pub fn disconnect(&mut self, cx: &mut Context<Self>) {
+        self._task = None;
+        self.set_status(ConnectionStatus::Disconnected, cx);
+    }
+
+    fn apply(&mut self, update: FeedUpdate<T>, cx: &mut Context<Self>) {
+        match update {
+            FeedUpdate::Status(status) => self.set_status(status, cx),
+            FeedUpdate::Message(message) => {
+                self.bindings.retain_mut(|binding| binding(&message, cx));
+                cx.emit(LiveFeedEvent::Message(message));
+            }
+        }
+    }
+
+    fn set_status(&mut self, status: ConnectionStatus, cx: &mut Context<Self>) {
+        if self.status == status {
+            return;
+        }
+        match &status {
+            ConnectionStatus::Reconnecting {
+                attempt,
+                retry_in,
+                error,
+            } => tracing::warn!(
+                url = %self.source.url(),
+                attempt,
+                retry_in_ms = retry_in.as_millis() as u64,
+                %error,
+                "live feed disconnected"
+            ),
+            status => tracing::debug!(url = %self.source.url(), ?status, "live feed status"),
+        }
+        self.status = status.clone();
+        cx.emit(LiveFeedEvent::StatusChanged(status));
+        cx.notify();
+    }
+}
+
+/// Connect, read until the connection drops, back off, repeat. Returns when the
+/// receiving side is gone.
+async fn run_connection<T: Send + 'static>(
+    client: Arc<dyn HttpClient>,
+    source: FeedSource,
+    headers: Vec<(SharedString, SharedString)>,
+    connect_messages: Vec<SharedString>,
+    parse: ParseFn<T>,
+    tx: smol::channel::Sender<FeedUpdate<T>>,
+) {
+    let mut attempt = 0u32;
+    let mut sse_state = SseReconnect::default();
+    loop {
+        let result = match &source {
+            FeedSource::WebSocket(url) => {
+                read_websocket(url, &headers, &connect_messages, &parse, &tx, &mut attempt).await
+            }
+            FeedSource::ServerSentEvents(url) => {
+                read_event_stream(
+                    client.as_ref(),
+                    url,
+                    &headers,
+                    &parse,
+                    &tx,
+                    &mut attempt,
+                    &mut sse_state,
+                )
+                .await
+            }
+        };
+        if tx.is_closed() {
+            return;
+        }
+        let error = match result {
+            Ok(()) => SharedString::from("connection closed"),
+            Err(error) => error,
+        };
+        let retry_in = sse_state
+            .retry
+            .unwrap_or_else(|| backoff_delay(attempt, jitter()));
+        attempt += 1;
+        let status = ConnectionStatus::Reconnecting {
+            attempt,
+            retry_in,
+            error,
+        };
+        if tx.send(FeedUpdate::Status(status)).await.is_err() {
+            return;
+        }
+        smol::Timer::after(retry_in).await;
+        if tx
+            .send(FeedUpdate::Status(ConnectionStatus::Connecting))
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+async fn read_websocket<T>(
+    url: &str,
+    headers: &[(SharedString, SharedString)],
+    connect_messages: &[SharedString],
+    parse: &ParseFn<T>,
+    tx: &smol::channel::Sender<FeedUpdate<T>>,
+    attempt: &mut u32,
+) -> Result<(), SharedString> {
+    let error = |err: &dyn fmt::Display| SharedString::from(err.to_string());
+    let mut request = url.into_client_request().map_err(|err| error(&err))?;
+    for (name, value) in headers {
+        let name = async_tungstenite::tungstenite::http::HeaderName::from_bytes(name.as_bytes())
+            .map_err(|err| error(&err))?;
+        let value = value.parse().map_err(|err| error(&err))?;
+        request.headers_mut().insert(name, value);
+    }
+    let host = request
+        .uri()
+        .host()
+        .ok_or_else(|| SharedString::from("URL has no host"))?
+        .to_string();
+    let port = request
+        .uri()
+        .port_u16()
+        .unwrap_or(if request.uri().scheme_str() == Some("wss") {
+            443
+        } else {
+            80
+        });
+
+    let stream = smol::net::TcpStream::connect((host.as_str(), port))
+        .await
+        .map_err(|err| error(&err))?;
+    let (mut socket, _) = async_tungstenite::async_tls::client_async_tls(request, stream)
+        .await
+        .map_err(|err| error(&err))?;
+
+    for text in connect_messages {
+        socket
+            .send(Message::Text(text.to_string()))
+            .await
+            .map_err(|err| error(&err))?;
+    }
+    *attempt = 0;
+    if tx
+        .send(FeedUpdate::Status(ConnectionStatus::Connected))
+        .await
+        .is_err()
+    {
+        return Ok(());
+    }
+
+    while let Some(message) = socket.next().await {
+        let text = match message.map_err(|err| error(&err))? {
+            Message::Text(text) => text,
+            Message::Binary(bytes) => match String::from_utf8(bytes) {
+                Ok(text) => text,
+                Err(_) => continue,
+            },
+            Message::Close(_) => break,
+            _ => continue,
+        };
+        if let Some(value) = parse(&text) {
+            if tx.send(FeedUpdate::Message(value)).await.is_err() {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// What a server-sent event stream asked for across reconnects.
+#[derive(Debug, Default)]
+struct SseReconnect {
+    last_event_id: Option<String>,
+    retry: Option<Duration>,
+}
+
+async fn read_event_stream<T>(
+    client: &dyn HttpClient,
+    url: &str,
+    headers: &[(SharedString, SharedString)],
+    parse: &ParseFn<T>,
+    tx: &smol::channel::Sender<FeedUpdate<T>>,
+    attempt: &mut u32,
+    reconnect: &mut SseReconnect,
+) -> Result<(), SharedString> {
+    let mut request = Request::builder()
+        .uri(url)
+        .header("Accept", "text/event-stream")
+        .header("Cache-Control", "no-cache");
+    for (name, value) in headers {
+        request = request.header(name.as_ref(), value.as_ref());
+    }
+    if let Some(id) = &reconnect.last_event_id {
+        request = request.header("Last-Event-ID", id.as_str());
+    }
+    let request = request
+        .body(AsyncBody::default())
+        .map_err(|err| SharedString::from(err.to_string()))?;
+    let mut response = client
+        .send(request)
+        .await
+        .map_err(|err| SharedString::from(err.to_string()))?;
+    if !response.status().is_success() {
+        return Err(format!("server returned {}", response.status()).into());
+    }
+
+    *attempt = 0;
+    if tx
+        .send(FeedUpdate::Status(ConnectionStatus::Connected))
+        .await
+        .is_err()
+    {
+        return Ok(());
+    }
+
+    let mut lines = futures::io::BufReader::new(response.body_mut()).lines();
+    let mut parser = SseParser::default();
+    while let Some(line) = lines.next().await {
+        let line = line.map_err(|err| SharedString::from(err.to_string()))?;
+        let Some(data) = parser.feed_line(&line) else {
+            continue;
+        };
+        reconnect.last_event_id = parser.last_event_id.clone();
+        reconnect.retry = parser.retry;
+        if let Some(value) = parse(&data) {
+            if tx.send(FeedUpdate::Message(value)).await.is_err() {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Line-by-line parser for the `text/event-stream` format.
+#[derive(Debug, Default)]
+struct SseParser {
+    data: Vec<String>,
+    last_event_id: Option<String>,
+    retry: Option<Duration>,
+}
+
+impl SseParser {
+    /// Consume one line, without its terminator. Returns an event's data when `line`
+    /// is the blank line ending an event that had data.
+    fn feed_line(&mut self, line: &str) -> Option<String> {
+        if line.is_empty() {
+            if self.data.is_empty() {
+                return None;
+            }
+            return Some(std::mem::take(&mut self.data).join("\n"));
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "data" => self.data.push(value.to_string()),
+            "id" if !value.contains('\0') => self.last_event_id = Some(value.to_string()),
+            "retry" => {
+                if let Ok(millis) = value.parse() {
+                    self.retry = Some(Duration::from_millis(millis));
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Exponential backoff from [`INITIAL_BACKOFF`] to [`MAX_BACKOFF`], scaled by `jitter`
+/// (expected in `0.8..1.2`) so clients that dropped together don't retry together.
+fn backoff_delay(attempt: u32, jitter: f64) -> Duration {
+    let base = INITIAL_BACKOFF.saturating_mul(1u32 << attempt.min(16));
+    base.min(MAX_BACKOFF).mul_f64(jitter)
+}
+
+fn jitter() -> f64 {
+    let random = std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish();
+    0.8 + (random % 1000) as f64 / 2500.0
+}
+
+/// A dot and label for a feed's [`ConnectionStatus`], for toolbars and panel headers.
+#[derive(IntoElement)]
+pub struct ConnectionIndicator {
+    status: ConnectionStatus,
+    show_label: bool,
+    style: StyleRefinement,
+}
+
+impl ConnectionIndicator {
+    pub fn new(status: ConnectionStatus) -> Self {
+        Self {
+            status,
+            show_label: true,
+            style: StyleRefinement::default(),
+        }
+    }
+
+    /// Show only the dot, e.g. in a dense status bar.
+    pub fn dot_only(mut self) -> Self {
+        self.show_label = false;
+        self
+    }
+}
+
+impl Styled for ConnectionIndicator {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+impl RenderOnce for ConnectionIndicator {
+    fn render(self, _window: &mut Window, _cx: &mut App) -> impl IntoElement {
+        let theme = use_theme();
+        let user_style = self.style;
+        let (color, label) = match &self.status {
+            ConnectionStatus::Connected => (
+                hsla(142.0 / 360.0, 0.71, 0.45, 1.0),
+                SharedString::from("Live"),
+            ),
+            ConnectionStatus::Connecting => {
+                (hsla(45.0 / 360.0, 0.93, 0.47, 1.0), "Connecting…".into())
+            }
+            ConnectionStatus::Reconnecting { retry_in, .. } => (
+                hsla(45.0 / 360.0, 0.93, 0.47, 1.0),
+                SharedString::from(format!("Reconnecting in {}s", retry_in.as_secs().max(1))),
+            ),
+            ConnectionStatus::Disconnected => (theme.tokens.muted_foreground, "Offline".into()),
+        };
+
+        div()
+            .flex()
+            .items_center()
+            .gap(px(6.0))
+            .text_size(px(12.0))
+            .text_color(theme.tokens.muted_foreground)
+            .map(|mut this| {
+                this.style().refine(&user_style);
+                this
+            })
+            .child(div().size(px(8.0)).rounded_full().bg(color))
+            .when(self.show_label, |this| this.child(label))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_event_stream() {
+        let mut parser = SseParser::default();
+        let lines = [
+            ": keep-alive",
+            "event: tick",
+            "id: 7",
+            "data: {\"a\":1,",
+            "data:\"b\":2}",
+            "",
+            "retry: 2500",
+            "",
+            "data: second",
+            "",
+        ];
+        let events: Vec<String> = lines
+            .iter()
+            .filter_map(|line| parser.feed_line(line))
+            .collect();
+        assert_eq!(events, ["{\"a\":1,\n\"b\":2}", "second"]);
+        assert_eq!(parser.last_event_id.as_deref(), Some("7"));
+        assert_eq!(parser.retry, Some(Duration::from_millis(2500)));
+    }
+
+    #[test]
+    fn backs_off_exponentially() {
+        assert_eq!(backoff_delay(0, 1.0), INITIAL_BACKOFF);
+        assert_eq!(backoff_delay(3, 1.0), Duration::from_secs(4));
+        assert_eq!(backoff_delay(40, 1.0), MAX_BACKOFF);
+        assert_eq!(backoff_delay(1, 0.8), Duration::from_millis(800));
+        let jitter = jitter();
+        assert!((0.8..1.2).contains(&jitter));
+    }
+}
@@ -0,0 +1,8 @@
+//! Network data sources bound to entities: [`Resource`] for JSON endpoints that are
+//! fetched and refreshed, and [`LiveFeed`] for WebSocket and server-sent event streams.
+
+pub mod live_feed;
+pub mod resource;
+
+pub use live_feed::{ConnectionIndicator, ConnectionStatus, FeedSource, LiveFeed, LiveFeedEvent};
+pub use resource::{NetError, Resource, ResourceEvent};
@@ -0,0 +1,310 @@
+//! JSON resources fetched over HTTP and held in an entity.
+//!
+//! A [`Resource`] fetches a URL through the app's HTTP client, decodes the body on the
+//! background executor and keeps the latest value along with its loading and error
+//! state. Views observe the entity and re-render when it changes, and
+//! [`AsyncView::resource`](crate::components::async_view::AsyncView::resource) renders
+//! the spinner, error-with-retry and loaded states.
+//!
+//! ```rust,ignore
+//! #[derive(Deserialize)]
+//! struct Stats { active_users: u64 }
+//!
+//! let stats = cx.new(|cx| {
+//!     Resource::<Stats>::new("https://api.example.com/stats", cx)
+//!         .header("Authorization", format!("Bearer {}", token))
+//!         .poll_every(Duration::from_secs(30))
+//! });
+//! cx.observe(&stats, |_, _, cx| cx.notify()).detach();
+//!
+//! // in render
+//! AsyncView::resource("stats", &self.stats, cx, |stats| {
+//!     div().child(format!("{} active", stats.active_users)).into_any_element()
+//! })
+//! ```
+//!
+//! Refreshes are conditional: the `ETag` and `Last-Modified` of the last response are
+//! sent back, and a `304 Not Modified` keeps the current value without decoding.
+
+use crate::activity;
+use futures::AsyncReadExt as _;
+use gpui::http_client::{AsyncBody, HttpClient, Request};
+use gpui::*;
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::Instrument as _;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetError {
+    Network(SharedString),
+    /// The server answered with a non-success status.
+    Status(u16),
+    /// The body wasn't valid JSON for the expected type.
+    Decode(SharedString),
+}
+
+impl fmt::Display for NetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetError::Network(message) => write!(f, "network error: {}", message),
+            NetError::Status(status) => write!(f, "server returned {}", status),
+            NetError::Decode(message) => write!(f, "unexpected response: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for NetError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceEvent {
+    /// A new value was decoded.
+    Updated,
+    /// The server confirmed the current value is still fresh.
+    NotModified,
+    Failed(NetError),
+}
+
+/// Validators from the last response, sent back as conditional request headers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Validators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+enum Fetched<T> {
+    Data(T, Validators),
+    NotModified,
+}
+
+/// The latest value of a JSON endpoint. The value from the last successful fetch is
+/// kept while refreshing and after a failed refresh, so views don't flash empty.
+pub struct Resource<T> {
+    url: SharedString,
+    headers: Vec<(SharedString, SharedString)>,
+    data: Option<Arc<T>>,
+    loading: bool,
+    error: Option<NetError>,
+    validators: Validators,
+    fetched_at: Option<Instant>,
+    poll_interval: Option<Duration>,
+    _task: Option<Task<()>>,
+}
+
+impl<T: 'static> EventEmitter<ResourceEvent> for Resource<T> {}
+
+impl<T: DeserializeOwned + Send + Sync + 'static> Resource<T> {
+    /// Create the resource and start fetching it.
+    pub fn new(url: impl Into<SharedString>, cx: &mut Context<Self>) -> Self {
+        let mut this = Self {
+            url: url.into(),
+            headers: Vec::new(),
+            data: None,
+            loading: false,
+            error: None,
+            validators: Validators::default(),
+            fetched_at: None,
+            poll_interval: None,
+            _task: None,
+        };
+        this.refresh(cx);
+        this
+    }
+
+    /// Send an extra header with every request, e.g. `Authorization`.
+    pub fn header(mut self, name: impl Into<SharedString>, value: impl Into<SharedString>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Refetch every `interval` after each completed fetch. Polls are held back while
+    /// [`activity::should_poll`] is false.
+    pub fn poll_every(mut self, interval: Duration) -> Self {
+        self.poll_interval = Some(interval);
+        self
+    }
+
+    pub fn url(&self) -> &SharedString {
+        &self.url
+    }
+
+    pub fn data(&self) -> Option<&T> {
+        self.data.as_deref()
+    }
+
+    /// A shared handle to the current value, for moving it into closures or tasks.
+    pub fn data_arc(&self) -> Option<Arc<T>> {
+        self.data.clone()
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.loading
+    }
+
+    /// The error from the last fetch, cleared once a fetch succeeds.
+    pub fn error(&self) -> Option<&NetError> {
+        self.error.as_ref()
+    }
+
+    /// When the current value was last fetched or confirmed unchanged.
+    pub fn fetched_at(&self) -> Option<Instant> {
+        self.fetched_at
+    }
+
+    /// Point the resource at a different URL and fetch it. The current value stays
+    /// until the new one arrives.
+    pub fn set_url(&mut self, url: impl Into<SharedString>, cx: &mut Context<Self>) {
+        self.url = url.into();
+        self.validators = Validators::default();
+        self.refresh(cx);
+    }
+
+    /// Change how often the resource is refetched; `None` stops polling.
+    pub fn set_poll_interval(&mut self, interval: Option<Duration>, cx: &mut Context<Self>) {
+        self.poll_interval = interval;
+        if interval.is_some() && !self.loading {
+            self.refresh(cx);
+        }
+    }
+
+    /// Fetch now, replacing any fetch in flight. Polling continues from this fetch.
+    pub fn refresh(&mut self, cx: &mut Context<Self>) {
+        self.loading = true;
+        cx.notify();
+        // The request is built when the task first runs, so headers and polling set by
+        // the builders after `new` apply to the first fetch too.
+        self._task = Some(cx.spawn(async move |this, cx| loop {
+            let Ok(fetch) = this.update(cx, |this, cx| this.start_fetch(cx)) else {
+                break;
+            };
+            let result = fetch.await;
+            let interval = this.update(cx, |this, cx| {
+                this.finish_fetch(result, cx);
+                this.poll_interval
+            });
+            match interval {
+                Ok(Some(interval)) => smol::Timer::after(interval).await,
+                _ => break,
+            }
+            // Skip polls while the app is in the background or the user is away, and
+            // refetch as soon as they're back.
+            activity::wait_until_pollable(cx).await;
+            let polling = this.update(cx, |this, cx| {
+                this.loading = this.poll_interval.is_some();
+                cx.notify();
+                this.loading
+            });
+            if !matches!(polling, Ok(true)) {
+                break;
+            }
+        }));
+    }
+
+    /// Refresh if the value is missing or older than `max_age`.
+    pub fn refresh_if_stale(&mut self, max_age: Duration, cx: &mut Context<Self>) {
+        let fresh = self
+            .fetched_at
+            .is_some_and(|fetched_at| fetched_at.elapsed() < max_age);
+        if !fresh && !self.loading {
+            self.refresh(cx);
+        }
+    }
+
+    fn start_fetch(&self, cx: &mut Context<Self>) -> Task<Result<Fetched<T>, NetError>> {
+        let client = cx.http_client();
+        let url = self.url.clone();
+        let headers = self.headers.clone();
+        let validators = if self.data.is_some() {
+            self.validators.clone()
+        } else {
+            Validators::default()
+        };
+        cx.background_executor().spawn(async move {
+            let span = tracing::debug_span!("net.fetch", url = %url);
+            fetch_json(client.as_ref(), &url, &headers, &validators)
+                .instrument(span)
+                .await
+        })
+    }
+
+    fn finish_fetch(&mut self, result: Result<Fetched<T>, NetError>, cx: &mut Context<Self>) {
+        self.loading = false;
+        match result {
+            Ok(Fetched::Data(data, validators)) => {
+                self.data = Some(Arc::new(data));
+                self.validators = validators;
+                self.error = None;
+                self.fetched_at = Some(Instant::now());
+                cx.emit(ResourceEvent::Updated);
+            }
+            Ok(Fetched::NotModified) => {
+                self.error = None;
+                self.fetched_at = Some(Instant::now());
+                cx.emit(ResourceEvent::NotModified);
+            }
+            Err(err) => {
+                tracing::warn!(%err, url = %self.url, "resource fetch failed");
+                self.error = Some(err.clone());
+                cx.emit(ResourceEvent::Failed(err));
+            }
+        }
+        cx.notify();
+    }
+}
+
+async fn fetch_json<T: DeserializeOwned>(
+    client: &dyn HttpClient,
+    url: &str,
+    headers: &[(SharedString, SharedString)],
+    validators: &Validators,
+) -> Result<Fetched<T>, NetError> {
+    let mut request = Request::builder()
+        .uri(url)
+        .header("Accept", "application/json");
+    for (name, value) in headers {
+        request = request.header(name.as_ref(), value.as_ref());
+    }
+    if let Some(etag) = &validators.etag {
+        request = request.header("If-None-Match", etag.as_str());
+    }
+    if let Some(last_modified) = &validators.last_modified {
+        request = request.header("If-Modified-Since", last_modified.as_str());
+    }
+    let request = request
+        .body(AsyncBody::default())
+        .map_err(|err| NetError::Network(err.to_string().into()))?;
+
+    let mut response = client
+        .send(request)
+        .await
+        .map_err(|err| NetError::Network(err.to_string().into()))?;
+    let status = response.status();
+    if status.as_u16() == 304 {
+        return Ok(Fetched::NotModified);
+    }
+    if !status.is_success() {
+        return Err(NetError::Status(status.as_u16()));
+    }
+
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let validators = Validators {
+        etag: header("etag"),
+        last_modified: header("last-modified"),
+    };
+    let mut body = Vec::new();
+    response
+        .body_mut()
+        .read_to_end(&mut body)
+        .await
+        .map_err(|err| NetError::Network(err.to_string().into()))?;
+    let data =
+        serde_json::from_slice(&body).map_err(|err| NetError::Decode(err.to_string().into()))?;
+    Ok(Fetched::Data(data, validators))
+}
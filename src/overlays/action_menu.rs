@@ -0,0 +1,265 @@
+//! Secondary actions behind a chevron, also offered in the command palette.
+//!
+//! A component puts an [`ActionMenu`] next to its title and lists the actions that
+//! don't deserve a button of their own. While the menu is rendered, its actions are
+//! registered under the component's title, and [`palette_commands`] returns them as
+//! commands named e.g. "Terminal: Clear Scrollback", so they can be found and run
+//! from the keyboard without opening the menu. Disabled items and separators stay in
+//! the menu only; submenu items are offered as "Title: Submenu › Item".
+//!
+//! ```rust,ignore
+//! div()
+//!     .flex()
+//!     .child("Terminal")
+//!     .child(
+//!         ActionMenu::new("terminal-actions", "Terminal")
+//!             .item(MenuItem::new("clear", "Clear Scrollback").on_click(clear))
+//!             .item(MenuItem::new("split", "Split Right").with_shortcut("⌘D").on_click(split)),
+//!     )
+//!
+//! // when opening the palette:
+//! let mut commands = app_commands();
+//! commands.extend(action_menu::palette_commands(window, cx));
+//! ```
+
+use crate::components::icon::Icon;
+use crate::components::icon_source::IconSource;
+use crate::navigation::menu::{Menu, MenuItem, MenuItemKind};
+use crate::overlays::command_palette::Command;
+use crate::overlays::popover::{Popover, PopoverContent};
+use crate::theme::use_theme;
+use gpui::{prelude::FluentBuilder as _, *};
+use std::rc::Rc;
+
+struct RegisteredMenu {
+    id: EntityId,
+    key: SharedString,
+    window: AnyWindowHandle,
+    title: SharedString,
+    items: Vec<MenuItem>,
+}
+
+#[derive(Default)]
+struct ActionMenuRegistry {
+    menus: Vec<RegisteredMenu>,
+}
+
+impl Global for ActionMenuRegistry {}
+
+/// Keyed state behind a rendered menu; its release takes the menu's actions out of
+/// the palette.
+struct ActionMenuRegistration;
+
+fn unregister(id: EntityId, cx: &mut App) {
+    cx.default_global::<ActionMenuRegistry>()
+        .menus
+        .retain(|menu| menu.id != id);
+}
+
+/// Commands for the actions of every [`ActionMenu`] rendered in `window`, named after
+/// the component's title and grouped under it as the category.
+pub fn palette_commands(window: &Window, cx: &App) -> Vec<Command> {
+    let Some(registry) = cx.try_global::<ActionMenuRegistry>() else {
+        return Vec::new();
+    };
+    let handle = window.window_handle();
+    let mut commands = Vec::new();
+    for menu in registry.menus.iter().filter(|menu| menu.window == handle) {
+        collect_commands(menu, &menu.items, &[], &mut commands);
+    }
+    commands
+}
+
+fn collect_commands(
+    menu: &RegisteredMenu,
+    items: &[MenuItem],
+    path: &[SharedString],
+    commands: &mut Vec<Command>,
+) {
+    for item in items.iter().filter(|item| !item.disabled) {
+        match item.kind {
+            MenuItemKind::Separator => {}
+            MenuItemKind::Submenu => {
+                let mut path = path.to_vec();
+                path.push(item.label.clone());
+                collect_commands(menu, &item.children, &path, commands);
+            }
+            _ => {
+                let Some(on_click) = item.on_click.clone() else {
+                    continue;
+                };
+                let mut label: Vec<&str> = path.iter().map(|part| part.as_ref()).collect();
+                label.push(&item.label);
+                let mut command = Command::new(
+                    format!("{}.{}", menu.key, item.id),
+                    format!("{}: {}", menu.title, label.join(" › ")),
+                )
+                .category(menu.title.clone())
+                .on_select(move |window, cx| on_click(window, cx));
+                if let Some(icon) = item.icon.clone() {
+                    command = command.icon(icon);
+                }
+                if let Some(shortcut) = item.shortcut.clone() {
+                    command = command.shortcut(shortcut);
+                }
+                commands.push(command);
+            }
+        }
+    }
+}
+
+/// A chevron button opening a menu of a component's secondary actions. See the
+/// module docs.
+#[derive(IntoElement)]
+pub struct ActionMenu {
+    id: ElementId,
+    title: SharedString,
+    items: Vec<MenuItem>,
+    icon: IconSource,
+    anchor: Corner,
+    in_palette: bool,
+    style: StyleRefinement,
+}
+
+impl ActionMenu {
+    /// `title` names the component; it prefixes the actions in the command palette.
+    pub fn new(id: impl Into<ElementId>, title: impl Into<SharedString>) -> Self {
+        Self {
+            id: id.into(),
+            title: title.into(),
+            items: Vec::new(),
+            icon: IconSource::from("chevron-down"),
+            anchor: Corner::TopRight,
+            in_palette: true,
+            style: StyleRefinement::default(),
+        }
+    }
+
+    pub fn item(mut self, item: MenuItem) -> Self {
+        self.items.push(item);
+        self
+    }
+
+    pub fn items(mut self, items: impl IntoIterator<Item = MenuItem>) -> Self {
+        self.items.extend(items);
+        self
+    }
+
+    /// The button's icon. Defaults to a chevron; `"ellipsis"` suits toolbars.
+    pub fn icon(mut self, icon: impl Into<IconSource>) -> Self {
+        self.icon = icon.into();
+        self
+    }
+
+    /// Which corner of the button the menu lines up with. Defaults to the top right,
+    /// so the menu opens below and extends to the left.
+    pub fn anchor(mut self, anchor: Corner) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// Whether the actions are offered in the command palette too. Defaults to true.
+    pub fn in_palette(mut self, in_palette: bool) -> Self {
+        self.in_palette = in_palette;
+        self
+    }
+
+    fn register(&self, window: &mut Window, cx: &mut App) {
+        let registration = window.use_keyed_state(self.id.clone(), cx, |window, cx| {
+            let id = cx.entity_id();
+            cx.default_global::<ActionMenuRegistry>()
+                .menus
+                .push(RegisteredMenu {
+                    id,
+                    key: SharedString::default(),
+                    window: window.window_handle(),
+                    title: SharedString::default(),
+                    items: Vec::new(),
+                });
+            cx.on_release(move |_: &mut ActionMenuRegistration, cx| unregister(id, cx))
+                .detach();
+            ActionMenuRegistration
+        });
+        let id = registration.entity_id();
+        let registry = cx.default_global::<ActionMenuRegistry>();
+        if let Some(menu) = registry.menus.iter_mut().find(|menu| menu.id == id) {
+            menu.key = self.id.to_string().into();
+            menu.title = self.title.clone();
+            menu.items = if self.in_palette {
+                self.items.clone()
+            } else {
+                Vec::new()
+            };
+        }
+    }
+}
+
+impl Styled for ActionMenu {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+/// `items` with each handler also closing the menu's popover.
+fn closing_items(items: Vec<MenuItem>, popover: &Entity<PopoverContent>) -> Vec<MenuItem> {
+    items
+        .into_iter()
+        .map(|mut item| {
+            item.children = closing_items(std::mem::take(&mut item.children), popover);
+            if let Some(handler) = item.on_click.take() {
+                let popover = popover.clone();
+                item.on_click = Some(Rc::new(move |window: &mut Window, cx: &mut App| {
+                    popover.update(cx, |_, cx| cx.emit(DismissEvent));
+                    handler(window, cx);
+                }));
+            }
+            item
+        })
+        .collect()
+}
+
+impl RenderOnce for ActionMenu {
+    fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let theme = use_theme();
+        self.register(window, cx);
+
+        let user_style = self.style;
+        let items = self.items;
+        let trigger = div()
+            .flex()
+            .items_center()
+            .justify_center()
+            .size(px(22.0))
+            .rounded(theme.tokens.radius_sm)
+            .cursor_pointer()
+            .hover(|style| style.bg(theme.tokens.muted))
+            .child(
+                Icon::new(self.icon)
+                    .size(px(14.0))
+                    .color(theme.tokens.muted_foreground),
+            )
+            .map(|this| {
+                let mut div = this;
+                div.style().refine(&user_style);
+                div
+            });
+
+        Popover::new(self.id)
+            .anchor(self.anchor)
+            .trigger(trigger)
+            .content(move |window, cx| {
+                let items = items.clone();
+                cx.new(|cx| {
+                    PopoverContent::new(window, cx, move |_, cx: &mut Context<PopoverContent>| {
+                        let popover = cx.entity();
+                        // The popover draws the panel; the menu only lays out the items.
+                        Menu::new(closing_items(items.clone(), &popover))
+                            .border_0()
+                            .shadow_none()
+                            .p_0()
+                            .into_any_element()
+                    })
+                })
+            })
+    }
+}
@@ -172,7 +172,14 @@ impl CommandPaletteState {
     pub fn execute_selected(&mut self, window: &mut Window, cx: &mut App) -> bool {
         if let Some(command) = self.filtered_commands.get(self.selected_index) {
             if let Some(handler) = &command.on_select {
+                let _span = tracing::debug_span!("command_palette.execute", command = %command.id)
+                    .entered();
                 handler(window, cx);
+                crate::telemetry::track(
+                    "command_palette.execute",
+                    [("command", command.id.clone())],
+                    cx,
+                );
                 self.recent_commands.push(command.id.clone());
                 if self.recent_commands.len() > 10 {
                     self.recent_commands.remove(0);
@@ -408,8 +415,14 @@ fn render_command_item(command: Command, selected: bool, _cx: &App) -> impl Into
             div.hover(|style| style.bg(theme.tokens.muted))
         })
         .when_some(command.on_select, |div, handler| {
+            let command_id = command.id.clone();
             div.on_mouse_down(MouseButton::Left, move |_event, window, cx| {
                 handler(window, cx);
+                crate::telemetry::track(
+                    "command_palette.execute",
+                    [("command", command_id.clone())],
+                    cx,
+                );
             })
         })
         .when_some(command.icon, |div, icon| {
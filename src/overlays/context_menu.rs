@@ -5,6 +5,7 @@ use std::rc::Rc;
 use std::time::Duration;
 
 use crate::animations::easings;
+use crate::overlays::dismiss::{dismiss_layer, OutsideClick};
 use crate::theme::use_theme;
 
 #[derive(Clone)]
@@ -66,6 +67,7 @@ pub struct ContextMenu {
     position: Point<Pixels>,
     items: Vec<ContextMenuItem>,
     on_close: Option<Rc<dyn Fn(&mut Window, &mut App)>>,
+    outside_click: OutsideClick,
     dismissing: bool,
     style: StyleRefinement,
 }
@@ -76,6 +78,7 @@ impl ContextMenu {
             position,
             items: Vec::new(),
             on_close: None,
+            outside_click: OutsideClick::Dismiss,
             dismissing: false,
             style: StyleRefinement::default(),
         }
@@ -103,6 +106,13 @@ impl ContextMenu {
         self.on_close = Some(Rc::new(handler));
         self
     }
+
+    /// What clicking outside the menu does once `on_close` is set. Defaults to closing
+    /// it and letting the click through.
+    pub fn outside_click(mut self, policy: OutsideClick) -> Self {
+        self.outside_click = policy;
+        self
+    }
 }
 
 impl Styled for ContextMenu {
@@ -116,21 +126,16 @@ impl RenderOnce for ContextMenu {
         let theme = use_theme();
         let position = self.position;
         let on_close_handler = self.on_close.clone();
+        let outside_click = self.outside_click;
         let user_style = self.style;
         let dismissing = self.dismissing;
 
         div()
             .absolute()
             .inset_0()
-            .when(on_close_handler.is_some(), |this: Div| {
-                let handler = on_close_handler.clone().unwrap();
-                let handler2 = handler.clone();
-                this.on_mouse_down(MouseButton::Left, move |_, window, cx| {
-                    handler(window, cx);
-                })
-                .on_mouse_down(MouseButton::Right, move |_, window, cx| {
-                    handler2(window, cx);
-                })
+            // A menu that swallows outside clicks keeps hover off the content below too.
+            .when(outside_click == OutsideClick::DismissAndBlock, |this| {
+                this.occlude()
             })
             .child(
                 div()
@@ -157,6 +162,15 @@ impl RenderOnce for ContextMenu {
                         div
                     })
                     .on_mouse_down(MouseButton::Left, |_, _, _| {})
+                    .when_some(on_close_handler.filter(|_| !dismissing), |this, handler| {
+                        this.child(
+                            dismiss_layer("context-menu-dismiss", move |window, cx| {
+                                handler(window, cx)
+                            })
+                            .outside_click(outside_click)
+                            .dismiss_on_scroll(true),
+                        )
+                    })
                     .children(self.items.into_iter().map(|item| {
                         if item.label.is_empty() && item.divider {
                             return div()
@@ -0,0 +1,172 @@
+//! Full-window "Something went wrong" overlay for recovered panics.
+
+use crate::components::button::{Button, ButtonSize, ButtonVariant};
+use crate::components::icon::Icon;
+use crate::components::text::{h3, muted};
+use crate::crash_reporter::{self, CrashReport};
+use crate::theme::use_theme;
+use gpui::{prelude::FluentBuilder as _, *};
+use std::rc::Rc;
+
+#[derive(IntoElement)]
+pub struct CrashOverlay {
+    report: CrashReport,
+    on_dismiss: Option<Rc<dyn Fn(&mut Window, &mut App)>>,
+    show_restart: bool,
+    style: StyleRefinement,
+}
+
+impl CrashOverlay {
+    pub fn new(report: CrashReport) -> Self {
+        Self {
+            report,
+            on_dismiss: None,
+            show_restart: true,
+            style: StyleRefinement::default(),
+        }
+    }
+
+    /// Show a "Dismiss" button. The recorded crash is cleared before `handler` runs.
+    pub fn on_dismiss(mut self, handler: impl Fn(&mut Window, &mut App) + 'static) -> Self {
+        self.on_dismiss = Some(Rc::new(handler));
+        self
+    }
+
+    pub fn show_restart(mut self, show: bool) -> Self {
+        self.show_restart = show;
+        self
+    }
+}
+
+impl Styled for CrashOverlay {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+impl RenderOnce for CrashOverlay {
+    fn render(self, _window: &mut Window, _cx: &mut App) -> impl IntoElement {
+        let theme = use_theme();
+        let user_style = self.style;
+        let details = self.report.details();
+        let summary = match &self.report.location {
+            Some(location) => format!("{}\n  at {}", self.report.message, location),
+            None => self.report.message.clone(),
+        };
+
+        div()
+            .absolute()
+            .inset_0()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(theme.tokens.background.opacity(0.96))
+            .map(|this| {
+                let mut div = this;
+                div.style().refine(&user_style);
+                div
+            })
+            .child(
+                div()
+                    .w(px(560.0))
+                    .max_w(relative(0.9))
+                    .flex()
+                    .flex_col()
+                    .gap(px(16.0))
+                    .p(px(24.0))
+                    .bg(theme.tokens.card)
+                    .border_1()
+                    .border_color(theme.tokens.border)
+                    .rounded(theme.tokens.radius_lg)
+                    .shadow(smallvec::smallvec![theme.tokens.shadow_lg.clone()])
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap(px(10.0))
+                            .child(
+                                Icon::new("triangle-alert")
+                                    .size(px(22.0))
+                                    .color(theme.tokens.destructive),
+                            )
+                            .child(h3("Something went wrong")),
+                    )
+                    .child(muted(
+                        "An unexpected error occurred. Copy the details below when reporting the problem.",
+                    ))
+                    .child(
+                        div()
+                            .id("crash-details")
+                            .max_h(px(200.0))
+                            .overflow_y_scroll()
+                            .p(px(12.0))
+                            .rounded(theme.tokens.radius_md)
+                            .bg(theme.tokens.muted)
+                            .font_family(theme.tokens.font_mono.clone())
+                            .text_size(px(12.0))
+                            .text_color(theme.tokens.foreground)
+                            .child(summary),
+                    )
+                    .child(
+                        div()
+                            .flex()
+                            .justify_end()
+                            .gap(px(8.0))
+                            .child(
+                                Button::new("crash-copy", "Copy Details")
+                                    .variant(ButtonVariant::Outline)
+                                    .size(ButtonSize::Sm)
+                                    .icon("copy")
+                                    .on_click(move |_, _, cx| {
+                                        cx.write_to_clipboard(ClipboardItem::new_string(
+                                            details.clone(),
+                                        ));
+                                    }),
+                            )
+                            .when_some(self.on_dismiss, |this, on_dismiss| {
+                                this.child(
+                                    Button::new("crash-dismiss", "Dismiss")
+                                        .variant(ButtonVariant::Ghost)
+                                        .size(ButtonSize::Sm)
+                                        .on_click(move |_, window, cx| {
+                                            crash_reporter::take_crash();
+                                            on_dismiss(window, cx);
+                                        }),
+                                )
+                            })
+                            .when(self.show_restart, |this| {
+                                this.child(
+                                    Button::new("crash-restart", "Restart")
+                                        .variant(ButtonVariant::Default)
+                                        .size(ButtonSize::Sm)
+                                        .icon("rotate-ccw")
+                                        .on_click(|_, _, cx| crash_reporter::restart_app(cx)),
+                                )
+                            }),
+                    ),
+            )
+    }
+}
+
+/// Build `content`, or a [`CrashOverlay`] if a crash was recorded or building the
+/// content panics. Only panics raised while building `content` are caught: ones raised
+/// later, during layout, paint or event handling, end the process and reach only the
+/// panic hook's crash log, never this overlay.
+pub fn crash_boundary(
+    content: impl FnOnce(&mut Window, &mut App) -> AnyElement,
+    window: &mut Window,
+    cx: &mut App,
+) -> AnyElement {
+    let content = match crash_reporter::last_crash() {
+        Some(report) => Err(report),
+        None => crash_reporter::catch_crash(|| content(window, cx)),
+    };
+    match content {
+        Ok(element) => element,
+        Err(report) => div()
+            .relative()
+            .size_full()
+            .child(CrashOverlay::new(report).on_dismiss(|window, _| window.refresh()))
+            .into_any_element(),
+    }
+}
@@ -0,0 +1,461 @@
+//! In-app console that tails `tracing` output.
+//!
+//! Components emit `tracing` spans and events (`editor.parse`, `editor.search`,
+//! `command_palette.execute`, `chart.*`, ...). [`DebugConsoleLayer`] captures them into
+//! a bounded in-memory log, and [`DebugConsole`] renders that log as a bottom panel with
+//! level filters, toggled with `cmd-alt-shift-d` / `ctrl-alt-shift-d`.
+//!
+//! ```rust,ignore
+//! // Without a subscriber of your own:
+//! debug_console::install_subscriber();
+//! // Or alongside an existing one:
+//! tracing_subscriber::registry().with(fmt::layer()).with(DebugConsoleLayer::new()).init();
+//!
+//! let console = cx.new(DebugConsole::new);
+//! div().relative().size_full().child(app).child(console.clone())
+//! ```
+
+use crate::components::button::{Button, ButtonSize, ButtonVariant};
+use crate::components::icon::Icon;
+use crate::components::icon_button::IconButton;
+use crate::components::text::caption;
+use crate::navigation::virtual_list::v_virtual_list;
+use crate::theme::use_theme;
+use gpui::{prelude::FluentBuilder as _, *};
+use once_cell::sync::Lazy;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::rc::Rc;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context as LayerContext, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+actions!(debug_console, [ToggleDebugConsole]);
+
+const DEFAULT_CAPACITY: usize = 5_000;
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+static LOG: Lazy<Mutex<LogBuffer>> = Lazy::new(|| {
+    Mutex::new(LogBuffer {
+        records: VecDeque::new(),
+        capacity: DEFAULT_CAPACITY,
+        generation: 0,
+    })
+});
+
+struct LogBuffer {
+    records: VecDeque<LogRecord>,
+    capacity: usize,
+    generation: u64,
+}
+
+/// One captured `tracing` event.
+#[derive(Debug, Clone)]
+pub struct LogRecord {
+    pub level: Level,
+    pub target: String,
+    /// Names of the enclosing spans, outermost first, joined with `:`.
+    pub spans: String,
+    pub message: String,
+    pub fields: Vec<(&'static str, String)>,
+    pub timestamp: SystemTime,
+}
+
+impl LogRecord {
+    fn display_line(&self) -> String {
+        let secs = self
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs_f64() % 86_400.0)
+            .unwrap_or_default();
+        let mut line = format!(
+            "{:02}:{:02}:{:06.3} {:<5} {}",
+            (secs / 3600.0) as u32,
+            (secs % 3600.0 / 60.0) as u32,
+            secs % 60.0,
+            self.level,
+            self.target
+        );
+        if !self.spans.is_empty() {
+            let _ = write!(line, " [{}]", self.spans);
+        }
+        let _ = write!(line, " {}", self.message);
+        for (name, value) in &self.fields {
+            let _ = write!(line, " {}={}", name, value);
+        }
+        line
+    }
+}
+
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: Vec<(&'static str, String)>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields.push((field.name(), value.to_string()));
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.fields.push((field.name(), format!("{:?}", value)));
+        }
+    }
+}
+
+/// `tracing_subscriber` layer that feeds the debug console.
+pub struct DebugConsoleLayer {
+    max_level: Level,
+}
+
+impl DebugConsoleLayer {
+    /// Capture `DEBUG` and more severe events.
+    pub fn new() -> Self {
+        Self {
+            max_level: Level::DEBUG,
+        }
+    }
+
+    /// Most verbose level captured, e.g. `Level::TRACE` to include chart render spans.
+    pub fn max_level(mut self, level: Level) -> Self {
+        self.max_level = level;
+        self
+    }
+}
+
+impl Default for DebugConsoleLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for DebugConsoleLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &Event<'_>, ctx: LayerContext<'_, S>) {
+        let metadata = event.metadata();
+        if *metadata.level() > self.max_level {
+            return;
+        }
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let spans = ctx
+            .event_scope(event)
+            .map(|scope| {
+                scope
+                    .from_root()
+                    .map(|span| span.name())
+                    .collect::<Vec<_>>()
+                    .join(":")
+            })
+            .unwrap_or_default();
+
+        push_record(LogRecord {
+            level: *metadata.level(),
+            target: metadata.target().to_string(),
+            spans,
+            message: visitor.message,
+            fields: visitor.fields,
+            timestamp: SystemTime::now(),
+        });
+    }
+}
+
+fn push_record(record: LogRecord) {
+    if let Ok(mut log) = LOG.lock() {
+        log.records.push_back(record);
+        while log.records.len() > log.capacity {
+            log.records.pop_front();
+        }
+        log.generation += 1;
+    }
+}
+
+/// Install a global subscriber whose only layer is [`DebugConsoleLayer`]. Returns
+/// `false` if the app already installed a global subscriber.
+pub fn install_subscriber() -> bool {
+    use tracing_subscriber::layer::SubscriberExt as _;
+    let subscriber = tracing_subscriber::registry().with(DebugConsoleLayer::new());
+    tracing::subscriber::set_global_default(subscriber).is_ok()
+}
+
+/// Number of records retained before the oldest are dropped.
+pub fn set_capacity(capacity: usize) {
+    if let Ok(mut log) = LOG.lock() {
+        log.capacity = capacity.max(1);
+        while log.records.len() > log.capacity {
+            log.records.pop_front();
+        }
+    }
+}
+
+pub fn clear_log() {
+    if let Ok(mut log) = LOG.lock() {
+        log.records.clear();
+        log.generation += 1;
+    }
+}
+
+fn log_generation() -> u64 {
+    LOG.lock().map(|log| log.generation).unwrap_or(0)
+}
+
+#[derive(Default)]
+struct DebugConsoleVisibility(bool);
+
+impl Global for DebugConsoleVisibility {}
+
+/// Show or hide every [`DebugConsole`].
+pub fn toggle(cx: &mut App) {
+    let visible = !is_visible(cx);
+    set_visible(visible, cx);
+}
+
+pub fn set_visible(visible: bool, cx: &mut App) {
+    cx.set_global(DebugConsoleVisibility(visible));
+}
+
+pub fn is_visible(cx: &App) -> bool {
+    cx.try_global::<DebugConsoleVisibility>()
+        .is_some_and(|visibility| visibility.0)
+}
+
+pub fn init(cx: &mut App) {
+    cx.on_action(|_: &ToggleDebugConsole, cx| toggle(cx));
+    #[cfg(target_os = "macos")]
+    cx.bind_keys([KeyBinding::new("cmd-alt-shift-d", ToggleDebugConsole, None)]);
+    #[cfg(not(target_os = "macos"))]
+    cx.bind_keys([KeyBinding::new(
+        "ctrl-alt-shift-d",
+        ToggleDebugConsole,
+        None,
+    )]);
+}
+
+const LEVELS: [Level; 5] = [
+    Level::ERROR,
+    Level::WARN,
+    Level::INFO,
+    Level::DEBUG,
+    Level::TRACE,
+];
+
+/// Bottom panel tailing the captured log. Renders nothing while hidden.
+pub struct DebugConsole {
+    min_level: Level,
+    visible_records: Rc<Vec<LogRecord>>,
+    seen_generation: u64,
+    scroll_handle: ScrollHandle,
+    follow_tail: bool,
+    height: Pixels,
+    _poll_task: Task<()>,
+}
+
+impl DebugConsole {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        cx.observe_global::<DebugConsoleVisibility>(|this, cx| {
+            this.refresh(cx);
+            cx.notify();
+        })
+        .detach();
+
+        let poll_task = cx.spawn(async |this, cx| loop {
+            smol::Timer::after(POLL_INTERVAL).await;
+            let ok = this
+                .update(cx, |console, cx| {
+                    if is_visible(cx) && log_generation() != console.seen_generation {
+                        console.refresh(cx);
+                        cx.notify();
+                    }
+                })
+                .is_ok();
+            if !ok {
+                break;
+            }
+        });
+
+        Self {
+            min_level: Level::INFO,
+            visible_records: Rc::new(Vec::new()),
+            seen_generation: u64::MAX,
+            scroll_handle: ScrollHandle::new(),
+            follow_tail: true,
+            height: px(260.0),
+            _poll_task: poll_task,
+        }
+    }
+
+    pub fn height(mut self, height: impl Into<Pixels>) -> Self {
+        self.height = height.into();
+        self
+    }
+
+    /// Least severe level shown. Records below it stay in the log.
+    pub fn set_level(&mut self, level: Level, cx: &mut Context<Self>) {
+        self.min_level = level;
+        self.seen_generation = u64::MAX;
+        self.refresh(cx);
+        cx.notify();
+    }
+
+    fn refresh(&mut self, cx: &mut Context<Self>) {
+        if !is_visible(cx) {
+            return;
+        }
+        let Ok(log) = LOG.lock() else {
+            return;
+        };
+        if log.generation == self.seen_generation {
+            return;
+        }
+        self.seen_generation = log.generation;
+        self.visible_records = Rc::new(
+            log.records
+                .iter()
+                .filter(|record| record.level <= self.min_level)
+                .cloned()
+                .collect(),
+        );
+        if self.follow_tail {
+            self.scroll_handle.scroll_to_bottom();
+        }
+    }
+}
+
+fn level_color(level: Level) -> Hsla {
+    let theme = use_theme();
+    match level {
+        Level::ERROR => theme.tokens.destructive,
+        Level::WARN => hsla(0.11, 0.85, 0.55, 1.0),
+        Level::INFO => theme.tokens.foreground,
+        _ => theme.tokens.muted_foreground,
+    }
+}
+
+impl Render for DebugConsole {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if !is_visible(cx) {
+            return div().into_any_element();
+        }
+        let theme = use_theme();
+        let line_height = px(18.0);
+        let item_sizes = Rc::new(vec![size(px(0.0), line_height); self.visible_records.len()]);
+        let is_empty = self.visible_records.is_empty();
+        let entity = cx.entity();
+
+        let level_buttons = LEVELS.iter().map(|&level| {
+            let entity = entity.clone();
+            Button::new(
+                SharedString::from(format!("debug-console-level-{}", level)),
+                level.to_string(),
+            )
+            .variant(if level == self.min_level {
+                ButtonVariant::Secondary
+            } else {
+                ButtonVariant::Ghost
+            })
+            .size(ButtonSize::Sm)
+            .on_click(move |_, _, cx| {
+                entity.update(cx, |console, cx| console.set_level(level, cx));
+            })
+        });
+
+        div()
+            .absolute()
+            .left_0()
+            .right_0()
+            .bottom_0()
+            .h(self.height)
+            .flex()
+            .flex_col()
+            .bg(theme.tokens.background)
+            .border_t_1()
+            .border_color(theme.tokens.border)
+            .shadow(smallvec::smallvec![theme.tokens.shadow_lg.clone()])
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap(px(4.0))
+                    .px(px(8.0))
+                    .py(px(4.0))
+                    .border_b_1()
+                    .border_color(theme.tokens.border)
+                    .child(
+                        Icon::new("terminal")
+                            .size(px(14.0))
+                            .color(theme.tokens.muted_foreground),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_size(px(12.0))
+                            .text_color(theme.tokens.foreground)
+                            .child("Debug Console"),
+                    )
+                    .children(level_buttons)
+                    .child(
+                        Button::new("debug-console-clear", "Clear")
+                            .variant(ButtonVariant::Ghost)
+                            .size(ButtonSize::Sm)
+                            .on_click(|_, _, _| clear_log()),
+                    )
+                    .child(
+                        IconButton::new("x")
+                            .variant(ButtonVariant::Ghost)
+                            .size(px(24.0))
+                            .on_click(|_, _, cx| set_visible(false, cx)),
+                    ),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .overflow_hidden()
+                    .font_family(theme.tokens.font_mono.clone())
+                    .text_size(px(12.0))
+                    .when(is_empty, |this| {
+                        this.child(
+                            div().p(px(12.0)).child(
+                                caption("No log records").color(theme.tokens.muted_foreground),
+                            ),
+                        )
+                    })
+                    .when(!is_empty, |this| {
+                        this.child(
+                            v_virtual_list(
+                                entity.clone(),
+                                "debug-console-lines",
+                                item_sizes,
+                                move |console, range, _window, _cx| {
+                                    range
+                                        .map(|ix| {
+                                            let record = &console.visible_records[ix];
+                                            div()
+                                                .h(line_height)
+                                                .px(px(8.0))
+                                                .whitespace_nowrap()
+                                                .text_color(level_color(record.level))
+                                                .child(SharedString::from(record.display_line()))
+                                        })
+                                        .collect::<Vec<_>>()
+                                },
+                            )
+                            .track_scroll(&self.scroll_handle),
+                        )
+                    }),
+            )
+            .into_any_element()
+    }
+}
@@ -164,6 +164,7 @@ impl Styled for Dialog {
 impl Render for Dialog {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         if self.dismiss_complete {
+            tracing::trace!("dialog dismissed");
             if let Some(handler) = &self.on_close {
                 (handler)(window, cx);
             }
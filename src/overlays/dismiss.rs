@@ -0,0 +1,226 @@
+//! Shared dismissal for overlays.
+//!
+//! Every open popover, select dropdown, context menu or hover card renders a
+//! [`dismiss_layer`] inside its panel. The layers form one stack per app, in the order
+//! the overlays opened, so all of them close the same way:
+//!
+//! - Escape closes the top-most overlay only, even while an input inside it has focus.
+//! - A click outside the top-most overlay follows its [`OutsideClick`] policy. Overlays
+//!   below it ignore the click, so picking from a select inside a popover leaves the
+//!   popover open.
+//! - Scrolling outside an overlay re-anchors it to its trigger, or closes it.
+//! - When an overlay goes away, focus returns to where it was when it opened if
+//!   nothing else has taken it since.
+
+use gpui::*;
+use std::rc::Rc;
+
+/// What a click outside an overlay does.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutsideClick {
+    /// Close the overlay and let the click reach whatever is under it.
+    #[default]
+    Dismiss,
+    /// Close the overlay and swallow the click.
+    DismissAndBlock,
+    /// Stay open; only escape or the overlay itself closes it.
+    Ignore,
+}
+
+type DismissHandler = Rc<dyn Fn(&mut Window, &mut App)>;
+
+struct OverlayEntry {
+    id: EntityId,
+    window: AnyWindowHandle,
+    restore_focus: Option<FocusHandle>,
+    on_dismiss: DismissHandler,
+}
+
+#[derive(Default)]
+struct OverlayStack {
+    entries: Vec<OverlayEntry>,
+    _escape: Option<Subscription>,
+}
+
+impl Global for OverlayStack {}
+
+/// Keyed state behind a layer; dropping it when the overlay stops rendering takes the
+/// overlay off the stack.
+struct OverlayRegistration;
+
+pub fn init(cx: &mut App) {
+    let escape = cx.intercept_keystrokes(|event, window, cx| {
+        let modifiers = &event.keystroke.modifiers;
+        if event.keystroke.key != "escape" || modifiers.modified() {
+            return;
+        }
+        if let Some(on_dismiss) = top_overlay_handler(window, cx) {
+            cx.stop_propagation();
+            on_dismiss(window, cx);
+        }
+    });
+    cx.default_global::<OverlayStack>()._escape = Some(escape);
+}
+
+fn top_overlay_handler(window: &Window, cx: &App) -> Option<DismissHandler> {
+    let stack = cx.try_global::<OverlayStack>()?;
+    let handle = window.window_handle();
+    stack
+        .entries
+        .iter()
+        .rev()
+        .find(|entry| entry.window == handle)
+        .map(|entry| entry.on_dismiss.clone())
+}
+
+fn is_top_overlay(id: EntityId, window: &Window, cx: &App) -> bool {
+    let handle = window.window_handle();
+    cx.try_global::<OverlayStack>().is_some_and(|stack| {
+        stack
+            .entries
+            .iter()
+            .rev()
+            .find(|entry| entry.window == handle)
+            .is_some_and(|entry| entry.id == id)
+    })
+}
+
+/// Whether any overlay is open in `window`, e.g. to hold off global shortcuts.
+pub fn has_open_overlay(window: &Window, cx: &App) -> bool {
+    let handle = window.window_handle();
+    cx.try_global::<OverlayStack>()
+        .is_some_and(|stack| stack.entries.iter().any(|entry| entry.window == handle))
+}
+
+fn register(id: EntityId, window: &Window, cx: &mut App) {
+    let entry = OverlayEntry {
+        id,
+        window: window.window_handle(),
+        restore_focus: window.focused(cx),
+        on_dismiss: Rc::new(|_, _| {}),
+    };
+    cx.default_global::<OverlayStack>().entries.push(entry);
+}
+
+fn unregister(id: EntityId, cx: &mut App) {
+    let stack = cx.default_global::<OverlayStack>();
+    let Some(ix) = stack.entries.iter().position(|entry| entry.id == id) else {
+        return;
+    };
+    let entry = stack.entries.remove(ix);
+    let Some(focus) = entry.restore_focus else {
+        return;
+    };
+    // Released mid-frame; return focus once the frame is done.
+    cx.defer(move |cx| {
+        let _ = entry.window.update(cx, |_, window, cx| {
+            if window.focused(cx).is_none() {
+                window.focus(&focus);
+            }
+        });
+    });
+}
+
+/// Registers an overlay for shared dismissal while it is rendered. See the module docs.
+pub fn dismiss_layer(
+    id: impl Into<ElementId>,
+    on_dismiss: impl Fn(&mut Window, &mut App) + 'static,
+) -> DismissLayer {
+    DismissLayer {
+        id: id.into(),
+        on_dismiss: Rc::new(on_dismiss),
+        outside_click: OutsideClick::default(),
+        dismiss_on_scroll: false,
+        inside: Vec::new(),
+    }
+}
+
+/// Add as a child of an overlay's panel. It fills the panel, which is what counts as
+/// inside; [`DismissLayer::inside`] adds more, such as the trigger.
+#[derive(IntoElement)]
+pub struct DismissLayer {
+    id: ElementId,
+    on_dismiss: DismissHandler,
+    outside_click: OutsideClick,
+    dismiss_on_scroll: bool,
+    inside: Vec<Bounds<Pixels>>,
+}
+
+impl DismissLayer {
+    pub fn outside_click(mut self, policy: OutsideClick) -> Self {
+        self.outside_click = policy;
+        self
+    }
+
+    /// Close on scrolling outside the overlay instead of following the trigger, for
+    /// overlays placed at a fixed point such as context menus.
+    pub fn dismiss_on_scroll(mut self, dismiss: bool) -> Self {
+        self.dismiss_on_scroll = dismiss;
+        self
+    }
+
+    /// Treat clicks in `bounds` as inside too, so a trigger can toggle the overlay.
+    pub fn inside(mut self, bounds: Bounds<Pixels>) -> Self {
+        self.inside.push(bounds);
+        self
+    }
+}
+
+impl RenderOnce for DismissLayer {
+    fn render(self, window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let registration = window.use_keyed_state(self.id, cx, |window, cx| {
+            let id = cx.entity_id();
+            register(id, window, cx);
+            cx.on_release(move |_: &mut OverlayRegistration, cx| unregister(id, cx))
+                .detach();
+            OverlayRegistration
+        });
+        let id = registration.entity_id();
+        let on_dismiss = self.on_dismiss;
+        let stack = cx.default_global::<OverlayStack>();
+        if let Some(entry) = stack.entries.iter_mut().find(|entry| entry.id == id) {
+            entry.on_dismiss = on_dismiss.clone();
+        }
+        let outside_click = self.outside_click;
+        let dismiss_on_scroll = self.dismiss_on_scroll;
+        let inside = self.inside;
+
+        canvas(
+            |_, _, _| {},
+            move |bounds, _, window, _| {
+                let contains = move |position: Point<Pixels>| {
+                    bounds.contains(&position) || inside.iter().any(|b| b.contains(&position))
+                };
+                let contains_click = contains.clone();
+                let on_click_dismiss = on_dismiss.clone();
+                window.on_mouse_event(move |event: &MouseDownEvent, phase, window, cx| {
+                    if phase != DispatchPhase::Capture
+                        || outside_click == OutsideClick::Ignore
+                        || contains_click(event.position)
+                        || !is_top_overlay(id, window, cx)
+                    {
+                        return;
+                    }
+                    if outside_click == OutsideClick::DismissAndBlock {
+                        cx.stop_propagation();
+                        window.prevent_default();
+                    }
+                    on_click_dismiss(window, cx);
+                });
+                window.on_mouse_event(move |event: &ScrollWheelEvent, phase, window, cx| {
+                    if phase != DispatchPhase::Capture || contains(event.position) {
+                        return;
+                    }
+                    if !dismiss_on_scroll {
+                        // Anchored overlays take their position from the last frame.
+                        window.refresh();
+                    } else if is_top_overlay(id, window, cx) {
+                        on_dismiss(window, cx);
+                    }
+                });
+            },
+        )
+        .absolute()
+        .size_full()
+    }
+}
@@ -1,8 +1,10 @@
 //! Hover card component with popover on hover.
 
 use gpui::{prelude::FluentBuilder as _, *};
+use std::rc::Rc;
 use std::time::Duration;
 
+use crate::overlays::dismiss::dismiss_layer;
 use crate::theme::use_theme;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
@@ -31,6 +33,7 @@ pub struct HoverCard {
     _open_delay: Duration,
     _close_delay: Duration,
     is_open: bool,
+    on_dismiss: Option<Rc<dyn Fn(&mut Window, &mut App)>>,
     _arrow: bool,
     style: StyleRefinement,
 }
@@ -45,6 +48,7 @@ impl HoverCard {
             _open_delay: Duration::from_millis(200),
             _close_delay: Duration::from_millis(300),
             is_open: false,
+            on_dismiss: None,
             _arrow: true,
             style: StyleRefinement::default(),
         }
@@ -89,6 +93,13 @@ impl HoverCard {
         self.is_open = is_open;
         self
     }
+
+    /// Called when escape or a click outside should close the open card; the host
+    /// then renders it with `is_open(false)`.
+    pub fn on_dismiss(mut self, handler: impl Fn(&mut Window, &mut App) + 'static) -> Self {
+        self.on_dismiss = Some(Rc::new(handler));
+        self
+    }
 }
 
 impl Default for HoverCard {
@@ -107,6 +118,7 @@ impl RenderOnce for HoverCard {
     fn render(self, _window: &mut Window, _cx: &mut App) -> impl IntoElement {
         let theme = use_theme();
         let user_style = self.style;
+        let on_dismiss = self.on_dismiss;
 
         div()
             .relative()
@@ -159,6 +171,12 @@ impl RenderOnce for HoverCard {
                                     div.style().refine(&user_style);
                                     div
                                 })
+                                .when_some(on_dismiss, |this, on_dismiss| {
+                                    this.child(dismiss_layer(
+                                        "hover-card-dismiss",
+                                        move |window, cx| on_dismiss(window, cx),
+                                    ))
+                                })
                                 .child(self.content),
                         ),
                 )
@@ -1,16 +1,24 @@
 //! Overlay components module.
 
+pub mod action_menu;
 pub mod alert_dialog;
 pub mod bottom_sheet;
 pub mod command_palette;
 pub mod context_menu;
+pub mod crash_overlay;
+#[cfg(feature = "debug-console")]
+pub mod debug_console;
 pub mod dialog;
+pub mod dismiss;
 pub mod hover_card;
 pub mod popover;
 pub mod popover_menu;
 pub mod sheet;
+#[cfg(feature = "state-inspector")]
+pub mod state_inspector;
 pub mod toast;
 
+pub use action_menu::ActionMenu;
 pub use alert_dialog::{init_alert_dialog, AlertDialog};
 pub use bottom_sheet::{BottomSheet, BottomSheetSize};
 pub use command_palette::{
@@ -18,7 +26,13 @@ pub use command_palette::{
     SelectCommand,
 };
 pub use context_menu::{ContextMenu, ContextMenuItem};
+pub use crash_overlay::{crash_boundary, CrashOverlay};
+#[cfg(feature = "debug-console")]
+pub use debug_console::{DebugConsole, DebugConsoleLayer, ToggleDebugConsole};
 pub use dialog::{init_dialog, Dialog, DialogSize};
+pub use dismiss::{dismiss_layer, has_open_overlay, DismissLayer, OutsideClick};
 pub use hover_card::{HoverCard, HoverCardAlignment, HoverCardPosition};
 pub use popover_menu::{PopoverMenu, PopoverMenuItem};
 pub use sheet::{init_sheet, Sheet, SheetSide, SheetSize};
+#[cfg(feature = "state-inspector")]
+pub use state_inspector::{Inspectable, StateInspector, StateSnapshot, ToggleStateInspector};
@@ -5,6 +5,7 @@ use std::time::Duration;
 use std::{cell::RefCell, rc::Rc};
 
 use crate::animations::easings;
+use crate::overlays::dismiss::{dismiss_layer, OutsideClick};
 use crate::theme::use_theme;
 
 const POPOVER_MARGIN: Pixels = px(8.0);
@@ -33,6 +34,23 @@ impl PopoverContent {
             dismissing: false,
         }
     }
+
+    /// Play the exit animation, then emit [`DismissEvent`].
+    pub fn dismiss(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.dismissing {
+            return;
+        }
+        self.dismissing = true;
+        cx.notify();
+
+        cx.spawn_in(window, async move |entity, cx| {
+            smol::Timer::after(Duration::from_millis(120)).await;
+            let _ = entity.update(cx, |_, cx| {
+                cx.emit(DismissEvent);
+            });
+        })
+        .detach();
+    }
 }
 
 impl EventEmitter<DismissEvent> for PopoverContent {}
@@ -62,19 +80,7 @@ impl Render for PopoverContent {
             .track_focus(&self.focus_handle)
             .key_context(CONTEXT)
             .on_action(cx.listener(|this, _: &ClosePopover, window, cx| {
-                if this.dismissing {
-                    return;
-                }
-                this.dismissing = true;
-                cx.notify();
-
-                cx.spawn_in(window, async move |entity, cx| {
-                    smol::Timer::after(Duration::from_millis(120)).await;
-                    let _ = entity.update(cx, |_, cx| {
-                        cx.emit(DismissEvent);
-                    });
-                })
-                .detach();
+                this.dismiss(window, cx);
             }))
             .child((self.content)(window, cx))
             .with_animation(
@@ -106,6 +112,7 @@ pub struct Popover {
     trigger: Option<Box<dyn FnOnce(bool, &Window, &App) -> AnyElement + 'static>>,
     content: Option<Rc<dyn Fn(&mut Window, &mut App) -> Entity<PopoverContent> + 'static>>,
     mouse_button: MouseButton,
+    outside_click: OutsideClick,
     style: StyleRefinement,
 }
 
@@ -117,6 +124,7 @@ impl Popover {
             trigger: None,
             content: None,
             mouse_button: MouseButton::Left,
+            outside_click: OutsideClick::Dismiss,
             style: StyleRefinement::default(),
         }
     }
@@ -131,6 +139,12 @@ impl Popover {
         self
     }
 
+    /// What clicking outside the open popover does. Defaults to closing it.
+    pub fn outside_click(mut self, policy: OutsideClick) -> Self {
+        self.outside_click = policy;
+        self
+    }
+
     pub fn trigger<T>(mut self, trigger: T) -> Self
     where
         T: IntoElement + 'static,
@@ -260,7 +274,7 @@ impl Element for Popover {
                     }
 
                     let mut element = {
-                        let content_view_mut = element_state.content_view.clone();
+                        let dismiss_view = content_view.clone();
                         let anchor = view.anchor;
 
                         deferred(
@@ -276,10 +290,14 @@ impl Element for Popover {
                                         }
                                     })
                                     .child(content_view.clone())
-                                    .on_mouse_down_out(move |_, window, _| {
-                                        *content_view_mut.borrow_mut() = None;
-                                        window.refresh();
-                                    }),
+                                    .child(
+                                        dismiss_layer("popover-dismiss", move |window, cx| {
+                                            dismiss_view.update(cx, |content, cx| {
+                                                content.dismiss(window, cx)
+                                            });
+                                        })
+                                        .outside_click(view.outside_click),
+                                    ),
                             ),
                         )
                         .with_priority(1)
@@ -357,6 +375,13 @@ impl Element for Popover {
             window,
             cx,
             |this, element_state, window, cx| {
+                // The popover was placed against last frame's trigger bounds; follow the
+                // trigger when scrolling or a resize has moved it.
+                if request_layout.popover_element.is_some()
+                    && element_state.trigger_bounds != prepaint.trigger_bounds
+                {
+                    window.refresh();
+                }
                 element_state.trigger_bounds = prepaint.trigger_bounds;
 
                 if let Some(mut element) = request_layout.trigger_element.take() {
@@ -385,7 +410,9 @@ impl Element for Popover {
                         window.prevent_default();
 
                         let new_content_view = (content_build)(window, cx);
-                        let old_content_view1 = old_content_view.clone();
+                        // The subscription lives as long as the content does, so it
+                        // mustn't hold the slot that holds the content.
+                        let content_slot = Rc::downgrade(&old_content_view);
 
                         let previous_focus_handle = window.focused(cx);
 
@@ -401,7 +428,9 @@ impl Element for Popover {
                                             window.focus(previous_focus_handle);
                                         }
                                     }
-                                    *old_content_view1.borrow_mut() = None;
+                                    if let Some(slot) = content_slot.upgrade() {
+                                        *slot.borrow_mut() = None;
+                                    }
                                     window.refresh();
                                 },
                             )
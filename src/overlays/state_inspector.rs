@@ -0,0 +1,536 @@
+//! Time-travel inspector for entity state.
+//!
+//! Entities opt in by implementing [`Inspectable`] and being [`register`]ed. From then
+//! on, every `cx.notify()` on one records a snapshot of its state when the state has
+//! changed. [`StateInspector`] is a side panel, toggled with `cmd-alt-shift-i` /
+//! `ctrl-alt-shift-i`, listing the registered entities with their current state and
+//! snapshot history; restoring a snapshot puts the entity back in that state.
+//!
+//! ```rust,ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Filters { query: String, tags: Vec<String> }
+//!
+//! impl Inspectable for FilterPanel {
+//!     fn snapshot(&self) -> serde_json::Value {
+//!         serde_json::to_value(&self.filters).unwrap_or_default()
+//!     }
+//!
+//!     fn restore(&mut self, snapshot: &serde_json::Value, _cx: &mut Context<Self>) -> Result<(), String> {
+//!         self.filters = serde_json::from_value(snapshot.clone()).map_err(|e| e.to_string())?;
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let panel = cx.new(FilterPanel::new);
+//! state_inspector::register(&panel, "Filters", cx);
+//!
+//! let inspector = cx.new(StateInspector::new);
+//! div().relative().size_full().child(app).child(inspector.clone())
+//! ```
+
+use crate::components::button::{Button, ButtonSize, ButtonVariant};
+use crate::components::icon::Icon;
+use crate::components::icon_button::IconButton;
+use crate::components::text::caption;
+use crate::theme::use_theme;
+use gpui::{prelude::FluentBuilder as _, *};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+actions!(state_inspector, [ToggleStateInspector]);
+
+const HISTORY_CAPACITY: usize = 200;
+
+/// State an entity exposes to the [`StateInspector`].
+pub trait Inspectable: 'static + Sized {
+    /// The entity's state as JSON. Leave out handles, subscriptions and caches; only
+    /// what [`Inspectable::restore`] needs to rebuild it.
+    fn snapshot(&self) -> Value;
+
+    /// Put the entity back in the state of an earlier `snapshot`. Entities that can't
+    /// be restored are still listed and recorded.
+    fn restore(&mut self, _snapshot: &Value, _cx: &mut Context<Self>) -> Result<(), String> {
+        Err("This entity can't be restored".into())
+    }
+}
+
+/// A recorded state of an entity.
+#[derive(Clone, Debug)]
+pub struct StateSnapshot {
+    pub value: Value,
+    pub recorded_at: SystemTime,
+}
+
+type SnapshotFn = Rc<dyn Fn(&App) -> Option<Value>>;
+type RestoreFn = Rc<dyn Fn(&Value, &mut App) -> Result<(), String>>;
+
+struct InspectedEntity {
+    id: EntityId,
+    name: SharedString,
+    history: VecDeque<StateSnapshot>,
+    /// The value just restored, which the next change notification shouldn't record.
+    restored: Option<Value>,
+    snapshot: SnapshotFn,
+    restore: RestoreFn,
+    _subscriptions: [Subscription; 2],
+}
+
+#[derive(Default)]
+struct InspectorRegistry {
+    entities: Vec<InspectedEntity>,
+}
+
+impl Global for InspectorRegistry {}
+
+impl InspectorRegistry {
+    fn record(&mut self, id: EntityId, value: Value) {
+        let Some(entity) = self.entities.iter_mut().find(|entity| entity.id == id) else {
+            return;
+        };
+        if entity.restored.take().as_ref() == Some(&value)
+            || entity.history.back().map(|last| &last.value) == Some(&value)
+        {
+            return;
+        }
+        if entity.history.len() == HISTORY_CAPACITY {
+            entity.history.pop_front();
+        }
+        entity.history.push_back(StateSnapshot {
+            value,
+            recorded_at: SystemTime::now(),
+        });
+    }
+}
+
+/// Start recording `entity` under `name`. It's dropped from the inspector when the
+/// entity is released.
+pub fn register<T: Inspectable>(entity: &Entity<T>, name: impl Into<SharedString>, cx: &mut App) {
+    let id = entity.entity_id();
+    let weak = entity.downgrade();
+    let snapshot: SnapshotFn = Rc::new({
+        let weak = weak.clone();
+        move |cx| weak.upgrade().map(|entity| entity.read(cx).snapshot())
+    });
+    let restore: RestoreFn = Rc::new(move |value, cx| {
+        let entity = weak.upgrade().ok_or("The entity was released")?;
+        entity.update(cx, |entity, cx| {
+            entity.restore(value, cx)?;
+            cx.notify();
+            Ok(())
+        })
+    });
+
+    let on_notify = cx.observe(entity, move |entity, cx| {
+        let value = entity.read(cx).snapshot();
+        if cx.has_global::<InspectorRegistry>() {
+            cx.update_global::<InspectorRegistry, _>(|registry, _| registry.record(id, value));
+        }
+    });
+    let on_release = cx.observe_release(entity, move |_, cx| {
+        if cx.has_global::<InspectorRegistry>() {
+            cx.update_global::<InspectorRegistry, _>(|registry, _| {
+                registry.entities.retain(|entity| entity.id != id);
+            });
+        }
+    });
+
+    let initial = entity.read(cx).snapshot();
+    let inspected = InspectedEntity {
+        id,
+        name: name.into(),
+        history: VecDeque::new(),
+        restored: None,
+        snapshot,
+        restore,
+        _subscriptions: [on_notify, on_release],
+    };
+    cx.default_global::<InspectorRegistry>();
+    cx.update_global::<InspectorRegistry, _>(|registry, _| {
+        registry.entities.retain(|entity| entity.id != id);
+        registry.entities.push(inspected);
+        registry.record(id, initial);
+    });
+}
+
+/// Stop recording `entity` and forget its history.
+pub fn unregister<T: 'static>(entity: &Entity<T>, cx: &mut App) {
+    let id = entity.entity_id();
+    if cx.has_global::<InspectorRegistry>() {
+        cx.update_global::<InspectorRegistry, _>(|registry, _| {
+            registry.entities.retain(|entity| entity.id != id);
+        });
+    }
+}
+
+/// The snapshots recorded for `entity`, oldest first.
+pub fn history<T: 'static>(entity: &Entity<T>, cx: &App) -> Vec<StateSnapshot> {
+    let id = entity.entity_id();
+    cx.try_global::<InspectorRegistry>()
+        .and_then(|registry| registry.entities.iter().find(|entity| entity.id == id))
+        .map(|entity| entity.history.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Put the entity with `id` back in the state of `value`.
+fn restore(id: EntityId, value: &Value, cx: &mut App) -> Result<(), String> {
+    let restore_entity = cx
+        .try_global::<InspectorRegistry>()
+        .and_then(|registry| registry.entities.iter().find(|entity| entity.id == id))
+        .map(|entity| entity.restore.clone())
+        .ok_or("The entity is no longer registered")?;
+    restore_entity(value, cx)?;
+    cx.update_global::<InspectorRegistry, _>(|registry, _| {
+        if let Some(entity) = registry.entities.iter_mut().find(|entity| entity.id == id) {
+            entity.restored = Some(value.clone());
+        }
+    });
+    Ok(())
+}
+
+#[derive(Default)]
+struct StateInspectorVisibility(bool);
+
+impl Global for StateInspectorVisibility {}
+
+/// Show or hide every [`StateInspector`].
+pub fn toggle(cx: &mut App) {
+    let visible = !is_visible(cx);
+    set_visible(visible, cx);
+}
+
+pub fn set_visible(visible: bool, cx: &mut App) {
+    cx.set_global(StateInspectorVisibility(visible));
+}
+
+pub fn is_visible(cx: &App) -> bool {
+    cx.try_global::<StateInspectorVisibility>()
+        .is_some_and(|visibility| visibility.0)
+}
+
+pub fn init(cx: &mut App) {
+    cx.on_action(|_: &ToggleStateInspector, cx| toggle(cx));
+    #[cfg(target_os = "macos")]
+    cx.bind_keys([KeyBinding::new(
+        "cmd-alt-shift-i",
+        ToggleStateInspector,
+        None,
+    )]);
+    #[cfg(not(target_os = "macos"))]
+    cx.bind_keys([KeyBinding::new(
+        "ctrl-alt-shift-i",
+        ToggleStateInspector,
+        None,
+    )]);
+}
+
+fn format_time(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64() % 86_400.0)
+        .unwrap_or_default();
+    format!(
+        "{:02}:{:02}:{:06.3}",
+        (secs / 3600.0) as u32,
+        (secs % 3600.0 / 60.0) as u32,
+        secs % 60.0
+    )
+}
+
+/// Side panel over the app listing the registered entities. Renders nothing while
+/// hidden.
+pub struct StateInspector {
+    selected_entity: Option<EntityId>,
+    /// The snapshot being previewed, as an index into the entity's history.
+    selected_snapshot: Option<usize>,
+    error: Option<SharedString>,
+    width: Pixels,
+}
+
+impl StateInspector {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        cx.observe_global::<StateInspectorVisibility>(|_, cx| cx.notify())
+            .detach();
+        cx.observe_global::<InspectorRegistry>(|_, cx| cx.notify())
+            .detach();
+        Self {
+            selected_entity: None,
+            selected_snapshot: None,
+            error: None,
+            width: px(420.0),
+        }
+    }
+
+    pub fn width(mut self, width: impl Into<Pixels>) -> Self {
+        self.width = width.into();
+        self
+    }
+
+    fn select_entity(&mut self, id: EntityId, cx: &mut Context<Self>) {
+        self.selected_entity = Some(id);
+        self.selected_snapshot = None;
+        self.error = None;
+        cx.notify();
+    }
+
+    fn select_snapshot(&mut self, ix: usize, cx: &mut Context<Self>) {
+        self.selected_snapshot = Some(ix);
+        self.error = None;
+        cx.notify();
+    }
+
+    fn restore_selected(&mut self, cx: &mut Context<Self>) {
+        let (Some(id), Some(ix)) = (self.selected_entity, self.selected_snapshot) else {
+            return;
+        };
+        let value = cx
+            .try_global::<InspectorRegistry>()
+            .and_then(|registry| registry.entities.iter().find(|entity| entity.id == id))
+            .and_then(|entity| entity.history.get(ix))
+            .map(|snapshot| snapshot.value.clone());
+        let Some(value) = value else {
+            return;
+        };
+        self.error = restore(id, &value, cx).err().map(SharedString::from);
+        if self.error.is_none() {
+            self.selected_snapshot = None;
+        }
+        cx.notify();
+    }
+}
+
+impl Render for StateInspector {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if !is_visible(cx) {
+            return div().into_any_element();
+        }
+        let theme = use_theme();
+        let entity = cx.entity();
+
+        let entities: Vec<(EntityId, SharedString)> = cx
+            .try_global::<InspectorRegistry>()
+            .map(|registry| {
+                registry
+                    .entities
+                    .iter()
+                    .map(|entity| (entity.id, entity.name.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        if self
+            .selected_entity
+            .map_or(true, |id| !entities.iter().any(|(entity, _)| *entity == id))
+        {
+            self.selected_entity = entities.first().map(|(id, _)| *id);
+            self.selected_snapshot = None;
+        }
+
+        let selected = self.selected_entity.and_then(|id| {
+            let registry = cx.try_global::<InspectorRegistry>()?;
+            let inspected = registry.entities.iter().find(|entity| entity.id == id)?;
+            Some((inspected.history.clone(), inspected.snapshot.clone()))
+        });
+        let (history, current) = match selected {
+            Some((history, snapshot)) => (history, snapshot(cx)),
+            None => (VecDeque::new(), None),
+        };
+        let shown = match self.selected_snapshot.and_then(|ix| history.get(ix)) {
+            Some(snapshot) => Some(snapshot.value.clone()),
+            None => current,
+        };
+        let shown_text = shown
+            .map(|value| serde_json::to_string_pretty(&value).unwrap_or_default())
+            .unwrap_or_default();
+        let previewing = self.selected_snapshot.is_some();
+
+        let entity_tabs = entities.into_iter().map(|(id, name)| {
+            let entity = entity.clone();
+            Button::new(
+                SharedString::from(format!("state-inspector-entity-{}", id.as_u64())),
+                name,
+            )
+            .variant(if Some(id) == self.selected_entity {
+                ButtonVariant::Secondary
+            } else {
+                ButtonVariant::Ghost
+            })
+            .size(ButtonSize::Sm)
+            .on_click(move |_, _, cx| {
+                entity.update(cx, |inspector, cx| inspector.select_entity(id, cx));
+            })
+        });
+
+        let history_rows = history.iter().enumerate().rev().map(|(ix, snapshot)| {
+            let entity = entity.clone();
+            let selected = self.selected_snapshot == Some(ix);
+            div()
+                .id(("state-inspector-snapshot", ix))
+                .flex()
+                .justify_between()
+                .px(px(8.0))
+                .py(px(2.0))
+                .rounded(theme.tokens.radius_sm)
+                .cursor_pointer()
+                .when(selected, |this| this.bg(theme.tokens.accent))
+                .when(!selected, |this| {
+                    this.hover(|style| style.bg(theme.tokens.muted))
+                })
+                .child(format!("#{}", ix + 1))
+                .child(
+                    div()
+                        .text_color(theme.tokens.muted_foreground)
+                        .child(format_time(snapshot.recorded_at)),
+                )
+                .on_click(move |_, _, cx| {
+                    entity.update(cx, |inspector, cx| inspector.select_snapshot(ix, cx));
+                })
+        });
+
+        let restore_entity = entity.clone();
+        let live_entity = entity.clone();
+
+        div()
+            .absolute()
+            .top_0()
+            .right_0()
+            .bottom_0()
+            .w(self.width)
+            .flex()
+            .flex_col()
+            .bg(theme.tokens.background)
+            .border_l_1()
+            .border_color(theme.tokens.border)
+            .shadow(smallvec::smallvec![theme.tokens.shadow_lg.clone()])
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap(px(4.0))
+                    .px(px(8.0))
+                    .py(px(4.0))
+                    .border_b_1()
+                    .border_color(theme.tokens.border)
+                    .child(
+                        Icon::new("history")
+                            .size(px(14.0))
+                            .color(theme.tokens.muted_foreground),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_size(px(12.0))
+                            .text_color(theme.tokens.foreground)
+                            .child("State Inspector"),
+                    )
+                    .child(
+                        IconButton::new("x")
+                            .variant(ButtonVariant::Ghost)
+                            .size(px(24.0))
+                            .on_click(|_, _, cx| set_visible(false, cx)),
+                    ),
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_wrap()
+                    .gap(px(4.0))
+                    .p(px(8.0))
+                    .border_b_1()
+                    .border_color(theme.tokens.border)
+                    .children(entity_tabs),
+            )
+            .map(|this| {
+                if self.selected_entity.is_none() {
+                    return this.child(div().p(px(12.0)).child(
+                        caption("No entities registered").color(theme.tokens.muted_foreground),
+                    ));
+                }
+                this.child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap(px(4.0))
+                        .px(px(8.0))
+                        .py(px(4.0))
+                        .child(
+                            div()
+                                .flex_1()
+                                .text_size(px(12.0))
+                                .text_color(theme.tokens.muted_foreground)
+                                .child(if previewing {
+                                    "Snapshot"
+                                } else {
+                                    "Current state"
+                                }),
+                        )
+                        .when(previewing, |this| {
+                            this.child(
+                                Button::new("state-inspector-live", "Live")
+                                    .variant(ButtonVariant::Ghost)
+                                    .size(ButtonSize::Sm)
+                                    .on_click(move |_, _, cx| {
+                                        live_entity.update(cx, |inspector, cx| {
+                                            inspector.selected_snapshot = None;
+                                            cx.notify();
+                                        });
+                                    }),
+                            )
+                            .child(
+                                Button::new("state-inspector-restore", "Restore")
+                                    .variant(ButtonVariant::Secondary)
+                                    .size(ButtonSize::Sm)
+                                    .on_click(move |_, _, cx| {
+                                        restore_entity.update(cx, |inspector, cx| {
+                                            inspector.restore_selected(cx)
+                                        });
+                                    }),
+                            )
+                        }),
+                )
+                .when_some(self.error.clone(), |this, error| {
+                    this.child(
+                        div()
+                            .px(px(8.0))
+                            .text_size(px(12.0))
+                            .text_color(theme.tokens.destructive)
+                            .child(error),
+                    )
+                })
+                .child(
+                    div()
+                        .id("state-inspector-value")
+                        .flex_1()
+                        .overflow_y_scroll()
+                        .px(px(8.0))
+                        .font_family(theme.tokens.font_mono.clone())
+                        .text_size(px(12.0))
+                        .text_color(theme.tokens.foreground)
+                        .children(
+                            shown_text
+                                .lines()
+                                .map(|line| {
+                                    div()
+                                        .whitespace_nowrap()
+                                        .child(SharedString::from(line.to_string()))
+                                })
+                                .collect::<Vec<_>>(),
+                        ),
+                )
+                .child(
+                    div()
+                        .id("state-inspector-history")
+                        .h(px(180.0))
+                        .overflow_y_scroll()
+                        .p(px(4.0))
+                        .border_t_1()
+                        .border_color(theme.tokens.border)
+                        .font_family(theme.tokens.font_mono.clone())
+                        .text_size(px(12.0))
+                        .text_color(theme.tokens.foreground)
+                        .children(history_rows),
+                )
+            })
+            .into_any_element()
+    }
+}
@@ -109,6 +109,7 @@ impl ToastManager {
 
         let id = toast.id;
         let duration = toast.duration;
+        tracing::trace!(id, ?duration, "toast shown");
 
         self.toasts.push(toast);
 
@@ -0,0 +1,371 @@
+//! Plugin host for third-party components, actions, keybindings, theme tokens and
+//! command palette entries.
+//!
+//! A plugin implements [`Plugin`] and describes its contributions through a
+//! [`PluginRegistrar`]. Plugins are registered with [`register_plugin`] after
+//! [`crate::init`] and can be toggled at runtime with [`set_plugin_enabled`].
+//!
+//! Every call into plugin code (registration, action handlers, component factories,
+//! palette commands) runs behind `catch_unwind`. A panicking plugin is disabled and
+//! its error recorded instead of taking the app down. This only works with the
+//! default `panic = "unwind"` strategy.
+//!
+//! ```rust,ignore
+//! struct ClockPlugin;
+//!
+//! impl Plugin for ClockPlugin {
+//!     fn id(&self) -> &'static str { "clock" }
+//!
+//!     fn register(&self, registrar: &mut PluginRegistrar) {
+//!         registrar
+//!             .key_binding(KeyBinding::new("cmd-shift-t", ShowTime, None))
+//!             .on_action(|_: &ShowTime, _cx| println!("{:?}", std::time::SystemTime::now()))
+//!             .command(Command::new("clock.show", "Show Current Time"))
+//!             .component("clock", |_window, _cx| div().child("12:00").into_any_element());
+//!     }
+//! }
+//!
+//! adabraka_ui::plugins::register_plugin(ClockPlugin, cx)?;
+//! ```
+
+use crate::overlays::command_palette::Command;
+use crate::theme::{install_theme, use_theme, Theme, ThemeTokens};
+use gpui::*;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::rc::Rc;
+
+/// A third-party extension. `register` is called once, when the plugin is added.
+pub trait Plugin: 'static {
+    /// Unique, stable identifier (e.g. `"acme.git-tools"`).
+    fn id(&self) -> &'static str;
+
+    /// Human-readable name. Defaults to the id.
+    fn name(&self) -> &'static str {
+        self.id()
+    }
+
+    fn register(&self, registrar: &mut PluginRegistrar);
+}
+
+type ComponentFactory = Rc<dyn Fn(&mut Window, &mut App) -> AnyElement>;
+type ThemeOverride = Rc<dyn Fn(&mut ThemeTokens)>;
+type ActionInstaller = Box<dyn FnOnce(SharedString, &mut App)>;
+
+/// Collects a plugin's contributions during [`Plugin::register`].
+#[derive(Default)]
+pub struct PluginRegistrar {
+    key_bindings: Vec<KeyBinding>,
+    actions: Vec<ActionInstaller>,
+    commands: Vec<Command>,
+    components: Vec<(SharedString, ComponentFactory)>,
+    theme_overrides: Vec<ThemeOverride>,
+}
+
+impl PluginRegistrar {
+    pub fn key_binding(&mut self, binding: KeyBinding) -> &mut Self {
+        self.key_bindings.push(binding);
+        self
+    }
+
+    pub fn key_bindings(&mut self, bindings: impl IntoIterator<Item = KeyBinding>) -> &mut Self {
+        self.key_bindings.extend(bindings);
+        self
+    }
+
+    /// Handle an action globally. The handler is skipped while the plugin is disabled.
+    pub fn on_action<A: Action>(&mut self, handler: impl Fn(&A, &mut App) + 'static) -> &mut Self {
+        self.actions.push(Box::new(move |plugin_id, cx| {
+            cx.on_action(move |action: &A, cx| {
+                if !is_plugin_enabled(&plugin_id, cx) {
+                    return;
+                }
+                run_guarded(&plugin_id, cx, |cx| handler(action, cx));
+            });
+        }));
+        self
+    }
+
+    pub fn command(&mut self, command: Command) -> &mut Self {
+        self.commands.push(command);
+        self
+    }
+
+    /// Register a named component that apps can render with [`render_component`].
+    pub fn component(
+        &mut self,
+        name: impl Into<SharedString>,
+        factory: impl Fn(&mut Window, &mut App) -> AnyElement + 'static,
+    ) -> &mut Self {
+        self.components.push((name.into(), Rc::new(factory)));
+        self
+    }
+
+    /// Adjust theme tokens while the plugin is enabled.
+    pub fn theme_tokens(&mut self, apply: impl Fn(&mut ThemeTokens) + 'static) -> &mut Self {
+        self.theme_overrides.push(Rc::new(apply));
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PluginError {
+    AlreadyRegistered(SharedString),
+    NotFound(SharedString),
+    Panicked {
+        id: SharedString,
+        message: SharedString,
+    },
+}
+
+impl std::fmt::Display for PluginError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PluginError::AlreadyRegistered(id) => {
+                write!(f, "plugin `{}` is already registered", id)
+            }
+            PluginError::NotFound(id) => write!(f, "plugin `{}` is not registered", id),
+            PluginError::Panicked { id, message } => {
+                write!(f, "plugin `{}` panicked: {}", id, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PluginError {}
+
+/// Public snapshot of a registered plugin.
+#[derive(Debug, Clone)]
+pub struct PluginInfo {
+    pub id: SharedString,
+    pub name: SharedString,
+    pub enabled: bool,
+    pub error: Option<SharedString>,
+}
+
+struct PluginEntry {
+    id: SharedString,
+    name: SharedString,
+    enabled: bool,
+    error: Option<SharedString>,
+    commands: Vec<Command>,
+    components: Vec<(SharedString, ComponentFactory)>,
+    theme_overrides: Vec<ThemeOverride>,
+}
+
+#[derive(Default)]
+struct PluginHost {
+    plugins: Vec<PluginEntry>,
+    base_theme: Option<Theme>,
+}
+
+impl Global for PluginHost {}
+
+impl PluginHost {
+    fn entry(&self, id: &str) -> Option<&PluginEntry> {
+        self.plugins.iter().find(|p| p.id.as_ref() == id)
+    }
+
+    fn entry_mut(&mut self, id: &str) -> Option<&mut PluginEntry> {
+        self.plugins.iter_mut().find(|p| p.id.as_ref() == id)
+    }
+}
+
+pub(crate) fn init(cx: &mut App) {
+    cx.default_global::<PluginHost>();
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> SharedString {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        SharedString::from(s.to_string())
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        SharedString::from(s.clone())
+    } else {
+        SharedString::from("unknown panic")
+    }
+}
+
+/// Run plugin code, disabling the plugin if it panics.
+fn run_guarded<R>(
+    plugin_id: &SharedString,
+    cx: &mut App,
+    f: impl FnOnce(&mut App) -> R,
+) -> Option<R> {
+    match catch_unwind(AssertUnwindSafe(|| f(cx))) {
+        Ok(result) => Some(result),
+        Err(payload) => {
+            let message = panic_message(payload);
+            tracing::error!(plugin = %plugin_id, %message, "plugin panicked and was disabled");
+            crate::telemetry::track_error("plugins", &message, cx);
+            let had_theme = cx
+                .default_global::<PluginHost>()
+                .entry_mut(plugin_id)
+                .map(|entry| {
+                    entry.enabled = false;
+                    entry.error = Some(message);
+                    !entry.theme_overrides.is_empty()
+                })
+                .unwrap_or(false);
+            if had_theme {
+                apply_theme_overrides(cx);
+            }
+            cx.refresh_windows();
+            None
+        }
+    }
+}
+
+/// Register and enable a plugin.
+pub fn register_plugin(plugin: impl Plugin, cx: &mut App) -> Result<(), PluginError> {
+    let id = SharedString::from(plugin.id());
+    if cx.default_global::<PluginHost>().entry(&id).is_some() {
+        return Err(PluginError::AlreadyRegistered(id));
+    }
+
+    let mut registrar = PluginRegistrar::default();
+    if let Err(payload) = catch_unwind(AssertUnwindSafe(|| plugin.register(&mut registrar))) {
+        let message = panic_message(payload);
+        tracing::error!(plugin = %id, %message, "plugin panicked during registration");
+        crate::telemetry::track_error("plugins", &message, cx);
+        cx.default_global::<PluginHost>().plugins.push(PluginEntry {
+            id: id.clone(),
+            name: plugin.name().into(),
+            enabled: false,
+            error: Some(message.clone()),
+            commands: Vec::new(),
+            components: Vec::new(),
+            theme_overrides: Vec::new(),
+        });
+        return Err(PluginError::Panicked { id, message });
+    }
+
+    let PluginRegistrar {
+        key_bindings,
+        actions,
+        commands,
+        components,
+        theme_overrides,
+    } = registrar;
+    let has_theme = !theme_overrides.is_empty();
+
+    cx.default_global::<PluginHost>().plugins.push(PluginEntry {
+        id: id.clone(),
+        name: plugin.name().into(),
+        enabled: true,
+        error: None,
+        commands,
+        components,
+        theme_overrides,
+    });
+
+    cx.bind_keys(key_bindings);
+    for install in actions {
+        install(id.clone(), cx);
+    }
+    if has_theme {
+        apply_theme_overrides(cx);
+    }
+    Ok(())
+}
+
+pub fn set_plugin_enabled(id: &str, enabled: bool, cx: &mut App) -> Result<(), PluginError> {
+    let host = cx.default_global::<PluginHost>();
+    let entry = host
+        .entry_mut(id)
+        .ok_or_else(|| PluginError::NotFound(SharedString::from(id.to_string())))?;
+    if entry.enabled == enabled {
+        return Ok(());
+    }
+    entry.enabled = enabled;
+    if enabled {
+        entry.error = None;
+    }
+    if !entry.theme_overrides.is_empty() {
+        apply_theme_overrides(cx);
+    }
+    cx.refresh_windows();
+    Ok(())
+}
+
+pub fn is_plugin_enabled(id: &str, cx: &App) -> bool {
+    cx.try_global::<PluginHost>()
+        .and_then(|host| host.entry(id))
+        .is_some_and(|entry| entry.enabled)
+}
+
+pub fn plugins(cx: &App) -> Vec<PluginInfo> {
+    cx.try_global::<PluginHost>()
+        .map(|host| {
+            host.plugins
+                .iter()
+                .map(|p| PluginInfo {
+                    id: p.id.clone(),
+                    name: p.name.clone(),
+                    enabled: p.enabled,
+                    error: p.error.clone(),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Command palette entries contributed by enabled plugins.
+pub fn palette_commands(cx: &App) -> Vec<Command> {
+    let Some(host) = cx.try_global::<PluginHost>() else {
+        return Vec::new();
+    };
+    host.plugins
+        .iter()
+        .filter(|p| p.enabled)
+        .flat_map(|p| {
+            let plugin_id = p.id.clone();
+            p.commands.iter().cloned().map(move |mut command| {
+                if let Some(handler) = command.on_select.take() {
+                    let plugin_id = plugin_id.clone();
+                    command.on_select = Some(Rc::new(move |window: &mut Window, cx: &mut App| {
+                        run_guarded(&plugin_id, cx, |cx| handler(window, cx));
+                    }));
+                }
+                command
+            })
+        })
+        .collect()
+}
+
+/// Render a component registered by an enabled plugin. Returns `None` if no enabled
+/// plugin provides `name`, or if the factory panicked.
+pub fn render_component(name: &str, window: &mut Window, cx: &mut App) -> Option<AnyElement> {
+    let (plugin_id, factory) = cx.try_global::<PluginHost>().and_then(|host| {
+        host.plugins.iter().filter(|p| p.enabled).find_map(|p| {
+            p.components
+                .iter()
+                .find(|(component, _)| component.as_ref() == name)
+                .map(|(_, factory)| (p.id.clone(), factory.clone()))
+        })
+    })?;
+    run_guarded(&plugin_id, cx, |cx| factory(window, cx))
+}
+
+/// Install a theme and re-apply token overrides from enabled plugins on top of it.
+/// Use this instead of [`install_theme`] when plugins contribute theme tokens.
+pub fn install_theme_with_plugins(theme: Theme, cx: &mut App) {
+    cx.default_global::<PluginHost>().base_theme = Some(theme);
+    apply_theme_overrides(cx);
+}
+
+fn apply_theme_overrides(cx: &mut App) {
+    let host = cx.default_global::<PluginHost>();
+    let base = host.base_theme.get_or_insert_with(use_theme).clone();
+    let overrides: Vec<ThemeOverride> = host
+        .plugins
+        .iter()
+        .filter(|p| p.enabled)
+        .flat_map(|p| p.theme_overrides.iter().cloned())
+        .collect();
+
+    let mut theme = base;
+    for apply in overrides {
+        // A panicking override is skipped; the theme keeps the tokens applied so far.
+        let _ = catch_unwind(AssertUnwindSafe(|| apply(&mut theme.tokens)));
+    }
+    install_theme(cx, theme);
+}
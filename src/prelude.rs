@@ -11,7 +11,10 @@ pub use crate::animate::{
     StaggerConfig, Transition,
 };
 pub use crate::animated_state::AnimatedInteraction;
-pub use crate::animations::{lerp_color, lerp_f32, lerp_pixels, lerp_shadow, lerp_shadows};
+pub use crate::animations::{
+    lerp_color, lerp_f32, lerp_pixels, lerp_shadow, lerp_shadows, reduced_motion,
+    set_reduced_motion,
+};
 pub use crate::charts::bar_chart::{
     BarChart, BarChartData, BarChartMode, BarChartOrientation, BarChartSeries,
 };
@@ -19,6 +22,7 @@ pub use crate::charts::chart::{
     Axis, AxisPosition, Chart, ChartArea, ChartPadding, DataPoint, DataRange, Legend,
     LegendPosition, Series, SeriesType, TooltipConfig,
 };
+pub use crate::charts::description::{ChartDataTable, ChartDescription, Describe, DescribedChart};
 pub use crate::charts::line_chart::{LineChart, LineChartPoint, LineChartSeries};
 pub use crate::charts::pie_chart::{
     PieChart, PieChartLabelPosition, PieChartSegment, PieChartSize, PieChartVariant,
@@ -26,6 +30,7 @@ pub use crate::charts::pie_chart::{
 pub use crate::components::alert::{alert, Alert, AlertVariant};
 pub use crate::components::animated_collapsible::AnimatedCollapsible;
 pub use crate::components::animated_switch::{AnimatedSwitch, AnimatedSwitchTransition};
+pub use crate::components::async_view::AsyncView;
 pub use crate::components::audio_player::{
     AudioPlayer, AudioPlayerSize, AudioPlayerState, PlaybackSpeed,
 };
@@ -40,15 +45,29 @@ pub use crate::components::carousel::{
 pub use crate::components::checkbox::{Checkbox, CheckboxSize};
 pub use crate::components::code_block::CodeBlock;
 pub use crate::components::collapsible::Collapsible;
-pub use crate::components::color_picker::{ColorMode, ColorPicker, ColorPickerState};
+pub use crate::components::color_picker::{
+    ColorMode, ColorPicker, ColorPickerPanel, ColorPickerState,
+};
 pub use crate::components::combobox::{Combobox, ComboboxEvent, ComboboxState};
 pub use crate::components::countdown::{
     Countdown, CountdownFormat, CountdownSeparator, CountdownSize, CountdownState, TimeUnits,
 };
 pub use crate::components::date_picker::{DateFormat, DatePicker, DatePickerState};
+pub use crate::components::date_range_picker::{
+    DateRangePicker, DateRangePickerState, DateRangePreset,
+};
+pub use crate::components::diff_editor::DiffEditor;
 pub use crate::components::drag_drop::{DragData, Draggable, DropZone, DropZoneStyle};
 pub use crate::components::dropdown::{Dropdown, DropdownAlign, DropdownItem, DropdownState};
-pub use crate::components::editor::{Editor, EditorState, Language as EditorLanguage};
+pub use crate::components::editor::{
+    BufferEvent, CursorShape, Editor, EditorSession, EditorState, GutterCell, GutterClick,
+    GutterDecoration, GutterLane, Language as EditorLanguage, LineHighlight, OverlayId,
+    OverlayPlacement, ScrollSyncMode, SemanticToken,
+};
+pub use crate::components::editor_keymap::{EditorKeyBinding, EditorKeymap, EditorKeymapError};
+pub use crate::components::editor_settings::{
+    EditorSettings, EditorSettingsOverride, LanguageSettings,
+};
 pub use crate::components::empty_state::{EmptyState, EmptyStateSize};
 pub use crate::components::file_upload::{
     FileTypeFilter, FileUpload, FileUploadError, FileUploadSize, FileUploadState, SelectedFile,
@@ -67,19 +86,28 @@ pub use crate::components::inline_edit::{InlineEdit, InlineEditState, InlineEdit
 pub use crate::components::keyboard_shortcuts::{
     KeyboardShortcuts, ShortcutCategory, ShortcutItem,
 };
+pub use crate::components::knob::{Knob, KnobEvent, KnobState};
 pub use crate::components::label::Label;
+pub use crate::components::layout_presets::{
+    LayoutPreset, NextLayoutPreset, PanelLayout, PresetPanel, PreviousLayoutPreset,
+};
 pub use crate::components::mention_input::{
     init_mention_input, Mention, MentionInput, MentionInputEvent, MentionInputState, MentionItem,
 };
+pub use crate::components::multi_buffer::{ExcerptId, MultiBuffer};
+pub use crate::components::multi_select::{MultiSelect, MultiSelectEvent};
 pub use crate::components::navigation_menu::{NavigationMenu, NavigationMenuItem};
 pub use crate::components::notification_center::{
     NotificationBell, NotificationCenter, NotificationCenterState, NotificationItem,
     NotificationVariant,
 };
-pub use crate::components::number_input::{NumberInput, NumberInputSize, NumberInputState};
+pub use crate::components::number_input::{
+    NumberInput, NumberInputMode, NumberInputSize, NumberInputState,
+};
 pub use crate::components::otp_input::{
     OTPInput, OTPInputEvent, OTPInputSize, OTPInputState, OTPState,
 };
+pub use crate::components::output_panel::{OutputChannel, OutputChannels, OutputPanel};
 pub use crate::components::pagination::Pagination;
 pub use crate::components::progress::{
     CircularProgress, ProgressBar, ProgressSize, ProgressVariant,
@@ -90,8 +118,9 @@ pub use crate::components::rating::{Rating, RatingSize, RatingState};
 pub use crate::components::resizable::{ResizablePanel, ResizablePanelGroup, ResizableState};
 pub use crate::components::ripple::Ripple;
 pub use crate::components::scrollable::{
-    scrollable_both, scrollable_horizontal, scrollable_vertical,
+    scrollable_both, scrollable_horizontal, scrollable_vertical, SmoothScroll,
 };
+pub use crate::components::scrubber::Scrubber;
 pub use crate::components::search_input::{SearchFilter, SearchInput, SearchInputState};
 pub use crate::components::select::{Select, SelectOption};
 pub use crate::components::separator::{Separator, SeparatorOrientation};
@@ -101,6 +130,7 @@ pub use crate::components::sortable_list::{SortableList, SortableListState};
 pub use crate::components::sparkline::{
     Sparkline, SparklineSize, SparklineTrend, SparklineVariant,
 };
+pub use crate::components::spell_check::{Misspelling, SpellChecker};
 pub use crate::components::spinner::{Spinner, SpinnerSize, SpinnerVariant};
 pub use crate::components::split_pane::{
     CollapsiblePane, SplitDirection, SplitPane, SplitPaneEvent, SplitPaneState,
@@ -113,11 +143,17 @@ pub use crate::components::text::{
     body, body_large, body_small, caption, code, code_small, h1, h2, h3, h4, h5, h6, label,
     label_small, muted, muted_small, Text, TextVariant,
 };
+pub use crate::components::text_diff::{
+    diff_texts, parse_unified_diff, DiffRow, DiffRowKind, DiffSide,
+};
 pub use crate::components::text_field::{TextField, TextFieldSize};
-pub use crate::components::textarea::Textarea;
+pub use crate::components::textarea::{Textarea, TextareaEvent, TextareaState};
 pub use crate::components::time_picker::{
     TimeFormat, TimePeriod, TimePicker, TimePickerState, TimeValue,
 };
+pub use crate::components::time_range_slider::{
+    TimeRangeEvent, TimeRangeSlider, TimeRangeSliderState,
+};
 pub use crate::components::timeline::{
     timeline, Timeline, TimelineConnectorStyle, TimelineIndicatorStyle, TimelineItem,
     TimelineItemPosition, TimelineItemVariant, TimelineLayout, TimelineOrientation, TimelineSize,
@@ -127,11 +163,14 @@ pub use crate::components::toggle_group::{
     ToggleGroup, ToggleGroupItem, ToggleGroupSize, ToggleGroupVariant,
 };
 pub use crate::components::tooltip::tooltip;
+pub use crate::components::type_ahead::{TypeAhead, TypeAheadAction};
 pub use crate::components::video_player::{
     init_video_player, VideoPlaybackSpeed, VideoPlaybackState, VideoPlayer, VideoPlayerSize,
     VideoPlayerState,
 };
 pub use crate::components::view_router::{PageTransition, ViewRouter, ViewRouterState};
+pub use crate::components::xy_pad::{XYPad, XYPadEvent, XYPadState};
+pub use crate::components::zen_mode::{ExitZenMode, ToggleZenMode, ZenMode};
 pub use crate::display::accordion::{Accordion, AccordionItem};
 pub use crate::display::badge::{Badge, BadgeVariant};
 pub use crate::display::card::Card;
@@ -139,8 +178,11 @@ pub use crate::display::data_grid::{
     CellEditor, CellPosition, DataGrid, DataGridState, GridColumnDef, GridSortDirection,
 };
 pub use crate::display::data_table::{ColumnDef, DataTable, SortDirection};
+pub use crate::display::docs_viewer::{DocFormat, DocPage, DocsLink, DocsViewer};
 pub use crate::display::html::Html;
 pub use crate::display::markdown::Markdown;
+#[cfg(feature = "markdown")]
+pub use crate::display::markdown_view::MarkdownView;
 pub use crate::display::rich_text::{RichBlock, RichInline, TableAlignment as RichTableAlignment};
 pub use crate::display::table::{Table, TableColumn, TableRow};
 pub use crate::layout::{
@@ -153,25 +195,30 @@ pub use crate::navigation::app_menu::{
     StandardMacMenuBar,
 };
 pub use crate::navigation::breadcrumbs::{BreadcrumbItem, Breadcrumbs};
+pub use crate::navigation::closed_tabs::{ClosedTab, ClosedTabs};
 pub use crate::navigation::file_tree::{FileNode, FileNodeKind, FileTree};
 pub use crate::navigation::menu::{
     ContextMenu, Menu, MenuBar, MenuBarItem, MenuItem, MenuItemKind,
 };
 pub use crate::navigation::status_bar::{StatusBar, StatusItem};
-pub use crate::navigation::tabs::{TabItem, Tabs};
+pub use crate::navigation::tabs::{ReopenClosedTab, TabItem, Tabs};
 pub use crate::navigation::toolbar::{
     Toolbar, ToolbarButton, ToolbarButtonVariant, ToolbarGroup, ToolbarItem, ToolbarSize,
 };
-pub use crate::navigation::tree::{TreeList, TreeNode};
+pub use crate::navigation::tree::{TreeList, TreeListEvent, TreeListState, TreeNode};
+pub use crate::overlays::action_menu::ActionMenu;
 pub use crate::overlays::alert_dialog::AlertDialog;
 pub use crate::overlays::bottom_sheet::{BottomSheet, BottomSheetSize};
 pub use crate::overlays::command_palette::{Command, CommandPalette, CommandPaletteState};
+pub use crate::overlays::crash_overlay::CrashOverlay;
 pub use crate::overlays::dialog::{Dialog, DialogSize};
+pub use crate::overlays::dismiss::{dismiss_layer, DismissLayer, OutsideClick};
 pub use crate::overlays::hover_card::{HoverCard, HoverCardAlignment, HoverCardPosition};
 pub use crate::overlays::popover::Popover;
 pub use crate::overlays::popover_menu::{PopoverMenu, PopoverMenuItem};
 pub use crate::overlays::sheet::{Sheet, SheetSide, SheetSize};
 pub use crate::overlays::toast::{ToastItem, ToastManager, ToastPosition, ToastVariant};
+pub use crate::services::{use_service, ServiceExt, ServiceScope};
 pub use crate::theme::{install_theme, use_theme, Theme, ThemeTokens, ThemeVariant};
 
 pub use crate::animation_coordinator::AnimationCoordinator;
@@ -0,0 +1,467 @@
+//! Embedded [Rhai](https://rhai.rs) scripting for user automation (feature `scripting`).
+//!
+//! A [`ScriptHost`] compiles named scripts and runs them from key bindings, palette
+//! commands or app code. Scripts see a small API:
+//!
+//! | Function | Effect |
+//! | --- | --- |
+//! | `dispatch(name)` | dispatch a registered action, e.g. `"editor::Undo"` |
+//! | `actions()` | names of all registered actions |
+//! | `get_setting(key)` / `set_setting(key, value)` | read and write app settings |
+//! | `toast(title)` / `toast(title, kind)` | show a toast; kind is `"success"`, `"warning"` or `"error"` |
+//! | `prompt(title, callback)` | ask for text, then call `callback(answer)` |
+//! | `pick(title, items, callback)` | ask to choose an item, then call `callback(item)` |
+//!
+//! Effects are applied after the script returns. Prompts and pickers are surfaced as
+//! [`ScriptEvent`]s for the app to present; answer them with [`ScriptHost::respond`].
+//!
+//! ```rust,ignore
+//! let scripts = cx.new(|cx| ScriptHost::new(cx).toast_manager(toasts.clone()));
+//! scripts.update(cx, |host, _| {
+//!     host.load("greet", r#"prompt("Your name?", |name| toast("Hello " + name))"#)?;
+//!     host.bind_key("cmd-alt-g", "greet")
+//! })?;
+//! ```
+
+use crate::callbacks;
+use crate::overlays::command_palette::Command;
+use crate::overlays::toast::{ToastItem, ToastManager, ToastVariant};
+use crate::workspace_trust;
+use gpui::*;
+use rhai::{Array, Engine, FnPtr, AST};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+pub use rhai::Dynamic;
+
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+#[derive(Debug, Clone)]
+pub enum ScriptError {
+    NotFound(SharedString),
+    Compile {
+        script: SharedString,
+        message: SharedString,
+    },
+    Runtime {
+        script: SharedString,
+        message: SharedString,
+    },
+    InvalidKeystroke(SharedString),
+    /// The workspace is in restricted mode; see [`crate::workspace_trust`].
+    Restricted(SharedString),
+}
+
+impl std::fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScriptError::NotFound(name) => write!(f, "script `{}` is not loaded", name),
+            ScriptError::Compile { script, message } => {
+                write!(f, "script `{}` failed to compile: {}", script, message)
+            }
+            ScriptError::Runtime { script, message } => {
+                write!(f, "script `{}` failed: {}", script, message)
+            }
+            ScriptError::InvalidKeystroke(keystroke) => {
+                write!(f, "invalid keystroke `{}`", keystroke)
+            }
+            ScriptError::Restricted(script) => {
+                write!(f, "script `{}` can't run in restricted mode", script)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+#[derive(Clone)]
+pub enum ScriptEvent {
+    /// A script asked for free-form text.
+    Prompt {
+        request: usize,
+        title: SharedString,
+    },
+    /// A script asked the user to choose one of `items`.
+    Pick {
+        request: usize,
+        title: SharedString,
+        items: Vec<SharedString>,
+    },
+    /// A toast requested while no [`ToastManager`] is attached.
+    Toast {
+        title: SharedString,
+        variant: ToastVariant,
+    },
+    SettingChanged {
+        key: SharedString,
+        value: Dynamic,
+    },
+    Failed {
+        script: SharedString,
+        error: SharedString,
+    },
+}
+
+enum ScriptEffect {
+    Dispatch(String),
+    SetSetting(String, Dynamic),
+    Toast(String, ToastVariant),
+    Prompt(String, FnPtr),
+    Pick(String, Vec<String>, FnPtr),
+}
+
+/// State shared between the host and the functions registered on the engine.
+#[derive(Default)]
+struct ScriptBridge {
+    effects: Vec<ScriptEffect>,
+    settings: HashMap<String, Dynamic>,
+    action_names: Vec<String>,
+}
+
+fn register_api(engine: &mut Engine, bridge: &Rc<RefCell<ScriptBridge>>) {
+    let b = bridge.clone();
+    engine.register_fn("dispatch", move |name: &str| {
+        b.borrow_mut()
+            .effects
+            .push(ScriptEffect::Dispatch(name.to_string()));
+    });
+
+    let b = bridge.clone();
+    engine.register_fn("actions", move || -> Array {
+        b.borrow()
+            .action_names
+            .iter()
+            .map(|name| Dynamic::from(name.clone()))
+            .collect()
+    });
+
+    let b = bridge.clone();
+    engine.register_fn("get_setting", move |key: &str| -> Dynamic {
+        b.borrow()
+            .settings
+            .get(key)
+            .cloned()
+            .unwrap_or(Dynamic::UNIT)
+    });
+
+    let b = bridge.clone();
+    engine.register_fn("set_setting", move |key: &str, value: Dynamic| {
+        let mut bridge = b.borrow_mut();
+        bridge.settings.insert(key.to_string(), value.clone());
+        bridge
+            .effects
+            .push(ScriptEffect::SetSetting(key.to_string(), value));
+    });
+
+    let b = bridge.clone();
+    engine.register_fn("toast", move |title: &str| {
+        b.borrow_mut().effects.push(ScriptEffect::Toast(
+            title.to_string(),
+            ToastVariant::Default,
+        ));
+    });
+
+    let b = bridge.clone();
+    engine.register_fn("toast", move |title: &str, kind: &str| {
+        let variant = match kind {
+            "success" => ToastVariant::Success,
+            "warning" => ToastVariant::Warning,
+            "error" => ToastVariant::Error,
+            _ => ToastVariant::Default,
+        };
+        b.borrow_mut()
+            .effects
+            .push(ScriptEffect::Toast(title.to_string(), variant));
+    });
+
+    let b = bridge.clone();
+    engine.register_fn("prompt", move |title: &str, callback: FnPtr| {
+        b.borrow_mut()
+            .effects
+            .push(ScriptEffect::Prompt(title.to_string(), callback));
+    });
+
+    let b = bridge.clone();
+    engine.register_fn("pick", move |title: &str, items: Array, callback: FnPtr| {
+        let items = items.iter().map(|item| item.to_string()).collect();
+        b.borrow_mut()
+            .effects
+            .push(ScriptEffect::Pick(title.to_string(), items, callback));
+    });
+}
+
+/// Loads user scripts and runs them against the app.
+pub struct ScriptHost {
+    engine: Engine,
+    bridge: Rc<RefCell<ScriptBridge>>,
+    scripts: Vec<(SharedString, AST)>,
+    key_bindings: Vec<(Keystroke, SharedString)>,
+    pending: HashMap<usize, (SharedString, FnPtr)>,
+    next_request: usize,
+    toasts: Option<Entity<ToastManager>>,
+    next_toast_id: u64,
+    _keystrokes: Subscription,
+}
+
+impl EventEmitter<ScriptEvent> for ScriptHost {}
+
+impl ScriptHost {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        let bridge = Rc::new(RefCell::new(ScriptBridge::default()));
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        register_api(&mut engine, &bridge);
+
+        let host = cx.entity().downgrade();
+        let keystrokes = cx.observe_keystrokes(move |event, window, cx| {
+            let _ = host.update(cx, |host, cx| host.handle_keystroke(event, window, cx));
+        });
+
+        Self {
+            engine,
+            bridge,
+            scripts: Vec::new(),
+            key_bindings: Vec::new(),
+            pending: HashMap::new(),
+            next_request: 0,
+            toasts: None,
+            next_toast_id: 0,
+            _keystrokes: keystrokes,
+        }
+    }
+
+    /// Show script toasts in this manager instead of emitting [`ScriptEvent::Toast`].
+    pub fn toast_manager(mut self, toasts: Entity<ToastManager>) -> Self {
+        self.toasts = Some(toasts);
+        self
+    }
+
+    /// Compile a script, replacing any script with the same name.
+    pub fn load(&mut self, name: impl Into<SharedString>, source: &str) -> Result<(), ScriptError> {
+        let name = name.into();
+        let ast = self
+            .engine
+            .compile(source)
+            .map_err(|e| ScriptError::Compile {
+                script: name.clone(),
+                message: e.to_string().into(),
+            })?;
+        self.scripts.retain(|(existing, _)| *existing != name);
+        self.scripts.push((name, ast));
+        Ok(())
+    }
+
+    pub fn unload(&mut self, name: &str) {
+        self.scripts
+            .retain(|(existing, _)| existing.as_ref() != name);
+        self.key_bindings
+            .retain(|(_, script)| script.as_ref() != name);
+        self.pending
+            .retain(|_, (script, _)| script.as_ref() != name);
+    }
+
+    pub fn script_names(&self) -> Vec<SharedString> {
+        self.scripts.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    /// Run `script` whenever `keystroke` (e.g. `"cmd-alt-g"`) is pressed in any window.
+    pub fn bind_key(
+        &mut self,
+        keystroke: &str,
+        script: impl Into<SharedString>,
+    ) -> Result<(), ScriptError> {
+        let parsed = Keystroke::parse(keystroke)
+            .map_err(|_| ScriptError::InvalidKeystroke(keystroke.to_string().into()))?;
+        self.key_bindings.push((parsed, script.into()));
+        Ok(())
+    }
+
+    pub fn set_setting(&mut self, key: impl Into<String>, value: impl Into<Dynamic>) {
+        self.bridge
+            .borrow_mut()
+            .settings
+            .insert(key.into(), value.into());
+    }
+
+    pub fn setting(&self, key: &str) -> Option<Dynamic> {
+        self.bridge.borrow().settings.get(key).cloned()
+    }
+
+    /// A "Run Script" palette entry per loaded script.
+    pub fn palette_commands(host: &Entity<Self>, cx: &App) -> Vec<Command> {
+        host.read(cx)
+            .scripts
+            .iter()
+            .map(|(name, _)| {
+                let script = name.clone();
+                Command::new(format!("script.{}", name), format!("Run Script: {}", name))
+                    .category("Scripts")
+                    .on_select(callbacks::weak_handler(host, move |host, window, cx| {
+                        // Failures are reported through `ScriptEvent::Failed`.
+                        let _ = host.run(&script, window, cx);
+                    }))
+            })
+            .collect()
+    }
+
+    pub fn run(
+        &mut self,
+        name: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Result<(), ScriptError> {
+        let (script, ast) = self
+            .scripts
+            .iter()
+            .find(|(existing, _)| existing.as_ref() == name)
+            .cloned()
+            .ok_or_else(|| ScriptError::NotFound(name.to_string().into()))?;
+        if !workspace_trust::is_allowed(workspace_trust::SCRIPTING, cx) {
+            return Err(ScriptError::Restricted(script));
+        }
+        self.prepare(cx);
+        let result = self.engine.run_ast(&ast);
+        self.finish(&script, result.map(|_| ()), window, cx)
+    }
+
+    /// Answer a [`ScriptEvent::Prompt`] or [`ScriptEvent::Pick`]. `None` cancels the
+    /// request without calling the script back.
+    pub fn respond(
+        &mut self,
+        request: usize,
+        answer: Option<String>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Result<(), ScriptError> {
+        let Some((script, callback)) = self.pending.remove(&request) else {
+            return Ok(());
+        };
+        let Some(answer) = answer else {
+            return Ok(());
+        };
+        let ast = self
+            .scripts
+            .iter()
+            .find(|(existing, _)| *existing == script)
+            .map(|(_, ast)| ast.clone())
+            .ok_or_else(|| ScriptError::NotFound(script.clone()))?;
+        if !workspace_trust::is_allowed(workspace_trust::SCRIPTING, cx) {
+            return Err(ScriptError::Restricted(script));
+        }
+        self.prepare(cx);
+        let result = callback.call::<Dynamic>(&self.engine, &ast, (answer,));
+        self.finish(&script, result.map(|_| ()), window, cx)
+    }
+
+    fn handle_keystroke(
+        &mut self,
+        event: &KeystrokeEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let scripts: Vec<SharedString> = self
+            .key_bindings
+            .iter()
+            .filter(|(keystroke, _)| {
+                keystroke.modifiers == event.keystroke.modifiers
+                    && keystroke.key == event.keystroke.key
+            })
+            .map(|(_, script)| script.clone())
+            .collect();
+        for script in scripts {
+            let _ = self.run(&script, window, cx);
+        }
+    }
+
+    fn prepare(&mut self, cx: &App) {
+        let mut bridge = self.bridge.borrow_mut();
+        bridge.effects.clear();
+        bridge.action_names = cx
+            .all_action_names()
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+    }
+
+    fn finish(
+        &mut self,
+        script: &SharedString,
+        result: Result<(), Box<rhai::EvalAltResult>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Result<(), ScriptError> {
+        let effects = std::mem::take(&mut self.bridge.borrow_mut().effects);
+        for effect in effects {
+            self.apply_effect(script, effect, window, cx);
+        }
+        result.map_err(|e| {
+            let message: SharedString = e.to_string().into();
+            cx.emit(ScriptEvent::Failed {
+                script: script.clone(),
+                error: message.clone(),
+            });
+            ScriptError::Runtime {
+                script: script.clone(),
+                message,
+            }
+        })
+    }
+
+    fn apply_effect(
+        &mut self,
+        script: &SharedString,
+        effect: ScriptEffect,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match effect {
+            ScriptEffect::Dispatch(name) => match cx.build_action(&name, None) {
+                Ok(action) => window.dispatch_action(action, cx),
+                Err(_) => cx.emit(ScriptEvent::Failed {
+                    script: script.clone(),
+                    error: format!("unknown action `{}`", name).into(),
+                }),
+            },
+            ScriptEffect::SetSetting(key, value) => cx.emit(ScriptEvent::SettingChanged {
+                key: key.into(),
+                value,
+            }),
+            ScriptEffect::Toast(title, variant) => match self.toasts.clone() {
+                Some(toasts) => {
+                    let id = self.next_toast_id;
+                    self.next_toast_id += 1;
+                    toasts.update(cx, |toasts, cx| {
+                        toasts.add_toast(ToastItem::new(id, title).variant(variant), window, cx)
+                    });
+                }
+                None => cx.emit(ScriptEvent::Toast {
+                    title: title.into(),
+                    variant,
+                }),
+            },
+            ScriptEffect::Prompt(title, callback) => {
+                let request = self.track_request(script, callback);
+                cx.emit(ScriptEvent::Prompt {
+                    request,
+                    title: title.into(),
+                });
+            }
+            ScriptEffect::Pick(title, items, callback) => {
+                let request = self.track_request(script, callback);
+                cx.emit(ScriptEvent::Pick {
+                    request,
+                    title: title.into(),
+                    items: items.into_iter().map(SharedString::from).collect(),
+                });
+            }
+        }
+    }
+
+    fn track_request(&mut self, script: &SharedString, callback: FnPtr) -> usize {
+        let request = self.next_request;
+        self.next_request += 1;
+        self.pending.insert(request, (script.clone(), callback));
+        request
+    }
+}
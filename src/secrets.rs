@@ -0,0 +1,110 @@
+//! Credential storage in the OS keychain: the macOS Keychain, Windows Credential
+//! Manager, or the Secret Service (libsecret / KWallet) on Linux.
+//!
+//! Secrets are addressed by a service name, usually the app's identifier, and an
+//! account, such as `"openai"` or `"github-token"`. Keychain calls can block on a
+//! user prompt, so every function runs on the background executor and returns a task.
+//!
+//! ```rust,ignore
+//! secrets::store("com.example.notes", "openai", key, cx).detach();
+//!
+//! let key = secrets::get("com.example.notes", "openai", cx);
+//! cx.spawn(async move |cx| {
+//!     if let Ok(Some(key)) = key.await {
+//!         // ...
+//!     }
+//! })
+//! .detach();
+//! ```
+
+use gpui::*;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SecretError {
+    /// No keychain is available, e.g. no Secret Service is running, or it is locked
+    /// and the user declined to unlock it.
+    Unavailable(SharedString),
+    /// The secret, service or account is empty, too long, or otherwise rejected.
+    Invalid(SharedString),
+    Platform(SharedString),
+}
+
+impl fmt::Display for SecretError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecretError::Unavailable(message) => write!(f, "keychain unavailable: {}", message),
+            SecretError::Invalid(message) => write!(f, "invalid credential: {}", message),
+            SecretError::Platform(message) => write!(f, "keychain error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SecretError {}
+
+impl From<keyring::Error> for SecretError {
+    fn from(err: keyring::Error) -> Self {
+        let message = SharedString::from(err.to_string());
+        match err {
+            keyring::Error::NoStorageAccess(_) => SecretError::Unavailable(message),
+            keyring::Error::TooLong(..) | keyring::Error::Invalid(..) => {
+                SecretError::Invalid(message)
+            }
+            _ => SecretError::Platform(message),
+        }
+    }
+}
+
+fn entry(service: &str, account: &str) -> Result<keyring::Entry, SecretError> {
+    Ok(keyring::Entry::new(service, account)?)
+}
+
+/// Save `secret`, replacing any value stored for the same service and account.
+pub fn store(
+    service: impl Into<String>,
+    account: impl Into<String>,
+    secret: impl Into<String>,
+    cx: &App,
+) -> Task<Result<(), SecretError>> {
+    let (service, account, secret) = (service.into(), account.into(), secret.into());
+    cx.background_executor().spawn(async move {
+        entry(&service, &account)?.set_password(&secret)?;
+        tracing::debug!(%service, %account, "secret stored");
+        Ok(())
+    })
+}
+
+/// The stored secret, or `None` if there isn't one.
+pub fn get(
+    service: impl Into<String>,
+    account: impl Into<String>,
+    cx: &App,
+) -> Task<Result<Option<String>, SecretError>> {
+    let (service, account) = (service.into(), account.into());
+    cx.background_executor().spawn(async move {
+        match entry(&service, &account)?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    })
+}
+
+/// Remove the stored secret. Resolves to `false` if there was nothing to remove.
+pub fn delete(
+    service: impl Into<String>,
+    account: impl Into<String>,
+    cx: &App,
+) -> Task<Result<bool, SecretError>> {
+    let (service, account) = (service.into(), account.into());
+    cx.background_executor().spawn(async move {
+        match entry(&service, &account)?.delete_credential() {
+            Ok(()) => {
+                tracing::debug!(%service, %account, "secret deleted");
+                Ok(true)
+            }
+            Err(keyring::Error::NoEntry) => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    })
+}
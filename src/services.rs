@@ -0,0 +1,227 @@
+//! Typed services looked up from anywhere in the element tree.
+//!
+//! Apps register long-lived services (a git client, settings, an LSP connection) once
+//! with [`ServiceExt::provide`], and deeply nested components such as status bar items
+//! or palette providers fetch them with [`use_service`] instead of having entities
+//! passed down through every constructor.
+//!
+//! [`ServiceScope`] provides services to the elements it wraps only, shadowing the
+//! app-wide ones. Wrapping a window's root view scopes a service to that window; wrapping
+//! a panel gives that subtree its own instance.
+//!
+//! ```rust,ignore
+//! cx.provide(git.clone()); // Entity<GitRepository>, app-wide
+//!
+//! // in a root view
+//! ServiceScope::new(self.workspace.clone()).provide(self.project_settings.clone())
+//!
+//! // in a status bar item, anywhere below
+//! if let Some(git) = use_service::<Entity<GitRepository>>() {
+//!     let branch = git.read(cx).branch_name();
+//! }
+//! ```
+//!
+//! Scoped services are only visible while elements are being built, so click handlers
+//! and tasks should capture what they need during render rather than look it up later.
+
+use gpui::*;
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+type ServiceMap = HashMap<TypeId, Rc<dyn Any>>;
+
+thread_local! {
+    static APP_SERVICES: RefCell<ServiceMap> = RefCell::new(HashMap::new());
+    static SCOPES: RefCell<Vec<Rc<ServiceMap>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The service of type `T` from the innermost [`ServiceScope`] being built, or the
+/// app-wide one.
+pub fn use_service<T: 'static>() -> Option<Rc<T>> {
+    SCOPES
+        .with(|scopes| {
+            scopes
+                .borrow()
+                .iter()
+                .rev()
+                .find_map(|scope| lookup::<T>(scope))
+        })
+        .or_else(|| APP_SERVICES.with(|services| lookup::<T>(&services.borrow())))
+}
+
+fn lookup<T: 'static>(services: &ServiceMap) -> Option<Rc<T>> {
+    let service = services.get(&TypeId::of::<T>())?.clone();
+    service.downcast::<T>().ok()
+}
+
+fn insert_app_service<T: 'static>(service: T) {
+    APP_SERVICES.with(|services| {
+        services
+            .borrow_mut()
+            .insert(TypeId::of::<T>(), Rc::new(service));
+    });
+}
+
+fn remove_app_service<T: 'static>() -> Option<Rc<T>> {
+    let service = APP_SERVICES.with(|services| services.borrow_mut().remove(&TypeId::of::<T>()))?;
+    service.downcast::<T>().ok()
+}
+
+/// Run `f` with `services` as the innermost scope.
+fn with_scope<R>(services: &Rc<ServiceMap>, f: impl FnOnce() -> R) -> R {
+    struct PopScope;
+
+    impl Drop for PopScope {
+        fn drop(&mut self) {
+            SCOPES.with(|scopes| scopes.borrow_mut().pop());
+        }
+    }
+
+    SCOPES.with(|scopes| scopes.borrow_mut().push(services.clone()));
+    let _pop = PopScope;
+    f()
+}
+
+/// App-wide service registration.
+pub trait ServiceExt {
+    /// Register `service` for the whole app, replacing any earlier service of the same
+    /// type, and re-render windows so components pick it up.
+    fn provide<T: 'static>(&mut self, service: T);
+
+    /// Unregister the app-wide service of type `T`, returning it.
+    fn remove_service<T: 'static>(&mut self) -> Option<Rc<T>>;
+
+    /// The app-wide service of type `T`, ignoring scopes. Unlike [`use_service`] this
+    /// works outside of render, e.g. in actions and tasks.
+    fn service<T: 'static>(&self) -> Option<Rc<T>>;
+}
+
+impl ServiceExt for App {
+    fn provide<T: 'static>(&mut self, service: T) {
+        insert_app_service(service);
+        self.refresh_windows();
+    }
+
+    fn remove_service<T: 'static>(&mut self) -> Option<Rc<T>> {
+        let service = remove_app_service::<T>();
+        if service.is_some() {
+            self.refresh_windows();
+        }
+        service
+    }
+
+    fn service<T: 'static>(&self) -> Option<Rc<T>> {
+        APP_SERVICES.with(|services| lookup::<T>(&services.borrow()))
+    }
+}
+
+/// Provides services to the element it wraps and everything below it.
+pub struct ServiceScope {
+    services: Rc<ServiceMap>,
+    child: AnyElement,
+}
+
+impl ServiceScope {
+    pub fn new(child: impl IntoElement) -> Self {
+        Self {
+            services: Rc::new(HashMap::new()),
+            child: child.into_any_element(),
+        }
+    }
+
+    /// Provide `service` in this scope, shadowing any outer service of the same type.
+    pub fn provide<T: 'static>(mut self, service: T) -> Self {
+        Rc::make_mut(&mut self.services).insert(TypeId::of::<T>(), Rc::new(service));
+        self
+    }
+}
+
+impl IntoElement for ServiceScope {
+    type Element = Self;
+
+    fn into_element(self) -> Self::Element {
+        self
+    }
+}
+
+impl Element for ServiceScope {
+    type RequestLayoutState = ();
+    type PrepaintState = ();
+
+    fn id(&self) -> Option<ElementId> {
+        None
+    }
+
+    fn source_location(&self) -> Option<&'static core::panic::Location<'static>> {
+        None
+    }
+
+    fn request_layout(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&gpui::InspectorElementId>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> (LayoutId, Self::RequestLayoutState) {
+        let layout_id = with_scope(&self.services, || self.child.request_layout(window, cx));
+        (layout_id, ())
+    }
+
+    fn prepaint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&gpui::InspectorElementId>,
+        _bounds: Bounds<Pixels>,
+        _request_layout: &mut Self::RequestLayoutState,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Self::PrepaintState {
+        with_scope(&self.services, || self.child.prepaint(window, cx));
+    }
+
+    fn paint(
+        &mut self,
+        _id: Option<&GlobalElementId>,
+        _inspector_id: Option<&gpui::InspectorElementId>,
+        _bounds: Bounds<Pixels>,
+        _request_layout: &mut Self::RequestLayoutState,
+        _prepaint: &mut Self::PrepaintState,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        with_scope(&self.services, || self.child.paint(window, cx));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Settings(&'static str);
+
+    #[test]
+    fn inner_scopes_shadow_outer_ones() {
+        insert_app_service(Settings("app"));
+        assert_eq!(use_service::<Settings>().unwrap().0, "app");
+
+        let mut panel: ServiceMap = HashMap::new();
+        panel.insert(TypeId::of::<Settings>(), Rc::new(Settings("panel")));
+        let mut other: ServiceMap = HashMap::new();
+        other.insert(TypeId::of::<u32>(), Rc::new(7u32));
+
+        with_scope(&Rc::new(panel), || {
+            with_scope(&Rc::new(other), || {
+                assert_eq!(use_service::<Settings>().unwrap().0, "panel");
+                assert_eq!(*use_service::<u32>().unwrap(), 7);
+            });
+            assert!(use_service::<u32>().is_none());
+        });
+
+        assert_eq!(use_service::<Settings>().unwrap().0, "app");
+        assert_eq!(remove_app_service::<Settings>().unwrap().0, "app");
+        assert!(use_service::<Settings>().is_none());
+    }
+}
@@ -0,0 +1,382 @@
+//! Input recording and replay for reproducing bug reports.
+//!
+//! While recording, every mouse press, release, move and scroll in one window is
+//! written to a [`SessionTrace`] with its timing, along with each keystroke and the
+//! action it ran. A user saves the trace and attaches it to an issue; a maintainer
+//! loads it and replays it into the same view to watch the bug happen.
+//!
+//! ```rust,ignore
+//! // In the root view's render, first so nothing can hide clicks from it:
+//! div().size_full().child(session_recording::recording_layer()).child(app)
+//!
+//! // From a "Record a bug report" menu item:
+//! session_recording::start_recording(window, cx);
+//! // ...and when the user is done:
+//! if let Some(trace) = session_recording::stop_recording(cx) {
+//!     trace.save("bug-report.trace")?;
+//! }
+//!
+//! // Reproducing it, at the speed it was recorded:
+//! let trace = SessionTrace::load("bug-report.trace")?;
+//! session_recording::replay(trace, window.window_handle(), 1.0, cx).detach();
+//! ```
+//!
+//! Replay needs no special build: it dispatches the inputs as if the platform had
+//! delivered them. In a headless gpui test, step through the trace yourself so each
+//! input is followed by a frame:
+//!
+//! ```rust,ignore
+//! for event in &trace.events {
+//!     cx.update(|window, cx| session_recording::dispatch_trace_input(&event.input, window, cx));
+//!     cx.run_until_parked();
+//! }
+//! ```
+//!
+//! Text composed through an input method arrives as its final keystrokes, not as the
+//! composition.
+
+mod trace;
+
+pub use trace::{
+    SessionTrace, TraceButton, TraceEvent, TraceInput, TraceModifiers, TraceParseError,
+    TRACE_HEADER,
+};
+
+use gpui::*;
+use smol::Timer;
+use std::time::{Duration, Instant};
+
+/// Shortest gap between replayed inputs, so each is hit-tested against a frame drawn
+/// after the one before.
+const MIN_REPLAY_STEP: Duration = Duration::from_millis(16);
+
+struct Recording {
+    window: AnyWindowHandle,
+    started: Instant,
+    trace: SessionTrace,
+    _keystrokes: Subscription,
+}
+
+#[derive(Default)]
+struct SessionRecorder {
+    recording: Option<Recording>,
+}
+
+impl Global for SessionRecorder {}
+
+/// Start recording the inputs to `window`, dropping any recording in progress.
+/// Mouse input is only seen where a [`recording_layer`] is rendered.
+pub fn start_recording(window: &mut Window, cx: &mut App) {
+    let handle = window.window_handle();
+    let keystrokes = cx.observe_keystrokes(move |event, window, cx| {
+        if window.window_handle() != handle {
+            return;
+        }
+        let keystroke = event.keystroke.unparse();
+        record(TraceInput::Key { keystroke }, cx);
+        if let Some(action) = event.action.as_ref() {
+            record_action(action.name(), cx);
+        }
+    });
+
+    let viewport = window.viewport_size();
+    let mut trace = SessionTrace::new();
+    trace.viewport = Some((f32::from(viewport.width), f32::from(viewport.height)));
+    cx.default_global::<SessionRecorder>().recording = Some(Recording {
+        window: handle,
+        started: Instant::now(),
+        trace,
+        _keystrokes: keystrokes,
+    });
+    window.refresh();
+}
+
+/// Stop recording and return what was recorded, if anything was being recorded.
+pub fn stop_recording(cx: &mut App) -> Option<SessionTrace> {
+    let recording = cx.default_global::<SessionRecorder>().recording.take()?;
+    Some(recording.trace)
+}
+
+pub fn is_recording(cx: &App) -> bool {
+    cx.try_global::<SessionRecorder>()
+        .is_some_and(|recorder| recorder.recording.is_some())
+}
+
+/// Note an action in the recording, e.g. a component reporting what a click did. Does
+/// nothing when not recording.
+pub fn record_action(name: impl Into<String>, cx: &mut App) {
+    record(TraceInput::Action { name: name.into() }, cx);
+}
+
+fn record(input: TraceInput, cx: &mut App) {
+    if let Some(recording) = cx.default_global::<SessionRecorder>().recording.as_mut() {
+        let at = recording.started.elapsed();
+        recording.trace.push(at, input);
+    }
+}
+
+fn is_recording_window(window: &Window, cx: &App) -> bool {
+    cx.try_global::<SessionRecorder>()
+        .and_then(|recorder| recorder.recording.as_ref())
+        .is_some_and(|recording| recording.window == window.window_handle())
+}
+
+/// Records the window's mouse input while a recording is running. Render it as the
+/// first child of the window's root, so it sees clicks before any overlay can stop
+/// them.
+pub fn recording_layer() -> RecordingLayer {
+    RecordingLayer
+}
+
+#[derive(IntoElement)]
+pub struct RecordingLayer;
+
+impl RenderOnce for RecordingLayer {
+    fn render(self, _window: &mut Window, _cx: &mut App) -> impl IntoElement {
+        canvas(
+            |_, _, _| {},
+            |_, _, window, cx| {
+                if !is_recording_window(window, cx) {
+                    return;
+                }
+                window.on_mouse_event(|event: &MouseDownEvent, phase, _, cx| {
+                    if phase == DispatchPhase::Capture {
+                        record(
+                            TraceInput::MouseDown {
+                                button: trace_button(event.button),
+                                x: f32::from(event.position.x),
+                                y: f32::from(event.position.y),
+                                modifiers: trace_modifiers(&event.modifiers),
+                                click_count: event.click_count,
+                            },
+                            cx,
+                        );
+                    }
+                });
+                window.on_mouse_event(|event: &MouseUpEvent, phase, _, cx| {
+                    if phase == DispatchPhase::Capture {
+                        record(
+                            TraceInput::MouseUp {
+                                button: trace_button(event.button),
+                                x: f32::from(event.position.x),
+                                y: f32::from(event.position.y),
+                                modifiers: trace_modifiers(&event.modifiers),
+                                click_count: event.click_count,
+                            },
+                            cx,
+                        );
+                    }
+                });
+                window.on_mouse_event(|event: &MouseMoveEvent, phase, _, cx| {
+                    if phase == DispatchPhase::Capture {
+                        record(
+                            TraceInput::MouseMove {
+                                x: f32::from(event.position.x),
+                                y: f32::from(event.position.y),
+                                pressed: event.pressed_button.map(trace_button),
+                                modifiers: trace_modifiers(&event.modifiers),
+                            },
+                            cx,
+                        );
+                    }
+                });
+                window.on_mouse_event(|event: &ScrollWheelEvent, phase, _, cx| {
+                    if phase != DispatchPhase::Capture {
+                        return;
+                    }
+                    let (dx, dy, lines) = match event.delta {
+                        ScrollDelta::Pixels(delta) => {
+                            (f32::from(delta.x), f32::from(delta.y), false)
+                        }
+                        ScrollDelta::Lines(delta) => (delta.x, delta.y, true),
+                    };
+                    record(
+                        TraceInput::Scroll {
+                            x: f32::from(event.position.x),
+                            y: f32::from(event.position.y),
+                            dx,
+                            dy,
+                            lines,
+                            modifiers: trace_modifiers(&event.modifiers),
+                        },
+                        cx,
+                    );
+                });
+            },
+        )
+        .absolute()
+        .size_full()
+    }
+}
+
+/// Play `trace` back into `window`. `speed` scales the recorded pauses: 2.0 replays
+/// twice as fast, and 0.0 only waits a frame between inputs. Dropping the task stops
+/// the replay.
+pub fn replay(trace: SessionTrace, window: AnyWindowHandle, speed: f32, cx: &mut App) -> Task<()> {
+    cx.spawn(async move |cx| {
+        let viewport = trace.viewport;
+        let _ = window.update(cx, |_, window, _| {
+            warn_on_viewport_mismatch(viewport, window)
+        });
+        let mut previous = Duration::ZERO;
+        for event in trace.events {
+            let pause = event.at.saturating_sub(previous);
+            previous = event.at;
+            let pause = if speed > 0.0 && speed.is_finite() {
+                pause.div_f32(speed)
+            } else {
+                Duration::ZERO
+            };
+            Timer::after(pause.max(MIN_REPLAY_STEP)).await;
+
+            let dispatched = window.update(cx, |_, window, cx| {
+                dispatch_trace_input(&event.input, window, cx)
+            });
+            if dispatched.is_err() {
+                break;
+            }
+        }
+    })
+}
+
+fn warn_on_viewport_mismatch(recorded: Option<(f32, f32)>, window: &Window) {
+    let Some((width, height)) = recorded else {
+        return;
+    };
+    let viewport = window.viewport_size();
+    if (f32::from(viewport.width), f32::from(viewport.height)) != (width, height) {
+        tracing::warn!(
+            width,
+            height,
+            "replaying a trace recorded at a different window size; clicks may miss"
+        );
+    }
+}
+
+/// Deliver one recorded input to `window` as if the platform had sent it.
+pub fn dispatch_trace_input(input: &TraceInput, window: &mut Window, cx: &mut App) {
+    let position = |x: f32, y: f32| point(px(x), px(y));
+    match input {
+        TraceInput::MouseDown {
+            button,
+            x,
+            y,
+            modifiers,
+            click_count,
+        } => {
+            window.dispatch_event(
+                PlatformInput::MouseDown(MouseDownEvent {
+                    button: mouse_button(*button),
+                    position: position(*x, *y),
+                    modifiers: gpui_modifiers(modifiers),
+                    click_count: *click_count,
+                    ..Default::default()
+                }),
+                cx,
+            );
+        }
+        TraceInput::MouseUp {
+            button,
+            x,
+            y,
+            modifiers,
+            click_count,
+        } => {
+            window.dispatch_event(
+                PlatformInput::MouseUp(MouseUpEvent {
+                    button: mouse_button(*button),
+                    position: position(*x, *y),
+                    modifiers: gpui_modifiers(modifiers),
+                    click_count: *click_count,
+                }),
+                cx,
+            );
+        }
+        TraceInput::MouseMove {
+            x,
+            y,
+            pressed,
+            modifiers,
+        } => {
+            window.dispatch_event(
+                PlatformInput::MouseMove(MouseMoveEvent {
+                    position: position(*x, *y),
+                    pressed_button: pressed.map(mouse_button),
+                    modifiers: gpui_modifiers(modifiers),
+                }),
+                cx,
+            );
+        }
+        TraceInput::Scroll {
+            x,
+            y,
+            dx,
+            dy,
+            lines,
+            modifiers,
+        } => {
+            let delta = if *lines {
+                ScrollDelta::Lines(point(*dx, *dy))
+            } else {
+                ScrollDelta::Pixels(point(px(*dx), px(*dy)))
+            };
+            window.dispatch_event(
+                PlatformInput::ScrollWheel(ScrollWheelEvent {
+                    position: position(*x, *y),
+                    delta,
+                    modifiers: gpui_modifiers(modifiers),
+                    ..Default::default()
+                }),
+                cx,
+            );
+        }
+        TraceInput::Key { keystroke } => match Keystroke::parse(keystroke) {
+            Ok(keystroke) => {
+                window.dispatch_keystroke(keystroke, cx);
+            }
+            Err(_) => tracing::warn!(%keystroke, "skipping unparseable keystroke in trace"),
+        },
+        TraceInput::Action { .. } => {}
+    }
+    window.refresh();
+}
+
+fn trace_button(button: MouseButton) -> TraceButton {
+    match button {
+        MouseButton::Left => TraceButton::Left,
+        MouseButton::Right => TraceButton::Right,
+        MouseButton::Middle => TraceButton::Middle,
+        MouseButton::Navigate(NavigationDirection::Back) => TraceButton::Back,
+        MouseButton::Navigate(NavigationDirection::Forward) => TraceButton::Forward,
+    }
+}
+
+fn mouse_button(button: TraceButton) -> MouseButton {
+    match button {
+        TraceButton::Left => MouseButton::Left,
+        TraceButton::Right => MouseButton::Right,
+        TraceButton::Middle => MouseButton::Middle,
+        TraceButton::Back => MouseButton::Navigate(NavigationDirection::Back),
+        TraceButton::Forward => MouseButton::Navigate(NavigationDirection::Forward),
+    }
+}
+
+fn trace_modifiers(modifiers: &Modifiers) -> TraceModifiers {
+    TraceModifiers {
+        control: modifiers.control,
+        alt: modifiers.alt,
+        shift: modifiers.shift,
+        platform: modifiers.platform,
+        function: modifiers.function,
+    }
+}
+
+fn gpui_modifiers(modifiers: &TraceModifiers) -> Modifiers {
+    Modifiers {
+        control: modifiers.control,
+        alt: modifiers.alt,
+        shift: modifiers.shift,
+        platform: modifiers.platform,
+        function: modifiers.function,
+    }
+}
@@ -0,0 +1,531 @@
+//! The trace file format.
+//!
+//! A trace is plain text so it can be pasted into an issue and read by a maintainer:
+//! a header line, the window's viewport size, then one input per line as the
+//! milliseconds since recording started, a kind and its fields.
+//!
+//! ```text
+//! adabraka-trace 1
+//! viewport 1280 800
+//! 0 move 412 96 - -
+//! 120 down left 412 96 - 1
+//! 184 move 460 96 left -
+//! 240 up left 460 96 - 1
+//! 910 scroll 300 420 px 0 -24 -
+//! 1500 key cmd-s
+//! 1500 action editor::Save
+//! ```
+//!
+//! Positions are in window pixels; `-` stands for no button or no modifiers. Blank
+//! lines and lines starting with `#` are ignored, so a reporter can annotate a trace.
+
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::Duration;
+
+pub const TRACE_HEADER: &str = "adabraka-trace 1";
+
+/// Mouse moves closer together than this, with the same buttons and modifiers held,
+/// are stored as the last of them.
+const MOVE_COALESCE: Duration = Duration::from_millis(16);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceButton {
+    Left,
+    Right,
+    Middle,
+    Back,
+    Forward,
+}
+
+impl TraceButton {
+    fn name(self) -> &'static str {
+        match self {
+            TraceButton::Left => "left",
+            TraceButton::Right => "right",
+            TraceButton::Middle => "middle",
+            TraceButton::Back => "back",
+            TraceButton::Forward => "forward",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "left" => TraceButton::Left,
+            "right" => TraceButton::Right,
+            "middle" => TraceButton::Middle,
+            "back" => TraceButton::Back,
+            "forward" => TraceButton::Forward,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TraceModifiers {
+    pub control: bool,
+    pub alt: bool,
+    pub shift: bool,
+    /// Cmd on macOS, the Windows/Super key elsewhere.
+    pub platform: bool,
+    pub function: bool,
+}
+
+impl TraceModifiers {
+    const NAMES: [&'static str; 5] = ["ctrl", "alt", "shift", "cmd", "fn"];
+
+    fn flags(&self) -> [bool; 5] {
+        [
+            self.control,
+            self.alt,
+            self.shift,
+            self.platform,
+            self.function,
+        ]
+    }
+
+    fn write(&self, out: &mut String) {
+        let names: Vec<&str> = Self::NAMES
+            .iter()
+            .zip(self.flags())
+            .filter(|(_, held)| *held)
+            .map(|(name, _)| *name)
+            .collect();
+        if names.is_empty() {
+            out.push('-');
+        } else {
+            out.push_str(&names.join("-"));
+        }
+    }
+
+    fn parse(token: &str) -> Option<Self> {
+        let mut modifiers = Self::default();
+        if token == "-" {
+            return Some(modifiers);
+        }
+        for name in token.split('-') {
+            match name {
+                "ctrl" => modifiers.control = true,
+                "alt" => modifiers.alt = true,
+                "shift" => modifiers.shift = true,
+                "cmd" => modifiers.platform = true,
+                "fn" => modifiers.function = true,
+                _ => return None,
+            }
+        }
+        Some(modifiers)
+    }
+}
+
+/// One recorded input.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TraceInput {
+    MouseDown {
+        button: TraceButton,
+        x: f32,
+        y: f32,
+        modifiers: TraceModifiers,
+        click_count: usize,
+    },
+    MouseUp {
+        button: TraceButton,
+        x: f32,
+        y: f32,
+        modifiers: TraceModifiers,
+        click_count: usize,
+    },
+    MouseMove {
+        x: f32,
+        y: f32,
+        pressed: Option<TraceButton>,
+        modifiers: TraceModifiers,
+    },
+    /// `lines` is true for line-based wheels, whose deltas count lines, not pixels.
+    Scroll {
+        x: f32,
+        y: f32,
+        dx: f32,
+        dy: f32,
+        lines: bool,
+        modifiers: TraceModifiers,
+    },
+    /// A keystroke in the form key bindings use, e.g. `cmd-shift-p`.
+    Key { keystroke: String },
+    /// An action that ran, for reading the trace. Replay skips these: the inputs
+    /// before them run the action again.
+    Action { name: String },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceEvent {
+    /// Time since recording started.
+    pub at: Duration,
+    pub input: TraceInput,
+}
+
+/// A recording of one window's inputs.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SessionTrace {
+    /// Viewport size the inputs were recorded at. Positions only line up when the
+    /// trace is replayed into a window of the same size.
+    pub viewport: Option<(f32, f32)>,
+    pub events: Vec<TraceEvent>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceParseError {
+    /// 1-based line of the trace text.
+    pub line: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for TraceParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for TraceParseError {}
+
+impl SessionTrace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an input, folding it into the previous one if both are mouse moves a
+    /// few milliseconds apart.
+    pub fn push(&mut self, at: Duration, input: TraceInput) {
+        if let (
+            Some(TraceEvent {
+                at: last_at,
+                input: last @ TraceInput::MouseMove { .. },
+            }),
+            TraceInput::MouseMove {
+                pressed, modifiers, ..
+            },
+        ) = (self.events.last_mut(), &input)
+        {
+            let same_state = matches!(
+                last,
+                TraceInput::MouseMove { pressed: last_pressed, modifiers: last_modifiers, .. }
+                    if last_pressed == pressed && last_modifiers == modifiers
+            );
+            if same_state && at.saturating_sub(*last_at) < MOVE_COALESCE {
+                *last = input;
+                return;
+            }
+        }
+        self.events.push(TraceEvent { at, input });
+    }
+
+    /// How long the recording ran, up to its last input.
+    pub fn duration(&self) -> Duration {
+        self.events.last().map_or(Duration::ZERO, |event| event.at)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        out.push_str(TRACE_HEADER);
+        out.push('\n');
+        if let Some((width, height)) = self.viewport {
+            let _ = writeln!(out, "viewport {} {}", width, height);
+        }
+        for event in &self.events {
+            let _ = write!(out, "{} ", event.at.as_millis());
+            match &event.input {
+                TraceInput::MouseDown {
+                    button,
+                    x,
+                    y,
+                    modifiers,
+                    click_count,
+                }
+                | TraceInput::MouseUp {
+                    button,
+                    x,
+                    y,
+                    modifiers,
+                    click_count,
+                } => {
+                    let kind = match event.input {
+                        TraceInput::MouseDown { .. } => "down",
+                        _ => "up",
+                    };
+                    let _ = write!(out, "{} {} {} {} ", kind, button.name(), x, y);
+                    modifiers.write(&mut out);
+                    let _ = write!(out, " {}", click_count);
+                }
+                TraceInput::MouseMove {
+                    x,
+                    y,
+                    pressed,
+                    modifiers,
+                } => {
+                    let pressed = pressed.map_or("-", TraceButton::name);
+                    let _ = write!(out, "move {} {} {} ", x, y, pressed);
+                    modifiers.write(&mut out);
+                }
+                TraceInput::Scroll {
+                    x,
+                    y,
+                    dx,
+                    dy,
+                    lines,
+                    modifiers,
+                } => {
+                    let unit = if *lines { "lines" } else { "px" };
+                    let _ = write!(out, "scroll {} {} {} {} {} ", x, y, unit, dx, dy);
+                    modifiers.write(&mut out);
+                }
+                TraceInput::Key { keystroke } => {
+                    let _ = write!(out, "key {}", keystroke);
+                }
+                TraceInput::Action { name } => {
+                    let _ = write!(out, "action {}", name);
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    pub fn parse(text: &str) -> Result<Self, TraceParseError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(ix, line)| (ix + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        match lines.next() {
+            Some((_, TRACE_HEADER)) => {}
+            Some((line, _)) => return Err(error(line, "not an adabraka trace")),
+            None => return Err(error(1, "empty trace")),
+        }
+
+        let mut trace = Self::new();
+        for (line, text) in lines {
+            let fields: Vec<&str> = text.split_whitespace().collect();
+            if fields[0] == "viewport" {
+                let [width, height] = numbers(&fields[1..], line)?;
+                trace.viewport = Some((width, height));
+                continue;
+            }
+            let at = fields[0]
+                .parse::<u64>()
+                .map(Duration::from_millis)
+                .map_err(|_| error(line, "expected a time in milliseconds"))?;
+            let kind = fields.get(1).copied().unwrap_or_default();
+            let args = fields.get(2..).unwrap_or_default();
+            let input = match kind {
+                "down" | "up" => {
+                    let [button, x, y, modifiers, click_count] = fixed(args, line)?;
+                    let button = parse_button(button, line)?;
+                    let [x, y] = numbers(&[x, y], line)?;
+                    let modifiers = parse_modifiers(modifiers, line)?;
+                    let click_count = click_count
+                        .parse()
+                        .map_err(|_| error(line, "expected a click count"))?;
+                    if kind == "down" {
+                        TraceInput::MouseDown {
+                            button,
+                            x,
+                            y,
+                            modifiers,
+                            click_count,
+                        }
+                    } else {
+                        TraceInput::MouseUp {
+                            button,
+                            x,
+                            y,
+                            modifiers,
+                            click_count,
+                        }
+                    }
+                }
+                "move" => {
+                    let [x, y, pressed, modifiers] = fixed(args, line)?;
+                    let [x, y] = numbers(&[x, y], line)?;
+                    let pressed = match pressed {
+                        "-" => None,
+                        name => Some(parse_button(name, line)?),
+                    };
+                    TraceInput::MouseMove {
+                        x,
+                        y,
+                        pressed,
+                        modifiers: parse_modifiers(modifiers, line)?,
+                    }
+                }
+                "scroll" => {
+                    let [x, y, unit, dx, dy, modifiers] = fixed(args, line)?;
+                    let [x, y, dx, dy] = numbers(&[x, y, dx, dy], line)?;
+                    let lines = match unit {
+                        "px" => false,
+                        "lines" => true,
+                        _ => return Err(error(line, "expected `px` or `lines`")),
+                    };
+                    TraceInput::Scroll {
+                        x,
+                        y,
+                        dx,
+                        dy,
+                        lines,
+                        modifiers: parse_modifiers(modifiers, line)?,
+                    }
+                }
+                "key" | "action" => {
+                    let [value] = fixed(args, line)?;
+                    if kind == "key" {
+                        TraceInput::Key {
+                            keystroke: value.to_string(),
+                        }
+                    } else {
+                        TraceInput::Action {
+                            name: value.to_string(),
+                        }
+                    }
+                }
+                "" => return Err(error(line, "missing input kind")),
+                other => return Err(error(line, &format!("unknown input `{}`", other))),
+            };
+            trace.events.push(TraceEvent { at, input });
+        }
+        Ok(trace)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_text())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Self::parse(&text).map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+    }
+}
+
+fn error(line: usize, message: &str) -> TraceParseError {
+    TraceParseError {
+        line,
+        message: message.to_string(),
+    }
+}
+
+fn fixed<'a, const N: usize>(
+    args: &[&'a str],
+    line: usize,
+) -> Result<[&'a str; N], TraceParseError> {
+    args.try_into()
+        .map_err(|_| error(line, &format!("expected {} fields", N)))
+}
+
+fn numbers<const N: usize>(args: &[&str], line: usize) -> Result<[f32; N], TraceParseError> {
+    let args: [&str; N] = fixed(args, line)?;
+    let mut values = [0.0; N];
+    for (value, arg) in values.iter_mut().zip(args) {
+        *value = arg
+            .parse()
+            .map_err(|_| error(line, &format!("expected a number, found `{}`", arg)))?;
+    }
+    Ok(values)
+}
+
+fn parse_button(name: &str, line: usize) -> Result<TraceButton, TraceParseError> {
+    TraceButton::parse(name).ok_or_else(|| error(line, &format!("unknown button `{}`", name)))
+}
+
+fn parse_modifiers(token: &str, line: usize) -> Result<TraceModifiers, TraceParseError> {
+    TraceModifiers::parse(token)
+        .ok_or_else(|| error(line, &format!("unknown modifiers `{}`", token)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn round_trips_through_text() {
+        let mut trace = SessionTrace::new();
+        trace.viewport = Some((1280.0, 800.0));
+        trace.push(
+            ms(120),
+            TraceInput::MouseDown {
+                button: TraceButton::Left,
+                x: 412.5,
+                y: 96.0,
+                modifiers: TraceModifiers {
+                    shift: true,
+                    platform: true,
+                    ..Default::default()
+                },
+                click_count: 2,
+            },
+        );
+        trace.push(
+            ms(300),
+            TraceInput::Scroll {
+                x: 10.0,
+                y: 20.0,
+                dx: 0.0,
+                dy: -3.0,
+                lines: true,
+                modifiers: TraceModifiers::default(),
+            },
+        );
+        trace.push(
+            ms(900),
+            TraceInput::Key {
+                keystroke: "cmd-s".into(),
+            },
+        );
+        trace.push(
+            ms(900),
+            TraceInput::Action {
+                name: "editor::Save".into(),
+            },
+        );
+
+        let text = trace.to_text();
+        assert!(text.contains("120 down left 412.5 96 shift-cmd 2\n"));
+        assert_eq!(SessionTrace::parse(&text).unwrap(), trace);
+        assert_eq!(trace.duration(), ms(900));
+    }
+
+    #[test]
+    fn coalesces_quick_mouse_moves() {
+        let moved = |x: f32, pressed| TraceInput::MouseMove {
+            x,
+            y: 0.0,
+            pressed,
+            modifiers: TraceModifiers::default(),
+        };
+        let mut trace = SessionTrace::new();
+        trace.push(ms(0), moved(1.0, None));
+        trace.push(ms(5), moved(2.0, None));
+        trace.push(ms(10), moved(3.0, Some(TraceButton::Left)));
+        trace.push(ms(40), moved(4.0, Some(TraceButton::Left)));
+
+        let xs: Vec<(u128, f32)> = trace
+            .events
+            .iter()
+            .map(|event| match event.input {
+                TraceInput::MouseMove { x, .. } => (event.at.as_millis(), x),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(xs, vec![(0, 2.0), (10, 3.0), (40, 4.0)]);
+    }
+
+    #[test]
+    fn reports_the_failing_line() {
+        let text = "adabraka-trace 1\n# clicked the slider\n10 down left 1 2 - 1\n20 drag 1 2\n";
+        let err = SessionTrace::parse(text).unwrap_err();
+        assert_eq!(err.line, 4);
+        assert!(SessionTrace::parse("hello").is_err());
+        assert!(SessionTrace::parse("adabraka-trace 1\n5 move 1 2 left ctrl-hyper").is_err());
+    }
+}
@@ -0,0 +1,248 @@
+//! Single-instance apps: forward a second launch's arguments to the running instance.
+//!
+//! Call [`acquire`] at the top of `main`, before creating the `Application`. The first
+//! process becomes the primary and listens on a local socket (a Unix domain socket, or
+//! a loopback TCP port recorded in a temp file on Windows). Later launches send their
+//! arguments to it and exit, so double-clicking a file opens it in the running app.
+//!
+//! ```rust,ignore
+//! fn main() {
+//!     let Some(instance) = single_instance::acquire("com.example.notes").unwrap() else {
+//!         return; // forwarded to the running instance
+//!     };
+//!     Application::new().run(move |cx| {
+//!         adabraka_ui::init(cx);
+//!         instance.on_activate(|args, cx| {
+//!             for arg in args {
+//!                 if arg.contains("://") {
+//!                     deeplink::deliver(arg);
+//!                 } else {
+//!                     open_tab(arg.into(), cx);
+//!                 }
+//!             }
+//!         }, cx);
+//!     });
+//! }
+//! ```
+
+use gpui::*;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// The primary instance. Hand it to [`SingleInstance::on_activate`] once the app is
+/// running; dropping it instead stops accepting forwarded launches.
+pub struct SingleInstance {
+    listener: Listener,
+    path: PathBuf,
+}
+
+enum Listener {
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixListener),
+    #[cfg(not(unix))]
+    Tcp(std::net::TcpListener),
+}
+
+/// Become the primary instance for `app_id`, or forward this process's arguments to
+/// the existing one. Returns `Ok(None)` when the arguments were forwarded and this
+/// process should exit.
+pub fn acquire(app_id: &str) -> io::Result<Option<SingleInstance>> {
+    acquire_with_args(app_id, std::env::args().skip(1).collect())
+}
+
+/// Like [`acquire`], forwarding `args` instead of the process arguments. Relative paths
+/// that exist are made absolute, since the primary has its own working directory.
+pub fn acquire_with_args(app_id: &str, args: Vec<String>) -> io::Result<Option<SingleInstance>> {
+    let path = endpoint_path(app_id);
+    let args: Vec<String> = args.into_iter().map(absolutize).collect();
+
+    if forward(&path, &args).is_ok() {
+        return Ok(None);
+    }
+    let listener = bind(&path)?;
+    Ok(Some(SingleInstance { listener, path }))
+}
+
+impl SingleInstance {
+    /// Call `handler` on the main thread with the arguments of every later launch, after
+    /// bringing the app's window to the front.
+    pub fn on_activate(self, handler: impl Fn(Vec<String>, &mut App) + 'static, cx: &mut App) {
+        let (tx, rx) = smol::channel::unbounded::<Vec<String>>();
+        std::thread::Builder::new()
+            .name("single-instance".into())
+            .spawn(move || {
+                let instance = self;
+                loop {
+                    let args = match &instance.listener {
+                        #[cfg(unix)]
+                        Listener::Unix(listener) => listener
+                            .accept()
+                            .and_then(|(mut stream, _)| read_args(&mut stream)),
+                        #[cfg(not(unix))]
+                        Listener::Tcp(listener) => listener
+                            .accept()
+                            .and_then(|(mut stream, _)| read_args(&mut stream)),
+                    };
+                    match args {
+                        Ok(args) => {
+                            if tx.send_blocking(args).is_err() {
+                                break;
+                            }
+                        }
+                        Err(err) => tracing::warn!(%err, "failed to read forwarded launch"),
+                    }
+                }
+            })
+            .ok();
+
+        cx.spawn(async move |cx| {
+            while let Ok(args) = rx.recv().await {
+                let updated = cx.update(|cx| {
+                    tracing::debug!(count = args.len(), "activated by another launch");
+                    focus_app(cx);
+                    handler(args, cx);
+                });
+                if updated.is_err() {
+                    break;
+                }
+            }
+        })
+        .detach();
+    }
+}
+
+impl Drop for SingleInstance {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+fn focus_app(cx: &mut App) {
+    cx.activate(true);
+    let window = cx.active_window().or_else(|| cx.windows().first().copied());
+    if let Some(window) = window {
+        let _ = window.update(cx, |_, window, _| window.activate_window());
+    }
+}
+
+fn endpoint_path(app_id: &str) -> PathBuf {
+    let name: String = app_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    #[cfg(unix)]
+    {
+        let dir = std::env::var_os("XDG_RUNTIME_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(std::env::temp_dir);
+        dir.join(format!("{}.sock", name))
+    }
+    #[cfg(not(unix))]
+    {
+        std::env::temp_dir().join(format!("{}.port", name))
+    }
+}
+
+fn absolutize(arg: String) -> String {
+    if arg.starts_with('-') || arg.contains("://") {
+        return arg;
+    }
+    let path = Path::new(&arg);
+    if path.is_relative() && path.exists() {
+        if let Ok(absolute) = std::fs::canonicalize(path) {
+            return absolute.to_string_lossy().into_owned();
+        }
+    }
+    arg
+}
+
+/// Arguments are sent NUL-separated; the sender closing the stream ends the message.
+fn encode_args(args: &[String]) -> Vec<u8> {
+    args.join("\0").into_bytes()
+}
+
+fn decode_args(bytes: &[u8]) -> Vec<String> {
+    if bytes.is_empty() {
+        return Vec::new();
+    }
+    String::from_utf8_lossy(bytes)
+        .split('\0')
+        .map(str::to_string)
+        .collect()
+}
+
+fn read_args(stream: &mut impl Read) -> io::Result<Vec<String>> {
+    let mut bytes = Vec::new();
+    stream.read_to_end(&mut bytes)?;
+    Ok(decode_args(&bytes))
+}
+
+#[cfg(unix)]
+fn forward(path: &Path, args: &[String]) -> io::Result<()> {
+    let mut stream = std::os::unix::net::UnixStream::connect(path)?;
+    stream.set_write_timeout(Some(CONNECT_TIMEOUT))?;
+    stream.write_all(&encode_args(args))?;
+    stream.shutdown(std::net::Shutdown::Write)
+}
+
+#[cfg(not(unix))]
+fn forward(path: &Path, args: &[String]) -> io::Result<()> {
+    let port: u16 = std::fs::read_to_string(path)?
+        .trim()
+        .parse()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let address = std::net::SocketAddr::from(([127, 0, 0, 1], port));
+    let mut stream = std::net::TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?;
+    stream.set_write_timeout(Some(CONNECT_TIMEOUT))?;
+    stream.write_all(&encode_args(args))?;
+    stream.shutdown(std::net::Shutdown::Write)
+}
+
+#[cfg(unix)]
+fn bind(path: &Path) -> io::Result<Listener> {
+    // Nothing answered on the socket, so any file left there is from a crashed instance.
+    let _ = std::fs::remove_file(path);
+    std::os::unix::net::UnixListener::bind(path).map(Listener::Unix)
+}
+
+#[cfg(not(unix))]
+fn bind(path: &Path) -> io::Result<Listener> {
+    let listener = std::net::TcpListener::bind(("127.0.0.1", 0))?;
+    std::fs::write(path, listener.local_addr()?.port().to_string())?;
+    Ok(Listener::Tcp(listener))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn args_round_trip() {
+        let args = vec![
+            "--new-window".to_string(),
+            "my notes.md".to_string(),
+            String::new(),
+        ];
+        assert_eq!(decode_args(&encode_args(&args)), args);
+        assert!(decode_args(&encode_args(&[])).is_empty());
+    }
+
+    #[test]
+    fn leaves_flags_and_urls_alone() {
+        assert_eq!(absolutize("--verbose".into()), "--verbose");
+        assert_eq!(
+            absolutize("myapp://open?file=a".into()),
+            "myapp://open?file=a"
+        );
+        assert_eq!(absolutize("does/not/exist.md".into()), "does/not/exist.md");
+    }
+}
@@ -0,0 +1,428 @@
+//! Vector export of components as structured SVG.
+//!
+//! [`export_svg`] draws a component into an SVG document without going through the
+//! GPU: shapes become `<rect>`, `<circle>` and `<path>` elements and text stays
+//! `<text>`, so the result can be edited in a design tool, searched, and printed at
+//! any resolution. It's meant for design hand-off and documentation figures.
+//!
+//! Components take part by implementing [`ExportSvg`], drawing the same geometry they
+//! paint on screen into an [`SvgDocument`]: shapes traced through [`PathSink`] go to a
+//! [`PathBuilder`] when painting and an [`SvgPath`] when exporting, and text is
+//! measured with the app's text system. The line and pie charts do; a figure made of
+//! several components implements it by handing each its own bounds.
+//!
+//! ```rust,ignore
+//! let chart = LineChart::new(series).x_labels(months);
+//! let svg = svg_export::export_svg(&chart, size(px(640.0), px(360.0)), &Theme::light(), cx);
+//! std::fs::write("docs/revenue.svg", svg)?;
+//! ```
+
+use crate::theme::Theme;
+use gpui::*;
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+/// A component that can draw itself as vector shapes and text.
+pub trait ExportSvg {
+    /// Draw into `svg`, filling `bounds`, in the colors of `theme`.
+    fn export_svg(&self, bounds: Bounds<Pixels>, theme: &Theme, svg: &mut SvgDocument);
+}
+
+/// Render `element` at `size` into a standalone SVG document on the theme's
+/// background.
+pub fn export_svg(element: &impl ExportSvg, size: Size<Pixels>, theme: &Theme, cx: &App) -> String {
+    let mut svg = SvgDocument::new(size, theme, cx);
+    let bounds = Bounds::new(Point::default(), size);
+    svg.rect(bounds, px(0.0), Some(theme.tokens.background), None);
+    element.export_svg(bounds, theme, &mut svg);
+    svg.finish()
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SvgTextAnchor {
+    #[default]
+    Start,
+    Middle,
+    End,
+}
+
+#[derive(Clone, Debug)]
+pub struct SvgTextStyle {
+    pub size: Pixels,
+    pub color: Hsla,
+    pub weight: FontWeight,
+    pub anchor: SvgTextAnchor,
+    /// `None` uses the document's font, the theme's UI font.
+    pub font_family: Option<SharedString>,
+}
+
+impl SvgTextStyle {
+    pub fn new(size: Pixels, color: Hsla) -> Self {
+        Self {
+            size,
+            color,
+            weight: FontWeight::NORMAL,
+            anchor: SvgTextAnchor::Start,
+            font_family: None,
+        }
+    }
+
+    pub fn weight(mut self, weight: FontWeight) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    pub fn anchor(mut self, anchor: SvgTextAnchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    pub fn font_family(mut self, family: impl Into<SharedString>) -> Self {
+        self.font_family = Some(family.into());
+        self
+    }
+}
+
+/// Somewhere to trace a shape: a [`PathBuilder`] to paint it, or an [`SvgPath`] to
+/// export it. Components trace through this once so both come out the same.
+pub trait PathSink {
+    fn move_to(&mut self, to: Point<Pixels>);
+    fn line_to(&mut self, to: Point<Pixels>);
+    /// A quadratic curve to `to` through the control point `ctrl`.
+    fn curve_to(&mut self, to: Point<Pixels>, ctrl: Point<Pixels>);
+    /// A circular arc to `to`, clockwise on screen when `clockwise` is set.
+    fn arc_to(&mut self, radius: Pixels, large_arc: bool, clockwise: bool, to: Point<Pixels>);
+    fn close(&mut self);
+}
+
+impl PathSink for PathBuilder {
+    fn move_to(&mut self, to: Point<Pixels>) {
+        PathBuilder::move_to(self, to);
+    }
+
+    fn line_to(&mut self, to: Point<Pixels>) {
+        PathBuilder::line_to(self, to);
+    }
+
+    fn curve_to(&mut self, to: Point<Pixels>, ctrl: Point<Pixels>) {
+        PathBuilder::curve_to(self, to, ctrl);
+    }
+
+    fn arc_to(&mut self, radius: Pixels, large_arc: bool, clockwise: bool, to: Point<Pixels>) {
+        PathBuilder::arc_to(
+            self,
+            point(radius, radius),
+            px(0.0),
+            large_arc,
+            clockwise,
+            to,
+        );
+    }
+
+    fn close(&mut self) {
+        PathBuilder::close(self);
+    }
+}
+
+impl PathSink for SvgPath {
+    fn move_to(&mut self, to: Point<Pixels>) {
+        SvgPath::move_to(self, to);
+    }
+
+    fn line_to(&mut self, to: Point<Pixels>) {
+        SvgPath::line_to(self, to);
+    }
+
+    fn curve_to(&mut self, to: Point<Pixels>, ctrl: Point<Pixels>) {
+        SvgPath::curve_to(self, to, ctrl);
+    }
+
+    fn arc_to(&mut self, radius: Pixels, large_arc: bool, clockwise: bool, to: Point<Pixels>) {
+        SvgPath::arc_to(self, radius, large_arc, clockwise, to);
+    }
+
+    fn close(&mut self) {
+        SvgPath::close(self);
+    }
+}
+
+/// Path data in the same terms as [`PathBuilder`]: points in pixels, and
+/// `curve_to` a quadratic curve through a control point.
+#[derive(Clone, Debug, Default)]
+pub struct SvgPath {
+    data: String,
+}
+
+impl SvgPath {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn move_to(&mut self, to: Point<Pixels>) {
+        self.command('M', &[to]);
+    }
+
+    pub fn line_to(&mut self, to: Point<Pixels>) {
+        self.command('L', &[to]);
+    }
+
+    pub fn curve_to(&mut self, to: Point<Pixels>, ctrl: Point<Pixels>) {
+        self.command('Q', &[ctrl, to]);
+    }
+
+    /// A circular arc to `to`, clockwise on screen when `clockwise` is set.
+    pub fn arc_to(&mut self, radius: Pixels, large_arc: bool, clockwise: bool, to: Point<Pixels>) {
+        let r = pixels(radius);
+        let _ = write!(
+            self.data,
+            "A{} {} 0 {} {} {} {} ",
+            r,
+            r,
+            large_arc as u8,
+            clockwise as u8,
+            pixels(to.x),
+            pixels(to.y)
+        );
+    }
+
+    pub fn close(&mut self) {
+        self.data.push_str("Z ");
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn command(&mut self, command: char, points: &[Point<Pixels>]) {
+        self.data.push(command);
+        for (ix, point) in points.iter().enumerate() {
+            if ix > 0 {
+                self.data.push(' ');
+            }
+            let _ = write!(self.data, "{} {}", pixels(point.x), pixels(point.y));
+        }
+        self.data.push(' ');
+    }
+}
+
+/// An SVG document being drawn into. Elements are written in order, so later ones
+/// draw on top; [`SvgDocument::begin_group`] nests them under a named `<g>`.
+pub struct SvgDocument {
+    size: Size<Pixels>,
+    font_family: SharedString,
+    text_system: Arc<TextSystem>,
+    body: String,
+    depth: usize,
+}
+
+impl SvgDocument {
+    pub fn new(size: Size<Pixels>, theme: &Theme, cx: &App) -> Self {
+        Self {
+            size,
+            font_family: theme.tokens.font_family.clone(),
+            text_system: cx.text_system().clone(),
+            body: String::new(),
+            depth: 1,
+        }
+    }
+
+    /// How wide `text` sets in `style`, for laying out around it the way the
+    /// component does on screen.
+    pub fn text_width(&self, text: &str, style: &SvgTextStyle) -> Pixels {
+        let mut font = font(
+            style
+                .font_family
+                .clone()
+                .unwrap_or_else(|| self.font_family.clone()),
+        );
+        font.weight = style.weight;
+        let font_id = self.text_system.resolve_font(&font);
+        text.chars()
+            .filter_map(|ch| self.text_system.advance(font_id, style.size, ch).ok())
+            .fold(px(0.0), |width, advance| width + advance.width)
+    }
+
+    pub fn rect(
+        &mut self,
+        bounds: Bounds<Pixels>,
+        corner_radius: Pixels,
+        fill: Option<Hsla>,
+        stroke: Option<(Hsla, Pixels)>,
+    ) {
+        let mut element = format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"",
+            pixels(bounds.origin.x),
+            pixels(bounds.origin.y),
+            pixels(bounds.size.width),
+            pixels(bounds.size.height)
+        );
+        if corner_radius > px(0.0) {
+            let _ = write!(element, " rx=\"{}\"", pixels(corner_radius));
+        }
+        paint_attributes(&mut element, fill, stroke);
+        element.push_str("/>");
+        self.line(&element);
+    }
+
+    pub fn circle(&mut self, center: Point<Pixels>, radius: Pixels, fill: Hsla) {
+        let mut element = format!(
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\"",
+            pixels(center.x),
+            pixels(center.y),
+            pixels(radius)
+        );
+        paint_attributes(&mut element, Some(fill), None);
+        element.push_str("/>");
+        self.line(&element);
+    }
+
+    pub fn line_segment(&mut self, from: Point<Pixels>, to: Point<Pixels>, stroke: (Hsla, Pixels)) {
+        let mut path = SvgPath::new();
+        path.move_to(from);
+        path.line_to(to);
+        self.path(&path, None, Some(stroke));
+    }
+
+    pub fn path(&mut self, path: &SvgPath, fill: Option<Hsla>, stroke: Option<(Hsla, Pixels)>) {
+        if path.is_empty() {
+            return;
+        }
+        let mut element = format!("<path d=\"{}\"", path.data.trim_end());
+        paint_attributes(&mut element, fill, stroke);
+        if stroke.is_some() {
+            element.push_str(" stroke-linecap=\"round\" stroke-linejoin=\"round\"");
+        }
+        element.push_str("/>");
+        self.line(&element);
+    }
+
+    /// Text with its baseline starting at, centered on or ending at `origin`,
+    /// depending on the style's anchor.
+    pub fn text(&mut self, origin: Point<Pixels>, text: &str, style: &SvgTextStyle) {
+        let mut element = format!(
+            "<text x=\"{}\" y=\"{}\" font-size=\"{}\"",
+            pixels(origin.x),
+            pixels(origin.y),
+            pixels(style.size)
+        );
+        if let Some(family) = &style.font_family {
+            let _ = write!(element, " font-family=\"{}\"", escape(family));
+        }
+        if style.weight != FontWeight::NORMAL {
+            let _ = write!(element, " font-weight=\"{}\"", number(style.weight.0));
+        }
+        match style.anchor {
+            SvgTextAnchor::Start => {}
+            SvgTextAnchor::Middle => element.push_str(" text-anchor=\"middle\""),
+            SvgTextAnchor::End => element.push_str(" text-anchor=\"end\""),
+        }
+        paint_attributes(&mut element, Some(style.color), None);
+        let _ = write!(element, ">{}</text>", escape(text));
+        self.line(&element);
+    }
+
+    /// Start a `<g>`; everything up to the matching [`SvgDocument::end_group`] goes in
+    /// it. The label becomes its `id`, so design tools show it as a named layer.
+    pub fn begin_group(&mut self, label: Option<&str>) {
+        let element = match label {
+            Some(label) => format!("<g id=\"{}\">", escape(label)),
+            None => "<g>".to_string(),
+        };
+        self.line(&element);
+        self.depth += 1;
+    }
+
+    pub fn end_group(&mut self) {
+        if self.depth > 1 {
+            self.depth -= 1;
+            self.line("</g>");
+        }
+    }
+
+    /// The finished document, closing any groups left open.
+    pub fn finish(mut self) -> String {
+        while self.depth > 1 {
+            self.end_group();
+        }
+        let (width, height) = (pixels(self.size.width), pixels(self.size.height));
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+             viewBox=\"0 0 {w} {h}\" font-family=\"{font}\">\n{body}</svg>\n",
+            w = width,
+            h = height,
+            font = escape(&self.font_family),
+            body = self.body
+        )
+    }
+
+    fn line(&mut self, element: &str) {
+        for _ in 0..self.depth {
+            self.body.push_str("  ");
+        }
+        self.body.push_str(element);
+        self.body.push('\n');
+    }
+}
+
+fn paint_attributes(element: &mut String, fill: Option<Hsla>, stroke: Option<(Hsla, Pixels)>) {
+    match fill {
+        Some(fill) => {
+            let _ = write!(element, " fill=\"{}\"", hex(fill));
+            if fill.a < 1.0 {
+                let _ = write!(element, " fill-opacity=\"{}\"", number(fill.a));
+            }
+        }
+        None => element.push_str(" fill=\"none\""),
+    }
+    if let Some((color, width)) = stroke {
+        let _ = write!(
+            element,
+            " stroke=\"{}\" stroke-width=\"{}\"",
+            hex(color),
+            pixels(width)
+        );
+        if color.a < 1.0 {
+            let _ = write!(element, " stroke-opacity=\"{}\"", number(color.a));
+        }
+    }
+}
+
+fn hex(color: Hsla) -> String {
+    let rgba = Rgba::from(color);
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        channel(rgba.r),
+        channel(rgba.g),
+        channel(rgba.b)
+    )
+}
+
+fn pixels(value: Pixels) -> String {
+    number(value / px(1.0))
+}
+
+/// At most two decimals, without trailing zeros.
+fn number(value: f32) -> String {
+    let rounded = (value * 100.0).round() / 100.0;
+    let text = format!("{:.2}", rounded);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    match text {
+        "-0" => "0".to_string(),
+        text => text.to_string(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
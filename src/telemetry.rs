@@ -0,0 +1,233 @@
+//! Opt-in usage telemetry with pluggable sinks.
+//!
+//! Nothing is recorded until the user grants consent with [`set_consent`], and
+//! nothing leaves the process unless the app installs a [`TelemetrySink`]; the crate
+//! ships no network code. Events are buffered and handed to the sink in batches.
+//!
+//! Built-in components report a few events of their own: `command_palette.execute`
+//! (with the command id) and `error` (with a `source` and `message`) for failures such
+//! as panicking plugins. Events never carry typed text or document content.
+//!
+//! ```rust,ignore
+//! telemetry::set_sink(|events: &[TelemetryEvent]| my_backend.enqueue(events), cx);
+//! telemetry::set_consent(stored_consent, cx);
+//!
+//! if telemetry::consent(cx) == TelemetryConsent::Unknown {
+//!     let dialog = telemetry::consent_dialog("Acme", |consent, _, _| save(consent), cx);
+//!     // render `dialog` until `on_decided` runs
+//! }
+//!
+//! telemetry::track("export.finished", [("format", "png")], cx);
+//! ```
+
+use crate::components::button::{Button, ButtonVariant};
+use crate::overlays::dialog::Dialog;
+use crate::theme::use_theme;
+use gpui::*;
+use std::rc::Rc;
+use std::time::{Duration, SystemTime};
+
+const DEFAULT_BATCH_SIZE: usize = 20;
+const FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TelemetryConsent {
+    /// The user hasn't decided yet. Events are dropped.
+    #[default]
+    Unknown,
+    Granted,
+    Denied,
+}
+
+#[derive(Debug, Clone)]
+pub struct TelemetryEvent {
+    pub name: SharedString,
+    pub properties: Vec<(SharedString, SharedString)>,
+    pub timestamp: SystemTime,
+}
+
+/// Receives batches of events. Implement this to forward events to a backend.
+pub trait TelemetrySink: 'static {
+    fn send(&self, events: &[TelemetryEvent]);
+}
+
+impl<F: Fn(&[TelemetryEvent]) + 'static> TelemetrySink for F {
+    fn send(&self, events: &[TelemetryEvent]) {
+        self(events)
+    }
+}
+
+struct TelemetryState {
+    consent: TelemetryConsent,
+    sink: Option<Rc<dyn TelemetrySink>>,
+    buffer: Vec<TelemetryEvent>,
+    batch_size: usize,
+    _flush_task: Option<Task<()>>,
+}
+
+impl Default for TelemetryState {
+    fn default() -> Self {
+        Self {
+            consent: TelemetryConsent::Unknown,
+            sink: None,
+            buffer: Vec::new(),
+            batch_size: DEFAULT_BATCH_SIZE,
+            _flush_task: None,
+        }
+    }
+}
+
+impl Global for TelemetryState {}
+
+/// Install the sink events are delivered to, replacing any previous one. Buffered
+/// events are also flushed every 30 seconds.
+pub fn set_sink(sink: impl TelemetrySink, cx: &mut App) {
+    let flush_task = cx.spawn(async move |cx| loop {
+        smol::Timer::after(FLUSH_INTERVAL).await;
+        if cx.update(flush).is_err() {
+            break;
+        }
+    });
+    let state = cx.default_global::<TelemetryState>();
+    state.sink = Some(Rc::new(sink));
+    state._flush_task = Some(flush_task);
+}
+
+/// Number of buffered events that triggers a flush.
+pub fn set_batch_size(batch_size: usize, cx: &mut App) {
+    cx.default_global::<TelemetryState>().batch_size = batch_size.max(1);
+}
+
+/// Record the user's decision. Denying consent discards any buffered events.
+pub fn set_consent(consent: TelemetryConsent, cx: &mut App) {
+    let state = cx.default_global::<TelemetryState>();
+    state.consent = consent;
+    if consent != TelemetryConsent::Granted {
+        state.buffer.clear();
+    }
+}
+
+pub fn consent(cx: &App) -> TelemetryConsent {
+    cx.try_global::<TelemetryState>()
+        .map(|state| state.consent)
+        .unwrap_or_default()
+}
+
+/// Whether events are currently being collected.
+pub fn is_enabled(cx: &App) -> bool {
+    cx.try_global::<TelemetryState>()
+        .is_some_and(|state| state.consent == TelemetryConsent::Granted && state.sink.is_some())
+}
+
+/// Record an event. A no-op without consent or a sink.
+pub fn track<K, V>(
+    name: impl Into<SharedString>,
+    properties: impl IntoIterator<Item = (K, V)>,
+    cx: &mut App,
+) where
+    K: Into<SharedString>,
+    V: Into<SharedString>,
+{
+    if !is_enabled(cx) {
+        return;
+    }
+    let event = TelemetryEvent {
+        name: name.into(),
+        properties: properties
+            .into_iter()
+            .map(|(key, value)| (key.into(), value.into()))
+            .collect(),
+        timestamp: SystemTime::now(),
+    };
+    let state = cx.default_global::<TelemetryState>();
+    state.buffer.push(event);
+    if state.buffer.len() >= state.batch_size {
+        flush(cx);
+    }
+}
+
+/// Record an `error` event from a component or subsystem.
+pub fn track_error(source: &str, message: &str, cx: &mut App) {
+    track(
+        "error",
+        [
+            ("source", source.to_string()),
+            ("message", message.to_string()),
+        ],
+        cx,
+    );
+}
+
+/// Deliver buffered events to the sink now.
+pub fn flush(cx: &mut App) {
+    let Some(state) = cx.try_global::<TelemetryState>() else {
+        return;
+    };
+    if state.buffer.is_empty() {
+        return;
+    }
+    let state = cx.global_mut::<TelemetryState>();
+    let events = std::mem::take(&mut state.buffer);
+    if state.consent != TelemetryConsent::Granted {
+        return;
+    }
+    if let Some(sink) = state.sink.clone() {
+        sink.send(&events);
+    }
+}
+
+/// Build an opt-in dialog asking whether `app_name` may collect anonymous usage data.
+/// Choosing a button records the decision with [`set_consent`] and calls `on_decided`;
+/// dismissing the dialog leaves consent [`TelemetryConsent::Unknown`].
+pub fn consent_dialog(
+    app_name: impl Into<SharedString>,
+    on_decided: impl Fn(TelemetryConsent, &mut Window, &mut App) + 'static,
+    cx: &mut App,
+) -> Entity<Dialog> {
+    let app_name = app_name.into();
+    let on_decided = Rc::new(on_decided);
+    let theme = use_theme();
+
+    let decide = |consent: TelemetryConsent| {
+        let on_decided = on_decided.clone();
+        move |_: &ClickEvent, window: &mut Window, cx: &mut App| {
+            set_consent(consent, cx);
+            on_decided(consent, window, cx);
+        }
+    };
+
+    let footer = div()
+        .flex()
+        .justify_end()
+        .gap(px(8.0))
+        .child(
+            Button::new("telemetry-deny", "Don't Allow")
+                .variant(ButtonVariant::Outline)
+                .on_click(decide(TelemetryConsent::Denied)),
+        )
+        .child(
+            Button::new("telemetry-allow", "Allow")
+                .variant(ButtonVariant::Default)
+                .on_click(decide(TelemetryConsent::Granted)),
+        );
+
+    let body = div()
+        .flex()
+        .flex_col()
+        .gap(px(6.0))
+        .text_size(px(13.0))
+        .text_color(theme.tokens.muted_foreground)
+        .child("• Which features and commands are used")
+        .child("• Errors, such as components that failed")
+        .child("Never collected: your files, typed text, or personal information. You can change this later in settings.");
+
+    let on_close = on_decided.clone();
+    cx.new(|cx| {
+        Dialog::new(cx)
+            .title(format!("Help improve {}?", app_name))
+            .description("Share anonymous usage data with the developers. This includes:")
+            .child(body)
+            .footer(footer)
+            .on_close(move |window, cx| on_close(TelemetryConsent::Unknown, window, cx))
+    })
+}
@@ -70,6 +70,13 @@ pub struct ThemeTokens {
     pub z_modal: u32,
     pub z_popover: u32,
     pub z_tooltip: u32,
+
+    /// Editor selection fill, derived from `primary`.
+    pub editor_selection: Hsla,
+    /// Editor current-line fill, derived from `foreground`.
+    pub editor_line_highlight: Hsla,
+    /// Rules above and below the editor's current line in the border highlight mode.
+    pub editor_line_highlight_border: Hsla,
 }
 
 impl ThemeTokens {
@@ -170,6 +177,9 @@ impl ThemeTokens {
         self.z_modal = zm;
         self.z_popover = zp;
         self.z_tooltip = zt;
+        self.editor_selection = self.primary.opacity(0.25);
+        self.editor_line_highlight = self.foreground.opacity(0.06);
+        self.editor_line_highlight_border = self.foreground.opacity(0.15);
         self
     }
 
@@ -267,6 +277,9 @@ impl ThemeTokens {
             z_modal: 0,
             z_popover: 0,
             z_tooltip: 0,
+            editor_selection: Hsla::transparent_black(),
+            editor_line_highlight: Hsla::transparent_black(),
+            editor_line_highlight_border: Hsla::transparent_black(),
         }
         .apply_standard()
     }
@@ -365,6 +378,9 @@ impl ThemeTokens {
             z_modal: 0,
             z_popover: 0,
             z_tooltip: 0,
+            editor_selection: Hsla::transparent_black(),
+            editor_line_highlight: Hsla::transparent_black(),
+            editor_line_highlight_border: Hsla::transparent_black(),
         }
         .apply_standard()
     }
@@ -463,6 +479,9 @@ impl ThemeTokens {
             z_modal: 0,
             z_popover: 0,
             z_tooltip: 0,
+            editor_selection: Hsla::transparent_black(),
+            editor_line_highlight: Hsla::transparent_black(),
+            editor_line_highlight_border: Hsla::transparent_black(),
         }
         .apply_standard()
     }
@@ -561,6 +580,9 @@ impl ThemeTokens {
             z_modal: 0,
             z_popover: 0,
             z_tooltip: 0,
+            editor_selection: Hsla::transparent_black(),
+            editor_line_highlight: Hsla::transparent_black(),
+            editor_line_highlight_border: Hsla::transparent_black(),
         }
         .apply_standard()
     }
@@ -659,6 +681,9 @@ impl ThemeTokens {
             z_modal: 0,
             z_popover: 0,
             z_tooltip: 0,
+            editor_selection: Hsla::transparent_black(),
+            editor_line_highlight: Hsla::transparent_black(),
+            editor_line_highlight_border: Hsla::transparent_black(),
         }
         .apply_standard()
     }
@@ -757,6 +782,9 @@ impl ThemeTokens {
             z_modal: 0,
             z_popover: 0,
             z_tooltip: 0,
+            editor_selection: Hsla::transparent_black(),
+            editor_line_highlight: Hsla::transparent_black(),
+            editor_line_highlight_border: Hsla::transparent_black(),
         }
         .apply_standard()
     }
@@ -855,6 +883,9 @@ impl ThemeTokens {
             z_modal: 0,
             z_popover: 0,
             z_tooltip: 0,
+            editor_selection: Hsla::transparent_black(),
+            editor_line_highlight: Hsla::transparent_black(),
+            editor_line_highlight_border: Hsla::transparent_black(),
         }
         .apply_standard()
     }
@@ -953,6 +984,9 @@ impl ThemeTokens {
             z_modal: 0,
             z_popover: 0,
             z_tooltip: 0,
+            editor_selection: Hsla::transparent_black(),
+            editor_line_highlight: Hsla::transparent_black(),
+            editor_line_highlight_border: Hsla::transparent_black(),
         }
         .apply_standard()
     }
@@ -1051,6 +1085,9 @@ impl ThemeTokens {
             z_modal: 0,
             z_popover: 0,
             z_tooltip: 0,
+            editor_selection: Hsla::transparent_black(),
+            editor_line_highlight: Hsla::transparent_black(),
+            editor_line_highlight_border: Hsla::transparent_black(),
         }
         .apply_standard()
     }
@@ -1149,6 +1186,9 @@ impl ThemeTokens {
             z_modal: 0,
             z_popover: 0,
             z_tooltip: 0,
+            editor_selection: Hsla::transparent_black(),
+            editor_line_highlight: Hsla::transparent_black(),
+            editor_line_highlight_border: Hsla::transparent_black(),
         }
         .apply_standard()
     }
@@ -1247,6 +1287,9 @@ impl ThemeTokens {
             z_modal: 0,
             z_popover: 0,
             z_tooltip: 0,
+            editor_selection: Hsla::transparent_black(),
+            editor_line_highlight: Hsla::transparent_black(),
+            editor_line_highlight_border: Hsla::transparent_black(),
         }
         .apply_standard()
     }
@@ -1345,6 +1388,9 @@ impl ThemeTokens {
             z_modal: 0,
             z_popover: 0,
             z_tooltip: 0,
+            editor_selection: Hsla::transparent_black(),
+            editor_line_highlight: Hsla::transparent_black(),
+            editor_line_highlight_border: Hsla::transparent_black(),
         }
         .apply_standard()
     }
@@ -1443,6 +1489,9 @@ impl ThemeTokens {
             z_modal: 0,
             z_popover: 0,
             z_tooltip: 0,
+            editor_selection: Hsla::transparent_black(),
+            editor_line_highlight: Hsla::transparent_black(),
+            editor_line_highlight_border: Hsla::transparent_black(),
         }
         .apply_standard()
     }
@@ -1541,6 +1590,9 @@ impl ThemeTokens {
             z_modal: 0,
             z_popover: 0,
             z_tooltip: 0,
+            editor_selection: Hsla::transparent_black(),
+            editor_line_highlight: Hsla::transparent_black(),
+            editor_line_highlight_border: Hsla::transparent_black(),
         }
         .apply_standard()
     }
@@ -1639,6 +1691,9 @@ impl ThemeTokens {
             z_modal: 0,
             z_popover: 0,
             z_tooltip: 0,
+            editor_selection: Hsla::transparent_black(),
+            editor_line_highlight: Hsla::transparent_black(),
+            editor_line_highlight_border: Hsla::transparent_black(),
         }
         .apply_standard()
     }
@@ -1737,6 +1792,9 @@ impl ThemeTokens {
             z_modal: 0,
             z_popover: 0,
             z_tooltip: 0,
+            editor_selection: Hsla::transparent_black(),
+            editor_line_highlight: Hsla::transparent_black(),
+            editor_line_highlight_border: Hsla::transparent_black(),
         }
         .apply_standard()
     }
@@ -1835,6 +1893,9 @@ impl ThemeTokens {
             z_modal: 0,
             z_popover: 0,
             z_tooltip: 0,
+            editor_selection: Hsla::transparent_black(),
+            editor_line_highlight: Hsla::transparent_black(),
+            editor_line_highlight_border: Hsla::transparent_black(),
         }
         .apply_standard()
     }
@@ -1933,6 +1994,9 @@ impl ThemeTokens {
             z_modal: 0,
             z_popover: 0,
             z_tooltip: 0,
+            editor_selection: Hsla::transparent_black(),
+            editor_line_highlight: Hsla::transparent_black(),
+            editor_line_highlight_border: Hsla::transparent_black(),
         }
         .apply_standard()
     }
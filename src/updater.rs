@@ -0,0 +1,1429 @@
+//! Self-update for desktop apps: check a GitHub release or a JSON feed, download and
+//! verify the build for this platform, install it, and relaunch.
+//!
+//! An [`Updater`] entity runs the flow and reports its [`UpdateStatus`]. The shipped UI
+//! follows it: [`UpdateNotification`] is the "update available" card with download
+//! progress and a restart button, and [`release_notes_dialog`] shows the release notes
+//! as Markdown.
+//!
+//! ```rust,ignore
+//! let updater = cx.new(|cx| {
+//!     Updater::new(
+//!         env!("CARGO_PKG_VERSION"),
+//!         UpdateSource::github("acme", "notes"),
+//!         cx,
+//!     )
+//! });
+//! updater.update(cx, |updater, cx| {
+//!     updater.check_periodically(Duration::from_secs(6 * 60 * 60), cx)
+//! });
+//!
+//! // in the root view's render
+//! .child(UpdateNotification::new(self.updater.clone()).on_view_notes(cx.listener(
+//!     |this, _, window, cx| this.show_release_notes(window, cx),
+//! )))
+//! ```
+//!
+//! A feed is a JSON document with the latest version and one entry per platform, keyed
+//! `{os}-{arch}` (`darwin-aarch64`, `linux-x86_64`, `windows-x86_64`, ...):
+//!
+//! ```json
+//! {
+//!   "version": "1.4.0",
+//!   "notes": "## Fixes\n- ...",
+//!   "pub_date": "2026-03-01",
+//!   "platforms": {
+//!     "darwin-aarch64": { "url": "https://.../Notes-1.4.0-arm64.dmg", "sha256": "..." }
+//!   }
+//! }
+//! ```
+//!
+//! For GitHub releases the asset is picked by OS and architecture in its file name, and
+//! verified against a `<asset>.sha256` or `SHA256SUMS` asset. Releases and feed entries
+//! without a checksum are refused unless the updater was built with
+//! [`Updater::allow_unverified`], and the notification says so when it installs one.
+//!
+//! Installing depends on the asset: `.dmg` and `.zip` builds replace the running `.app`
+//! bundle, AppImages and bare executables replace the running binary, archives are
+//! unpacked and searched for either, and `.msi` or setup `.exe` installers are launched
+//! on relaunch.
+
+use crate::activity;
+use crate::components::button::{Button, ButtonSize, ButtonVariant};
+use crate::components::icon::Icon;
+use crate::components::progress::ProgressBar;
+use crate::components::text::caption;
+use crate::display::markdown::Markdown;
+use crate::overlays::dialog::{Dialog, DialogSize};
+use crate::theme::use_theme;
+use futures::AsyncReadExt as _;
+use gpui::http_client::{AsyncBody, HttpClient, Request, Response};
+use gpui::{prelude::FluentBuilder as _, *};
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::Instrument as _;
+
+const MAX_REDIRECTS: usize = 5;
+const CHECKSUM_SUFFIXES: &[&str] = &[
+    ".sha256",
+    ".sha512",
+    ".sig",
+    ".asc",
+    ".minisig",
+    ".txt",
+    ".json",
+    ".blockmap",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateSource {
+    /// The latest non-draft, non-prerelease GitHub release.
+    GitHub {
+        owner: SharedString,
+        repo: SharedString,
+    },
+    /// URL of a JSON feed, see the module docs for the format.
+    Feed(SharedString),
+}
+
+impl UpdateSource {
+    pub fn github(owner: impl Into<SharedString>, repo: impl Into<SharedString>) -> Self {
+        Self::GitHub {
+            owner: owner.into(),
+            repo: repo.into(),
+        }
+    }
+
+    pub fn feed(url: impl Into<SharedString>) -> Self {
+        Self::Feed(url.into())
+    }
+}
+
+/// A release newer than the running version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateInfo {
+    pub version: SharedString,
+    /// Release notes, usually Markdown.
+    pub notes: SharedString,
+    pub published_at: Option<SharedString>,
+    pub asset_name: SharedString,
+    pub download_url: SharedString,
+    pub size: Option<u64>,
+    /// Expected SHA-256 of the download, lowercase hex.
+    pub sha256: Option<SharedString>,
+}
+
+impl UpdateInfo {
+    /// Whether the download can be checked against a published checksum.
+    pub fn is_verifiable(&self) -> bool {
+        self.sha256.is_some()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateError {
+    Network(SharedString),
+    /// The release or feed couldn't be understood.
+    InvalidResponse(SharedString),
+    /// The release has no build for this OS and architecture.
+    NoCompatibleAsset,
+    /// The download didn't match its published checksum, or none was published and
+    /// [`Updater::allow_unverified`] isn't set.
+    Verification(SharedString),
+    Install(SharedString),
+}
+
+impl fmt::Display for UpdateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UpdateError::Network(message) => write!(f, "network error: {}", message),
+            UpdateError::InvalidResponse(message) => write!(f, "invalid response: {}", message),
+            UpdateError::NoCompatibleAsset => write!(f, "no download for this platform"),
+            UpdateError::Verification(message) => write!(f, "verification failed: {}", message),
+            UpdateError::Install(message) => write!(f, "install failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for UpdateError {}
+
+impl From<std::io::Error> for UpdateError {
+    fn from(err: std::io::Error) -> Self {
+        UpdateError::Install(err.to_string().into())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateStatus {
+    Idle,
+    Checking,
+    UpToDate,
+    Available(UpdateInfo),
+    Downloading {
+        info: UpdateInfo,
+        downloaded: u64,
+        total: Option<u64>,
+    },
+    Installing(UpdateInfo),
+    /// Installed; takes effect once the app is relaunched.
+    ReadyToRelaunch(UpdateInfo),
+    Failed(UpdateError),
+}
+
+impl UpdateStatus {
+    pub fn info(&self) -> Option<&UpdateInfo> {
+        match self {
+            UpdateStatus::Available(info)
+            | UpdateStatus::Downloading { info, .. }
+            | UpdateStatus::Installing(info)
+            | UpdateStatus::ReadyToRelaunch(info) => Some(info),
+            _ => None,
+        }
+    }
+
+    /// Download progress from 0.0 to 1.0, when the size is known.
+    pub fn progress(&self) -> Option<f32> {
+        match self {
+            UpdateStatus::Downloading {
+                downloaded,
+                total: Some(total),
+                ..
+            } if *total > 0 => Some((*downloaded as f64 / *total as f64).min(1.0) as f32),
+            _ => None,
+        }
+    }
+
+    fn is_busy(&self) -> bool {
+        matches!(
+            self,
+            UpdateStatus::Checking | UpdateStatus::Downloading { .. } | UpdateStatus::Installing(_)
+        )
+    }
+}
+
+type AssetFilter = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+pub struct Updater {
+    current_version: SharedString,
+    source: UpdateSource,
+    asset_filter: Option<AssetFilter>,
+    require_checksum: bool,
+    status: UpdateStatus,
+    dismissed: bool,
+    pending_installer: Option<PathBuf>,
+    _task: Option<Task<()>>,
+    _periodic_task: Option<Task<()>>,
+}
+
+impl EventEmitter<UpdateStatus> for Updater {}
+
+impl Updater {
+    /// `current_version` is usually `env!("CARGO_PKG_VERSION")`.
+    pub fn new(
+        current_version: impl Into<SharedString>,
+        source: UpdateSource,
+        _cx: &mut Context<Self>,
+    ) -> Self {
+        remove_replaced_files();
+        Self {
+            current_version: current_version.into(),
+            source,
+            asset_filter: None,
+            require_checksum: true,
+            status: UpdateStatus::Idle,
+            dismissed: false,
+            pending_installer: None,
+            _task: None,
+            _periodic_task: None,
+        }
+    }
+
+    /// Pick the release asset whose file name matches `filter`, instead of guessing from
+    /// the OS and architecture. Only used for GitHub releases.
+    pub fn asset_filter(mut self, filter: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        self.asset_filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Install downloads that have no published checksum instead of failing with
+    /// [`UpdateError::Verification`]. [`UpdateNotification`] marks such updates as
+    /// unverified.
+    pub fn allow_unverified(mut self) -> Self {
+        self.require_checksum = false;
+        self
+    }
+
+    pub fn status(&self) -> &UpdateStatus {
+        &self.status
+    }
+
+    pub fn current_version(&self) -> &SharedString {
+        &self.current_version
+    }
+
+    /// Whether the user hid the notification for the current update.
+    pub fn is_dismissed(&self) -> bool {
+        self.dismissed
+    }
+
+    /// Check for an update, reporting failures through [`UpdateStatus::Failed`].
+    pub fn check(&mut self, cx: &mut Context<Self>) {
+        self.check_internal(false, cx);
+    }
+
+    /// Check now and then every `interval`, holding off while the app is in the
+    /// background or the user is idle. Failed background checks are logged and leave
+    /// the status idle, so an offline machine isn't nagged.
+    pub fn check_periodically(&mut self, interval: Duration, cx: &mut Context<Self>) {
+        self._periodic_task = Some(cx.spawn(async move |this, cx| loop {
+            let checked = this.update(cx, |this, cx| this.check_internal(true, cx));
+            if checked.is_err() {
+                break;
+            }
+            smol::Timer::after(interval).await;
+            activity::wait_until_pollable(cx).await;
+        }));
+    }
+
+    fn check_internal(&mut self, silent: bool, cx: &mut Context<Self>) {
+        if self.status.is_busy() || matches!(self.status, UpdateStatus::ReadyToRelaunch(_)) {
+            return;
+        }
+        if !silent {
+            self.set_status(UpdateStatus::Checking, cx);
+        }
+
+        let client = cx.http_client();
+        let source = self.source.clone();
+        let filter = self.asset_filter.clone();
+        let fetch = cx.background_executor().spawn(async move {
+            fetch_latest(client.as_ref(), &source, filter)
+                .instrument(tracing::debug_span!("updater.check"))
+                .await
+        });
+
+        self._task = Some(cx.spawn(async move |this, cx| {
+            let result = fetch.await;
+            let _ = this.update(cx, |this, cx| {
+                let status = match result {
+                    Ok(info) if is_newer(&info.version, &this.current_version) => {
+                        if this.status.info() != Some(&info) {
+                            this.dismissed = false;
+                        }
+                        UpdateStatus::Available(info)
+                    }
+                    Ok(_) => UpdateStatus::UpToDate,
+                    Err(err) if silent => {
+                        tracing::warn!(%err, "update check failed");
+                        UpdateStatus::Idle
+                    }
+                    Err(err) => UpdateStatus::Failed(err),
+                };
+                this.set_status(status, cx);
+            });
+        }));
+    }
+
+    /// Download, verify and install the available update. The status ends in
+    /// [`UpdateStatus::ReadyToRelaunch`]; call [`Updater::relaunch`] to finish.
+    pub fn install(&mut self, cx: &mut Context<Self>) {
+        let UpdateStatus::Available(info) = self.status.clone() else {
+            return;
+        };
+        self.dismissed = false;
+        self.set_status(
+            UpdateStatus::Downloading {
+                info: info.clone(),
+                downloaded: 0,
+                total: info.size,
+            },
+            cx,
+        );
+
+        let client = cx.http_client();
+        let require_checksum = self.require_checksum;
+        let (progress_tx, progress_rx) = smol::channel::bounded::<(u64, Option<u64>)>(1);
+        let download = cx.background_executor().spawn({
+            let info = info.clone();
+            async move {
+                let path =
+                    download_update(client.as_ref(), &info, require_checksum, progress_tx).await?;
+                Ok::<_, UpdateError>(path)
+            }
+        });
+
+        self._task = Some(cx.spawn(async move |this, cx| {
+            while let Ok((downloaded, total)) = progress_rx.recv().await {
+                let updated = this.update(cx, |this, cx| {
+                    if let UpdateStatus::Downloading { info, .. } = &this.status {
+                        let info = info.clone();
+                        this.set_status(
+                            UpdateStatus::Downloading {
+                                info,
+                                downloaded,
+                                total,
+                            },
+                            cx,
+                        );
+                    }
+                });
+                if updated.is_err() {
+                    return;
+                }
+            }
+
+            let path = match download.await {
+                Ok(path) => path,
+                Err(err) => {
+                    let _ = this.update(cx, |this, cx| {
+                        this.set_status(UpdateStatus::Failed(err), cx)
+                    });
+                    return;
+                }
+            };
+
+            let installing = this.update(cx, |this, cx| {
+                this.set_status(UpdateStatus::Installing(info.clone()), cx);
+                cx.background_executor().spawn({
+                    let asset_name = info.asset_name.clone();
+                    async move { apply_update(&path, &asset_name) }
+                })
+            });
+            let Ok(installing) = installing else {
+                return;
+            };
+            let result = installing.await;
+            let _ = this.update(cx, |this, cx| match result {
+                Ok(installer) => {
+                    this.pending_installer = installer;
+                    this.set_status(UpdateStatus::ReadyToRelaunch(info), cx);
+                }
+                Err(err) => this.set_status(UpdateStatus::Failed(err), cx),
+            });
+        }));
+    }
+
+    /// Quit and start the updated app, or run the downloaded installer.
+    pub fn relaunch(&mut self, cx: &mut Context<Self>) {
+        if let Some(installer) = self.pending_installer.take() {
+            let spawned = if has_extension(&installer, "msi") {
+                std::process::Command::new("msiexec")
+                    .arg("/i")
+                    .arg(&installer)
+                    .arg("/passive")
+                    .spawn()
+            } else {
+                std::process::Command::new(&installer).spawn()
+            };
+            match spawned {
+                Ok(_) => cx.quit(),
+                Err(err) => self.set_status(
+                    UpdateStatus::Failed(UpdateError::Install(err.to_string().into())),
+                    cx,
+                ),
+            }
+            return;
+        }
+        if let Some(app_image) = std::env::var_os("APPIMAGE") {
+            let _ = std::process::Command::new(app_image)
+                .args(std::env::args_os().skip(1))
+                .spawn();
+            cx.quit();
+            return;
+        }
+        crate::crash_reporter::restart_app(cx);
+    }
+
+    /// Hide the notification until a newer update is found. A failure is cleared.
+    pub fn dismiss(&mut self, cx: &mut Context<Self>) {
+        self.dismissed = true;
+        if matches!(
+            self.status,
+            UpdateStatus::Failed(_) | UpdateStatus::UpToDate
+        ) {
+            self.status = UpdateStatus::Idle;
+        }
+        cx.notify();
+    }
+
+    fn set_status(&mut self, status: UpdateStatus, cx: &mut Context<Self>) {
+        self.status = status;
+        cx.emit(self.status.clone());
+        cx.notify();
+    }
+}
+
+/// Compare dotted versions, ignoring a leading `v`. A pre-release (`1.2.0-beta.1`) sorts
+/// before its release.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    fn split(version: &str) -> (Vec<u64>, Option<&str>) {
+        let version = version.trim().trim_start_matches(['v', 'V']);
+        let version = version.split('+').next().unwrap_or(version);
+        let (core, pre) = match version.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (version, None),
+        };
+        let numbers = core
+            .split('.')
+            .map(|part| {
+                part.chars()
+                    .take_while(char::is_ascii_digit)
+                    .collect::<String>()
+                    .parse()
+                    .unwrap_or(0)
+            })
+            .collect();
+        (numbers, pre)
+    }
+
+    let (a_numbers, a_pre) = split(a);
+    let (b_numbers, b_pre) = split(b);
+    let len = a_numbers.len().max(b_numbers.len());
+    for index in 0..len {
+        let a_part = a_numbers.get(index).copied().unwrap_or(0);
+        let b_part = b_numbers.get(index).copied().unwrap_or(0);
+        match a_part.cmp(&b_part) {
+            Ordering::Equal => {}
+            ordering => return ordering,
+        }
+    }
+    match (a_pre, b_pre) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a_pre), Some(b_pre)) => compare_prerelease(a_pre, b_pre),
+    }
+}
+
+fn compare_prerelease(a: &str, b: &str) -> Ordering {
+    let mut a_parts = a.split('.');
+    let mut b_parts = b.split('.');
+    loop {
+        match (a_parts.next(), b_parts.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a_part), Some(b_part)) => {
+                let ordering = match (a_part.parse::<u64>(), b_part.parse::<u64>()) {
+                    (Ok(a_number), Ok(b_number)) => a_number.cmp(&b_number),
+                    _ => a_part.cmp(b_part),
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+        }
+    }
+}
+
+fn is_newer(candidate: &str, current: &str) -> bool {
+    compare_versions(candidate, current) == Ordering::Greater
+}
+
+fn platform_key() -> String {
+    let os = match std::env::consts::OS {
+        "macos" => "darwin",
+        os => os,
+    };
+    format!("{}-{}", os, std::env::consts::ARCH)
+}
+
+fn os_tokens(os: &str) -> &'static [&'static str] {
+    match os {
+        "macos" => &["macos", "darwin", "mac", "osx", ".dmg", ".app."],
+        "windows" => &["windows", "win64", "win32", "-win", "_win", ".msi", ".exe"],
+        "linux" => &["linux", ".appimage", ".deb", ".rpm"],
+        _ => &[],
+    }
+}
+
+fn arch_tokens(arch: &str) -> &'static [&'static str] {
+    match arch {
+        "x86_64" => &["x86_64", "x86-64", "x64", "amd64"],
+        "aarch64" => &["aarch64", "arm64"],
+        _ => &[],
+    }
+}
+
+/// Index of the asset that best fits `os` and `arch`, judged by file name.
+fn select_asset(names: &[&str], os: &str, arch: &str) -> Option<usize> {
+    let other_arches: Vec<&str> = ["x86_64", "aarch64"]
+        .into_iter()
+        .filter(|other| *other != arch)
+        .flat_map(arch_tokens)
+        .copied()
+        .collect();
+
+    names
+        .iter()
+        .enumerate()
+        .filter_map(|(index, name)| {
+            let name = name.to_ascii_lowercase();
+            if CHECKSUM_SUFFIXES
+                .iter()
+                .any(|suffix| name.ends_with(suffix))
+                || name.contains("sha256sums")
+            {
+                return None;
+            }
+            // `.deb` and `.rpm` need a package manager; prefer self-contained builds.
+            if name.ends_with(".deb") || name.ends_with(".rpm") {
+                return None;
+            }
+            if !os_tokens(os).iter().any(|token| name.contains(token)) {
+                return None;
+            }
+            let matches_arch = arch_tokens(arch).iter().any(|token| name.contains(token));
+            let other_arch = other_arches.iter().any(|token| name.contains(token));
+            let score = if matches_arch {
+                3
+            } else if name.contains("universal") {
+                2
+            } else if other_arch {
+                return None;
+            } else {
+                1
+            };
+            Some((score, index))
+        })
+        .max_by_key(|(score, index)| (*score, std::cmp::Reverse(*index)))
+        .map(|(_, index)| index)
+}
+
+/// Find the SHA-256 for `asset_name` in a checksum file: either a bare digest or
+/// `sha256sum` output with one `<digest>  <file>` line per asset.
+fn parse_checksum(text: &str, asset_name: &str) -> Option<String> {
+    let is_digest = |token: &str| token.len() == 64 && token.chars().all(|c| c.is_ascii_hexdigit());
+    for line in text.lines() {
+        let mut tokens = line.split_whitespace();
+        let Some(digest) = tokens.next().filter(|token| is_digest(token)) else {
+            continue;
+        };
+        match tokens.next() {
+            None => return Some(digest.to_ascii_lowercase()),
+            Some(file) if file.trim_start_matches('*') == asset_name => {
+                return Some(digest.to_ascii_lowercase())
+            }
+            Some(_) => {}
+        }
+    }
+    None
+}
+
+fn json_str(value: &serde_json::Value, key: &str) -> Option<SharedString> {
+    value
+        .get(key)
+        .and_then(serde_json::Value::as_str)
+        .map(|text| SharedString::from(text.to_string()))
+}
+
+fn parse_feed(json: &serde_json::Value, platform: &str) -> Result<UpdateInfo, UpdateError> {
+    let version = json_str(json, "version")
+        .ok_or_else(|| UpdateError::InvalidResponse("feed has no version".into()))?;
+    let entry = json
+        .get("platforms")
+        .and_then(|platforms| platforms.get(platform))
+        .unwrap_or(json);
+    let download_url = json_str(entry, "url").ok_or(UpdateError::NoCompatibleAsset)?;
+    let asset_name = download_url
+        .rsplit('/')
+        .next()
+        .and_then(|name| name.split('?').next())
+        .unwrap_or_default()
+        .to_string();
+    Ok(UpdateInfo {
+        version,
+        notes: json_str(json, "notes").unwrap_or_default(),
+        published_at: json_str(json, "pub_date"),
+        asset_name: asset_name.into(),
+        download_url,
+        size: entry.get("size").and_then(serde_json::Value::as_u64),
+        sha256: json_str(entry, "sha256").map(|digest| digest.to_ascii_lowercase().into()),
+    })
+}
+
+/// The release's update for this platform, plus the URL of its checksum file if any.
+fn parse_github_release(
+    json: &serde_json::Value,
+    filter: Option<&AssetFilter>,
+) -> Result<(UpdateInfo, Option<String>), UpdateError> {
+    let version = json_str(json, "tag_name")
+        .ok_or_else(|| UpdateError::InvalidResponse("release has no tag".into()))?;
+    let assets: Vec<(&str, &serde_json::Value)> = json
+        .get("assets")
+        .and_then(serde_json::Value::as_array)
+        .map(|assets| {
+            assets
+                .iter()
+                .filter_map(|asset| Some((asset.get("name")?.as_str()?, asset)))
+                .collect()
+        })
+        .unwrap_or_default();
+    let names: Vec<&str> = assets.iter().map(|(name, _)| *name).collect();
+
+    let index = match filter {
+        Some(filter) => names.iter().position(|name| filter(*name)),
+        None => select_asset(&names, std::env::consts::OS, std::env::consts::ARCH),
+    }
+    .ok_or(UpdateError::NoCompatibleAsset)?;
+    let (asset_name, asset) = assets[index];
+    let download_url = json_str(asset, "browser_download_url")
+        .ok_or_else(|| UpdateError::InvalidResponse("asset has no download URL".into()))?;
+
+    let checksum_names = [
+        format!("{}.sha256", asset_name),
+        "SHA256SUMS".to_string(),
+        "SHA256SUMS.txt".to_string(),
+        "checksums.txt".to_string(),
+    ];
+    let checksum_url = checksum_names.iter().find_map(|checksum_name| {
+        assets
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(checksum_name))
+            .and_then(|(_, asset)| json_str(asset, "browser_download_url"))
+            .map(|url| url.to_string())
+    });
+
+    let info = UpdateInfo {
+        version,
+        notes: json_str(json, "body").unwrap_or_default(),
+        published_at: json_str(json, "published_at"),
+        asset_name: asset_name.to_string().into(),
+        download_url,
+        size: asset.get("size").and_then(serde_json::Value::as_u64),
+        sha256: None,
+    };
+    Ok((info, checksum_url))
+}
+
+fn network_error(err: impl fmt::Display) -> UpdateError {
+    UpdateError::Network(err.to_string().into())
+}
+
+/// GET `url`, following redirects (release downloads are usually redirected to a CDN).
+async fn get(
+    client: &dyn HttpClient,
+    url: &str,
+    accept: &str,
+) -> Result<Response<AsyncBody>, UpdateError> {
+    let mut url = url.to_string();
+    for _ in 0..=MAX_REDIRECTS {
+        let request = Request::builder()
+            .uri(url.as_str())
+            .header("Accept", accept)
+            .body(AsyncBody::default())
+            .map_err(network_error)?;
+        let response = client.send(request).await.map_err(network_error)?;
+        let status = response.status();
+        if status.is_redirection() {
+            url = response
+                .headers()
+                .get("location")
+                .and_then(|location| location.to_str().ok())
+                .ok_or_else(|| network_error("redirect without a location"))?
+                .to_string();
+            continue;
+        }
+        if !status.is_success() {
+            return Err(network_error(format!("{} returned {}", url, status)));
+        }
+        return Ok(response);
+    }
+    Err(network_error("too many redirects"))
+}
+
+async fn get_text(client: &dyn HttpClient, url: &str, accept: &str) -> Result<String, UpdateError> {
+    let mut response = get(client, url, accept).await?;
+    let mut text = String::new();
+    response
+        .body_mut()
+        .read_to_string(&mut text)
+        .await
+        .map_err(network_error)?;
+    Ok(text)
+}
+
+async fn get_json(client: &dyn HttpClient, url: &str) -> Result<serde_json::Value, UpdateError> {
+    let text = get_text(client, url, "application/json").await?;
+    serde_json::from_str(&text).map_err(|err| UpdateError::InvalidResponse(err.to_string().into()))
+}
+
+async fn fetch_latest(
+    client: &dyn HttpClient,
+    source: &UpdateSource,
+    filter: Option<AssetFilter>,
+) -> Result<UpdateInfo, UpdateError> {
+    match source {
+        UpdateSource::Feed(url) => parse_feed(&get_json(client, url).await?, &platform_key()),
+        UpdateSource::GitHub { owner, repo } => {
+            let url = format!(
+                "https://api.github.com/repos/{}/{}/releases/latest",
+                owner, repo
+            );
+            let release = get_json(client, &url).await?;
+            let (mut info, checksum_url) = parse_github_release(&release, filter.as_ref())?;
+            if let Some(checksum_url) = checksum_url {
+                let checksums = get_text(client, &checksum_url, "text/plain").await?;
+                info.sha256 = parse_checksum(&checksums, &info.asset_name).map(Into::into);
+            }
+            Ok(info)
+        }
+    }
+}
+
+fn updates_dir() -> PathBuf {
+    let exe_name = std::env::current_exe()
+        .ok()
+        .and_then(|exe| {
+            exe.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| "app".to_string());
+    std::env::temp_dir().join(format!("{}-update", exe_name))
+}
+
+async fn download_update(
+    client: &dyn HttpClient,
+    info: &UpdateInfo,
+    require_checksum: bool,
+    progress: smol::channel::Sender<(u64, Option<u64>)>,
+) -> Result<PathBuf, UpdateError> {
+    if require_checksum && info.sha256.is_none() {
+        return Err(UpdateError::Verification(
+            "the release has no published checksum".into(),
+        ));
+    }
+    let dir = updates_dir();
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(info.asset_name.as_ref());
+
+    let mut response = get(client, &info.download_url, "application/octet-stream").await?;
+    let total = response
+        .headers()
+        .get("content-length")
+        .and_then(|length| length.to_str().ok()?.parse().ok())
+        .or(info.size);
+    let mut file = std::fs::File::create(&path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut downloaded = 0u64;
+    loop {
+        let read = response
+            .body_mut()
+            .read(&mut buffer)
+            .await
+            .map_err(network_error)?;
+        if read == 0 {
+            break;
+        }
+        file.write_all(&buffer[..read])?;
+        hasher.update(&buffer[..read]);
+        downloaded += read as u64;
+        let _ = progress.try_send((downloaded, total));
+    }
+    file.flush()?;
+
+    if let Some(expected) = &info.sha256 {
+        let actual: String = hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        if actual != expected.as_ref() {
+            let _ = std::fs::remove_file(&path);
+            return Err(UpdateError::Verification(
+                format!("expected SHA-256 {}, got {}", expected, actual).into(),
+            ));
+        }
+    }
+    Ok(path)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AssetKind {
+    Installer,
+    DiskImage,
+    Archive,
+    Binary,
+}
+
+fn asset_kind(asset_name: &str) -> AssetKind {
+    let name = asset_name.to_ascii_lowercase();
+    if name.ends_with(".msi")
+        || (name.ends_with(".exe") && (name.contains("setup") || name.contains("install")))
+    {
+        AssetKind::Installer
+    } else if name.ends_with(".dmg") {
+        AssetKind::DiskImage
+    } else if [".zip", ".tar.gz", ".tgz", ".tar.xz", ".tar.bz2", ".tar"]
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+    {
+        AssetKind::Archive
+    } else {
+        AssetKind::Binary
+    }
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+}
+
+fn run(command: &mut std::process::Command) -> Result<(), UpdateError> {
+    let output = command.output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(UpdateError::Install(
+            String::from_utf8_lossy(&output.stderr)
+                .trim()
+                .to_string()
+                .into(),
+        ))
+    }
+}
+
+/// Install the downloaded asset. Returns the installer to run on relaunch, if the
+/// asset is one.
+fn apply_update(download: &Path, asset_name: &str) -> Result<Option<PathBuf>, UpdateError> {
+    let _span = tracing::debug_span!("updater.install", asset = asset_name).entered();
+    match asset_kind(asset_name) {
+        AssetKind::Installer => Ok(Some(download.to_path_buf())),
+        AssetKind::DiskImage => install_disk_image(download).map(|_| None),
+        AssetKind::Archive => {
+            let dir = download.with_extension("extracted");
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(&dir)?;
+            extract(download, asset_name, &dir)?;
+            install_from_dir(&dir).map(|_| None)
+        }
+        AssetKind::Binary => replace_executable(download).map(|_| None),
+    }
+}
+
+fn extract(archive: &Path, asset_name: &str, dir: &Path) -> Result<(), UpdateError> {
+    let is_zip = asset_name.to_ascii_lowercase().ends_with(".zip");
+    if is_zip && cfg!(target_os = "macos") {
+        run(std::process::Command::new("ditto")
+            .args(["-x", "-k"])
+            .arg(archive)
+            .arg(dir))
+    } else if is_zip && cfg!(target_os = "linux") {
+        run(std::process::Command::new("unzip")
+            .args(["-q", "-o"])
+            .arg(archive)
+            .arg("-d")
+            .arg(dir))
+    } else {
+        // bsdtar ships with macOS and Windows 10+, and reads zip as well as tarballs.
+        run(std::process::Command::new("tar")
+            .arg("-xf")
+            .arg(archive)
+            .arg("-C")
+            .arg(dir))
+    }
+}
+
+/// The `.app` bundle containing the running executable.
+fn current_bundle() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    exe.ancestors()
+        .find(|path| has_extension(path, "app"))
+        .map(Path::to_path_buf)
+}
+
+/// The first entry in `dir` accepted by `matches`, searching `depth` levels of
+/// subdirectories.
+fn find_entry(dir: &Path, matches: &dyn Fn(&Path) -> bool, depth: usize) -> Option<PathBuf> {
+    let entries: Vec<PathBuf> = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    if let Some(found) = entries.iter().find(|path| matches(path)) {
+        return Some(found.clone());
+    }
+    if depth == 0 {
+        return None;
+    }
+    entries
+        .iter()
+        .filter(|path| path.is_dir() && !has_extension(path, "app"))
+        .find_map(|path| find_entry(path, matches, depth - 1))
+}
+
+fn install_from_dir(dir: &Path) -> Result<(), UpdateError> {
+    if let Some(bundle) = current_bundle() {
+        let new_bundle = find_entry(dir, &|path: &Path| has_extension(path, "app"), 2)
+            .ok_or_else(|| UpdateError::Install("the archive contains no .app bundle".into()))?;
+        return replace_bundle(&new_bundle, &bundle);
+    }
+    let exe_name = std::env::current_exe()?
+        .file_name()
+        .map(|name| name.to_os_string())
+        .ok_or_else(|| UpdateError::Install("unknown executable name".into()))?;
+    let new_exe = find_entry(
+        dir,
+        &|path: &Path| path.is_file() && path.file_name() == Some(exe_name.as_os_str()),
+        3,
+    )
+    .ok_or_else(|| {
+        UpdateError::Install(
+            format!("the archive contains no {}", exe_name.to_string_lossy()).into(),
+        )
+    })?;
+    replace_executable(&new_exe)
+}
+
+fn install_disk_image(image: &Path) -> Result<(), UpdateError> {
+    let bundle = current_bundle()
+        .ok_or_else(|| UpdateError::Install("not running from an .app bundle".into()))?;
+    let mount_point = image.with_extension("mount");
+    std::fs::create_dir_all(&mount_point)?;
+    run(std::process::Command::new("hdiutil")
+        .args(["attach", "-nobrowse", "-readonly", "-mountpoint"])
+        .arg(&mount_point)
+        .arg(image))?;
+    let result = find_entry(&mount_point, &|path: &Path| has_extension(path, "app"), 0)
+        .ok_or_else(|| UpdateError::Install("the disk image contains no .app bundle".into()))
+        .and_then(|new_bundle| replace_bundle(&new_bundle, &bundle));
+    let _ = run(std::process::Command::new("hdiutil")
+        .args(["detach", "-quiet"])
+        .arg(&mount_point));
+    result
+}
+
+fn replaced_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".old");
+    path.with_file_name(name)
+}
+
+/// Move `target` aside, put `new` in its place, and restore `target` if that fails.
+fn swap_in(
+    new: &Path,
+    target: &Path,
+    copy: impl FnOnce(&Path, &Path) -> Result<(), UpdateError>,
+) -> Result<(), UpdateError> {
+    let old = replaced_path(target);
+    if old.is_dir() {
+        std::fs::remove_dir_all(&old)?;
+    } else if old.exists() {
+        std::fs::remove_file(&old)?;
+    }
+    std::fs::rename(target, &old)?;
+    if let Err(err) = copy(new, target) {
+        let _ = std::fs::remove_dir_all(target);
+        let _ = std::fs::remove_file(target);
+        let _ = std::fs::rename(&old, target);
+        return Err(err);
+    }
+    Ok(())
+}
+
+fn replace_bundle(new_bundle: &Path, bundle: &Path) -> Result<(), UpdateError> {
+    swap_in(new_bundle, bundle, |new, target| {
+        run(std::process::Command::new("ditto").arg(new).arg(target))
+    })?;
+    let _ = std::fs::remove_dir_all(replaced_path(bundle));
+    Ok(())
+}
+
+/// Replace the running executable, or the AppImage it was started from. The old file is
+/// kept next to it until the next launch, since Windows can't delete a running binary.
+fn replace_executable(new_exe: &Path) -> Result<(), UpdateError> {
+    let target = match std::env::var_os("APPIMAGE") {
+        Some(app_image) => PathBuf::from(app_image),
+        None => std::env::current_exe()?,
+    };
+    swap_in(new_exe, &target, |new, target| {
+        std::fs::copy(new, target)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt as _;
+            std::fs::set_permissions(target, std::fs::Permissions::from_mode(0o755))?;
+        }
+        Ok(())
+    })
+}
+
+/// Clean up the executable moved aside by the previous update.
+fn remove_replaced_files() {
+    if let Ok(exe) = std::env::current_exe() {
+        let _ = std::fs::remove_file(replaced_path(&exe));
+    }
+    if let Some(app_image) = std::env::var_os("APPIMAGE") {
+        let _ = std::fs::remove_file(replaced_path(Path::new(&app_image)));
+    }
+}
+
+/// Card announcing an update, with its download progress and a restart button. Renders
+/// nothing while there is no update to show, so it can stay in the tree. Positioned at
+/// the bottom right of its parent by default.
+#[derive(IntoElement)]
+pub struct UpdateNotification {
+    updater: Entity<Updater>,
+    on_view_notes: Option<Rc<dyn Fn(&ClickEvent, &mut Window, &mut App)>>,
+    style: StyleRefinement,
+}
+
+impl UpdateNotification {
+    pub fn new(updater: Entity<Updater>) -> Self {
+        Self {
+            updater,
+            on_view_notes: None,
+            style: StyleRefinement::default(),
+        }
+    }
+
+    /// Show a "Release Notes" button, e.g. to open [`release_notes_dialog`].
+    pub fn on_view_notes(
+        mut self,
+        handler: impl Fn(&ClickEvent, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.on_view_notes = Some(Rc::new(handler));
+        self
+    }
+}
+
+impl Styled for UpdateNotification {
+    fn style(&mut self) -> &mut StyleRefinement {
+        &mut self.style
+    }
+}
+
+impl RenderOnce for UpdateNotification {
+    fn render(self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let theme = use_theme();
+        let updater = self.updater.read(cx);
+        if updater.is_dismissed() {
+            return div().into_any_element();
+        }
+        let status = updater.status().clone();
+
+        let (icon, title, detail) = match &status {
+            UpdateStatus::Available(info) if info.is_verifiable() => (
+                "download",
+                "Update available".to_string(),
+                format!("Version {} is ready to install.", info.version),
+            ),
+            UpdateStatus::Available(info) => (
+                "shield-alert",
+                "Unverified update available".to_string(),
+                format!(
+                    "Version {} has no published checksum, so the download can't be verified.",
+                    info.version
+                ),
+            ),
+            UpdateStatus::Downloading { info, .. } => (
+                "download",
+                format!("Downloading {}", info.version),
+                match status.progress() {
+                    Some(progress) => format!("{:.0}%", progress * 100.0),
+                    None => "Starting download…".to_string(),
+                },
+            ),
+            UpdateStatus::Installing(info) => (
+                "loader",
+                format!("Installing {}", info.version),
+                "This only takes a moment.".to_string(),
+            ),
+            UpdateStatus::ReadyToRelaunch(info) => (
+                "rotate-ccw",
+                "Update installed".to_string(),
+                format!("Restart to finish updating to {}.", info.version),
+            ),
+            UpdateStatus::Failed(err) => (
+                "triangle-alert",
+                "Update failed".to_string(),
+                err.to_string(),
+            ),
+            _ => return div().into_any_element(),
+        };
+        let icon_color = if matches!(status, UpdateStatus::Failed(_)) {
+            theme.tokens.destructive
+        } else {
+            theme.tokens.primary
+        };
+
+        let entity = self.updater.clone();
+        let dismiss = move |_: &ClickEvent, _: &mut Window, cx: &mut App| {
+            entity.update(cx, |updater, cx| updater.dismiss(cx));
+        };
+        let entity = self.updater.clone();
+        let install = move |_: &ClickEvent, _: &mut Window, cx: &mut App| {
+            entity.update(cx, |updater, cx| updater.install(cx));
+        };
+        let entity = self.updater.clone();
+        let relaunch = move |_: &ClickEvent, _: &mut Window, cx: &mut App| {
+            entity.update(cx, |updater, cx| updater.relaunch(cx));
+        };
+        let user_style = self.style;
+
+        let actions = div()
+            .flex()
+            .justify_end()
+            .gap(px(8.0))
+            .when(matches!(status, UpdateStatus::Available(_)), |this| {
+                this.when_some(self.on_view_notes, |this, on_view_notes| {
+                    this.child(
+                        Button::new("update-notes", "Release Notes")
+                            .variant(ButtonVariant::Ghost)
+                            .size(ButtonSize::Sm)
+                            .on_click(move |event, window, cx| on_view_notes(event, window, cx)),
+                    )
+                })
+                .child(
+                    Button::new("update-later", "Later")
+                        .variant(ButtonVariant::Outline)
+                        .size(ButtonSize::Sm)
+                        .on_click(dismiss.clone()),
+                )
+                .child(
+                    Button::new("update-install", "Update")
+                        .variant(ButtonVariant::Default)
+                        .size(ButtonSize::Sm)
+                        .on_click(install),
+                )
+            })
+            .when(matches!(status, UpdateStatus::ReadyToRelaunch(_)), |this| {
+                this.child(
+                    Button::new("update-later", "Later")
+                        .variant(ButtonVariant::Outline)
+                        .size(ButtonSize::Sm)
+                        .on_click(dismiss.clone()),
+                )
+                .child(
+                    Button::new("update-relaunch", "Restart Now")
+                        .variant(ButtonVariant::Default)
+                        .size(ButtonSize::Sm)
+                        .on_click(relaunch),
+                )
+            })
+            .when(matches!(status, UpdateStatus::Failed(_)), |this| {
+                this.child(
+                    Button::new("update-dismiss", "Dismiss")
+                        .variant(ButtonVariant::Outline)
+                        .size(ButtonSize::Sm)
+                        .on_click(dismiss),
+                )
+            });
+
+        div()
+            .absolute()
+            .bottom(px(16.0))
+            .right(px(16.0))
+            .w(px(340.0))
+            .flex()
+            .flex_col()
+            .gap(px(10.0))
+            .p(px(14.0))
+            .bg(theme.tokens.card)
+            .border_1()
+            .border_color(theme.tokens.border)
+            .rounded(theme.tokens.radius_lg)
+            .shadow(smallvec::smallvec![theme.tokens.shadow_lg.clone()])
+            .map(|this| {
+                let mut div = this;
+                div.style().refine(&user_style);
+                div
+            })
+            .child(
+                div()
+                    .flex()
+                    .gap(px(10.0))
+                    .child(Icon::new(icon).size(px(18.0)).color(icon_color))
+                    .child(
+                        div()
+                            .flex()
+                            .flex_col()
+                            .gap(px(2.0))
+                            .min_w_0()
+                            .child(
+                                div()
+                                    .text_size(px(14.0))
+                                    .font_weight(FontWeight::SEMIBOLD)
+                                    .text_color(theme.tokens.foreground)
+                                    .child(title),
+                            )
+                            .child(caption(detail).color(theme.tokens.muted_foreground)),
+                    ),
+            )
+            .when(matches!(status, UpdateStatus::Downloading { .. }), |this| {
+                this.child(match status.progress() {
+                    Some(progress) => ProgressBar::new(progress),
+                    None => ProgressBar::indeterminate(),
+                })
+            })
+            .when(matches!(status, UpdateStatus::Installing(_)), |this| {
+                this.child(ProgressBar::indeterminate())
+            })
+            .child(actions)
+            .into_any_element()
+    }
+}
+
+/// Build a dialog showing the available update's release notes as Markdown, with a
+/// button that starts installing it. Either button, or closing the dialog, calls
+/// `on_done`.
+pub fn release_notes_dialog(
+    updater: &Entity<Updater>,
+    on_done: impl Fn(&mut Window, &mut App) + 'static,
+    cx: &mut App,
+) -> Entity<Dialog> {
+    let info = updater.read(cx).status().info().cloned();
+    let current_version = updater.read(cx).current_version().clone();
+    let on_done = Rc::new(on_done);
+    let theme = use_theme();
+
+    let (title, notes) = match &info {
+        Some(info) => (
+            format!("What's new in {}", info.version),
+            info.notes.clone(),
+        ),
+        None => ("Release notes".to_string(), SharedString::default()),
+    };
+    let notes = if notes.trim().is_empty() {
+        SharedString::from("No release notes were published for this version.")
+    } else {
+        notes
+    };
+    let description = match info.as_ref().and_then(|info| info.published_at.clone()) {
+        Some(published_at) => format!(
+            "You have {}. Released {}.",
+            current_version,
+            published_at.split('T').next().unwrap_or_default()
+        ),
+        None => format!("You have {}.", current_version),
+    };
+
+    let body = div()
+        .id("release-notes")
+        .max_h(px(360.0))
+        .overflow_y_scroll()
+        .pr(px(4.0))
+        .text_color(theme.tokens.foreground)
+        .child(Markdown::new(notes));
+
+    let later = on_done.clone();
+    let entity = updater.clone();
+    let update_now = on_done.clone();
+    let footer = div()
+        .flex()
+        .justify_end()
+        .gap(px(8.0))
+        .child(
+            Button::new("release-notes-later", "Later")
+                .variant(ButtonVariant::Outline)
+                .on_click(move |_, window, cx| later(window, cx)),
+        )
+        .when(
+            matches!(updater.read(cx).status(), UpdateStatus::Available(_)),
+            |this| {
+                this.child(
+                    Button::new("release-notes-update", "Update Now")
+                        .variant(ButtonVariant::Default)
+                        .icon("download")
+                        .on_click(move |_, window, cx| {
+                            entity.update(cx, |updater, cx| updater.install(cx));
+                            update_now(window, cx);
+                        }),
+                )
+            },
+        );
+
+    let on_close = on_done.clone();
+    cx.new(|cx| {
+        Dialog::new(cx)
+            .title(title)
+            .description(description)
+            .size(DialogSize::Lg)
+            .child(body)
+            .footer(footer)
+            .on_close(move |window, cx| on_close(window, cx))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_versions() {
+        assert_eq!(compare_versions("v1.10.0", "1.9.3"), Ordering::Greater);
+        assert_eq!(compare_versions("1.2", "1.2.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.2.0-beta.2", "1.2.0"), Ordering::Less);
+        assert_eq!(
+            compare_versions("1.2.0-beta.10", "1.2.0-beta.9"),
+            Ordering::Greater
+        );
+        assert_eq!(compare_versions("2.0.0+build.5", "2.0.0"), Ordering::Equal);
+    }
+
+    #[test]
+    fn selects_asset_for_platform() {
+        let names = [
+            "notes-1.4.0-darwin-universal.dmg",
+            "notes-1.4.0-linux-x86_64.AppImage",
+            "notes-1.4.0-linux-x86_64.AppImage.sha256",
+            "notes-1.4.0-linux-aarch64.tar.gz",
+            "notes-1.4.0-windows-x64-setup.exe",
+            "SHA256SUMS",
+        ];
+        assert_eq!(select_asset(&names, "macos", "aarch64"), Some(0));
+        assert_eq!(select_asset(&names, "linux", "x86_64"), Some(1));
+        assert_eq!(select_asset(&names, "linux", "aarch64"), Some(3));
+        assert_eq!(select_asset(&names, "windows", "x86_64"), Some(4));
+        assert_eq!(select_asset(&names, "windows", "aarch64"), None);
+    }
+
+    #[test]
+    fn parses_checksums() {
+        let digest = "a".repeat(64);
+        let sums = format!("{}  other.zip\n{} *notes.zip\n", "b".repeat(64), digest);
+        assert_eq!(parse_checksum(&sums, "notes.zip"), Some(digest.clone()));
+        assert_eq!(
+            parse_checksum(&digest.to_uppercase(), "notes.zip"),
+            Some(digest)
+        );
+        assert_eq!(parse_checksum("not a digest", "notes.zip"), None);
+    }
+
+    #[test]
+    fn parses_feed() {
+        let feed: serde_json::Value = serde_json::from_str(
+            r#"{
+                "version": "1.4.0",
+                "notes": "Fixes",
+                "platforms": {
+                    "linux-x86_64": { "url": "https://example.com/notes.AppImage?x=1", "sha256": "ABC" }
+                }
+            }"#,
+        )
+        .unwrap();
+        let info = parse_feed(&feed, "linux-x86_64").unwrap();
+        assert_eq!(info.version.as_ref(), "1.4.0");
+        assert_eq!(info.asset_name.as_ref(), "notes.AppImage");
+        assert_eq!(info.sha256.as_deref(), Some("abc"));
+        assert_eq!(
+            parse_feed(&feed, "windows-x86_64"),
+            Err(UpdateError::NoCompatibleAsset)
+        );
+    }
+
+    #[test]
+    fn classifies_assets() {
+        assert_eq!(asset_kind("Notes-Setup-1.4.0.exe"), AssetKind::Installer);
+        assert_eq!(asset_kind("notes.msi"), AssetKind::Installer);
+        assert_eq!(asset_kind("Notes.dmg"), AssetKind::DiskImage);
+        assert_eq!(asset_kind("notes-linux.tar.gz"), AssetKind::Archive);
+        assert_eq!(asset_kind("notes.AppImage"), AssetKind::Binary);
+    }
+}
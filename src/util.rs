@@ -7,6 +7,7 @@
 //! - **`AxisExt`**: Convenient methods for checking axis orientation
 //! - **`PixelsExt`**: Conversion utilities for Pixels type
 //! - **`ScrollHandleOffsetable`**: Trait for scroll handle offset operations
+//! - **`SnapToDevicePixels`**: Alignment of painted geometry to physical pixels
 //! - **Color utilities**: Color manipulation and conversion helpers
 //! - **Layout helpers**: Common layout calculations and measurements
 //!
@@ -19,7 +20,10 @@
 //! - **Discoverability**: Clear naming that makes functionality obvious
 //!
 
-use gpui::{Pixels, Point, ScrollHandle, Size};
+use gpui::{
+    px, Bounds, Context, Entity, MouseMoveEvent, MouseUpEvent, Pixels, Point, ScrollHandle, Size,
+    Subscription, Window,
+};
 
 /// Extension trait for Axis
 pub trait AxisExt {
@@ -80,3 +84,209 @@ impl ScrollHandleOffsetable for ScrollHandle {
         self.max_offset() + self.bounds().size
     }
 }
+
+/// Rounding of painted geometry to whole device pixels.
+///
+/// At fractional scale factors (125%, 150%) a logical pixel is not a whole number of
+/// physical pixels, so quads and hairlines painted at arbitrary logical coordinates
+/// straddle pixel boundaries and come out blurred. Paint code snaps with the window's
+/// current `scale_factor()`, which keeps the result correct after the window moves to a
+/// monitor with a different scale.
+pub trait SnapToDevicePixels {
+    /// Round to the nearest device pixel boundary.
+    fn snap_to_device(self, scale_factor: f32) -> Self;
+}
+
+impl SnapToDevicePixels for Pixels {
+    fn snap_to_device(self, scale_factor: f32) -> Self {
+        if scale_factor <= 0.0 {
+            return self;
+        }
+        Pixels::from((f32::from(self) * scale_factor).round() / scale_factor)
+    }
+}
+
+impl SnapToDevicePixels for Point<Pixels> {
+    fn snap_to_device(self, scale_factor: f32) -> Self {
+        Point::new(
+            self.x.snap_to_device(scale_factor),
+            self.y.snap_to_device(scale_factor),
+        )
+    }
+}
+
+impl SnapToDevicePixels for Bounds<Pixels> {
+    /// Snaps each edge independently, so boxes that share an edge still meet exactly.
+    /// Non-empty bounds keep at least one device pixel in each dimension.
+    fn snap_to_device(self, scale_factor: f32) -> Self {
+        let origin = self.origin.snap_to_device(scale_factor);
+        let far = self.bottom_right().snap_to_device(scale_factor);
+        let min_size = |original: Pixels, snapped: Pixels| {
+            if original > px(0.0) {
+                snapped.max(device_pixel(scale_factor))
+            } else {
+                snapped
+            }
+        };
+        Bounds::new(
+            origin,
+            Size::new(
+                min_size(self.size.width, far.x - origin.x),
+                min_size(self.size.height, far.y - origin.y),
+            ),
+        )
+    }
+}
+
+/// The logical size of one device pixel.
+pub fn device_pixel(scale_factor: f32) -> Pixels {
+    Pixels::from(1.0 / scale_factor.max(f32::EPSILON))
+}
+
+/// `length` rounded to whole device pixels, never less than one. Use for line and caret
+/// thicknesses so they render at the same width wherever they are placed.
+pub fn snap_length(length: Pixels, scale_factor: f32) -> Pixels {
+    length
+        .snap_to_device(scale_factor)
+        .max(device_pixel(scale_factor))
+}
+
+/// Position for the centerline of a horizontal or vertical stroke of `width` near
+/// `coord`, such that both of its edges fall on device pixel boundaries. Odd device
+/// widths are centered on a pixel, even ones on a boundary.
+pub fn snap_stroke_center(coord: Pixels, width: Pixels, scale_factor: f32) -> Pixels {
+    if scale_factor <= 0.0 {
+        return coord;
+    }
+    let device_width = (f32::from(width) * scale_factor).round().max(1.0) as u32;
+    let device_coord = f32::from(coord) * scale_factor;
+    let snapped = if device_width % 2 == 1 {
+        device_coord.floor() + 0.5
+    } else {
+        device_coord.round()
+    };
+    Pixels::from(snapped / scale_factor)
+}
+
+/// Call `callback` with the new scale factor whenever `window` moves to a display with a
+/// different scale. For views that cache anything measured in device pixels.
+pub fn observe_scale_factor<T: 'static>(
+    window: &mut Window,
+    cx: &mut Context<T>,
+    mut callback: impl FnMut(&mut T, f32, &mut Window, &mut Context<T>) + 'static,
+) -> Subscription {
+    let mut last_scale_factor = window.scale_factor();
+    cx.observe_window_bounds(window, move |this, window, cx| {
+        let scale_factor = window.scale_factor();
+        if scale_factor != last_scale_factor {
+            last_scale_factor = scale_factor;
+            callback(this, scale_factor, window, cx);
+        }
+    })
+}
+
+/// Follow a drag window-wide until the mouse button is released, so it carries on when
+/// the pointer leaves the control that started it. Call while painting a control that is
+/// being dragged: `drag_to` gets each move and `end_drag` the release.
+pub fn track_window_drag<T: 'static>(
+    entity: Entity<T>,
+    window: &mut Window,
+    drag_to: impl Fn(&mut T, &MouseMoveEvent, &mut Context<T>) + 'static,
+    end_drag: impl Fn(&mut T, &mut Context<T>) + 'static,
+) {
+    window.on_mouse_event({
+        let entity = entity.clone();
+        move |event: &MouseMoveEvent, phase, _, cx| {
+            if phase.bubble() {
+                entity.update(cx, |state, cx| drag_to(state, event, cx));
+            }
+        }
+    });
+    window.on_mouse_event(move |_: &MouseUpEvent, phase, _, cx| {
+        if phase.bubble() {
+            entity.update(cx, |state, cx| end_drag(state, cx));
+        }
+    });
+}
+
+/// Decode a URL query string into key/value pairs, in order. Keys without a value map
+/// to an empty string.
+pub(crate) fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key, true), percent_decode(value, true))
+        })
+        .collect()
+}
+
+/// Decode `%XX` escapes in a URL component, and `+` as a space in query strings. A `%`
+/// not followed by two hex digits is kept as is.
+pub(crate) fn percent_decode(text: &str, plus_as_space: bool) -> String {
+    fn hex_value(digit: u8) -> Option<u8> {
+        (digit as char).to_digit(16).map(|value| value as u8)
+    }
+
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => match (
+                bytes.get(i + 1).copied().and_then(hex_value),
+                bytes.get(i + 2).copied().and_then(hex_value),
+            ) {
+                (Some(high), Some(low)) => {
+                    decoded.push((high << 4) | low);
+                    i += 3;
+                    continue;
+                }
+                _ => decoded.push(b'%'),
+            },
+            b'+' if plus_as_space => decoded.push(b' '),
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{point, size};
+
+    #[test]
+    fn snaps_to_fractional_scale_factors() {
+        assert_eq!(px(10.3).snap_to_device(1.25), px(10.4));
+        assert_eq!(snap_length(px(2.0), 1.25), px(2.4));
+        assert_eq!(snap_length(px(0.2), 1.5), device_pixel(1.5));
+
+        // A 1px hairline at 150% is two device pixels wide, so it sits on a boundary.
+        assert_eq!(snap_stroke_center(px(10.1), px(1.0), 1.5), px(10.0));
+        assert_eq!(snap_stroke_center(px(10.1), px(1.0), 1.0), px(10.5));
+
+        let left = Bounds::new(point(px(0.3), px(0.0)), size(px(10.1), px(5.0)));
+        let right = Bounds::new(point(px(10.4), px(0.0)), size(px(3.0), px(5.0)));
+        let (left, right) = (left.snap_to_device(1.25), right.snap_to_device(1.25));
+        assert_eq!(left.right(), right.left());
+    }
+
+    #[test]
+    fn percent_decodes_only_hex_escapes() {
+        assert_eq!(percent_decode("a%20b+c", true), "a b c");
+        assert_eq!(percent_decode("a+b", false), "a+b");
+        assert_eq!(percent_decode("%+1%-1%4", true), "% 1%-1%4");
+        assert_eq!(percent_decode("caf%C3%A9", false), "café");
+        assert_eq!(
+            parse_query("code=a%2Fb&state=&flag"),
+            [
+                ("code".to_string(), "a/b".to_string()),
+                ("state".to_string(), String::new()),
+                ("flag".to_string(), String::new()),
+            ]
+        );
+    }
+}
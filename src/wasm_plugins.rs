@@ -0,0 +1,648 @@
+//! Sandboxed WebAssembly extension host (feature `wasm-plugins`).
+//!
+//! Unlike [`crate::plugins`], which runs trusted Rust code in-process, a wasm extension
+//! only reaches the app through a small host API, and only for the [`Capability`]s it
+//! was granted when loaded. Denied calls return `-1` and are recorded as violations.
+//! Guest execution is metered with fuel and linear memory is capped, so a runaway
+//! module traps instead of hanging or exhausting the app; a trapped extension is
+//! disabled.
+//!
+//! ## Guest ABI
+//!
+//! Strings are UTF-8 `(ptr, len)` pairs in the guest's exported `memory`.
+//!
+//! Host imports, module `"adabraka"`:
+//! - `log(ptr, len)`; the latest 500 lines are kept, each cut to 4 KB
+//! - `register_command(id_ptr, id_len, title_ptr, title_len) -> i32` — [`Capability::Commands`]
+//! - `set_status_item(id_ptr, id_len, text_ptr, text_len) -> i32` — [`Capability::StatusBar`]
+//!   (an empty text removes the item)
+//! - `register_document_scheme(ptr, len) -> i32` — [`Capability::VirtualDocuments`]
+//! - `show_message(level, ptr, len) -> i32` — [`Capability::Notifications`]; level is
+//!   0 info, 1 warning, 2 error
+//!
+//! Guest exports:
+//! - `memory` and `alloc(len) -> ptr`, used by the host to pass strings in
+//! - `activate()` (optional), called once after loading
+//! - `run_command(ptr, len)` (optional), called with the id of a registered command
+//! - `provide_document(ptr, len) -> i64` (optional), called with a URI in one of the
+//!   registered schemes; returns `(ptr << 32) | len` of the document text
+//!
+//! ```rust,ignore
+//! let host = WasmExtensionHost::new()?;
+//! let host = cx.new(|_| host);
+//! host.update(cx, |host, cx| {
+//!     host.load("acme.todo", &wasm_bytes, [Capability::Commands, Capability::StatusBar], cx)
+//! })?;
+//! let commands = WasmExtensionHost::palette_commands(&host, cx);
+//! ```
+
+use crate::callbacks;
+use crate::navigation::status_bar::StatusItem;
+use crate::overlays::command_palette::Command;
+use crate::overlays::toast::ToastVariant;
+use gpui::*;
+use std::collections::VecDeque;
+use wasmtime::{
+    Caller, Config, Engine, Instance, Linker, Module, Store, StoreLimits, StoreLimitsBuilder,
+};
+
+const HOST_MODULE: &str = "adabraka";
+const DEFAULT_FUEL_PER_CALL: u64 = 50_000_000;
+const DEFAULT_MEMORY_LIMIT: usize = 64 * 1024 * 1024;
+/// Log lines kept per extension; older ones are dropped first.
+const LOG_LIMIT: usize = 500;
+/// Bytes kept of each log line.
+const LOG_LINE_LIMIT: usize = 4096;
+
+/// A permission an extension must be granted to use part of the host API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// Contribute command palette entries.
+    Commands,
+    /// Show text items in the status bar.
+    StatusBar,
+    /// Serve read-only documents for a URI scheme.
+    VirtualDocuments,
+    /// Show info, warning and error messages.
+    Notifications,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+impl MessageLevel {
+    fn from_raw(level: i32) -> Self {
+        match level {
+            1 => MessageLevel::Warning,
+            2 => MessageLevel::Error,
+            _ => MessageLevel::Info,
+        }
+    }
+
+    pub fn toast_variant(self) -> ToastVariant {
+        match self {
+            MessageLevel::Info => ToastVariant::Default,
+            MessageLevel::Warning => ToastVariant::Warning,
+            MessageLevel::Error => ToastVariant::Error,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum WasmExtensionError {
+    /// The wasm engine or the host API couldn't be set up.
+    Engine(SharedString),
+    AlreadyLoaded(SharedString),
+    NotFound(SharedString),
+    Disabled(SharedString),
+    Compile {
+        id: SharedString,
+        message: SharedString,
+    },
+    Trap {
+        id: SharedString,
+        message: SharedString,
+    },
+    /// No enabled extension serves the URI's scheme.
+    NoDocumentProvider(SharedString),
+}
+
+impl std::fmt::Display for WasmExtensionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WasmExtensionError::Engine(message) => {
+                write!(f, "wasm engine unavailable: {}", message)
+            }
+            WasmExtensionError::AlreadyLoaded(id) => {
+                write!(f, "extension `{}` is already loaded", id)
+            }
+            WasmExtensionError::NotFound(id) => write!(f, "extension `{}` is not loaded", id),
+            WasmExtensionError::Disabled(id) => write!(f, "extension `{}` is disabled", id),
+            WasmExtensionError::Compile { id, message } => {
+                write!(f, "extension `{}` failed to load: {}", id, message)
+            }
+            WasmExtensionError::Trap { id, message } => {
+                write!(f, "extension `{}` trapped: {}", id, message)
+            }
+            WasmExtensionError::NoDocumentProvider(uri) => {
+                write!(f, "no extension provides `{}`", uri)
+            }
+        }
+    }
+}
+
+impl std::error::Error for WasmExtensionError {}
+
+#[derive(Debug, Clone)]
+pub enum WasmExtensionEvent {
+    Message {
+        extension: SharedString,
+        level: MessageLevel,
+        text: SharedString,
+    },
+    /// Commands, status items or document schemes changed.
+    ContributionsChanged,
+    /// An extension trapped and was disabled.
+    Failed {
+        extension: SharedString,
+        error: SharedString,
+    },
+}
+
+/// Public snapshot of a loaded extension.
+#[derive(Debug, Clone)]
+pub struct WasmExtensionInfo {
+    pub id: SharedString,
+    pub capabilities: Vec<Capability>,
+    pub enabled: bool,
+    pub error: Option<SharedString>,
+    /// Capabilities the extension tried to use without being granted them.
+    pub violations: Vec<Capability>,
+    pub logs: Vec<SharedString>,
+}
+
+#[derive(Default)]
+struct GuestState {
+    granted: Vec<Capability>,
+    commands: Vec<(SharedString, SharedString)>,
+    status_items: Vec<(SharedString, SharedString)>,
+    schemes: Vec<SharedString>,
+    messages: Vec<(MessageLevel, SharedString)>,
+    logs: VecDeque<SharedString>,
+    violations: Vec<Capability>,
+    contributions_changed: bool,
+    limits: StoreLimits,
+}
+
+struct WasmExtension {
+    id: SharedString,
+    store: Store<GuestState>,
+    instance: Instance,
+    enabled: bool,
+    error: Option<SharedString>,
+}
+
+/// Loads wasm extensions and exposes their contributions to the app.
+pub struct WasmExtensionHost {
+    engine: Engine,
+    linker: Linker<GuestState>,
+    extensions: Vec<WasmExtension>,
+    fuel_per_call: u64,
+    memory_limit: usize,
+}
+
+impl EventEmitter<WasmExtensionEvent> for WasmExtensionHost {}
+
+fn read_string(caller: &mut Caller<'_, GuestState>, ptr: i32, len: i32) -> Option<String> {
+    let memory = caller.get_export("memory")?.into_memory()?;
+    let start = usize::try_from(ptr).ok()?;
+    let end = start.checked_add(usize::try_from(len).ok()?)?;
+    let bytes = memory.data(&*caller).get(start..end)?;
+    String::from_utf8(bytes.to_vec()).ok()
+}
+
+fn allowed(caller: &mut Caller<'_, GuestState>, capability: Capability) -> bool {
+    let state = caller.data_mut();
+    if state.granted.contains(&capability) {
+        return true;
+    }
+    if !state.violations.contains(&capability) {
+        state.violations.push(capability);
+    }
+    false
+}
+
+fn host_linker(engine: &Engine) -> wasmtime::Result<Linker<GuestState>> {
+    let mut linker = Linker::new(engine);
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "log",
+        |mut caller: Caller<'_, GuestState>, ptr: i32, len: i32| {
+            if let Some(mut text) = read_string(&mut caller, ptr, len) {
+                if text.len() > LOG_LINE_LIMIT {
+                    let mut end = LOG_LINE_LIMIT;
+                    while !text.is_char_boundary(end) {
+                        end -= 1;
+                    }
+                    text.truncate(end);
+                }
+                let logs = &mut caller.data_mut().logs;
+                if logs.len() >= LOG_LIMIT {
+                    logs.pop_front();
+                }
+                logs.push_back(text.into());
+            }
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "register_command",
+        |mut caller: Caller<'_, GuestState>,
+         id_ptr: i32,
+         id_len: i32,
+         title_ptr: i32,
+         title_len: i32|
+         -> i32 {
+            if !allowed(&mut caller, Capability::Commands) {
+                return -1;
+            }
+            let (Some(id), Some(title)) = (
+                read_string(&mut caller, id_ptr, id_len),
+                read_string(&mut caller, title_ptr, title_len),
+            ) else {
+                return -1;
+            };
+            let state = caller.data_mut();
+            state
+                .commands
+                .retain(|(existing, _)| existing.as_ref() != id);
+            state.commands.push((id.into(), title.into()));
+            state.contributions_changed = true;
+            0
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "set_status_item",
+        |mut caller: Caller<'_, GuestState>,
+         id_ptr: i32,
+         id_len: i32,
+         text_ptr: i32,
+         text_len: i32|
+         -> i32 {
+            if !allowed(&mut caller, Capability::StatusBar) {
+                return -1;
+            }
+            let (Some(id), Some(text)) = (
+                read_string(&mut caller, id_ptr, id_len),
+                read_string(&mut caller, text_ptr, text_len),
+            ) else {
+                return -1;
+            };
+            let state = caller.data_mut();
+            match state
+                .status_items
+                .iter_mut()
+                .find(|(item, _)| item.as_ref() == id)
+            {
+                Some(_) if text.is_empty() => {
+                    state.status_items.retain(|(item, _)| item.as_ref() != id);
+                }
+                Some((_, existing)) => *existing = text.into(),
+                None if text.is_empty() => {}
+                None => state.status_items.push((id.into(), text.into())),
+            }
+            state.contributions_changed = true;
+            0
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "register_document_scheme",
+        |mut caller: Caller<'_, GuestState>, ptr: i32, len: i32| -> i32 {
+            if !allowed(&mut caller, Capability::VirtualDocuments) {
+                return -1;
+            }
+            let Some(scheme) = read_string(&mut caller, ptr, len) else {
+                return -1;
+            };
+            let state = caller.data_mut();
+            if !state.schemes.iter().any(|s| s.as_ref() == scheme) {
+                state.schemes.push(scheme.into());
+                state.contributions_changed = true;
+            }
+            0
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "show_message",
+        |mut caller: Caller<'_, GuestState>, level: i32, ptr: i32, len: i32| -> i32 {
+            if !allowed(&mut caller, Capability::Notifications) {
+                return -1;
+            }
+            let Some(text) = read_string(&mut caller, ptr, len) else {
+                return -1;
+            };
+            caller
+                .data_mut()
+                .messages
+                .push((MessageLevel::from_raw(level), text.into()));
+            0
+        },
+    )?;
+
+    Ok(linker)
+}
+
+/// Copy `text` into guest memory through the guest's `alloc` export.
+fn write_string(
+    store: &mut Store<GuestState>,
+    instance: Instance,
+    text: &str,
+) -> wasmtime::Result<(i32, i32)> {
+    let alloc = instance.get_typed_func::<i32, i32>(&mut *store, "alloc")?;
+    let len = i32::try_from(text.len())?;
+    let ptr = alloc.call(&mut *store, len)?;
+    let memory = instance
+        .get_memory(&mut *store, "memory")
+        .ok_or_else(|| wasmtime::Error::msg("missing `memory` export"))?;
+    memory.write(&mut *store, usize::try_from(ptr)?, text.as_bytes())?;
+    Ok((ptr, len))
+}
+
+impl WasmExtensionHost {
+    /// Set up the engine and host API. Fails if wasmtime can't run on this platform.
+    pub fn new() -> Result<Self, WasmExtensionError> {
+        let engine_error =
+            |err: wasmtime::Error| WasmExtensionError::Engine(err.to_string().into());
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(engine_error)?;
+        let linker = host_linker(&engine).map_err(engine_error)?;
+
+        Ok(Self {
+            engine,
+            linker,
+            extensions: Vec::new(),
+            fuel_per_call: DEFAULT_FUEL_PER_CALL,
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+        })
+    }
+
+    /// Fuel budget for each call into an extension; running out traps the call.
+    pub fn fuel_per_call(mut self, fuel: u64) -> Self {
+        self.fuel_per_call = fuel;
+        self
+    }
+
+    /// Maximum linear memory, in bytes, of each extension loaded after this call.
+    pub fn memory_limit(mut self, bytes: usize) -> Self {
+        self.memory_limit = bytes;
+        self
+    }
+
+    /// Compile, instantiate and activate an extension with the given grants. An extension
+    /// whose `activate` traps is unloaded again.
+    pub fn load(
+        &mut self,
+        id: impl Into<SharedString>,
+        wasm: &[u8],
+        grants: impl IntoIterator<Item = Capability>,
+        cx: &mut Context<Self>,
+    ) -> Result<(), WasmExtensionError> {
+        let id = id.into();
+        if self.extensions.iter().any(|e| e.id == id) {
+            return Err(WasmExtensionError::AlreadyLoaded(id));
+        }
+
+        let compile_error = |e: wasmtime::Error| WasmExtensionError::Compile {
+            id: id.clone(),
+            message: e.to_string().into(),
+        };
+        let module = Module::new(&self.engine, wasm).map_err(compile_error)?;
+        let mut store = Store::new(
+            &self.engine,
+            GuestState {
+                granted: grants.into_iter().collect(),
+                limits: StoreLimitsBuilder::new()
+                    .memory_size(self.memory_limit)
+                    .instances(1)
+                    .build(),
+                ..Default::default()
+            },
+        );
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(self.fuel_per_call).map_err(compile_error)?;
+        let instance = self
+            .linker
+            .instantiate(&mut store, &module)
+            .map_err(compile_error)?;
+
+        self.extensions.push(WasmExtension {
+            id: id.clone(),
+            store,
+            instance,
+            enabled: true,
+            error: None,
+        });
+
+        let activated = self.call_guest(
+            &id,
+            |store, instance| {
+                if let Ok(activate) = instance.get_typed_func::<(), ()>(&mut *store, "activate") {
+                    activate.call(&mut *store, ())?;
+                }
+                Ok(())
+            },
+            cx,
+        );
+        if activated.is_err() {
+            self.unload(&id, cx);
+        }
+        activated
+    }
+
+    pub fn unload(&mut self, id: &str, cx: &mut Context<Self>) {
+        self.extensions.retain(|e| e.id.as_ref() != id);
+        cx.emit(WasmExtensionEvent::ContributionsChanged);
+        cx.notify();
+    }
+
+    pub fn set_enabled(
+        &mut self,
+        id: &str,
+        enabled: bool,
+        cx: &mut Context<Self>,
+    ) -> Result<(), WasmExtensionError> {
+        let extension = self
+            .extensions
+            .iter_mut()
+            .find(|e| e.id.as_ref() == id)
+            .ok_or_else(|| WasmExtensionError::NotFound(id.to_string().into()))?;
+        extension.enabled = enabled;
+        if enabled {
+            extension.error = None;
+        }
+        cx.emit(WasmExtensionEvent::ContributionsChanged);
+        cx.notify();
+        Ok(())
+    }
+
+    pub fn extensions(&self) -> Vec<WasmExtensionInfo> {
+        self.extensions
+            .iter()
+            .map(|e| {
+                let state = e.store.data();
+                WasmExtensionInfo {
+                    id: e.id.clone(),
+                    capabilities: state.granted.clone(),
+                    enabled: e.enabled,
+                    error: e.error.clone(),
+                    violations: state.violations.clone(),
+                    logs: state.logs.iter().cloned().collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// Run an extension's command by the id it registered.
+    pub fn run_command(
+        &mut self,
+        extension: &str,
+        command: &str,
+        cx: &mut Context<Self>,
+    ) -> Result<(), WasmExtensionError> {
+        self.call_guest(
+            extension,
+            |store, instance| {
+                let run = instance.get_typed_func::<(i32, i32), ()>(&mut *store, "run_command")?;
+                let (ptr, len) = write_string(store, instance, command)?;
+                run.call(&mut *store, (ptr, len))
+            },
+            cx,
+        )
+    }
+
+    /// Fetch a virtual document, e.g. `"todo:inbox"`, from the extension that
+    /// registered its scheme.
+    pub fn open_document(
+        &mut self,
+        uri: &str,
+        cx: &mut Context<Self>,
+    ) -> Result<String, WasmExtensionError> {
+        let scheme = uri.split_once(':').map_or(uri, |(scheme, _)| scheme);
+        let provider = self
+            .extensions
+            .iter()
+            .filter(|e| e.enabled)
+            .find(|e| e.store.data().schemes.iter().any(|s| s.as_ref() == scheme))
+            .map(|e| e.id.clone())
+            .ok_or_else(|| WasmExtensionError::NoDocumentProvider(uri.to_string().into()))?;
+
+        self.call_guest(
+            &provider,
+            |store, instance| {
+                let provide =
+                    instance.get_typed_func::<(i32, i32), i64>(&mut *store, "provide_document")?;
+                let (ptr, len) = write_string(store, instance, uri)?;
+                let packed = provide.call(&mut *store, (ptr, len))? as u64;
+                let (start, len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+                let memory = instance
+                    .get_memory(&mut *store, "memory")
+                    .ok_or_else(|| wasmtime::Error::msg("missing `memory` export"))?;
+                let bytes = memory
+                    .data(&*store)
+                    .get(start..start + len)
+                    .ok_or_else(|| wasmtime::Error::msg("document out of bounds"))?;
+                Ok(String::from_utf8_lossy(bytes).into_owned())
+            },
+            cx,
+        )
+    }
+
+    /// Palette entries for commands registered by enabled extensions. Selecting one
+    /// runs it in its extension.
+    pub fn palette_commands(host: &Entity<Self>, cx: &App) -> Vec<Command> {
+        host.read(cx)
+            .extensions
+            .iter()
+            .filter(|e| e.enabled)
+            .flat_map(|e| {
+                let extension = e.id.clone();
+                e.store.data().commands.iter().map(move |(id, title)| {
+                    let (extension, command) = (extension.clone(), id.clone());
+                    Command::new(format!("{}.{}", extension, id), title.clone())
+                        .category(extension.clone())
+                        .on_select(callbacks::weak_handler(host, move |host, _window, cx| {
+                            // Failures are reported through `WasmExtensionEvent::Failed`.
+                            let _ = host.run_command(&extension, &command, cx);
+                        }))
+                })
+            })
+            .collect()
+    }
+
+    /// Status bar items set by enabled extensions.
+    pub fn status_items(&self) -> Vec<StatusItem> {
+        self.extensions
+            .iter()
+            .filter(|e| e.enabled)
+            .flat_map(|e| {
+                e.store
+                    .data()
+                    .status_items
+                    .iter()
+                    .map(|(_, text)| StatusItem::text(text.clone()).tooltip(e.id.clone()))
+            })
+            .collect()
+    }
+
+    /// Call into an extension with a fresh fuel budget, then publish whatever it
+    /// contributed. A trap disables the extension.
+    fn call_guest<R>(
+        &mut self,
+        id: &str,
+        call: impl FnOnce(&mut Store<GuestState>, Instance) -> wasmtime::Result<R>,
+        cx: &mut Context<Self>,
+    ) -> Result<R, WasmExtensionError> {
+        let fuel = self.fuel_per_call;
+        let extension = self
+            .extensions
+            .iter_mut()
+            .find(|e| e.id.as_ref() == id)
+            .ok_or_else(|| WasmExtensionError::NotFound(id.to_string().into()))?;
+        if !extension.enabled {
+            return Err(WasmExtensionError::Disabled(extension.id.clone()));
+        }
+
+        let instance = extension.instance;
+        let result = extension
+            .store
+            .set_fuel(fuel)
+            .and_then(|_| call(&mut extension.store, instance));
+
+        let state = extension.store.data_mut();
+        let messages = std::mem::take(&mut state.messages);
+        let changed = std::mem::take(&mut state.contributions_changed);
+        let extension_id = extension.id.clone();
+
+        let result = result.map_err(|e| {
+            let message: SharedString = e.to_string().into();
+            extension.enabled = false;
+            extension.error = Some(message.clone());
+            WasmExtensionError::Trap {
+                id: extension_id.clone(),
+                message,
+            }
+        });
+
+        for (level, text) in messages {
+            cx.emit(WasmExtensionEvent::Message {
+                extension: extension_id.clone(),
+                level,
+                text,
+            });
+        }
+        if let Err(WasmExtensionError::Trap { message, .. }) = &result {
+            cx.emit(WasmExtensionEvent::Failed {
+                extension: extension_id.clone(),
+                error: message.clone(),
+            });
+        }
+        if changed || result.is_err() {
+            cx.emit(WasmExtensionEvent::ContributionsChanged);
+        }
+        cx.notify();
+        result
+    }
+}
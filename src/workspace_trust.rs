@@ -0,0 +1,284 @@
+//! Workspace trust and restricted mode.
+//!
+//! Opening a folder can run code from it: task definitions, scripts, formatter
+//! binaries named in its config. Until the user says they trust a folder, it is opened
+//! in restricted mode, where the features registered as risky report themselves as not
+//! allowed. Everything else works as usual.
+//!
+//! The app asks [`open_workspace`] when it opens a folder and shows [`trust_dialog`] if
+//! the folder hasn't been decided on yet. Integrations check [`is_allowed`] before
+//! doing anything risky; [`ScriptHost`](crate::scripting::ScriptHost) does so itself.
+//! A decision covers the folder and everything below it, unless a subfolder has a
+//! decision of its own.
+//!
+//! ```rust,ignore
+//! workspace_trust::load_decisions(saved_decisions, cx);
+//! if workspace_trust::open_workspace(&folder, cx) == TrustState::Unknown {
+//!     self.trust_dialog = Some(workspace_trust::trust_dialog(&folder, |_, _, cx| {
+//!         save(workspace_trust::decisions(cx));
+//!     }, cx));
+//! }
+//!
+//! // in the task runner:
+//! if !workspace_trust::is_allowed(workspace_trust::TASKS, cx) {
+//!     return Err(TaskError::Restricted);
+//! }
+//!
+//! // in the status bar:
+//! if let Some(item) = workspace_trust::status_item(cx, open_trust_dialog) {
+//!     status_bar = status_bar.add_right(item);
+//! }
+//! ```
+//!
+//! Changes go through `cx.update_global`, so `cx.observe_global::<WorkspaceTrust>`
+//! sees each one, e.g. to start a language server once a folder becomes trusted.
+
+use crate::components::button::{Button, ButtonVariant};
+use crate::navigation::status_bar::StatusItem;
+use crate::overlays::dialog::Dialog;
+use crate::theme::use_theme;
+use gpui::*;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// Running tasks or build commands defined by the workspace.
+pub const TASKS: &str = "tasks";
+/// Running user scripts.
+pub const SCRIPTING: &str = "scripting";
+/// Running external formatters configured by the workspace.
+pub const FORMATTERS: &str = "formatters";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrustState {
+    /// Not decided yet. Treated as restricted until the user chooses.
+    Unknown,
+    Trusted,
+    Restricted,
+}
+
+pub struct WorkspaceTrust {
+    decisions: HashMap<PathBuf, bool>,
+    workspace: Option<PathBuf>,
+    restricted_features: BTreeSet<SharedString>,
+    prompt_on_open: bool,
+}
+
+impl Default for WorkspaceTrust {
+    fn default() -> Self {
+        Self {
+            decisions: HashMap::new(),
+            workspace: None,
+            restricted_features: [TASKS, SCRIPTING, FORMATTERS]
+                .into_iter()
+                .map(SharedString::from)
+                .collect(),
+            prompt_on_open: true,
+        }
+    }
+}
+
+impl Global for WorkspaceTrust {}
+
+impl WorkspaceTrust {
+    /// The trust of `path`, from the decision on it or its nearest decided ancestor.
+    /// Undecided folders are trusted when prompting is turned off.
+    pub fn state_of(&self, path: &Path) -> TrustState {
+        let decision = path
+            .ancestors()
+            .find_map(|ancestor| self.decisions.get(ancestor).copied());
+        match decision {
+            Some(true) => TrustState::Trusted,
+            Some(false) => TrustState::Restricted,
+            None if self.prompt_on_open => TrustState::Unknown,
+            None => TrustState::Trusted,
+        }
+    }
+
+    /// The trust of the open workspace. With no folder open there is nothing to
+    /// distrust.
+    pub fn state(&self) -> TrustState {
+        self.workspace
+            .as_deref()
+            .map_or(TrustState::Trusted, |workspace| self.state_of(workspace))
+    }
+
+    pub fn workspace(&self) -> Option<&Path> {
+        self.workspace.as_deref()
+    }
+
+    pub fn is_allowed(&self, feature: &str) -> bool {
+        self.state() == TrustState::Trusted || !self.restricted_features.contains(feature)
+    }
+
+    pub fn restricted_features(&self) -> impl Iterator<Item = &SharedString> {
+        self.restricted_features.iter()
+    }
+}
+
+fn update(cx: &mut App, f: impl FnOnce(&mut WorkspaceTrust)) {
+    cx.default_global::<WorkspaceTrust>();
+    cx.update_global::<WorkspaceTrust, _>(|trust, _| f(trust));
+}
+
+/// Make `path` the open workspace and return its trust. [`TrustState::Unknown`] means
+/// the user should be asked, with [`trust_dialog`].
+pub fn open_workspace(path: impl Into<PathBuf>, cx: &mut App) -> TrustState {
+    let path = path.into();
+    update(cx, |trust| trust.workspace = Some(path));
+    state(cx)
+}
+
+pub fn close_workspace(cx: &mut App) {
+    update(cx, |trust| trust.workspace = None);
+}
+
+/// Trust or restrict `path` and everything below it.
+pub fn set_trusted(path: impl Into<PathBuf>, trusted: bool, cx: &mut App) {
+    let path = path.into();
+    update(cx, |trust| {
+        trust.decisions.insert(path, trusted);
+    });
+}
+
+/// Drop the decision on `path`, so it is asked about again.
+pub fn forget(path: &Path, cx: &mut App) {
+    update(cx, |trust| {
+        trust.decisions.remove(path);
+    });
+}
+
+/// Every decision made, to persist between sessions.
+pub fn decisions(cx: &App) -> Vec<(PathBuf, bool)> {
+    let mut decisions: Vec<_> = cx
+        .try_global::<WorkspaceTrust>()
+        .map(|trust| {
+            trust
+                .decisions
+                .iter()
+                .map(|(path, trusted)| (path.clone(), *trusted))
+                .collect()
+        })
+        .unwrap_or_default();
+    decisions.sort();
+    decisions
+}
+
+/// Restore decisions saved from [`decisions`], replacing any made so far.
+pub fn load_decisions(decisions: impl IntoIterator<Item = (PathBuf, bool)>, cx: &mut App) {
+    let decisions = decisions.into_iter().collect();
+    update(cx, |trust| trust.decisions = decisions);
+}
+
+/// Which features restricted mode turns off. Defaults to [`TASKS`], [`SCRIPTING`] and
+/// [`FORMATTERS`]; apps add their own, e.g. `"language-servers"`.
+pub fn set_restricted_features<S: Into<SharedString>>(
+    features: impl IntoIterator<Item = S>,
+    cx: &mut App,
+) {
+    let features = features.into_iter().map(Into::into).collect();
+    update(cx, |trust| trust.restricted_features = features);
+}
+
+/// Whether undecided folders are restricted until the user is asked (the default) or
+/// simply trusted.
+pub fn set_prompt_on_open(prompt: bool, cx: &mut App) {
+    update(cx, |trust| trust.prompt_on_open = prompt);
+}
+
+pub fn state(cx: &App) -> TrustState {
+    cx.try_global::<WorkspaceTrust>()
+        .map_or(TrustState::Trusted, WorkspaceTrust::state)
+}
+
+/// Whether `feature` may run in the open workspace.
+pub fn is_allowed(feature: &str, cx: &App) -> bool {
+    cx.try_global::<WorkspaceTrust>()
+        .map_or(true, |trust| trust.is_allowed(feature))
+}
+
+/// Build a dialog asking whether to trust `path`, listing what restricted mode turns
+/// off. It serves both as the prompt when a folder is first opened and for changing
+/// the decision later. Choosing records the decision with [`set_trusted`] and calls
+/// `on_decided` with it; dismissing leaves the folder as it was.
+pub fn trust_dialog(
+    path: &Path,
+    on_decided: impl Fn(bool, &mut Window, &mut App) + 'static,
+    cx: &mut App,
+) -> Entity<Dialog> {
+    let path = path.to_path_buf();
+    let on_decided = Rc::new(on_decided);
+    let theme = use_theme();
+    let trust = cx.default_global::<WorkspaceTrust>();
+    let current = trust.state_of(&path);
+    let features: Vec<SharedString> = trust.restricted_features().cloned().collect();
+
+    let decide = |trusted: bool| {
+        let on_decided = on_decided.clone();
+        let path = path.clone();
+        move |_: &ClickEvent, window: &mut Window, cx: &mut App| {
+            set_trusted(path.clone(), trusted, cx);
+            on_decided(trusted, window, cx);
+        }
+    };
+
+    let footer = div()
+        .flex()
+        .justify_end()
+        .gap(px(8.0))
+        .child(
+            Button::new("workspace-trust-restrict", "Stay in Restricted Mode")
+                .variant(ButtonVariant::Outline)
+                .on_click(decide(false)),
+        )
+        .child(
+            Button::new("workspace-trust-trust", "Trust Folder")
+                .variant(ButtonVariant::Default)
+                .on_click(decide(true)),
+        );
+
+    let status = match current {
+        TrustState::Unknown => "This folder hasn't been trusted yet.",
+        TrustState::Trusted => "This folder is trusted.",
+        TrustState::Restricted => "This folder is open in restricted mode.",
+    };
+    let body = div()
+        .flex()
+        .flex_col()
+        .gap(px(6.0))
+        .text_size(px(13.0))
+        .text_color(theme.tokens.muted_foreground)
+        .child(
+            div()
+                .text_color(theme.tokens.foreground)
+                .child(path.display().to_string()),
+        )
+        .child(status)
+        .child("Restricted mode turns off:")
+        .children(features.into_iter().map(|feature| format!("• {}", feature)))
+        .child("Trust it only if you know where its files came from.");
+
+    cx.new(|cx| {
+        Dialog::new(cx)
+            .title("Do you trust the authors of this folder?")
+            .description("Code in it may run on your computer if you do.")
+            .child(body)
+            .footer(footer)
+    })
+}
+
+/// A "Restricted Mode" status bar item while the open workspace isn't trusted, to
+/// open the [`trust_dialog`] from. `None` in a trusted workspace.
+pub fn status_item(
+    cx: &App,
+    on_click: impl Fn(&mut Window, &mut App) + 'static,
+) -> Option<StatusItem> {
+    if state(cx) == TrustState::Trusted {
+        return None;
+    }
+    Some(
+        StatusItem::icon_text("shield-alert", "Restricted Mode")
+            .tooltip("Some features are off until you trust this folder")
+            .on_click(on_click),
+    )
+}